@@ -15,16 +15,19 @@ use buck2_client_ctx::streaming::BuckSubcommand;
 
 use crate::commands::docs::query::DocsCqueryCommand;
 use crate::commands::docs::query::DocsUqueryCommand;
+use crate::commands::docs::rules::DocsRulesCommand;
 use crate::commands::docs::starlark::DocsStarlarkCommand;
 
 mod output;
 mod query;
+mod rules;
 mod starlark;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, clap::Parser)]
 enum DocsKind {
     Starlark(DocsStarlarkCommand),
+    Rules(DocsRulesCommand),
     Uquery(DocsUqueryCommand),
     Query(DocsUqueryCommand),
     Cquery(DocsCqueryCommand),
@@ -49,6 +52,7 @@ impl DocsCommand {
         };
         match self.docs_kind {
             DocsKind::Starlark(cmd) => cmd.exec(submatches, ctx),
+            DocsKind::Rules(cmd) => cmd.exec(submatches, ctx),
             DocsKind::Uquery(cmd) => cmd.exec(submatches, ctx),
             DocsKind::Query(cmd) => cmd.exec(submatches, ctx),
             DocsKind::Cquery(cmd) => cmd.exec(submatches, ctx),
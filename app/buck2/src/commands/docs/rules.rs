@@ -0,0 +1,115 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_cli_proto::unstable_docs_request;
+use buck2_cli_proto::UnstableDocsRequest;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonConsoleOptions;
+use buck2_client_ctx::common::CommonDaemonCommandOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::daemon::client::NoPartialResultHandler;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::streaming::StreamingCommand;
+use dupe::Dupe;
+
+#[derive(Debug, Clone, Dupe, clap::ArgEnum)]
+#[clap(rename_all = "snake_case")]
+enum DocsRulesFormatArg {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, clap::Parser)]
+#[clap(
+    name = "docs-rules",
+    about = "Print documentation of the rules, toolchain rules and providers in the prelude"
+)]
+pub(crate) struct DocsRulesCommand {
+    #[clap(flatten)]
+    pub config_opts: CommonBuildConfigurationOptions,
+
+    #[clap(flatten)]
+    console_opts: CommonConsoleOptions,
+
+    #[clap(flatten)]
+    event_log_opts: CommonDaemonCommandOptions,
+
+    #[clap(
+        long = "format",
+        help = "how to format the returned documentation",
+        default_value = "markdown",
+        arg_enum,
+        ignore_case = true
+    )]
+    format: DocsRulesFormatArg,
+
+    #[clap(
+        name = "BZL_FILES",
+        help = "Additional .bzl files to document the rules and providers of, e.g. //foo:defs.bzl"
+    )]
+    patterns: Vec<String>,
+}
+
+#[async_trait]
+impl StreamingCommand for DocsRulesCommand {
+    const COMMAND_NAME: &'static str = "docs rules";
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        matches: &clap::ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let client_context =
+            ctx.client_context(&self.config_opts, matches, ctx.sanitized_argv.argv.clone())?;
+
+        let response = buckd
+            .with_flushing()
+            .unstable_docs(
+                UnstableDocsRequest {
+                    context: Some(client_context),
+                    symbol_patterns: self.patterns.clone(),
+                    retrieve_builtins: false,
+                    retrieve_prelude: false,
+                    format: match self.format {
+                        DocsRulesFormatArg::Json => unstable_docs_request::Format::Json as i32,
+                        DocsRulesFormatArg::Markdown => {
+                            unstable_docs_request::Format::Markdown as i32
+                        }
+                    },
+                    markdown_output_path: None,
+                    markdown_native_subdir: String::new(),
+                    markdown_starlark_subdir: String::new(),
+                    rules: true,
+                },
+                ctx.stdin().console_interaction_stream(&self.console_opts),
+                &mut NoPartialResultHandler,
+            )
+            .await??;
+
+        if let Some(output) = response.json_output.or(response.markdown_output) {
+            buck2_client_ctx::println!("{}", output.trim_end())?;
+        }
+
+        ExitResult::success()
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        &self.console_opts
+    }
+
+    fn event_log_opts(&self) -> &CommonDaemonCommandOptions {
+        &self.event_log_opts
+    }
+
+    fn common_opts(&self) -> &CommonBuildConfigurationOptions {
+        &self.config_opts
+    }
+}
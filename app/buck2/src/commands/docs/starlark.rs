@@ -120,6 +120,7 @@ impl StreamingCommand for DocsStarlarkCommand {
                         .transpose()?,
                     markdown_starlark_subdir: self.markdown_file_opts.starlark_subdir.clone(),
                     markdown_native_subdir: self.markdown_file_opts.native_subdir.clone(),
+                    rules: false,
                 },
                 ctx.stdin().console_interaction_stream(&self.console_opts),
                 &mut NoPartialResultHandler,
@@ -21,9 +21,11 @@ use buck2_audit::AuditCommand;
 use buck2_client::args::expand_argfiles_with_context;
 use buck2_client::commands::build::BuildCommand;
 use buck2_client::commands::bxl::BxlCommand;
+use buck2_client::commands::changed_targets::ChangedTargetsCommand;
 use buck2_client::commands::clean::CleanCommand;
 use buck2_client::commands::ctargets::ConfiguredTargetsCommand;
 use buck2_client::commands::debug::DebugCommand;
+use buck2_client::commands::explain::ExplainCommand;
 use buck2_client::commands::init::InitCommand;
 use buck2_client::commands::install::InstallCommand;
 use buck2_client::commands::kill::KillCommand;
@@ -267,6 +269,8 @@ pub(crate) enum CommandKind {
     Starlark(StarlarkCommand),
     Targets(TargetsCommand),
     Ctargets(ConfiguredTargetsCommand),
+    ChangedTargets(ChangedTargetsCommand),
+    Explain(ExplainCommand),
     Uquery(UqueryCommand),
     #[clap(subcommand, setting(AppSettings::Hidden))]
     Debug(DebugCommand),
@@ -412,6 +416,8 @@ impl CommandKind {
             CommandKind::Status(cmd) => cmd.exec(matches, command_ctx).into(),
             CommandKind::Targets(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Ctargets(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::ChangedTargets(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Explain(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Audit(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Starlark(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Run(cmd) => cmd.exec(matches, command_ctx),
@@ -447,6 +453,8 @@ impl CommandKind {
             CommandKind::Status(cmd) => cmd.sanitize_argv(argv),
             CommandKind::Targets(cmd) => cmd.sanitize_argv(argv),
             CommandKind::Ctargets(cmd) => cmd.sanitize_argv(argv),
+            CommandKind::ChangedTargets(cmd) => cmd.sanitize_argv(argv),
+            CommandKind::Explain(cmd) => cmd.sanitize_argv(argv),
             CommandKind::Audit(cmd) => cmd.sanitize_argv(argv),
             CommandKind::Starlark(cmd) => cmd.sanitize_argv(argv),
             CommandKind::Run(cmd) => cmd.sanitize_argv(argv),
@@ -542,6 +542,52 @@ pub(crate) async fn eagerly_compute_fingerprints(
     Ok(fingerprints)
 }
 
+#[derive(Error, Debug)]
+#[error(
+    "Action `{action}` had a dep file hit, but running it changed outputs: {}. \
+    Either its dep files omit inputs it uses, or it is not deterministic.",
+    .changed.join(", ")
+)]
+struct DepFilesVerificationError {
+    action: String,
+    changed: Vec<String>,
+}
+
+/// Check that running an action which had a dep file hit produced the outputs of the hit. If not,
+/// the dep files let the action skip an input change, so report it.
+pub(crate) fn verify_dep_file_hit(
+    ctx: &dyn ActionExecutionCtx,
+    hit: &ActionOutputs,
+    outputs: &ActionOutputs,
+) -> anyhow::Result<()> {
+    let changed: Vec<String> = outputs
+        .iter()
+        .filter(|(path, value)| hit.get(path) != Some(value))
+        .map(|(path, _)| ctx.fs().resolve_build(path).to_string())
+        .collect();
+
+    if changed.is_empty() {
+        tracing::trace!("Dep files verified");
+        return Ok(());
+    }
+
+    let target = ctx.target();
+    soft_error!(
+        "dep_files_verification_failed",
+        DepFilesVerificationError {
+            action: match target.identifier() {
+                Some(identifier) => {
+                    format!("{} {} {}", target.owner(), target.category(), identifier)
+                }
+                None => format!("{} {}", target.owner(), target.category()),
+            },
+            changed,
+        }
+        .into()
+    )?;
+    Ok(())
+}
+
 /// Post-process the dep files produced by an action.
 pub(crate) async fn populate_dep_files(
     key: DepFilesKey,
@@ -10,6 +10,7 @@
 use std::borrow::Cow;
 use std::fmt::Display;
 use std::ops::ControlFlow;
+use std::time::Duration;
 
 use allocative::Allocative;
 use anyhow::Context;
@@ -33,9 +34,11 @@ use buck2_build_api::interpreter::rule_defs::cmd_args::DefaultCommandLineContext
 use buck2_build_api::interpreter::rule_defs::cmd_args::SimpleCommandLineArtifactVisitor;
 use buck2_build_api::interpreter::rule_defs::provider::builtin::worker_info::WorkerInfo;
 use buck2_core::category::Category;
+use buck2_core::directory::FingerprintedDirectory;
 use buck2_core::fs::buck_out_path::BuckOutPath;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
 use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::directory::ActionDirectoryBuilder;
 use buck2_execute::execute::environment_inheritance::EnvironmentInheritance;
 use buck2_execute::execute::request::ActionMetadataBlob;
 use buck2_execute::execute::request::CommandExecutionInput;
@@ -49,6 +52,7 @@ use derive_more::Display;
 use dupe::Dupe;
 use gazebo::prelude::*;
 use host_sharing::HostSharingRequirements;
+use host_sharing::ResourceWeights;
 use host_sharing::WeightClass;
 use indexmap::indexmap;
 use indexmap::IndexSet;
@@ -75,6 +79,7 @@ use thiserror::Error;
 use crate::actions::impls::run::dep_files::check_local_dep_file_cache;
 use crate::actions::impls::run::dep_files::make_local_dep_file_lookup_key;
 use crate::actions::impls::run::dep_files::populate_dep_files;
+use crate::actions::impls::run::dep_files::verify_dep_file_hit;
 use crate::actions::impls::run::dep_files::DepFilesCommandLineVisitor;
 use crate::actions::impls::run::dep_files::LocalDepFileLookUpKey;
 use crate::actions::impls::run::dep_files::RunActionDepFiles;
@@ -155,12 +160,16 @@ pub(crate) struct UnregisteredRunAction {
     pub(crate) executor_preference: ExecutorPreference,
     pub(crate) always_print_stderr: bool,
     pub(crate) weight: WeightClass,
+    pub(crate) resource_weights: ResourceWeights,
     pub(crate) low_pass_filter: bool,
     pub(crate) dep_files: RunActionDepFiles,
     pub(crate) metadata_param: Option<MetadataParameter>,
     pub(crate) no_outputs_cleanup: bool,
     pub(crate) allow_cache_upload: bool,
     pub(crate) force_full_hybrid_if_capable: bool,
+    pub(crate) remote_execution_properties: SortedVectorMap<String, String>,
+    /// Overrides the timeout configured for the category of the action.
+    pub(crate) timeout: Option<Duration>,
 }
 
 impl UnregisteredAction for UnregisteredRunAction {
@@ -215,7 +224,7 @@ struct UnpackedRunActionValues<'v> {
     exe: &'v dyn CommandLineArgLike,
     args: &'v dyn CommandLineArgLike,
     env: Vec<(&'v str, &'v dyn CommandLineArgLike)>,
-    worker: Option<(&'v dyn CommandLineArgLike, WorkerId)>,
+    worker: Option<(&'v dyn CommandLineArgLike, WorkerId, Option<usize>)>,
 }
 
 #[derive(Debug, Allocative)]
@@ -249,7 +258,7 @@ impl RunAction {
         let worker = if let Some(worker) = worker.into_option() {
             let worker_exe = worker.exe_command_line();
             let worker_id = WorkerId(worker.id);
-            Some((worker_exe, worker_id))
+            Some((worker_exe, worker_id, worker.concurrency()))
         } else {
             None
         };
@@ -267,7 +276,10 @@ impl RunAction {
         &self,
         fs: &ExecutorFs,
         artifact_visitor: &mut impl CommandLineArtifactVisitor,
-    ) -> anyhow::Result<(ExpandedCommandLine, Option<WorkerSpec>)> {
+    ) -> anyhow::Result<(
+        ExpandedCommandLine,
+        Option<(WorkerSpec, Vec<ArtifactGroup>)>,
+    )> {
         let mut ctx = DefaultCommandLineContext::new(fs);
         let values = Self::unpack(&self.starlark_values)?;
 
@@ -277,14 +289,21 @@ impl RunAction {
             .add_to_command_line(&mut exe_rendered, &mut ctx)?;
         values.exe.visit_artifacts(artifact_visitor)?;
 
-        let worker = if let Some((worker_exe, worker_id)) = values.worker {
+        let worker = if let Some((worker_exe, worker_id, concurrency)) = values.worker {
             let mut worker_rendered = Vec::<String>::new();
             worker_exe.add_to_command_line(&mut worker_rendered, &mut ctx)?;
             worker_exe.visit_artifacts(artifact_visitor)?;
-            Some(WorkerSpec {
-                id: worker_id,
-                exe: worker_rendered,
-            })
+            let mut worker_visitor = SimpleCommandLineArtifactVisitor::new();
+            worker_exe.visit_artifacts(&mut worker_visitor)?;
+            Some((
+                WorkerSpec {
+                    id: worker_id,
+                    exe: worker_rendered,
+                    inputs_digest: None,
+                    concurrency,
+                },
+                worker_visitor.inputs.into_iter().collect(),
+            ))
         } else {
             None
         };
@@ -362,6 +381,23 @@ impl RunAction {
         let mut inputs: Vec<CommandExecutionInput> =
             artifact_inputs[..].map(|&i| CommandExecutionInput::Artifact(Box::new(i.dupe())));
 
+        let worker = worker
+            .map(|(mut worker, worker_inputs)| {
+                let mut builder = ActionDirectoryBuilder::empty();
+                for group in &worker_inputs {
+                    ctx.artifact_values(group)
+                        .add_to_directory(&mut builder, fs)?;
+                }
+                worker.inputs_digest = Some(
+                    builder
+                        .fingerprint(ctx.digest_config().as_directory_serializer())
+                        .fingerprint()
+                        .dupe(),
+                );
+                anyhow::Ok(worker)
+            })
+            .transpose()?;
+
         // Handle case when user requested file with action metadata to be generated.
         // Generate content and output path for the file. It will be either passed
         // to RE as a blob or written to disk in local executor.
@@ -462,7 +498,7 @@ impl Action for RunAction {
         let mut artifact_visitor = SimpleCommandLineArtifactVisitor::new();
         values.args.visit_artifacts(&mut artifact_visitor)?;
         values.exe.visit_artifacts(&mut artifact_visitor)?;
-        if let Some((worker_exe, _)) = values.worker {
+        if let Some((worker_exe, ..)) = values.worker {
             worker_exe.visit_artifacts(&mut artifact_visitor)?;
         }
         for (_, v) in values.env.iter() {
@@ -509,12 +545,25 @@ impl Action for RunAction {
             "executor_preference".to_owned() => self.inner.executor_preference.to_string(),
             "always_print_stderr".to_owned() => self.inner.always_print_stderr.to_string(),
             "weight".to_owned() => self.inner.weight.to_string(),
+            "resource_weights".to_owned() => self.inner.resource_weights.to_string(),
             "dep_files".to_owned() => self.inner.dep_files.to_string(),
             "metadata_param".to_owned() => match &self.inner.metadata_param {
                 None => "None".to_owned(),
                 Some(x) => x.to_string(),
             },
             "no_outputs_cleanup".to_owned() => self.inner.no_outputs_cleanup.to_string(),
+            "remote_execution_properties".to_owned() => format!(
+                "{{{}}}",
+                self.inner
+                    .remote_execution_properties
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .join(", ")
+            ),
+            "timeout".to_owned() => match self.inner.timeout {
+                None => "None".to_owned(),
+                Some(timeout) => format!("{}s", timeout.as_secs()),
+            },
         }
     }
 }
@@ -542,36 +591,53 @@ impl IncrementalActionExecutable for RunAction {
         // Run actions are assumed to be shared
         let host_sharing_requirements = HostSharingRequirements::Shared(self.inner.weight);
 
-        let req = prepared_run_action
+        let mut req = prepared_run_action
             .into_command_execution_request()
             .with_prefetch_lossy_stderr(true)
             .with_executor_preference(self.inner.executor_preference)
             .with_host_sharing_requirements(host_sharing_requirements)
+            .with_resource_weights(self.inner.resource_weights)
             .with_low_pass_filter(self.inner.low_pass_filter)
             .with_outputs_cleanup(!self.inner.no_outputs_cleanup)
+            .with_allow_cache_upload(self.inner.allow_cache_upload)
             .with_local_environment_inheritance(EnvironmentInheritance::local_command_exclusions())
             .with_force_full_hybrid_if_capable(self.inner.force_full_hybrid_if_capable)
+            .with_remote_execution_properties(self.inner.remote_execution_properties.clone())
             .with_custom_tmpdir(ctx.target().custom_tmpdir());
+        let timeout = self
+            .inner
+            .timeout
+            .or_else(|| knobs.action_timeouts.get(self.inner.category.as_str()));
+        if let Some(timeout) = timeout {
+            req = req.with_timeout(timeout);
+        }
 
         // First prepare the action, check the action cache, check dep_files if needed, and execute the command
         let prepared_action = ctx.prepare_action(&req)?;
         let manager = ctx.command_execution_manager();
+        let mut dep_file_hit = None;
         let (mut result, dep_files) = match ctx.action_cache(manager, &req, &prepared_action).await
         {
             ControlFlow::Break(res) => (res, dep_files),
             ControlFlow::Continue(manager) => {
-                let dep_files = if let Some(dep_files) = dep_files {
-                    match check_local_dep_file_cache(ctx, self.outputs.as_slice(), &dep_files)
-                        .await?
-                    {
-                        Some(m) => {
-                            // We have a dep_file based match, return early
-                            return Ok(m);
+                let dep_files = match dep_files {
+                    Some(dep_files) if !ctx.skip_action_cache() => {
+                        match check_local_dep_file_cache(ctx, self.outputs.as_slice(), &dep_files)
+                            .await?
+                        {
+                            Some(m) => {
+                                if !knobs.verify_dep_files.map_or(false, |p| p.roll()) {
+                                    // We have a dep_file based match, return early
+                                    return Ok(m);
+                                }
+                                // Run the command anyway, to check the dep files are correct.
+                                dep_file_hit = Some(m.0);
+                                Some(dep_files)
+                            }
+                            None => Some(dep_files),
                         }
-                        None => Some(dep_files),
                     }
-                } else {
-                    None
+                    dep_files => dep_files,
                 };
 
                 (
@@ -590,6 +656,10 @@ impl IncrementalActionExecutable for RunAction {
         let (outputs, metadata) =
             ctx.unpack_command_execution_result(&req, result, self.inner.allow_cache_upload)?;
 
+        if let Some(dep_file_hit) = dep_file_hit {
+            verify_dep_file_hit(ctx, &dep_file_hit, &outputs)?;
+        }
+
         if let Some(dep_files) = dep_files {
             let LocalDepFileLookUpKey {
                 dep_files_key,
@@ -10,6 +10,7 @@
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context;
 use buck2_artifact::artifact::artifact_type::OutputArtifact;
@@ -50,6 +51,7 @@ use chrono::Utc;
 use dupe::Dupe;
 use dupe::OptionDupedExt;
 use either::Either;
+use host_sharing::ResourceWeights;
 use host_sharing::WeightClass;
 use host_sharing::WeightPercentage;
 use indexmap::indexset;
@@ -123,6 +125,10 @@ enum RunActionError {
     InvalidWeight(i32),
     #[error("`weight` and `weight_percentage` cannot both be passed")]
     DuplicateWeightsSpecified,
+    #[error("`{0}` must be a non-negative integer, got `{1}`")]
+    InvalidResourceWeight(&'static str, i32),
+    #[error("`timeout_seconds` must be a positive integer, got `{0}`")]
+    InvalidTimeout(i32),
     #[error("`dep_files` value with key `{}` has an invalid count of associated outputs. Expected 1, got {}.", .key, .count)]
     InvalidDepFileOutputs { key: String, count: usize },
     #[error("`dep_files` with keys `{}` and {} are using the same tag", .first, .second)]
@@ -560,6 +566,7 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
     /// * `arguments`: must be of type `cmd_args`, or a type convertible to such (such as a list of strings and artifacts) and must contain at least one `.as_output()` artifact
     /// * `category`: category and identifier - when used together, identify the action in Buck2's event stream, and must be unique for a given target
     /// * `weight`: used to note how heavy the command is and will typically be set to a higher value to indicate that less such commands should be run in parallel (if running locally)
+    /// * `weight_memory_mb` and `weight_io`: memory (in megabytes) and io used by the command; when running locally, commands only start when the total of those of the commands running fits within the `buck2.local_memory_budget_mb` and `buck2.local_io_budget` buckconfigs
     /// * `no_outputs_cleanup`: if this flag is set then Buck2 won't clean the outputs of a previous build that might be present on a disk; in which case, command from arguments should be responsible for the cleanup (that is useful, for example, when an action is supporting incremental mode and its outputs are based on result from a previous build)
     /// * `metadata_env_var` and `meadata_path` should be used together: both set or both unset
     ///     * `metadata_path`: defines a path relative to the result directory for a file with action metadata, which will be created right before the command will be run.
@@ -578,6 +585,13 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
     ///     and `--local-only` CLI flags. The CLI flags take precedence.
     ///     * The `force_full_hybrid_if_capable` option overrides the `use_limited_hybrid` hybrid.
     ///     The options listed above take precedence if set.
+    /// * `remote_execution_properties`: RE platform properties for this action (e.g. a container
+    /// image, an OS family or a GPU requirement), added to those of the executor, and overriding
+    /// them when they have the same name. They only apply when the action runs remotely.
+    /// * `timeout_seconds`: how long the command may run for, locally or remotely, before it is
+    /// killed and the action fails with a timeout. The output it printed until then is kept in
+    /// the error. Defaults to the timeout configured for the category of the action in the
+    /// `buck2_action_timeouts` buckconfig section, if any.
     fn run<'v>(
         this: &AnalysisActions<'v>,
         #[starlark(require = pos)] arguments: Value<'v>,
@@ -591,6 +605,8 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
         #[starlark(require = named, default = false)] always_print_stderr: bool,
         #[starlark(require = named)] weight: Option<i32>,
         #[starlark(require = named)] weight_percentage: Option<i32>,
+        #[starlark(require = named, default = 0)] weight_memory_mb: i32,
+        #[starlark(require = named, default = 0)] weight_io: i32,
         #[starlark(require = named)] dep_files: Option<SmallMap<&'v str, &'v ArtifactTag>>,
         #[starlark(require = named)] metadata_env_var: Option<String>,
         #[starlark(require = named)] metadata_path: Option<String>,
@@ -598,6 +614,8 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
         #[starlark(require = named, default = false)] no_outputs_cleanup: bool,
         #[starlark(require = named, default = false)] allow_cache_upload: bool,
         #[starlark(require = named, default = false)] force_full_hybrid_if_capable: bool,
+        #[starlark(require = named)] remote_execution_properties: Option<SmallMap<String, String>>,
+        #[starlark(require = named)] timeout_seconds: Option<i32>,
         #[starlark(require = named)] exe: Option<
             Either<ValueOf<'v, &'v WorkerRunInfo<'v>>, ValueOf<'v, &'v RunInfo<'v>>>,
         >,
@@ -692,6 +710,21 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
             }
         };
 
+        let resource_weights = ResourceWeights {
+            memory_mb: usize::try_from(weight_memory_mb).map_err(|_| {
+                RunActionError::InvalidResourceWeight("weight_memory_mb", weight_memory_mb)
+            })?,
+            io: usize::try_from(weight_io)
+                .map_err(|_| RunActionError::InvalidResourceWeight("weight_io", weight_io))?,
+        };
+
+        let timeout = timeout_seconds
+            .map(|t| match u64::try_from(t) {
+                Ok(t) if t > 0 => Ok(Duration::from_secs(t)),
+                _ => Err(RunActionError::InvalidTimeout(t)),
+            })
+            .transpose()?;
+
         let starlark_env = match env {
             None => Value::new_none(),
             Some(env) => {
@@ -771,12 +804,18 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
             executor_preference,
             always_print_stderr,
             weight,
+            resource_weights,
             low_pass_filter,
             dep_files: dep_files_configuration,
             metadata_param,
             no_outputs_cleanup,
             allow_cache_upload,
             force_full_hybrid_if_capable,
+            remote_execution_properties: remote_execution_properties
+                .into_iter()
+                .flatten()
+                .collect(),
+            timeout,
         };
         this.state().register_action(
             artifacts.inputs,
@@ -275,19 +275,22 @@ async fn run_analysis_with_env_underlying(
             let mut eval = provider.make(&env)?;
             eval.set_print_handler(&print);
 
-            let ctx = env.heap().alloc_typed(AnalysisContext::new(
-                eval.heap(),
-                attributes,
-                Some(
-                    eval.heap()
-                        .alloc_typed(Label::new(ConfiguredProvidersLabel::new(
-                            analysis_env.label,
-                            ProvidersName::Default,
-                        ))),
-                ),
-                registry,
-                dice.global_data().get_digest_config(),
-            ));
+            let ctx = env.heap().alloc_typed(
+                AnalysisContext::new(
+                    eval.heap(),
+                    attributes,
+                    Some(
+                        eval.heap()
+                            .alloc_typed(Label::new(ConfiguredProvidersLabel::new(
+                                analysis_env.label,
+                                ProvidersName::Default,
+                            ))),
+                    ),
+                    registry,
+                    dice.global_data().get_digest_config(),
+                )
+                .with_package_values(eval.heap(), node.package_values())?,
+            );
 
             let list_res = analysis_env.impl_function.invoke(&mut eval, ctx)?;
 
@@ -66,6 +66,7 @@ pub enum AnonTargetAttr {
     // Accepts unresolved promise artifacts. Maps to `attr.source()`.
     PromiseArtifact(StarlarkPromiseArtifact),
     Arg(ConfiguredStringWithMacros),
+    Label(Box<ConfiguredProvidersLabel>),
 }
 
 impl AttrSerializeWithContext for AnonTargetAttr {
@@ -99,6 +100,7 @@ impl AttrDisplayWithContext for AnonTargetAttr {
             AnonTargetAttr::Artifact(e) => write!(f, "\"{}\"", e),
             AnonTargetAttr::Arg(e) => write!(f, "\"{}\"", e),
             AnonTargetAttr::PromiseArtifact(e) => write!(f, "\"{}\"", e),
+            AnonTargetAttr::Label(e) => write!(f, "\"{}\"", e),
         }
     }
 }
@@ -124,6 +126,7 @@ impl ToJsonWithContext for AnonTargetAttr {
             AnonTargetAttr::Artifact(e) => Ok(to_value(e.to_string())?),
             AnonTargetAttr::Arg(e) => Ok(to_value(e.to_string())?),
             AnonTargetAttr::PromiseArtifact(e) => Ok(to_value(e.to_string())?),
+            AnonTargetAttr::Label(e) => Ok(to_value(e.to_string())?),
         }
     }
 }
@@ -171,6 +174,7 @@ impl AnonTargetAttr {
             AnonTargetAttr::Artifact(_) => Ok(()),
             AnonTargetAttr::Arg(e) => e.string_with_macros.traverse(traversal),
             AnonTargetAttr::PromiseArtifact(..) => Ok(()),
+            AnonTargetAttr::Label(label) => traversal.label(label),
         }
     }
 
@@ -245,6 +249,9 @@ impl AnonTargetAttr {
                 let configured = AnonTargetAttr::from_coerced_attr(l, item_ty, ctx)?;
                 AnonTargetAttr::OneOf(Box::new(configured), i)
             }
+            CoercedAttrWithType::Label(label, _t) => {
+                AnonTargetAttr::Label(Box::new(ctx.configure_target(label)))
+            }
             _ => {
                 return Err(AnonTargetFromCoercedAttrError::DefaultAttrTypeNotSupported(
                     ty.to_string(),
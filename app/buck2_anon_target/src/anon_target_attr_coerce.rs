@@ -16,6 +16,7 @@ use buck2_build_api::interpreter::rule_defs::artifact::StarlarkPromiseArtifact;
 use buck2_build_api::interpreter::rule_defs::provider::dependency::Dependency;
 use buck2_build_api::interpreter::rule_defs::resolved_macro::ResolvedStringWithMacros;
 use buck2_core::soft_error;
+use buck2_interpreter::types::label::Label;
 use buck2_node::attrs::attr_type::bool::BoolLiteral;
 use buck2_node::attrs::attr_type::dep::DepAttr;
 use buck2_node::attrs::attr_type::dep::DepAttrTransition;
@@ -157,11 +158,19 @@ impl AnonTargetAttrTypeCoerce for AttrType {
                 },
                 None => Err(AnonTargetCoercionError::type_error("resolved_macro", value).into()),
             },
+            AttrTypeInner::Label(_) => {
+                // Labels are accepted as-is, or taken from a dependency, but are never
+                // reconfigured since anon targets do not support transitions.
+                if let Some(label) = value.downcast_ref::<Label>() {
+                    Ok(AnonTargetAttr::Label(Box::new(label.inner().clone())))
+                } else if let Some(dep) = Dependency::from_value(value) {
+                    Ok(AnonTargetAttr::Label(Box::new(dep.label().inner().clone())))
+                } else {
+                    Err(AnonTargetCoercionError::type_error("label", value).into())
+                }
+            }
             _ => {
-                return Err(AnonTargetCoercionError::AttrTypeNotSupported(
-                    value.get_type().to_owned(),
-                )
-                .into());
+                return Err(AnonTargetCoercionError::AttrTypeNotSupported(self.to_string()).into());
             }
         }
     }
@@ -179,7 +188,7 @@ pub(crate) enum AnonTargetCoercionError {
     InvalidEnumVariant(String, Vec<String>),
     #[error("Cannot coerce value of type `{0}` to any: `{1}`")]
     CannotCoerceToAny(&'static str, String),
-    #[error("Attr value of type `{0}` not supported")]
+    #[error("Attr type `{0}` is not supported by anon targets")]
     AttrTypeNotSupported(String),
     #[error("Arg attribute must have `anon_target_compatible` set to `True`")]
     ArgNotAnonTargetCompatible,
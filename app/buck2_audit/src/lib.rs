@@ -36,6 +36,7 @@ use crate::output::command::AuditOutputCommand;
 use crate::output::parse::AuditParseCommand;
 use crate::prelude::AuditPreludeCommand;
 use crate::providers::AuditProvidersCommand;
+use crate::select::AuditSelectCommand;
 use crate::starlark::StarlarkCommand;
 use crate::visibility::AuditVisibilityCommand;
 
@@ -51,6 +52,7 @@ pub mod includes;
 pub mod output;
 pub mod prelude;
 pub mod providers;
+pub mod select;
 pub mod starlark;
 pub mod visibility;
 
@@ -67,6 +69,7 @@ pub enum AuditCommand {
     AnalysisQueries(AuditAnalysisQueriesCommand),
     ExecutionPlatformResolution(AuditExecutionPlatformResolutionCommand),
     Visibility(AuditVisibilityCommand),
+    Select(AuditSelectCommand),
     #[clap(subcommand)]
     Starlark(StarlarkCommand),
     DepFiles(AuditDepFilesCommand),
@@ -104,6 +107,7 @@ impl AuditCommand {
             AuditCommand::DepFiles(cmd) => cmd,
             AuditCommand::DeferredMaterializer(cmd) => cmd,
             AuditCommand::Visibility(cmd) => cmd,
+            AuditCommand::Select(cmd) => cmd,
             AuditCommand::Output(cmd) => cmd,
             AuditCommand::Parse(cmd) => cmd,
         }
@@ -0,0 +1,39 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_client_ctx::common::CommonCommandOptions;
+
+use crate::AuditSubcommand;
+
+/// Explains how each `select()` in the attributes of the given targets was resolved.
+///
+/// For every select, this prints the key which was chosen (or `DEFAULT`), and for each
+/// other key, whether it matched the target configuration. For keys which did not match,
+/// the constraints or buckconfigs which did not hold are listed; for keys which matched
+/// but were not chosen, the more specific key which was chosen instead is shown.
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(
+    name = "audit-select",
+    about = "Explain which select() branches were chosen for the specified target(s)"
+)]
+pub struct AuditSelectCommand {
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+
+    #[clap(name = "TARGET_PATTERNS", help = "Patterns to analyze")]
+    pub patterns: Vec<String>,
+}
+
+#[async_trait]
+impl AuditSubcommand for AuditSelectCommand {
+    fn common_opts(&self) -> &CommonCommandOptions {
+        &self.common_opts
+    }
+}
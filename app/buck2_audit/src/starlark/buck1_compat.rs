@@ -0,0 +1,26 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_client_ctx::common::CommonCommandOptions;
+
+/// Report Buck1 constructs in build files and `.bzl` files, with their buck2 equivalent.
+///
+/// Fails if some of the constructs have no buck2 equivalent and need to be rewritten by hand.
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(name = "buck1-compat")]
+pub struct StarlarkBuck1CompatCommand {
+    #[clap(
+        name = "FILES",
+        help = "Build files and `.bzl` files to check, relative to the working dir."
+    )]
+    pub files: Vec<String>,
+
+    #[clap(flatten)]
+    pub(crate) common_opts: CommonCommandOptions,
+}
@@ -9,12 +9,14 @@
 
 //! Starlark debugging.
 
+pub mod buck1_compat;
 pub mod module;
 pub mod package_deps;
 
 use async_trait::async_trait;
 use buck2_client_ctx::common::CommonCommandOptions;
 
+use crate::starlark::buck1_compat::StarlarkBuck1CompatCommand;
 use crate::starlark::module::StarlarkModuleCommand;
 use crate::starlark::package_deps::StarlarkPackageDepsCommand;
 use crate::AuditSubcommand;
@@ -24,6 +26,7 @@ use crate::AuditSubcommand;
 pub enum StarlarkCommand {
     Module(StarlarkModuleCommand),
     PackageDeps(StarlarkPackageDepsCommand),
+    Buck1Compat(StarlarkBuck1CompatCommand),
 }
 
 #[async_trait]
@@ -32,6 +35,7 @@ impl AuditSubcommand for StarlarkCommand {
         match self {
             StarlarkCommand::Module(cmd) => &cmd.common_opts,
             StarlarkCommand::PackageDeps(cmd) => &cmd.common_opts,
+            StarlarkCommand::Buck1Compat(cmd) => &cmd.common_opts,
         }
     }
 }
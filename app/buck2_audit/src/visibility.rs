@@ -23,6 +23,12 @@ pub struct AuditVisibilityCommand {
 
     #[clap(name = "TARGET_PATTERNS", help = "Target pattern(s) to analyze.")]
     pub patterns: Vec<String>,
+
+    /// Instead of verifying visibility, print the effective `visibility` and `within_view` of
+    /// the specified target(s). Targets which do not set these attributes get the values declared
+    /// in the nearest `PACKAGE` files.
+    #[clap(long)]
+    pub explain: bool,
 }
 
 #[async_trait]
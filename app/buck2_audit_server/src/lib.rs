@@ -31,6 +31,7 @@ mod includes;
 pub mod output;
 mod prelude;
 mod providers;
+mod select;
 pub mod server;
 mod starlark;
 mod visibility;
@@ -91,6 +92,7 @@ impl AuditCommandExt for AuditCommand {
             AuditCommand::DepFiles(cmd) => cmd,
             AuditCommand::DeferredMaterializer(cmd) => cmd,
             AuditCommand::Visibility(cmd) => cmd,
+            AuditCommand::Select(cmd) => cmd,
             AuditCommand::Output(cmd) => cmd,
             AuditCommand::Parse(cmd) => cmd,
         }
@@ -0,0 +1,137 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io::Write;
+
+use async_trait::async_trait;
+use buck2_audit::select::AuditSelectCommand;
+use buck2_cli_proto::ClientContext;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_core::target::label::TargetLabel;
+use buck2_node::configuration::resolved::ResolvedConfiguration;
+use buck2_node::load_patterns::load_patterns;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use buck2_node::target_calculation::ConfiguredTargetCalculation;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
+use buck2_server_ctx::pattern::target_platform_from_client_context;
+use gazebo::prelude::*;
+
+use crate::AuditSubcommand;
+
+#[async_trait]
+impl AuditSubcommand for AuditSelectCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        client_ctx: ClientContext,
+    ) -> anyhow::Result<()> {
+        server_ctx
+            .with_dice_ctx(async move |server_ctx, mut ctx| {
+                let target_platform =
+                    target_platform_from_client_context(&client_ctx, server_ctx, &ctx).await?;
+                let parsed_patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                    &mut ctx,
+                    &self
+                        .patterns
+                        .map(|pat| buck2_data::TargetPattern { value: pat.clone() }),
+                    server_ctx.working_dir(),
+                )
+                .await?;
+                let loaded_patterns =
+                    load_patterns(&ctx, parsed_patterns, MissingTargetBehavior::Fail).await?;
+
+                let mut stdout = stdout.as_writer();
+
+                for (_, targets) in loaded_patterns.into_iter() {
+                    for (_, node) in targets? {
+                        let configured_target = ctx
+                            .get_configured_target(node.label(), target_platform.as_ref())
+                            .await?;
+                        let configured_node =
+                            ctx.get_configured_target_node(&configured_target).await?;
+                        let configured_node = configured_node.require_compatible()?;
+                        writeln!(stdout, "{}:", configured_target)?;
+                        write_selects(&mut stdout, &configured_node)?;
+                    }
+                }
+
+                Ok(())
+            })
+            .await
+    }
+}
+
+fn write_selects(stdout: &mut impl Write, node: &ConfiguredTargetNode) -> anyhow::Result<()> {
+    let resolved = node.resolved_configuration();
+    for (attr, selector) in node.selectors() {
+        let chosen = node.selected_key(selector)?;
+        match chosen {
+            Some(key) => writeln!(stdout, "  {}: chose {}", attr, key)?,
+            None => writeln!(stdout, "  {}: chose DEFAULT", attr)?,
+        }
+        for (key, _) in selector.entries() {
+            if resolved.setting(key).matches() {
+                if Some(key) == chosen {
+                    writeln!(stdout, "    {}: matched", key)?;
+                } else {
+                    // A key can only lose to another matching key which refines it.
+                    writeln!(
+                        stdout,
+                        "    {}: matched, but {} is more specific",
+                        key,
+                        chosen.expect("a key matched, so DEFAULT was not chosen")
+                    )?;
+                }
+            } else {
+                writeln!(stdout, "    {}: not matched", key)?;
+                for reason in mismatch_reasons(resolved, key) {
+                    writeln!(stdout, "      {}", reason)?;
+                }
+            }
+        }
+        if selector.default().is_some() && chosen.is_some() {
+            writeln!(stdout, "    DEFAULT: not used")?;
+        }
+    }
+    Ok(())
+}
+
+/// Explains why the `select()` key `key` does not match the configuration of a target.
+fn mismatch_reasons(resolved: &ResolvedConfiguration, key: &TargetLabel) -> Vec<String> {
+    let setting = resolved.setting(key).configuration_data();
+    let platform = resolved.cfg().cfg().data().ok();
+
+    let mut reasons = Vec::new();
+    for (constraint_key, constraint_value) in &setting.constraints {
+        match platform.and_then(|p| p.get_constraint_value(constraint_key)) {
+            Some(actual) if actual == constraint_value => {}
+            Some(actual) => reasons.push(format!(
+                "requires {} = {}, configuration has {}",
+                constraint_key, constraint_value, actual
+            )),
+            None => reasons.push(format!(
+                "requires {} = {}, configuration has no value",
+                constraint_key, constraint_value
+            )),
+        }
+    }
+    if reasons.is_empty() {
+        // All the constraints hold, so one of the buckconfig values must differ.
+        for (buckconfig, value) in &setting.buckconfigs {
+            reasons.push(format!("requires buckconfig {} = {}", buckconfig, value));
+        }
+    }
+    reasons
+}
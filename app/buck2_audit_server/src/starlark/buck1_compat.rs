@@ -0,0 +1,142 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io::Write;
+
+use buck2_audit::starlark::buck1_compat::StarlarkBuck1CompatCommand;
+use buck2_cli_proto::ClientContext;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_core::fs::fs_util;
+use buck2_interpreter::file_type::StarlarkFileType;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use starlark::syntax::AstModule;
+
+#[derive(Debug, thiserror::Error)]
+enum Buck1CompatError {
+    #[error("{0} Buck1 construct(s) have no buck2 equivalent and must be rewritten by hand")]
+    NotMigratable(usize),
+}
+
+/// How a Buck1 construct maps onto buck2.
+enum Migration {
+    /// Mechanical rewrite.
+    Replace(&'static str),
+    /// No equivalent, the code needs to be restructured.
+    Manual(&'static str),
+}
+
+/// Buck1 functions which are not available, or deprecated, in buck2.
+const BUCK1_FUNCTIONS: &[(&str, Migration)] = &[
+    (
+        "include_defs",
+        Migration::Manual(
+            "move the definitions to a `.bzl` file and `load()` the symbols used from it",
+        ),
+    ),
+    (
+        "allow_unsafe_import",
+        Migration::Manual("Python imports are not available in Starlark"),
+    ),
+    (
+        "import_module",
+        Migration::Manual("Python imports are not available in Starlark"),
+    ),
+    (
+        "add_build_file_dep",
+        Migration::Replace("remove the call, buck2 tracks the files read by build files"),
+    ),
+    ("get_base_path", Migration::Replace("use `package_name()`")),
+];
+
+pub(crate) async fn server_execute(
+    command: &StarlarkBuck1CompatCommand,
+    server_ctx: &dyn ServerCommandContextTrait,
+    mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+    _client_ctx: ClientContext,
+) -> anyhow::Result<()> {
+    server_ctx
+        .with_dice_ctx(async move |server_ctx, dice_ctx| {
+            let cell_resolver = dice_ctx.get_cell_resolver().await?;
+            let fs = server_ctx.project_root();
+            let mut stdout = stdout.as_writer();
+
+            let mut not_migratable = 0;
+            for file in &command.files {
+                let abs_path = fs_util::canonicalize(
+                    server_ctx.working_dir_abs().path().as_abs_path().join(file),
+                )?;
+                let cell_path = cell_resolver.get_cell_path(&fs.relativize(&abs_path)?)?;
+                let is_buildfile = match cell_path.path().file_name() {
+                    Some(name) => cell_resolver
+                        .get(cell_path.cell())?
+                        .buildfiles()
+                        .iter()
+                        .any(|b| &**b == name),
+                    None => false,
+                };
+                let file_type = if is_buildfile {
+                    StarlarkFileType::Buck
+                } else {
+                    StarlarkFileType::Bzl
+                };
+
+                let content = fs_util::read_to_string(&abs_path)?;
+                let module = match AstModule::parse(file, content, &file_type.dialect(false)) {
+                    Ok(module) => module,
+                    Err(e) => {
+                        // Buck1 evaluated build files as Python, so syntax which is not
+                        // Starlark, like `import` statements, fails to parse.
+                        writeln!(stdout, "{}: not valid Starlark, rewrite by hand", file)?;
+                        writeln!(stdout, "  {:#}", e)?;
+                        not_migratable += 1;
+                        continue;
+                    }
+                };
+
+                not_migratable += report_buck1_functions(file, &module, &mut stdout)?;
+            }
+
+            if not_migratable != 0 {
+                return Err(Buck1CompatError::NotMigratable(not_migratable).into());
+            }
+            Ok(())
+        })
+        .await
+}
+
+/// Print the calls of Buck1 functions in the module. Returns the number of calls which cannot be
+/// migrated mechanically.
+fn report_buck1_functions(
+    file: &str,
+    module: &AstModule,
+    stdout: &mut dyn Write,
+) -> anyhow::Result<usize> {
+    let mut calls = Vec::new();
+    for (function, migration) in BUCK1_FUNCTIONS {
+        for span in module.find_function_calls(function) {
+            calls.push((span, function, migration));
+        }
+    }
+    calls.sort_by_key(|(span, ..)| (span.begin_line, span.begin_column));
+
+    let mut not_migratable = 0;
+    for (span, function, migration) in calls {
+        let suggestion = match migration {
+            Migration::Replace(suggestion) => suggestion,
+            Migration::Manual(suggestion) => {
+                not_migratable += 1;
+                suggestion
+            }
+        };
+        writeln!(stdout, "{}:{}: `{}`: {}", file, span, function, suggestion)?;
+    }
+    Ok(not_migratable)
+}
@@ -9,6 +9,7 @@
 
 //! Starlark debugging.
 
+mod buck1_compat;
 mod module;
 mod package_deps;
 
@@ -35,6 +36,9 @@ impl AuditSubcommand for StarlarkCommand {
             StarlarkCommand::PackageDeps(cmd) => {
                 package_deps::server_execute(cmd, server_ctx, stdout, client_ctx).await
             }
+            StarlarkCommand::Buck1Compat(cmd) => {
+                buck1_compat::server_execute(cmd, server_ctx, stdout, client_ctx).await
+            }
         }
     }
 }
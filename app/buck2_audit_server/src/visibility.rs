@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::io::Write;
+
 use async_trait::async_trait;
 use buck2_audit::visibility::AuditVisibilityCommand;
 use buck2_cli_proto::ClientContext;
@@ -109,12 +111,24 @@ async fn verify_visibility(
     Ok(())
 }
 
+fn explain_visibility(
+    mut stdout: impl Write,
+    targets: &TargetSet<TargetNode>,
+) -> anyhow::Result<()> {
+    for target in targets.iter() {
+        writeln!(stdout, "{}:", target.label())?;
+        writeln!(stdout, "  visibility: {}", target.visibility()?)?;
+        writeln!(stdout, "  within_view: {}", target.within_view()?)?;
+    }
+    Ok(())
+}
+
 #[async_trait]
 impl AuditSubcommand for AuditVisibilityCommand {
     async fn server_execute(
         &self,
         server_ctx: &dyn ServerCommandContextTrait,
-        _stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
         _client_ctx: ClientContext,
     ) -> anyhow::Result<()> {
         server_ctx
@@ -143,7 +157,11 @@ impl AuditSubcommand for AuditVisibilityCommand {
                     }
                 }
 
-                verify_visibility(ctx, nodes).await?;
+                if self.explain {
+                    explain_visibility(stdout.as_writer(), &nodes)?;
+                } else {
+                    verify_visibility(ctx, nodes).await?;
+                }
                 Ok(())
             })
             .await
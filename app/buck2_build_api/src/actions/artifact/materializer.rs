@@ -21,8 +21,12 @@ use buck2_execute::artifact::artifact_dyn::ArtifactDyn;
 use buck2_execute::materialize::materializer::HasMaterializer;
 use dice::DiceComputations;
 use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
 
 use crate::actions::artifact::get_artifact_fs::GetArtifactFs;
+use crate::actions::calculation::expired_path;
+use crate::actions::calculation::rerun_action;
+use crate::actions::impls::run_action_knobs::HasRunActionKnobs;
 use crate::build_signals::HasBuildSignals;
 
 #[async_trait]
@@ -64,14 +68,48 @@ impl ArtifactMaterializer for DiceComputations {
         span_async(start_event, async move {
             let now = Instant::now();
 
-            let result: anyhow::Result<_> = try {
+            let materializer = &materializer;
+            let materialize = move |path: ProjectRelativePathBuf| async move {
                 if required {
-                    materializer.ensure_materialized(vec![path]).await?;
+                    materializer.ensure_materialized(vec![path]).await
                 } else {
-                    materializer.try_materialize_final_artifact(path).await?;
+                    materializer
+                        .try_materialize_final_artifact(path)
+                        .await
+                        .map(|_| ())
                 }
             };
 
+            let result = match materialize(path.clone()).await {
+                // Final outputs can expire in the CAS like inputs, so recreate them the same way.
+                Err(e)
+                    if expired_path(&e).is_some()
+                        && self
+                            .per_transaction_data()
+                            .get_run_action_knobs()
+                            .cas_backfill_max_depth
+                            > 0 =>
+                {
+                    tracing::info!(
+                        "Output `{}` has expired in the CAS, re-running `{}` to recreate it",
+                        path,
+                        artifact.key()
+                    );
+                    match rerun_action(
+                        self,
+                        CancellationContext::never_cancelled(),
+                        artifact.key(),
+                        1,
+                    )
+                    .await
+                    {
+                        Ok(()) => materialize(path).await,
+                        Err(rerun_error) => Err(e.context(rerun_error)),
+                    }
+                }
+                result => result,
+            };
+
             if let Some(signals) = self.per_transaction_data().get_build_signals() {
                 let duration = now.elapsed();
 
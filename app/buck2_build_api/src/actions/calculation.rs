@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::collections::HashSet;
 use std::iter::zip;
 use std::sync::Arc;
 use std::time::Instant;
@@ -20,6 +21,9 @@ use buck2_build_signals::NodeDuration;
 use buck2_common::result::SharedResult;
 use buck2_common::result::ToSharedResultExt;
 use buck2_common::result::ToUnsharedResultExt;
+use buck2_core::base_deferred_key::BaseDeferredKey;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_data::ToProtoMessage;
 use buck2_events::dispatch::async_record_root_spans;
 use buck2_events::dispatch::span_async;
@@ -27,12 +31,14 @@ use buck2_events::span::SpanId;
 use buck2_execute::execute::kind::CommandExecutionKind;
 use buck2_execute::execute::result::CommandExecutionReport;
 use buck2_execute::execute::result::CommandExecutionStatus;
+use buck2_execute::materialize::materializer::MaterializationError;
 use buck2_execute::output_size::OutputSize;
 use derive_more::Display;
 use dice::DiceComputations;
 use dice::Key;
 use dupe::Dupe;
 use futures::future;
+use futures::future::BoxFuture;
 use futures::stream::FuturesOrdered;
 use futures::FutureExt;
 use indexmap::IndexMap;
@@ -41,11 +47,20 @@ use ref_cast::RefCast;
 use smallvec::SmallVec;
 use tracing::debug;
 
+use crate::actions::artifact::get_artifact_fs::GetArtifactFs;
+use crate::actions::cas_backfill::HasCasBackfillReruns;
+use crate::actions::execute::action_executor::ActionExecutionMetadata;
+use crate::actions::execute::action_executor::ActionExecutor;
 use crate::actions::execute::action_executor::ActionOutputs;
 use crate::actions::execute::action_executor::HasActionExecutor;
+use crate::actions::execute::action_timings::HasActionTimings;
+use crate::actions::execute::error::ExecuteError;
+use crate::actions::impls::run_action_knobs::HasRunActionKnobs;
 use crate::actions::key::ActionKeyExt;
 use crate::actions::RegisteredAction;
 use crate::artifact_groups::calculation::ensure_artifact_group_staged;
+use crate::artifact_groups::ArtifactGroup;
+use crate::artifact_groups::ArtifactGroupValues;
 use crate::deferred::calculation::DeferredCalculation;
 use crate::keep_going;
 
@@ -85,22 +100,7 @@ async fn build_action_no_redirect(
     cancellation: &CancellationContext<'_>,
     action: Arc<RegisteredAction>,
 ) -> anyhow::Result<ActionOutputs> {
-    let materialized_inputs = {
-        let inputs = action.inputs()?;
-        let ensure_futs: FuturesOrdered<_> = inputs
-            .iter()
-            .map(|v| ensure_artifact_group_staged(ctx, v))
-            .collect();
-
-        let ready_inputs: Vec<_> =
-            tokio::task::unconstrained(keep_going::try_join_all(ctx, ensure_futs)).await?;
-
-        let mut results = IndexMap::with_capacity(inputs.len());
-        for (artifact, ready) in zip(inputs.iter(), ready_inputs.into_iter()) {
-            results.insert(artifact.clone(), ready.to_group_values(artifact)?);
-        }
-        results
-    };
+    let materialized_inputs = ensure_action_inputs(ctx, &action).await?;
 
     let start_event = buck2_data::ActionExecutionStart {
         key: Some(action.key().as_proto()),
@@ -120,9 +120,23 @@ async fn build_action_no_redirect(
     let action = &action;
 
     let fut = async move {
-        let (execute_result, command_reports) = executor
-            .execute(materialized_inputs, &action, cancellation)
-            .await;
+        let (execute_result, command_reports) = execute_with_cas_backfill(
+            ctx,
+            cancellation,
+            &*executor,
+            action,
+            materialized_inputs,
+            false,
+            0,
+        )
+        .await;
+
+        if let (BaseDeferredKey::TargetLabel(target), Some(timings)) = (
+            action.owner(),
+            ctx.per_transaction_data().get_action_timings(),
+        ) {
+            timings.record(target, command_reports.iter().map(|r| &r.timing));
+        }
 
         let allow_omit_details = execute_result.is_ok();
 
@@ -249,6 +263,180 @@ async fn build_action_no_redirect(
     res
 }
 
+async fn ensure_action_inputs(
+    ctx: &DiceComputations,
+    action: &RegisteredAction,
+) -> anyhow::Result<IndexMap<ArtifactGroup, ArtifactGroupValues>> {
+    let inputs = action.inputs()?;
+    let ensure_futs: FuturesOrdered<_> = inputs
+        .iter()
+        .map(|v| ensure_artifact_group_staged(ctx, v))
+        .collect();
+
+    let ready_inputs: Vec<_> =
+        tokio::task::unconstrained(keep_going::try_join_all(ctx, ensure_futs)).await?;
+
+    let mut results = IndexMap::with_capacity(inputs.len());
+    for (artifact, ready) in zip(inputs.iter(), ready_inputs.into_iter()) {
+        results.insert(artifact.clone(), ready.to_group_values(artifact)?);
+    }
+    Ok(results)
+}
+
+/// Execute an action. If it fails because one of its inputs has expired in the CAS, re-run the
+/// action producing that input to recreate it and try again. Producers can need expired inputs
+/// too, so this cascades, up to `cas_backfill_max_depth` levels of producers.
+async fn execute_with_cas_backfill(
+    ctx: &DiceComputations,
+    cancellation: &CancellationContext<'_>,
+    executor: &dyn ActionExecutor,
+    action: &RegisteredAction,
+    inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
+    uncached: bool,
+    depth: usize,
+) -> (
+    Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
+    Vec<CommandExecutionReport>,
+) {
+    let max_depth = ctx
+        .per_transaction_data()
+        .get_run_action_knobs()
+        .cas_backfill_max_depth;
+
+    let mut command_reports = Vec::new();
+    let mut backfilled = HashSet::new();
+    loop {
+        let (result, mut reports) = if uncached {
+            executor
+                .execute_uncached(inputs.clone(), action, cancellation)
+                .await
+        } else {
+            executor.execute(inputs.clone(), action, cancellation).await
+        };
+        command_reports.append(&mut reports);
+
+        if result.is_ok() || depth >= max_depth {
+            return (result, command_reports);
+        }
+        let artifact_fs = match ctx.get_artifact_fs().await {
+            Ok(artifact_fs) => artifact_fs,
+            Err(_) => return (result, command_reports),
+        };
+        let producer =
+            match expired_input_producer(&result, &command_reports, &inputs, &artifact_fs) {
+                // Re-running the producer once is enough, if the input is still missing
+                // something else is wrong.
+                Some(producer) if backfilled.insert(producer.dupe()) => producer,
+                _ => return (result, command_reports),
+            };
+
+        tracing::info!(
+            "Input of `{}` has expired in the CAS, re-running `{}` to recreate it",
+            action,
+            producer
+        );
+        if let Err(e) = rerun_action(ctx, cancellation, &producer, depth + 1).await {
+            tracing::warn!("{:#}", e);
+            return (result, command_reports);
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum CasBackfillError {
+    #[error("Re-running `{0}` to recreate its expired outputs failed: {1}")]
+    RerunFailed(String, String),
+    #[error(
+        "Re-running `{0}` to recreate its expired outputs produced different outputs, so the \
+        action is not deterministic and the actions using its previous outputs can't be trusted. \
+        Run `buck2 clean` to build them again"
+    )]
+    OutputsChanged(String),
+}
+
+/// Run an action again, ignoring caches, to recreate its outputs. The action must produce the
+/// same outputs as when it was first built, since its dependents already hold those. An action
+/// is only re-run once per command, however many of its dependents find its outputs expired.
+pub(crate) fn rerun_action<'a>(
+    ctx: &'a DiceComputations,
+    cancellation: &'a CancellationContext<'a>,
+    key: &'a ActionKey,
+    depth: usize,
+) -> BoxFuture<'a, anyhow::Result<()>> {
+    async move {
+        let rerun = async {
+            let action = ActionCalculation::get_action(ctx, key).await?;
+            let inputs = ensure_action_inputs(ctx, &action).await?;
+            let executor = ctx.get_action_executor(action.execution_config()).await?;
+            let (result, _command_reports) = execute_with_cas_backfill(
+                ctx,
+                cancellation,
+                &*executor,
+                &action,
+                inputs,
+                true,
+                depth,
+            )
+            .await;
+            let (outputs, _metadata) = result.map_err(|e| {
+                CasBackfillError::RerunFailed(action.to_string(), format!("{:?}", e))
+            })?;
+
+            // This is the value DICE already computed, and that dependents were built against.
+            let expected = ctx.build_action(key).await?;
+            if outputs != expected {
+                return Err(CasBackfillError::OutputsChanged(action.to_string()).into());
+            }
+            Ok(())
+        };
+
+        match ctx.per_transaction_data().get_cas_backfill_reruns() {
+            Some(reruns) => reruns.get_or_rerun(key, rerun).await,
+            None => rerun.await,
+        }
+    }
+    .boxed()
+}
+
+/// If an execution failed because one of the inputs has expired in the CAS, the key of the action
+/// producing that input.
+fn expired_input_producer(
+    result: &Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
+    command_reports: &[CommandExecutionReport],
+    inputs: &IndexMap<ArtifactGroup, ArtifactGroupValues>,
+    artifact_fs: &ArtifactFs,
+) -> Option<ActionKey> {
+    let error = match result {
+        Err(ExecuteError::Error { error }) => error,
+        Err(ExecuteError::CommandExecutionError) => match &command_reports.last()?.status {
+            CommandExecutionStatus::Error { error, .. } => error,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let path = expired_path(error)?;
+
+    inputs
+        .values()
+        .flat_map(|values| values.iter())
+        .find_map(|(artifact, _value)| {
+            let key = artifact.action_key()?;
+            let artifact_path = artifact.get_path().resolve(artifact_fs).ok()?;
+            path.starts_with(&artifact_path).then(|| key.dupe())
+        })
+}
+
+/// The path of the artifact that couldn't be materialized because it expired in the CAS, if that's
+/// why the operation failed.
+pub(crate) fn expired_path(error: &anyhow::Error) -> Option<&ProjectRelativePathBuf> {
+    error
+        .chain()
+        .find_map(|e| match e.downcast_ref::<MaterializationError>()? {
+            MaterializationError::NotFound { path, .. } => Some(path),
+            _ => None,
+        })
+}
+
 pub struct BuildKeyActivationData {
     pub action: Arc<RegisteredAction>,
     pub duration: NodeDuration,
@@ -449,5 +637,12 @@ pub async fn command_details(
             .input_materialization_duration
             .try_into()
             .ok(),
+        queue_duration: command
+            .timing
+            .re_queue_time
+            .or(command.timing.local_queue_time)
+            .and_then(|d| d.try_into().ok()),
+        execution_duration: command.timing.execution_time.try_into().ok(),
+        output_upload_duration: command.timing.output_upload_duration.try_into().ok(),
     }
 }
@@ -0,0 +1,68 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Actions re-run during a command to recreate outputs which expired in the CAS, see
+//! `buck2.cas_backfill_max_depth`. Many consumers of the same expired output can notice it at
+//! once, so re-runs are shared, and each action is re-run at most once per command.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use buck2_artifact::actions::key::ActionKey;
+use buck2_common::result::SharedResult;
+use buck2_common::result::ToSharedResultExt;
+use buck2_common::result::ToUnsharedResultExt;
+use dice::UserComputationData;
+use dupe::Dupe;
+use tokio::sync::OnceCell;
+
+#[derive(Default)]
+pub struct CasBackfillReruns {
+    reruns: Mutex<HashMap<ActionKey, Arc<OnceCell<SharedResult<()>>>>>,
+}
+
+impl CasBackfillReruns {
+    /// Run `rerun` for the action, unless it already ran (or is running) during this command, in
+    /// which case its result is shared.
+    pub(crate) async fn get_or_rerun(
+        &self,
+        key: &ActionKey,
+        rerun: impl Future<Output = anyhow::Result<()>>,
+    ) -> anyhow::Result<()> {
+        let cell = self
+            .reruns
+            .lock()
+            .unwrap()
+            .entry(key.dupe())
+            .or_default()
+            .dupe();
+        cell.get_or_init(|| async { rerun.await.shared_error() })
+            .await
+            .clone()
+            .unshared_error()
+    }
+}
+
+pub trait HasCasBackfillReruns {
+    fn set_cas_backfill_reruns(&mut self, reruns: Arc<CasBackfillReruns>);
+
+    fn get_cas_backfill_reruns(&self) -> Option<Arc<CasBackfillReruns>>;
+}
+
+impl HasCasBackfillReruns for UserComputationData {
+    fn set_cas_backfill_reruns(&mut self, reruns: Arc<CasBackfillReruns>) {
+        self.data.set(reruns);
+    }
+
+    fn get_cas_backfill_reruns(&self) -> Option<Arc<CasBackfillReruns>> {
+        self.data.get::<Arc<CasBackfillReruns>>().ok().cloned()
+    }
+}
@@ -212,6 +212,18 @@ pub trait ActionExecutor: Send + Sync {
         Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
         Vec<CommandExecutionReport>,
     );
+
+    /// Like `execute`, but ignores cached results: the action is always run. Used to recreate
+    /// outputs which are no longer available.
+    async fn execute_uncached(
+        &self,
+        inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
+        action: &RegisteredAction,
+        cancellation: &CancellationContext,
+    ) -> (
+        Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
+        Vec<CommandExecutionReport>,
+    );
 }
 
 #[async_trait]
@@ -317,6 +329,8 @@ struct BuckActionExecutionContext<'a> {
     outputs: &'a [BuildArtifact],
     command_reports: &'a mut Vec<CommandExecutionReport>,
     cancellations: &'a CancellationContext<'a>,
+    /// Do not look up the action in caches, the command must run.
+    skip_action_cache: bool,
 }
 
 #[async_trait]
@@ -329,6 +343,10 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
         self.executor.command_executor.fs()
     }
 
+    fn skip_action_cache(&self) -> bool {
+        self.skip_action_cache
+    }
+
     fn executor_fs(&self) -> ExecutorFs {
         self.executor.command_executor.executor_fs()
     }
@@ -366,7 +384,7 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
     }
 
     fn run_action_knobs(&self) -> RunActionKnobs {
-        self.executor.run_action_knobs
+        self.executor.run_action_knobs.dupe()
     }
 
     fn cancellation_context(&self) -> &CancellationContext {
@@ -392,6 +410,9 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
         request: &CommandExecutionRequest,
         prepared_action: &PreparedAction,
     ) -> ControlFlow<CommandExecutionResult, CommandExecutionManager> {
+        if self.skip_action_cache {
+            return ControlFlow::Continue(manager);
+        }
         let action = self.target();
         self.executor
             .command_executor
@@ -546,13 +567,13 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
     }
 }
 
-#[async_trait]
-impl ActionExecutor for BuckActionExecutor {
-    async fn execute(
+impl BuckActionExecutor {
+    async fn execute_impl(
         &self,
         inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
         action: &RegisteredAction,
-        cancellations: &CancellationContext,
+        cancellations: &CancellationContext<'_>,
+        skip_action_cache: bool,
     ) -> (
         Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
         Vec<CommandExecutionReport>,
@@ -569,6 +590,7 @@ impl ActionExecutor for BuckActionExecutor {
                 outputs: outputs.as_ref(),
                 command_reports: &mut command_reports,
                 cancellations,
+                skip_action_cache,
             };
 
             let (result, metadata) = match action.as_executable() {
@@ -641,6 +663,34 @@ impl ActionExecutor for BuckActionExecutor {
     }
 }
 
+#[async_trait]
+impl ActionExecutor for BuckActionExecutor {
+    async fn execute(
+        &self,
+        inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
+        action: &RegisteredAction,
+        cancellations: &CancellationContext,
+    ) -> (
+        Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
+        Vec<CommandExecutionReport>,
+    ) {
+        self.execute_impl(inputs, action, cancellations, false)
+            .await
+    }
+
+    async fn execute_uncached(
+        &self,
+        inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
+        action: &RegisteredAction,
+        cancellations: &CancellationContext,
+    ) -> (
+        Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
+        Vec<CommandExecutionReport>,
+    ) {
+        self.execute_impl(inputs, action, cancellations, true).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
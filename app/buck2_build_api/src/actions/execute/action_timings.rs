@@ -0,0 +1,126 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Time spent by the commands of each target in the phases of their execution, for the build
+//! report. Only the commands which ran during the current command are counted: actions whose
+//! results were already computed don't contribute.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use buck2_core::target::label::ConfiguredTargetLabel;
+use buck2_execute::execute::result::CommandExecutionMetadata;
+use dice::UserComputationData;
+use dupe::Dupe;
+
+/// Sum of the phases of the commands of a target.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ActionTimings {
+    /// In the RE queue, or waiting for local resources.
+    pub queue: Duration,
+    /// Fetching inputs on the RE worker, or materializing them locally.
+    pub input_materialization: Duration,
+    pub execution: Duration,
+    /// Uploading outputs on the RE worker, or hashing them locally.
+    pub output_upload: Duration,
+}
+
+impl ActionTimings {
+    fn add(&mut self, timing: &CommandExecutionMetadata) {
+        self.queue += timing
+            .re_queue_time
+            .or(timing.local_queue_time)
+            .unwrap_or_default();
+        self.input_materialization += timing.input_materialization_duration;
+        self.execution += timing.execution_time;
+        self.output_upload += timing.output_upload_duration;
+    }
+}
+
+#[derive(Default)]
+pub struct ActionTimingsCollector {
+    timings: Mutex<HashMap<ConfiguredTargetLabel, ActionTimings>>,
+}
+
+impl ActionTimingsCollector {
+    pub(crate) fn record<'a>(
+        &self,
+        target: &ConfiguredTargetLabel,
+        timings: impl IntoIterator<Item = &'a CommandExecutionMetadata>,
+    ) {
+        let mut all = self.timings.lock().unwrap();
+        let target_timings = all.entry(target.dupe()).or_default();
+        for timing in timings {
+            target_timings.add(timing);
+        }
+    }
+
+    pub fn get(&self, target: &ConfiguredTargetLabel) -> Option<ActionTimings> {
+        self.timings.lock().unwrap().get(target).copied()
+    }
+}
+
+pub trait HasActionTimings {
+    fn set_action_timings(&mut self, collector: Arc<ActionTimingsCollector>);
+
+    fn get_action_timings(&self) -> Option<Arc<ActionTimingsCollector>>;
+}
+
+impl HasActionTimings for UserComputationData {
+    fn set_action_timings(&mut self, collector: Arc<ActionTimingsCollector>) {
+        self.data.set(collector);
+    }
+
+    fn get_action_timings(&self) -> Option<Arc<ActionTimingsCollector>> {
+        self.data.get::<Arc<ActionTimingsCollector>>().ok().cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buck2_core::configuration::data::ConfigurationData;
+    use buck2_core::target::label::ConfiguredTargetLabel;
+    use buck2_execute::execute::result::CommandExecutionMetadata;
+
+    use crate::actions::execute::action_timings::ActionTimings;
+    use crate::actions::execute::action_timings::ActionTimingsCollector;
+
+    #[test]
+    fn test_record() {
+        let target =
+            ConfiguredTargetLabel::testing_parse("cell//pkg:foo", ConfigurationData::testing_new());
+        let collector = ActionTimingsCollector::default();
+        let remote = CommandExecutionMetadata {
+            re_queue_time: Some(Duration::from_secs(1)),
+            execution_time: Duration::from_secs(2),
+            output_upload_duration: Duration::from_secs(3),
+            ..Default::default()
+        };
+        let local = CommandExecutionMetadata {
+            local_queue_time: Some(Duration::from_secs(4)),
+            input_materialization_duration: Duration::from_secs(5),
+            ..Default::default()
+        };
+        collector.record(&target, [&remote]);
+        collector.record(&target, [&local]);
+        assert_eq!(
+            Some(ActionTimings {
+                queue: Duration::from_secs(5),
+                input_materialization: Duration::from_secs(5),
+                execution: Duration::from_secs(2),
+                output_upload: Duration::from_secs(3),
+            }),
+            collector.get(&target)
+        );
+    }
+}
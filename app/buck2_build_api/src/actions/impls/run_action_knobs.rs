@@ -7,15 +7,39 @@
  * of this source tree.
  */
 
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use buck2_core::rollout_percentage::RolloutPercentage;
 use dice::UserComputationData;
 use dupe::Dupe;
 
+/// Timeouts of the actions which don't set their own.
+#[derive(Default, Debug)]
+pub struct ActionTimeouts {
+    /// Timeout of actions whose category has none.
+    pub default: Option<Duration>,
+    /// Timeouts by action category.
+    pub categories: HashMap<String, Duration>,
+}
+
+impl ActionTimeouts {
+    pub fn get(&self, category: &str) -> Option<Duration> {
+        self.categories.get(category).copied().or(self.default)
+    }
+}
+
 /// Knobs controlling how RunAction works.
-#[derive(Copy, Clone, Dupe, Default)]
+#[derive(Clone, Dupe, Default)]
 pub struct RunActionKnobs {
     /// Process dep files as they are generated.
     pub eager_dep_files: bool,
 
+    /// Fraction of the dep file hits for which the command is run anyway, to check that running
+    /// it produces the same outputs.
+    pub verify_dep_files: Option<RolloutPercentage>,
+
     /// Hash all commands using the same mechanism as dep files. This allows us to skip
     /// re-executing commands if their inputs and outputs haven't changed.
     pub hash_all_commands: bool,
@@ -27,6 +51,13 @@ pub struct RunActionKnobs {
 
     /// Whether to enforce timeouts when running things on RE.
     pub enforce_re_timeouts: bool,
+
+    /// How many levels of producing actions may be re-run when an action input has expired in
+    /// the CAS. Zero disables re-running actions to recreate their outputs.
+    pub cas_backfill_max_depth: usize,
+
+    /// Timeouts of run actions which don't declare one.
+    pub action_timeouts: Arc<ActionTimeouts>,
 }
 
 pub trait HasRunActionKnobs {
@@ -41,9 +72,9 @@ impl HasRunActionKnobs for UserComputationData {
     }
 
     fn get_run_action_knobs(&self) -> RunActionKnobs {
-        *self
-            .data
+        self.data
             .get::<RunActionKnobs>()
             .expect("RunActionKnobs should be set")
+            .dupe()
     }
 }
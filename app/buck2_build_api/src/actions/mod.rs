@@ -26,6 +26,7 @@
 pub mod artifact;
 pub mod box_slice_set;
 pub mod calculation;
+pub mod cas_backfill;
 pub mod execute;
 pub mod impls;
 pub mod key;
@@ -186,6 +187,10 @@ pub trait ActionExecutionCtx: Send + Sync {
     /// An 'ArtifactFs' to be used for managing 'Artifact's
     fn fs(&self) -> &ArtifactFs;
 
+    /// Whether cached results must be ignored because the action is re-run to recreate its
+    /// outputs.
+    fn skip_action_cache(&self) -> bool;
+
     fn executor_fs(&self) -> ExecutorFs;
 
     /// A `Materializer` used for expensive materializations
@@ -39,6 +39,7 @@ use serde::Serializer;
 
 use crate::actions::RegisteredAction;
 use crate::analysis::AnalysisResult;
+use crate::artifact_groups::ArtifactGroup;
 use crate::artifact_groups::TransitiveSetProjectionKey;
 
 #[derive(Debug, derive_more::Display, RefCast, Serialize)]
@@ -122,6 +123,40 @@ impl ActionQueryNode {
     pub fn action(&self) -> Arc<RegisteredAction> {
         self.action.dupe()
     }
+
+    /// Project relative paths of the artifacts this action reads directly. Inputs coming from
+    /// transitive set projections are not expanded here; they show up as deps instead.
+    fn input_paths(&self) -> anyhow::Result<Vec<String>> {
+        let mut paths = Vec::new();
+        for input in self.action.inputs()?.iter() {
+            let artifact = match input {
+                ArtifactGroup::Artifact(artifact) => artifact,
+                ArtifactGroup::Promise(promise) => promise.get_err()?,
+                ArtifactGroup::TransitiveSetProjection(_) => continue,
+            };
+            paths.push(artifact.get_path().resolve(&self.fs)?.to_string());
+        }
+        Ok(paths)
+    }
+
+    /// Project relative paths of the artifacts this action produces.
+    fn output_paths(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .action
+            .outputs()?
+            .iter()
+            .map(|output| self.fs.resolve_build(output.get_path()).to_string())
+            .collect())
+    }
+}
+
+/// The value of an attribute listing paths. Attributes can't fail, so if the paths can't be
+/// resolved, the value says so instead of listing none of them.
+fn paths_attr(paths: anyhow::Result<Vec<String>>) -> String {
+    match paths {
+        Ok(paths) => paths.join(" "),
+        Err(e) => format!("<error: {:#}>", e),
+    }
 }
 
 impl LabeledNode for ActionQueryNode {
@@ -228,9 +263,8 @@ impl QueryTarget for ActionQueryNode {
             "identifier",
             ActionAttr::new(self.action.identifier().unwrap_or("")),
         )?;
-        // TODO(cjhopman): impl inputs/outputs for actions in aquery
-        func("inputs", ActionAttr::new(""))?;
-        func("outputs", ActionAttr::new(""))?;
+        func("inputs", ActionAttr::new(&paths_attr(self.input_paths())))?;
+        func("outputs", ActionAttr::new(&paths_attr(self.output_paths())))?;
 
         for (k, v) in self.attrs() {
             func(&k, ActionAttr::new(&v))?;
@@ -97,7 +97,7 @@ impl<'v> AnalysisRegistry<'v> {
             deferred,
             actions: ActionsRegistry::new(owner.dupe(), execution_platform.dupe()),
             artifact_groups: ArtifactGroupRegistry::new(),
-            dynamic: DynamicRegistry::new(owner.dupe()),
+            dynamic: DynamicRegistry::new(owner.dupe(), execution_platform.dupe()),
             anon_targets: (ANON_TARGET_REGISTRY_NEW.get()?)(PhantomData, execution_platform),
             analysis_value_storage: AnalysisValueStorage::new(),
             artifact_promises: PromiseArtifactRegistry::new(owner),
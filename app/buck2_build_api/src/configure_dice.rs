@@ -10,6 +10,7 @@
 use std::sync::Arc;
 
 use buck2_common::dice::cells::SetCellResolver;
+use buck2_common::dice::data::SetFileContentHashing;
 use buck2_common::dice::data::SetIoProvider;
 use buck2_common::io::IoProvider;
 use buck2_common::legacy_configs::dice::SetLegacyConfigs;
@@ -55,12 +56,17 @@ pub async fn configure_dice_for_buck(
         .and_then(|c| c.parse::<WhichSpawner>("buck2", "dice_spawner").transpose())
         .unwrap_or(Ok(WhichSpawner::DropCancel))?;
 
+    let file_content_hashing = root_config
+        .and_then(|c| c.parse::<bool>("buck2", "file_content_hashing").transpose())
+        .unwrap_or(Ok(false))?;
+
     let mut dice = match which_dice {
         WhichDice::Legacy => Dice::builder(),
         WhichDice::Modern => Dice::modern(),
     };
     dice.set_io_provider(io);
     dice.set_digest_config(digest_config);
+    dice.set_file_content_hashing(file_content_hashing);
 
     let dice = dice.build_with_which_spawner(detect_cycles, which_spawner);
     let mut dice_ctx = dice.updater();
@@ -24,6 +24,7 @@ use buck2_core::provider::label::ProvidersName;
 use buck2_events::dispatch::get_dispatcher;
 use buck2_interpreter::print_handler::EventDispatcherPrintHandler;
 use buck2_interpreter::types::label::Label;
+use buck2_node::configuration::execution::ExecutionPlatformResolution;
 use dupe::Dupe;
 use gazebo::prelude::*;
 use indexmap::indexset;
@@ -34,12 +35,10 @@ use starlark::eval::Evaluator;
 use starlark::values::dict::Dict;
 use starlark::values::tuple::TupleRef;
 use starlark::values::OwnedFrozenValue;
-use thiserror::Error;
 
 use crate::actions::key::ActionKeyExt;
 use crate::actions::RegisteredAction;
 use crate::analysis::registry::AnalysisRegistry;
-use crate::bxl;
 use crate::deferred::types::BaseKey;
 use crate::deferred::types::Deferred;
 use crate::deferred::types::DeferredCtx;
@@ -75,6 +74,10 @@ impl DynamicAction {
 pub(crate) struct DynamicLambda {
     /// the owner that defined this lambda
     owner: BaseDeferredKey,
+    /// The execution platform of the registry the lambda was declared in. Only used for BXL,
+    /// where it comes from `bxl_actions()`, and for anon targets, which inherit it from the
+    /// target that created them, rather than from a configured target.
+    execution_platform: ExecutionPlatformResolution,
     /// Things required by the lambda (wrapped in DeferredInput)
     dynamic: IndexSet<DeferredInput>,
     /// Things I am allowed to use as inputs, but don't wait for
@@ -88,6 +91,7 @@ pub(crate) struct DynamicLambda {
 impl DynamicLambda {
     pub(crate) fn new(
         owner: BaseDeferredKey,
+        execution_platform: ExecutionPlatformResolution,
         dynamic: IndexSet<Artifact>,
         inputs: IndexSet<Artifact>,
         outputs: Vec<BuildArtifact>,
@@ -99,15 +103,17 @@ impl DynamicLambda {
             }
             BaseDeferredKey::BxlLabel(_) => {
                 // do nothing. This is for grabbing the execution platform, which for bxl, we
-                // hard code to a local execution.
+                // take from the `bxl_actions()` the lambda was declared with.
             }
             BaseDeferredKey::AnonTarget(_) => {
-                // This will return an error later, so doesn't need to have the dependency
+                // do nothing. The execution platform was resolved when the anon target was
+                // created, and is part of its key.
             }
         }
         depends.extend(dynamic.into_iter().map(DeferredInput::MaterializedArtifact));
         Self {
             owner,
+            execution_platform,
             dynamic: depends,
             inputs,
             outputs,
@@ -161,12 +167,6 @@ impl Deferred for DynamicAction {
     }
 }
 
-#[derive(Debug, Error)]
-enum DynamicLambdaError {
-    #[error("dynamic_output and anon_target cannot be used together (yet)")]
-    AnonTargetIncompatible,
-}
-
 impl any::Provider for DynamicLambda {
     fn provide<'a>(&'a self, demand: &mut Demand<'a>) {
         demand.provide_value_with(|| ProvideOutputs(Ok(self.outputs.clone())));
@@ -207,11 +207,8 @@ impl Deferred for DynamicLambda {
 
                         configured_target.execution_platform_resolution().dupe()
                     }
-                    BaseDeferredKey::BxlLabel(_) => {
-                        (*bxl::execution_platform::EXECUTION_PLATFORM).dupe()
-                    }
-                    BaseDeferredKey::AnonTarget(_) => {
-                        return Err(DynamicLambdaError::AnonTargetIncompatible.into());
+                    BaseDeferredKey::BxlLabel(_) | BaseDeferredKey::AnonTarget(_) => {
+                        self.execution_platform.dupe()
                     }
                 }
             };
@@ -14,6 +14,7 @@ use buck2_artifact::artifact::artifact_type::Artifact;
 use buck2_artifact::artifact::artifact_type::OutputArtifact;
 use buck2_artifact::deferred::id::DeferredId;
 use buck2_core::base_deferred_key::BaseDeferredKey;
+use buck2_node::configuration::execution::ExecutionPlatformResolution;
 use dupe::Dupe;
 use indexmap::IndexSet;
 
@@ -28,13 +29,15 @@ use crate::dynamic::deferred::DynamicLambdaOutput;
 #[derive(Allocative)]
 pub(crate) struct DynamicRegistry {
     owner: BaseDeferredKey,
+    execution_platform: ExecutionPlatformResolution,
     pending: Vec<(ReservedDeferredData<DynamicLambdaOutput>, DynamicLambda)>,
 }
 
 impl DynamicRegistry {
-    pub fn new(owner: BaseDeferredKey) -> Self {
+    pub fn new(owner: BaseDeferredKey, execution_platform: ExecutionPlatformResolution) -> Self {
         Self {
             owner,
+            execution_platform,
             pending: Vec::new(),
         }
     }
@@ -59,7 +62,13 @@ impl DynamicRegistry {
                 Ok(bound)
             })
             .collect::<anyhow::Result<_>>()?;
-        let lambda = DynamicLambda::new(self.owner.dupe(), dynamic, inputs, outputs);
+        let lambda = DynamicLambda::new(
+            self.owner.dupe(),
+            self.execution_platform.dupe(),
+            dynamic,
+            inputs,
+            outputs,
+        );
         let lambda_id = reserved.data().deferred_key().id();
         self.pending.push((reserved, lambda));
         Ok(lambda_id)
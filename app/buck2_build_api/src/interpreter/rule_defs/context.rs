@@ -9,6 +9,7 @@
 
 use std::cell::RefCell;
 use std::cell::RefMut;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
@@ -139,6 +140,8 @@ pub struct AnalysisContext<'v> {
     pub actions: ValueTyped<'v, AnalysisActions<'v>>,
     /// Only `None` when running a `dynamic_output` action from Bxl.
     label: Option<ValueTyped<'v, Label>>,
+    /// A dict of the values set in `PACKAGE` files for the target.
+    package_values: Value<'v>,
 }
 
 impl<'v> Display for AnalysisContext<'v> {
@@ -173,9 +176,20 @@ impl<'v> AnalysisContext<'v> {
                 digest_config,
             }),
             label,
+            package_values: heap.alloc(serde_json::Map::new()),
         }
     }
 
+    /// Make the `PACKAGE` values of the target (as stored in target nodes) available to the rule.
+    pub fn with_package_values(
+        mut self,
+        heap: &'v Heap,
+        package_values: &BTreeMap<String, String>,
+    ) -> anyhow::Result<Self> {
+        self.package_values = package_values_to_value(heap, package_values)?;
+        Ok(self)
+    }
+
     pub(crate) fn assert_no_promises(&self) -> anyhow::Result<()> {
         self.actions.state().assert_no_promises()
     }
@@ -253,6 +267,25 @@ fn register_context(builder: &mut MethodsBuilder) {
     fn label<'v>(this: RefAnalysisContext) -> anyhow::Result<Option<ValueTyped<'v, Label>>> {
         Ok(this.0.label)
     }
+
+    /// Returns a dict of the values set with `write_package_value` in the `PACKAGE` files
+    /// enclosing the target. Only values which can be encoded as JSON are available.
+    #[starlark(attribute)]
+    fn package_values<'v>(this: RefAnalysisContext) -> anyhow::Result<Value<'v>> {
+        Ok(this.0.package_values)
+    }
+}
+
+/// Convert `PACKAGE` values, encoded as JSON in target nodes, to a Starlark dict.
+pub fn package_values_to_value<'v>(
+    heap: &'v Heap,
+    package_values: &BTreeMap<String, String>,
+) -> anyhow::Result<Value<'v>> {
+    let mut map = serde_json::Map::with_capacity(package_values.len());
+    for (key, value) in package_values {
+        map.insert(key.clone(), serde_json::from_str(value)?);
+    }
+    Ok(heap.alloc(map))
 }
 
 pub static ANALYSIS_ACTIONS_METHODS_ACTIONS: LateBinding<fn(&mut MethodsBuilder)> =
@@ -19,8 +19,11 @@ use starlark::coerce::Coerce;
 use starlark::environment::GlobalsBuilder;
 use starlark::eval::Evaluator;
 use starlark::values::list::AllocList;
+use starlark::values::none::NoneOr;
+use starlark::values::none::NoneType;
 use starlark::values::Freeze;
 use starlark::values::Trace;
+use starlark::values::UnpackValue;
 use starlark::values::Value;
 use starlark::values::ValueLike;
 
@@ -38,6 +41,10 @@ pub struct WorkerInfoGen<V> {
     #[provider(field_type = "StarlarkCommandLine")]
     pub exe: V,
 
+    // Maximum number of commands sent to the worker at once, unlimited if `None`
+    #[provider(field_type = "NoneOr<i32>")]
+    pub concurrency: V,
+
     pub id: u64,
 }
 
@@ -51,13 +58,19 @@ fn worker_info_creator(globals: &mut GlobalsBuilder) {
     #[starlark(as_type = FrozenWorkerInfo)]
     fn WorkerInfo<'v>(
         #[starlark(default = AllocList::EMPTY)] exe: Value<'v>,
+        #[starlark(require = named, default = NoneType)] concurrency: Value<'v>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<WorkerInfo<'v>> {
         let heap = eval.heap();
         let valid_exe = StarlarkCommandLine::try_from_value(exe)?;
         let exe = heap.alloc(valid_exe);
+        validate_concurrency(concurrency)?;
         let id = next_id();
-        Ok(WorkerInfo { exe, id })
+        Ok(WorkerInfo {
+            exe,
+            concurrency,
+            id,
+        })
     }
 }
 
@@ -68,6 +81,13 @@ impl<'v, V: ValueLike<'v>> WorkerInfoGen<V> {
             .as_command_line()
             .expect("validated at construction")
     }
+
+    pub fn concurrency(&self) -> Option<usize> {
+        NoneOr::<i32>::unpack_value(self.concurrency.to_value())
+            .expect("validated at construction")
+            .into_option()
+            .map(|c| c as usize)
+    }
 }
 
 fn validate_worker_info<'v, V>(info: &WorkerInfoGen<V>) -> anyhow::Result<()>
@@ -86,6 +106,25 @@ where
             info.exe
         ));
     }
+    validate_concurrency(info.concurrency.to_value())?;
 
     Ok(())
 }
+
+fn validate_concurrency(concurrency: Value) -> anyhow::Result<()> {
+    let unpacked = NoneOr::<i32>::unpack_value(concurrency).with_context(|| {
+        format!(
+            "Value for `concurrency` field is not an int: `{}`",
+            concurrency
+        )
+    })?;
+    if let NoneOr::Other(c) = unpacked {
+        if c <= 0 {
+            return Err(anyhow::anyhow!(
+                "Value for `concurrency` field must be positive: `{}`",
+                c
+            ));
+        }
+    }
+    Ok(())
+}
@@ -87,6 +87,8 @@ pub trait BxlUqueryFunctions<'c>: Send + 'c {
         targets: &TargetSet<TargetNode>,
     ) -> anyhow::Result<TargetSet<TargetNode>>;
     async fn owner(&self, file_set: &FileSet) -> anyhow::Result<TargetSet<TargetNode>>;
+    async fn allbuildfiles(&self, universe: &TargetSet<TargetNode>) -> anyhow::Result<FileSet>;
+    async fn rbuildfiles(&self, universe: &FileSet, argset: &FileSet) -> anyhow::Result<FileSet>;
 }
 
 pub static NEW_BXL_CQUERY_FUNCTIONS: LateBinding<
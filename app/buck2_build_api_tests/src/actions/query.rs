@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+
+use buck2_artifact::artifact::artifact_type::testing::BuildArtifactTestingExt;
+use buck2_artifact::artifact::artifact_type::Artifact;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_artifact::artifact::source_artifact::SourceArtifact;
+use buck2_artifact::deferred::id::DeferredId;
+use buck2_build_api::actions::query::ActionQueryNode;
+use buck2_build_api::actions::RegisteredAction;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_common::executor_config::CommandExecutorConfig;
+use buck2_core::buck_path::path::BuckPath;
+use buck2_core::category::Category;
+use buck2_core::cells::cell_root_path::CellRootPathBuf;
+use buck2_core::cells::name::CellName;
+use buck2_core::cells::CellResolver;
+use buck2_core::configuration::data::ConfigurationData;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_core::package::package_relative_path::PackageRelativePathBuf;
+use buck2_core::package::PackageLabel;
+use buck2_core::target::label::TargetLabel;
+use buck2_core::target::name::TargetNameRef;
+use buck2_query::query::environment::QueryTarget;
+use dupe::Dupe;
+
+use crate::actions::testings::SimpleAction;
+
+fn artifact_fs() -> ArtifactFs {
+    ArtifactFs::new(
+        CellResolver::testing_with_name_and_path(
+            CellName::testing_new("cell"),
+            CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new("cell_path".into())),
+        ),
+        BuckOutPathResolver::new(ProjectRelativePathBuf::unchecked_new("buck_out".into())),
+        ProjectRoot::new(AbsNormPathBuf::try_from(std::env::current_dir().unwrap()).unwrap())
+            .unwrap(),
+    )
+}
+
+fn source_artifact(cell: &str, path: &str) -> ArtifactGroup {
+    ArtifactGroup::Artifact(Artifact::from(SourceArtifact::new(BuckPath::testing_new(
+        PackageLabel::testing_new(cell, "pkg"),
+        PackageRelativePathBuf::unchecked_new(path.into()),
+    ))))
+}
+
+fn build_artifact(path: &str) -> BuildArtifact {
+    let label = TargetLabel::new(
+        PackageLabel::testing_new("cell", "pkg"),
+        TargetNameRef::unchecked_new("foo"),
+    )
+    .configure(ConfigurationData::testing_new());
+    BuildArtifact::testing_new(
+        label,
+        ForwardRelativePathBuf::unchecked_new(path.into()),
+        DeferredId::testing_new(0),
+    )
+}
+
+fn action_node(inputs: Vec<ArtifactGroup>, outputs: Vec<BuildArtifact>) -> ActionQueryNode {
+    let action = RegisteredAction::new(
+        outputs[0].key().dupe(),
+        Box::new(SimpleAction::new(
+            inputs.into_iter().collect(),
+            outputs.into_iter().collect(),
+            vec![],
+            Category::try_from("fake_action").unwrap(),
+            None,
+        )),
+        CommandExecutorConfig::testing_local(),
+    );
+    ActionQueryNode::new(Arc::new(action), Vec::new(), Arc::new(artifact_fs()))
+}
+
+fn attr(node: &ActionQueryNode, key: &str) -> String {
+    node.map_attr(key, |attr| attr.expect("attribute is set").to_string())
+}
+
+#[test]
+fn inputs_and_outputs_attrs() {
+    let outputs = vec![build_artifact("out.o"), build_artifact("out.d")];
+    let node = action_node(
+        vec![
+            source_artifact("cell", "a.c"),
+            source_artifact("cell", "b.h"),
+        ],
+        outputs.clone(),
+    );
+
+    assert_eq!("cell_path/pkg/a.c cell_path/pkg/b.h", attr(&node, "inputs"));
+    let fs = artifact_fs();
+    assert_eq!(
+        format!(
+            "{} {}",
+            fs.resolve_build(outputs[0].get_path()),
+            fs.resolve_build(outputs[1].get_path())
+        ),
+        attr(&node, "outputs")
+    );
+}
+
+#[test]
+fn inputs_attr_reports_errors() {
+    let node = action_node(
+        vec![
+            source_artifact("cell", "a.c"),
+            source_artifact("unknown_cell", "b.h"),
+        ],
+        vec![build_artifact("out.o")],
+    );
+
+    let inputs = attr(&node, "inputs");
+    assert!(inputs.starts_with("<error: "), "{}", inputs);
+    assert!(inputs.contains("unknown_cell"), "{}", inputs);
+    assert!(attr(&node, "outputs").ends_with("out.o"));
+}
@@ -9,12 +9,16 @@
 
 use allocative::Allocative;
 use buck2_build_api::bxl::build_result::BxlBuildResult;
+use buck2_build_api::interpreter::rule_defs::artifact::StarlarkArtifact;
+use dupe::Dupe;
 use starlark::environment::Methods;
 use starlark::environment::MethodsBuilder;
 use starlark::environment::MethodsStatic;
 use starlark::starlark_module;
 use starlark::starlark_simple_value;
+use starlark::values::dict::Dict;
 use starlark::values::starlark_value;
+use starlark::values::Heap;
 use starlark::values::NoSerialize;
 use starlark::values::ProvidesStaticType;
 use starlark::values::StarlarkValue;
@@ -76,6 +80,39 @@ fn starlark_build_result_methods(builder: &mut MethodsBuilder) {
             BxlBuildResult::Built { .. } => Ok(Some(StarlarkFailedArtifactIterableGen(this))),
         }
     }
+
+    /// Returns a dict of the successfully built artifacts to their digests, formatted as
+    /// `<hash>:<size>`. The digest is `None` for artifacts which are symlinks.
+    ///
+    /// Combined with `ctx.build(..., materializations = "skip")`, this gives the digests of
+    /// the outputs without downloading them.
+    ///
+    /// Sample usage:
+    /// ```text
+    /// def _impl(ctx):
+    ///     for target, value in ctx.build(ctx.cli_args.target, materializations = "skip").items():
+    ///         ctx.output.print(value.digests())
+    /// ```
+    fn digests<'v>(this: &StarlarkBxlBuildResult, heap: &'v Heap) -> anyhow::Result<Dict<'v>> {
+        let mut digests = Vec::new();
+        if let BxlBuildResult::Built(result) = &this.0 {
+            for artifacts in result
+                .outputs
+                .iter()
+                .filter_map(|built| built.as_ref().ok())
+            {
+                for (artifact, value) in artifacts.values.iter() {
+                    let digest = value.digest().map(|d| d.to_string());
+                    digests.push((
+                        heap.alloc(StarlarkArtifact::new(artifact.dupe()))
+                            .get_hashed()?,
+                        heap.alloc(digest),
+                    ));
+                }
+            }
+        }
+        Ok(Dict::new(digests.into_iter().collect()))
+    }
 }
 
 starlark_simple_value!(StarlarkBxlBuildResult);
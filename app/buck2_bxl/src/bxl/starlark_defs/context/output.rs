@@ -321,7 +321,11 @@ fn register_output_stream(builder: &mut MethodsBuilder) {
     /// accessible by users at the end of bxl script.
     ///
     /// This function returns an `ensured_artifact` type that can be printed via `ctx.output.print()`
-    /// to print its actual path on disk.
+    /// to print its actual path on disk. Call `abs_path()` on it to print the absolute path instead.
+    ///
+    /// Ensured artifacts are materialized according to the `--materializations` flag of
+    /// `buck2 bxl`. To get the digests of outputs without materializing them, build them with
+    /// `ctx.build(..., materializations = "skip")` and call `digests()` on the results instead.
     ///
     /// Sample usage:
     /// ```text
@@ -23,6 +23,7 @@ use buck2_artifact::artifact::artifact_type::Artifact;
 use buck2_artifact::artifact::source_artifact::SourceArtifact;
 use buck2_build_api::analysis::AnalysisResult;
 use buck2_build_api::interpreter::rule_defs::artifact::StarlarkArtifact;
+use buck2_build_api::interpreter::rule_defs::context::package_values_to_value;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::data::HasIoProvider;
 use buck2_core::buck_path::path::BuckPathRef;
@@ -274,6 +275,24 @@ fn configured_target_node_value_methods(builder: &mut MethodsBuilder) {
         Ok(this.0.rule_type().to_string())
     }
 
+    /// Gets the values set with `write_package_value` in the `PACKAGE` files enclosing this
+    /// configured target node, as a dict. Only values which can be encoded as JSON are
+    /// available.
+    ///
+    /// Sample usage:
+    /// ```text
+    /// def _impl_package_values(ctx):
+    ///     node = ctx.configured_targets("my_cell//bin:the_binary")
+    ///     ctx.output.print(node.package_values.get("cxx.compiler"))
+    /// ```
+    #[starlark(attribute)]
+    fn package_values<'v>(
+        this: &StarlarkConfiguredTargetNode,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        package_values_to_value(heap, this.0.package_values())
+    }
+
     /// Returns a List of all the sources used by this node.
     ///
     /// Sample usage:
@@ -8,6 +8,7 @@
  */
 
 use allocative::Allocative;
+use buck2_build_api::interpreter::rule_defs::context::package_values_to_value;
 use buck2_interpreter::types::target_label::StarlarkTargetLabel;
 use buck2_node::attrs::inspect_options::AttrInspectOptions;
 use buck2_node::nodes::unconfigured::TargetNode;
@@ -145,4 +146,19 @@ fn target_node_value_methods(builder: &mut MethodsBuilder) {
     fn rule_type(this: &StarlarkTargetNode) -> anyhow::Result<String> {
         Ok(this.0.rule_type().to_string())
     }
+
+    /// Gets the values set with `write_package_value` in the `PACKAGE` files enclosing this
+    /// unconfigured target node, as a dict. Only values which can be encoded as JSON are
+    /// available.
+    ///
+    /// Sample usage:
+    /// ```text
+    /// def _impl_package_values(ctx):
+    ///     target_node = ctx.uquery().owner('path/to/file')[0]
+    ///     ctx.output.print(target_node.package_values.get("cxx.compiler"))
+    /// ```
+    #[starlark(attribute)]
+    fn package_values<'v>(this: &StarlarkTargetNode, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        package_values_to_value(heap, this.0.package_values())
+    }
 }
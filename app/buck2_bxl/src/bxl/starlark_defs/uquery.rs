@@ -370,6 +370,63 @@ fn register_uquery(builder: &mut MethodsBuilder) {
             .map(StarlarkFileSet::from)
     }
 
+    /// Find all the build files, and all the files they load, for the targets in `universe`.
+    ///
+    /// Sample usage:
+    /// ```text
+    /// def _allbuildfiles_impl(ctx):
+    ///     result = ctx.uquery().allbuildfiles("root//bin/...")
+    ///     ctx.output.print(result)
+    /// ```
+    fn allbuildfiles<'v>(
+        this: &StarlarkUQueryCtx<'v>,
+        universe: Value<'v>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<StarlarkFileSet> {
+        this.ctx
+            .async_ctx
+            .via_dice(|ctx| async {
+                let universe = &*TargetExpr::<'v, TargetNode>::unpack(universe, this.ctx, eval)
+                    .await?
+                    .get(ctx)
+                    .await?;
+
+                get_uquery_env(this.ctx)
+                    .await?
+                    .allbuildfiles(universe)
+                    .await
+            })
+            .map(StarlarkFileSet::from)
+    }
+
+    /// Find the build files in `universe` which transitively load any of the files in `argset`.
+    ///
+    /// Sample usage:
+    /// ```text
+    /// def _rbuildfiles_impl(ctx):
+    ///     buildfiles = ctx.uquery().allbuildfiles("root//bin/...")
+    ///     result = ctx.uquery().rbuildfiles(buildfiles, "root//defs.bzl")
+    ///     ctx.output.print(result)
+    /// ```
+    fn rbuildfiles<'v>(
+        this: &StarlarkUQueryCtx<'v>,
+        universe: FileSetExpr,
+        argset: FileSetExpr,
+    ) -> anyhow::Result<StarlarkFileSet> {
+        this.ctx
+            .async_ctx
+            .via(|| async {
+                get_uquery_env(this.ctx)
+                    .await?
+                    .rbuildfiles(
+                        universe.get(this.ctx).await?.as_ref(),
+                        argset.get(this.ctx).await?.as_ref(),
+                    )
+                    .await
+            })
+            .map(StarlarkFileSet::from)
+    }
+
     /// The owner query for finding targets that own specified files.
     ///
     /// Sample usage:
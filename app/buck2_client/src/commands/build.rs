@@ -8,6 +8,7 @@
  */
 
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::io;
 use std::io::Write;
@@ -95,6 +96,11 @@ pub struct BuildCommand {
     )]
     show_full_json_output: bool,
 
+    /// Print the outputs of each of the built rules in a machine-readable format, along with
+    /// their content digests and sizes.
+    #[clap(long, ignore_case = true, value_name = "FORMAT", arg_enum)]
+    show_output_format: Option<ShowOutputFormat>,
+
     #[clap(
         long = "materializations",
         help = "Materialize (or skip) the final artifacts, bypassing buckconfig.",
@@ -182,6 +188,14 @@ impl BuildCommand {
     }
 }
 
+#[derive(Debug, Clone, Dupe, clap::ArgEnum)]
+#[clap(rename_all = "snake_case")]
+enum ShowOutputFormat {
+    /// A JSON object mapping each target to its outputs, each with a project relative `path`,
+    /// a hex `digest` (null for symlinks) and a `size` in bytes.
+    Json,
+}
+
 #[derive(Debug, Clone, Dupe, clap::ArgEnum)]
 #[clap(rename_all = "snake_case")]
 pub enum FinalArtifactMaterializations {
@@ -249,6 +263,7 @@ impl StreamingCommand for BuildCommand {
                             || self.show_full_output
                             || self.show_json_output
                             || self.show_full_json_output
+                            || self.show_output_format.is_some()
                             || self.output_path.is_some(),
                         return_default_other_outputs: show_default_other_outputs,
                     }),
@@ -304,6 +319,13 @@ impl StreamingCommand for BuildCommand {
                 .context("Error requesting specific output path for --out")?;
             }
 
+            match self.show_output_format {
+                Some(ShowOutputFormat::Json) => {
+                    print_outputs_with_digests_json(&mut stdout, &response.build_targets)?
+                }
+                None => {}
+            }
+
             if self.show_output
                 || self.show_full_output
                 || self.show_json_output
@@ -438,6 +460,42 @@ pub(crate) fn print_outputs(
     Ok(())
 }
 
+/// Print the default outputs of each target, along with their digests and sizes, as JSON.
+fn print_outputs_with_digests_json(
+    mut out: impl Write,
+    targets: &[BuildTarget],
+) -> anyhow::Result<()> {
+    #[derive(Serialize)]
+    struct OutputWithDigest<'a> {
+        path: &'a str,
+        digest: Option<&'a str>,
+        size: u64,
+    }
+
+    let mut output_map: BTreeMap<&str, Vec<OutputWithDigest>> = BTreeMap::new();
+    for build_target in targets {
+        let outputs = output_map.entry(&build_target.target).or_default();
+        for output in &build_target.outputs {
+            if !output
+                .providers
+                .as_ref()
+                .map_or(true, |p| p.default_info && !p.other)
+            {
+                continue;
+            }
+            outputs.push(OutputWithDigest {
+                path: &output.path,
+                digest: (!output.digest.is_empty()).then_some(output.digest.as_str()),
+                size: output.size,
+            });
+        }
+    }
+
+    serde_json::to_writer(&mut out, &output_map)?;
+    writeln!(&mut out)?;
+    Ok(())
+}
+
 /// Given a list of targets built by this command, extracts a reasonable default output from the list and writes it
 /// to the path given by `out`.
 ///
@@ -0,0 +1,288 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use buck2_cli_proto::targets_request;
+use buck2_cli_proto::targets_request::OutputFormat;
+use buck2_cli_proto::TargetsRequest;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonCommandOptions;
+use buck2_client_ctx::common::CommonConsoleOptions;
+use buck2_client_ctx::common::CommonDaemonCommandOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::daemon::client::NoPartialResultHandler;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::path_arg::PathArg;
+use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_core::fs::fs_util;
+use gazebo::prelude::*;
+
+#[derive(Debug, thiserror::Error)]
+enum ChangedTargetsError {
+    #[error(
+        "Nothing to compare against, pass `--base-hashes`, `--changed-files` or `--changed-files-from` (or only `--save-hashes`)"
+    )]
+    NothingToCompare,
+    #[error("Target in `buck2 targets` output is missing `{0}`")]
+    MissingField(&'static str),
+}
+
+/// Find the targets affected by a change, for selective building and testing in CI.
+///
+/// Targets are compared by their configured target hash, which covers their definition,
+/// configuration, dependencies, and the files they reference, transitively. There are two
+/// ways to find the changes:
+///
+/// * Run with `--save-hashes` at the base revision, then with `--base-hashes` at the new
+///   revision. This finds every change, including to `BUCK` and `.bzl` files.
+///
+/// * Pass the changed files with `--changed-files` or `--changed-files-from`. This needs no
+///   second checkout, but only finds changes to files referenced by targets.
+///
+/// Affected targets are printed one per line.
+#[derive(Debug, clap::Parser)]
+#[clap(name = "changed-targets")]
+pub struct ChangedTargetsCommand {
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+
+    /// Target hashes written by `--save-hashes` at the base revision.
+    #[clap(long, value_name = "PATH")]
+    base_hashes: Option<PathArg>,
+
+    /// Files changed since the base revision.
+    #[clap(long, multiple_values = true, conflicts_with = "base-hashes")]
+    changed_files: Vec<PathArg>,
+
+    /// A file listing the files changed since the base revision, one per line and relative to
+    /// the project root, like the output of `git diff --name-only`.
+    #[clap(long, value_name = "PATH", conflicts_with = "base-hashes")]
+    changed_files_from: Option<PathArg>,
+
+    /// Write the target hashes at this revision to a file, to be passed as `--base-hashes`
+    /// at a later revision.
+    #[clap(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = &["changed-files", "changed-files-from"]
+    )]
+    save_hashes: Option<PathArg>,
+
+    /// Patterns of targets to consider.
+    #[clap(name = "TARGET_PATTERNS")]
+    patterns: Vec<String>,
+}
+
+/// Parse the output of `buck2 targets --json --show-target-hash` into a map from target
+/// label to hash.
+fn parse_target_hashes(json: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    let targets: Vec<serde_json::Map<String, serde_json::Value>> =
+        serde_json::from_str(json).context("Error parsing `buck2 targets` output")?;
+    let field = |target: &serde_json::Map<String, serde_json::Value>, name| {
+        target
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_owned())
+            .ok_or(ChangedTargetsError::MissingField(name))
+    };
+    let mut hashes = BTreeMap::new();
+    for target in targets {
+        hashes.insert(
+            format!(
+                "{}:{}",
+                field(&target, "buck.package")?,
+                field(&target, "name")?
+            ),
+            field(&target, "buck.target_hash")?,
+        );
+    }
+    Ok(hashes)
+}
+
+/// Targets whose hash differs between `base` and `head`, or which are new in `head`.
+fn changed_targets<'a>(
+    base: &BTreeMap<String, String>,
+    head: &'a BTreeMap<String, String>,
+) -> Vec<&'a str> {
+    head.iter()
+        .filter(|(target, hash)| base.get(*target) != Some(*hash))
+        .map(|(target, _)| target.as_str())
+        .collect()
+}
+
+impl ChangedTargetsCommand {
+    async fn target_hashes(
+        &self,
+        buckd: &mut BuckdClientConnector<'_>,
+        ctx: &mut ClientCommandContext<'_>,
+        matches: &clap::ArgMatches,
+        modified_paths: Option<Vec<String>>,
+    ) -> anyhow::Result<BTreeMap<String, String>> {
+        let context = Some(ctx.client_context(
+            &self.common_opts.config_opts,
+            matches,
+            ctx.sanitized_argv.argv.clone(),
+        )?);
+        let target_hash_file_mode = match modified_paths {
+            Some(_) => targets_request::TargetHashFileMode::PathsOnly,
+            None => targets_request::TargetHashFileMode::PathsAndContents,
+        };
+        let request = TargetsRequest {
+            context,
+            target_patterns: self.patterns.map(|pat| buck2_data::TargetPattern {
+                value: pat.to_owned(),
+            }),
+            output_format: OutputFormat::Json as i32,
+            targets: Some(targets_request::Targets::Other(targets_request::Other {
+                output_attributes: vec![
+                    "^name$".to_owned(),
+                    "^buck\\.package$".to_owned(),
+                    "^buck\\.target_hash$".to_owned(),
+                ],
+                target_hash_file_mode: target_hash_file_mode as i32,
+                target_hash_modified_paths: modified_paths.unwrap_or_default(),
+                target_hash_function: targets_request::TargetHashFunction::Fast as i32,
+                target_hash_graph_type: targets_request::TargetHashGraphType::Configured as i32,
+                include_default_attributes: false,
+                target_hash_recursive: true,
+                keep_going: false,
+                streaming: false,
+                cached: true,
+                imports: false,
+            })),
+            output: None,
+            concurrency: None,
+        };
+        let response = buckd
+            .with_flushing()
+            .targets(
+                request,
+                ctx.stdin()
+                    .console_interaction_stream(&self.common_opts.console_opts),
+                &mut NoPartialResultHandler,
+            )
+            .await??;
+        parse_target_hashes(&response.serialized_targets_output)
+    }
+}
+
+#[async_trait]
+impl StreamingCommand for ChangedTargetsCommand {
+    const COMMAND_NAME: &'static str = "changed-targets";
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        matches: &clap::ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let mut changed_files = self
+            .changed_files
+            .try_map(|path| path.resolve(&ctx.working_dir).into_string())?;
+        if let Some(changed_files_from) = &self.changed_files_from {
+            let project_root = ctx.paths()?.roots.project_root.clone();
+            let list = fs_util::read_to_string(changed_files_from.resolve(&ctx.working_dir))?;
+            for line in list.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                changed_files.push(
+                    project_root
+                        .root()
+                        .as_path()
+                        .join(line)
+                        .to_string_lossy()
+                        .into_owned(),
+                );
+            }
+        }
+        let by_changed_files = !self.changed_files.is_empty() || self.changed_files_from.is_some();
+
+        if by_changed_files {
+            // Referencing a modified path changes a target's hash, so the targets which
+            // (transitively) reference any of the changed files are those whose hashes differ.
+            let base = self
+                .target_hashes(buckd, ctx, matches, Some(Vec::new()))
+                .await?;
+            let head = self
+                .target_hashes(buckd, ctx, matches, Some(changed_files))
+                .await?;
+            for target in changed_targets(&base, &head) {
+                buck2_client_ctx::println!("{}", target)?;
+            }
+            return ExitResult::success();
+        }
+
+        if self.base_hashes.is_none() && self.save_hashes.is_none() {
+            return ExitResult::err(ChangedTargetsError::NothingToCompare.into());
+        }
+
+        let head = self.target_hashes(buckd, ctx, matches, None).await?;
+        if let Some(save_hashes) = &self.save_hashes {
+            fs_util::write(
+                save_hashes.resolve(&ctx.working_dir),
+                serde_json::to_string_pretty(&head)?,
+            )?;
+        }
+        if let Some(base_hashes) = &self.base_hashes {
+            let base: BTreeMap<String, String> = serde_json::from_str(&fs_util::read_to_string(
+                base_hashes.resolve(&ctx.working_dir),
+            )?)
+            .context("Error parsing `--base-hashes`")?;
+            for target in changed_targets(&base, &head) {
+                buck2_client_ctx::println!("{}", target)?;
+            }
+        }
+        ExitResult::success()
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        &self.common_opts.console_opts
+    }
+
+    fn event_log_opts(&self) -> &CommonDaemonCommandOptions {
+        &self.common_opts.event_log_opts
+    }
+
+    fn common_opts(&self) -> &CommonBuildConfigurationOptions {
+        &self.common_opts.config_opts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target_hashes() -> anyhow::Result<()> {
+        let hashes = parse_target_hashes(
+            r#"[{"name": "a", "buck.package": "root//foo", "buck.target_hash": "123"}]"#,
+        )?;
+        assert_eq!(Some("123"), hashes.get("root//foo:a").map(|h| h.as_str()));
+        assert!(parse_target_hashes(r#"[{"name": "a"}]"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_targets() {
+        let map = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let base = map(&[("root//:a", "1"), ("root//:b", "2"), ("root//:gone", "3")]);
+        let head = map(&[("root//:a", "1"), ("root//:b", "4"), ("root//:new", "5")]);
+        assert_eq!(
+            vec!["root//:b", "root//:new"],
+            changed_targets(&base, &head)
+        );
+    }
+}
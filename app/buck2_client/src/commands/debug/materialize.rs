@@ -24,11 +24,18 @@ pub struct MaterializeCommand {
     #[clap(flatten)]
     common_opts: CommonCommandOptions,
 
-    /// Paths to materialize, relative to project root
-    #[clap(value_name = "PATH")]
+    /// Paths to materialize, relative to project root, or target patterns whose default
+    /// outputs are materialized. The artifacts must have been built, but may not be on disk
+    /// because of the deferred materializer, in which case they are fetched from the CAS.
+    #[clap(value_name = "PATH|TARGET")]
     paths: Vec<String>,
 }
 
+/// Target patterns always contain `:` or `//`, which project relative paths never do.
+fn is_target_pattern(arg: &str) -> bool {
+    arg.contains(':') || arg.contains("//")
+}
+
 #[async_trait]
 impl StreamingCommand for MaterializeCommand {
     const COMMAND_NAME: &'static str = "materialize";
@@ -48,12 +55,15 @@ impl StreamingCommand for MaterializeCommand {
             matches,
             ctx.sanitized_argv.argv.clone(),
         )?;
+        let (targets, paths): (Vec<_>, Vec<_>) =
+            self.paths.into_iter().partition(|p| is_target_pattern(p));
         buckd
             .with_flushing()
             .materialize(
                 MaterializeRequest {
                     context: Some(context),
-                    paths: self.paths,
+                    paths,
+                    targets,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -77,7 +77,7 @@ pub enum DebugCommand {
     ChromeTrace(ChromeTraceCommand),
     /// Flushes all dep files known to Buck2.
     FlushDepFiles(FlushDepFilesCommand),
-    /// Forces materialization of a path, even on the deferred materializer
+    /// Forces materialization of paths or target outputs, even on the deferred materializer
     Materialize(MaterializeCommand),
     // Upload RE logs given an RE session ID
     UploadReLogs(UploadReLogsCommand),
@@ -0,0 +1,124 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_cli_proto::CqueryRequest;
+use buck2_cli_proto::QueryOutputFormat;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonCommandOptions;
+use buck2_client_ctx::common::CommonConsoleOptions;
+use buck2_client_ctx::common::CommonDaemonCommandOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::daemon::client::StdoutPartialResultHandler;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::streaming::StreamingCommand;
+
+/// Explain why a target is part of a build.
+///
+/// Prints a shortest dependency path from the universe roots to the target. Each hop shows the
+/// configured target and the attribute which introduced the dependency on the next one. Nothing
+/// is printed if the target is not reachable from the roots.
+#[derive(Debug, clap::Parser)]
+#[clap(name = "explain")]
+pub struct ExplainCommand {
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+
+    /// The target to explain.
+    #[clap(name = "TARGET")]
+    target: String,
+
+    /// The targets being built, from which to look for a path to the target.
+    #[clap(long, required = true, multiple_values = true, use_delimiter = true)]
+    universe: Vec<String>,
+}
+
+fn quote(literal: &str) -> String {
+    format!("\"{}\"", literal)
+}
+
+/// The cquery expression finding a shortest path from `universe` to `target`.
+fn explain_query(target: &str, universe: &[String]) -> String {
+    let roots: Vec<String> = universe.iter().map(|root| quote(root)).collect();
+    format!("shortestpath(set({}), {})", roots.join(" "), quote(target))
+}
+
+#[async_trait]
+impl StreamingCommand for ExplainCommand {
+    const COMMAND_NAME: &'static str = "explain";
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        matches: &clap::ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let context = ctx.client_context(
+            &self.common_opts.config_opts,
+            matches,
+            ctx.sanitized_argv.argv.clone(),
+        )?;
+
+        let response = buckd
+            .with_flushing()
+            .cquery(
+                CqueryRequest {
+                    query: explain_query(&self.target, &self.universe),
+                    query_args: Vec::new(),
+                    context: Some(context),
+                    output_attributes: Vec::new(),
+                    target_universe: self.universe,
+                    show_providers: false,
+                    unstable_output_format: QueryOutputFormat::Path as i32,
+                    correct_owner: true,
+                    dot_options: None,
+                },
+                ctx.stdin()
+                    .console_interaction_stream(&self.common_opts.console_opts),
+                &mut StdoutPartialResultHandler,
+            )
+            .await??;
+
+        for message in &response.error_messages {
+            buck2_client_ctx::eprintln!("{}", message)?;
+        }
+
+        if !response.error_messages.is_empty() {
+            ExitResult::failure()
+        } else {
+            ExitResult::success()
+        }
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        &self.common_opts.console_opts
+    }
+
+    fn event_log_opts(&self) -> &CommonDaemonCommandOptions {
+        &self.common_opts.event_log_opts
+    }
+
+    fn common_opts(&self) -> &CommonBuildConfigurationOptions {
+        &self.common_opts.config_opts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_query() {
+        assert_eq!(
+            r#"shortestpath(set("//:a" "//:b"), "//foo:bar")"#,
+            explain_query("//foo:bar", &["//:a".to_owned(), "//:b".to_owned()])
+        );
+    }
+}
@@ -160,22 +160,158 @@ fn initialize_buckconfig(repo_root: &AbsPath, prelude: bool, git: bool) -> anyho
     Ok(())
 }
 
-fn initialize_toolchains_buck(repo_root: &AbsPath) -> anyhow::Result<()> {
-    let mut buck = std::fs::File::create(repo_root.join("BUCK"))?;
+/// C/C++ tools found on the host, as `system_cxx_toolchain` attributes.
+#[derive(Debug, PartialEq, Eq)]
+struct CxxTools {
+    compiler_type: &'static str,
+    compiler: &'static str,
+    cxx_compiler: &'static str,
+    linker: &'static str,
+}
+
+/// Tools found on the host, so the generated toolchains work without editing.
+#[derive(Debug, PartialEq, Eq)]
+struct HostTools {
+    cxx: Option<CxxTools>,
+    python: Option<&'static str>,
+    rustc: bool,
+}
+
+impl HostTools {
+    fn detect() -> HostTools {
+        Self::detect_with(cfg!(windows), |tool| which::which(tool).is_ok())
+    }
+
+    fn detect_with(windows: bool, found: impl Fn(&str) -> bool) -> HostTools {
+        let cxx_candidates = if windows {
+            vec![
+                CxxTools {
+                    compiler_type: "windows",
+                    compiler: "cl.exe",
+                    cxx_compiler: "cl.exe",
+                    linker: "link.exe",
+                },
+                CxxTools {
+                    compiler_type: "clang",
+                    compiler: "clang",
+                    cxx_compiler: "clang++",
+                    linker: "link.exe",
+                },
+            ]
+        } else {
+            vec![
+                CxxTools {
+                    compiler_type: "clang",
+                    compiler: "clang",
+                    cxx_compiler: "clang++",
+                    linker: "clang++",
+                },
+                CxxTools {
+                    compiler_type: "gcc",
+                    compiler: "gcc",
+                    cxx_compiler: "g++",
+                    linker: "g++",
+                },
+            ]
+        };
+        let python_candidates: &[&'static str] = if windows {
+            &["python", "python3"]
+        } else {
+            &["python3", "python"]
+        };
+        HostTools {
+            cxx: cxx_candidates
+                .into_iter()
+                .find(|c| found(c.compiler) && found(c.cxx_compiler)),
+            python: python_candidates.iter().copied().find(|p| found(p)),
+            rustc: found("rustc"),
+        }
+    }
+}
 
+fn write_toolchains_buck(buck: &mut impl Write, host: &HostTools) -> anyhow::Result<()> {
+    if host.cxx.is_some() {
+        writeln!(
+            buck,
+            "load(\"@prelude//toolchains:cxx.bzl\", \"system_cxx_toolchain\")",
+        )?;
+    }
     writeln!(
         buck,
         "load(\"@prelude//toolchains:genrule.bzl\", \"system_genrule_toolchain\")",
     )?;
+    if host.python.is_some() {
+        writeln!(
+            buck,
+            "load(\"@prelude//toolchains:python.bzl\", \"system_python_bootstrap_toolchain\", \"system_python_toolchain\")",
+        )?;
+    } else {
+        writeln!(
+            buck,
+            "load(\"@prelude//toolchains:python.bzl\", \"system_python_bootstrap_toolchain\")",
+        )?;
+    }
+    if host.rustc {
+        writeln!(
+            buck,
+            "load(\"@prelude//toolchains:rust.bzl\", \"system_rust_toolchain\")",
+        )?;
+    }
+
+    if let Some(cxx) = &host.cxx {
+        writeln!(buck)?;
+        writeln!(buck, "system_cxx_toolchain(")?;
+        writeln!(buck, "    name = \"cxx\",")?;
+        writeln!(buck, "    compiler_type = \"{}\",", cxx.compiler_type)?;
+        writeln!(buck, "    compiler = \"{}\",", cxx.compiler)?;
+        writeln!(buck, "    cxx_compiler = \"{}\",", cxx.cxx_compiler)?;
+        writeln!(buck, "    linker = \"{}\",", cxx.linker)?;
+        writeln!(buck, "    visibility = [\"PUBLIC\"],")?;
+        writeln!(buck, ")")?;
+    }
+
     writeln!(buck)?;
     writeln!(buck, "system_genrule_toolchain(")?;
     writeln!(buck, "    name = \"genrule\",")?;
     writeln!(buck, "    visibility = [\"PUBLIC\"],")?;
     writeln!(buck, ")")?;
 
+    // The bootstrap toolchain is used by the prelude's own tools, so always declare it.
+    writeln!(buck)?;
+    writeln!(buck, "system_python_bootstrap_toolchain(")?;
+    writeln!(buck, "    name = \"python_bootstrap\",")?;
+    if let Some(python) = host.python {
+        writeln!(buck, "    interpreter = \"{}\",", python)?;
+    }
+    writeln!(buck, "    visibility = [\"PUBLIC\"],")?;
+    writeln!(buck, ")")?;
+
+    if let Some(python) = host.python {
+        writeln!(buck)?;
+        writeln!(buck, "system_python_toolchain(")?;
+        writeln!(buck, "    name = \"python\",")?;
+        writeln!(buck, "    interpreter = \"{}\",", python)?;
+        writeln!(buck, "    visibility = [\"PUBLIC\"],")?;
+        writeln!(buck, ")")?;
+    }
+
+    if host.rustc {
+        writeln!(buck)?;
+        writeln!(buck, "system_rust_toolchain(")?;
+        writeln!(buck, "    name = \"rust\",")?;
+        writeln!(buck, "    default_edition = \"2021\",")?;
+        writeln!(buck, "    visibility = [\"PUBLIC\"],")?;
+        writeln!(buck, ")")?;
+    }
+
     Ok(())
 }
 
+fn initialize_toolchains_buck(repo_root: &AbsPath, host: &HostTools) -> anyhow::Result<()> {
+    let mut buck = std::fs::File::create(repo_root.join("BUCK"))?;
+    write_toolchains_buck(&mut buck, host)
+}
+
 fn initialize_root_buck(repo_root: &AbsPath, prelude: bool) -> anyhow::Result<()> {
     let mut buck = std::fs::File::create(repo_root.join("BUCK"))?;
 
@@ -264,7 +400,7 @@ fn set_up_project(repo_root: &AbsPath, git: bool, prelude: bool) -> anyhow::Resu
         let toolchains = repo_root.join("toolchains");
         if !toolchains.exists() {
             fs_util::create_dir(&toolchains)?;
-            initialize_toolchains_buck(&toolchains)?;
+            initialize_toolchains_buck(&toolchains, &HostTools::detect())?;
         }
     }
     if !repo_root.join("BUCK").exists() {
@@ -282,6 +418,9 @@ mod tests {
     use crate::commands::init::initialize_root_buck;
     use crate::commands::init::set_up_gitignore;
     use crate::commands::init::set_up_project;
+    use crate::commands::init::write_toolchains_buck;
+    use crate::commands::init::CxxTools;
+    use crate::commands::init::HostTools;
 
     #[test]
     fn test_set_up_project_with_prelude_no_git() -> anyhow::Result<()> {
@@ -405,4 +544,71 @@ genrule(
         assert_eq!(actual_buck, expected_buck);
         Ok(())
     }
+
+    #[test]
+    fn test_detect_host_tools() {
+        let host = HostTools::detect_with(false, |tool| {
+            ["gcc", "g++", "python3", "clang"].contains(&tool)
+        });
+        assert_eq!(
+            HostTools {
+                cxx: Some(CxxTools {
+                    compiler_type: "gcc",
+                    compiler: "gcc",
+                    cxx_compiler: "g++",
+                    linker: "g++",
+                }),
+                python: Some("python3"),
+                rustc: false,
+            },
+            host
+        );
+
+        let host = HostTools::detect_with(true, |tool| ["cl.exe", "rustc"].contains(&tool));
+        assert_eq!(Some("windows"), host.cxx.map(|c| c.compiler_type));
+        assert_eq!(None, host.python);
+        assert!(host.rustc);
+    }
+
+    #[test]
+    fn test_toolchains_generation() -> anyhow::Result<()> {
+        let mut buck = Vec::new();
+        write_toolchains_buck(
+            &mut buck,
+            &HostTools {
+                cxx: None,
+                python: Some("python3"),
+                rustc: true,
+            },
+        )?;
+        let expected_buck = "load(\"@prelude//toolchains:genrule.bzl\", \"system_genrule_toolchain\")
+load(\"@prelude//toolchains:python.bzl\", \"system_python_bootstrap_toolchain\", \"system_python_toolchain\")
+load(\"@prelude//toolchains:rust.bzl\", \"system_rust_toolchain\")
+
+system_genrule_toolchain(
+    name = \"genrule\",
+    visibility = [\"PUBLIC\"],
+)
+
+system_python_bootstrap_toolchain(
+    name = \"python_bootstrap\",
+    interpreter = \"python3\",
+    visibility = [\"PUBLIC\"],
+)
+
+system_python_toolchain(
+    name = \"python\",
+    interpreter = \"python3\",
+    visibility = [\"PUBLIC\"],
+)
+
+system_rust_toolchain(
+    name = \"rust\",
+    default_edition = \"2021\",
+    visibility = [\"PUBLIC\"],
+)
+";
+        assert_eq!(String::from_utf8(buck)?, expected_buck);
+        Ok(())
+    }
 }
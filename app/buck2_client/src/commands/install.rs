@@ -81,9 +81,9 @@ struct AndroidInstallOptions {
         short,
         long,
         alias = "udid",
-        help = "Use Android device or emulator with specific serial or UDID number. Here for compatibility with buck1 - it is automatically forwarded to the installer"
+        help = "Use Android device or emulator with specific serial or UDID number. May be repeated to install to several devices concurrently. Here for compatibility with buck1 - it is automatically forwarded to the installer"
     )]
-    serial: Option<String>,
+    serial: Vec<String>,
 
     #[clap(
         short = 'x',
@@ -133,9 +133,9 @@ impl StreamingCommand for InstallCommand {
         if self.android_install_opts.device {
             extra_run_args.push("-d".to_owned());
         }
-        if let Some(serial) = self.android_install_opts.serial {
+        for serial in &self.android_install_opts.serial {
             extra_run_args.push("-s".to_owned());
-            extra_run_args.push(serial);
+            extra_run_args.push(serial.to_owned());
         }
         if self.android_install_opts.all_devices {
             extra_run_args.push("-x".to_owned());
@@ -168,6 +168,7 @@ impl StreamingCommand for InstallCommand {
                     build_opts: Some(self.build_opts.to_proto()),
                     installer_run_args: extra_run_args,
                     installer_debug: self.installer_debug,
+                    devices: self.android_install_opts.serial,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
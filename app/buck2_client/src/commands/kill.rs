@@ -23,7 +23,8 @@ use buck2_client_ctx::startup_deadline::StartupDeadline;
 ///
 /// Note there's also `buck2 killall` and `buck2 clean`.
 ///
-/// `buck2 killall` kills all the buck2 processes on the machine.
+/// `buck2 killall` kills all the buck2 processes on the machine, and
+/// `buck2 killall --repo` shuts down the daemons of this project in every isolation dir.
 ///
 /// `buck2 clean` kills the buck2 daemon and also deletes the buck2 state files.
 #[derive(Debug, clap::Parser)]
@@ -6,20 +6,38 @@
  * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
  * of this source tree.
  */
+use std::time::Duration;
 
+use anyhow::Context as _;
 use buck2_client_ctx::argv::Argv;
 use buck2_client_ctx::argv::SanitizedArgv;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::daemon::client::BuckdLifecycleLock;
 use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::startup_deadline::StartupDeadline;
+use buck2_common::daemon_dir::DaemonDir;
+use buck2_common::invocation_paths::InvocationPaths;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_wrapper_common::is_buck2::WhoIsAsking;
 
+use crate::commands::kill::kill_command_impl;
+
 #[derive(Debug, clap::Parser)]
 #[clap(about = "Kill all buck2 processes on the machine")]
-pub struct KillallCommand {}
+pub struct KillallCommand {
+    /// Only shut down the daemons of the current project, in every isolation dir. They are
+    /// asked to shut down gracefully, like `buck2 kill` does.
+    #[clap(long)]
+    repo: bool,
+}
 
 impl KillallCommand {
     pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
-        ctx.instant_command("killall", async move |_ctx| {
+        ctx.instant_command("killall", async move |ctx| {
+            if self.repo {
+                return kill_repo_daemons(ctx.paths()?).await;
+            }
             buck2_wrapper_common::killall(WhoIsAsking::Buck2, |s| {
                 let _ignored = buck2_client_ctx::eprintln!("{}", s);
             })
@@ -32,3 +50,72 @@ impl KillallCommand {
         argv.no_need_to_sanitize()
     }
 }
+
+/// The daemon dirs of the project of `paths`, one per isolation dir which has a daemon.
+fn repo_daemon_dirs(paths: &InvocationPaths) -> anyhow::Result<Vec<DaemonDir>> {
+    let daemon_dir = paths.daemon_dir()?;
+    let isolation_dirs_parent = daemon_dir
+        .path
+        .parent()
+        .context("Daemon dir has no parent")?;
+    let mut daemon_dirs = Vec::new();
+    if let Some(entries) = fs_util::read_dir_if_exists(isolation_dirs_parent)? {
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let dir = DaemonDir {
+                path: AbsNormPathBuf::try_from(entry.path())?,
+            };
+            if dir.buckd_info().exists() {
+                daemon_dirs.push(dir);
+            }
+        }
+    }
+    daemon_dirs.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(daemon_dirs)
+}
+
+async fn kill_repo_daemons(paths: &InvocationPaths) -> anyhow::Result<()> {
+    let daemon_dirs = repo_daemon_dirs(paths)?;
+    if daemon_dirs.is_empty() {
+        buck2_client_ctx::eprintln!("no buckd server running for this project")?;
+        return Ok(());
+    }
+    // Keep going when killing one daemon fails, so the others are still killed.
+    let mut failed = Vec::new();
+    for daemon_dir in daemon_dirs {
+        let isolation_dir = daemon_dir
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        buck2_client_ctx::eprintln!("isolation dir `{}`:", isolation_dir)?;
+        if let Err(e) = kill_daemon(daemon_dir).await {
+            buck2_client_ctx::eprintln!("{:#}", e)?;
+            failed.push(isolation_dir);
+        }
+    }
+    if !failed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Failed to kill the daemons of isolation dirs: {}",
+            failed
+                .iter()
+                .map(|d| format!("`{}`", d))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(())
+}
+
+async fn kill_daemon(daemon_dir: DaemonDir) -> anyhow::Result<()> {
+    let lifecycle_lock = BuckdLifecycleLock::lock_with_timeout(
+        daemon_dir,
+        StartupDeadline::duration_from_now(Duration::from_secs(10))?,
+    )
+    .await
+    .with_context(|| "Error locking buckd lifecycle.lock")?;
+    kill_command_impl(&lifecycle_lock, "`buck2 killall --repo` was invoked").await
+}
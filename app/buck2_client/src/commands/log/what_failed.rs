@@ -10,24 +10,24 @@
 use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::exit_result::ExitResult;
 
-use crate::commands::log::what_ran::WhatRanCommand;
+use crate::commands::log::what_ran::WhatFailedOptions;
 use crate::commands::log::what_ran::WhatRanCommandCommon;
 
 /// Outputs every command that failed in the selected invocation.
 ///
-/// Look at the help for what-ran to understand the output format.
+/// Look at the help for what-ran to understand the output format. With `--show-std-err`, the
+/// stderr of each failed command is shown after it.
 #[derive(Debug, clap::Parser)]
 pub struct WhatFailedCommand {
     #[clap(flatten)]
     pub common: WhatRanCommandCommon,
+
+    #[clap(flatten)]
+    pub failed: WhatFailedOptions,
 }
 
 impl WhatFailedCommand {
-    pub fn exec(self, matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
-        WhatRanCommand {
-            common: self.common,
-            failed: true,
-        }
-        .exec(matches, ctx)
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        self.common.exec(ctx, Some(self.failed))
     }
 }
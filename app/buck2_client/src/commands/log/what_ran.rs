@@ -9,11 +9,15 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::stream_value::StreamValue;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_path::AbsPathBuf;
 use buck2_data::re_platform::Property;
 use buck2_event_observer::what_ran;
 use buck2_event_observer::what_ran::CommandReproducer;
@@ -23,6 +27,7 @@ use buck2_event_observer::what_ran::WhatRanOutputCommandExtra;
 use buck2_event_observer::what_ran::WhatRanOutputWriter;
 use buck2_event_observer::what_ran::WhatRanRelevantAction;
 use buck2_event_observer::what_ran::WhatRanState;
+use buck2_util::indent::indent;
 use futures::stream::Stream;
 use futures::TryStreamExt;
 use indexmap::IndexMap;
@@ -42,6 +47,10 @@ use crate::commands::log::LogCommandOutputFormat;
 ///
 /// Details to reproduce it. For RE, that's the action digest. For local, the command.
 ///
+/// How long the command took, in seconds. This is empty if the command didn't finish.
+///
+/// The digest of the action the command was for.
+///
 ///
 /// To reproduce an action that ran on RE, use the following command then follow the instructions.
 /// The DIGEST is of the form `hash:size`.
@@ -78,21 +87,56 @@ pub struct WhatRanCommandCommon {
 
     #[clap(flatten)]
     pub options: WhatRanOptions,
+
+    /// Only show commands for targets matching this pattern, e.g. `//foo:bar`, `//foo:` or
+    /// `//foo/...`. Patterns without a cell match targets in any cell. Can be repeated.
+    #[clap(long, value_name = "PATTERN")]
+    pub filter_target: Vec<String>,
+}
+
+/// Options only relevant when showing commands that failed.
+#[derive(Debug, Default, clap::Parser)]
+pub struct WhatFailedOptions {
+    /// Also show the stderr of the failed commands.
+    #[clap(long)]
+    pub show_std_err: bool,
+
+    /// Write a shell script reproducing each failed local command to this directory. The
+    /// scripts run the command from the project root.
+    #[clap(long, value_name = "DIR")]
+    pub repro_dir: Option<PathArg>,
 }
 
 impl WhatRanCommand {
     pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let Self { common, failed } = self;
+        common.exec(ctx, failed.then(WhatFailedOptions::default))
+    }
+}
+
+impl WhatRanCommandCommon {
+    /// Show the commands, or only the ones that failed if `failed` is set.
+    pub(crate) fn exec(
+        self,
+        ctx: ClientCommandContext<'_>,
+        failed: Option<WhatFailedOptions>,
+    ) -> ExitResult {
         let Self {
-            common:
-                WhatRanCommandCommon {
-                    event_log,
-                    mut output,
-                    options,
-                },
-            failed,
+            event_log,
+            output,
+            options,
+            filter_target,
         } = self;
 
         ctx.with_runtime(async move |ctx| {
+            let filters = filter_target
+                .iter()
+                .map(|p| TargetPatternFilter::parse(p))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let mut output = TargetFilteredOutput {
+                inner: output,
+                filters,
+            };
             let log_path = event_log.get(&ctx).await?;
 
             let (invocation, events) = log_path.unpack_stream().await?;
@@ -102,10 +146,36 @@ impl WhatRanCommand {
                 invocation.display_command_line()
             )?;
 
-            if failed {
-                WhatFailedImpl::execute(events, &mut output, &options).await?;
-            } else {
-                WhatRanImpl::execute(events, &mut output, &options).await?;
+            match failed {
+                Some(failed) => {
+                    let scripts = match &failed.repro_dir {
+                        Some(dir) => {
+                            let dir = dir.resolve(&ctx.working_dir);
+                            fs_util::create_dir_all(&dir)?;
+                            Some(ReproScripts {
+                                dir,
+                                project_root: ctx.paths()?.project_root().root().to_string(),
+                                count: 0,
+                            })
+                        }
+                        None => None,
+                    };
+                    let mut output = ReproScriptOutput {
+                        inner: output,
+                        scripts,
+                    };
+                    WhatFailedImpl {
+                        known_actions: HashMap::new(),
+                        show_std_err: failed.show_std_err,
+                    }
+                    .execute(events, &mut output, &options)
+                    .await?;
+                }
+                None => {
+                    WhatRanImpl::default()
+                        .execute(events, &mut output, &options)
+                        .await?;
+                }
             };
 
             anyhow::Ok(())
@@ -116,7 +186,7 @@ impl WhatRanCommand {
 }
 
 #[async_trait]
-trait WhatRanCommandImplementation: Default {
+trait WhatRanCommandImplementation: Send + Sized {
     fn event(
         &mut self,
         event: Box<buck2_data::BuckEvent>,
@@ -125,29 +195,55 @@ trait WhatRanCommandImplementation: Default {
     ) -> anyhow::Result<()>;
 
     async fn execute(
+        mut self,
         mut events: impl Stream<Item = anyhow::Result<StreamValue>> + Unpin + Send,
         output: &mut (impl WhatRanOutputWriter + Send),
         options: &WhatRanOptions,
     ) -> anyhow::Result<()> {
-        let mut cmd = Self::default();
-
         while let Some(event) = events.try_next().await? {
             match event {
-                StreamValue::Event(event) => cmd.event(event, output, options)?,
+                StreamValue::Event(event) => self.event(event, output, options)?,
                 _ => {}
             }
         }
 
+        self.finish(output, options)
+    }
+
+    /// Called once all the events have been seen.
+    fn finish(
+        &mut self,
+        _output: &mut impl WhatRanOutputWriter,
+        _options: &WhatRanOptions,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
 }
 
 /// The state for a WhatRan command. This is all the events we have seen that are
-/// WhatRanRelevantActions. This emits the actions immediately.
+/// WhatRanRelevantActions, and the commands that are running. This emits commands when they
+/// finish, so that we know how long they took.
 #[derive(Default)]
 pub struct WhatRanImpl {
     /// Maps action spans to their details.
     known_actions: HashMap<u64, Box<buck2_data::BuckEvent>>,
+    /// Maps command spans to the event that started them. Known to be CommandReproducers.
+    running_commands: IndexMap<u64, Box<buck2_data::BuckEvent>>,
+}
+
+impl WhatRanImpl {
+    fn emit_command(
+        &self,
+        event: &buck2_data::BuckEvent,
+        duration: Option<Duration>,
+        output: &mut impl WhatRanOutputWriter,
+        options: &WhatRanOptions,
+    ) -> anyhow::Result<()> {
+        let repro =
+            CommandReproducer::from_buck_data(event.data.as_ref().expect("Checked above"), options)
+                .expect("Checked above");
+        what_ran::emit_reproducer(self.get(event.parent_id), repro, duration, None, output)
+    }
 }
 
 impl WhatRanState<u64> for WhatRanImpl {
@@ -160,10 +256,8 @@ impl WhatRanState<u64> for WhatRanImpl {
 }
 
 impl WhatRanCommandImplementation for WhatRanImpl {
-    /// Receive a new event. We start by emitting it if it's relevant (since that only takes a
-    /// borrow), and then if it's relevant as a parent, we store it for latter use. Note that in
-    /// practice we don't expect the event to be *both* relevant to emit *and* a
-    /// WhatRanRelevantAction, but it doesn't hurt to always check both.
+    /// Receive a new event. Commands are stored until their span ends, at which point we emit
+    /// them. If the event is relevant as a parent, we store it for latter use.
     fn event(
         &mut self,
         event: Box<buck2_data::BuckEvent>,
@@ -171,7 +265,21 @@ impl WhatRanCommandImplementation for WhatRanImpl {
         options: &WhatRanOptions,
     ) -> anyhow::Result<()> {
         if let Some(data) = &event.data {
-            what_ran::emit_event_if_relevant(event.parent_id, data, &*self, output, options)?;
+            if CommandReproducer::from_buck_data(data, options).is_some() {
+                self.running_commands.insert(event.span_id, event);
+                return Ok(());
+            }
+
+            if let buck2_data::buck_event::Data::SpanEnd(end) = data {
+                if let Some(start) = self.running_commands.shift_remove(&event.span_id) {
+                    let duration = end
+                        .duration
+                        .clone()
+                        .and_then(|d| Duration::try_from(d).ok());
+                    self.emit_command(&start, duration, output, options)?;
+                }
+                return Ok(());
+            }
 
             if WhatRanRelevantAction::from_buck_data(data).is_some() {
                 self.known_actions.insert(event.span_id, event);
@@ -180,14 +288,28 @@ impl WhatRanCommandImplementation for WhatRanImpl {
 
         Ok(())
     }
+
+    /// Commands that never finished (e.g. the invocation was interrupted) are still emitted, with
+    /// no duration.
+    fn finish(
+        &mut self,
+        output: &mut impl WhatRanOutputWriter,
+        options: &WhatRanOptions,
+    ) -> anyhow::Result<()> {
+        for event in std::mem::take(&mut self.running_commands).into_values() {
+            self.emit_command(&event, None, output, options)?;
+        }
+        Ok(())
+    }
 }
 
 /// The state for a WhatRan command when only showing actions that failed. This stores all the events
 /// we have seen that are WhatRanRelevantActions, and the CommandReproducer associated with them.
-#[derive(Default)]
 pub struct WhatFailedImpl {
     /// Maps action spans to their details.
     known_actions: HashMap<u64, WhatFailedEntry>,
+    /// Whether to emit the stderr of failed commands.
+    show_std_err: bool,
 }
 
 #[allow(clippy::vec_box)]
@@ -240,24 +362,26 @@ impl WhatRanCommandImplementation for WhatFailedImpl {
 
             match data {
                 buck2_data::buck_event::Data::SpanEnd(span) => match &span.data {
-                    Some(buck2_data::span_end_event::Data::ActionExecution(action))
-                        if action.failed =>
+                    Some(buck2_data::span_end_event::Data::ActionExecution(action_end))
+                        if action_end.failed =>
                     {
                         if let Some(entry) = self.known_actions.remove(&event.span_id) {
                             let action = WhatRanRelevantAction::from_buck_data(
                                 entry.event.data.as_ref().expect("Checked above"),
                             );
+                            let std_err = if self.show_std_err {
+                                action_std_err(action_end)
+                            } else {
+                                None
+                            };
 
                             for repro in entry.reproducers.iter() {
-                                what_ran::emit_reproducer(
-                                    action,
-                                    CommandReproducer::from_buck_data(
-                                        repro.data.as_ref().expect("Checked above"),
-                                        options,
-                                    )
-                                    .expect("Checked above"),
-                                    output,
-                                )?;
+                                let repro = CommandReproducer::from_buck_data(
+                                    repro.data.as_ref().expect("Checked above"),
+                                    options,
+                                )
+                                .expect("Checked above");
+                                what_ran::emit_reproducer(action, repro, None, std_err, output)?;
                             }
                         }
                     }
@@ -271,18 +395,35 @@ impl WhatRanCommandImplementation for WhatFailedImpl {
     }
 }
 
+/// The stderr of the last command of an action, which is the one whose failure is shown to users.
+fn action_std_err(action: &buck2_data::ActionExecutionEnd) -> Option<&str> {
+    action
+        .commands
+        .last()?
+        .details
+        .as_ref()
+        .map(|d| d.stderr.as_str())
+        .filter(|s| !s.is_empty())
+}
+
 /// An output that writes to stdout in a tabulated format.
 impl WhatRanOutputWriter for LogCommandOutputFormat {
     fn emit_command(&mut self, command: WhatRanOutputCommand<'_>) -> anyhow::Result<()> {
         match self {
             Self::Tabulated => {
                 buck2_client_ctx::println!(
-                    "{}\t{}\t{}\t{}",
+                    "{}\t{}\t{}\t{}\t{}\t{}",
                     command.reason(),
                     command.identity(),
                     command.repro().executor(),
-                    command.repro().as_human_readable()
-                )
+                    command.repro().as_human_readable(),
+                    display_duration(command.duration()),
+                    command.repro().action_digest(),
+                )?;
+                if let Some(std_err) = command.std_err() {
+                    buck2_client_ctx::println!("{}", indent("  ", std_err.trim_end()))?;
+                }
+                Ok(())
             }
             Self::Json => {
                 let reproducer = match command.repro() {
@@ -331,6 +472,9 @@ impl WhatRanOutputWriter for LogCommandOutputFormat {
                     identity: command.identity(),
                     reproducer,
                     extra: command.extra().map(Into::into),
+                    duration_ms: command.duration().map(|d| d.as_millis() as u64),
+                    digest: command.repro().action_digest(),
+                    std_err: command.std_err(),
                 };
 
                 buck2_client_ctx::stdio::print_with_writer(|mut w| {
@@ -345,6 +489,8 @@ impl WhatRanOutputWriter for LogCommandOutputFormat {
                     identity: &'a str,
                     executor: String,
                     reproducer: String,
+                    duration: String,
+                    digest: &'a str,
                 }
 
                 buck2_client_ctx::stdio::print_with_writer(|w| {
@@ -354,6 +500,8 @@ impl WhatRanOutputWriter for LogCommandOutputFormat {
                         identity: command.identity(),
                         executor: command.repro().executor(),
                         reproducer: command.repro().as_human_readable().to_string(),
+                        duration: display_duration(command.duration()),
+                        digest: command.repro().action_digest(),
                     })
                 })
             }
@@ -361,6 +509,152 @@ impl WhatRanOutputWriter for LogCommandOutputFormat {
     }
 }
 
+fn display_duration(duration: Option<Duration>) -> String {
+    duration.map_or_else(String::new, |d| format!("{:.3}s", d.as_secs_f64()))
+}
+
+/// A target pattern given to `--filter-target`. Unlike target patterns in build commands, these
+/// are matched against the rendered target of each command, so they never need resolving.
+#[derive(Debug, PartialEq, Eq)]
+struct TargetPatternFilter {
+    /// The cell, or `None` to match any cell.
+    cell: Option<String>,
+    package: String,
+    kind: TargetPatternFilterKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum TargetPatternFilterKind {
+    /// `//foo:bar`
+    Target(String),
+    /// `//foo:`
+    Package,
+    /// `//foo/...`
+    Recursive,
+}
+
+impl TargetPatternFilter {
+    fn parse(pattern: &str) -> anyhow::Result<Self> {
+        let (cell, rest) = pattern.split_once("//").ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid target pattern `{}`, expected a pattern like `//foo:bar`, `//foo:` or `//foo/...`",
+                pattern
+            )
+        })?;
+        let cell = (!cell.is_empty()).then(|| cell.to_owned());
+
+        let (package, kind) = if let Some(package) = rest.strip_suffix("...") {
+            let package = package.strip_suffix('/').unwrap_or(package);
+            (package.to_owned(), TargetPatternFilterKind::Recursive)
+        } else if let Some((package, name)) = rest.split_once(':') {
+            let kind = if name.is_empty() {
+                TargetPatternFilterKind::Package
+            } else {
+                TargetPatternFilterKind::Target(name.to_owned())
+            };
+            (package.to_owned(), kind)
+        } else {
+            // `//foo/bar` is short for `//foo/bar:bar`.
+            let name = rest.rsplit('/').next().unwrap_or_default();
+            (
+                rest.to_owned(),
+                TargetPatternFilterKind::Target(name.to_owned()),
+            )
+        };
+
+        Ok(Self {
+            cell,
+            package,
+            kind,
+        })
+    }
+
+    /// Check whether the target of a command matches. `identity` is the target followed by
+    /// details of the action (e.g. `cell//foo:bar (platform) (category identifier)`).
+    fn matches(&self, identity: &str) -> bool {
+        let target = identity.split(' ').next().unwrap_or_default();
+        let (cell, rest) = match target.split_once("//") {
+            Some(x) => x,
+            None => return false,
+        };
+        let (package, name) = match rest.split_once(':') {
+            Some(x) => x,
+            None => return false,
+        };
+        if let Some(want) = &self.cell {
+            if want != cell {
+                return false;
+            }
+        }
+        match &self.kind {
+            TargetPatternFilterKind::Target(want) => package == self.package && name == want,
+            TargetPatternFilterKind::Package => package == self.package,
+            TargetPatternFilterKind::Recursive => {
+                self.package.is_empty()
+                    || package == self.package
+                    || package
+                        .strip_prefix(&self.package)
+                        .map_or(false, |rest| rest.starts_with('/'))
+            }
+        }
+    }
+}
+
+/// Forwards only the commands whose target matches one of the filters, or all of them if there
+/// are no filters.
+struct TargetFilteredOutput {
+    inner: LogCommandOutputFormat,
+    filters: Vec<TargetPatternFilter>,
+}
+
+impl WhatRanOutputWriter for TargetFilteredOutput {
+    fn emit_command(&mut self, command: WhatRanOutputCommand<'_>) -> anyhow::Result<()> {
+        if self.filters.is_empty() || self.filters.iter().any(|f| f.matches(command.identity())) {
+            self.inner.emit_command(command)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a shell script for each local command emitted, if a directory was requested.
+struct ReproScriptOutput<W> {
+    inner: W,
+    scripts: Option<ReproScripts>,
+}
+
+struct ReproScripts {
+    dir: AbsPathBuf,
+    project_root: String,
+    /// Number of scripts written so far, used to name them.
+    count: usize,
+}
+
+impl<W: WhatRanOutputWriter> WhatRanOutputWriter for ReproScriptOutput<W> {
+    fn emit_command(&mut self, command: WhatRanOutputCommand<'_>) -> anyhow::Result<()> {
+        if let Some(scripts) = &mut self.scripts {
+            match command.repro() {
+                CommandReproducer::LocalExecute(..) | CommandReproducer::WorkerExecute(..) => {
+                    scripts.count += 1;
+                    let path = scripts.dir.join(format!("repro_{}.sh", scripts.count));
+                    fs_util::write(
+                        &path,
+                        format!(
+                            "#!/bin/sh\n# {}\ncd {} || exit 1\n{}\n",
+                            command.identity(),
+                            shlex::quote(&scripts.project_root),
+                            command.repro().as_human_readable(),
+                        ),
+                    )?;
+                    fs_util::set_executable(&path)?;
+                    buck2_client_ctx::eprintln!("Wrote {}", path.display())?;
+                }
+                _ => {}
+            }
+        }
+        self.inner.emit_command(command)
+    }
+}
+
 fn into_index_map(platform: &Option<buck2_data::RePlatform>) -> IndexMap<&str, &str> {
     platform.as_ref().map_or_else(IndexMap::new, |p| {
         p.properties
@@ -377,6 +671,13 @@ struct JsonCommand<'a> {
     reproducer: JsonReproducer<'a>,
     #[serde(skip_serializing_if = "Option::is_none")]
     extra: Option<JsonExtra<'a>>,
+    /// How long the command took, if it finished.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    digest: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    std_err: Option<&'a str>,
 }
 
 mod json_reproducer {
@@ -438,6 +739,8 @@ mod tests {
             identity: "some/target",
             reproducer: JsonReproducer::Local { command, env },
             extra: None,
+            duration_ms: None,
+            digest: "",
         }
     }
 
@@ -453,6 +756,8 @@ mod tests {
                 action_key: None,
             },
             extra: None,
+            duration_ms: None,
+            digest: "",
         }
     }
 
@@ -531,4 +836,65 @@ mod tests {
         assert_eq!(expected, serde_json::to_string_pretty(&command)?);
         Ok(())
     }
+
+    #[test]
+    fn serialize_what_ran_command_with_duration() -> anyhow::Result<()> {
+        let mut command = make_base_command_in_re();
+        command.duration_ms = Some(1500);
+        command.digest = "placeholder";
+
+        let expected = r#"{
+  "reason": "test.run",
+  "identity": "some/target",
+  "reproducer": {
+    "executor": "Re",
+    "details": {
+      "digest": "placeholder",
+      "platform_properties": {
+        "platform": "linux-remote-execution"
+      }
+    }
+  },
+  "duration_ms": 1500,
+  "digest": "placeholder"
+}"#;
+        assert_eq!(expected, serde_json::to_string_pretty(&command)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_target_pattern_filter() -> anyhow::Result<()> {
+        let identity = "root//foo/bar:baz (cfg#abc) (rustc lib)";
+
+        for pattern in [
+            "root//foo/bar:baz",
+            "//foo/bar:baz",
+            "//foo/bar:",
+            "//foo/...",
+            "//foo/bar/...",
+            "//...",
+        ] {
+            assert!(
+                TargetPatternFilter::parse(pattern)?.matches(identity),
+                "{}",
+                pattern
+            );
+        }
+        for pattern in [
+            "other//foo/bar:baz",
+            "//foo/bar:qux",
+            "//foo:",
+            "//fo/...",
+            "//foo/bar",
+        ] {
+            assert!(
+                !TargetPatternFilter::parse(pattern)?.matches(identity),
+                "{}",
+                pattern
+            );
+        }
+        assert!(TargetPatternFilter::parse("//foo/baz")?.matches("root//foo/baz:baz"));
+        assert!(TargetPatternFilter::parse("foo:bar").is_err());
+        Ok(())
+    }
 }
@@ -9,10 +9,12 @@
 
 pub mod build;
 pub mod bxl;
+pub mod changed_targets;
 pub mod clean;
 pub mod clean_stale;
 pub mod ctargets;
 pub mod debug;
+pub mod explain;
 pub mod init;
 pub mod install;
 pub mod kill;
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::cmp::Reverse;
 use std::time::Duration;
 
 use anyhow::Context as _;
@@ -15,6 +16,7 @@ use buck2_cli_proto::profile_request::ProfileOpts;
 use buck2_cli_proto::profile_request::Profiler;
 use buck2_cli_proto::target_profile::Action;
 use buck2_cli_proto::BxlProfile;
+use buck2_cli_proto::PackageLoadingProfile;
 use buck2_cli_proto::ProfileRequest;
 use buck2_cli_proto::ProfileResponse;
 use buck2_cli_proto::TargetProfile;
@@ -41,7 +43,12 @@ pub enum ProfileCommand {
     #[clap(about = "Profile analysis")]
     Analysis(BuckProfileOptions),
 
-    #[clap(about = "Profile loading")]
+    /// Profile loading.
+    ///
+    /// The pattern may match several packages (e.g. `//foo/...`), in which case the profile is
+    /// merged across packages, and the evaluation time and allocations of each package are
+    /// printed, most expensive first (see `--sort-by`). Use `--mode heap-summary-allocated` to
+    /// attribute time and allocations to each macro.
     Loading(BuckProfileOptions),
 
     #[clap(about = "Profile BXL script")]
@@ -104,6 +111,27 @@ enum BuckProfileMode {
     Typecheck,
 }
 
+/// How packages are ranked in the loading profile report.
+#[derive(clap::ValueEnum, Dupe, Clone, Copy, Debug)]
+enum PackageSortKey {
+    Time,
+    AllocatedBytes,
+    PeakMemory,
+    Allocations,
+}
+
+impl PackageSortKey {
+    fn sort(self, profiles: &mut [PackageLoadingProfile]) {
+        match self {
+            // Sorted by the server.
+            PackageSortKey::Time => {}
+            PackageSortKey::AllocatedBytes => profiles.sort_by_key(|p| Reverse(p.allocated_bytes)),
+            PackageSortKey::PeakMemory => profiles.sort_by_key(|p| Reverse(p.peak_allocated_bytes)),
+            PackageSortKey::Allocations => profiles.sort_by_key(|p| Reverse(p.allocation_count)),
+        }
+    }
+}
+
 #[derive(Debug, clap::Parser)]
 pub struct BxlProfileOptions {
     #[clap(flatten)]
@@ -131,6 +159,10 @@ pub struct AnalysisLoadProfileOptions {
     /// and output the merged profile.
     #[clap(long, short = 'r')]
     recursive: bool,
+
+    /// In loading profiling, how to rank the packages in the printed report.
+    #[clap(long, value_enum, default_value = "time")]
+    sort_by: PackageSortKey,
 }
 
 #[derive(Debug, clap::Parser)]
@@ -201,6 +233,11 @@ impl StreamingCommand for ProfileSubcommand {
 
         let console_opts = ctx.stdin().console_interaction_stream(self.console_opts());
 
+        let sort_by = match &self.opts {
+            ProfileOptionsType::BuckProfileOptions { opts, .. } => opts.sort_by,
+            ProfileOptionsType::BxlProfileOptions { .. } => PackageSortKey::Time,
+        };
+
         let response = match self.opts {
             ProfileOptionsType::BuckProfileOptions { opts, action } => {
                 let target_opts = TargetProfile {
@@ -250,6 +287,8 @@ impl StreamingCommand for ProfileSubcommand {
         let ProfileResponse {
             elapsed,
             total_retained_bytes,
+            total_allocated_bytes,
+            mut package_profiles,
         } = response;
 
         let elapsed = elapsed
@@ -266,6 +305,33 @@ impl StreamingCommand for ProfileSubcommand {
         )?;
         buck2_client_ctx::println!("Elapsed: {:.3}s", elapsed.as_secs_f64())?;
         buck2_client_ctx::println!("Total retained bytes: {}", total_retained_bytes)?;
+        buck2_client_ctx::println!("Total allocated bytes: {}", total_allocated_bytes)?;
+
+        if package_profiles.len() > 1 {
+            sort_by.sort(&mut package_profiles);
+            buck2_client_ctx::println!()?;
+            buck2_client_ctx::println!(
+                "{:>10}  {:>15}  {:>15}  {:>11}  Package",
+                "Time (s)",
+                "Allocated bytes",
+                "Peak bytes",
+                "Allocations"
+            )?;
+            for profile in package_profiles {
+                let elapsed = profile
+                    .elapsed
+                    .and_then(|d| Duration::try_from(d).ok())
+                    .unwrap_or_default();
+                buck2_client_ctx::println!(
+                    "{:>10.3}  {:>15}  {:>15}  {:>11}  {}",
+                    elapsed.as_secs_f64(),
+                    profile.allocated_bytes,
+                    profile.peak_allocated_bytes,
+                    profile.allocation_count,
+                    profile.package
+                )?;
+            }
+        }
 
         ExitResult::success()
     }
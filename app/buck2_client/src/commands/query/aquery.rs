@@ -36,6 +36,18 @@ use crate::commands::query::common::CommonQueryOptions;
 ///
 /// `buck2 aquery 'kind(run, deps("//java/com/example/app:amazing+more"))' --output-attribute=cmd`
 ///
+/// List the actions of a given category (mnemonic)
+///
+/// `buck2 aquery 'attrfilter(category, cxx_compile, deps("//java/com/example/app:amazing"))'`
+///
+/// Find the actions reading or producing a file, with their inputs and outputs as JSON
+///
+/// `buck2 aquery 'attrregexfilter(outputs, "\.so$", deps("//java/com/example/app:amazing"))' --output-attribute=inputs --output-attribute=outputs --output-format=json`
+///
+/// Print the action graph in DOT format
+///
+/// `buck2 aquery 'deps("//java/com/example/app:amazing")' --output-format=dot`
+///
 /// Dynamic outputs (`ctx.actions.dynamic_output`):
 ///
 /// Currently, aquery interacts poorly with dynamic outputs. It may return incorrect results or otherwise
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use buck2_cli_proto::QueryDotNodeLabel;
+use buck2_cli_proto::QueryDotOptions;
 use buck2_cli_proto::QueryOutputFormat;
 use buck2_client_ctx::query_args::CommonAttributeArgs;
 use buck2_query_parser::placeholder::QUERY_PERCENT_SS_PLACEHOLDER;
@@ -25,6 +27,63 @@ enum QueryOutputFormatArg {
     Dot,
     Json,
     DotCompact,
+    Path,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Dupe,
+    clap::ArgEnum,
+    serde::Serialize,
+    serde::Deserialize
+)]
+#[clap(rename_all = "snake_case")]
+enum DotNodeLabelArg {
+    Label,
+    ConfigurationHash,
+    RuleType,
+}
+
+/// Args controlling the `dot` and `dot_compact` output of the query commands on the target graph
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+pub(crate) struct CommonDotOptions {
+    #[clap(
+        long,
+        ignore_case = true,
+        use_delimiter = true,
+        help = "Comma separated list of fields to show in the label of each node of dot output \
+                (default: label)",
+        value_name = "label|configuration_hash|rule_type",
+        arg_enum
+    )]
+    dot_node_label: Vec<DotNodeLabelArg>,
+
+    #[clap(
+        long,
+        help = "Only include nodes at most this many edges away from the roots of the result \
+                (the nodes no other node of the result depends on) in dot output"
+    )]
+    dot_max_depth: Option<u32>,
+}
+
+impl CommonDotOptions {
+    pub fn to_proto(&self) -> QueryDotOptions {
+        QueryDotOptions {
+            node_labels: self
+                .dot_node_label
+                .iter()
+                .map(|label| {
+                    (match label {
+                        DotNodeLabelArg::Label => QueryDotNodeLabel::TargetLabel,
+                        DotNodeLabelArg::ConfigurationHash => QueryDotNodeLabel::ConfigurationHash,
+                        DotNodeLabelArg::RuleType => QueryDotNodeLabel::RuleType,
+                    }) as i32
+                })
+                .collect(),
+            max_depth: self.dot_max_depth,
+        }
+    }
 }
 
 /// Args common to all the query commands
@@ -53,9 +112,11 @@ pub(crate) struct CommonQueryOptions {
         long_help = "Output format (default: list). \n
            dot -  dot graph format. \n
            dot_compact - compact alternative to dot format. \n
-           json - JSON format.
+           json - JSON format. \n
+           path - each target followed by its edges to the other results and the attributes \
+           which introduced them, e.g. to explain the result of `somepath` or `allpaths`.
          ",
-        value_name = "dot|dot_compact|json",
+        value_name = "dot|dot_compact|json|path",
         arg_enum
     )]
     output_format: Option<QueryOutputFormatArg>,
@@ -87,6 +148,7 @@ impl CommonQueryOptions {
             Some(QueryOutputFormatArg::Json) => QueryOutputFormat::Json,
             Some(QueryOutputFormatArg::Dot) => QueryOutputFormat::Dot,
             Some(QueryOutputFormatArg::DotCompact) => QueryOutputFormat::DotCompact,
+            Some(QueryOutputFormatArg::Path) => QueryOutputFormat::Path,
             None => {
                 if self.json {
                     QueryOutputFormat::Json
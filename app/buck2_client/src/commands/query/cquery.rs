@@ -19,6 +19,7 @@ use buck2_client_ctx::daemon::client::StdoutPartialResultHandler;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::streaming::StreamingCommand;
 
+use crate::commands::query::common::CommonDotOptions;
 use crate::commands::query::common::CommonQueryOptions;
 
 /// Perform queries on the configured target graph.
@@ -46,6 +47,11 @@ use crate::commands::query::common::CommonQueryOptions;
 /// List the deps of a target (special characters in a target will require quotes):
 ///
 /// `buck2 cquery 'deps("//java/com/example/app:amazing+more")'`
+///
+/// Render the first two levels of the deps of a target as a graph, with nodes labelled by
+/// their rule type and configuration hash:
+///
+/// `buck2 cquery 'deps("//java/com/example/app:amazing")' --output-format=dot --dot-node-label=label,rule_type,configuration_hash --dot-max-depth=2 | dot -Tpng -o deps.png`
 #[derive(Debug, clap::Parser)]
 #[clap(name = "cquery")]
 pub struct CqueryCommand {
@@ -55,6 +61,9 @@ pub struct CqueryCommand {
     #[clap(flatten)]
     query_common: CommonQueryOptions,
 
+    #[clap(flatten)]
+    dot_options: CommonDotOptions,
+
     #[clap(
         long,
         use_delimiter = true,
@@ -129,6 +138,7 @@ impl StreamingCommand for CqueryCommand {
                     show_providers: self.show_providers,
                     unstable_output_format,
                     correct_owner,
+                    dot_options: Some(self.dot_options.to_proto()),
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -19,6 +19,7 @@ use buck2_client_ctx::daemon::client::StdoutPartialResultHandler;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::streaming::StreamingCommand;
 
+use crate::commands::query::common::CommonDotOptions;
 use crate::commands::query::common::CommonQueryOptions;
 
 /// Perform queries on the unconfigured target graph.
@@ -58,6 +59,9 @@ pub struct UqueryCommand {
 
     #[clap(flatten)]
     query_common: CommonQueryOptions,
+
+    #[clap(flatten)]
+    dot_options: CommonDotOptions,
 }
 
 #[async_trait]
@@ -87,6 +91,7 @@ impl StreamingCommand for UqueryCommand {
                     query_args,
                     context: Some(context),
                     output_attributes,
+                    dot_options: Some(self.dot_options.to_proto()),
                     unstable_output_format,
                 },
                 ctx.stdin()
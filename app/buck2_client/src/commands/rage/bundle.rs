@@ -0,0 +1,221 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! A local alternative to uploading rage reports: everything is collected into a gzipped
+//! tarball which can be attached to a bug report.
+
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use buck2_client_ctx::daemon::client::connect::BootstrapBuckdClient;
+use buck2_client_ctx::subscribers::event_log::read::EventLogPathBuf;
+use buck2_common::result::SharedResult;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::abs_path::AbsPath;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::commands::rage::build_info;
+use crate::commands::rage::dice;
+use crate::commands::rage::source_control;
+use crate::commands::rage::system_info;
+use crate::commands::rage::RageSection;
+
+/// Config keys containing any of these are assumed to hold credentials.
+const SENSITIVE_CONFIG_KEYS: &[&str] = &["token", "secret", "password", "credential", "auth"];
+
+const REDACTED: &str = "<redacted>";
+
+/// Buckconfig files at the project root which are added to the bundle, after redaction.
+const BUCKCONFIG_FILES: &[&str] = &[".buckconfig", ".buckconfig.local"];
+
+pub(crate) struct RageBundle {
+    tar: tar::Builder<GzEncoder<File>>,
+}
+
+impl RageBundle {
+    pub(crate) fn create(path: &AbsPath) -> anyhow::Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create rage bundle `{}`", path.display()))?;
+        Ok(Self {
+            tar: tar::Builder::new(GzEncoder::new(file, Compression::default())),
+        })
+    }
+
+    pub(crate) fn add_text(&mut self, name: &str, content: &str) -> anyhow::Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)?
+                .as_secs(),
+        );
+        header.set_cksum();
+        self.tar
+            .append_data(&mut header, name, content.as_bytes())
+            .with_context(|| format!("Failed to add `{}` to rage bundle", name))
+    }
+
+    pub(crate) fn add_file(&mut self, name: &str, path: &Path) -> anyhow::Result<()> {
+        self.tar
+            .append_path_with_name(path, name)
+            .with_context(|| format!("Failed to add `{}` to rage bundle", path.display()))
+    }
+
+    pub(crate) fn add_dir(&mut self, name: &str, path: &Path) -> anyhow::Result<()> {
+        self.tar
+            .append_dir_all(name, path)
+            .with_context(|| format!("Failed to add `{}` to rage bundle", path.display()))
+    }
+
+    pub(crate) fn finish(self) -> anyhow::Result<()> {
+        self.tar
+            .into_inner()
+            .context("Failed to write rage bundle")?
+            .finish()
+            .context("Failed to write rage bundle")?;
+        Ok(())
+    }
+}
+
+/// Replace the values of buckconfig keys which look like they hold credentials.
+pub(crate) fn redact_config(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    for line in content.lines() {
+        match line.split_once('=') {
+            Some((key, _))
+                if SENSITIVE_CONFIG_KEYS
+                    .iter()
+                    .any(|s| key.to_lowercase().contains(s)) =>
+            {
+                out.push_str(key.trim_end());
+                out.push_str(" = ");
+                out.push_str(REDACTED);
+            }
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Replace the user's home directory in free-form text, which otherwise leaks usernames and
+/// local directory layout.
+pub(crate) fn redact_text(content: &str, home: Option<&str>) -> String {
+    match home {
+        Some(home) if !home.is_empty() => content.replace(home, "~"),
+        _ => content.to_owned(),
+    }
+}
+
+/// Write a rage bundle containing the report, the selected invocation's event log, the daemon
+/// stderr, a DICE dump if a daemon is running, and the project's buckconfigs.
+pub(crate) async fn write_bundle(
+    output: &AbsPath,
+    timeout: Duration,
+    buckd: SharedResult<BootstrapBuckdClient>,
+    selected_invocation: Option<&EventLogPathBuf>,
+    daemon_stderr: &AbsNormPath,
+    dice_dump_dir: AbsNormPathBuf,
+    project_root: &AbsNormPath,
+) -> anyhow::Result<()> {
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().into_owned());
+    let home = home.as_deref();
+    let mut bundle = RageBundle::create(output)?;
+    let mut files = Vec::new();
+
+    if let Some(invocation) = selected_invocation {
+        let name = format!("event_log{}", invocation.extension());
+        bundle.add_file(&name, invocation.path().as_path())?;
+        files.push(name);
+    }
+
+    if let Ok(stderr) = std::fs::read_to_string(daemon_stderr) {
+        bundle.add_text("buckd.stderr", &redact_text(&stderr, home))?;
+        files.push("buckd.stderr".to_owned());
+    }
+
+    for config in BUCKCONFIG_FILES {
+        if let Ok(content) = std::fs::read_to_string(project_root.as_path().join(config)) {
+            let name = format!("config/{}", config);
+            bundle.add_text(&name, &redact_config(&content))?;
+            files.push(name);
+        }
+    }
+
+    let dice_dump = {
+        let title = "DICE dump".to_owned();
+        match buckd {
+            Ok(buckd) => {
+                RageSection::get(title, timeout, || {
+                    dice::bundle_dice_dump(buckd, dice_dump_dir, &mut bundle)
+                })
+                .await
+            }
+            Err(_) => RageSection::get_skipped(title).await,
+        }
+    };
+
+    let system_info = RageSection::get("System info".to_owned(), timeout, system_info::get);
+    let source_control = RageSection::get(
+        "Source control".to_owned(),
+        timeout,
+        source_control::get_info,
+    );
+    let build_info = {
+        let title = "Associated invocation info".to_owned();
+        match selected_invocation {
+            None => RageSection::get_skipped(title),
+            Some(invocation) => RageSection::get(title, timeout, || build_info::get(invocation)),
+        }
+    };
+    let (system_info, source_control, build_info) =
+        tokio::join!(system_info, source_control, build_info);
+
+    let report = [
+        system_info.to_string(),
+        source_control.to_string(),
+        build_info.to_string(),
+        dice_dump.to_string(),
+        format!("Bundled files\n{}\n{}\n", "-".repeat(30), files.join("\n")),
+    ]
+    .join("");
+    bundle.add_text("report.txt", &redact_text(&report, home))?;
+    bundle.finish()?;
+
+    buck2_client_ctx::eprintln!("Rage bundle written to {}", output.display())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_config() {
+        assert_eq!(
+            "[cache]\nhttp_auth_token = <redacted>\nmode = readwrite\n",
+            redact_config("[cache]\nhttp_auth_token = abc123\nmode = readwrite\n")
+        );
+    }
+
+    #[test]
+    fn test_redact_text() {
+        assert_eq!(
+            "~/repo/BUCK",
+            redact_text("/home/alice/repo/BUCK", Some("/home/alice"))
+        );
+        assert_eq!("/repo/BUCK", redact_text("/repo/BUCK", None));
+    }
+}
@@ -21,6 +21,8 @@ use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::abs_path::AbsPathBuf;
 use buck2_util::process::background_command;
 
+use crate::commands::rage::bundle::RageBundle;
+
 pub async fn upload_dice_dump(
     buckd: BootstrapBuckdClient,
     buck_out_dice: AbsNormPathBuf,
@@ -36,6 +38,20 @@ pub async fn upload_dice_dump(
     Ok(format!("buck2_rage_dumps/flat/{}", manifold_filename))
 }
 
+/// Generate a DICE dump and add it to the `dice` directory of a local rage bundle.
+pub async fn bundle_dice_dump(
+    buckd: BootstrapBuckdClient,
+    buck_out_dice: AbsNormPathBuf,
+    bundle: &mut RageBundle,
+) -> anyhow::Result<String> {
+    let buckd = buckd.with_subscribers(Default::default());
+    let this_dump_folder_name = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let dump = DiceDump::new(buck_out_dice, &this_dump_folder_name);
+    dump.generate(buckd).await?;
+    bundle.add_dir("dice", &dump.dump_folder)?;
+    Ok("dice/".to_owned())
+}
+
 struct DiceDump {
     buck_out_dice: AbsNormPathBuf,
     dump_folder: AbsPathBuf,
@@ -53,9 +69,25 @@ impl DiceDump {
 
     async fn upload(
         &self,
-        mut buckd: BuckdClientConnector<'_>,
+        buckd: BuckdClientConnector<'_>,
         manifold_filename: &str,
     ) -> anyhow::Result<()> {
+        self.generate(buckd).await?;
+
+        // create DICE dump name using the old command being rage on and the trace id of this rage command.
+
+        buck2_client_ctx::eprintln!(
+            "Compressed DICE dump being uploaded to manifold as {}...",
+            &manifold_filename
+        )?;
+        upload_to_manifold(&self.dump_folder, manifold_filename)
+            .await
+            .with_context(|| "Failed during manifold upload!")?;
+
+        Ok(())
+    }
+
+    async fn generate(&self, mut buckd: BuckdClientConnector<'_>) -> anyhow::Result<()> {
         buck2_client_ctx::eprintln!("Generating Buck2 DICE dump...")?;
         create_dir_all(&self.buck_out_dice).with_context(|| {
             format!(
@@ -78,16 +110,6 @@ impl DiceDump {
                 )
             })?;
 
-        // create DICE dump name using the old command being rage on and the trace id of this rage command.
-
-        buck2_client_ctx::eprintln!(
-            "Compressed DICE dump being uploaded to manifold as {}...",
-            &manifold_filename
-        )?;
-        upload_to_manifold(&self.dump_folder, manifold_filename)
-            .await
-            .with_context(|| "Failed during manifold upload!")?;
-
         Ok(())
     }
 }
@@ -8,6 +8,7 @@
  */
 
 mod build_info;
+mod bundle;
 mod dice;
 mod materializer;
 mod source_control;
@@ -29,6 +30,7 @@ use buck2_client_ctx::daemon::client::connect::BootstrapBuckdClient;
 use buck2_client_ctx::daemon::client::connect::BuckdConnectConstraints;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::manifold;
+use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::stdin::Stdin;
 use buck2_client_ctx::subscribers::event_log::file_names::do_find_log_by_trace_id;
 use buck2_client_ctx::subscribers::event_log::file_names::get_local_logs;
@@ -204,6 +206,11 @@ pub struct RageCommand {
     /// Obtain thread dump for debugging
     #[clap(long)]
     thread_dump: bool,
+    /// Instead of uploading the report, write it to a gzipped tarball at this path, along with
+    /// the event log, daemon stderr, DICE dump and buckconfigs. Buckconfig values which look
+    /// like credentials and the home directory are redacted.
+    #[clap(long, value_name = "PATH")]
+    output: Option<PathArg>,
 }
 
 impl RageCommand {
@@ -216,6 +223,7 @@ impl RageCommand {
             let stderr_path = paths.daemon_dir()?.buckd_stderr();
             let logdir = paths.log_dir();
             let dice_dump_dir = paths.dice_dump_dir();
+            let project_root = paths.project_root().root().to_owned();
 
             let client_ctx = ctx.empty_client_context()?;
 
@@ -235,6 +243,20 @@ impl RageCommand {
                 .shared_error();
 
             let selected_invocation = maybe_select_invocation(ctx.stdin(), &logdir, &self).await?;
+            if let Some(output) = &self.output {
+                bundle::write_bundle(
+                    &output.resolve(&ctx.working_dir),
+                    timeout,
+                    buckd,
+                    selected_invocation.as_ref(),
+                    &stderr_path,
+                    dice_dump_dir,
+                    &project_root,
+                )
+                .await?;
+                return ExitResult::success();
+            }
+
             let invocation_id = get_trace_id(&selected_invocation).await?;
             if let Some(ref invocation_id) = invocation_id {
                 manifold_id = format!("{}_{}", invocation_id, manifold_id);
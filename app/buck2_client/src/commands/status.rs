@@ -140,6 +140,10 @@ fn process_status(status: StatusResponse) -> anyhow::Result<serde_json::Value> {
         "isolation_dir": status.isolation_dir,
         "forkserver_pid": serde_json::to_value(status.forkserver_pid)?,
         "supports_vpnless": status.supports_vpnless.unwrap_or_default(),
+        "rss_bytes": status.rss_bytes,
+        "dice": serde_json::to_value(status.dice)?,
+        "active_commands": serde_json::to_value(status.active_commands)?,
+        "file_watcher": serde_json::to_value(status.file_watcher)?,
     }))
 }
 
@@ -54,6 +54,10 @@ pub struct SubscribeCommand {
     #[clap(long)]
     active_commands: bool,
 
+    /// Whether to request notifications of the file changes reported by the file watcher.
+    #[clap(long)]
+    file_changes: bool,
+
     /// Whether to get output as JSON. The JSON format is deemed unstable so this should only be
     /// used for debugging.
     #[clap(long)]
@@ -101,15 +105,18 @@ impl StreamingCommand for SubscribeCommand {
             ok: true,
         };
 
-        let stream = if self.active_commands {
-            futures::stream::once(futures::future::ready(SubscriptionRequest {
+        let mut initial_requests = Vec::new();
+        if self.active_commands {
+            initial_requests.push(SubscriptionRequest {
                 request: Some(buck2_subscription_proto::SubscribeToActiveCommands {}.into()),
-            }))
-            .chain(stream)
-            .left_stream()
-        } else {
-            stream.right_stream()
-        };
+            });
+        }
+        if self.file_changes {
+            initial_requests.push(SubscriptionRequest {
+                request: Some(buck2_subscription_proto::SubscribeToFileChanges {}.into()),
+            });
+        }
+        let stream = futures::stream::iter(initial_requests).chain(stream);
 
         let stream = stream.map(|request| buck2_cli_proto::SubscriptionRequestWrapper {
             request: Some(request),
@@ -53,8 +53,8 @@ enum TargetHashGraphType {
 }
 
 // Use non-camel case so the possible values match buck1's
-/// Possible values for the --target-hash-function arg. We don't actually
-/// honor the specific algorithms, we use them as a hint to pick "fast" or "strong".
+/// Possible values for the --target-hash-function arg. `murmur_hash3` is not supported and
+/// is treated as a hint to pick "fast".
 #[allow(non_camel_case_types)]
 #[derive(Debug, clap::ArgEnum, Clone, Dupe)]
 enum TargetHashFunction {
@@ -75,7 +75,7 @@ pub struct TargetsCommand {
     #[clap(long)]
     json: bool,
 
-    /// Print targets as JSON-lines
+    /// Print targets as JSON-lines (one JSON object per line, see also `--streaming`)
     #[clap(long, conflicts_with = "json")]
     json_lines: bool,
 
@@ -115,10 +115,12 @@ pub struct TargetsCommand {
     #[clap(long, multiple_values = true, conflicts_with = "streaming")]
     target_hash_modified_paths: Vec<PathArg>,
 
-    /// Selects either the "fast" or the "strong" target hash function to be used for computing target hashes.
+    /// Selects the target hash function to be used for computing target hashes.
     /// While we don't specify the exact algorithm, the "strong" algorithm should be a reasonable cryptographic
-    /// hash (ex. blake3) while the "fast" function will likely be a non-crypto hash. Both functions are
-    /// guaranteed to be deterministic and to have the same value across different platforms/architectures.
+    /// hash (ex. blake3) while the "fast" function will likely be a non-crypto hash. Target hashes are 128 bits,
+    /// so "sha1" and "sha256" are those digests truncated to their first 128 bits (32 hex digits), and won't
+    /// match the full digests. All functions are guaranteed to be deterministic and to have the same value
+    /// across different platforms/architectures.
     #[clap(long, ignore_case = true, default_value = "fast", arg_enum)]
     target_hash_function: TargetHashFunction,
 
@@ -150,6 +152,10 @@ pub struct TargetsCommand {
     /// Write output as soon as it is available. The order of the output items
     /// is non-deterministic and if multiple patterns cover the same target, may
     /// have duplicates.
+    ///
+    /// Combined with `--json-lines`, each target is written as one line of JSON as soon as its
+    /// package has been evaluated, so the output of large target universes can be piped into
+    /// other tools without buffering the whole result.
     #[clap(long)]
     streaming: bool,
 
@@ -213,21 +219,17 @@ impl StreamingCommand for TargetsCommand {
         matches: &clap::ArgMatches,
         ctx: &mut ClientCommandContext<'_>,
     ) -> ExitResult {
-        let target_hash_use_fast_hash = match self.target_hash_function {
-            TargetHashFunction::Sha1 | TargetHashFunction::Sha256 => {
-                buck2_client_ctx::eprintln!(
-                    "buck2 only supports \"fast\" and \"strong\" target hash functions. Using the \"strong\" hash."
-                )?;
-                false
-            }
+        let target_hash_function = match self.target_hash_function {
+            TargetHashFunction::Sha1 => targets_request::TargetHashFunction::Sha1,
+            TargetHashFunction::Sha256 => targets_request::TargetHashFunction::Sha256,
             TargetHashFunction::Murmur_Hash3 => {
                 buck2_client_ctx::eprintln!(
-                    "buck2 only supports \"fast\" and \"strong\" target hash functions. Using the \"fast\" hash."
+                    "buck2 does not support the \"murmur_hash3\" target hash function. Using the \"fast\" hash."
                 )?;
-                true
+                targets_request::TargetHashFunction::Fast
             }
-            TargetHashFunction::Fast => true,
-            TargetHashFunction::Strong => false,
+            TargetHashFunction::Fast => targets_request::TargetHashFunction::Fast,
+            TargetHashFunction::Strong => targets_request::TargetHashFunction::Strong,
         };
 
         let output_attributes = self.attributes.get()?;
@@ -278,7 +280,7 @@ impl StreamingCommand for TargetsCommand {
                         }
                     },
                     target_hash_modified_paths,
-                    target_hash_use_fast_hash,
+                    target_hash_function: target_hash_function as i32,
                     target_hash_graph_type,
                     include_default_attributes: self.include_defaults,
                     target_hash_recursive: self.target_hash_recursive,
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::str::FromStr;
+
 use anyhow::Context;
 use async_trait::async_trait;
 use buck2_cli_proto::CounterWithExamples;
@@ -26,6 +28,8 @@ use buck2_client_ctx::output_destination_arg::OutputDestinationArg;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::stdio::eprint_line;
 use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_client_ctx::subscribers::junit::JunitWriter;
+use buck2_client_ctx::subscribers::subscriber::EventSubscriber;
 use buck2_client_ctx::subscribers::superconsole::test::span_from_build_failure_count;
 use buck2_client_ctx::subscribers::superconsole::test::TestCounterColumn;
 use buck2_core::fs::fs_util;
@@ -65,6 +69,29 @@ fn print_error_counter(
     }
     Ok(())
 }
+#[derive(Debug, thiserror::Error)]
+enum TestOutputArgError {
+    #[error("Invalid test output `{0}`, expected `junit:<path>`")]
+    InvalidFormat(String),
+}
+
+/// A machine-readable report of the test results, written when the command finishes.
+#[derive(Debug)]
+enum TestOutputArg {
+    Junit(PathArg),
+}
+
+impl FromStr for TestOutputArg {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.split_once(':') {
+            Some(("junit", path)) if !path.is_empty() => Ok(TestOutputArg::Junit(path.parse()?)),
+            _ => Err(TestOutputArgError::InvalidFormat(s.to_owned()).into()),
+        }
+    }
+}
+
 #[derive(Debug, clap::Parser)]
 #[clap(name = "test", about = "Build and test the specified targets")]
 pub struct TestCommand {
@@ -123,6 +150,13 @@ If include patterns are present, regardless of whether exclude patterns are pres
     #[clap(long, group = "re_options", alias = "unstable-force-tests-on-re")]
     unstable_allow_all_tests_on_re: bool,
 
+    /// Writes a report of the test results when the command finishes.
+    ///
+    /// --output=junit:FILEPATH writes JUnit XML, with a test suite per test target and a test
+    /// case per test.
+    #[clap(long, value_name = "FORMAT:PATH")]
+    output: Option<TestOutputArg>,
+
     #[clap(name = "TARGET_PATTERNS", help = "Patterns to test")]
     patterns: Vec<String>,
 
@@ -298,4 +332,13 @@ impl StreamingCommand for TestCommand {
     fn common_opts(&self) -> &CommonBuildConfigurationOptions {
         &self.common_opts.config_opts
     }
+
+    fn extra_subscribers(&self, ctx: &ClientCommandContext<'_>) -> Vec<Box<dyn EventSubscriber>> {
+        match &self.output {
+            Some(TestOutputArg::Junit(path)) => {
+                vec![Box::new(JunitWriter::new(path.resolve(&ctx.working_dir)))]
+            }
+            None => vec![],
+        }
+    }
 }
@@ -124,6 +124,12 @@ pub struct CommonDaemonCommandOptions {
     #[clap(long, value_name = "PATH")]
     pub(crate) write_build_id: Option<PathArg>,
 
+    /// Write every action which failed during the command (as JSON) to this path, including the
+    /// target that owns it, the category of the error and the end of its stderr. Use with
+    /// `--keep-going` to collect all the failures of a build.
+    #[clap(long, value_name = "PATH")]
+    pub(crate) write_failure_report: Option<PathArg>,
+
     /// Write the invocation record (as JSON) to this path. No guarantees whatsoever are made
     /// regarding the stability of the format.
     #[clap(long, value_name = "PATH")]
@@ -136,6 +142,7 @@ impl CommonDaemonCommandOptions {
             event_log: None,
             no_event_log: false,
             write_build_id: None,
+            write_failure_report: None,
             unstable_write_invocation_record: None,
         };
         &DEFAULT
@@ -40,6 +40,11 @@ pub struct CommonAttributeArgs {
     /// When using in automation, please specify the regular expression to match the attribute
     /// precisely, for example `--output-attribute '^headers$'` to make it easier to track
     /// which special attributes are used.
+    ///
+    /// An attribute name followed by `.key`, `[index]`, `.*` or `[*]` selectors is a path
+    /// expression instead, which outputs only a part of the attribute under the expression as the
+    /// key, for example `--output-attribute 'deps[*]'` or `--output-attribute 'platform_srcs.*'`.
+    /// `.*` and `[*]` select all values of a dict or all elements of a list.
     #[clap(
          short = 'a',
          long,
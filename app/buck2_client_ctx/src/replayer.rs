@@ -24,6 +24,12 @@ use crate::stream_value::StreamValue;
 use crate::subscribers::event_log::read::EventLogPathBuf;
 use crate::subscribers::event_log::utils::Invocation;
 
+#[derive(Debug, thiserror::Error)]
+enum ReplayerError {
+    #[error("Playback speed must be a positive number, got `{0}`")]
+    InvalidSpeed(f64),
+}
+
 #[pin_project]
 struct Pending {
     #[pin]
@@ -47,6 +53,13 @@ impl Replayer {
         speed: Option<f64>,
         preload: bool,
     ) -> anyhow::Result<(Self, Invocation)> {
+        if let Some(speed) = speed {
+            // Event delays are divided by the speed, which must yield a valid duration.
+            if !(speed.is_finite() && speed > 0.0) {
+                return Err(ReplayerError::InvalidSpeed(speed).into());
+            }
+        }
+
         let (invocation, events) = log_path.unpack_stream().await?;
 
         let events = if preload {
@@ -33,6 +33,7 @@ use crate::path_arg::PathArg;
 use crate::subscribers::get::get_console_with_root;
 use crate::subscribers::get::try_get_build_id_writer;
 use crate::subscribers::get::try_get_event_log_subscriber;
+use crate::subscribers::get::try_get_failure_report_writer;
 use crate::subscribers::get::try_get_re_log_subscriber;
 use crate::subscribers::recorder::try_get_invocation_recorder;
 use crate::subscribers::subscriber::EventSubscriber;
@@ -76,6 +77,9 @@ fn default_subscribers<'a, T: StreamingCommand>(
     if let Some(build_id_writer) = try_get_build_id_writer(cmd.event_log_opts(), ctx)? {
         subscribers.push(build_id_writer)
     }
+    if let Some(failure_report_writer) = try_get_failure_report_writer(cmd.event_log_opts(), ctx)? {
+        subscribers.push(failure_report_writer)
+    }
     let recorder = try_get_invocation_recorder(
         ctx,
         cmd.event_log_opts(),
@@ -85,7 +89,7 @@ fn default_subscribers<'a, T: StreamingCommand>(
     )?;
     subscribers.push(recorder);
 
-    subscribers.extend(cmd.extra_subscribers());
+    subscribers.extend(cmd.extra_subscribers(ctx));
     Ok(subscribers)
 }
 
@@ -123,7 +127,7 @@ pub trait StreamingCommand: Sized + Send + Sync {
 
     fn common_opts(&self) -> &CommonBuildConfigurationOptions;
 
-    fn extra_subscribers(&self) -> Vec<Box<dyn EventSubscriber>> {
+    fn extra_subscribers(&self, _ctx: &ClientCommandContext<'_>) -> Vec<Box<dyn EventSubscriber>> {
         vec![]
     }
 
@@ -62,6 +62,12 @@ pub struct ActionExecutionEndSimple {
     duration_millis: u64,
     output_size: u64,
     input_materialization_duration_millis: u64,
+    #[serde(default)]
+    queue_duration_millis: u64,
+    #[serde(default)]
+    execution_duration_millis: u64,
+    #[serde(default)]
+    output_upload_duration_millis: u64,
     execution_kind: ActionExecutionKind,
     owner: String,
 }
@@ -80,6 +86,14 @@ pub fn try_get_user_event_for_read(
     }
 }
 
+/// Durations which events from older versions may not have are zero.
+fn duration_millis(duration: &Option<impl ProstDurationExt>) -> anyhow::Result<u64> {
+    Ok(match duration {
+        Some(duration) => duration.try_into_duration()?.as_millis() as u64,
+        None => 0,
+    })
+}
+
 pub(crate) fn try_get_user_event(buck_event: &BuckEvent) -> anyhow::Result<Option<UserEvent>> {
     let timestamp = buck_event
         .timestamp
@@ -122,8 +136,11 @@ pub(crate) fn try_get_user_event(buck_event: &BuckEvent) -> anyhow::Result<Optio
                 ))? {
                 buck2_data::span_end_event::Data::ActionExecution(action_execution) => {
                     let mut input_materialization_duration_millis = 0;
+                    let mut queue_duration_millis = 0;
+                    let mut execution_duration_millis = 0;
+                    let mut output_upload_duration_millis = 0;
 
-                    // Take the last command report's input materialization duration
+                    // Take the last command report's durations
                     if let Some(command) = action_execution.commands.last() {
                         if let Some(details) = &command.details {
                             input_materialization_duration_millis = details
@@ -135,6 +152,11 @@ pub(crate) fn try_get_user_event(buck_event: &BuckEvent) -> anyhow::Result<Optio
                                 .try_into_duration()?
                                 .as_millis()
                                 as u64;
+                            queue_duration_millis = duration_millis(&details.queue_duration)?;
+                            execution_duration_millis =
+                                duration_millis(&details.execution_duration)?;
+                            output_upload_duration_millis =
+                                duration_millis(&details.output_upload_duration)?;
                         }
                     }
 
@@ -159,6 +181,9 @@ pub(crate) fn try_get_user_event(buck_event: &BuckEvent) -> anyhow::Result<Optio
                         duration_millis,
                         output_size: action_execution.output_size,
                         input_materialization_duration_millis,
+                        queue_duration_millis,
+                        execution_duration_millis,
+                        output_upload_duration_millis,
                         execution_kind: action_execution.execution_kind(),
                         owner,
                     };
@@ -0,0 +1,173 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use buck2_core::fs::paths::abs_path::AbsPathBuf;
+use buck2_event_observer::display::display_action_error;
+use buck2_event_observer::display::display_action_owner;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_events::BuckEvent;
+use serde::Serialize;
+
+use crate::subscribers::subscriber::EventSubscriber;
+
+/// Number of trailing lines of stderr kept for each failure.
+const STDERR_EXCERPT_LINES: usize = 50;
+
+#[derive(Serialize)]
+struct FailureReport {
+    trace_id: String,
+    failures: Vec<ActionFailure>,
+}
+
+#[derive(Serialize)]
+struct ActionFailure {
+    /// The target which owns the action.
+    target: Option<String>,
+    /// The action, as displayed on the console.
+    action: String,
+    category: FailureCategory,
+    reason: String,
+    /// The last lines of the stderr of the command which failed.
+    stderr: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum FailureCategory {
+    /// The command exited with a non-zero exit code.
+    CommandFailed,
+    /// The command didn't produce the outputs the action declared.
+    MissingOutputs,
+    Timeout,
+    Cancelled,
+    /// The command could not be run, or buck2 hit an error running the action.
+    InternalError,
+}
+
+/// Collects every failed action of the command, and writes them as JSON to a file when the
+/// command finishes. Combined with `--keep-going`, this reports all the failures of a build
+/// rather than just the first one.
+pub struct FailureReportWriter {
+    path: AbsPathBuf,
+    trace_id: Option<String>,
+    failures: Vec<ActionFailure>,
+}
+
+impl FailureReportWriter {
+    pub fn new(path: AbsPathBuf) -> Self {
+        Self {
+            path,
+            trace_id: None,
+            failures: Vec::new(),
+        }
+    }
+
+    fn handle_action_execution_end(
+        &mut self,
+        action: &buck2_data::ActionExecutionEnd,
+    ) -> anyhow::Result<()> {
+        use buck2_data::action_execution_end::Error;
+        use buck2_data::command_execution::Status;
+
+        let error = match &action.error {
+            Some(error) => error,
+            None => return Ok(()),
+        };
+
+        let display = display_action_error(action, error, TargetDisplayOptions::for_log())?;
+        let target = action
+            .key
+            .as_ref()
+            .and_then(|key| key.owner.as_ref())
+            .map(|owner| display_action_owner(owner, TargetDisplayOptions::for_log()))
+            .transpose()?;
+        let category = match error {
+            Error::MissingOutputs(..) => FailureCategory::MissingOutputs,
+            Error::Unknown(..) => FailureCategory::InternalError,
+            Error::CommandExecutionError(..) => {
+                match action.commands.last().and_then(|c| c.status.as_ref()) {
+                    Some(Status::Failure(..)) => FailureCategory::CommandFailed,
+                    Some(Status::Timeout(..)) => FailureCategory::Timeout,
+                    Some(Status::Cancelled(..)) => FailureCategory::Cancelled,
+                    Some(Status::Success(..)) | Some(Status::Error(..)) | None => {
+                        FailureCategory::InternalError
+                    }
+                }
+            }
+        };
+        let stderr = display
+            .command
+            .as_ref()
+            .map_or("", |command| command.stderr.as_str());
+
+        self.failures.push(ActionFailure {
+            target,
+            action: display.action_id,
+            category,
+            reason: display.reason,
+            stderr: stderr_excerpt(stderr),
+        });
+        Ok(())
+    }
+}
+
+fn stderr_excerpt(stderr: &str) -> String {
+    let lines: Vec<&str> = stderr.lines().collect();
+    let start = lines.len().saturating_sub(STDERR_EXCERPT_LINES);
+    lines[start..].join("\n")
+}
+
+#[async_trait]
+impl EventSubscriber for FailureReportWriter {
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> anyhow::Result<()> {
+        for event in events {
+            if self.trace_id.is_none() {
+                self.trace_id = Some(event.trace_id()?.to_string());
+            }
+            if let buck2_data::buck_event::Data::SpanEnd(end) = event.data() {
+                if let Some(buck2_data::span_end_event::Data::ActionExecution(action)) = &end.data {
+                    self.handle_action_execution_end(action)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn exit(&mut self) -> anyhow::Result<()> {
+        let report = FailureReport {
+            trace_id: self.trace_id.take().unwrap_or_default(),
+            failures: std::mem::take(&mut self.failures),
+        };
+        tokio::fs::write(&self.path, serde_json::to_string_pretty(&report)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stderr_excerpt() {
+        assert_eq!("", stderr_excerpt(""));
+        assert_eq!("a\nb", stderr_excerpt("a\nb\n"));
+
+        let long = (0..100)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let excerpt = stderr_excerpt(&long);
+        assert_eq!(STDERR_EXCERPT_LINES, excerpt.lines().count());
+        assert!(excerpt.starts_with("50\n"));
+        assert!(excerpt.ends_with("\n99"));
+    }
+}
@@ -23,6 +23,7 @@ use crate::common::ConsoleType;
 use crate::path_arg::PathArg;
 use crate::subscribers::build_id_writer::BuildIdWriter;
 use crate::subscribers::event_log::subscriber::EventLog;
+use crate::subscribers::failure_report::FailureReportWriter;
 use crate::subscribers::re_log::ReLog;
 use crate::subscribers::simpleconsole::SimpleConsole;
 use crate::subscribers::subscriber::EventSubscriber;
@@ -155,3 +156,16 @@ pub(crate) fn try_get_build_id_writer<'a>(
         Ok(None)
     }
 }
+
+pub(crate) fn try_get_failure_report_writer<'a>(
+    opts: &CommonDaemonCommandOptions,
+    ctx: &ClientCommandContext<'a>,
+) -> anyhow::Result<Option<Box<dyn EventSubscriber + 'a>>> {
+    if let Some(file_loc) = opts.write_failure_report.as_ref() {
+        Ok(Some(Box::new(FailureReportWriter::new(
+            file_loc.resolve(&ctx.working_dir),
+        ))))
+    } else {
+        Ok(None)
+    }
+}
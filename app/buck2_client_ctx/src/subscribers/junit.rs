@@ -0,0 +1,270 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use buck2_core::fs::paths::abs_path::AbsPathBuf;
+use buck2_data::TestStatus;
+use buck2_event_observer::display::display_configured_target_label;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_events::BuckEvent;
+
+use crate::subscribers::subscriber::EventSubscriber;
+
+enum JunitOutcome {
+    Pass,
+    /// The test failed, timed out, or the test runner hit an error running it.
+    Failure {
+        kind: &'static str,
+        message: String,
+    },
+    Skipped {
+        message: String,
+    },
+}
+
+struct JunitTestCase {
+    name: String,
+    duration: Duration,
+    outcome: JunitOutcome,
+    details: String,
+}
+
+/// Collects the test results of `buck2 test`, and writes them as JUnit XML to a file when the
+/// command finishes. Each test target becomes a `<testsuite>`, each test a `<testcase>`.
+pub struct JunitWriter {
+    path: AbsPathBuf,
+    suites: BTreeMap<String, Vec<JunitTestCase>>,
+}
+
+impl JunitWriter {
+    pub fn new(path: AbsPathBuf) -> Self {
+        Self {
+            path,
+            suites: BTreeMap::new(),
+        }
+    }
+
+    fn handle_test_result(&mut self, result: &buck2_data::TestResult) -> anyhow::Result<()> {
+        let message = result
+            .msg
+            .as_ref()
+            .map_or_else(String::new, |msg| msg.msg.clone());
+        let outcome = match TestStatus::from_i32(result.status) {
+            Some(TestStatus::Pass) | Some(TestStatus::ListingSuccess) => JunitOutcome::Pass,
+            // Earlier attempts of a retried test, the final attempt is reported separately.
+            Some(TestStatus::Rerun) => return Ok(()),
+            Some(TestStatus::Skip) | Some(TestStatus::Omitted) => JunitOutcome::Skipped { message },
+            Some(TestStatus::Fail) | Some(TestStatus::ListingFailed) => JunitOutcome::Failure {
+                kind: "failure",
+                message,
+            },
+            Some(TestStatus::Timeout) => JunitOutcome::Failure {
+                kind: "timeout",
+                message,
+            },
+            Some(TestStatus::Fatal)
+            | Some(TestStatus::Unknown)
+            | Some(TestStatus::NotSetTestStatus)
+            | None => JunitOutcome::Failure {
+                kind: "error",
+                message,
+            },
+        };
+        let suite = match &result.target_label {
+            Some(label) => display_configured_target_label(label, TargetDisplayOptions::for_log())?,
+            None => String::new(),
+        };
+        let duration = result
+            .duration
+            .clone()
+            .and_then(|d| Duration::try_from(d).ok())
+            .unwrap_or_default();
+
+        self.suites.entry(suite).or_default().push(JunitTestCase {
+            name: result.name.clone(),
+            duration,
+            outcome,
+            details: result.details.clone(),
+        });
+        Ok(())
+    }
+}
+
+/// Escape text for use in XML attribute values and character data.
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0, even escaped.
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn render_junit(suites: &BTreeMap<String, Vec<JunitTestCase>>) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<testsuites>\n");
+    for (suite, cases) in suites {
+        let failures = cases
+            .iter()
+            .filter(
+                |c| matches!(&c.outcome, JunitOutcome::Failure { kind, .. } if *kind != "error"),
+            )
+            .count();
+        let errors = cases
+            .iter()
+            .filter(
+                |c| matches!(&c.outcome, JunitOutcome::Failure { kind, .. } if *kind == "error"),
+            )
+            .count();
+        let skipped = cases
+            .iter()
+            .filter(|c| matches!(c.outcome, JunitOutcome::Skipped { .. }))
+            .count();
+        let time: Duration = cases.iter().map(|c| c.duration).sum();
+        writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            xml_escape(suite),
+            cases.len(),
+            failures,
+            errors,
+            skipped,
+            time.as_secs_f64(),
+        )
+        .unwrap();
+        for case in cases {
+            write!(
+                out,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                xml_escape(&case.name),
+                xml_escape(suite),
+                case.duration.as_secs_f64(),
+            )
+            .unwrap();
+            match &case.outcome {
+                JunitOutcome::Pass => out.push_str(">\n"),
+                JunitOutcome::Failure { kind, message } => {
+                    // JUnit distinguishes assertion failures from errors, timeouts are failures.
+                    let element = if *kind == "error" { "error" } else { "failure" };
+                    writeln!(
+                        out,
+                        ">\n      <{} message=\"{}\" type=\"{}\">{}</{}>",
+                        element,
+                        xml_escape(message),
+                        kind,
+                        xml_escape(&case.details),
+                        element,
+                    )
+                    .unwrap();
+                }
+                JunitOutcome::Skipped { message } => {
+                    writeln!(
+                        out,
+                        ">\n      <skipped message=\"{}\"/>",
+                        xml_escape(message)
+                    )
+                    .unwrap();
+                }
+            }
+            if matches!(case.outcome, JunitOutcome::Pass) && !case.details.is_empty() {
+                writeln!(
+                    out,
+                    "      <system-out>{}</system-out>",
+                    xml_escape(&case.details)
+                )
+                .unwrap();
+            }
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+#[async_trait]
+impl EventSubscriber for JunitWriter {
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> anyhow::Result<()> {
+        for event in events {
+            if let buck2_data::buck_event::Data::Instant(instant) = event.data() {
+                if let Some(buck2_data::instant_event::Data::TestResult(result)) = &instant.data {
+                    self.handle_test_result(result)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn exit(&mut self) -> anyhow::Result<()> {
+        tokio::fs::write(&self.path, render_junit(&self.suites)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            "a &lt;b&gt; &amp; &quot;c&quot;",
+            xml_escape("a <b> & \"c\"")
+        );
+        assert_eq!("x\ny", xml_escape("x\u{1b}\ny"));
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let mut suites = BTreeMap::new();
+        suites.insert(
+            "root//:t".to_owned(),
+            vec![
+                JunitTestCase {
+                    name: "ok".to_owned(),
+                    duration: Duration::from_millis(1500),
+                    outcome: JunitOutcome::Pass,
+                    details: String::new(),
+                },
+                JunitTestCase {
+                    name: "bad".to_owned(),
+                    duration: Duration::from_millis(500),
+                    outcome: JunitOutcome::Failure {
+                        kind: "failure",
+                        message: "1 != 2".to_owned(),
+                    },
+                    details: "assertion failed".to_owned(),
+                },
+            ],
+        );
+        let xml = render_junit(&suites);
+        assert!(xml.contains(
+            "<testsuite name=\"root//:t\" tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"0\" time=\"2.000\">"
+        ));
+        assert!(xml.contains("<testcase name=\"ok\" classname=\"root//:t\" time=\"1.500\">"));
+        assert!(
+            xml.contains("<failure message=\"1 != 2\" type=\"failure\">assertion failed</failure>")
+        );
+    }
+}
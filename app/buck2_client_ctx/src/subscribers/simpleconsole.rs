@@ -475,6 +475,23 @@ where
         self.handle_stderr(&message.message).await
     }
 
+    async fn handle_live_action_output(
+        &mut self,
+        output: &buck2_data::LiveActionOutput,
+        _event: &BuckEvent,
+    ) -> anyhow::Result<()> {
+        let action_id = display::display_action_identity(
+            output.key.as_ref(),
+            output.name.as_ref(),
+            TargetDisplayOptions::for_log(),
+        )?;
+        for line in output.output.lines() {
+            self.handle_stderr(&format!("[{}] {}", action_id, line))
+                .await?;
+        }
+        Ok(())
+    }
+
     async fn handle_re_session_created(
         &mut self,
         session: &buck2_data::RemoteExecutionSessionCreated,
@@ -130,6 +130,9 @@ pub trait UnpackingEventSubscriber: Send {
             buck2_data::instant_event::Data::DebugAdapterSnapshot(snapshot) => {
                 self.handle_debug_adapter_snapshot(snapshot).await
             }
+            buck2_data::instant_event::Data::LiveActionOutput(output) => {
+                self.handle_live_action_output(output, event).await
+            }
             _ => Ok(()),
         }
     }
@@ -202,6 +205,14 @@ pub trait UnpackingEventSubscriber: Send {
         Ok(())
     }
 
+    async fn handle_live_action_output(
+        &mut self,
+        _output: &buck2_data::LiveActionOutput,
+        _event: &BuckEvent,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     /// Give the subscriber a chance to react to errors as we start trying to clean up.
     /// They may return another error, which will be incorporated into the end result.
     async fn handle_error(&mut self, _error: &anyhow::Error) -> anyhow::Result<()>;
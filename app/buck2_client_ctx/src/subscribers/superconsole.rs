@@ -616,6 +616,36 @@ impl UnpackingEventSubscriber for StatefulSuperConsole {
         }
     }
 
+    async fn handle_live_action_output(
+        &mut self,
+        output: &buck2_data::LiveActionOutput,
+        event: &BuckEvent,
+    ) -> anyhow::Result<()> {
+        match &mut self.super_console {
+            Some(super_console) => {
+                let action_id = display::display_action_identity(
+                    output.key.as_ref(),
+                    output.name.as_ref(),
+                    TargetDisplayOptions::for_console(self.state.config.display_platform),
+                )?;
+                super_console.emit(
+                    output
+                        .output
+                        .lines()
+                        .map(|line| Line::sanitized(&format!("[{}] {}", action_id, line)))
+                        .collect(),
+                );
+                Ok(())
+            }
+            None => {
+                self.state
+                    .simple_console
+                    .handle_live_action_output(output, event)
+                    .await
+            }
+        }
+    }
+
     async fn handle_action_execution_end(
         &mut self,
         action: &buck2_data::ActionExecutionEnd,
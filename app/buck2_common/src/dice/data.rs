@@ -41,6 +41,32 @@ impl SetIoProvider for DiceDataBuilder {
     }
 }
 
+/// Whether reads of source files are keyed on the file contents. If enabled, a change
+/// notification for a file whose contents are unchanged (e.g. it was touched, or rewritten by a
+/// checkout) doesn't invalidate anything that read it.
+#[derive(Clone, Copy, Dupe)]
+struct FileContentHashing(bool);
+
+pub trait HasFileContentHashing {
+    fn file_content_hashing(&self) -> bool;
+}
+
+pub trait SetFileContentHashing {
+    fn set_file_content_hashing(&mut self, enabled: bool);
+}
+
+impl HasFileContentHashing for DiceData {
+    fn file_content_hashing(&self) -> bool {
+        self.get::<FileContentHashing>().map_or(false, |h| h.0)
+    }
+}
+
+impl SetFileContentHashing for DiceDataBuilder {
+    fn set_file_content_hashing(&mut self, enabled: bool) {
+        self.set(FileContentHashing(enabled))
+    }
+}
+
 pub mod testing {
     use buck2_core::fs::project::ProjectRootTemp;
 
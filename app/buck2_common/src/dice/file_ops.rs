@@ -11,6 +11,7 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use allocative::Allocative;
 use anyhow::Context;
@@ -35,6 +36,7 @@ use dupe::Dupe;
 use more_futures::cancellation::CancellationContext;
 
 use crate::dice::cells::HasCellResolver;
+use crate::dice::data::HasFileContentHashing;
 use crate::dice::data::HasIoProvider;
 use crate::dice::file_ops::keys::FileOpsKey;
 use crate::dice::file_ops::keys::FileOpsValue;
@@ -66,12 +68,32 @@ impl<'c> HasFileOps<'c> for DiceComputations {
 // transient values.
 /// This is used as the "result" of a read_file computation so that we don't
 /// need to store the file content's in dice's cache.
-#[derive(Clone, Dupe, Allocative)]
-struct FileToken(Arc<CellPath>);
+#[derive(Clone, Allocative)]
+struct FileToken {
+    path: Arc<CellPath>,
+    /// Hash of the file contents when the token was computed (`None` inside if the file didn't
+    /// exist). Only set with file content hashing enabled, and only for files which are read,
+    /// in which case tokens with the same hash are equal.
+    #[allocative(skip)]
+    content_hash: Option<Option<blake3::Hash>>,
+    /// The contents which were hashed, handed to the first reader so that it reads exactly what
+    /// was hashed, without reading the file a second time.
+    #[allocative(skip)]
+    contents: Arc<Mutex<Option<Option<String>>>>,
+}
+
+impl Dupe for FileToken {}
 
 impl FileToken {
     async fn read_if_exists(&self, fs: &dyn FileOps) -> anyhow::Result<Option<String>> {
-        fs.read_file_if_exists((*self.0).as_ref()).await
+        if let Some(contents) = self.take_contents() {
+            return Ok(contents);
+        }
+        fs.read_file_if_exists((*self.path).as_ref()).await
+    }
+
+    fn take_contents(&self) -> Option<Option<String>> {
+        self.contents.lock().unwrap().take()
     }
 }
 
@@ -358,14 +380,33 @@ impl Key for ReadFileKey {
     type Value = FileToken;
     async fn compute(
         &self,
-        _ctx: &DiceComputations,
+        ctx: &DiceComputations,
         _cancellations: &CancellationContext,
     ) -> Self::Value {
-        FileToken(self.0.dupe())
+        let contents = if ctx.global_data().file_content_hashing() {
+            // If we can't read the file, leave the hash unset so the token is never equal to
+            // another one, and the error is reported by whatever reads the file.
+            match get_default_file_ops(ctx).await {
+                Ok(file_ops) => file_ops.read_file_if_exists((*self.0).as_ref()).await.ok(),
+                Err(_) => None,
+            }
+        } else {
+            None
+        };
+        FileToken {
+            path: self.0.dupe(),
+            content_hash: contents
+                .as_ref()
+                .map(|contents| contents.as_ref().map(|c| blake3::hash(c.as_bytes()))),
+            contents: Arc::new(Mutex::new(contents)),
+        }
     }
 
-    fn equality(_: &Self::Value, _: &Self::Value) -> bool {
-        false
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        match (&x.content_hash, &y.content_hash) {
+            (Some(x), Some(y)) => x == y,
+            _ => false,
+        }
     }
 }
 
@@ -420,7 +461,10 @@ impl Key for PathMetadataKey {
                 at: ref path,
                 to: _,
             }) => {
-                ctx.compute(&ReadFileKey(path.dupe())).await?;
+                // Only a dependency on the file, so don't keep its contents around.
+                ctx.compute(&ReadFileKey(path.dupe()))
+                    .await?
+                    .take_contents();
             }
             _ => (),
         };
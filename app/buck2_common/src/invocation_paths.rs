@@ -204,8 +204,21 @@ impl InvocationPaths {
         FileName::unchecked_new("materializer_state")
     }
 
+    /// Subdirectory of `cache_dir` storing the local action cache
+    pub fn local_action_cache_path(&self) -> AbsNormPathBuf {
+        self.cache_dir_path()
+            .join(self.local_action_cache_dir_name())
+    }
+
+    pub fn local_action_cache_dir_name(&self) -> &FileName {
+        FileName::unchecked_new("local_action_cache")
+    }
+
     pub fn valid_cache_dirs(&self) -> Vec<&FileName> {
-        vec![self.materializer_state_dir_name()]
+        vec![
+            self.materializer_state_dir_name(),
+            self.local_action_cache_dir_name(),
+        ]
     }
 }
 
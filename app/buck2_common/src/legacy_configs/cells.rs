@@ -30,6 +30,7 @@ use once_cell::unsync::OnceCell;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::legacy_configs::external_cells::ExternalCell;
 use crate::legacy_configs::path::BuckConfigFile;
 use crate::legacy_configs::path::DEFAULT_BUCK_CONFIG_FILES;
 use crate::legacy_configs::push_all_files_from_a_directory;
@@ -81,6 +82,7 @@ impl BuckConfigBasedCells {
     ) -> anyhow::Result<ImmediateConfig> {
         let opts = BuckConfigParseOptions {
             follow_includes: false,
+            materialize_external_cells: false,
         };
         let cells = Self::parse_with_file_ops_and_options(
             project_fs,
@@ -131,6 +133,7 @@ impl BuckConfigBasedCells {
     ) -> anyhow::Result<Self> {
         let opts = BuckConfigParseOptions {
             follow_includes: true,
+            materialize_external_cells: true,
         };
         Self::parse_with_file_ops_and_options(project_fs, file_ops, config_args, cwd, opts)
     }
@@ -343,6 +346,17 @@ impl BuckConfigBasedCells {
                 return Err(CellsError::MissingRootCellName.into());
             }
 
+            if is_root {
+                for external_cell in ExternalCell::parse_all(&config)? {
+                    let alias_path =
+                        external_cell.cell_root(project_fs, options.materialize_external_cells)?;
+                    let alias = NonEmptyCellAlias::new(external_cell.name().to_owned())?;
+                    root_aliases.insert(alias.clone(), alias_path.clone());
+                    cells_aggregator.add_cell_entry(path.clone(), alias, alias_path.clone())?;
+                    work.push(alias_path);
+                }
+            }
+
             if let Some(aliases) = config.get_section("repository_aliases") {
                 for (alias, destination) in aliases.iter() {
                     let alias = NonEmptyCellAlias::new(alias.to_owned())?;
@@ -0,0 +1,551 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Cells whose contents are not checked in, but are fetched or generated when the configs are
+//! parsed. They are declared in the root `.buckconfig`:
+//!
+//! ```ini
+//! [external_cells]
+//!   fmt = archive
+//!   gen = command
+//!
+//! [external_cell_fmt]
+//!   path = third-party/fmt-10.0.0.tar.gz
+//!   sha256 = 0123...
+//!   strip_prefix = fmt-10.0.0
+//!
+//! [external_cell_gen]
+//!   cmd = tools/generate_cell.sh
+//! ```
+//!
+//! The contents are materialized in `buck-out/external_cells/<name>/<hash>`, where the hash
+//! covers the definition of the cell (and the archive contents if no `sha256` is given). The cell
+//! root changes when the definition does, which invalidates everything computed from the cell,
+//! while cells whose definition didn't change are reused as-is.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Component;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Context;
+use buck2_core::cells::cell_root_path::CellRootPathBuf;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::paths::file_name::FileName;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::project::ProjectRoot;
+use flate2::read::GzDecoder;
+use rand::Rng;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::invocation_paths::InvocationPaths;
+use crate::legacy_configs::LegacyBuckConfig;
+
+#[derive(Debug, thiserror::Error)]
+enum ExternalCellError {
+    #[error("Unknown origin `{1}` for external cell `{0}`, expected `archive` or `command`")]
+    UnknownOrigin(String, String),
+    #[error("External cell `{0}` is missing required key `{1}` in section `[{2}]`")]
+    MissingKey(String, &'static str, String),
+    #[error("Archive `{0}` for external cell `{1}` has sha256 `{2}`, but `{3}` was expected")]
+    Sha256Mismatch(String, String, String, String),
+    #[error(
+        "Archive `{0}` for external cell `{1}` has an unsupported format, expected `.tar`, `.tar.gz` or `.tgz`"
+    )]
+    UnsupportedArchive(String, String),
+    #[error("Archive for external cell `{0}` contains invalid path `{1}`")]
+    InvalidArchivePath(String, String),
+    #[error("Archive for external cell `{0}` contains link `{1}` to `{2}`, outside of the cell")]
+    EscapingArchiveLink(String, String, String),
+    #[error("Command `{1}` for external cell `{0}` failed with {2}")]
+    CommandFailed(String, String, std::process::ExitStatus),
+}
+
+/// Where the contents of an external cell come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExternalCellOrigin {
+    /// A `.tar`, `.tar.gz` or `.tgz` archive, relative to the project root.
+    Archive {
+        path: String,
+        sha256: Option<String>,
+        strip_prefix: Option<String>,
+    },
+    /// A command run from the project root, which must write the cell contents to the directory
+    /// in `$BUCK2_EXTERNAL_CELL_OUT`.
+    Command { cmd: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExternalCell {
+    name: String,
+    origin: ExternalCellOrigin,
+}
+
+impl ExternalCell {
+    /// Read the external cells declared in the root config.
+    pub(crate) fn parse_all(config: &LegacyBuckConfig) -> anyhow::Result<Vec<ExternalCell>> {
+        let section = match config.get_section("external_cells") {
+            Some(section) => section,
+            None => return Ok(Vec::new()),
+        };
+        section
+            .iter()
+            .map(|(name, origin)| Self::parse(config, name, origin.as_str()))
+            .collect()
+    }
+
+    fn parse(config: &LegacyBuckConfig, name: &str, origin: &str) -> anyhow::Result<ExternalCell> {
+        let section = format!("external_cell_{}", name);
+        let get = |key| config.get(&section, key).map(|v| v.to_owned());
+        let require = |key| {
+            get(key)
+                .ok_or_else(|| ExternalCellError::MissingKey(name.to_owned(), key, section.clone()))
+        };
+        let origin = match origin {
+            "archive" => ExternalCellOrigin::Archive {
+                path: require("path")?,
+                sha256: get("sha256"),
+                strip_prefix: get("strip_prefix"),
+            },
+            "command" => ExternalCellOrigin::Command {
+                cmd: require("cmd")?,
+            },
+            _ => {
+                return Err(
+                    ExternalCellError::UnknownOrigin(name.to_owned(), origin.to_owned()).into(),
+                );
+            }
+        };
+        Ok(ExternalCell {
+            name: name.to_owned(),
+            origin,
+        })
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Hash of everything which determines the contents of the cell.
+    fn definition_hash(&self, project_fs: &ProjectRoot) -> anyhow::Result<String> {
+        let mut hasher = Sha256::new();
+        match &self.origin {
+            ExternalCellOrigin::Archive {
+                path,
+                sha256,
+                strip_prefix,
+            } => {
+                hasher.update(b"archive\0");
+                hasher.update(path.as_bytes());
+                hasher.update(b"\0");
+                hasher.update(strip_prefix.as_deref().unwrap_or_default().as_bytes());
+                hasher.update(b"\0");
+                match sha256 {
+                    Some(sha256) => hasher.update(sha256.as_bytes()),
+                    None => hasher.update(self.archive_sha256(project_fs, path)?.as_bytes()),
+                }
+            }
+            ExternalCellOrigin::Command { cmd } => {
+                hasher.update(b"command\0");
+                hasher.update(cmd.as_bytes());
+            }
+        }
+        // A short prefix is plenty to tell definitions apart, and keeps paths short.
+        Ok(hex::encode(hasher.finalize())[..16].to_owned())
+    }
+
+    fn archive_sha256(&self, project_fs: &ProjectRoot, path: &str) -> anyhow::Result<String> {
+        let path = project_fs.root().as_path().join(path);
+        let mut file = File::open(&path).with_context(|| {
+            format!(
+                "Error opening archive `{}` for external cell `{}`",
+                path.display(),
+                self.name
+            )
+        })?;
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+
+    /// The root of the cell, which depends on the definition of the cell. If `materialize` is
+    /// set, the contents are fetched if they are not already present.
+    pub(crate) fn cell_root(
+        &self,
+        project_fs: &ProjectRoot,
+        materialize: bool,
+    ) -> anyhow::Result<CellRootPathBuf> {
+        let cell_dir = InvocationPaths::buck_out_dir_prefix()
+            .join(ForwardRelativePath::new("external_cells")?)
+            .join(FileName::new(&self.name)?);
+        let root = cell_dir.join(FileName::new(&self.definition_hash(project_fs)?)?);
+        if materialize {
+            self.materialize(
+                project_fs,
+                &project_fs.resolve(&cell_dir),
+                &project_fs.resolve(&root),
+            )
+            .with_context(|| format!("Error materializing external cell `{}`", self.name))?;
+        }
+        Ok(CellRootPathBuf::new(root))
+    }
+
+    fn materialize(
+        &self,
+        project_fs: &ProjectRoot,
+        cell_dir: &AbsNormPath,
+        root: &AbsNormPath,
+    ) -> anyhow::Result<()> {
+        if fs_util::try_exists(root)? {
+            return Ok(());
+        }
+
+        // Fetch into a temporary directory, then rename, so that a cell root which exists is
+        // always complete, even if we are interrupted or another command fetches concurrently.
+        let tmp = cell_dir.join(FileName::new(&format!(
+            "tmp.{}",
+            rand::thread_rng().gen::<u64>()
+        ))?);
+        fs_util::create_dir_all(&tmp)?;
+        let res = match &self.origin {
+            ExternalCellOrigin::Archive {
+                path,
+                sha256,
+                strip_prefix,
+            } => self.extract_archive(
+                project_fs,
+                path,
+                sha256.as_deref(),
+                strip_prefix.as_deref(),
+                &tmp,
+            ),
+            ExternalCellOrigin::Command { cmd } => self.run_command(project_fs, cmd, &tmp),
+        };
+        if let Err(e) = res {
+            fs_util::remove_all(&tmp)?;
+            return Err(e);
+        }
+        if let Err(e) = fs_util::rename(&tmp, root) {
+            fs_util::remove_all(&tmp)?;
+            if !fs_util::try_exists(root)? {
+                return Err(e);
+            }
+        }
+
+        // Contents for previous definitions of the cell are no longer needed. Temporary directories
+        // may belong to other commands still fetching, and are removed by them.
+        for entry in fs_util::read_dir(cell_dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            if Some(file_name.as_os_str()) != root.as_path().file_name()
+                && !file_name.to_string_lossy().starts_with("tmp.")
+            {
+                fs_util::remove_all(AbsPath::new(&entry.path())?)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn extract_archive(
+        &self,
+        project_fs: &ProjectRoot,
+        path: &str,
+        sha256: Option<&str>,
+        strip_prefix: Option<&str>,
+        dest: &AbsNormPath,
+    ) -> anyhow::Result<()> {
+        if let Some(expected) = sha256 {
+            let actual = self.archive_sha256(project_fs, path)?;
+            if !actual.eq_ignore_ascii_case(expected) {
+                return Err(ExternalCellError::Sha256Mismatch(
+                    path.to_owned(),
+                    self.name.clone(),
+                    actual,
+                    expected.to_owned(),
+                )
+                .into());
+            }
+        }
+
+        let file = File::open(project_fs.root().as_path().join(path))?;
+        if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            self.unpack(tar::Archive::new(GzDecoder::new(file)), strip_prefix, dest)
+        } else if path.ends_with(".tar") {
+            self.unpack(tar::Archive::new(file), strip_prefix, dest)
+        } else {
+            Err(ExternalCellError::UnsupportedArchive(path.to_owned(), self.name.clone()).into())
+        }
+    }
+
+    fn unpack(
+        &self,
+        mut archive: tar::Archive<impl Read>,
+        strip_prefix: Option<&str>,
+        dest: &AbsNormPath,
+    ) -> anyhow::Result<()> {
+        // `unpack_in` refuses to write through symlinks, but only knows the paths in the archive,
+        // so with a prefix to strip, extract into a staging directory and move the prefix out.
+        let staging = match strip_prefix {
+            Some(_) => dest.join(FileName::new(".buck2_archive")?),
+            None => dest.to_buf(),
+        };
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let archive_path = entry.path()?.into_owned();
+            let path = match strip_prefix {
+                Some(prefix) => match archive_path.strip_prefix(prefix) {
+                    Ok(path) => path.to_owned(),
+                    Err(_) => continue,
+                },
+                None => archive_path.clone(),
+            };
+            if path.as_os_str().is_empty() {
+                continue;
+            }
+            if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+                return Err(ExternalCellError::InvalidArchivePath(
+                    self.name.clone(),
+                    path.display().to_string(),
+                )
+                .into());
+            }
+            if entry.header().entry_type().is_symlink() {
+                if let Some(link) = entry.link_name()? {
+                    if link_escapes(&path, &link) {
+                        return Err(ExternalCellError::EscapingArchiveLink(
+                            self.name.clone(),
+                            path.display().to_string(),
+                            link.display().to_string(),
+                        )
+                        .into());
+                    }
+                }
+            }
+            let unpacked = entry
+                .unpack_in(staging.as_path())
+                .with_context(|| format!("Error extracting `{}`", Path::new(&path).display()))?;
+            if !unpacked {
+                return Err(ExternalCellError::InvalidArchivePath(
+                    self.name.clone(),
+                    archive_path.display().to_string(),
+                )
+                .into());
+            }
+        }
+
+        if let Some(prefix) = strip_prefix {
+            let stripped = staging.as_path().join(prefix);
+            if stripped.is_dir() {
+                for entry in std::fs::read_dir(&stripped)? {
+                    let entry = entry?;
+                    fs_util::rename(
+                        AbsPath::new(&entry.path())?,
+                        AbsPath::new(&dest.as_path().join(entry.file_name()))?,
+                    )?;
+                }
+            }
+            fs_util::remove_all(&staging)?;
+        }
+        Ok(())
+    }
+
+    fn run_command(
+        &self,
+        project_fs: &ProjectRoot,
+        cmd: &str,
+        dest: &AbsNormPath,
+    ) -> anyhow::Result<()> {
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(cmd);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(cmd);
+            command
+        };
+        let status = command
+            .current_dir(project_fs.root().as_path())
+            .env("BUCK2_EXTERNAL_CELL_NAME", &self.name)
+            .env("BUCK2_EXTERNAL_CELL_OUT", dest.as_path())
+            .status()
+            .with_context(|| format!("Error running `{}`", cmd))?;
+        if !status.success() {
+            return Err(ExternalCellError::CommandFailed(
+                self.name.clone(),
+                cmd.to_owned(),
+                status,
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Whether the symlink at `path`, relative to the cell root, points outside of the cell.
+fn link_escapes(path: &Path, link: &Path) -> bool {
+    // The directory containing the link, as a depth below the cell root.
+    let mut depth = path.components().count().saturating_sub(1);
+    for component in link.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return true,
+            },
+            Component::RootDir | Component::Prefix(_) => return true,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::legacy_configs::testing::parse;
+
+    fn project(dir: &tempfile::TempDir) -> ProjectRoot {
+        ProjectRoot::new_unchecked(AbsNormPath::new(dir.path()).unwrap().to_buf())
+    }
+
+    #[test]
+    fn test_parse() -> anyhow::Result<()> {
+        let config = parse(
+            &[(
+                "/config",
+                indoc::indoc!(
+                    r#"
+                    [external_cells]
+                      fmt = archive
+                      gen = command
+                    [external_cell_fmt]
+                      path = third-party/fmt.tar.gz
+                      strip_prefix = fmt-10.0.0
+                    [external_cell_gen]
+                      cmd = ./generate.sh
+                "#
+                ),
+            )],
+            "/config",
+        )?;
+        assert_eq!(
+            vec![
+                ExternalCell {
+                    name: "fmt".to_owned(),
+                    origin: ExternalCellOrigin::Archive {
+                        path: "third-party/fmt.tar.gz".to_owned(),
+                        sha256: None,
+                        strip_prefix: Some("fmt-10.0.0".to_owned()),
+                    },
+                },
+                ExternalCell {
+                    name: "gen".to_owned(),
+                    origin: ExternalCellOrigin::Command {
+                        cmd: "./generate.sh".to_owned(),
+                    },
+                },
+            ],
+            ExternalCell::parse_all(&config)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_key() -> anyhow::Result<()> {
+        let config = parse(
+            &[("/config", "[external_cells]\n  fmt = archive\n")],
+            "/config",
+        )?;
+        assert!(ExternalCell::parse_all(&config).is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_cell_root_follows_definition() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let fs = project(&dir);
+        let cell = |cmd: &str| ExternalCell {
+            name: "gen".to_owned(),
+            origin: ExternalCellOrigin::Command {
+                cmd: cmd.to_owned(),
+            },
+        };
+
+        let v1 = cell("echo 'x = 1' > $BUCK2_EXTERNAL_CELL_OUT/defs.bzl");
+        let root1 = v1.cell_root(&fs, true)?;
+        assert_eq!(
+            "x = 1\n",
+            fs_util::read_to_string(
+                fs.resolve(root1.project_relative_path())
+                    .join(ForwardRelativePath::new("defs.bzl")?)
+            )?
+        );
+        // Unchanged definitions reuse the same root.
+        assert_eq!(root1, v1.cell_root(&fs, true)?);
+
+        let v2 = cell("echo 'x = 2' > $BUCK2_EXTERNAL_CELL_OUT/defs.bzl");
+        let root2 = v2.cell_root(&fs, true)?;
+        assert_ne!(root1, root2);
+        assert!(!fs_util::try_exists(
+            fs.resolve(root1.project_relative_path())
+        )?);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_archive_links_cannot_escape_cell() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let fs = project(&dir);
+        let outside = tempfile::tempdir()?;
+
+        let mut builder = tar::Builder::new(File::create(dir.path().join("evil.tar"))?);
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder.append_link(&mut header, "x", outside.path())?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        builder.append_data(&mut header, "x/pwned", &b"evil"[..])?;
+        builder.finish()?;
+
+        let cell = ExternalCell {
+            name: "evil".to_owned(),
+            origin: ExternalCellOrigin::Archive {
+                path: "evil.tar".to_owned(),
+                sha256: None,
+                strip_prefix: None,
+            },
+        };
+        let err = cell.cell_root(&fs, true).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("outside of the cell"),
+            "{:#}",
+            err
+        );
+        assert!(!outside.path().join("pwned").exists());
+
+        assert!(link_escapes(Path::new("a/b"), Path::new("../../c")));
+        assert!(!link_escapes(Path::new("a/b"), Path::new("../c")));
+        Ok(())
+    }
+}
@@ -12,6 +12,7 @@
 
 pub mod cells;
 pub mod dice;
+pub(crate) mod external_cells;
 pub(crate) mod path;
 pub mod view;
 
@@ -1205,6 +1206,8 @@ impl LegacyBuckConfig {
 struct BuckConfigParseOptions {
     // Defines whether includes are followed, this can significantly reduce parse time.
     follow_includes: bool,
+    // Defines whether external cells are fetched, rather than just resolving their paths.
+    materialize_external_cells: bool,
 }
 
 fn push_all_files_from_a_directory(
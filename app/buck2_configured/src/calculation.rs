@@ -94,6 +94,87 @@ impl std::fmt::Display for ConfiguredGraphCycleError {
     }
 }
 
+impl ConfiguredGraphCycleError {
+    /// Turn the cycle into a report showing, for each edge, the attributes creating it and where
+    /// the depending target is declared. Falls back to the plain cycle if the unconfigured nodes
+    /// cannot be loaded.
+    pub async fn into_report(self, ctx: &DiceComputations) -> anyhow::Error {
+        let mut edges = Vec::with_capacity(self.cycle.len());
+        for (i, from) in self.cycle.iter().enumerate() {
+            let to = &self.cycle[(i + 1) % self.cycle.len()];
+            let (from_key, to_key) = match (from, to) {
+                (
+                    ConfiguredGraphCycleKeys::ConfiguredTargetNode(from_key),
+                    ConfiguredGraphCycleKeys::ConfiguredTargetNode(to_key),
+                ) => (from_key, to_key),
+            };
+            let node = match ctx.get_target_node(from_key.0.unconfigured()).await {
+                Ok(node) => node,
+                Err(_) => return self.into(),
+            };
+            edges.push(ConfiguredGraphCycleEdge {
+                from: from_key.0.dupe(),
+                attrs: node
+                    .attrs_with_dep(to_key.0.unconfigured())
+                    .into_iter()
+                    .map(|a| a.to_owned())
+                    .collect(),
+                buildfile: node.buildfile_path().to_string(),
+                call_stack: node.call_stack(),
+            });
+        }
+        ConfiguredGraphCycleReport { edges }.into()
+    }
+}
+
+#[derive(Debug)]
+struct ConfiguredGraphCycleEdge {
+    from: ConfiguredTargetLabel,
+    /// Attributes of `from` referencing the next target in the cycle.
+    attrs: Vec<String>,
+    buildfile: String,
+    call_stack: Option<String>,
+}
+
+#[derive(Error, Debug)]
+struct ConfiguredGraphCycleReport {
+    edges: Vec<ConfiguredGraphCycleEdge>,
+}
+
+impl std::fmt::Display for ConfiguredGraphCycleReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Configured target cycle detected (`->` means \"depends on\"):"
+        )?;
+        for edge in &self.edges {
+            writeln!(f, "  {}", edge.from)?;
+            writeln!(f, "    declared in `{}`", edge.buildfile)?;
+            if let Some(call_stack) = &edge.call_stack {
+                for line in call_stack.lines() {
+                    writeln!(f, "      {}", line)?;
+                }
+            }
+            if edge.attrs.is_empty() {
+                writeln!(f, "  ->")?;
+            } else {
+                writeln!(
+                    f,
+                    "  -> via {}",
+                    edge.attrs
+                        .iter()
+                        .map(|a| format!("`{}`", a))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )?;
+            }
+        }
+        // point back at the first item in the cycle.
+        writeln!(f, "  {}", self.edges.first().unwrap().from)?;
+        Ok(())
+    }
+}
+
 // TODO(cjhopman): There's other keys that could be involved in a cycle in the configured graph and they should probably also be tracked
 // here. Would be good to check on things like transitions, toolchains, configuration nodes. Still, this will currently catch most
 // configured graph cycles.
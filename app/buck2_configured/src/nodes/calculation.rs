@@ -659,7 +659,10 @@ async fn compute_configured_target_node_no_transition(
         futures::future::join_all(exec_dep_futures),
     );
     let (dep_results, exec_dep_results): (Vec<_>, Vec<_>) =
-        ConfiguredGraphCycleDescriptor::guard_this(ctx, fut).await??;
+        match ConfiguredGraphCycleDescriptor::guard_this(ctx, fut).await? {
+            Ok(results) => results,
+            Err(cycle) => return Err(cycle.into_report(ctx).await),
+        };
 
     let mut deps = Vec::with_capacity(deps.len());
     let mut exec_deps = Vec::with_capacity(exec_deps.len());
@@ -277,6 +277,69 @@ pub fn copy<P: AsRef<AbsPath>, Q: AsRef<AbsPath>>(from: P, to: Q) -> anyhow::Res
     })
 }
 
+pub fn hardlink<P: AsRef<AbsPath>, Q: AsRef<AbsPath>>(original: P, link: Q) -> anyhow::Result<()> {
+    let _guard = IoCounterKey::Hardlink.guard();
+    fs::hard_link(
+        original.as_ref().as_maybe_relativized(),
+        link.as_ref().as_maybe_relativized(),
+    )
+    .with_context(|| {
+        format!(
+            "hardlink(original={}, link={})",
+            P::as_ref(&original).display(),
+            Q::as_ref(&link).display()
+        )
+    })
+}
+
+/// Copy a file by sharing its data blocks, copy-on-write. Fails if the filesystem does not
+/// support it.
+pub fn reflink<P: AsRef<AbsPath>, Q: AsRef<AbsPath>>(from: P, to: Q) -> anyhow::Result<()> {
+    let _guard = IoCounterKey::Copy.guard();
+    reflink_impl(
+        from.as_ref().as_maybe_relativized(),
+        to.as_ref().as_maybe_relativized(),
+    )
+    .with_context(|| {
+        format!(
+            "reflink(from={}, to={})",
+            P::as_ref(&from).display(),
+            Q::as_ref(&to).display()
+        )
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn reflink_impl(from: &Path, to: &Path) -> anyhow::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    /// `_IOW(0x94, 9, int)` from `linux/fs.h`.
+    const FICLONE: libc::c_ulong = 0x40049409;
+
+    let src = fs::File::open(from)?;
+    let permissions = src.metadata()?.permissions();
+    let dest = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(to)?;
+    // SAFETY: both file descriptors are open for the duration of the call.
+    if unsafe { libc::ioctl(dest.as_raw_fd(), FICLONE as _, src.as_raw_fd()) } != 0 {
+        let error = io::Error::last_os_error();
+        drop(dest);
+        fs::remove_file(to)?;
+        return Err(error.into());
+    }
+    dest.set_permissions(permissions)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink_impl(_from: &Path, _to: &Path) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "Reflinks are not supported on this platform"
+    ))
+}
+
 pub fn read_link<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<PathBuf> {
     let _guard = IoCounterKey::ReadLink.guard();
     fs::read_link(path.as_ref().as_maybe_relativized())
@@ -790,6 +853,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_hardlink_and_reflink() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsPath::new(tempdir.path())?;
+        let file_path = root.join("file");
+        write(&file_path, b"File content")?;
+
+        hardlink(&file_path, root.join("hardlink"))?;
+        assert_eq!(read_to_string(root.join("hardlink"))?, "File content");
+
+        // Not all filesystems support reflinks, but a failure must not leave a file behind.
+        match reflink(&file_path, root.join("reflink")) {
+            Ok(()) => assert_eq!(read_to_string(root.join("reflink"))?, "File content"),
+            Err(_) => assert!(!root.join("reflink").exists()),
+        }
+        Ok(())
+    }
+
     #[test]
     fn remove_file_removes_symlink_to_directory() -> anyhow::Result<()> {
         let tempdir = tempfile::tempdir()?;
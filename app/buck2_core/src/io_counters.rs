@@ -29,6 +29,7 @@ pub enum IoCounterKey {
     Remove,
     Rename,
     Read,
+    ReadEden,
     Write,
     Canonicalize,
     EdenSettle,
@@ -53,6 +54,7 @@ static IN_PROGRESS: [AtomicU32; IoCounterKey::COUNT] = [
     AtomicU32::new(0),
     AtomicU32::new(0),
     AtomicU32::new(0),
+    AtomicU32::new(0),
 ];
 
 impl IoCounterKey {
@@ -72,6 +74,7 @@ impl IoCounterKey {
         IoCounterKey::Remove,
         IoCounterKey::Rename,
         IoCounterKey::Read,
+        IoCounterKey::ReadEden,
         IoCounterKey::Write,
         IoCounterKey::Canonicalize,
         IoCounterKey::EdenSettle,
@@ -87,6 +87,18 @@ fn main() -> io::Result<()> {
             "buck.data.CommandExecutionDetails.input_materialization_duration",
             "#[serde(rename = \"input_materialization_duration_us\", with = \"crate::serialize_duration_as_micros\")]",
         )
+        .field_attribute(
+            "buck.data.CommandExecutionDetails.queue_duration",
+            "#[serde(rename = \"queue_duration_us\", with = \"crate::serialize_duration_as_micros\")]",
+        )
+        .field_attribute(
+            "buck.data.CommandExecutionDetails.execution_duration",
+            "#[serde(rename = \"execution_duration_us\", with = \"crate::serialize_duration_as_micros\")]",
+        )
+        .field_attribute(
+            "buck.data.CommandExecutionDetails.output_upload_duration",
+            "#[serde(rename = \"output_upload_duration_us\", with = \"crate::serialize_duration_as_micros\")]",
+        )
         .type_attribute(
             "buck.data.DynamicLambdaStart.owner",
             "#[derive(::derive_more::From, ::gazebo::variants::VariantName)]",
@@ -398,6 +398,7 @@ impl_has_error_handling_strategy!(SetPathObjectIdError);
 impl_has_error_handling_strategy!(RemoveRecursivelyError);
 impl_has_error_handling_strategy!(EnsureMaterializedError);
 impl_has_error_handling_strategy!(ReaddirError);
+impl_has_error_handling_strategy!(GetFileContentError);
 
 #[derive(Debug, Error)]
 pub enum EdenError {
@@ -467,6 +468,8 @@ impl_eden_data_into_result!(
 
 impl_eden_data_into_result!(SizeOrError, i64, size);
 
+impl_eden_data_into_result!(ScmBlobOrError, Vec<u8>, blob);
+
 impl_eden_data_into_result!(Sha1OrError, BinaryHash, sha1);
 
 impl_eden_data_into_result!(Blake3OrError, BinaryHash, blake3);
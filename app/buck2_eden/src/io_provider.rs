@@ -32,6 +32,8 @@ use compact_str::CompactString;
 use dupe::Dupe;
 use edenfs::types::FileAttributes;
 use edenfs::types::GetAttributesFromFilesParams;
+use edenfs::types::GetFileContentRequest;
+use edenfs::types::MountId;
 use edenfs::types::ReaddirParams;
 use edenfs::types::SourceControlType;
 use edenfs::types::SyncBehavior;
@@ -368,7 +370,51 @@ impl IoProvider for EdenIoProvider {
         &self,
         path: ProjectRelativePathBuf,
     ) -> anyhow::Result<Option<String>> {
-        self.fs.read_file_if_exists(path).await
+        let _guard = IoCounterKey::ReadEden.guard();
+
+        // Reading through Eden serves the contents from its object store, so files that were
+        // never read in this checkout don't need to be fetched into the overlay first.
+        let params = GetFileContentRequest {
+            mount: MountId {
+                mountPoint: self.manager.get_mount_point(),
+                ..Default::default()
+            },
+            filePath: path.to_string().into_bytes(),
+            sync: no_sync(),
+            ..Default::default()
+        };
+
+        let res = self
+            .manager
+            .with_eden(|eden| {
+                tracing::trace!("getFileContent({})", path);
+                eden.getFileContent(&params)
+            })
+            .await?
+            .blob
+            .into_result();
+
+        match res {
+            Ok(blob) => {
+                tracing::debug!("getFileContent({}): {} bytes", path, blob.len());
+                let content = String::from_utf8(blob)
+                    .with_context(|| format!("File `{}` is not UTF-8", path))?;
+                Ok(Some(content))
+            }
+            Err(EdenError::PosixError { code, .. }) if code == ENOENT => {
+                tracing::debug!("getFileContent({}): ENOENT", path);
+                Ok(None)
+            }
+            Err(EdenError::PosixError { code, .. })
+                if code == EISDIR || code == EINVAL || code == ENOTDIR =>
+            {
+                // Not a regular file, or a path through a symlink: let the filesystem resolve it
+                // and report the same errors as without Eden I/O.
+                tracing::debug!("getFileContent({}): fallthrough", path);
+                self.fs.read_file_if_exists(path).await
+            }
+            Err(err) => Err(err.into()),
+        }
     }
 
     async fn read_dir(&self, path: ProjectRelativePathBuf) -> anyhow::Result<Vec<RawDirEntry>> {
@@ -466,6 +466,7 @@ pub fn format_test_result(test_result: &buck2_data::TestResult) -> anyhow::Resul
         status,
         duration,
         details,
+        attempt,
         ..
     } = test_result;
     let status = TestStatus::try_from(*status)?;
@@ -500,6 +501,9 @@ pub fn format_test_result(test_result: &buck2_data::TestResult) -> anyhow::Resul
             ))?);
         }
     }
+    if *attempt > 1 {
+        base.push(Span::new_unstyled(format!(" [attempt {}]", attempt))?);
+    }
     // If a test has details, we always show them. It's the test runner's
     // responsibility to withhold details when these are not relevant.
     // For instance, tpx will always withhold details of passing tests
@@ -69,6 +69,7 @@ pub fn io_in_flight_non_zero_counters(
                 IoCounterKey::Remove => snapshot.io_in_flight_remove,
                 IoCounterKey::Rename => snapshot.io_in_flight_rename,
                 IoCounterKey::Read => snapshot.io_in_flight_read,
+                IoCounterKey::ReadEden => snapshot.io_in_flight_read_eden,
                 IoCounterKey::Write => snapshot.io_in_flight_write,
                 IoCounterKey::Canonicalize => snapshot.io_in_flight_canonicalize,
                 IoCounterKey::EdenSettle => snapshot.io_in_flight_eden_settle,
@@ -103,6 +103,12 @@ impl ReState {
             }
         }
 
+        if let Some((_, last)) = &self.two_snapshots.last {
+            if last.re_circuit_breaker_open {
+                parts.push("RE unhealthy, running actions locally".to_owned());
+            }
+        }
+
         if let Some(session_id) = self.session_id.as_ref() {
             parts.push(format!("({})", session_id.to_owned()));
         }
@@ -193,6 +199,13 @@ impl ReState {
                 last.re_get_digest_expirations_finished_successfully,
                 last.re_get_digest_expirations_finished_with_error,
             )?);
+            if last.re_retries > 0 {
+                let re_retries = last.re_retries;
+                r.push(Line::unstyled(&format!(
+                    "{:<20}: {re_retries:>5}",
+                    "re_retries"
+                ))?);
+            }
             // TODO(raulgarcia4): Add some in-progress-stats for http metrics as well.
             r.extend(self.render_detailed_item_no_progress_stats(
                 "http_download_bytes",
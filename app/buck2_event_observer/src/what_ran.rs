@@ -9,6 +9,7 @@
 
 use std::borrow::Cow;
 use std::fmt;
+use std::time::Duration;
 
 use buck2_data::re_platform::Property;
 use dupe::Dupe;
@@ -66,6 +67,8 @@ pub struct WhatRanOutputCommand<'a> {
     identity: &'a str,
     repro: CommandReproducer<'a>,
     extra: Option<WhatRanOutputCommandExtra<'a>>,
+    duration: Option<Duration>,
+    std_err: Option<&'a str>,
 }
 
 impl WhatRanOutputCommand<'_> {
@@ -81,6 +84,14 @@ impl WhatRanOutputCommand<'_> {
     pub fn extra(&self) -> Option<WhatRanOutputCommandExtra<'_>> {
         self.extra
     }
+    /// How long the command took, if it is known (i.e. the command is emitted once it finished).
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+    /// The stderr of the command, if it was requested (e.g. for failed commands).
+    pub fn std_err(&self) -> Option<&str> {
+        self.std_err
+    }
 }
 
 #[derive(Clone, Copy, Dupe)]
@@ -123,12 +134,14 @@ fn emit<T: fmt::Display + Copy>(
     state: &impl WhatRanState<T>,
     output: &mut impl WhatRanOutputWriter,
 ) -> anyhow::Result<()> {
-    emit_reproducer(state.get(parent_span_id), repro, output)
+    emit_reproducer(state.get(parent_span_id), repro, None, None, output)
 }
 
 pub fn emit_reproducer(
     action: Option<WhatRanRelevantAction<'_>>,
     repro: CommandReproducer<'_>,
+    duration: Option<Duration>,
+    std_err: Option<&str>,
     output: &mut impl WhatRanOutputWriter,
 ) -> anyhow::Result<()> {
     let (reason, identity, extra) = match action {
@@ -162,6 +175,8 @@ pub fn emit_reproducer(
         identity: &identity,
         repro,
         extra,
+        duration,
+        std_err,
     })?;
 
     Ok(())
@@ -188,6 +203,23 @@ impl<'a> CommandReproducer<'a> {
         }
     }
 
+    /// The digest of the action this command is for.
+    pub fn action_digest(&self) -> &'a str {
+        match *self {
+            Self::CacheQuery(cache_query) => &cache_query.action_digest,
+            Self::CacheHit(cache_hit) => &cache_hit.action_digest,
+            Self::ReExecute(execute) => &execute.action_digest,
+            Self::LocalExecute(execute) => execute
+                .command
+                .as_ref()
+                .map_or("", |command| &command.action_digest),
+            Self::WorkerExecute(execute) => execute
+                .command
+                .as_ref()
+                .map_or("", |command| &command.action_digest),
+        }
+    }
+
     /// Human-readable representation of this repro instruction
     pub fn as_human_readable(&self) -> HumanReadableCommandReproducer<'a> {
         HumanReadableCommandReproducer { command: *self }
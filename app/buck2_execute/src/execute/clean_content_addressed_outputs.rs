@@ -0,0 +1,128 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use anyhow::Context;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+
+use crate::execute::blocking::IoRequest;
+
+/// Directory in buck-out holding the content-addressed outputs, see
+/// `buck2.content_addressed_outputs`.
+pub const CONTENT_ADDRESSED_OUTPUTS_DIR: &str = "cas";
+
+/// Suffix of the entries being created in the content-addressed outputs directory.
+pub const CONTENT_ADDRESSED_OUTPUTS_TMP_SUFFIX: &str = ".tmp";
+
+/// IoRequest we dispatch to the blocking executor to delete the content-addressed outputs whose
+/// originals were deleted from buck-out.
+///
+/// The entries are made of hardlinks to the outputs, so they take no space of their own until
+/// those are deleted (or replaced). Once an entry holds a file nothing else links to, it is
+/// deleted too.
+pub struct CleanContentAddressedOutputs {
+    pub path: ProjectRelativePathBuf,
+}
+
+impl IoRequest for CleanContentAddressedOutputs {
+    fn execute(self: Box<Self>, project_fs: &ProjectRoot) -> anyhow::Result<()> {
+        let deleted = clean_content_addressed_outputs(&project_fs.resolve(&self.path))
+            .with_context(|| format!("Error cleaning up `{}`", self.path))?;
+        if deleted > 0 {
+            tracing::info!("Deleted {} content-addressed outputs", deleted);
+        }
+        Ok(())
+    }
+}
+
+/// Delete the entries of `cas_dir` which hold a file nothing else links to, and return how many
+/// were deleted.
+pub fn clean_content_addressed_outputs(cas_dir: &AbsNormPath) -> anyhow::Result<u64> {
+    let entries = match fs_util::read_dir_if_exists(cas_dir)? {
+        Some(entries) => entries,
+        None => return Ok(0),
+    };
+    let mut deleted = 0;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_name().to_str().map_or(false, |name| {
+            name.ends_with(CONTENT_ADDRESSED_OUTPUTS_TMP_SUFFIX)
+        }) {
+            // Being created by a build.
+            continue;
+        }
+        let path = entry.path();
+        if holds_unlinked_file(&path)? {
+            fs_util::remove_all(&path)?;
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+fn holds_unlinked_file(path: &AbsNormPath) -> anyhow::Result<bool> {
+    let metadata = fs_util::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        for entry in fs_util::read_dir(path)? {
+            if holds_unlinked_file(&entry?.path())? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    } else if metadata.is_file() {
+        #[cfg(unix)]
+        {
+            Ok(std::os::unix::fs::MetadataExt::nlink(&metadata) == 1)
+        }
+        // Link counts are not available here, so the entries are never deleted.
+        #[cfg(not(unix))]
+        {
+            Ok(false)
+        }
+    } else {
+        Ok(false)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+    use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+
+    use super::*;
+
+    #[test]
+    fn test_clean_content_addressed_outputs() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let path = |p: &str| root.join(ForwardRelativePath::unchecked_new(p));
+        fs_util::create_dir_all(path("gen"))?;
+        fs_util::create_dir_all(path("cas/linked.dir"))?;
+        fs_util::create_dir_all(path("cas/unlinked.dir"))?;
+
+        fs_util::write(path("gen/a"), "a")?;
+        fs_util::hardlink(path("gen/a"), path("cas/linked"))?;
+        fs_util::hardlink(path("gen/a"), path("cas/linked.dir/a"))?;
+        fs_util::write(path("cas/unlinked"), "b")?;
+        fs_util::hardlink(path("gen/a"), path("cas/unlinked.dir/a"))?;
+        fs_util::write(path("cas/unlinked.dir/b"), "b")?;
+        fs_util::write(path("cas/unlinked.tmp"), "b")?;
+
+        assert_eq!(clean_content_addressed_outputs(&path("cas"))?, 2);
+
+        let mut remaining = fs_util::read_dir(path("cas"))?
+            .map(|entry| Ok(entry?.file_name().into_string().unwrap()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        remaining.sort();
+        assert_eq!(remaining, vec!["linked", "linked.dir", "unlinked.tmp"]);
+        Ok(())
+    }
+}
@@ -175,7 +175,10 @@ impl CommandExecutor {
                 } else {
                     None
                 },
-                self.0.re_platform.clone(),
+                re_platform_with_properties(
+                    &self.0.re_platform,
+                    request.remote_execution_properties(),
+                ),
                 false,
                 digest_config,
                 self.0.options.output_paths_behavior,
@@ -186,6 +189,31 @@ impl CommandExecutor {
     }
 }
 
+/// The platform of the executor, with the properties set by the command added or overridden.
+fn re_platform_with_properties(
+    platform: &RE::Platform,
+    properties: &SortedVectorMap<String, String>,
+) -> RE::Platform {
+    if properties.is_empty() {
+        return platform.clone();
+    }
+    let mut merged: SortedVectorMap<&str, &str> = platform
+        .properties
+        .iter()
+        .map(|p| (p.name.as_str(), p.value.as_str()))
+        .collect();
+    merged.extend(properties.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+    RE::Platform {
+        properties: merged
+            .into_iter()
+            .map(|(name, value)| RE::Property {
+                name: name.to_owned(),
+                value: value.to_owned(),
+            })
+            .collect(),
+    }
+}
+
 fn re_create_action(
     args: Vec<String>,
     outputs: &[(ProjectRelativePathBuf, OutputType)],
@@ -286,3 +314,48 @@ fn re_create_action(
             .expect("We did put a platform a few lines up"),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use remote_execution as RE;
+    use sorted_vector_map::SortedVectorMap;
+
+    use crate::execute::command_executor::re_platform_with_properties;
+
+    #[test]
+    fn test_re_platform_with_properties() {
+        let platform = RE::Platform {
+            properties: vec![
+                RE::Property {
+                    name: "OSFamily".to_owned(),
+                    value: "linux".to_owned(),
+                },
+                RE::Property {
+                    name: "container-image".to_owned(),
+                    value: "default".to_owned(),
+                },
+            ],
+        };
+        let properties: SortedVectorMap<String, String> = [
+            ("container-image".to_owned(), "cuda".to_owned()),
+            ("gpu".to_owned(), "1".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+
+        let merged = re_platform_with_properties(&platform, &properties);
+        let merged: Vec<(&str, &str)> = merged
+            .properties
+            .iter()
+            .map(|p| (p.name.as_str(), p.value.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("OSFamily", "linux"),
+                ("container-image", "cuda"),
+                ("gpu", "1")
+            ],
+            merged
+        );
+    }
+}
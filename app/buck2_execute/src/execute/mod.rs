@@ -12,6 +12,7 @@ pub mod blobs;
 pub mod blocking;
 pub mod cache_uploader;
 pub mod claim;
+pub mod clean_content_addressed_outputs;
 pub mod clean_output_paths;
 pub mod command_executor;
 pub mod dice_data;
@@ -29,6 +29,7 @@ use derive_more::Display;
 use dupe::Dupe;
 use gazebo::variants::UnpackVariants;
 use host_sharing::host_sharing::HostSharingRequirements;
+use host_sharing::ResourceWeights;
 use indexmap::IndexSet;
 use itertools::Itertools;
 use sorted_vector_map::SortedVectorMap;
@@ -236,6 +237,11 @@ pub struct WorkerId(pub u64);
 pub struct WorkerSpec {
     pub id: WorkerId,
     pub exe: Vec<String>,
+    /// Digest of the artifacts the worker executable is made of, if known. A worker is replaced
+    /// when they change, even if its command line does not.
+    pub inputs_digest: Option<TrackedFileDigest>,
+    /// Maximum number of commands the worker runs at once, unlimited if `None`.
+    pub concurrency: Option<usize>,
 }
 
 /// The data contains the information about the command to be executed.
@@ -251,6 +257,8 @@ pub struct CommandExecutionRequest {
     // Run with a custom $TMPDIR, or just the standard system one
     custom_tmpdir: Option<BuckOutScratchPath>,
     host_sharing_requirements: HostSharingRequirements,
+    /// Memory and io required by the command when it runs locally.
+    resource_weights: ResourceWeights,
     // Used to disable the low pass filter for concurrent local actions. Enabled by default
     low_pass_filter: bool,
     /// Working directory, relative to the project root.
@@ -261,6 +269,9 @@ pub struct CommandExecutionRequest {
     prefetch_lossy_stderr: bool,
     /// Whether to cleanup outputs
     pub outputs_cleanup: bool,
+    /// Whether the results of the command may be written to caches, e.g. because it opted into
+    /// cache uploads.
+    allow_cache_upload: bool,
     /// What environment variables to inherit from the Buck2 daemon.
     local_environment_inheritance: Option<EnvironmentInheritance>,
     /// Whether this command should override the fallback-only behavior on an hybrid executor and
@@ -271,6 +282,9 @@ pub struct CommandExecutionRequest {
     required_local_resources: SortedSet<LocalResourceState>,
     /// Persistent worker to use for execution
     worker: Option<WorkerSpec>,
+    /// RE platform properties added to (or overriding) those of the executor when the command
+    /// runs remotely.
+    remote_execution_properties: SortedVectorMap<String, String>,
 }
 
 impl CommandExecutionRequest {
@@ -289,15 +303,18 @@ impl CommandExecutionRequest {
             executor_preference: ExecutorPreference::Default,
             custom_tmpdir: None,
             host_sharing_requirements: HostSharingRequirements::default(),
+            resource_weights: ResourceWeights::default(),
             low_pass_filter: true,
             working_directory: None,
             prefetch_lossy_stderr: false,
             outputs_cleanup: true,
+            allow_cache_upload: false,
             local_environment_inheritance: None,
             force_full_hybrid_if_capable: false,
             disable_miniperf: false,
             required_local_resources: SortedSet::new(),
             worker: None,
+            remote_execution_properties: SortedVectorMap::new(),
         }
     }
 
@@ -332,6 +349,11 @@ impl CommandExecutionRequest {
         self
     }
 
+    pub fn with_resource_weights(mut self, resource_weights: ResourceWeights) -> Self {
+        self.resource_weights = resource_weights;
+        self
+    }
+
     pub fn with_low_pass_filter(mut self, low_pass_filter: bool) -> Self {
         self.low_pass_filter = low_pass_filter;
         self
@@ -352,6 +374,24 @@ impl CommandExecutionRequest {
         self
     }
 
+    pub fn with_allow_cache_upload(mut self, allow_cache_upload: bool) -> Self {
+        self.allow_cache_upload = allow_cache_upload;
+        self
+    }
+
+    pub fn allow_cache_upload(&self) -> bool {
+        self.allow_cache_upload
+    }
+
+    /// Whether the command may be served from and recorded in the local action cache: it must
+    /// have opted into caching like for cache uploads, and not require local execution, which
+    /// usually means it isn't hermetic.
+    pub fn allows_local_action_cache(&self) -> bool {
+        self.allow_cache_upload
+            && self.outputs_cleanup
+            && !self.executor_preference.requires_local()
+    }
+
     pub fn prefetch_lossy_stderr(&self) -> bool {
         self.prefetch_lossy_stderr
     }
@@ -413,6 +453,10 @@ impl CommandExecutionRequest {
         &self.host_sharing_requirements
     }
 
+    pub fn resource_weights(&self) -> &ResourceWeights {
+        &self.resource_weights
+    }
+
     pub fn low_pass_filter(&self) -> bool {
         self.low_pass_filter
     }
@@ -442,6 +486,18 @@ impl CommandExecutionRequest {
         self.force_full_hybrid_if_capable
     }
 
+    pub fn with_remote_execution_properties(
+        mut self,
+        remote_execution_properties: SortedVectorMap<String, String>,
+    ) -> Self {
+        self.remote_execution_properties = remote_execution_properties;
+        self
+    }
+
+    pub fn remote_execution_properties(&self) -> &SortedVectorMap<String, String> {
+        &self.remote_execution_properties
+    }
+
     pub fn with_disable_miniperf(mut self, disable_miniperf: bool) -> Self {
         self.disable_miniperf = disable_miniperf;
         self
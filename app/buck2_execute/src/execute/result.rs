@@ -103,6 +103,13 @@ pub struct CommandExecutionMetadata {
 
     /// How long it took to materialize the action's inputs.
     pub input_materialization_duration: Duration,
+
+    /// How long this command waited for local resources (permits, memory and io budgets, local
+    /// resources) before it could start.
+    pub local_queue_time: Option<Duration>,
+
+    /// How long it took to upload the outputs to the CAS on RE, or to hash them locally.
+    pub output_upload_duration: Duration,
 }
 
 impl Default for CommandExecutionMetadata {
@@ -114,6 +121,8 @@ impl Default for CommandExecutionMetadata {
             start_time: SystemTime::now(),
             execution_stats: None,
             input_materialization_duration: Duration::default(),
+            local_queue_time: None,
+            output_upload_duration: Duration::default(),
         }
     }
 }
@@ -17,4 +17,11 @@ pub struct ExecutorGlobalKnobs {
     /// Whether to emit action keys to execution logs (thos are pretty verbose and omitted by
     /// default).
     pub log_action_keys: bool,
+
+    /// Whether to run local actions in a sandbox restricting them to their declared inputs.
+    pub sandbox_local_actions: bool,
+
+    /// Whether to send the output of local actions while they run, rather than only once they
+    /// finish.
+    pub live_action_output: bool,
 }
@@ -87,8 +87,9 @@ pub enum MaterializationError {
         "Your build requires materializing an artifact that has expired in the \
         RE CAS and Buck does not have it (path: {}, digest origin: {}). \
         This likely happened because your Buck daemon \
-        has been online for a long time. This error is currently unrecoverable. \
-        To proceed, you should restart Buck using `buck2 killall` (debug info: {})",
+        has been online for a long time. Actions needing an expired input re-run the \
+        action producing it (see `buck2.cas_backfill_max_depth`), if this error persists \
+        you should restart Buck using `buck2 killall` (debug info: {})",
         .path,
         .info.origin.as_display_for_not_found(),
         .debug
@@ -17,6 +17,7 @@ use buck2_core::env_helper::EnvHelper;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_re_configuration::RemoteExecutionRetryConfiguration;
 use buck2_re_configuration::RemoteExecutionStaticMetadata;
 use buck2_re_configuration::RemoteExecutionStaticMetadataImpl;
 use chrono::DateTime;
@@ -69,6 +70,8 @@ use crate::knobs::ExecutorGlobalKnobs;
 use crate::materialize::materializer::Materializer;
 use crate::re::action_identity::ReActionIdentity;
 use crate::re::metadata::RemoteExecutionMetadataExt;
+use crate::re::retry::CircuitBreaker;
+use crate::re::retry::Retrier;
 use crate::re::stats::OpStats;
 use crate::re::stats::RemoteExecutionClientOpStats;
 use crate::re::stats::RemoteExecutionClientStats;
@@ -97,6 +100,7 @@ struct RemoteExecutionClientData {
     materializes: OpStats,
     write_action_results: OpStats,
     get_digest_expirations: OpStats,
+    retrier: Retrier,
 }
 
 impl RemoteExecutionClient {
@@ -104,6 +108,8 @@ impl RemoteExecutionClient {
         fb: FacebookInit,
         skip_remote_cache: bool,
         static_metadata: Arc<RemoteExecutionStaticMetadata>,
+        retry_config: RemoteExecutionRetryConfiguration,
+        circuit_breaker: Arc<CircuitBreaker>,
         logs_dir_path: Option<&AbsNormPath>,
         buck_out_path: &AbsNormPath,
     ) -> anyhow::Result<Self> {
@@ -126,6 +132,7 @@ impl RemoteExecutionClient {
                 materializes: OpStats::default(),
                 write_action_results: OpStats::default(),
                 get_digest_expirations: OpStats::default(),
+                retrier: Retrier::new(retry_config, circuit_breaker),
             }),
         })
     }
@@ -135,6 +142,8 @@ impl RemoteExecutionClient {
         skip_remote_cache: bool,
         times: usize, // 0 is treated as 1
         static_metadata: Arc<RemoteExecutionStaticMetadata>,
+        retry_config: RemoteExecutionRetryConfiguration,
+        circuit_breaker: Arc<CircuitBreaker>,
         logs_dir_path: Option<&AbsNormPath>,
         buck_out_path: &AbsNormPath,
     ) -> anyhow::Result<Self> {
@@ -144,6 +153,8 @@ impl RemoteExecutionClient {
                 fb,
                 skip_remote_cache,
                 static_metadata.dupe(),
+                retry_config.clone(),
+                circuit_breaker.dupe(),
                 logs_dir_path,
                 buck_out_path,
             )
@@ -164,6 +175,8 @@ impl RemoteExecutionClient {
             fb,
             skip_remote_cache,
             static_metadata,
+            retry_config,
+            circuit_breaker,
             logs_dir_path,
             buck_out_path,
         )
@@ -184,7 +197,14 @@ impl RemoteExecutionClient {
     ) -> anyhow::Result<Option<ActionResultResponse>> {
         self.data
             .action_cache
-            .op(self.data.client.action_cache(action_digest, use_case))
+            .op(self
+                .data
+                .retrier
+                .retry(&self.data.retrier.config().action_cache, || {
+                    self.data
+                        .client
+                        .action_cache(action_digest.dupe(), use_case)
+                }))
             .await
     }
 
@@ -202,16 +222,18 @@ impl RemoteExecutionClient {
             .uploads
             .op(self
                 .data
-                .client
-                .upload(
-                    fs,
-                    materializer,
-                    blobs,
-                    dir_path,
-                    input_dir,
-                    use_case,
-                    digest_config,
-                )
+                .retrier
+                .retry(&self.data.retrier.config().upload, || {
+                    self.data.client.upload(
+                        fs,
+                        materializer,
+                        blobs,
+                        dir_path,
+                        input_dir,
+                        use_case,
+                        digest_config,
+                    )
+                })
                 .map_err(|e| self.decorate_error(e)))
             .await
     }
@@ -227,13 +249,15 @@ impl RemoteExecutionClient {
             .uploads
             .op(self
                 .data
-                .client
-                .upload_files_and_directories(
-                    files_with_digest,
-                    directories,
-                    inlined_blobs_with_digest,
-                    use_case,
-                )
+                .retrier
+                .retry(&self.data.retrier.config().upload, || {
+                    self.data.client.upload_files_and_directories(
+                        files_with_digest.clone(),
+                        directories.clone(),
+                        inlined_blobs_with_digest.clone(),
+                        use_case,
+                    )
+                })
                 .map_err(|e| self.decorate_error(e)))
             .await
     }
@@ -252,21 +276,30 @@ impl RemoteExecutionClient {
     ) -> anyhow::Result<ExecuteResponseOrCancelled> {
         self.data
             .executes
-            .op(self
-                .data
-                .client
-                .execute(
-                    action_digest,
-                    platform,
-                    use_case,
-                    identity,
-                    manager,
-                    skip_cache_read,
-                    skip_cache_write,
-                    re_max_queue_time,
-                    knobs,
-                )
-                .map_err(|e| self.decorate_error(e)))
+            .op(async {
+                // The manager is borrowed by every attempt, so this can't use `retry`.
+                let mut attempts = self
+                    .data
+                    .retrier
+                    .attempts(&self.data.retrier.config().execute);
+                loop {
+                    let attempt = self.data.client.execute(
+                        action_digest.dupe(),
+                        platform,
+                        use_case,
+                        identity,
+                        manager,
+                        skip_cache_read,
+                        skip_cache_write,
+                        re_max_queue_time,
+                        knobs,
+                    );
+                    if let Some(res) = attempts.run(attempt).await {
+                        break res;
+                    }
+                }
+            }
+            .map_err(|e| self.decorate_error(e)))
             .await
     }
 
@@ -277,7 +310,12 @@ impl RemoteExecutionClient {
     ) -> anyhow::Result<()> {
         self.data
             .materializes
-            .op(self.data.client.materialize_files(files, use_case))
+            .op(self
+                .data
+                .retrier
+                .retry(&self.data.retrier.config().download, || {
+                    self.data.client.materialize_files(files.clone(), use_case)
+                }))
             .await
     }
 
@@ -290,8 +328,12 @@ impl RemoteExecutionClient {
             .downloads
             .op(self
                 .data
-                .client
-                .download_typed_blobs(digests, use_case)
+                .retrier
+                .retry(&self.data.retrier.config().download, || {
+                    self.data
+                        .client
+                        .download_typed_blobs(digests.clone(), use_case)
+                })
                 .map_err(|e| self.decorate_error(e)))
             .await
     }
@@ -305,8 +347,10 @@ impl RemoteExecutionClient {
             .downloads
             .op(self
                 .data
-                .client
-                .download_blob(digest, use_case)
+                .retrier
+                .retry(&self.data.retrier.config().download, || {
+                    self.data.client.download_blob(digest, use_case)
+                })
                 .map_err(|e| self.decorate_error(e)))
             .await
     }
@@ -320,8 +364,10 @@ impl RemoteExecutionClient {
             .uploads
             .op(self
                 .data
-                .client
-                .upload_blob(blob, use_case)
+                .retrier
+                .retry(&self.data.retrier.config().upload, || {
+                    self.data.client.upload_blob(blob.clone(), use_case)
+                })
                 .map_err(|e| self.decorate_error(e)))
             .await
     }
@@ -335,8 +381,12 @@ impl RemoteExecutionClient {
             .get_digest_expirations
             .op(self
                 .data
-                .client
-                .get_digest_expirations(digests, use_case)
+                .retrier
+                .retry(&self.data.retrier.config().find_missing, || {
+                    self.data
+                        .client
+                        .get_digest_expirations(digests.clone(), use_case)
+                })
                 .map_err(|e| self.decorate_error(e)))
             .await
     }
@@ -351,8 +401,12 @@ impl RemoteExecutionClient {
             .write_action_results
             .op(self
                 .data
-                .client
-                .write_action_result(digest, result, use_case)
+                .retrier
+                .retry(&self.data.retrier.config().write_action_result, || {
+                    self.data
+                        .client
+                        .write_action_result(digest.clone(), result.clone(), use_case)
+                })
                 .map_err(|e| self.decorate_error(e)))
             .await
     }
@@ -390,6 +444,8 @@ impl RemoteExecutionClient {
             get_digest_expirations: RemoteExecutionClientOpStats::from(
                 &self.data.get_digest_expirations,
             ),
+            retries: self.data.retrier.retries(),
+            circuit_breaker_open: self.data.retrier.circuit_breaker().is_open(),
         })
     }
 }
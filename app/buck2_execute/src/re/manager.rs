@@ -25,6 +25,7 @@ use buck2_core::async_once_cell::AsyncOnceCell;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_re_configuration::RemoteExecutionRetryConfiguration;
 use buck2_re_configuration::RemoteExecutionStaticMetadata;
 use chrono::DateTime;
 use chrono::Utc;
@@ -51,6 +52,7 @@ use crate::re::action_identity::ReActionIdentity;
 use crate::re::client::ExecuteResponseOrCancelled;
 use crate::re::client::RemoteExecutionClient;
 use crate::re::re_get_session_id::ReGetSessionId;
+use crate::re::retry::CircuitBreaker;
 use crate::re::stats::RemoteExecutionClientStats;
 use crate::re::uploader::UploadStats;
 
@@ -87,6 +89,10 @@ struct RemoteExecutionConfig {
     /// number of retries when attempting the initial RE connection
     connection_retries: usize,
     static_metadata: Arc<RemoteExecutionStaticMetadata>,
+    #[allocative(skip)]
+    retry_config: RemoteExecutionRetryConfiguration,
+    /// Shared by successive connections, so that RE stays unhealthy across commands.
+    circuit_breaker: Arc<CircuitBreaker>,
     logs_dir_path: Option<AbsNormPathBuf>,
     buck_out_path: AbsNormPathBuf,
 }
@@ -98,6 +104,8 @@ impl RemoteExecutionConfig {
             self.skip_remote_cache,
             self.connection_retries,
             self.static_metadata.dupe(),
+            self.retry_config.clone(),
+            self.circuit_breaker.dupe(),
             self.logs_dir_path.as_deref(),
             &self.buck_out_path,
         )
@@ -199,6 +207,7 @@ impl ReConnectionManager {
         skip_remote_cache: bool,
         connection_retries: usize,
         static_metadata: Arc<RemoteExecutionStaticMetadata>,
+        retry_config: RemoteExecutionRetryConfiguration,
         logs_dir_path: Option<AbsNormPathBuf>,
         buck_out_path: AbsNormPathBuf,
     ) -> Self {
@@ -209,6 +218,8 @@ impl ReConnectionManager {
                 skip_remote_cache,
                 connection_retries,
                 static_metadata,
+                circuit_breaker: Arc::new(CircuitBreaker::new(&retry_config)),
+                retry_config,
                 logs_dir_path,
                 buck_out_path,
             },
@@ -304,6 +315,15 @@ impl ManagedRemoteExecutionClient {
             .context("Internal error: the underlying RE connection has terminated because the corresponding guard has been dropped.")
     }
 
+    /// Whether RE is healthy, i.e. calls to it have not been failing repeatedly. While it is not,
+    /// actions which can run locally should.
+    pub fn is_healthy(&self) -> bool {
+        match self.lock() {
+            Ok(client) => !client.config.circuit_breaker.is_open(),
+            Err(_) => true,
+        }
+    }
+
     pub async fn action_cache(
         &self,
         action_digest: ActionDigest,
@@ -11,8 +11,10 @@ pub mod action_identity;
 pub mod client;
 pub mod manager;
 pub mod metadata;
+pub mod output_trees_cache;
 pub mod re_get_session_id;
 pub mod remote_action_result;
+pub mod retry;
 mod stats;
 pub mod streams;
 pub mod uploader;
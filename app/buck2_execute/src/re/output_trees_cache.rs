@@ -0,0 +1,125 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! `Tree`s of the output directories of RE actions, by digest.
+//!
+//! Action cache hits return the same trees over and over, and cache uploads produce the trees RE
+//! later returns, so keep them around to skip downloading them again. The directories are still
+//! built from the trees every time: their files expire when the action result says they do.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use buck2_common::file_ops::FileDigest;
+use linked_hash_map::LinkedHashMap;
+use once_cell::sync::Lazy;
+use prost::Message;
+use remote_execution as RE;
+
+/// Maximum total size of the trees to keep, in bytes.
+const CAPACITY_BYTES: usize = 256 << 20;
+
+pub static OUTPUT_TREES_CACHE: Lazy<OutputTreesCache> =
+    Lazy::new(|| OutputTreesCache::new(CAPACITY_BYTES));
+
+struct Trees {
+    trees: LinkedHashMap<FileDigest, Arc<RE::Tree>>,
+    bytes: usize,
+}
+
+/// Least recently used output directory trees, by digest.
+pub struct OutputTreesCache {
+    capacity_bytes: usize,
+    trees: Mutex<Trees>,
+}
+
+impl OutputTreesCache {
+    fn new(capacity_bytes: usize) -> Self {
+        Self {
+            capacity_bytes,
+            trees: Mutex::new(Trees {
+                trees: LinkedHashMap::new(),
+                bytes: 0,
+            }),
+        }
+    }
+
+    pub fn get(&self, tree_digest: &FileDigest) -> Option<Arc<RE::Tree>> {
+        self.trees
+            .lock()
+            .unwrap()
+            .trees
+            .get_refresh(tree_digest)
+            .cloned()
+    }
+
+    pub fn insert(&self, tree_digest: FileDigest, tree: Arc<RE::Tree>) {
+        let size = tree.encoded_len();
+        if size > self.capacity_bytes {
+            return;
+        }
+        let mut trees = self.trees.lock().unwrap();
+        if let Some(old) = trees.trees.insert(tree_digest, tree) {
+            trees.bytes -= old.encoded_len();
+        }
+        trees.bytes += size;
+        while trees.bytes > self.capacity_bytes {
+            match trees.trees.pop_front() {
+                Some((_, evicted)) => trees.bytes -= evicted.encoded_len(),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use buck2_common::file_ops::FileDigest;
+    use prost::Message;
+    use remote_execution as RE;
+
+    use crate::digest_config::DigestConfig;
+    use crate::re::output_trees_cache::OutputTreesCache;
+
+    fn tree(name: &str) -> Arc<RE::Tree> {
+        Arc::new(RE::Tree {
+            root: Some(RE::Directory {
+                files: vec![RE::FileNode {
+                    name: name.to_owned(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let digest_config = DigestConfig::testing_default();
+        let digest = |name: &str| {
+            FileDigest::from_content(name.as_bytes(), digest_config.cas_digest_config())
+        };
+        let (a, b, c) = (tree("a"), tree("b"), tree("c"));
+        let cache = OutputTreesCache::new(a.encoded_len() * 2);
+
+        assert!(cache.get(&digest("a")).is_none());
+        cache.insert(digest("a"), a.clone());
+        cache.insert(digest("b"), b);
+        assert_eq!(Some(a), cache.get(&digest("a")));
+
+        // `b` was used least recently.
+        cache.insert(digest("c"), c.clone());
+        assert!(cache.get(&digest("b")).is_none());
+        assert!(cache.get(&digest("a")).is_some());
+        assert_eq!(Some(c), cache.get(&digest("c")));
+    }
+}
@@ -96,6 +96,7 @@ impl RemoteActionResult for ActionResultResponse {
         let mut timing = timing_from_re_metadata(&self.action_result.execution_metadata);
         timing.wall_time = Duration::ZERO; // This was a cache hit so we didn't wait.
         timing.input_materialization_duration = Duration::ZERO; // This was a cache hit so we didn't wait.
+        timing.output_upload_duration = Duration::ZERO;
         timing
     }
 
@@ -139,6 +140,10 @@ fn timing_from_re_metadata(meta: &TExecutedActionMetadata) -> CommandExecutionMe
         .input_fetch_completed_timestamp
         .saturating_duration_since(&meta.input_fetch_start_timestamp);
 
+    let output_upload_duration = meta
+        .output_upload_completed_timestamp
+        .saturating_duration_since(&meta.output_upload_start_timestamp);
+
     CommandExecutionMetadata {
         wall_time: execution_time,
         re_queue_time: Some(re_queue_time),
@@ -146,6 +151,8 @@ fn timing_from_re_metadata(meta: &TExecutedActionMetadata) -> CommandExecutionMe
         start_time,
         execution_stats,
         input_materialization_duration: fetch_input_time,
+        local_queue_time: None,
+        output_upload_duration,
     }
 }
 
@@ -0,0 +1,389 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Retries of RE calls, and a circuit breaker tracking whether RE is healthy.
+//!
+//! Calls failing with a transient error are retried with exponential backoff, and every attempt
+//! is bounded by the deadline of its kind of call. Once enough calls in a row have failed after
+//! their retries, the circuit breaker opens: while it is open, actions which can run locally do
+//! instead of waiting on RE. After a cooldown, calls decide again whether it closes or opens.
+
+use std::future::Future;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use allocative::Allocative;
+use buck2_re_configuration::RemoteExecutionRetryConfiguration;
+use buck2_re_configuration::RetryPolicy;
+use remote_execution::REClientError;
+use remote_execution::TCode;
+
+#[derive(Debug, thiserror::Error)]
+enum RetryError {
+    #[error("RE call timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+/// Whether an error may go away by trying again: attempts timing out, and the status codes RE
+/// uses when it is unavailable or overloaded, whether the client reports them as an
+/// `REClientError` or, for errors of the transport, as the gRPC status itself. Anything else,
+/// including errors we can't classify, is returned as is.
+fn is_transient(e: &anyhow::Error) -> bool {
+    if e.downcast_ref::<RetryError>().is_some() {
+        return true;
+    }
+    if let Some(e) = e.downcast_ref::<REClientError>() {
+        return [
+            TCode::UNAVAILABLE,
+            TCode::DEADLINE_EXCEEDED,
+            TCode::RESOURCE_EXHAUSTED,
+            TCode::ABORTED,
+        ]
+        .contains(&e.code);
+    }
+    match e.downcast_ref::<tonic::Status>() {
+        Some(status) => matches!(
+            status.code(),
+            tonic::Code::Unavailable
+                | tonic::Code::DeadlineExceeded
+                | tonic::Code::ResourceExhausted
+                | tonic::Code::Aborted
+        ),
+        None => false,
+    }
+}
+
+/// Tracks consecutive failures of RE calls, shared by all the RE connections of the daemon.
+#[derive(Allocative)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    #[allocative(skip)]
+    cooldown: Duration,
+    #[allocative(skip)]
+    state: Mutex<CircuitBreakerState>,
+}
+
+#[derive(Default)]
+struct CircuitBreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: &RemoteExecutionRetryConfiguration) -> Self {
+        Self {
+            threshold: config.circuit_breaker_threshold,
+            cooldown: config.circuit_breaker_cooldown,
+            state: Mutex::new(CircuitBreakerState::default()),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open_at(Instant::now())
+    }
+
+    fn is_open_at(&self, now: Instant) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .open_until
+            .map_or(false, |until| now < until)
+    }
+
+    fn record(&self, success: bool) {
+        self.record_at(success, Instant::now())
+    }
+
+    fn record_at(&self, success: bool, now: Instant) {
+        if self.threshold == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if success {
+            if state.open_until.take().is_some() {
+                tracing::info!("RE is healthy again");
+            }
+            state.consecutive_failures = 0;
+            return;
+        }
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        // Once the cooldown is over, a single failure is enough to open the breaker again.
+        if state.consecutive_failures >= self.threshold
+            && state.open_until.map_or(true, |until| now >= until)
+        {
+            tracing::warn!(
+                "{} RE calls failed in a row, running actions locally for {:?}",
+                state.consecutive_failures,
+                self.cooldown
+            );
+            state.open_until = Some(now + self.cooldown);
+        }
+    }
+}
+
+/// Retries the calls of one RE connection.
+#[derive(Allocative)]
+pub(crate) struct Retrier {
+    #[allocative(skip)]
+    config: RemoteExecutionRetryConfiguration,
+    circuit_breaker: Arc<CircuitBreaker>,
+    /// Number of retries done so far.
+    retries: AtomicU32,
+}
+
+impl Retrier {
+    pub(crate) fn new(
+        config: RemoteExecutionRetryConfiguration,
+        circuit_breaker: Arc<CircuitBreaker>,
+    ) -> Self {
+        Self {
+            config,
+            circuit_breaker,
+            retries: AtomicU32::new(0),
+        }
+    }
+
+    pub(crate) fn config(&self) -> &RemoteExecutionRetryConfiguration {
+        &self.config
+    }
+
+    pub(crate) fn retries(&self) -> u32 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn circuit_breaker(&self) -> &Arc<CircuitBreaker> {
+        &self.circuit_breaker
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.config
+            .initial_backoff
+            .checked_mul(2u32.saturating_pow(attempt))
+            .map_or(self.config.max_backoff, |b| b.min(self.config.max_backoff))
+    }
+
+    /// Attempts of a call which can't be expressed as a closure, e.g. because it borrows mutable
+    /// state.
+    pub(crate) fn attempts<'a>(&'a self, policy: &'a RetryPolicy) -> Attempts<'a> {
+        Attempts {
+            retrier: self,
+            policy,
+            attempt: 0,
+        }
+    }
+
+    /// Run the call `f` makes, retrying it according to `policy`.
+    pub(crate) async fn retry<T, F, Fut>(&self, policy: &RetryPolicy, mut f: F) -> anyhow::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let mut attempts = self.attempts(policy);
+        loop {
+            if let Some(res) = attempts.run(f()).await {
+                return res;
+            }
+        }
+    }
+}
+
+pub(crate) struct Attempts<'a> {
+    retrier: &'a Retrier,
+    policy: &'a RetryPolicy,
+    attempt: u32,
+}
+
+impl<'a> Attempts<'a> {
+    /// Run one attempt. Returns `None` if it should be retried, once the backoff has elapsed.
+    pub(crate) async fn run<T>(
+        &mut self,
+        fut: impl Future<Output = anyhow::Result<T>>,
+    ) -> Option<anyhow::Result<T>> {
+        let res = match self.policy.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, fut).await {
+                Ok(res) => res,
+                Err(_) => Err(RetryError::Timeout(timeout).into()),
+            },
+            None => fut.await,
+        };
+        match res {
+            Err(e) if is_transient(&e) && self.attempt < self.policy.retries => {
+                let backoff = self.retrier.backoff(self.attempt);
+                tracing::debug!("Retrying RE call in {:?}: {:#}", backoff, e);
+                self.attempt += 1;
+                self.retrier.retries.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(backoff).await;
+                None
+            }
+            res => {
+                self.retrier
+                    .circuit_breaker
+                    .record(res.as_ref().map_or_else(|e| !is_transient(e), |_| true));
+                Some(res)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use buck2_re_configuration::RemoteExecutionRetryConfiguration;
+    use buck2_re_configuration::RetryPolicy;
+    use remote_execution::REClientError;
+    use remote_execution::TCode;
+
+    use crate::re::retry::CircuitBreaker;
+    use crate::re::retry::Retrier;
+
+    fn config() -> RemoteExecutionRetryConfiguration {
+        RemoteExecutionRetryConfiguration {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            circuit_breaker_threshold: 2,
+            circuit_breaker_cooldown: Duration::from_secs(30),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let config = config();
+        let retrier = Retrier::new(config.clone(), Arc::new(CircuitBreaker::new(&config)));
+        assert_eq!(Duration::from_millis(1), retrier.backoff(0));
+        assert_eq!(Duration::from_millis(4), retrier.backoff(2));
+        assert_eq!(Duration::from_millis(5), retrier.backoff(3));
+        assert_eq!(Duration::from_millis(5), retrier.backoff(100));
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(&config());
+        let now = Instant::now();
+        breaker.record_at(false, now);
+        assert!(!breaker.is_open_at(now));
+        breaker.record_at(false, now);
+        assert!(breaker.is_open_at(now));
+
+        // Half-open after the cooldown: one failure opens it again, one success closes it.
+        let later = now + Duration::from_secs(31);
+        assert!(!breaker.is_open_at(later));
+        breaker.record_at(false, later);
+        assert!(breaker.is_open_at(later));
+        let even_later = later + Duration::from_secs(31);
+        breaker.record_at(true, even_later);
+        breaker.record_at(false, even_later);
+        assert!(!breaker.is_open_at(even_later));
+    }
+
+    #[tokio::test]
+    async fn test_retry() -> anyhow::Result<()> {
+        let config = config();
+        let retrier = Retrier::new(config.clone(), Arc::new(CircuitBreaker::new(&config)));
+        let policy = RetryPolicy {
+            retries: 2,
+            timeout: None,
+        };
+
+        let calls = AtomicU32::new(0);
+        let res = retrier
+            .retry(&policy, || async {
+                if calls.fetch_add(1, Ordering::Relaxed) < 2 {
+                    Err(REClientError {
+                        code: TCode::UNAVAILABLE,
+                        message: "unavailable".to_owned(),
+                    }
+                    .into())
+                } else {
+                    Ok(())
+                }
+            })
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(3, calls.load(Ordering::Relaxed));
+        assert_eq!(2, retrier.retries());
+
+        // So are the errors of the transport, which the client returns as the gRPC status.
+        let calls = AtomicU32::new(0);
+        let res = retrier
+            .retry(&policy, || async {
+                if calls.fetch_add(1, Ordering::Relaxed) < 1 {
+                    Err(tonic::Status::unavailable("connection reset").into())
+                } else {
+                    Ok(())
+                }
+            })
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(2, calls.load(Ordering::Relaxed));
+        assert_eq!(3, retrier.retries());
+
+        // Errors about the request itself are not retried, and RE is still healthy.
+        let calls = AtomicU32::new(0);
+        let res: anyhow::Result<()> = retrier
+            .retry(&policy, || async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err(REClientError {
+                    code: TCode::INVALID_ARGUMENT,
+                    message: "invalid".to_owned(),
+                }
+                .into())
+            })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(1, calls.load(Ordering::Relaxed));
+        assert!(!retrier.circuit_breaker().is_open());
+
+        // Neither are errors which aren't known to be transient.
+        let errors: [fn() -> anyhow::Error; 3] = [
+            || anyhow::anyhow!("unknown"),
+            || tonic::Status::permission_denied("denied").into(),
+            || {
+                REClientError {
+                    code: TCode(13),
+                    message: "internal".to_owned(),
+                }
+                .into()
+            },
+        ];
+        for err in errors {
+            let calls = AtomicU32::new(0);
+            let res: anyhow::Result<()> = retrier
+                .retry(&policy, || async {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    Err(err())
+                })
+                .await;
+            assert!(res.is_err());
+            assert_eq!(1, calls.load(Ordering::Relaxed));
+        }
+        assert!(!retrier.circuit_breaker().is_open());
+
+        // Transport errors which persist after the retries mean RE is unhealthy.
+        for _ in 0..2 {
+            let res: anyhow::Result<()> = retrier
+                .retry(&policy, || async {
+                    Err(tonic::Status::unavailable("unavailable").into())
+                })
+                .await;
+            assert!(res.is_err());
+        }
+        assert!(retrier.circuit_breaker().is_open());
+        Ok(())
+    }
+}
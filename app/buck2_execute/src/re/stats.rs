@@ -42,6 +42,10 @@ pub struct RemoteExecutionClientStats {
     pub materializes: RemoteExecutionClientOpStats,
     pub write_action_results: RemoteExecutionClientOpStats,
     pub get_digest_expirations: RemoteExecutionClientOpStats,
+    /// Number of calls retried after a transient error.
+    pub retries: u32,
+    /// Whether RE is considered unhealthy, see [crate::re::retry::CircuitBreaker].
+    pub circuit_breaker_open: bool,
 }
 
 #[derive(Default, Allocative)]
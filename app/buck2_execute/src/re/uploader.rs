@@ -26,6 +26,7 @@ use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::soft_error;
 use chrono::Duration;
 use chrono::Utc;
+use dupe::Dupe;
 use futures::FutureExt;
 use gazebo::prelude::*;
 use remote_execution::GetDigestsTtlRequest;
@@ -47,6 +48,7 @@ use crate::directory::ReDirectorySerializer;
 use crate::execute::blobs::ActionBlobs;
 use crate::materialize::materializer::ArtifactNotMaterializedReason;
 use crate::materialize::materializer::CasDownloadInfo;
+use crate::materialize::materializer::MaterializationError;
 use crate::materialize::materializer::Materializer;
 use crate::re::metadata::RemoteExecutionMetadataExt;
 
@@ -251,9 +253,9 @@ impl Uploader {
                     }
                     Err(
                         ref err @ ArtifactNotMaterializedReason::RequiresCasDownload {
+                            ref path,
                             ref entry,
                             ref info,
-                            ..
                         },
                     ) => {
                         if let DirectoryEntry::Leaf(ActionDirectoryMember::File(ref file)) =
@@ -276,14 +278,12 @@ impl Uploader {
                                         action_cache_is_corrupted: info.origin.guaranteed_by_action_cache()
                                     )?;
 
-                                    return Err(anyhow::anyhow!(
-                                        "Your build requires an artifact that has expired in the RE CAS \
-                                        and Buck does not have it. This likely happened because your Buck daemon \
-                                        has been online for a long time. This error is currently unrecoverable. \
-                                        To proceed, you should restart Buck using `buck2 killall`. \
-                                        Debug information: {:#}",
-                                        err
-                                    ));
+                                    return Err(MaterializationError::NotFound {
+                                        path: path.clone(),
+                                        info: info.dupe(),
+                                        debug: format!("{:#}", err).into(),
+                                    }
+                                    .into());
                                 }
 
                                 soft_error!(
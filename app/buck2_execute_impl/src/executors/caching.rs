@@ -7,31 +7,48 @@
  * of this source tree.
  */
 
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::SystemTime;
 
 use anyhow::Context as _;
 use async_trait::async_trait;
 use buck2_common::executor_config::RemoteExecutorUseCase;
+use buck2_common::file_ops::FileDigest;
+use buck2_common::file_ops::FileDigestConfig;
+use buck2_common::file_ops::FileMetadata;
+use buck2_core::directory::Directory;
 use buck2_core::directory::DirectoryEntry;
+use buck2_core::directory::DirectoryIterator;
 use buck2_core::env_helper::EnvHelper;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_events::dispatch::get_dispatcher_opt;
 use buck2_events::dispatch::span_async;
+use buck2_events::dispatch::with_dispatcher_async;
+use buck2_execute::artifact_value::ArtifactValue;
 use buck2_execute::digest::CasDigestToReExt;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::directory::directory_to_re_tree;
 use buck2_execute::directory::ActionDirectoryMember;
 use buck2_execute::execute::action_digest::ActionDigest;
 use buck2_execute::execute::blobs::ActionBlobs;
+use buck2_execute::execute::blocking::BlockingExecutor;
 use buck2_execute::execute::cache_uploader::CacheUploadInfo;
 use buck2_execute::execute::cache_uploader::UploadCache;
 use buck2_execute::execute::kind::CommandExecutionKind;
+use buck2_execute::execute::output::CommandStdStreams;
+use buck2_execute::execute::result::CommandExecutionMetadata;
 use buck2_execute::execute::result::CommandExecutionResult;
 use buck2_execute::execute::result::CommandExecutionStatus;
 use buck2_execute::execute::target::CommandExecutionTarget;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::manager::ManagedRemoteExecutionClient;
+use buck2_execute::re::output_trees_cache::OUTPUT_TREES_CACHE;
 use derive_more::Display;
 use dupe::Dupe;
 use futures::future;
@@ -46,11 +63,13 @@ use remote_execution::TExecutedActionMetadata;
 use remote_execution::TFile;
 use remote_execution::TStatus;
 use remote_execution::TTimestamp;
+use tokio::sync::Semaphore;
 
 // Whether to throw errors when cache uploads fail (primarily for tests).
 static ERROR_ON_CACHE_UPLOAD: EnvHelper<bool> = EnvHelper::new("BUCK2_TEST_ERROR_ON_CACHE_UPLOAD");
 
 /// A PreparedCommandExecutor that will write to cache after invoking the inner executor
+#[derive(Clone)]
 pub struct CacheUploader {
     pub artifact_fs: ArtifactFs,
     pub materializer: Arc<dyn Materializer>,
@@ -58,38 +77,136 @@ pub struct CacheUploader {
     pub re_use_case: RemoteExecutorUseCase,
     pub knobs: ExecutorGlobalKnobs,
     pub max_bytes: Option<u64>,
+    /// When set, uploads run in the background instead of delaying the action.
+    pub background_uploads: Option<Arc<BackgroundCacheUploads>>,
+}
+
+/// Throttles the cache uploads running in the background. This is owned by the daemon, so the
+/// limits apply across all the commands. Uploads beyond `max_queued` are dropped rather than
+/// queued, so that a slow cache does not make them pile up in memory.
+pub struct BackgroundCacheUploads {
+    permits: Arc<Semaphore>,
+    queued: AtomicUsize,
+    max_queued: usize,
+    /// Used to re-hash the outputs before uploading them.
+    blocking_executor: Arc<dyn BlockingExecutor>,
+}
+
+impl BackgroundCacheUploads {
+    pub fn new(
+        max_concurrency: usize,
+        max_queued: usize,
+        blocking_executor: Arc<dyn BlockingExecutor>,
+    ) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrency.max(1))),
+            queued: AtomicUsize::new(0),
+            max_queued,
+            blocking_executor,
+        }
+    }
+
+    fn try_enqueue(&self) -> bool {
+        self.queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |queued| {
+                (queued < self.max_queued).then_some(queued + 1)
+            })
+            .is_ok()
+    }
+
+    fn dequeue(&self) {
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The parts of a local action result which are uploaded. Owned, so that the upload can outlive
+/// the action.
+struct LocalActionResult {
+    outputs: Vec<(ProjectRelativePathBuf, ArtifactValue)>,
+    timing: CommandExecutionMetadata,
+    std_streams: CommandStdStreams,
+    output_bytes: u64,
+}
+
+impl LocalActionResult {
+    /// The action must have been successful and must have run locally (not much point in caching
+    /// something that ran on RE and is already cached).
+    fn new(artifact_fs: &ArtifactFs, result: &CommandExecutionResult) -> Option<Self> {
+        match &result.report.status {
+            CommandExecutionStatus::Success {
+                execution_kind: CommandExecutionKind::Local { .. },
+            } => Some(Self {
+                outputs: result
+                    .resolve_outputs(artifact_fs)
+                    .map(|(output, value)| (output.into_path(), value.dupe()))
+                    .collect(),
+                timing: result.report.timing,
+                std_streams: result.report.std_streams.clone(),
+                output_bytes: result.calc_output_size_bytes(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// A background upload reads the outputs from disk after the action finished, by which point
+    /// a later command may have rebuilt them. Check that they still have the digests the action
+    /// produced, so that we never upload other contents under this action.
+    fn outputs_unchanged(
+        &self,
+        fs: &ProjectRoot,
+        digest_config: DigestConfig,
+    ) -> anyhow::Result<bool> {
+        let config = FileDigestConfig::build(digest_config.cas_digest_config());
+        let unchanged = |path: &ProjectRelativePath, file: &FileMetadata| {
+            FileDigest::from_file_disk(&fs.resolve(path), config)
+                .ok()
+                .as_ref()
+                == Some(file.digest.data())
+        };
+
+        for (path, value) in &self.outputs {
+            match value.entry() {
+                DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) => {
+                    if !unchanged(path, f) {
+                        return Ok(false);
+                    }
+                }
+                DirectoryEntry::Dir(d) => {
+                    let mut walk = d.unordered_walk();
+                    while let Some((entry_path, entry)) = walk.next() {
+                        if let DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) = entry {
+                            if !unchanged(&path.join(entry_path.get()), f) {
+                                return Ok(false);
+                            }
+                        }
+                    }
+                }
+                DirectoryEntry::Leaf(..) => {}
+            }
+        }
+
+        Ok(true)
+    }
 }
 
 impl CacheUploader {
     /// Upload an action result to the RE action cache, assuming conditions for the upload are met:
-    /// the action must have been successful and must have run locally (not much point in caching
-    /// something that ran on RE and is already cached), and cache uploads must be enabled for this particular action.
+    /// cache uploads must be enabled for this particular action.
     /// The CacheUploader should only be used if cache uploads are enabled.
     async fn maybe_perform_cache_upload(
         &self,
-        target: &dyn CommandExecutionTarget,
+        key: buck2_data::ActionKey,
+        name: buck2_data::ActionName,
         digest: &ActionDigest,
-        result: &CommandExecutionResult,
+        result: &LocalActionResult,
         digest_config: DigestConfig,
-    ) -> anyhow::Result<Option<CacheUploadOutcome>> {
-        let output_bytes = result.calc_output_size_bytes();
-
-        match &result.report.status {
-            CommandExecutionStatus::Success {
-                execution_kind: CommandExecutionKind::Local { .. },
-            } => {
-                // Fall through to the upload.
-            }
-            _ => {
-                // In all other cases, skip.
-                return Ok(None);
-            }
-        }
+    ) -> anyhow::Result<CacheUploadOutcome> {
+        let output_bytes = result.output_bytes;
 
-        let outcome = span_async(
+        span_async(
             buck2_data::CacheUploadStart {
-                key: Some(target.as_proto_action_key()),
-                name: Some(target.as_proto_action_name()),
+                key: Some(key.clone()),
+                name: Some(name.clone()),
                 action_digest: digest.to_string(),
             },
             async move {
@@ -135,8 +252,8 @@ impl CacheUploader {
                 (
                     res,
                     Box::new(buck2_data::CacheUploadEnd {
-                        key: Some(target.as_proto_action_key()),
-                        name: Some(target.as_proto_action_name()),
+                        key: Some(key),
+                        name: Some(name),
                         action_digest: digest.to_string(),
                         success,
                         error,
@@ -148,28 +265,26 @@ impl CacheUploader {
                 )
             },
         )
-        .await?;
-
-        Ok(Some(outcome))
+        .await
     }
 
     async fn perform_cache_upload(
         &self,
         digest: &ActionDigest,
-        result: &CommandExecutionResult,
+        result: &LocalActionResult,
         file_digests: &mut Vec<String>,
         tree_digests: &mut Vec<String>,
         digest_config: DigestConfig,
     ) -> anyhow::Result<CacheUploadOutcome> {
         tracing::debug!("Uploading action result for `{}`", digest);
 
-        let timing = result.report.timing;
+        let timing = result.timing;
 
         let mut upload_futs = vec![];
         let mut output_files = vec![];
         let mut output_directories = vec![];
 
-        for (output, value) in result.resolve_outputs(&self.artifact_fs) {
+        for (path, value) in &result.outputs {
             match value.entry().as_ref() {
                 DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) => {
                     output_files.push(TFile {
@@ -182,7 +297,7 @@ impl CacheUploader {
                             },
                             ..Default::default()
                         },
-                        name: output.path().to_string(),
+                        name: path.to_string(),
                         executable: f.is_executable,
                         ..Default::default()
                     });
@@ -191,7 +306,7 @@ impl CacheUploader {
                         let name = self
                             .artifact_fs
                             .fs()
-                            .resolve(output.path())
+                            .resolve(path)
                             .as_maybe_relativized_str()?
                             .to_owned();
 
@@ -216,9 +331,11 @@ impl CacheUploader {
                     let tree = directory_to_re_tree(d);
                     let mut action_blobs = ActionBlobs::new(digest_config);
                     let tree_digest = action_blobs.add_protobuf_message(&tree, digest_config);
+                    // When RE returns this tree later, it does not need to be downloaded.
+                    OUTPUT_TREES_CACHE.insert(tree_digest.data().dupe(), Arc::new(tree));
 
                     output_directories.push(TDirectory2 {
-                        path: output.path().to_string(),
+                        path: path.to_string(),
                         tree_digest: tree_digest.to_re(),
                         root_directory_digest: d.fingerprint().to_re(),
                         ..Default::default()
@@ -230,7 +347,7 @@ impl CacheUploader {
                                 self.artifact_fs.fs(),
                                 &self.materializer,
                                 &action_blobs,
-                                output.path(),
+                                path,
                                 &d.dupe().as_immutable(),
                                 self.re_use_case,
                                 digest_config,
@@ -265,7 +382,6 @@ impl CacheUploader {
 
         let std_streams = async {
             result
-                .report
                 .std_streams
                 .clone()
                 .into_re(&self.re_client, self.re_use_case)
@@ -334,6 +450,8 @@ enum CacheUploadRejectionReason {
     SymlinkOutput,
     #[display(fmt = "OutputExceedsLimit({})", max_bytes)]
     OutputExceedsLimit { max_bytes: u64 },
+    #[display(fmt = "OutputsChanged")]
+    OutputsChanged,
 }
 
 #[async_trait]
@@ -349,24 +467,43 @@ impl UploadCache for CacheUploader {
         };
         let action = &info.action_digest;
 
+        let result = match LocalActionResult::new(&self.artifact_fs, res) {
+            Some(result) => result,
+            None => {
+                tracing::info!("Cache upload for `{}` not attempted", action);
+                return Ok(false);
+            }
+        };
+        let key = info.target.as_proto_action_key();
+        let name = info.target.as_proto_action_name();
+
+        if let Some(background_uploads) = &self.background_uploads {
+            self.spawn_background_upload(
+                background_uploads.dupe(),
+                key,
+                name,
+                action.dupe(),
+                result,
+                info.digest_config,
+            );
+            // The action does not wait for the upload, so it can't tell whether it succeeded.
+            return Ok(false);
+        }
+
         // TODO(bobyf, torozco) should these be critical sections?
         let upload_res = self
-            .maybe_perform_cache_upload(info.target.dupe(), action, res, info.digest_config)
+            .maybe_perform_cache_upload(key, name, action, &result, info.digest_config)
             .await;
 
         match upload_res {
-            Ok(Some(CacheUploadOutcome::Success)) => {
+            Ok(CacheUploadOutcome::Success) => {
                 tracing::info!("Cache upload for `{}` succeeded", action);
                 Ok(true)
             }
-            Ok(Some(CacheUploadOutcome::Rejected(reason))) => {
+            Ok(CacheUploadOutcome::Rejected(reason)) => {
                 tracing::info!("Cache upload for `{}` rejected: {:#}", action, reason);
                 Ok(false)
             }
-            Ok(None) => {
-                tracing::info!("Cache upload for `{}` not attempted", action);
-                Ok(false)
-            }
             Err(error) => {
                 if error_on_cache_upload {
                     return Err(error).context("cache_upload");
@@ -379,6 +516,84 @@ impl UploadCache for CacheUploader {
     }
 }
 
+impl CacheUploader {
+    /// Upload in a separate task. Failures are only logged, they never fail the build.
+    fn spawn_background_upload(
+        &self,
+        background_uploads: Arc<BackgroundCacheUploads>,
+        key: buck2_data::ActionKey,
+        name: buck2_data::ActionName,
+        action: ActionDigest,
+        result: LocalActionResult,
+        digest_config: DigestConfig,
+    ) {
+        if !background_uploads.try_enqueue() {
+            tracing::warn!(
+                "Cache upload for `{}` skipped: too many uploads in progress",
+                action
+            );
+            return;
+        }
+
+        let this = self.clone();
+        let upload = async move {
+            let upload_res = match background_uploads.permits.dupe().acquire_owned().await {
+                Ok(_permit) => {
+                    let fs = this.artifact_fs.fs();
+                    match background_uploads
+                        .blocking_executor
+                        .execute_io_inline(|| result.outputs_unchanged(fs, digest_config))
+                        .await
+                    {
+                        Ok(true) => {
+                            this.maybe_perform_cache_upload(
+                                key,
+                                name,
+                                &action,
+                                &result,
+                                digest_config,
+                            )
+                            .await
+                        }
+                        Ok(false) => Ok(CacheUploadOutcome::Rejected(
+                            CacheUploadRejectionReason::OutputsChanged,
+                        )),
+                        Err(e) => Err(e),
+                    }
+                }
+                Err(e) => Err(e.into()),
+            };
+            background_uploads.dequeue();
+
+            match upload_res {
+                Ok(CacheUploadOutcome::Success) => {
+                    tracing::info!("Background cache upload for `{}` succeeded", action);
+                }
+                Ok(CacheUploadOutcome::Rejected(reason)) => {
+                    tracing::info!(
+                        "Background cache upload for `{}` rejected: {:#}",
+                        action,
+                        reason
+                    );
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        "Background cache upload for `{}` failed: {:#}",
+                        action,
+                        error
+                    );
+                }
+            }
+        };
+
+        // Keep reporting the upload to the command that ran the action, if any.
+        match get_dispatcher_opt() {
+            Some(dispatcher) => tokio::spawn(with_dispatcher_async(dispatcher, upload)),
+            None => tokio::spawn(upload),
+        };
+    }
+}
+
 fn systemtime_to_ttimestamp(time: SystemTime) -> anyhow::Result<TTimestamp> {
     let duration = time.duration_since(SystemTime::UNIX_EPOCH)?;
     Ok(TTimestamp {
@@ -7,7 +7,9 @@
  * of this source tree.
  */
 
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context;
 use async_trait::async_trait;
@@ -52,6 +54,34 @@ pub struct HybridExecutor {
     pub level: HybridExecutionLevel,
     pub executor_preference: ExecutorPreference,
     pub low_pass_filter: Arc<LowPassFilter>,
+    pub racing_policy: Arc<HybridRacingPolicy>,
+}
+
+/// Tuning of how the hybrid executor races local and remote execution.
+#[derive(Default)]
+pub struct HybridRacingPolicy {
+    /// Race but prefer cache: local execution only joins races after this delay, so remote cache
+    /// hits that come back quickly win without any local work.
+    pub local_delay: Option<Duration>,
+    /// Don't cancel local commands which already started when remote execution finishes first.
+    pub keep_running_local: bool,
+    /// Action categories which prefer running locally. Actions still fall back to remote
+    /// execution.
+    pub prefer_local_categories: HashSet<String>,
+    /// Action categories which prefer running remotely.
+    pub prefer_remote_categories: HashSet<String>,
+}
+
+impl HybridRacingPolicy {
+    fn category_preference(&self, category: &str) -> ExecutorPreference {
+        if self.prefer_local_categories.contains(category) {
+            ExecutorPreference::LocalPreferred
+        } else if self.prefer_remote_categories.contains(category) {
+            ExecutorPreference::RemotePreferred
+        } else {
+            ExecutorPreference::Default
+        }
+    }
 }
 
 impl HybridExecutor {
@@ -89,8 +119,33 @@ impl HybridExecutor {
         &self,
         command: &PreparedCommand<'_, '_>,
     ) -> anyhow::Result<ExecutorPreference> {
+        // Preferences of the command and the CLI take precedence over the category preferences.
+        let category = command.target.as_proto_action_name().category;
         self.executor_preference
-            .and(command.request.executor_preference())
+            .and(command.request.executor_preference())?
+            .and(self.racing_policy.category_preference(&category))
+    }
+
+    fn record_decision(
+        &self,
+        command: &PreparedCommand<'_, '_>,
+        events: &EventDispatcher,
+        strategy: &str,
+        executor_preference: ExecutorPreference,
+        low_pass_filter: bool,
+        fell_back: bool,
+    ) {
+        events.instant_event(buck2_data::HybridExecutionDecision {
+            action: Some(command.target.as_proto_action_name()),
+            strategy: strategy.to_owned(),
+            executor_preference: executor_preference.to_string(),
+            low_pass_filter,
+            local_delay: self
+                .racing_policy
+                .local_delay
+                .and_then(|d| d.try_into().ok()),
+            fell_back,
+        });
     }
 }
 
@@ -148,6 +203,7 @@ impl PreparedCommandExecutor for HybridExecutor {
                 local_execution_liveliness_guard,
                 Box::new(claim_manager),
                 remote_execution_liveliness_guard,
+                self.racing_policy.keep_running_local,
             )),
             manager.events.dupe(),
             manager.liveliness_observer.dupe(),
@@ -157,13 +213,42 @@ impl PreparedCommandExecutor for HybridExecutor {
         if executor_preference.requires_local()
             || self.remote.is_action_too_large(command.request.paths())
         {
+            self.record_decision(
+                command,
+                &manager.events,
+                "local",
+                executor_preference,
+                false,
+                false,
+            );
             return local_result.await;
         };
 
         if executor_preference.requires_remote() {
+            self.record_decision(
+                command,
+                &manager.events,
+                "remote",
+                executor_preference,
+                false,
+                false,
+            );
             return remote_result.await;
         }
 
+        // Don't wait on RE while its calls are failing.
+        if !self.remote.re_client.is_healthy() {
+            self.record_decision(
+                command,
+                &manager.events,
+                "remote_unhealthy",
+                executor_preference,
+                false,
+                false,
+            );
+            return local_result.await;
+        }
+
         let jobs = HybridExecutorJobs {
             local: local_result.map(|r| (r, JobPriority(1))),
             remote: remote_result.map(|r| (r, JobPriority(0))),
@@ -187,6 +272,14 @@ impl PreparedCommandExecutor for HybridExecutor {
         };
 
         if is_limited {
+            self.record_decision(
+                command,
+                &manager.events,
+                "limited",
+                executor_preference,
+                false,
+                false,
+            );
             return jobs.into_primary().await.0;
         }
 
@@ -222,6 +315,17 @@ impl PreparedCommandExecutor for HybridExecutor {
 
         let fallback_only = fallback_only && !command.request.force_full_hybrid_if_capable();
 
+        let is_sequential =
+            executor_preference.prefers_local() || executor_preference.prefers_remote();
+        let strategy = if is_sequential {
+            "sequential"
+        } else if fallback_only {
+            "fallback"
+        } else {
+            "race"
+        };
+        let local_delay = self.racing_policy.local_delay;
+
         let ((mut first_res, first_priority), second) =
             if executor_preference.prefers_local() || executor_preference.prefers_remote() {
                 // Don't race in this scenario, since this is typically used for
@@ -241,17 +345,33 @@ impl PreparedCommandExecutor for HybridExecutor {
                         }
                         .boxed()
                     })
-                } else if low_pass_filter {
+                } else if low_pass_filter || local_delay.is_some() {
                     jobs.map_local(move |local| {
                         async move {
+                            // Give the remote executor a head start, unless it aborts.
+                            if let Some(local_delay) = local_delay {
+                                let delay = tokio::time::sleep(local_delay);
+                                let alive = remote_execution_liveliness_observer.while_alive();
+                                futures::pin_mut!(delay);
+                                futures::pin_mut!(alive);
+                                futures::future::select(delay, alive).await;
+                            }
+
                             // Block local until either condition is met:
                             // - we only have a few actions (that's low_pass_filter)
                             // - the remote executor aborts (that's remote_execution_liveliness_guard)
-                            let access = self.low_pass_filter.access(weight);
-                            let alive = remote_execution_liveliness_observer.while_alive();
-                            futures::pin_mut!(access);
-                            futures::pin_mut!(alive);
-                            let _guard = futures::future::select(access, alive).await;
+                            let _guard = if low_pass_filter {
+                                let access = self.low_pass_filter.access(weight);
+                                let alive = remote_execution_liveliness_observer.while_alive();
+                                futures::pin_mut!(access);
+                                futures::pin_mut!(alive);
+                                match futures::future::select(access, alive).await {
+                                    futures::future::Either::Left((guard, _)) => Some(guard),
+                                    futures::future::Either::Right(..) => None,
+                                }
+                            } else {
+                                None
+                            };
                             local.await
                         }
                         .boxed()
@@ -262,7 +382,8 @@ impl PreparedCommandExecutor for HybridExecutor {
                 jobs.execute_concurrent().await
             };
 
-        let mut res = if is_retryable_status(&first_res) {
+        let fell_back = is_retryable_status(&first_res);
+        let mut res = if fell_back {
             // If the first result had made a claim, then cancel it now to let the other result
             // proceed.
             if let Some(claim) = first_res.report.claim.take() {
@@ -296,6 +417,15 @@ impl PreparedCommandExecutor for HybridExecutor {
             first_res
         };
 
+        self.record_decision(
+            command,
+            &manager.events,
+            strategy,
+            executor_preference,
+            low_pass_filter && !is_sequential && !fallback_only,
+            fell_back,
+        );
+
         res.eligible_for_full_hybrid = !fallback_only;
         res
     }
@@ -324,12 +454,14 @@ impl ReClaimManager {
         local_execution_liveliness_guard: LivelinessGuard,
         claim_manager: Box<dyn ClaimManager>,
         remote_execution_liveliness_guard: LivelinessGuard,
+        keep_running_local: bool,
     ) -> Self {
         Self {
             inner: Some(ReClaimManagerInner {
                 local_execution_liveliness_guard,
                 claim_manager,
                 remote_execution_liveliness_guard: Some(remote_execution_liveliness_guard),
+                keep_running_local,
             }),
         }
     }
@@ -351,6 +483,9 @@ struct ReClaimManagerInner {
 
     /// Only kept alive while the ReClaimManager (or its Claim) is alive.
     remote_execution_liveliness_guard: Option<LivelinessGuard>,
+
+    /// Wait for local commands holding the claim to finish instead of killing them.
+    keep_running_local: bool,
 }
 
 #[async_trait::async_trait]
@@ -358,12 +493,19 @@ impl ClaimManager for ReClaimManager {
     async fn claim(mut self: Box<Self>) -> Box<dyn Claim> {
         let inner = self.inner.take().expect("This is only taken once");
 
-        // Kill in-flight local commands.
-        let released_liveliness_guard = inner.local_execution_liveliness_guard.cancel();
+        let (released_liveliness_guard, claim) = if inner.keep_running_local {
+            // Local commands hold the claim once they start running, so only cancel local
+            // execution once we have it.
+            let claim = inner.claim_manager.claim().await;
+            (inner.local_execution_liveliness_guard.cancel(), claim)
+        } else {
+            // Kill in-flight local commands.
+            let released_liveliness_guard = inner.local_execution_liveliness_guard.cancel();
 
-        // Ask for the lock. If we never get it then we just exit as that would mean local
-        // execution finished.
-        let claim = inner.claim_manager.claim().await;
+            // Ask for the lock. If we never get it then we just exit as that would mean local
+            // execution finished.
+            (released_liveliness_guard, inner.claim_manager.claim().await)
+        };
 
         Box::new(ReClaim {
             released_liveliness_guard,
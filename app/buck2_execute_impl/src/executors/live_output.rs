@@ -0,0 +1,160 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Live output of local actions.
+//!
+//! The stdout and stderr of local actions are only reported once they finish. When live output
+//! is enabled, the lines they print are also sent as `LiveActionOutput` events while they run, so
+//! slow tests or links can be watched.
+
+use buck2_events::dispatch::EventDispatcher;
+use buck2_forkserver::run::OutputObserver;
+
+/// Longest partial line to hold on to before sending it anyway.
+const MAX_PARTIAL_LINE: usize = 64 * 1024;
+
+pub(crate) struct LiveOutput {
+    dispatcher: EventDispatcher,
+    key: buck2_data::ActionKey,
+    name: buck2_data::ActionName,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl LiveOutput {
+    pub(crate) fn new(
+        dispatcher: EventDispatcher,
+        key: buck2_data::ActionKey,
+        name: buck2_data::ActionName,
+    ) -> Self {
+        Self {
+            dispatcher,
+            key,
+            name,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        }
+    }
+
+    fn send(&self, stderr: bool, output: &[u8]) {
+        if output.is_empty() {
+            return;
+        }
+        self.dispatcher.instant_event(buck2_data::LiveActionOutput {
+            key: Some(self.key.clone()),
+            name: Some(self.name.clone()),
+            stderr,
+            output: String::from_utf8_lossy(output).into_owned(),
+        });
+    }
+
+    fn push(&mut self, stderr: bool, bytes: &[u8]) {
+        let buffer = if stderr {
+            &mut self.stderr
+        } else {
+            &mut self.stdout
+        };
+        buffer.extend_from_slice(bytes);
+        let complete = match complete_lines_len(buffer) {
+            0 => return,
+            n => buffer.drain(..n).collect::<Vec<_>>(),
+        };
+        self.send(stderr, &complete);
+    }
+}
+
+/// Length of the prefix of `buffer` to send: up to the last newline, or up to the last character
+/// boundary if the line grew too long.
+fn complete_lines_len(buffer: &[u8]) -> usize {
+    match buffer.iter().rposition(|b| *b == b'\n') {
+        Some(i) => i + 1,
+        None if buffer.len() >= MAX_PARTIAL_LINE => complete_chars_len(buffer),
+        None => 0,
+    }
+}
+
+/// Length of `buffer` without the UTF-8 character its end cuts in the middle of, if any.
+fn complete_chars_len(buffer: &[u8]) -> usize {
+    // A character is at most 4 bytes, so only its last 3 bytes can start a truncated one.
+    for i in (buffer.len().saturating_sub(3)..buffer.len()).rev() {
+        let char_len = match buffer[i] {
+            0x80..=0xbf => continue,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+        return if i + char_len > buffer.len() {
+            i
+        } else {
+            buffer.len()
+        };
+    }
+    buffer.len()
+}
+
+impl OutputObserver for LiveOutput {
+    fn stdout(&mut self, bytes: &[u8]) {
+        self.push(false, bytes);
+    }
+
+    fn stderr(&mut self, bytes: &[u8]) {
+        self.push(true, bytes);
+    }
+
+    fn finish(&mut self) {
+        let stdout = std::mem::take(&mut self.stdout);
+        let stderr = std::mem::take(&mut self.stderr);
+        self.send(false, &stdout);
+        self.send(true, &stderr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executors::live_output::complete_chars_len;
+    use crate::executors::live_output::complete_lines_len;
+    use crate::executors::live_output::MAX_PARTIAL_LINE;
+
+    #[test]
+    fn test_complete_lines_len() {
+        assert_eq!(0, complete_lines_len(b"partial"));
+        assert_eq!(6, complete_lines_len(b"a\nbcd\nef"));
+        assert_eq!(
+            MAX_PARTIAL_LINE,
+            complete_lines_len(&vec![b'a'; MAX_PARTIAL_LINE])
+        );
+    }
+
+    #[test]
+    fn test_complete_lines_len_char_boundary() {
+        let mut line = vec![b'a'; MAX_PARTIAL_LINE - 1];
+        line.extend_from_slice(&"é".as_bytes()[..1]);
+        assert_eq!(MAX_PARTIAL_LINE - 1, complete_lines_len(&line));
+
+        let mut line = vec![b'a'; MAX_PARTIAL_LINE - 2];
+        line.extend_from_slice(&"🦀".as_bytes()[..3]);
+        assert_eq!(MAX_PARTIAL_LINE - 2, complete_lines_len(&line));
+
+        let mut line = vec![b'a'; MAX_PARTIAL_LINE - 2];
+        line.extend_from_slice("€".as_bytes());
+        assert_eq!(MAX_PARTIAL_LINE + 1, complete_lines_len(&line));
+    }
+
+    #[test]
+    fn test_complete_chars_len() {
+        assert_eq!(0, complete_chars_len(b""));
+        assert_eq!(3, complete_chars_len(b"abc"));
+        assert_eq!(1, complete_chars_len(&"aé".as_bytes()[..2]));
+        assert_eq!(3, complete_chars_len("aé".as_bytes()));
+        // Invalid bytes are left for the lossy conversion to replace.
+        assert_eq!(2, complete_chars_len(&[b'a', 0xff]));
+        assert_eq!(4, complete_chars_len(&[b'a', 0x80, 0x80, 0x80]));
+    }
+}
@@ -31,7 +31,6 @@ use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::tag_error;
 use buck2_core::tag_result;
 use buck2_events::dispatch::get_dispatcher_opt;
-use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::artifact_value::ArtifactValue;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::directory::extract_artifact_value;
@@ -57,14 +56,17 @@ use buck2_execute::execute::request::CommandExecutionRequest;
 use buck2_execute::execute::request::ExecutorPreference;
 use buck2_execute::execute::result::CommandExecutionMetadata;
 use buck2_execute::execute::result::CommandExecutionResult;
+use buck2_execute::execute::target::CommandExecutionTarget;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::MaterializationError;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_forkserver::client::ForkserverClient;
-use buck2_forkserver::run::gather_output;
+use buck2_forkserver::run::gather_output_observed;
 use buck2_forkserver::run::maybe_absolutize_exe;
 use buck2_forkserver::run::timeout_into_cancellation;
 use buck2_forkserver::run::GatherOutputStatus;
+use buck2_forkserver::run::NoOutputObserver;
+use buck2_forkserver::run::OutputObserver;
 use buck2_util::process::background_command;
 use derive_more::From;
 use dupe::Dupe;
@@ -80,6 +82,10 @@ use more_futures::cancellation::CancellationContext;
 use thiserror::Error;
 use tracing::info;
 
+use crate::executors::live_output::LiveOutput;
+use crate::executors::local_action_cache::LocalActionCache;
+use crate::executors::local_action_cache::LocalActionCacheEntry;
+use crate::executors::sandbox::Sandbox;
 use crate::executors::worker::WorkerHandle;
 use crate::executors::worker::WorkerPool;
 
@@ -105,6 +111,7 @@ pub struct LocalExecutor {
     knobs: ExecutorGlobalKnobs,
     #[allow(unused)]
     worker_pool: Option<Arc<WorkerPool>>,
+    local_action_cache: Option<Arc<LocalActionCache>>,
 }
 
 impl LocalExecutor {
@@ -117,6 +124,7 @@ impl LocalExecutor {
         forkserver: Option<ForkserverClient>,
         knobs: ExecutorGlobalKnobs,
         worker_pool: Option<Arc<WorkerPool>>,
+        local_action_cache: Option<Arc<LocalActionCache>>,
     ) -> Self {
         Self {
             artifact_fs,
@@ -127,6 +135,7 @@ impl LocalExecutor {
             forkserver,
             knobs,
             worker_pool,
+            local_action_cache,
         }
     }
 
@@ -142,6 +151,7 @@ impl LocalExecutor {
         env_inheritance: Option<&'a EnvironmentInheritance>,
         liveliness_observer: impl LivelinessObserver + 'static,
         disable_miniperf: bool,
+        mut live_output: Option<LiveOutput>,
     ) -> impl futures::future::Future<
         Output = anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>,
     > + Send
@@ -151,6 +161,11 @@ impl LocalExecutor {
                 Some(d) => Cow::Owned(self.root.join(d)),
                 None => Cow::Borrowed(&self.root),
             };
+            let mut no_output = NoOutputObserver;
+            let output_observer: &mut dyn OutputObserver = match &mut live_output {
+                Some(live_output) => live_output,
+                None => &mut no_output,
+            };
 
             match &self.forkserver {
                 Some(forkserver) => {
@@ -166,13 +181,14 @@ impl LocalExecutor {
                             env_inheritance,
                             liveliness_observer,
                             self.knobs.enable_miniperf && !disable_miniperf,
+                            output_observer,
                         )
                         .await
                     }
 
                     #[cfg(not(unix))]
                     {
-                        let _unused = (forkserver, disable_miniperf);
+                        let _unused = (forkserver, disable_miniperf, output_observer);
                         Err(anyhow::anyhow!("Forkserver is not supported off-UNIX"))
                     }
                 }
@@ -197,7 +213,7 @@ impl LocalExecutor {
                     let cancellation =
                         select(timeout.boxed(), alive.boxed()).map(|r| r.factor_first().0);
 
-                    gather_output(cmd, cancellation).await
+                    gather_output_observed(cmd, cancellation, output_observer).await
                 }
                 .with_context(|| format!("Failed to gather output from command: {}", exe)),
             }
@@ -207,12 +223,14 @@ impl LocalExecutor {
     async fn exec_request(
         &self,
         action_digest: &ActionDigest,
+        target: &dyn CommandExecutionTarget,
         request: &CommandExecutionRequest,
         manager: CommandExecutionManager,
         cancellation: CancellationObserver,
         cancellations: &CancellationContext<'_>,
         digest_config: DigestConfig,
         local_resource_holders: &[LocalResourceHolder],
+        queue_time: Duration,
     ) -> CommandExecutionResult {
         let args = &request.all_args_vec();
         if args.is_empty() {
@@ -375,7 +393,36 @@ impl LocalExecutor {
         };
         let liveliness_observer = manager.liveliness_observer.dupe().and(cancellation);
 
-        let (worker, manager) = self.initialize_worker(request, manager, dispatcher).await?;
+        let (worker, manager) = self.initialize_worker(request, manager).await?;
+
+        let sandboxed_args;
+        let exec_args = if self.knobs.sandbox_local_actions && worker.is_none() {
+            let working_directory = match request.working_directory() {
+                Some(d) => self.root.join(d),
+                None => self.root.clone(),
+            };
+            match Sandbox::for_request(&self.artifact_fs, request, scratch_dir.as_deref())
+                .and_then(|sandbox| sandbox.wrap(&self.root, &working_directory, args))
+            {
+                Ok(args) => {
+                    sandboxed_args = args;
+                    &sandboxed_args
+                }
+                Err(e) => return manager.error("sandbox_failed", e),
+            }
+        } else {
+            args
+        };
+
+        let live_output = if self.knobs.live_action_output && worker.is_none() {
+            Some(LiveOutput::new(
+                dispatcher.dupe(),
+                target.as_proto_action_key(),
+                target.as_proto_action_name(),
+            ))
+        } else {
+            None
+        };
 
         let execution_kind = match worker {
             None => CommandExecutionKind::Local {
@@ -433,14 +480,15 @@ impl LocalExecutor {
                     Ok(worker.exec_cmd(request.args(), env).await)
                 } else {
                     self.exec(
-                        &args[0],
-                        &args[1..],
+                        &exec_args[0],
+                        &exec_args[1..],
                         env,
                         request.working_directory(),
                         request.timeout(),
                         request.local_environment_inheritance(),
                         liveliness_observer,
                         request.disable_miniperf(),
+                        live_output,
                     )
                     .await
                 };
@@ -454,6 +502,8 @@ impl LocalExecutor {
                     start_time,
                     execution_stats: None, // We fill this in later if available.
                     input_materialization_duration,
+                    local_queue_time: Some(queue_time),
+                    output_upload_duration: Duration::ZERO, // We fill this in later.
                 };
 
                 (timing, r)
@@ -473,17 +523,37 @@ impl LocalExecutor {
                 exit_code,
                 execution_stats,
             } => {
-                let outputs = match self
-                    .calculate_and_declare_output_values(request, digest_config)
-                    .await
+                let outputs_start = Instant::now();
+                let outputs = match calculate_and_declare_output_values(
+                    &self.artifact_fs,
+                    &self.materializer,
+                    request,
+                    digest_config,
+                )
+                .await
                 {
                     Ok(output_values) => output_values,
                     Err(e) => return manager.error("calculate_output_values_failed", e),
                 };
 
                 timing.execution_stats = execution_stats;
+                timing.output_upload_duration = outputs_start.elapsed();
 
                 if exit_code == 0 {
+                    if let (Some(cache), CommandExecutionKind::Local { .. }, true) = (
+                        &self.local_action_cache,
+                        &execution_kind,
+                        request.allows_local_action_cache(),
+                    ) {
+                        self.store_in_local_action_cache(
+                            cache,
+                            action_digest,
+                            &outputs,
+                            &std_streams,
+                            timing.execution_time,
+                        )
+                        .await;
+                    }
                     manager.success(execution_kind, outputs, std_streams, timing)
                 } else {
                     let manager = check_inputs(
@@ -534,61 +604,44 @@ impl LocalExecutor {
         }
     }
 
-    async fn calculate_and_declare_output_values(
+    /// Record a successful execution in the local action cache. Failures are not fatal: the
+    /// action just won't be cached.
+    async fn store_in_local_action_cache(
         &self,
-        request: &CommandExecutionRequest,
-        digest_config: DigestConfig,
-    ) -> anyhow::Result<IndexMap<CommandExecutionOutput, ArtifactValue>> {
-        let mut builder = inputs_directory(request.inputs(), &self.artifact_fs)?;
-
-        // Read outputs from disk and add them to the builder
-        let mut entries = Vec::new();
-        for output in request.outputs() {
-            let path = output.resolve(&self.artifact_fs).into_path();
-            let abspath = self.root.join(&path);
-            let entry = build_entry_from_disk(
-                abspath,
-                FileDigestConfig::build(digest_config.cas_digest_config()),
-            )
-            .with_context(|| format!("collecting output {:?}", path))?;
-            if let Some(entry) = entry {
-                insert_entry(&mut builder, &path, entry)?;
-                entries.push((output.cloned(), path));
-            }
-        }
-
-        let mut to_declare = vec![];
-        let mut mapped_outputs = IndexMap::with_capacity(entries.len());
-
-        for (output, path) in entries {
-            let value = extract_artifact_value(&builder, &path, digest_config)?;
-            if let Some(value) = value {
-                match output {
-                    CommandExecutionOutput::BuildArtifact { .. } => {
-                        to_declare.push((path, value.dupe()));
-                    }
-                    CommandExecutionOutput::TestPath { .. } => {
-                        // Don't declare those as we don't currently have any form of GC so this
-                        // would take up space for nothing, and most importantly, we will never
-                        // need them to be in materializer state for e.g. matching as nothing
-                        // should depend on them.
-                    }
-                }
-
-                mapped_outputs.insert(output, value);
-            }
+        cache: &LocalActionCache,
+        action_digest: &ActionDigest,
+        outputs: &IndexMap<CommandExecutionOutput, ArtifactValue>,
+        std_streams: &CommandStdStreams,
+        execution_time: Duration,
+    ) {
+        let (stdout, stderr) = match std_streams {
+            CommandStdStreams::Local { stdout, stderr } => (stdout.clone(), stderr.clone()),
+            _ => Default::default(),
+        };
+        let entry = LocalActionCacheEntry::new(
+            outputs.iter().map(|(output, value)| {
+                (
+                    output.as_ref().resolve(&self.artifact_fs).into_path(),
+                    value,
+                )
+            }),
+            stdout,
+            stderr,
+            execution_time,
+        );
+        if let Err(e) = self
+            .blocking_executor
+            .execute_io_inline(|| cache.store(self.artifact_fs.fs(), action_digest, &entry))
+            .await
+        {
+            tracing::warn!("Error writing to local action cache: {:#}", e);
         }
-
-        self.materializer.declare_existing(to_declare).await?;
-
-        Ok(mapped_outputs)
     }
 
     async fn initialize_worker(
         &self,
         request: &CommandExecutionRequest,
         manager: CommandExecutionManagerWithClaim,
-        dispatcher: EventDispatcher,
     ) -> ControlFlow<
         CommandExecutionResult,
         (Option<Arc<WorkerHandle>>, CommandExecutionManagerWithClaim),
@@ -625,13 +678,7 @@ impl LocalExecutor {
                         .iter()
                         .map(|(k, v)| (OsString::from(k), OsString::from(v)));
                     worker_pool
-                        .get_or_create_worker(
-                            worker_spec,
-                            env,
-                            &self.root,
-                            forkserver.clone(),
-                            dispatcher,
-                        )
+                        .get_or_create_worker(worker_spec, env, &self.root, forkserver.clone())
                         .await
                 },
             )
@@ -673,11 +720,12 @@ impl PreparedCommandExecutor for LocalExecutor {
 
         let PreparedCommand {
             request,
-            target: _,
+            target,
             prepared_action,
             digest_config,
         } = command;
 
+        let queue_start = Instant::now();
         let local_resource_holders = executor_stage_async(
             {
                 let a = buck2_data::AcquireLocalResource {};
@@ -704,10 +752,13 @@ impl PreparedCommandExecutor for LocalExecutor {
             buck2_data::LocalStage {
                 stage: Some(buck2_data::LocalQueued {}.into()),
             },
-            self.host_sharing_broker
-                .acquire(request.host_sharing_requirements()),
+            self.host_sharing_broker.acquire_with_resources(
+                request.host_sharing_requirements(),
+                request.resource_weights(),
+            ),
         )
         .await;
+        let queue_time = queue_start.elapsed();
 
         // If we start running something, we don't want this task to get dropped, because if we do
         // we might interfere with e.g. clean up.
@@ -716,12 +767,14 @@ impl PreparedCommandExecutor for LocalExecutor {
                 Self::exec_request(
                     self,
                     &prepared_action.action,
+                    *target,
                     request,
                     manager,
                     cancellation,
                     cancellations,
                     *digest_config,
                     &local_resource_holders,
+                    queue_time,
                 )
             })
             .await
@@ -879,6 +932,58 @@ pub async fn materialize_build_outputs_from_previous_run(
     materializer.ensure_materialized(paths).await
 }
 
+/// Hash the outputs of a command from disk, and declare the build artifacts to the materializer.
+pub async fn calculate_and_declare_output_values(
+    artifact_fs: &ArtifactFs,
+    materializer: &Arc<dyn Materializer>,
+    request: &CommandExecutionRequest,
+    digest_config: DigestConfig,
+) -> anyhow::Result<IndexMap<CommandExecutionOutput, ArtifactValue>> {
+    let mut builder = inputs_directory(request.inputs(), artifact_fs)?;
+
+    // Read outputs from disk and add them to the builder
+    let mut entries = Vec::new();
+    for output in request.outputs() {
+        let path = output.resolve(artifact_fs).into_path();
+        let abspath = artifact_fs.fs().resolve(&path);
+        let entry = build_entry_from_disk(
+            abspath,
+            FileDigestConfig::build(digest_config.cas_digest_config()),
+        )
+        .with_context(|| format!("collecting output {:?}", path))?;
+        if let Some(entry) = entry {
+            insert_entry(&mut builder, &path, entry)?;
+            entries.push((output.cloned(), path));
+        }
+    }
+
+    let mut to_declare = vec![];
+    let mut mapped_outputs = IndexMap::with_capacity(entries.len());
+
+    for (output, path) in entries {
+        let value = extract_artifact_value(&builder, &path, digest_config)?;
+        if let Some(value) = value {
+            match output {
+                CommandExecutionOutput::BuildArtifact { .. } => {
+                    to_declare.push((path, value.dupe()));
+                }
+                CommandExecutionOutput::TestPath { .. } => {
+                    // Don't declare those as we don't currently have any form of GC so this
+                    // would take up space for nothing, and most importantly, we will never
+                    // need them to be in materializer state for e.g. matching as nothing
+                    // should depend on them.
+                }
+            }
+
+            mapped_outputs.insert(output, value);
+        }
+    }
+
+    materializer.declare_existing(to_declare).await?;
+
+    Ok(mapped_outputs)
+}
+
 /// Create any output dirs requested by the command. Note that this makes no effort to delete
 /// the output paths first. Eventually it should, but right now this happens earlier. This
 /// would be a separate refactor.
@@ -1010,6 +1115,7 @@ mod unix {
         env_inheritance: Option<&EnvironmentInheritance>,
         liveliness_observer: impl LivelinessObserver + 'static,
         enable_miniperf: bool,
+        output_observer: &mut dyn OutputObserver,
     ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)> {
         let exe = exe.as_ref();
 
@@ -1029,7 +1135,11 @@ mod unix {
         };
         apply_local_execution_environment(&mut req, working_directory, env, env_inheritance);
         forkserver
-            .execute(req, async move { liveliness_observer.while_alive().await })
+            .execute_observed(
+                req,
+                async move { liveliness_observer.while_alive().await },
+                output_observer,
+            )
             .await
     }
 
@@ -1210,6 +1320,7 @@ mod tests {
             None,
             ExecutorGlobalKnobs::default(),
             None,
+            None,
         );
 
         Ok((executor, temp.path().root().to_buf(), temp))
@@ -1230,6 +1341,7 @@ mod tests {
                 None,
                 NoopLivelinessObserver::create(),
                 false,
+                None,
             )
             .await?;
         assert!(matches!(status, GatherOutputStatus::Finished { exit_code, .. } if exit_code == 0));
@@ -1266,6 +1378,7 @@ mod tests {
                 Some(&EnvironmentInheritance::empty()),
                 NoopLivelinessObserver::create(),
                 false,
+                None,
             )
             .await?;
         assert!(matches!(status, GatherOutputStatus::Finished { exit_code, .. } if exit_code == 0));
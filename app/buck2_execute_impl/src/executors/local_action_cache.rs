@@ -0,0 +1,848 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! An on-disk action cache for local execution.
+//!
+//! Results of successful local actions are keyed by action digest in a sqlite db, and the
+//! contents of their output files are stored in a content addressed `blobs` directory next to it.
+//! This lives in buck-out, so it survives daemon restarts, and gives cache hits to users without
+//! any remote infrastructure. Once the blobs exceed the size budget, the least recently used
+//! entries are evicted. Blobs may be stored zstd-compressed, in which case their name has a
+//! `.zst` suffix.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use buck2_core::directory::Directory;
+use buck2_core::directory::DirectoryEntry;
+use buck2_core::directory::DirectoryIterator;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::directory::ActionDirectoryMember;
+use buck2_execute::execute::action_digest::ActionDigest;
+use buck2_execute::execute::blocking::BlockingExecutor;
+use buck2_execute::execute::kind::CommandExecutionKind;
+use buck2_execute::execute::manager::CommandExecutionManager;
+use buck2_execute::execute::manager::CommandExecutionManagerExt;
+use buck2_execute::execute::output::CommandStdStreams;
+use buck2_execute::execute::prepared::PreparedCommand;
+use buck2_execute::execute::prepared::PreparedCommandOptionalExecutor;
+use buck2_execute::execute::result::CommandExecutionMetadata;
+use buck2_execute::execute::result::CommandExecutionResult;
+use buck2_execute::materialize::materializer::Materializer;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use rusqlite::OptionalExtension;
+
+use crate::executors::local::calculate_and_declare_output_values;
+use crate::executors::local::create_output_dirs;
+
+/// Bump this when changing the schema of the db or the layout of the blobs. Caches with a
+/// different version are deleted.
+const SCHEMA_VERSION: i64 = 1;
+
+const DB_FILE_NAME: &str = "db.sqlite";
+const BLOBS_DIR_NAME: &str = "blobs";
+const COMPRESSED_BLOB_SUFFIX: &str = ".zst";
+
+#[derive(Debug, thiserror::Error)]
+enum LocalActionCacheError {
+    #[error("Blob `{0}` is missing or has an unexpected size")]
+    InvalidBlob(String),
+    #[error("Unknown entry kind `{0}`")]
+    UnknownEntryKind(String),
+}
+
+/// A file, directory or symlink produced by a cached action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalActionCacheOutput {
+    pub path: ProjectRelativePathBuf,
+    pub kind: LocalActionCacheOutputKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalActionCacheOutputKind {
+    Dir,
+    File {
+        /// Name of the file holding the contents in the blobs directory.
+        blob: String,
+        size: u64,
+        is_executable: bool,
+    },
+    Symlink {
+        target: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalActionCacheEntry {
+    pub outputs: Vec<LocalActionCacheOutput>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// How long the action originally took to run.
+    pub execution_time: Duration,
+}
+
+impl LocalActionCacheEntry {
+    /// Describe the outputs of an action, as computed after a local execution.
+    pub fn new<'a>(
+        outputs: impl IntoIterator<Item = (ProjectRelativePathBuf, &'a ArtifactValue)>,
+        stdout: Vec<u8>,
+        stderr: Vec<u8>,
+        execution_time: Duration,
+    ) -> Self {
+        fn leaf_kind(member: &ActionDirectoryMember) -> LocalActionCacheOutputKind {
+            match member {
+                ActionDirectoryMember::File(meta) => LocalActionCacheOutputKind::File {
+                    blob: meta.digest.raw_digest().to_string(),
+                    size: meta.digest.size(),
+                    is_executable: meta.is_executable,
+                },
+                ActionDirectoryMember::Symlink(symlink) => LocalActionCacheOutputKind::Symlink {
+                    target: symlink.target().as_str().to_owned(),
+                },
+                ActionDirectoryMember::ExternalSymlink(symlink) => {
+                    LocalActionCacheOutputKind::Symlink {
+                        target: symlink.to_path_buf().to_string_lossy().into_owned(),
+                    }
+                }
+            }
+        }
+
+        let mut entries = Vec::new();
+        for (path, value) in outputs {
+            match value.entry() {
+                DirectoryEntry::Dir(dir) => {
+                    entries.push(LocalActionCacheOutput {
+                        path: path.clone(),
+                        kind: LocalActionCacheOutputKind::Dir,
+                    });
+                    let mut walk = dir.unordered_walk();
+                    while let Some((entry_path, entry)) = walk.next() {
+                        entries.push(LocalActionCacheOutput {
+                            path: path.join(entry_path.get()),
+                            kind: match entry {
+                                DirectoryEntry::Dir(..) => LocalActionCacheOutputKind::Dir,
+                                DirectoryEntry::Leaf(member) => leaf_kind(member),
+                            },
+                        });
+                    }
+                }
+                DirectoryEntry::Leaf(member) => entries.push(LocalActionCacheOutput {
+                    path,
+                    kind: leaf_kind(member),
+                }),
+            }
+        }
+
+        Self {
+            outputs: entries,
+            stdout,
+            stderr,
+            execution_time,
+        }
+    }
+}
+
+pub struct LocalActionCache {
+    dir: AbsNormPathBuf,
+    max_bytes: u64,
+    /// Whether to compress new blobs.
+    compress: bool,
+    connection: Mutex<Connection>,
+}
+
+/// A blob in the blobs directory.
+struct StoredBlob {
+    path: AbsNormPathBuf,
+    compressed: bool,
+    /// Size on disk.
+    size: u64,
+}
+
+impl LocalActionCache {
+    /// Open the cache in `dir`, creating it if needed. A cache written with a different schema is
+    /// discarded. `max_bytes` is the budget of the blobs on disk, after compression.
+    pub fn open(dir: AbsNormPathBuf, max_bytes: u64, compress: bool) -> anyhow::Result<Self> {
+        let db_path = dir.join(ForwardRelativePath::unchecked_new(DB_FILE_NAME));
+        if fs_util::try_exists(&db_path)? {
+            let version: i64 = Connection::open(&db_path)
+                .and_then(|c| c.pragma_query_value(None, "user_version", |row| row.get(0)))
+                .unwrap_or(0);
+            if version != SCHEMA_VERSION {
+                tracing::info!(
+                    "Discarding local action cache with schema version {}",
+                    version
+                );
+                fs_util::remove_all(&dir)?;
+            }
+        }
+
+        fs_util::create_dir_all(dir.join(ForwardRelativePath::unchecked_new(BLOBS_DIR_NAME)))?;
+        let connection = Connection::open(&db_path)
+            .with_context(|| format!("Error opening local action cache at `{}`", db_path))?;
+        if cfg!(unix) {
+            connection.pragma_update(None, "journal_mode", "WAL")?;
+        }
+        // Like the materializer state, this is a cache: losing it on power loss is fine.
+        connection.pragma_update(None, "synchronous", "OFF")?;
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS actions (
+                action_digest   TEXT PRIMARY KEY NOT NULL,
+                stdout          BLOB NOT NULL,
+                stderr          BLOB NOT NULL,
+                execution_time_ms INTEGER NOT NULL,
+                last_access     INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS outputs (
+                action_digest   TEXT NOT NULL,
+                path            TEXT NOT NULL,
+                kind            TEXT NOT NULL,
+                blob            TEXT,
+                size            INTEGER,
+                is_executable   INTEGER,
+                symlink_target  TEXT
+            );
+            CREATE INDEX IF NOT EXISTS outputs_by_action ON outputs (action_digest);
+            CREATE INDEX IF NOT EXISTS outputs_by_blob ON outputs (blob);
+            CREATE TABLE IF NOT EXISTS blobs (
+                name    TEXT PRIMARY KEY NOT NULL,
+                size    INTEGER NOT NULL
+            );",
+        )?;
+        connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+        Ok(Self {
+            dir,
+            max_bytes,
+            compress,
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn blob_path(&self, blob: &str) -> anyhow::Result<AbsNormPathBuf> {
+        Ok(self
+            .dir
+            .join(ForwardRelativePath::unchecked_new(BLOBS_DIR_NAME))
+            .join(ForwardRelativePath::new(blob)?))
+    }
+
+    /// Find a blob, whether it was stored compressed or not.
+    fn find_blob(&self, blob: &str) -> anyhow::Result<Option<StoredBlob>> {
+        for compressed in [false, true] {
+            let path = if compressed {
+                self.blob_path(&format!("{}{}", blob, COMPRESSED_BLOB_SUFFIX))?
+            } else {
+                self.blob_path(blob)?
+            };
+            if let Some(meta) = fs_util::symlink_metadata_if_exists(&path)? {
+                return Ok(Some(StoredBlob {
+                    path,
+                    compressed,
+                    size: meta.len(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the entry for an action, marking it as recently used.
+    pub fn lookup(
+        &self,
+        action_digest: &ActionDigest,
+    ) -> anyhow::Result<Option<LocalActionCacheEntry>> {
+        let key = action_digest.to_string();
+        let connection = self.connection.lock();
+
+        let row = connection
+            .query_row(
+                "SELECT stdout, stderr, execution_time_ms FROM actions WHERE action_digest = ?1",
+                [&key],
+                |row| {
+                    Ok((
+                        row.get::<_, Vec<u8>>(0)?,
+                        row.get::<_, Vec<u8>>(1)?,
+                        row.get::<_, i64>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let (stdout, stderr, execution_time_ms) = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let mut stmt = connection.prepare(
+            "SELECT path, kind, blob, size, is_executable, symlink_target FROM outputs
+            WHERE action_digest = ?1 ORDER BY rowid",
+        )?;
+        let outputs = stmt
+            .query_map([&key], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, Option<bool>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            })?
+            .map(|row| {
+                let (path, kind, blob, size, is_executable, symlink_target) = row?;
+                let kind = match kind.as_str() {
+                    "dir" => LocalActionCacheOutputKind::Dir,
+                    "file" => LocalActionCacheOutputKind::File {
+                        blob: blob.unwrap_or_default(),
+                        size: size.unwrap_or_default() as u64,
+                        is_executable: is_executable.unwrap_or_default(),
+                    },
+                    "symlink" => LocalActionCacheOutputKind::Symlink {
+                        target: symlink_target.unwrap_or_default(),
+                    },
+                    _ => return Err(LocalActionCacheError::UnknownEntryKind(kind).into()),
+                };
+                Ok(LocalActionCacheOutput {
+                    path: ProjectRelativePathBuf::try_from(path)?,
+                    kind,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        drop(stmt);
+
+        connection.execute(
+            "UPDATE actions SET last_access = ?1 WHERE action_digest = ?2",
+            rusqlite::params![now(), key],
+        )?;
+
+        Ok(Some(LocalActionCacheEntry {
+            outputs,
+            stdout,
+            stderr,
+            execution_time: Duration::from_millis(execution_time_ms as u64),
+        }))
+    }
+
+    /// Record the result of an action whose outputs are on disk, then evict entries over the
+    /// size budget.
+    pub fn store(
+        &self,
+        fs: &ProjectRoot,
+        action_digest: &ActionDigest,
+        entry: &LocalActionCacheEntry,
+    ) -> anyhow::Result<()> {
+        static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let mut blobs = Vec::new();
+        for output in &entry.outputs {
+            if let LocalActionCacheOutputKind::File { blob, .. } = &output.kind {
+                let stored_size = match self.find_blob(blob)? {
+                    Some(stored) => stored.size,
+                    None => {
+                        // Write to a temporary file first so concurrent readers never see a
+                        // partial blob.
+                        let temp_path = self.blob_path(&format!(
+                            "{}.tmp{}",
+                            blob,
+                            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+                        ))?;
+                        let src = fs.resolve(&output.path);
+                        let blob_path = if self.compress {
+                            let mut encoder = zstd::stream::write::Encoder::new(
+                                fs_util::create_file(&temp_path)?,
+                                0,
+                            )?;
+                            io::copy(&mut fs_util::open_file(&src)?, &mut encoder)?;
+                            encoder.finish()?.flush()?;
+                            self.blob_path(&format!("{}{}", blob, COMPRESSED_BLOB_SUFFIX))?
+                        } else {
+                            fs_util::copy(&src, &temp_path)?;
+                            self.blob_path(blob)?
+                        };
+                        fs_util::rename(&temp_path, &blob_path)?;
+                        fs_util::symlink_metadata(&blob_path)?.len()
+                    }
+                };
+                blobs.push((blob, stored_size));
+            }
+        }
+
+        let key = action_digest.to_string();
+        let mut connection = self.connection.lock();
+        let tx = connection.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO actions
+            (action_digest, stdout, stderr, execution_time_ms, last_access)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                key,
+                entry.stdout,
+                entry.stderr,
+                entry.execution_time.as_millis() as i64,
+                now()
+            ],
+        )?;
+        tx.execute("DELETE FROM outputs WHERE action_digest = ?1", [&key])?;
+        for output in &entry.outputs {
+            let path = output.path.as_str();
+            match &output.kind {
+                LocalActionCacheOutputKind::Dir => tx.execute(
+                    "INSERT INTO outputs (action_digest, path, kind) VALUES (?1, ?2, 'dir')",
+                    rusqlite::params![key, path],
+                )?,
+                LocalActionCacheOutputKind::File {
+                    blob,
+                    size,
+                    is_executable,
+                } => tx.execute(
+                    "INSERT INTO outputs (action_digest, path, kind, blob, size, is_executable)
+                    VALUES (?1, ?2, 'file', ?3, ?4, ?5)",
+                    rusqlite::params![key, path, blob, *size as i64, is_executable],
+                )?,
+                LocalActionCacheOutputKind::Symlink { target } => tx.execute(
+                    "INSERT INTO outputs (action_digest, path, kind, symlink_target)
+                    VALUES (?1, ?2, 'symlink', ?3)",
+                    rusqlite::params![key, path, target],
+                )?,
+            };
+        }
+        for (blob, size) in blobs {
+            tx.execute(
+                "INSERT OR IGNORE INTO blobs (name, size) VALUES (?1, ?2)",
+                rusqlite::params![blob, size as i64],
+            )?;
+        }
+        tx.commit()?;
+
+        self.evict(&connection)
+    }
+
+    /// Write the outputs of an entry to disk. The output paths must not exist.
+    pub fn restore(&self, fs: &ProjectRoot, entry: &LocalActionCacheEntry) -> anyhow::Result<()> {
+        for output in &entry.outputs {
+            let path = fs.resolve(&output.path);
+            match &output.kind {
+                LocalActionCacheOutputKind::Dir => fs_util::create_dir_all(&path)?,
+                LocalActionCacheOutputKind::File {
+                    blob,
+                    size,
+                    is_executable,
+                } => {
+                    let stored = match self.find_blob(blob)? {
+                        Some(stored) if stored.compressed || stored.size == *size => stored,
+                        _ => return Err(LocalActionCacheError::InvalidBlob(blob.clone()).into()),
+                    };
+                    create_parent_dir(&path)?;
+                    if stored.compressed {
+                        let mut file = fs_util::create_file(&path)?;
+                        let written =
+                            zstd::stream::copy_decode(fs_util::open_file(&stored.path)?, &mut file)
+                                .and_then(|()| file.flush());
+                        if written.is_err() || fs_util::symlink_metadata(&path)?.len() != *size {
+                            return Err(LocalActionCacheError::InvalidBlob(blob.clone()).into());
+                        }
+                    } else {
+                        fs_util::copy(&stored.path, &path)?;
+                    }
+                    set_executable(&path, *is_executable)?;
+                }
+                LocalActionCacheOutputKind::Symlink { target } => {
+                    create_parent_dir(&path)?;
+                    fs_util::symlink(target, &path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the entry for an action, e.g. because its blobs are corrupted.
+    pub fn remove(&self, action_digest: &ActionDigest) -> anyhow::Result<()> {
+        let connection = self.connection.lock();
+        remove_action(&connection, &action_digest.to_string())?;
+        self.collect_garbage(&connection)
+    }
+
+    /// Evict least recently used entries until the blobs fit in the size budget. The entries to
+    /// evict are picked in memory, then removed in a single transaction.
+    fn evict(&self, connection: &Connection) -> anyhow::Result<()> {
+        let mut total: i64 =
+            connection.query_row("SELECT COALESCE(SUM(size), 0) FROM blobs", [], |row| {
+                row.get(0)
+            })?;
+        if total as u64 <= self.max_bytes {
+            return Ok(());
+        }
+
+        // The blobs of every action, least recently used first.
+        let mut stmt = connection.prepare(
+            "SELECT a.action_digest, o.blob, b.size FROM actions a
+            LEFT JOIN outputs o ON o.action_digest = a.action_digest AND o.blob IS NOT NULL
+            LEFT JOIN blobs b ON b.name = o.blob
+            ORDER BY a.last_access, a.action_digest",
+        )?;
+        let mut actions: Vec<(String, HashMap<String, i64>)> = Vec::new();
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let action: String = row.get(0)?;
+            if actions.last().map_or(true, |(last, _)| *last != action) {
+                actions.push((action, HashMap::new()));
+            }
+            if let (Some(blob), Some(size)) = (
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+            ) {
+                actions.last_mut().unwrap().1.insert(blob, size);
+            }
+        }
+        drop(rows);
+        drop(stmt);
+
+        let mut references: HashMap<&str, usize> = HashMap::new();
+        for (_, blobs) in &actions {
+            for blob in blobs.keys() {
+                *references.entry(blob).or_default() += 1;
+            }
+        }
+        let mut evicted = Vec::new();
+        for (action, blobs) in &actions {
+            if total as u64 <= self.max_bytes {
+                break;
+            }
+            evicted.push(action);
+            for (blob, size) in blobs {
+                let count = references.get_mut(blob.as_str()).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    total -= size;
+                }
+            }
+        }
+
+        let tx = connection.unchecked_transaction()?;
+        for action in evicted {
+            remove_action(&tx, action)?;
+        }
+        self.collect_garbage(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Delete the blobs no action refers to.
+    fn collect_garbage(&self, connection: &Connection) -> anyhow::Result<()> {
+        let mut stmt = connection.prepare(
+            "SELECT name FROM blobs WHERE name NOT IN
+            (SELECT blob FROM outputs WHERE blob IS NOT NULL)",
+        )?;
+        let unused = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for blob in unused {
+            if let Some(stored) = self.find_blob(&blob)? {
+                fs_util::remove_file(&stored.path)?;
+            }
+            connection.execute("DELETE FROM blobs WHERE name = ?1", [&blob])?;
+        }
+        Ok(())
+    }
+}
+
+fn remove_action(connection: &Connection, key: &str) -> anyhow::Result<()> {
+    connection.execute("DELETE FROM actions WHERE action_digest = ?1", [key])?;
+    connection.execute("DELETE FROM outputs WHERE action_digest = ?1", [key])?;
+    Ok(())
+}
+
+/// Timestamp used to order entries by last access.
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_micros() as i64)
+}
+
+fn create_parent_dir(path: &AbsNormPath) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs_util::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+/// Blobs are shared between files with and without the executable bit, so set it explicitly.
+fn set_executable(path: &AbsNormPath, is_executable: bool) -> anyhow::Result<()> {
+    if is_executable {
+        return fs_util::set_executable(path);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut perms = fs_util::metadata(path)?.permissions();
+        perms.set_mode(perms.mode() & !0o111);
+        fs_util::set_permissions(path, perms)?;
+    }
+
+    Ok(())
+}
+
+/// Serves actions from the local action cache.
+pub struct LocalActionCacheChecker {
+    pub artifact_fs: ArtifactFs,
+    pub materializer: Arc<dyn Materializer>,
+    pub blocking_executor: Arc<dyn BlockingExecutor>,
+    pub cache: Arc<LocalActionCache>,
+}
+
+#[async_trait]
+impl PreparedCommandOptionalExecutor for LocalActionCacheChecker {
+    async fn maybe_execute(
+        &self,
+        command: &PreparedCommand<'_, '_>,
+        manager: CommandExecutionManager,
+        cancellations: &CancellationContext,
+    ) -> ControlFlow<CommandExecutionResult, CommandExecutionManager> {
+        let request = command.request;
+        let action_digest = &command.prepared_action.action;
+
+        // Only actions that would have been stored, which excludes actions reusing their previous
+        // outputs since those can't be restored from the cache.
+        if !request.allows_local_action_cache() {
+            return ControlFlow::Continue(manager);
+        }
+
+        let start = Instant::now();
+        let entry = match self
+            .blocking_executor
+            .execute_io_inline(|| self.cache.lookup(action_digest))
+            .await
+        {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return ControlFlow::Continue(manager),
+            Err(e) => {
+                tracing::warn!("Error reading local action cache: {:#}", e);
+                return ControlFlow::Continue(manager);
+            }
+        };
+
+        let restored = async {
+            create_output_dirs(
+                &self.artifact_fs,
+                request,
+                self.materializer.dupe(),
+                self.blocking_executor.dupe(),
+                cancellations,
+            )
+            .await?;
+            self.blocking_executor
+                .execute_io_inline(|| self.cache.restore(self.artifact_fs.fs(), &entry))
+                .await
+        }
+        .await;
+
+        if let Err(e) = restored {
+            // The outputs written so far are cleaned up before the action executes.
+            tracing::warn!(
+                "Error restoring `{}` from local action cache, dropping it: {:#}",
+                action_digest,
+                e
+            );
+            if let Err(e) = self
+                .blocking_executor
+                .execute_io_inline(|| self.cache.remove(action_digest))
+                .await
+            {
+                tracing::warn!("Error updating local action cache: {:#}", e);
+            }
+            return ControlFlow::Continue(manager);
+        }
+
+        let manager = manager.claim().await;
+        let outputs = match calculate_and_declare_output_values(
+            &self.artifact_fs,
+            &self.materializer,
+            request,
+            command.digest_config,
+        )
+        .await
+        {
+            Ok(outputs) => outputs,
+            Err(e) => return ControlFlow::Break(manager.error("local_action_cache", e)),
+        };
+
+        ControlFlow::Break(manager.success(
+            CommandExecutionKind::ActionCache {
+                digest: action_digest.dupe(),
+            },
+            outputs,
+            CommandStdStreams::Local {
+                stdout: entry.stdout,
+                stderr: entry.stderr,
+            },
+            CommandExecutionMetadata {
+                wall_time: start.elapsed(),
+                execution_time: entry.execution_time,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_common::file_ops::TrackedFileDigest;
+    use buck2_core::fs::project::ProjectRootTemp;
+    use buck2_core::fs::project_rel_path::ProjectRelativePath;
+    use buck2_execute::digest_config::DigestConfig;
+
+    use super::*;
+
+    fn file_value(content: &str, is_executable: bool) -> ArtifactValue {
+        let digest_config = DigestConfig::testing_default();
+        ArtifactValue::file(FileMetadata {
+            digest: TrackedFileDigest::from_content(
+                content.as_bytes(),
+                digest_config.cas_digest_config(),
+            ),
+            is_executable,
+        })
+    }
+
+    fn action_digest(name: &str) -> ActionDigest {
+        ActionDigest::new_sha1([name.as_bytes()[0]; 20], 1)
+    }
+
+    #[test]
+    fn test_store_restore() -> anyhow::Result<()> {
+        let project = ProjectRootTemp::new()?;
+        let fs = project.path();
+        let cache = LocalActionCache::open(
+            fs.root().join(ForwardRelativePath::new("cache")?),
+            1024,
+            false,
+        )?;
+
+        let out = ProjectRelativePathBuf::unchecked_new("out/a".to_owned());
+        fs_util::create_dir_all(fs.resolve(ProjectRelativePath::unchecked_new("out")))?;
+        fs_util::write(fs.resolve(&out), "hello")?;
+
+        let value = file_value("hello", false);
+        let entry = LocalActionCacheEntry::new(
+            [(out.clone(), &value)],
+            b"stdout".to_vec(),
+            Vec::new(),
+            Duration::from_millis(10),
+        );
+        let digest = action_digest("1");
+        assert_eq!(None, cache.lookup(&digest)?);
+        cache.store(fs, &digest, &entry)?;
+
+        fs_util::remove_all(fs.resolve(&out))?;
+        let found = cache.lookup(&digest)?.unwrap();
+        assert_eq!(entry, found);
+        cache.restore(fs, &found)?;
+        assert_eq!("hello", fs_util::read_to_string(fs.resolve(&out))?);
+
+        cache.remove(&digest)?;
+        assert_eq!(None, cache.lookup(&digest)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_restore_compressed() -> anyhow::Result<()> {
+        let project = ProjectRootTemp::new()?;
+        let fs = project.path();
+        let cache_dir = fs.root().join(ForwardRelativePath::new("cache")?);
+        let cache = LocalActionCache::open(cache_dir.clone(), 1024, true)?;
+
+        let content = "hello ".repeat(100);
+        let out = ProjectRelativePathBuf::unchecked_new("out".to_owned());
+        fs_util::write(fs.resolve(&out), &content)?;
+
+        let value = file_value(&content, true);
+        let entry = LocalActionCacheEntry::new(
+            [(out.clone(), &value)],
+            Vec::new(),
+            Vec::new(),
+            Duration::default(),
+        );
+        let digest = action_digest("1");
+        cache.store(fs, &digest, &entry)?;
+
+        let blob = match &entry.outputs[0].kind {
+            LocalActionCacheOutputKind::File { blob, .. } => blob,
+            kind => panic!("Unexpected output {:?}", kind),
+        };
+        let stored = cache.find_blob(blob)?.unwrap();
+        assert!(stored.compressed);
+        assert!(stored.size < content.len() as u64);
+
+        fs_util::remove_all(fs.resolve(&out))?;
+        cache.restore(fs, &cache.lookup(&digest)?.unwrap())?;
+        assert_eq!(content, fs_util::read_to_string(fs.resolve(&out))?);
+
+        // Blobs stored compressed are still found by a cache which doesn't compress.
+        drop(cache);
+        let cache = LocalActionCache::open(cache_dir, 1024, false)?;
+        fs_util::remove_all(fs.resolve(&out))?;
+        cache.restore(fs, &cache.lookup(&digest)?.unwrap())?;
+        assert_eq!(content, fs_util::read_to_string(fs.resolve(&out))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() -> anyhow::Result<()> {
+        let project = ProjectRootTemp::new()?;
+        let fs = project.path();
+        let cache =
+            LocalActionCache::open(fs.root().join(ForwardRelativePath::new("cache")?), 8, false)?;
+
+        let store = |name: &str, content: &str| -> anyhow::Result<ActionDigest> {
+            let out = ProjectRelativePathBuf::unchecked_new(name.to_owned());
+            fs_util::write(fs.resolve(&out), content)?;
+            let value = file_value(content, false);
+            let entry = LocalActionCacheEntry::new(
+                [(out, &value)],
+                Vec::new(),
+                Vec::new(),
+                Duration::default(),
+            );
+            let digest = action_digest(name);
+            cache.store(fs, &digest, &entry)?;
+            Ok(digest)
+        };
+
+        let a = store("a", "aaaa")?;
+        let b = store("b", "bbbb")?;
+        // Use `a` so `b` is the least recently used.
+        assert!(cache.lookup(&a)?.is_some());
+        let c = store("c", "cccc")?;
+
+        assert!(cache.lookup(&a)?.is_some());
+        assert!(cache.lookup(&b)?.is_none());
+        assert!(cache.lookup(&c)?.is_some());
+        Ok(())
+    }
+}
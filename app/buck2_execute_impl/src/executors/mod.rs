@@ -10,6 +10,9 @@
 pub mod action_cache;
 pub mod caching;
 pub mod hybrid;
+pub(crate) mod live_output;
 pub mod local;
+pub mod local_action_cache;
 pub mod re;
+pub mod sandbox;
 pub mod worker;
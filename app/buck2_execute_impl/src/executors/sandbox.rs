@@ -0,0 +1,311 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Sandboxing of local actions.
+//!
+//! A sandboxed action only sees the files of the project which it declares as inputs, the
+//! directories its outputs are written to and its scratch directory. Reading any other file of
+//! the project fails, which catches undeclared dependencies that would otherwise only break on
+//! remote execution. Files outside of the project (e.g. system toolchains) are not restricted.
+//!
+//! On Linux, actions run in a new mount namespace set up by `bwrap` (bubblewrap), where the
+//! project root is replaced by an empty tmpfs and the paths the action may access are bind
+//! mounted back, along with the working directory of the action, read-only. On macOS, actions run under `sandbox-exec` with a profile denying access to the
+//! rest of the project.
+
+use std::collections::HashSet;
+
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_execute::execute::request::CommandExecutionInput;
+use buck2_execute::execute::request::CommandExecutionRequest;
+
+#[derive(Debug, thiserror::Error)]
+enum SandboxError {
+    #[error("Sandboxing local actions is not supported on this platform")]
+    Unsupported,
+}
+
+/// The paths of the project a local action may access.
+pub(crate) struct Sandbox {
+    /// Declared inputs, readable.
+    inputs: Vec<ProjectRelativePathBuf>,
+    /// Directories of declared outputs, and the scratch directory, readable and writable.
+    outputs: Vec<ProjectRelativePathBuf>,
+}
+
+impl Sandbox {
+    pub(crate) fn for_request(
+        artifact_fs: &ArtifactFs,
+        request: &CommandExecutionRequest,
+        scratch_dir: Option<&ProjectRelativePath>,
+    ) -> anyhow::Result<Self> {
+        let mut inputs = Vec::new();
+        for input in request.inputs() {
+            match input {
+                CommandExecutionInput::Artifact(group) => {
+                    for (artifact, _) in group.iter() {
+                        inputs.push(artifact.resolve_path(artifact_fs)?);
+                    }
+                }
+                CommandExecutionInput::ActionMetadata(metadata) => {
+                    inputs.push(
+                        artifact_fs
+                            .buck_out_path_resolver()
+                            .resolve_gen(&metadata.path),
+                    );
+                }
+            }
+        }
+
+        // Outputs don't exist yet, so expose the directories they are created in.
+        let mut outputs: Vec<ProjectRelativePathBuf> = request
+            .paths()
+            .output_paths()
+            .iter()
+            .filter_map(|(path, _)| path.parent().map(|p| p.to_buf()))
+            .collect();
+        outputs.extend(scratch_dir.map(|d| d.to_buf()));
+
+        Ok(Self {
+            inputs: collapse(inputs),
+            outputs: collapse(outputs),
+        })
+    }
+
+    /// The command line running `args` in the sandbox.
+    pub(crate) fn wrap(
+        &self,
+        root: &AbsNormPath,
+        working_directory: &AbsNormPath,
+        args: &[String],
+    ) -> anyhow::Result<Vec<String>> {
+        if cfg!(target_os = "linux") {
+            Ok(self.bwrap_args(root, working_directory, args))
+        } else if cfg!(target_os = "macos") {
+            Ok(self.sandbox_exec_args(root, args))
+        } else {
+            Err(SandboxError::Unsupported.into())
+        }
+    }
+
+    fn bwrap_args(
+        &self,
+        root: &AbsNormPath,
+        working_directory: &AbsNormPath,
+        args: &[String],
+    ) -> Vec<String> {
+        let mut res: Vec<String> = vec![
+            "bwrap".to_owned(),
+            "--die-with-parent".to_owned(),
+            "--dev-bind".to_owned(),
+            "/".to_owned(),
+            "/".to_owned(),
+            "--tmpfs".to_owned(),
+            root.to_string(),
+        ];
+        // A working directory in the project must exist in the sandbox for `--chdir`, so expose
+        // it read-only unless it's the root or already exposed. This comes before the other binds
+        // so that the outputs in it stay writable.
+        if working_directory != root
+            && working_directory.starts_with(root)
+            && !self
+                .inputs
+                .iter()
+                .chain(&self.outputs)
+                .any(|path| working_directory.starts_with(root.join(path)))
+        {
+            res.push("--ro-bind".to_owned());
+            res.push(working_directory.to_string());
+            res.push(working_directory.to_string());
+        }
+        for (flag, paths) in [("--ro-bind", &self.inputs), ("--bind", &self.outputs)] {
+            for path in paths {
+                let path = root.join(path).to_string();
+                res.push(flag.to_owned());
+                res.push(path.clone());
+                res.push(path);
+            }
+        }
+        // The working directory of the process refers to the project root outside of the
+        // sandbox, so change directory again inside it.
+        res.push("--chdir".to_owned());
+        res.push(working_directory.to_string());
+        res.push("--".to_owned());
+        res.extend(args.iter().cloned());
+        res
+    }
+
+    fn sandbox_exec_args(&self, root: &AbsNormPath, args: &[String]) -> Vec<String> {
+        let mut res = vec![
+            "/usr/bin/sandbox-exec".to_owned(),
+            "-p".to_owned(),
+            self.sandbox_profile(root),
+        ];
+        res.extend(args.iter().cloned());
+        res
+    }
+
+    /// Profile for `sandbox-exec`. Later rules take precedence over earlier ones.
+    fn sandbox_profile(&self, root: &AbsNormPath) -> String {
+        let mut profile = String::new();
+        profile.push_str("(version 1)\n(allow default)\n");
+        profile.push_str(&format!(
+            "(deny file-read* file-write* (subpath {}))\n",
+            quote(root)
+        ));
+
+        // Resolving a path requires reading the metadata of its ancestors.
+        let mut ancestors = vec![root.to_buf()];
+        for path in self.inputs.iter().chain(&self.outputs) {
+            let mut parent = path.parent();
+            while let Some(p) = parent {
+                ancestors.push(root.join(p));
+                parent = p.parent();
+            }
+        }
+        ancestors.sort();
+        ancestors.dedup();
+        for ancestor in &ancestors {
+            profile.push_str(&format!(
+                "(allow file-read-metadata (literal {}))\n",
+                quote(ancestor)
+            ));
+        }
+
+        for input in &self.inputs {
+            profile.push_str(&format!(
+                "(allow file-read* (subpath {}))\n",
+                quote(&root.join(input))
+            ));
+        }
+        for output in &self.outputs {
+            profile.push_str(&format!(
+                "(allow file-read* file-write* (subpath {}))\n",
+                quote(&root.join(output))
+            ));
+        }
+        profile
+    }
+}
+
+fn quote(path: &AbsNormPath) -> String {
+    format!(
+        "\"{}\"",
+        path.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Sort paths and remove those nested in another one.
+fn collapse(mut paths: Vec<ProjectRelativePathBuf>) -> Vec<ProjectRelativePathBuf> {
+    // Parents sort before their children.
+    paths.sort();
+    paths.dedup();
+    let mut kept = HashSet::new();
+    let mut res = Vec::with_capacity(paths.len());
+    for path in paths {
+        let mut parent = path.parent();
+        let mut nested = false;
+        while let Some(p) = parent {
+            if kept.contains(p) {
+                nested = true;
+                break;
+            }
+            parent = p.parent();
+        }
+        if !nested {
+            kept.insert(path.clone());
+            res.push(path);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+
+    use crate::executors::sandbox::collapse;
+    use crate::executors::sandbox::Sandbox;
+
+    fn paths(paths: &[&str]) -> Vec<ProjectRelativePathBuf> {
+        paths
+            .iter()
+            .map(|p| ProjectRelativePathBuf::unchecked_new((*p).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn test_collapse() {
+        assert_eq!(
+            paths(&["a/b", "a/b-x", "c"]),
+            collapse(paths(&["c", "a/b/c", "a/b", "a/b-x", "a/b/d/e", "c"]))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bwrap_args() -> anyhow::Result<()> {
+        let root = AbsNormPathBuf::from("/repo".to_owned())?;
+        let sandbox = Sandbox {
+            inputs: paths(&["src/a.c"]),
+            outputs: paths(&["buck-out/gen"]),
+        };
+        let args = sandbox.bwrap_args(&root, &root, &["cc".to_owned(), "src/a.c".to_owned()]);
+        assert_eq!(&["--tmpfs", "/repo"], &args[5..7]);
+        assert_eq!(
+            &["--ro-bind", "/repo/src/a.c", "/repo/src/a.c"],
+            &args[7..10]
+        );
+        assert_eq!("--bind", args[10]);
+        assert_eq!(&["--chdir", "/repo", "--", "cc", "src/a.c"], &args[13..]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bwrap_args_working_directory() -> anyhow::Result<()> {
+        let root = AbsNormPathBuf::from("/repo".to_owned())?;
+        let sandbox = Sandbox {
+            inputs: paths(&["src/a.c"]),
+            outputs: paths(&["buck-out/gen"]),
+        };
+        let args = |working_directory: &str| -> anyhow::Result<Vec<String>> {
+            let working_directory = AbsNormPathBuf::from(working_directory.to_owned())?;
+            Ok(sandbox.bwrap_args(&root, &working_directory, &["cc".to_owned()]))
+        };
+
+        let args_in_project = args("/repo/src")?;
+        assert_eq!(&["--tmpfs", "/repo"], &args_in_project[5..7]);
+        assert_eq!(
+            &["--ro-bind", "/repo/src", "/repo/src"],
+            &args_in_project[7..10]
+        );
+        assert_eq!(
+            &["--ro-bind", "/repo/src/a.c", "/repo/src/a.c"],
+            &args_in_project[10..13]
+        );
+        assert_eq!(
+            &["--chdir", "/repo/src", "--", "cc"],
+            &args_in_project[16..]
+        );
+
+        // Already exposed, or not hidden by the sandbox.
+        for working_directory in ["/repo/buck-out/gen/foo", "/elsewhere"] {
+            let args = args(working_directory)?;
+            assert_eq!("--ro-bind", args[7]);
+            assert_eq!("/repo/src/a.c", args[8]);
+            assert_eq!(&["--chdir", working_directory, "--", "cc"], &args[13..]);
+        }
+        Ok(())
+    }
+}
@@ -9,18 +9,24 @@
 
 use std::collections::HashMap;
 use std::ffi::OsString;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::Weak;
 use std::time::Duration;
+use std::time::Instant;
 
 use buck2_common::client_utils::get_channel_uds;
 use buck2_common::client_utils::retrying;
+use buck2_common::file_ops::TrackedFileDigest;
 use buck2_common::liveliness_observer::LivelinessGuard;
 use buck2_common::liveliness_observer::LivelinessObserver;
 use buck2_common::result::SharedError;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::file_name::FileName;
-use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::execute::kind::CommandExecutionKind;
 use buck2_execute::execute::manager::CommandExecutionManagerExt;
 use buck2_execute::execute::manager::CommandExecutionManagerWithClaim;
@@ -36,10 +42,12 @@ use buck2_worker_proto::execute_command::EnvironmentEntry;
 use buck2_worker_proto::worker_client::WorkerClient;
 use buck2_worker_proto::ExecuteCommand;
 use buck2_worker_proto::ExecuteResponse;
+use dupe::Dupe;
 use futures::future::BoxFuture;
 use futures::future::Shared;
 use futures::FutureExt;
 use indexmap::IndexMap;
+use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 use tonic::transport::Channel;
 
@@ -122,6 +130,7 @@ fn spawn_via_forkserver(
     stdout_path: &AbsNormPathBuf,
     stderr_path: &AbsNormPathBuf,
     socket_path: &AbsNormPathBuf,
+    exited: Arc<AtomicBool>,
 ) -> JoinHandle<anyhow::Result<GatherOutputStatus>> {
     use std::os::unix::ffi::OsStrExt;
 
@@ -151,6 +160,7 @@ fn spawn_via_forkserver(
             .execute(req, async move { liveliness_observer.while_alive().await })
             .await
             .map(|(status, _, _)| status);
+        exited.store(true, Ordering::Relaxed);
 
         // Socket is created by worker so won't exist if initialization fails.
         if fs_util::try_exists(&socket_path)? {
@@ -172,6 +182,7 @@ fn spawn_via_forkserver(
     _stdout_path: &AbsNormPathBuf,
     _stderr_path: &AbsNormPathBuf,
     _socket_path: &AbsNormPathBuf,
+    _exited: Arc<AtomicBool>,
 ) -> JoinHandle<anyhow::Result<GatherOutputStatus>> {
     unreachable!("workers should not be initialized off unix")
 }
@@ -181,10 +192,13 @@ async fn spawn_worker(
     env: impl IntoIterator<Item = (OsString, OsString)>,
     root: &AbsNormPathBuf,
     forkserver: ForkserverClient,
-    dispatcher: EventDispatcher,
+    spawn_number: u64,
+    stats: Arc<WorkerPoolStats>,
 ) -> Result<WorkerHandle, WorkerInitError> {
-    // Use fixed length path at /tmp to avoid 108 character limit for unix domain sockets
-    let dir_name = format!("{}-{}", dispatcher.trace_id(), worker_spec.id);
+    // Use fixed length path at /tmp to avoid 108 character limit for unix domain sockets. Workers
+    // outlive the command which spawned them, so the directory is named after the daemon, and
+    // the same worker may be spawned again after it exited or was idle.
+    let dir_name = format!("{}-{}-{}", std::process::id(), worker_spec.id, spawn_number);
     let worker_dir = AbsNormPathBuf::from("/tmp/buck2_worker".to_owned())
         .map_err(|e| WorkerInitError::InternalError(e.into()))?
         .join(FileName::unchecked_new(&dir_name));
@@ -212,6 +226,7 @@ async fn spawn_worker(
     let env: Vec<(OsString, OsString)> = env.into_iter().chain(worker_env).collect();
 
     let (liveliness_observer, liveliness_guard) = LivelinessGuard::create();
+    let exited = Arc::new(AtomicBool::new(false));
 
     let spawn_fut = spawn_via_forkserver(
         forkserver,
@@ -223,6 +238,7 @@ async fn spawn_worker(
         &stdout_path,
         &stderr_path,
         &socket_path,
+        exited.dupe(),
     );
 
     let initial_delay = Duration::from_millis(50);
@@ -285,22 +301,77 @@ async fn spawn_worker(
         client,
         stdout_path,
         stderr_path,
+        concurrency: worker_spec.concurrency.map(Semaphore::new),
+        exited,
+        failed: AtomicBool::new(false),
+        running: AtomicUsize::new(0),
+        last_used: parking_lot::Mutex::new(Instant::now()),
+        stats,
         _liveliness_guard: liveliness_guard,
     })
 }
 
 type WorkerFuture = Shared<BoxFuture<'static, Result<Arc<WorkerHandle>, Arc<WorkerInitError>>>>;
 
+struct PooledWorker {
+    /// Number of times the worker was spawned before this one.
+    generation: u64,
+    worker: WorkerFuture,
+}
+
+/// Actions share a worker only if they would spawn the same process: a worker whose executable,
+/// the artifacts it is made of or its environment changed is a different worker, and the stale
+/// one is eventually shut down for being idle.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct WorkerKey {
+    id: WorkerId,
+    exe: Vec<String>,
+    inputs_digest: Option<TrackedFileDigest>,
+    env: Vec<(OsString, OsString)>,
+}
+
+type PooledWorkers = parking_lot::Mutex<HashMap<WorkerKey, PooledWorker>>;
+
+/// Cumulative statistics of a `WorkerPool`, reported in snapshots.
+#[derive(Default)]
+struct WorkerPoolStats {
+    spawns: AtomicU64,
+    restarts: AtomicU64,
+    idle_shutdowns: AtomicU64,
+    commands: AtomicU64,
+}
+
+/// The persistent workers of the daemon, shared by all the commands.
 pub struct WorkerPool {
-    workers: Arc<parking_lot::Mutex<HashMap<WorkerId, WorkerFuture>>>,
+    workers: Arc<PooledWorkers>,
+    stats: Arc<WorkerPoolStats>,
 }
 
 impl WorkerPool {
-    pub fn new() -> WorkerPool {
+    /// Workers which run no command for `idle_timeout` are shut down. They are otherwise kept
+    /// until the daemon exits.
+    pub fn new(idle_timeout: Option<Duration>) -> WorkerPool {
         tracing::info!("Creating new WorkerPool");
-        WorkerPool {
+        let pool = WorkerPool {
             workers: Arc::new(parking_lot::Mutex::new(HashMap::default())),
+            stats: Arc::new(WorkerPoolStats::default()),
+        };
+        if let Some(idle_timeout) = idle_timeout {
+            tokio::spawn(shutdown_idle_workers(
+                Arc::downgrade(&pool.workers),
+                pool.stats.dupe(),
+                idle_timeout,
+            ));
         }
+        pool
+    }
+
+    pub fn add_snapshot_stats(&self, snapshot: &mut buck2_data::Snapshot) {
+        snapshot.worker_pool_workers = self.workers.lock().len() as u64;
+        snapshot.worker_pool_spawns = self.stats.spawns.load(Ordering::Relaxed);
+        snapshot.worker_pool_restarts = self.stats.restarts.load(Ordering::Relaxed);
+        snapshot.worker_pool_idle_shutdowns = self.stats.idle_shutdowns.load(Ordering::Relaxed);
+        snapshot.worker_pool_commands = self.stats.commands.load(Ordering::Relaxed);
     }
 
     pub fn get_or_create_worker(
@@ -309,28 +380,92 @@ impl WorkerPool {
         env: impl IntoIterator<Item = (OsString, OsString)>,
         root: &AbsNormPathBuf,
         forkserver: ForkserverClient,
-        dispatcher: EventDispatcher,
     ) -> WorkerFuture {
+        let env: Vec<(OsString, OsString)> = env.into_iter().collect();
+        let key = WorkerKey {
+            id: worker_spec.id,
+            exe: worker_spec.exe.clone(),
+            inputs_digest: worker_spec.inputs_digest.dupe(),
+            env: env.clone(),
+        };
+
         let mut workers = self.workers.lock();
-        if let Some(worker_fut) = workers.get(&worker_spec.id) {
-            worker_fut.clone()
-        } else {
-            let worker_id = worker_spec.id;
-            let worker_spec = worker_spec.clone();
-            let root = root.clone();
-            let env: Vec<(OsString, OsString)> = env.into_iter().collect();
-            let fut = async move {
-                match spawn_worker(&worker_spec, env, &root, forkserver, dispatcher).await {
-                    Ok(worker) => Ok(Arc::new(worker)),
-                    Err(e) => Err(Arc::new(e)),
+        let generation = match workers.get(&key) {
+            Some(pooled) => match pooled.worker.peek() {
+                // Workers which exited or stopped responding are replaced, failures to spawn
+                // are not retried.
+                Some(Ok(worker)) if !worker.is_healthy() => {
+                    tracing::info!(
+                        "Worker {} is unhealthy, respawning it, see worker logs:\n{}\n{}",
+                        worker_spec.id,
+                        worker.stdout_path,
+                        worker.stderr_path,
+                    );
+                    pooled.generation + 1
                 }
-            }
-            .boxed()
-            .shared();
+                _ => return pooled.worker.clone(),
+            },
+            None => 0,
+        };
 
-            workers.insert(worker_id, fut.clone());
-            fut
+        let spawn_number = self.stats.spawns.fetch_add(1, Ordering::Relaxed);
+        if generation > 0 {
+            self.stats.restarts.fetch_add(1, Ordering::Relaxed);
         }
+
+        let worker_spec = worker_spec.clone();
+        let root = root.clone();
+        let stats = self.stats.dupe();
+        let fut = async move {
+            match spawn_worker(&worker_spec, env, &root, forkserver, spawn_number, stats).await {
+                Ok(worker) => Ok(Arc::new(worker)),
+                Err(e) => Err(Arc::new(e)),
+            }
+        }
+        .boxed()
+        .shared();
+
+        workers.insert(
+            key,
+            PooledWorker {
+                generation,
+                worker: fut.clone(),
+            },
+        );
+        fut
+    }
+}
+
+/// Remove the workers idle for `idle_timeout` from the pool, until the pool is dropped. A worker
+/// is shut down once the commands which got it before it was removed are done with it.
+async fn shutdown_idle_workers(
+    workers: Weak<PooledWorkers>,
+    stats: Arc<WorkerPoolStats>,
+    idle_timeout: Duration,
+) {
+    let mut interval = tokio::time::interval(idle_timeout.min(Duration::from_secs(60)));
+    loop {
+        interval.tick().await;
+        let workers = match workers.upgrade() {
+            Some(workers) => workers,
+            None => return,
+        };
+        workers
+            .lock()
+            .retain(|key, pooled| match pooled.worker.peek() {
+                Some(Ok(worker)) if worker.is_idle(idle_timeout) => {
+                    tracing::info!(
+                        "Shutting down worker {} after it was idle for {:?}",
+                        key.id,
+                        idle_timeout
+                    );
+                    stats.idle_shutdowns.fetch_add(1, Ordering::Relaxed);
+                    false
+                }
+                // Let the next command which needs the worker try to spawn it again.
+                Some(Err(_)) => false,
+                _ => true,
+            });
     }
 }
 
@@ -338,9 +473,38 @@ pub struct WorkerHandle {
     client: WorkerClient<Channel>,
     stdout_path: AbsNormPathBuf,
     stderr_path: AbsNormPathBuf,
+    /// Limits the number of commands sent to the worker at once.
+    concurrency: Option<Semaphore>,
+    /// Set when the worker process exits.
+    exited: Arc<AtomicBool>,
+    /// Set when the worker fails to respond to a command.
+    failed: AtomicBool,
+    /// Number of commands sent to the worker, or waiting to be, which are not done yet.
+    running: AtomicUsize,
+    /// When the worker last finished a command, or was spawned.
+    last_used: parking_lot::Mutex<Instant>,
+    stats: Arc<WorkerPoolStats>,
     _liveliness_guard: LivelinessGuard,
 }
 
+/// Marks a command as running on a worker until dropped.
+struct RunningCommand<'a>(&'a WorkerHandle);
+
+impl<'a> RunningCommand<'a> {
+    fn new(worker: &'a WorkerHandle) -> Self {
+        worker.running.fetch_add(1, Ordering::Relaxed);
+        worker.stats.commands.fetch_add(1, Ordering::Relaxed);
+        Self(worker)
+    }
+}
+
+impl Drop for RunningCommand<'_> {
+    fn drop(&mut self) {
+        *self.0.last_used.lock() = Instant::now();
+        self.0.running.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 #[cfg(unix)]
 fn env_entries(env: &[(OsString, OsString)]) -> Vec<EnvironmentEntry> {
     use std::os::unix::ffi::OsStrExt;
@@ -358,6 +522,15 @@ fn env_entries(_env: &[(OsString, OsString)]) -> Vec<EnvironmentEntry> {
 }
 
 impl WorkerHandle {
+    /// Whether the worker can still accept commands. Unhealthy workers are replaced by the pool.
+    pub fn is_healthy(&self) -> bool {
+        !self.exited.load(Ordering::Relaxed) && !self.failed.load(Ordering::Relaxed)
+    }
+
+    fn is_idle(&self, idle_timeout: Duration) -> bool {
+        self.running.load(Ordering::Relaxed) == 0 && self.last_used.lock().elapsed() >= idle_timeout
+    }
+
     pub async fn exec_cmd(
         &self,
         args: &[String],
@@ -371,6 +544,17 @@ impl WorkerHandle {
         let argv: Vec<Vec<u8>> = args.iter().map(|s| s.as_str().into()).collect();
         let env: Vec<EnvironmentEntry> = env_entries(&env);
 
+        let _running = RunningCommand::new(self);
+        let _permit = match &self.concurrency {
+            Some(semaphore) => Some(
+                semaphore
+                    .acquire()
+                    .await
+                    .expect("worker semaphore is never closed"),
+            ),
+            None => None,
+        };
+
         let request = ExecuteCommand { argv, env };
         let response = self.client.clone().execute(request).await;
 
@@ -388,6 +572,7 @@ impl WorkerHandle {
                 )
             }
             Err(err) => {
+                self.failed.store(true, Ordering::Relaxed);
                 (
                     GatherOutputStatus::SpawnFailed(format!(
                         "Error sending ExecuteCommand to worker: {:?}, see worker logs:\n{}\n{}",
@@ -0,0 +1,201 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Deduplication of materialized files with identical contents.
+//!
+//! Files downloaded from the CAS are indexed by digest. When another file with the same digest
+//! is materialized later, it is linked to the file already on disk instead of being downloaded
+//! again.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use buck2_common::file_ops::FileDigest;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use dupe::Dupe;
+use parking_lot::Mutex;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum DedupModeError {
+    #[error(
+        "Invalid value for buckconfig `[buck2] materialization_dedup`. Got `{0}`. Expected one of `none`, `hardlink` or `reflink`."
+    )]
+    InvalidValueForConfig(String),
+}
+
+/// How to materialize a file whose contents are already materialized elsewhere in buck-out.
+#[derive(Copy, Clone, Dupe, Debug, PartialEq, Eq)]
+pub enum DedupMode {
+    /// Always download the file.
+    Disabled,
+    /// Hardlink the file already on disk. All the links share the same data, so a tool modifying
+    /// one of them in place modifies all of them.
+    Hardlink,
+    /// Clone the file already on disk, copy-on-write. Only some filesystems support this (e.g.
+    /// Btrfs or XFS), elsewhere the file is downloaded.
+    Reflink,
+}
+
+impl FromStr for DedupMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "" | "none" => Ok(DedupMode::Disabled),
+            "hardlink" => Ok(DedupMode::Hardlink),
+            "reflink" => Ok(DedupMode::Reflink),
+            v => Err(DedupModeError::InvalidValueForConfig(v.to_owned()).into()),
+        }
+    }
+}
+
+/// Identifies the file on disk, to detect files which were replaced or modified since they were
+/// indexed.
+#[derive(Clone, PartialEq, Eq)]
+struct FileIdentity {
+    len: u64,
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    ino: u64,
+}
+
+impl FileIdentity {
+    fn of(path: &AbsPath) -> anyhow::Result<Self> {
+        let metadata = fs_util::symlink_metadata(path)?;
+        Ok(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            ino: std::os::unix::fs::MetadataExt::ino(&metadata),
+        })
+    }
+}
+
+/// Index of the files materialized from the CAS, by digest and executable bit.
+pub(crate) struct DedupIndex {
+    mode: DedupMode,
+    files: Mutex<HashMap<(FileDigest, bool), (ProjectRelativePathBuf, FileIdentity)>>,
+}
+
+impl DedupIndex {
+    pub(crate) fn new(mode: DedupMode) -> Self {
+        Self {
+            mode,
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        self.mode != DedupMode::Disabled
+    }
+
+    /// Materialize the file at `dest` by linking a file already materialized with the same
+    /// contents. Returns `false` if there is no such file or it could not be linked, in which
+    /// case the caller must materialize the file itself.
+    pub(crate) fn try_link(
+        &self,
+        fs: &ProjectRoot,
+        digest: &FileDigest,
+        is_executable: bool,
+        dest: &ProjectRelativePath,
+    ) -> bool {
+        let key = (digest.dupe(), is_executable);
+        let (src, identity) = match self.files.lock().get(&key) {
+            Some(v) => v.clone(),
+            None => return false,
+        };
+
+        let src_abs = fs.resolve(&src);
+        match FileIdentity::of(&src_abs) {
+            Ok(current) if current == identity => {}
+            _ => {
+                // The file was deleted or replaced since it was indexed.
+                let mut files = self.files.lock();
+                if files.get(&key).map(|(path, _)| path) == Some(&src) {
+                    files.remove(&key);
+                }
+                return false;
+            }
+        }
+
+        let dest_abs = fs.resolve(dest);
+        let res = match self.mode {
+            DedupMode::Disabled => return false,
+            DedupMode::Hardlink => fs_util::hardlink(&src_abs, &dest_abs),
+            DedupMode::Reflink => fs_util::reflink(&src_abs, &dest_abs),
+        };
+        match res {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::debug!("Failed to deduplicate `{}`: {:#}", dest, e);
+                false
+            }
+        }
+    }
+
+    /// Index a file which was just materialized.
+    pub(crate) fn record(
+        &self,
+        fs: &ProjectRoot,
+        digest: &FileDigest,
+        is_executable: bool,
+        path: ProjectRelativePathBuf,
+    ) {
+        if let Ok(identity) = FileIdentity::of(&fs.resolve(&path)) {
+            self.files
+                .lock()
+                .insert((digest.dupe(), is_executable), (path, identity));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::cas_digest::CasDigestConfig;
+    use buck2_common::file_ops::FileDigest;
+    use buck2_core::fs::fs_util;
+    use buck2_core::fs::project::ProjectRootTemp;
+    use buck2_core::fs::project_rel_path::ProjectRelativePath;
+
+    use crate::materializers::dedup::DedupIndex;
+    use crate::materializers::dedup::DedupMode;
+
+    #[test]
+    fn test_hardlink_dedup() -> anyhow::Result<()> {
+        let root = ProjectRootTemp::new()?;
+        let fs = root.path();
+        let digest = FileDigest::from_content(b"content", CasDigestConfig::testing_default());
+
+        let index = DedupIndex::new(DedupMode::Hardlink);
+        let a = ProjectRelativePath::new("a")?;
+        let b = ProjectRelativePath::new("b")?;
+        let c = ProjectRelativePath::new("c")?;
+
+        assert!(!index.try_link(fs, &digest, false, b));
+
+        fs.write_file(a, "content", false)?;
+        index.record(fs, &digest, false, a.to_buf());
+        assert!(!index.try_link(fs, &digest, true, b));
+        assert!(index.try_link(fs, &digest, false, b));
+        assert_eq!("content", fs_util::read_to_string(fs.resolve(b))?);
+
+        // Replacing the indexed file invalidates it.
+        fs_util::remove_file(fs.resolve(a))?;
+        fs.write_file(a, "other", false)?;
+        assert!(!index.try_link(fs, &digest, false, c));
+        assert!(!fs.resolve(c).exists());
+        Ok(())
+    }
+}
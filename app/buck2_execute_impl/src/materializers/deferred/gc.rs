@@ -0,0 +1,210 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Garbage collection of buck-out.
+//!
+//! Artifacts declared by the running daemon, or accessed by recent builds, are reachable, and so
+//! are the artifacts their symlinks point to. Once the materialized artifacts take more space than
+//! allowed, the least recently accessed artifacts which aren't reachable are deleted, a bounded
+//! number at a time, until they fit in the target size again.
+
+use std::collections::HashSet;
+
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use chrono::DateTime;
+use chrono::Utc;
+use dupe::Dupe;
+use futures::FutureExt;
+
+use crate::materializers::deferred::file_tree::FileTree;
+use crate::materializers::deferred::io_handler::IoHandler;
+use crate::materializers::deferred::join_all_existing_futs;
+use crate::materializers::deferred::ArtifactMaterializationStage;
+use crate::materializers::deferred::ArtifactTree;
+use crate::materializers::deferred::CleaningFuture;
+use crate::materializers::deferred::DeferredMaterializerCommandProcessor;
+use crate::materializers::deferred::GcConfiguration;
+use crate::materializers::deferred::ProcessingFuture;
+
+/// The artifacts to collect in one run.
+#[derive(Debug, Default)]
+pub(super) struct Garbage {
+    pub(super) paths: Vec<ProjectRelativePathBuf>,
+    pub(super) bytes: u64,
+}
+
+pub(super) fn find_garbage(
+    tree: &ArtifactTree,
+    config: &GcConfiguration,
+    now: DateTime<Utc>,
+) -> Garbage {
+    let keep_since_time = now - config.min_age;
+
+    let mut total_size = 0;
+    let mut reachable = HashSet::new();
+    let mut roots = Vec::new();
+    let mut candidates = Vec::new();
+
+    for (path, data) in tree.iter_with_paths() {
+        let path = ProjectRelativePathBuf::from(path);
+        match &data.stage {
+            ArtifactMaterializationStage::Materialized {
+                metadata,
+                last_access_time,
+                active,
+            } => {
+                total_size += metadata.size();
+                if *active || *last_access_time >= keep_since_time {
+                    roots.push(path);
+                } else {
+                    candidates.push((*last_access_time, metadata.size(), path));
+                }
+            }
+            // Being materialized, or about to be.
+            ArtifactMaterializationStage::Declared { .. } => roots.push(path),
+        }
+    }
+
+    if total_size <= config.max_size {
+        return Garbage::default();
+    }
+
+    while let Some(path) = roots.pop() {
+        if !reachable.insert(path.clone()) {
+            continue;
+        }
+        if let Some(deps) = tree
+            .prefix_get(&mut path.iter())
+            .and_then(|data| data.deps.as_ref())
+        {
+            roots.extend(tree.find_artifacts(deps));
+        }
+    }
+
+    candidates.sort_by_key(|(last_access_time, ..)| *last_access_time);
+
+    let mut garbage = Garbage::default();
+    for (_, size, path) in candidates {
+        if total_size <= config.target_size || garbage.paths.len() >= config.max_deletions {
+            break;
+        }
+        if reachable.contains(&path) {
+            continue;
+        }
+        total_size -= size;
+        garbage.bytes += size;
+        garbage.paths.push(path);
+    }
+    garbage
+}
+
+/// The deletion of collected artifacts, while it runs. The paths it deletes are no longer in the
+/// tree, so this is how artifacts declared there again wait for it, just like they wait for the
+/// cleanup of whatever was declared there before.
+pub(super) struct GcDeletion {
+    paths: FileTree<()>,
+    future: CleaningFuture,
+}
+
+impl GcDeletion {
+    fn is_finished(&self) -> bool {
+        self.future.peek().is_some()
+    }
+
+    /// The deletion, if it may still be deleting `path`, or something in it.
+    fn future_for(&self, path: &ProjectRelativePath) -> Option<ProcessingFuture> {
+        if self.is_finished() {
+            return None;
+        }
+        let deletes_above = self.paths.prefix_get(&mut path.iter()).is_some();
+        let deletes_below = matches!(
+            self.paths.get_subtree(&mut path.iter()),
+            Ok(Some(subtree)) if !subtree.is_empty()
+        );
+        if deletes_above || deletes_below {
+            Some(ProcessingFuture::Cleaning(self.future.clone()))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: IoHandler> DeferredMaterializerCommandProcessor<T> {
+    /// Invalidate `paths` in the tree, and collect the futures to wait for before writing there:
+    /// the ones which were processing those paths, and the deletion of collected garbage if it
+    /// overlaps them.
+    pub(super) fn invalidate_paths_and_collect_futures(
+        &mut self,
+        paths: Vec<ProjectRelativePathBuf>,
+    ) -> anyhow::Result<Vec<(ProjectRelativePathBuf, ProcessingFuture)>> {
+        let gc_futs = match &self.gc_instance {
+            Some(gc) => paths
+                .iter()
+                .filter_map(|path| Some((path.clone(), gc.future_for(path)?)))
+                .collect(),
+            None => Vec::new(),
+        };
+        let mut futs = self
+            .tree
+            .invalidate_paths_and_collect_futures(paths, self.sqlite_db.as_mut())?;
+        futs.extend(gc_futs);
+        Ok(futs)
+    }
+
+    /// Delete the garbage in buck-out, unless the previous collection is still deleting it.
+    pub(super) fn collect_garbage(&mut self, config: &GcConfiguration) {
+        if let Some(gc) = &self.gc_instance {
+            if !gc.is_finished() {
+                return;
+            }
+        }
+        self.gc_instance = None;
+
+        let garbage = find_garbage(&self.tree, config, Utc::now());
+        if garbage.paths.is_empty() {
+            return;
+        }
+        tracing::info!(
+            "Collecting {} artifacts ({} bytes) from buck-out",
+            garbage.paths.len(),
+            garbage.bytes
+        );
+
+        let existing_futs = self
+            .tree
+            .invalidate_paths_and_collect_futures(garbage.paths.clone(), self.sqlite_db.as_mut());
+        let io = self.io.dupe();
+        let cancellations = self.cancellations;
+
+        let mut paths = FileTree::new();
+        for path in &garbage.paths {
+            paths.insert(path.iter().map(|f| f.to_owned()), ());
+        }
+        // Failing to delete garbage doesn't prevent writing there again: whatever is declared
+        // there cleans its path anyway. So this only logs errors, and the deletion always succeeds.
+        let future = self
+            .rt
+            .spawn(async move {
+                let res = async {
+                    // Wait for the operations in progress on those paths before deleting them.
+                    join_all_existing_futs(existing_futs?).await?;
+                    io.delete_paths(garbage.paths, cancellations).await
+                }
+                .await;
+                if let Err(e) = res {
+                    tracing::warn!("Error collecting garbage from buck-out: {:#}", e);
+                }
+            })
+            .map(|_| Ok(()))
+            .boxed()
+            .shared();
+        self.gc_instance = Some(GcDeletion { paths, future });
+    }
+}
@@ -21,6 +21,7 @@ use buck2_common::result::ToSharedResultExt;
 use buck2_core::directory::unordered_entry_walk;
 use buck2_core::directory::DirectoryEntry;
 use buck2_core::env_helper::EnvHelper;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_events::dispatch::EventDispatcher;
@@ -32,7 +33,10 @@ use buck2_execute::directory::ActionDirectoryMember;
 use buck2_execute::directory::ActionSharedDirectory;
 use buck2_execute::execute::blocking::BlockingExecutor;
 use buck2_execute::execute::blocking::IoRequest;
+use buck2_execute::execute::clean_content_addressed_outputs::CleanContentAddressedOutputs;
+use buck2_execute::execute::clean_content_addressed_outputs::CONTENT_ADDRESSED_OUTPUTS_DIR;
 use buck2_execute::execute::clean_output_paths::cleanup_path;
+use buck2_execute::execute::clean_output_paths::CleanOutputPaths;
 use buck2_execute::materialize::http::http_download;
 use buck2_execute::output_size::OutputSize;
 use buck2_execute::re::manager::ReConnectionManager;
@@ -53,6 +57,7 @@ use remote_execution::TCode;
 use remote_execution::TDigest;
 use tracing::instrument;
 
+use crate::materializers::dedup::DedupIndex;
 use crate::materializers::deferred::ArtifactMaterializationMethod;
 use crate::materializers::deferred::ArtifactMaterializationStage;
 use crate::materializers::deferred::ArtifactTree;
@@ -74,6 +79,7 @@ pub(super) struct DefaultIoHandler {
     /// Executor for blocking IO operations
     pub(super) io_executor: Arc<dyn BlockingExecutor>,
     pub(super) http_client: CountingHttpClient,
+    pub(super) dedup: DedupIndex,
 }
 
 struct MaterializationStat {
@@ -100,6 +106,13 @@ pub(super) trait IoHandler: Sized + Sync + Send + 'static {
         cancellations: &'a CancellationContext,
     ) -> BoxFuture<'a, Result<(), SharedError>>;
 
+    /// Delete paths which the materializer no longer tracks.
+    fn delete_paths(
+        self: &Arc<Self>,
+        paths: Vec<ProjectRelativePathBuf>,
+        cancellations: &'static CancellationContext,
+    ) -> BoxFuture<'static, anyhow::Result<()>>;
+
     async fn materialize_entry(
         self: &Arc<Self>,
         path: ProjectRelativePathBuf,
@@ -143,6 +156,8 @@ impl DefaultIoHandler {
         match method.as_ref() {
             ArtifactMaterializationMethod::CasDownload { info } => {
                 let mut files = Vec::new();
+                // The path, digest and executable bit of each file in `files`, to deduplicate them.
+                let mut file_digests = Vec::new();
 
                 {
                     let mut walk = unordered_entry_walk(entry.as_ref());
@@ -153,7 +168,7 @@ impl DefaultIoHandler {
                             let digest = maybe_tombstone_digest(f.digest.data())?.to_re();
 
                             tracing::trace!(name = %name, digest = %digest, "push download");
-                            let name = self
+                            let resolved_name = self
                                 .fs
                                 .resolve(&name)
                                 .as_maybe_relativized_str()?
@@ -161,13 +176,14 @@ impl DefaultIoHandler {
 
                             files.push(NamedDigestWithPermissions {
                                 named_digest: NamedDigest {
-                                    name,
+                                    name: resolved_name,
                                     digest,
                                     ..Default::default()
                                 },
                                 is_executable: f.is_executable,
                                 ..Default::default()
                             });
+                            file_digests.push((name, f.digest.data().dupe(), f.is_executable));
                         }
                     }
                 }
@@ -177,6 +193,30 @@ impl DefaultIoHandler {
                     .map(|x| u64::try_from(x.named_digest.digest.size_in_bytes).unwrap_or_default())
                     .sum();
 
+                if self.dedup.enabled() {
+                    // Link the files already on disk, and only download the others.
+                    let (downloads, download_digests) = self
+                        .io_executor
+                        .execute_io_inline(|| {
+                            let mut downloads = Vec::new();
+                            let mut download_digests = Vec::new();
+                            for (file, (name, digest, is_executable)) in
+                                files.into_iter().zip(file_digests)
+                            {
+                                if self.dedup.try_link(&self.fs, &digest, is_executable, &name) {
+                                    self.dedup.record(&self.fs, &digest, is_executable, name);
+                                } else {
+                                    downloads.push(file);
+                                    download_digests.push((name, digest, is_executable));
+                                }
+                            }
+                            Ok((downloads, download_digests))
+                        })
+                        .await?;
+                    files = downloads;
+                    file_digests = download_digests;
+                }
+
                 let connection = self.re_client_manager.get_re_connection();
                 let re_client = connection.get_client();
 
@@ -195,6 +235,17 @@ impl DefaultIoHandler {
                             )
                         })),
                     })?;
+
+                if self.dedup.enabled() {
+                    self.io_executor
+                        .execute_io_inline(|| {
+                            for (name, digest, is_executable) in file_digests {
+                                self.dedup.record(&self.fs, &digest, is_executable, name);
+                            }
+                            Ok(())
+                        })
+                        .await?;
+                }
             }
             ArtifactMaterializationMethod::HttpDownload { info } => {
                 async {
@@ -314,6 +365,36 @@ impl IoHandler for DefaultIoHandler {
             .boxed()
     }
 
+    fn delete_paths(
+        self: &Arc<Self>,
+        paths: Vec<ProjectRelativePathBuf>,
+        cancellations: &'static CancellationContext,
+    ) -> BoxFuture<'static, anyhow::Result<()>> {
+        let io_executor = self.io_executor.dupe();
+        let cas_dir = self.buck_out_path.join(ForwardRelativePath::unchecked_new(
+            CONTENT_ADDRESSED_OUTPUTS_DIR,
+        ));
+        async move {
+            // One CleanOutputPaths per path, to delete them in parallel.
+            futures::future::try_join_all(paths.into_iter().map(|path| {
+                io_executor.execute_io(
+                    Box::new(CleanOutputPaths { paths: vec![path] }),
+                    cancellations,
+                )
+            }))
+            .await?;
+            // The content-addressed outputs linking to what was deleted are garbage now.
+            io_executor
+                .execute_io(
+                    Box::new(CleanContentAddressedOutputs { path: cas_dir }),
+                    cancellations,
+                )
+                .await?;
+            anyhow::Ok(())
+        }
+        .boxed()
+    }
+
     /// Materializes an `entry` at `path`, using the materialization `method`
     #[instrument(level = "debug", skip(self, cancellations), fields(path = %path, method = %method, entry = %entry))]
     async fn materialize_entry(
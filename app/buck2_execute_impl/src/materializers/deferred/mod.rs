@@ -10,6 +10,7 @@
 mod clean_stale;
 mod extension;
 mod file_tree;
+mod gc;
 mod io_handler;
 mod subscriptions;
 
@@ -97,8 +98,11 @@ use tokio::sync::oneshot::error::TryRecvError;
 use tokio::time::Interval;
 use tracing::instrument;
 
+use crate::materializers::dedup::DedupIndex;
+use crate::materializers::dedup::DedupMode;
 use crate::materializers::deferred::extension::ExtensionCommand;
 use crate::materializers::deferred::file_tree::FileTree;
+use crate::materializers::deferred::gc::GcDeletion;
 use crate::materializers::deferred::io_handler::DefaultIoHandler;
 use crate::materializers::deferred::io_handler::IoHandler;
 use crate::materializers::deferred::subscriptions::MaterializerSubscriptionOperation;
@@ -172,6 +176,10 @@ pub struct DeferredMaterializerConfigs {
     pub materialize_final_artifacts: bool,
     pub defer_write_actions: bool,
     pub ttl_refresh: TtlRefreshConfiguration,
+    /// How to materialize files already materialized elsewhere.
+    pub dedup: DedupMode,
+    /// Deleting artifacts from buck-out once it grows too large. Disabled if `None`.
+    pub gc: Option<GcConfiguration>,
 }
 
 pub struct TtlRefreshConfiguration {
@@ -180,6 +188,19 @@ pub struct TtlRefreshConfiguration {
     pub enabled: bool,
 }
 
+pub struct GcConfiguration {
+    pub frequency: std::time::Duration,
+    /// Collect once the materialized artifacts take more bytes than this.
+    pub max_size: u64,
+    /// Collect until the materialized artifacts take fewer bytes than this.
+    pub target_size: u64,
+    /// Artifacts accessed more recently than this are used by recent builds, and are never
+    /// collected.
+    pub min_age: Duration,
+    /// Most artifacts to delete at once, so that collecting doesn't hold up the materializer.
+    pub max_deletions: usize,
+}
+
 #[derive(Copy, Dupe, Clone)]
 struct MaterializerCounters {
     sent: &'static AtomicUsize,
@@ -271,6 +292,8 @@ struct DeferredMaterializerCommandProcessor<T: 'static> {
     ttl_refresh_history: Vec<TtlRefreshHistoryEntry>,
     /// The current ttl_refresh instance, if any exists.
     ttl_refresh_instance: Option<oneshot::Receiver<(DateTime<Utc>, anyhow::Result<()>)>>,
+    /// The deletion of the artifacts last collected, if any is running.
+    gc_instance: Option<GcDeletion>,
     cancellations: &'static CancellationContext<'static>,
     stats: Arc<DeferredMaterializerStats>,
 }
@@ -954,6 +977,7 @@ impl DeferredMaterializer {
                     re_client_manager,
                     io_executor,
                     http_client,
+                    dedup: DedupIndex::new(configs.dedup),
                 }),
                 digest_config,
                 sqlite_db,
@@ -966,6 +990,7 @@ impl DeferredMaterializer {
                 subscriptions: MaterializerSubscriptions::new(),
                 ttl_refresh_history: Vec::new(),
                 ttl_refresh_instance: None,
+                gc_instance: None,
                 cancellations,
                 stats,
             }
@@ -982,9 +1007,11 @@ impl DeferredMaterializer {
 
                     let cancellations = CancellationContext::never_cancelled();
 
-                    rt.block_on(
-                        command_processor(cancellations).run(command_receiver, configs.ttl_refresh),
-                    );
+                    rt.block_on(command_processor(cancellations).run(
+                        command_receiver,
+                        configs.ttl_refresh,
+                        configs.gc,
+                    ));
                 }
             })
             .context("Cannot start materializer thread")?;
@@ -1037,12 +1064,14 @@ struct CommandStream<T: 'static> {
     high_priority: UnboundedReceiver<MaterializerCommand<T>>,
     low_priority: UnboundedReceiver<LowPriorityMaterializerCommand>,
     refresh_ttl_ticker: Option<Interval>,
+    gc_ticker: Option<Interval>,
 }
 
 enum Op<T: 'static> {
     Command(MaterializerCommand<T>),
     LowPriorityCommand(LowPriorityMaterializerCommand),
     RefreshTtls,
+    CollectGarbage,
 }
 
 impl<T: 'static> Stream for CommandStream<T> {
@@ -1065,6 +1094,12 @@ impl<T: 'static> Stream for CommandStream<T> {
             }
         }
 
+        if let Some(ticker) = this.gc_ticker.as_mut() {
+            if ticker.poll_tick(cx).is_ready() {
+                return Poll::Ready(Some(Op::CollectGarbage));
+            }
+        }
+
         // We can never be done because we never drop the senders, so let's not bother.
 
         Poll::Pending
@@ -1079,6 +1114,7 @@ impl<T: IoHandler> DeferredMaterializerCommandProcessor<T> {
         mut self,
         commands: MaterializerReceiver<T>,
         ttl_refresh: TtlRefreshConfiguration,
+        gc: Option<GcConfiguration>,
     ) {
         let MaterializerReceiver {
             high_priority,
@@ -1095,10 +1131,15 @@ impl<T: IoHandler> DeferredMaterializerCommandProcessor<T> {
             None
         };
 
+        let gc_ticker = gc.as_ref().map(|gc| {
+            tokio::time::interval_at(tokio::time::Instant::now() + gc.frequency, gc.frequency)
+        });
+
         let mut stream = CommandStream {
             high_priority,
             low_priority,
             refresh_ttl_ticker,
+            gc_ticker,
         };
 
         while let Some(op) = stream.next().await {
@@ -1150,6 +1191,11 @@ impl<T: IoHandler> DeferredMaterializerCommandProcessor<T> {
                         }
                     }
                 }
+                Op::CollectGarbage => {
+                    if let Some(gc) = &gc {
+                        self.collect_garbage(gc);
+                    }
+                }
             }
         }
     }
@@ -1187,9 +1233,7 @@ impl<T: IoHandler> DeferredMaterializerCommandProcessor<T> {
                     "invalidate paths",
                 );
 
-                let existing_futs = self
-                    .tree
-                    .invalidate_paths_and_collect_futures(paths, self.sqlite_db.as_mut());
+                let existing_futs = self.invalidate_paths_and_collect_futures(paths);
 
                 // TODO: This probably shouldn't return a CleanFuture
                 sender
@@ -1396,9 +1440,7 @@ impl<T: IoHandler> DeferredMaterializerCommandProcessor<T> {
         // Always invalidate materializer state before actual deleting from filesystem
         // so there will never be a moment where artifact is deleted but materializer
         // thinks it still exists.
-        let existing_futs = self
-            .tree
-            .invalidate_paths_and_collect_futures(vec![path.to_owned()], self.sqlite_db.as_mut());
+        let existing_futs = self.invalidate_paths_and_collect_futures(vec![path.to_owned()]);
 
         let existing_futs = ExistingFutures(existing_futs);
 
@@ -86,6 +86,72 @@ fn test_remove_path() {
     assert_eq!(removed_subtree.get("a/b/c/e"), Some(&"a/b/c/e".to_owned()));
 }
 
+#[test]
+fn test_find_garbage() {
+    let digest_config = DigestConfig::testing_default();
+    let now = Utc::now();
+
+    let mut tree = ArtifactTree::new();
+    let mut insert = |path: &str, age: Duration, active: bool| {
+        let file = FileMetadata {
+            digest: TrackedFileDigest::from_content(
+                b"0123456789",
+                digest_config.cas_digest_config(),
+            ),
+            is_executable: false,
+        };
+        tree.insert(
+            ProjectRelativePath::unchecked_new(path)
+                .iter()
+                .map(|f| f.to_owned()),
+            Box::new(ArtifactMaterializationData {
+                deps: None,
+                stage: ArtifactMaterializationStage::Materialized {
+                    metadata: ArtifactMetadata::new(&ActionDirectoryEntry::Leaf(
+                        ActionDirectoryMember::File(file),
+                    )),
+                    last_access_time: now - age,
+                    active,
+                },
+                processing: Processing::Done(Version(0)),
+            }),
+        );
+    };
+    insert("out/old", Duration::days(3), false);
+    insert("out/older", Duration::days(4), false);
+    insert("out/recent", Duration::hours(1), false);
+    insert("out/active", Duration::days(5), true);
+
+    let mut config = GcConfiguration {
+        frequency: std::time::Duration::from_secs(60),
+        max_size: 25,
+        target_size: 20,
+        min_age: Duration::days(1),
+        max_deletions: 10,
+    };
+    let garbage = gc::find_garbage(&tree, &config, now);
+    assert_eq!(
+        garbage.paths,
+        vec![
+            ProjectRelativePathBuf::unchecked_new("out/older".to_owned()),
+            ProjectRelativePathBuf::unchecked_new("out/old".to_owned()),
+        ]
+    );
+    assert_eq!(garbage.bytes, 20);
+
+    config.max_deletions = 1;
+    let garbage = gc::find_garbage(&tree, &config, now);
+    assert_eq!(
+        garbage.paths,
+        vec![ProjectRelativePathBuf::unchecked_new(
+            "out/older".to_owned()
+        )]
+    );
+
+    config.max_size = 40;
+    assert!(gc::find_garbage(&tree, &config, now).paths.is_empty());
+}
+
 mod state_machine {
     use std::path::Path;
 
@@ -165,6 +231,18 @@ mod state_machine {
             .boxed()
         }
 
+        fn delete_paths(
+            self: &Arc<Self>,
+            paths: Vec<ProjectRelativePathBuf>,
+            _cancellations: &'static CancellationContext,
+        ) -> BoxFuture<'static, anyhow::Result<()>> {
+            let mut log = self.log.lock();
+            for path in paths {
+                log.push((Op::Clean, path));
+            }
+            futures::future::ready(Ok(())).boxed()
+        }
+
         async fn materialize_entry(
             self: &Arc<Self>,
             path: ProjectRelativePathBuf,
@@ -256,6 +334,7 @@ mod state_machine {
                 subscriptions: MaterializerSubscriptions::new(),
                 ttl_refresh_history: Default::default(),
                 ttl_refresh_instance: Default::default(),
+                gc_instance: None,
                 cancellations: CancellationContext::testing(),
                 stats: Arc::new(DeferredMaterializerStats::default()),
             },
@@ -314,6 +393,64 @@ mod state_machine {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_declare_waits_for_gc() -> anyhow::Result<()> {
+        let digest_config = DigestConfig::testing_default();
+
+        let (mut dm, _) = make_processor(digest_config, Default::default());
+
+        let path = make_path("foo/bar");
+        let file = FileMetadata {
+            digest: TrackedFileDigest::from_content(b"content", digest_config.cas_digest_config()),
+            is_executable: false,
+        };
+        dm.tree.insert(
+            path.iter().map(|f| f.to_owned()),
+            Box::new(ArtifactMaterializationData {
+                deps: None,
+                stage: ArtifactMaterializationStage::Materialized {
+                    metadata: ArtifactMetadata::new(&ActionDirectoryEntry::Leaf(
+                        ActionDirectoryMember::File(file),
+                    )),
+                    last_access_time: Utc::now() - Duration::days(2),
+                    active: false,
+                },
+                processing: Processing::Done(Version(0)),
+            }),
+        );
+
+        dm.collect_garbage(&GcConfiguration {
+            frequency: std::time::Duration::from_secs(60),
+            max_size: 0,
+            target_size: 0,
+            min_age: Duration::days(1),
+            max_deletions: 10,
+        });
+
+        // Declaring the collected path again only cleans it once it was deleted.
+        dm.declare(
+            &path,
+            ArtifactValue::file(digest_config.empty_file()),
+            Box::new(ArtifactMaterializationMethod::Test),
+        );
+        assert_eq!(dm.io.take_log(), &[]);
+
+        let _ignore = dm
+            .materialize_artifact(&path, EventDispatcher::null())
+            .context("Expected a future")?
+            .await;
+        assert_eq!(
+            dm.io.take_log(),
+            &[
+                (Op::Clean, path.clone()),
+                (Op::Clean, path.clone()),
+                (Op::Materialize, path.clone())
+            ]
+        );
+
+        Ok(())
+    }
+
     fn make_artifact_value_with_symlink_dep(
         target_path: &ProjectRelativePathBuf,
         target_from_symlink: &RelativePathBuf,
@@ -10,6 +10,7 @@
 #[cfg(any(fbcode_build, cargo_internal_build))]
 pub mod eden;
 
+pub mod dedup;
 pub mod deferred;
 pub mod immediate;
 pub mod io;
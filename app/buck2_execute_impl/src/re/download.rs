@@ -42,6 +42,7 @@ use buck2_execute::execute::result::CommandExecutionResult;
 use buck2_execute::materialize::materializer::CasDownloadInfo;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::manager::ManagedRemoteExecutionClient;
+use buck2_execute::re::output_trees_cache::OUTPUT_TREES_CACHE;
 use buck2_execute::re::remote_action_result::RemoteActionResult;
 use chrono::DateTime;
 use chrono::Duration;
@@ -207,21 +208,36 @@ impl CasDownloader<'_> {
         }
 
         // Compute the re_outputs from the output_directories
-        // This requires traversing the trees to find symlinks that point outside such trees
+        // This requires traversing the trees to find symlinks that point outside such trees.
+        // Trees seen before are not downloaded again.
+        let mut missing_trees = Vec::new();
+        for dir in output_spec.output_directories() {
+            let tree_digest = FileDigest::from_re(&dir.tree_digest, self.digest_config)?;
+            match OUTPUT_TREES_CACHE.get(&tree_digest) {
+                Some(tree) => {
+                    let entry = re_tree_to_directory(&tree, &expires, self.digest_config)?;
+                    input_dir.insert(
+                        re_forward_path(dir.path.as_str())?,
+                        DirectoryEntry::Dir(entry),
+                    )?;
+                }
+                None => missing_trees.push((dir, tree_digest)),
+            }
+        }
+
         let trees = self
             .re_client
             .download_typed_blobs::<RE::Tree>(
-                output_spec
-                    .output_directories()
-                    .map(|x| x.tree_digest.clone()),
+                missing_trees.iter().map(|(dir, _)| dir.tree_digest.clone()),
                 self.re_use_case,
             )
             .boxed()
             .await
             .context(DownloadError::DownloadTrees)?;
 
-        for (dir, tree) in output_spec.output_directories().iter().zip(trees) {
+        for ((dir, tree_digest), tree) in missing_trees.into_iter().zip(trees) {
             let entry = re_tree_to_directory(&tree, &expires, self.digest_config)?;
+            OUTPUT_TREES_CACHE.insert(tree_digest, Arc::new(tree));
             input_dir.insert(
                 re_forward_path(dir.path.as_str())?,
                 DirectoryEntry::Dir(entry),
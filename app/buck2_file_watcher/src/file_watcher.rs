@@ -9,6 +9,7 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use allocative::Allocative;
 use anyhow::Context;
@@ -31,6 +32,41 @@ pub trait FileWatcher: Allocative + Send + Sync + 'static {
         &self,
         dice: DiceTransactionUpdater,
     ) -> anyhow::Result<(DiceTransactionUpdater, Mergebase)>;
+
+    /// How this watcher is doing, for `buck2 status`.
+    fn status(&self) -> FileWatcherStatus;
+}
+
+pub struct FileWatcherStatus {
+    pub provider: &'static str,
+    /// Number of syncs since the watcher was created.
+    pub syncs: u64,
+    /// Error of the last sync, if it failed.
+    pub last_sync_error: Option<String>,
+}
+
+/// Records the outcome of syncs, to be reported by `FileWatcher::status`.
+#[derive(Allocative, Default)]
+pub(crate) struct SyncTracker {
+    #[allocative(skip)]
+    last: Mutex<(u64, Option<String>)>,
+}
+
+impl SyncTracker {
+    pub(crate) fn record<T>(&self, res: &anyhow::Result<T>) {
+        let mut last = self.last.lock().unwrap();
+        last.0 += 1;
+        last.1 = res.as_ref().err().map(|e| format!("{:#}", e));
+    }
+
+    pub(crate) fn status(&self, provider: &'static str) -> FileWatcherStatus {
+        let last = self.last.lock().unwrap();
+        FileWatcherStatus {
+            provider,
+            syncs: last.0,
+            last_sync_error: last.1.clone(),
+        }
+    }
 }
 
 impl dyn FileWatcher {
@@ -57,7 +93,10 @@ impl dyn FileWatcher {
                 NotifyFileWatcher::new(project_root, cells, ignore_specs)
                     .context("Creating notify file watcher")?,
             )),
-            other => Err(anyhow::anyhow!("Invalid buck2.file_watcher: {}", other)),
+            other => Err(anyhow::anyhow!(
+                "Invalid buck2.file_watcher: `{}`, expected `watchman` or `notify`",
+                other
+            )),
         }
     }
 }
@@ -13,6 +13,7 @@ use std::sync::Arc;
 use std::sync::Mutex;
 
 use allocative::Allocative;
+use anyhow::Context;
 use async_trait::async_trait;
 use buck2_common::dice::file_ops::FileChangeTracker;
 use buck2_common::ignores::ignore_set::IgnoreSet;
@@ -36,6 +37,8 @@ use notify::Watcher;
 use tracing::info;
 
 use crate::file_watcher::FileWatcher;
+use crate::file_watcher::FileWatcherStatus;
+use crate::file_watcher::SyncTracker;
 use crate::mergebase::Mergebase;
 use crate::stats::FileWatcherStats;
 
@@ -83,6 +86,9 @@ impl ChangeType {
 struct NotifyFileData {
     ignored: u64,
     events: OrderedSet<(CellPath, ChangeType)>,
+    /// Set if notify reported an error or told us that events were dropped, in which case
+    /// `events` can't be trusted and everything has to be invalidated.
+    incomplete_events_reason: Option<String>,
 }
 
 impl NotifyFileData {
@@ -90,6 +96,7 @@ impl NotifyFileData {
         Self {
             ignored: 0,
             events: OrderedSet::new(),
+            incomplete_events_reason: None,
         }
     }
 
@@ -99,13 +106,33 @@ impl NotifyFileData {
         root: &ProjectRoot,
         cells: &CellResolver,
         ignore_specs: &HashMap<CellName, IgnoreSet>,
-    ) -> anyhow::Result<()> {
-        let event = event?;
+    ) {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                self.incomplete_events_reason
+                    .get_or_insert_with(|| format!("Notify error: {:#}", e));
+                return;
+            }
+        };
+        if event.need_rescan() {
+            // The OS event queue overflowed (e.g. a large checkout), so we lost events.
+            self.incomplete_events_reason
+                .get_or_insert_with(|| "Notify dropped events".to_owned());
+            return;
+        }
         let change_type = ChangeType::new(event.kind);
         for path in event.paths {
             // Testing shows that we get absolute paths back from the `notify` library.
-            // It's not documented though.
-            let path = root.relativize(AbsNormPath::new(&path)?)?;
+            // It's not documented though. Paths that aren't in the project (which we can get
+            // by following symlinks) can't affect the build.
+            let path = match AbsNormPath::new(&path).and_then(|p| root.relativize(p)) {
+                Ok(path) => path,
+                Err(_) => {
+                    self.ignored += 1;
+                    continue;
+                }
+            };
 
             // We ignore the buck-out prefix, as those are uninteresting events caused by us.
             // We also ignore other buck-out directories, as if you have two isolation dirs running at once, they are not interesting.
@@ -117,7 +144,13 @@ impl NotifyFileData {
                 continue;
             }
 
-            let cell_path = cells.get_cell_path(&path)?;
+            let cell_path = match cells.get_cell_path(&path) {
+                Ok(cell_path) => cell_path,
+                Err(_) => {
+                    self.ignored += 1;
+                    continue;
+                }
+            };
             let ignore = ignore_specs
                 .get(&cell_path.cell())
                 .expect("unexpected cell name mismatch")
@@ -134,7 +167,6 @@ impl NotifyFileData {
                 self.events.insert((cell_path, change_type));
             }
         }
-        Ok(())
     }
 
     fn sync(self) -> (buck2_data::FileWatcherStats, FileChangeTracker) {
@@ -180,7 +212,8 @@ impl NotifyFileData {
 pub struct NotifyFileWatcher {
     #[allocative(skip)]
     watcher: RecommendedWatcher,
-    data: Arc<Mutex<anyhow::Result<NotifyFileData>>>,
+    data: Arc<Mutex<NotifyFileData>>,
+    sync_tracker: SyncTracker,
 }
 
 impl NotifyFileWatcher {
@@ -189,31 +222,66 @@ impl NotifyFileWatcher {
         cells: CellResolver,
         ignore_specs: HashMap<CellName, IgnoreSet>,
     ) -> anyhow::Result<Self> {
-        let data = Arc::new(Mutex::new(Ok(NotifyFileData::new())));
+        let data = Arc::new(Mutex::new(NotifyFileData::new()));
         let data2 = data.dupe();
         let root2 = root.dupe();
         let mut watcher = notify::recommended_watcher(move |event| {
-            let mut guard = data2.lock().unwrap();
-            if let Ok(state) = &mut *guard {
-                if let Err(e) = state.process(event, &root2, &cells, &ignore_specs) {
-                    *guard = Err(e);
-                }
-            }
+            data2
+                .lock()
+                .unwrap()
+                .process(event, &root2, &cells, &ignore_specs);
         })?;
-        watcher.watch(root.root().as_path(), notify::RecursiveMode::Recursive)?;
-        Ok(Self { watcher, data })
+        watcher
+            .watch(root.root().as_path(), notify::RecursiveMode::Recursive)
+            .with_context(|| {
+                format!(
+                    "Watching `{}` for changes. On Linux, large repositories may need a higher \
+                    `fs.inotify.max_user_watches`, or set `buck2.file_watcher = watchman`",
+                    root.root()
+                )
+            })?;
+        Ok(Self {
+            watcher,
+            data,
+            sync_tracker: SyncTracker::default(),
+        })
     }
 
     fn sync2(
         &self,
         mut dice: DiceTransactionUpdater,
     ) -> anyhow::Result<(buck2_data::FileWatcherStats, DiceTransactionUpdater)> {
-        let mut guard = self.data.lock().unwrap();
-        let old = mem::replace(&mut *guard, Ok(NotifyFileData::new()));
-        let (stats, changes) = old?.sync();
+        let old = mem::replace(&mut *self.data.lock().unwrap(), NotifyFileData::new());
+        if let Some(reason) = old.incomplete_events_reason {
+            return Ok(Self::on_incomplete_events(dice, reason));
+        }
+        let (stats, changes) = old.sync();
         changes.write_to_dice(&mut dice)?;
         Ok((stats, dice))
     }
+
+    /// We don't know what changed, so like a Watchman fresh instance, invalidate everything.
+    fn on_incomplete_events(
+        dice: DiceTransactionUpdater,
+        reason: String,
+    ) -> (buck2_data::FileWatcherStats, DiceTransactionUpdater) {
+        info!("FileWatcher: invalidating all state: {}", reason);
+        crate::dep_files::flush_dep_files();
+        let dice = dice.unstable_take();
+        (
+            buck2_data::FileWatcherStats {
+                fresh_instance: true,
+                incomplete_events_reason: Some(reason),
+                fresh_instance_data: Some(buck2_data::FreshInstance {
+                    new_mergebase: false,
+                    cleared_dice: true,
+                    cleared_dep_files: true,
+                }),
+                ..Default::default()
+            },
+            dice,
+        )
+    }
 }
 
 #[async_trait]
@@ -222,7 +290,7 @@ impl FileWatcher for NotifyFileWatcher {
         &self,
         dice: DiceTransactionUpdater,
     ) -> anyhow::Result<(DiceTransactionUpdater, Mergebase)> {
-        span_async(
+        let res = span_async(
             buck2_data::FileWatcherStart {
                 provider: buck2_data::FileWatcherProvider::RustNotify as i32,
             },
@@ -237,6 +305,12 @@ impl FileWatcher for NotifyFileWatcher {
                 (res, buck2_data::FileWatcherEnd { stats })
             },
         )
-        .await
+        .await;
+        self.sync_tracker.record(&res);
+        res
+    }
+
+    fn status(&self) -> FileWatcherStatus {
+        self.sync_tracker.status("notify")
     }
 }
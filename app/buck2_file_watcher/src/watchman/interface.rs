@@ -31,6 +31,8 @@ use watchman_client::prelude::Connector;
 use watchman_client::prelude::FileType;
 
 use crate::file_watcher::FileWatcher;
+use crate::file_watcher::FileWatcherStatus;
+use crate::file_watcher::SyncTracker;
 use crate::mergebase::Mergebase;
 use crate::stats::FileWatcherStats;
 use crate::watchman::core::SyncableQuery;
@@ -276,6 +278,7 @@ impl SyncableQueryProcessor for WatchmanQueryProcessor {
 pub(crate) struct WatchmanFileWatcher {
     #[allocative(skip)]
     query: SyncableQuery<buck2_data::FileWatcherStats, DiceTransactionUpdater>,
+    sync_tracker: SyncTracker,
 }
 
 /// The watchman query is constructed once on daemon startup. It is an unfiltered watchman query
@@ -315,7 +318,10 @@ impl WatchmanFileWatcher {
             watchman_merge_base,
         )?;
 
-        Ok(Self { query })
+        Ok(Self {
+            query,
+            sync_tracker: SyncTracker::default(),
+        })
     }
 }
 
@@ -325,7 +331,7 @@ impl FileWatcher for WatchmanFileWatcher {
         &self,
         dice: DiceTransactionUpdater,
     ) -> anyhow::Result<(DiceTransactionUpdater, Mergebase)> {
-        span_async(
+        let res = span_async(
             buck2_data::FileWatcherStart {
                 provider: buck2_data::FileWatcherProvider::Watchman as i32,
             },
@@ -340,6 +346,12 @@ impl FileWatcher for WatchmanFileWatcher {
                 (res, buck2_data::FileWatcherEnd { stats })
             },
         )
-        .await
+        .await;
+        self.sync_tracker.record(&res);
+        res
+    }
+
+    fn status(&self) -> FileWatcherStatus {
+        self.sync_tracker.status("watchman")
     }
 }
@@ -29,6 +29,8 @@ use tonic::Request;
 use crate::convert::decode_event_stream;
 use crate::run::decode_command_event_stream;
 use crate::run::GatherOutputStatus;
+use crate::run::NoOutputObserver;
+use crate::run::OutputObserver;
 
 #[derive(Clone, Dupe, Allocative)]
 pub struct ForkserverClient {
@@ -93,6 +95,20 @@ impl ForkserverClient {
         req: buck2_forkserver_proto::CommandRequest,
         cancel: C,
     ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
+    where
+        C: Future<Output = ()> + Send + 'static,
+    {
+        self.execute_observed(req, cancel, &mut NoOutputObserver)
+            .await
+    }
+
+    /// Like [Self::execute], also passing the output to `observer` as the command produces it.
+    pub async fn execute_observed<C>(
+        &self,
+        req: buck2_forkserver_proto::CommandRequest,
+        cancel: C,
+        observer: &mut dyn OutputObserver,
+    ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
     where
         C: Future<Output = ()> + Send + 'static,
     {
@@ -124,7 +140,7 @@ impl ForkserverClient {
             .context("Error dispatching command to Forkserver")?
             .into_inner();
         let stream = decode_event_stream(stream);
-        decode_command_event_stream(stream).await
+        decode_command_event_stream(stream, observer).await
     }
 
     pub async fn set_log_filter(&self, log_filter: String) -> anyhow::Result<()> {
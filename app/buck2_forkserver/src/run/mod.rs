@@ -89,6 +89,25 @@ impl From<StdioEvent> for CommandEvent {
     }
 }
 
+/// Receives the output of a command while it runs.
+pub trait OutputObserver: Send {
+    fn stdout(&mut self, bytes: &[u8]);
+
+    fn stderr(&mut self, bytes: &[u8]);
+
+    /// Called once the command exited.
+    fn finish(&mut self) {}
+}
+
+/// Ignores the output until the command exits.
+pub struct NoOutputObserver;
+
+impl OutputObserver for NoOutputObserver {
+    fn stdout(&mut self, _bytes: &[u8]) {}
+
+    fn stderr(&mut self, _bytes: &[u8]) {}
+}
+
 /// This stream will yield [CommandEvent] whenever we have something on stdout or stderr (this is
 /// our stdio stream), and it'll finish up the stream with the exit status. This is basically like
 /// a select, but with the exit guaranteed to come last.
@@ -262,6 +281,7 @@ where
 
 pub(crate) async fn decode_command_event_stream<S>(
     stream: S,
+    observer: &mut dyn OutputObserver,
 ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
 where
     S: Stream<Item = anyhow::Result<CommandEvent>>,
@@ -273,9 +293,18 @@ where
 
     while let Some(event) = stream.try_next().await? {
         match event {
-            CommandEvent::Stdout(bytes) => stdout.extend(&bytes),
-            CommandEvent::Stderr(bytes) => stderr.extend(&bytes),
-            CommandEvent::Exit(exit) => return Ok((exit, stdout, stderr)),
+            CommandEvent::Stdout(bytes) => {
+                observer.stdout(&bytes);
+                stdout.extend(&bytes)
+            }
+            CommandEvent::Stderr(bytes) => {
+                observer.stderr(&bytes);
+                stderr.extend(&bytes)
+            }
+            CommandEvent::Exit(exit) => {
+                observer.finish();
+                return Ok((exit, stdout, stderr));
+            }
         }
     }
 
@@ -288,6 +317,18 @@ pub async fn gather_output<T>(
     cmd: Command,
     cancellation: T,
 ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
+where
+    T: Future<Output = anyhow::Result<GatherOutputStatus>> + Send,
+{
+    gather_output_observed(cmd, cancellation, &mut NoOutputObserver).await
+}
+
+/// Like [gather_output], also passing the output to `observer` as the command produces it.
+pub async fn gather_output_observed<T>(
+    cmd: Command,
+    cancellation: T,
+    observer: &mut dyn OutputObserver,
+) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
 where
     T: Future<Output = anyhow::Result<GatherOutputStatus>> + Send,
 {
@@ -301,7 +342,7 @@ where
         DefaultKillProcess,
         true,
     )?;
-    decode_command_event_stream(stream).await
+    decode_command_event_stream(stream, observer).await
 }
 
 /// Dependency injection for kill. We use this in testing.
@@ -455,6 +496,46 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_gather_output_observed() -> anyhow::Result<()> {
+        #[derive(Default)]
+        struct Observer {
+            stdout: Vec<u8>,
+            stderr: Vec<u8>,
+            finished: bool,
+        }
+
+        impl OutputObserver for Observer {
+            fn stdout(&mut self, bytes: &[u8]) {
+                self.stdout.extend(bytes);
+            }
+
+            fn stderr(&mut self, bytes: &[u8]) {
+                self.stderr.extend(bytes);
+            }
+
+            fn finish(&mut self) {
+                self.finished = true;
+            }
+        }
+
+        let mut cmd = if cfg!(windows) {
+            background_command("powershell")
+        } else {
+            background_command("sh")
+        };
+        cmd.args(["-c", "echo hello"]);
+
+        let mut observer = Observer::default();
+        let (_status, stdout, _stderr) =
+            gather_output_observed(cmd, futures::future::pending(), &mut observer).await?;
+        assert_eq!(stdout, observer.stdout);
+        assert_eq!(b"", &observer.stderr[..]);
+        assert!(observer.finished);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_gather_does_not_wait_for_children() -> anyhow::Result<()> {
         // If we wait for sleep, this will time out.
@@ -689,7 +770,8 @@ mod tests {
             true,
         )?;
 
-        let (status, _stdout, _stderr) = decode_command_event_stream(stream).await?;
+        let (status, _stdout, _stderr) =
+            decode_command_event_stream(stream, &mut NoOutputObserver).await?;
         assert!(matches!(status, GatherOutputStatus::TimedOut(..)));
 
         assert!(*killed.lock().unwrap());
@@ -53,6 +53,9 @@ pub struct StarlarkProfileDataAndStats {
     initialized_at: Instant,
     finalized_at: Instant,
     total_retained_bytes: usize,
+    total_allocated_bytes: usize,
+    peak_allocated_bytes: usize,
+    allocation_count: usize,
 }
 
 impl StarlarkProfileDataAndStats {
@@ -64,6 +67,21 @@ impl StarlarkProfileDataAndStats {
         self.total_retained_bytes
     }
 
+    /// Bytes allocated on the evaluation heap, including memory later garbage collected.
+    pub fn total_allocated_bytes(&self) -> usize {
+        self.total_allocated_bytes
+    }
+
+    /// Peak size of the evaluation heap. When merged, the largest peak of the merged profiles.
+    pub fn peak_allocated_bytes(&self) -> usize {
+        self.peak_allocated_bytes
+    }
+
+    /// Number of values on the evaluation heap when evaluation completed.
+    pub fn allocation_count(&self) -> usize {
+        self.allocation_count
+    }
+
     pub fn merge<'a>(
         datas: impl IntoIterator<Item = &'a StarlarkProfileDataAndStats> + Clone,
     ) -> anyhow::Result<StarlarkProfileDataAndStats> {
@@ -71,6 +89,9 @@ impl StarlarkProfileDataAndStats {
         let first = iter.next().context("empty collection of profile data")?;
         let profile_mode = first.profile_mode.dupe();
         let mut total_retained_bytes = first.total_retained_bytes;
+        let mut total_allocated_bytes = first.total_allocated_bytes;
+        let mut peak_allocated_bytes = first.peak_allocated_bytes;
+        let mut allocation_count = first.allocation_count;
         let mut initialized_at = first.initialized_at;
         let mut finalized_at = first.finalized_at;
 
@@ -81,6 +102,9 @@ impl StarlarkProfileDataAndStats {
             initialized_at = cmp::min(initialized_at, data.initialized_at);
             finalized_at = cmp::max(finalized_at, data.finalized_at);
             total_retained_bytes += data.total_retained_bytes;
+            total_allocated_bytes += data.total_allocated_bytes;
+            peak_allocated_bytes = cmp::max(peak_allocated_bytes, data.peak_allocated_bytes);
+            allocation_count += data.allocation_count;
         }
 
         let profile_data = ProfileData::merge(datas.into_iter().map(|data| &data.profile_data))?;
@@ -91,6 +115,9 @@ impl StarlarkProfileDataAndStats {
             initialized_at,
             finalized_at,
             total_retained_bytes,
+            total_allocated_bytes,
+            peak_allocated_bytes,
+            allocation_count,
         })
     }
 }
@@ -105,6 +132,9 @@ pub struct StarlarkProfiler {
     finalized_at: Option<Instant>,
     profile_data: Option<ProfileData>,
     total_retained_bytes: Option<usize>,
+    total_allocated_bytes: Option<usize>,
+    peak_allocated_bytes: Option<usize>,
+    allocation_count: Option<usize>,
 }
 
 impl StarlarkProfiler {
@@ -116,6 +146,9 @@ impl StarlarkProfiler {
             finalized_at: None,
             profile_data: None,
             total_retained_bytes: None,
+            total_allocated_bytes: None,
+            peak_allocated_bytes: None,
+            allocation_count: None,
         }
     }
 
@@ -132,6 +165,15 @@ impl StarlarkProfiler {
             total_retained_bytes: self
                 .total_retained_bytes
                 .context("did not visit heap (internal error)")?,
+            total_allocated_bytes: self
+                .total_allocated_bytes
+                .context("did not finalize (internal error)")?,
+            peak_allocated_bytes: self
+                .peak_allocated_bytes
+                .context("did not finalize (internal error)")?,
+            allocation_count: self
+                .allocation_count
+                .context("did not finalize (internal error)")?,
             profile_data: self
                 .profile_data
                 .context("profile_data not initialized (internal error)")?,
@@ -153,6 +195,16 @@ impl StarlarkProfiler {
     /// Post-analysis, produce the output of this profiler.
     fn evaluation_complete(&mut self, eval: &mut Evaluator) -> anyhow::Result<()> {
         self.finalized_at = Some(Instant::now());
+        self.total_allocated_bytes = Some(eval.heap().allocated_bytes());
+        self.peak_allocated_bytes = Some(eval.heap().peak_allocated_bytes());
+        self.allocation_count = Some(
+            eval.heap()
+                .allocated_summary()
+                .summary()
+                .values()
+                .map(|(count, _)| count)
+                .sum(),
+        );
         if !matches!(
             self.profile_mode,
             ProfileMode::HeapSummaryRetained | ProfileMode::HeapFlameRetained
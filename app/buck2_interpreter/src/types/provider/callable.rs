@@ -55,6 +55,7 @@ pub trait ProviderCallableLike {
                 let prop = DocProperty {
                     docs: docs.clone(),
                     typ: return_type.clone(),
+                    deprecated: None,
                 };
                 (*name, prop)
             })
@@ -90,6 +91,7 @@ pub trait ProviderCallableLike {
                     params,
                     ret,
                     dot_type,
+                    deprecated,
                 },
             )) => {
                 let summary = if let Some(x) = &docs {
@@ -136,6 +138,7 @@ pub trait ProviderCallableLike {
                     params,
                     ret,
                     dot_type,
+                    deprecated,
                 }))
             }
         }
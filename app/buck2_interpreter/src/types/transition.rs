@@ -17,11 +17,24 @@ use starlark::values::Value;
 enum TransitionError {
     #[error("cfg parameter is not a transition object: {}", _0)]
     WrongType(String),
+    #[error(
+        "`{1}` does not accept split transitions, but `{0}` is a split transition, \
+        use `attrs.split_transition_dep` instead"
+    )]
+    SplitNotAllowed(Arc<TransitionId>, &'static str),
+    #[error(
+        "`{1}` requires a split transition, but `{0}` is not a split transition, \
+        declare it with `transition(split = True)`"
+    )]
+    SplitRequired(Arc<TransitionId>, &'static str),
 }
 
 /// Implemented by starlark transition objects.
 pub trait TransitionValue {
     fn transition_id(&self) -> anyhow::Result<Arc<TransitionId>>;
+
+    /// Whether the transition produces multiple configurations.
+    fn is_split(&self) -> bool;
 }
 
 unsafe impl<'v> ProvidesStaticType<'v> for &'v dyn TransitionValue {
@@ -34,3 +47,22 @@ pub fn transition_id_from_value(value: Value) -> anyhow::Result<Arc<TransitionId
         None => Err(TransitionError::WrongType(value.to_repr()).into()),
     }
 }
+
+/// Like `transition_id_from_value`, but also checks that the transition is a split transition
+/// if and only if `split` is set, so misuse is reported where the transition is used, rather
+/// than when a target is configured. `usage` is the function the transition is passed to.
+pub fn transition_id_from_value_of_kind(
+    value: Value,
+    split: bool,
+    usage: &'static str,
+) -> anyhow::Result<Arc<TransitionId>> {
+    let transition = value
+        .request_value::<&dyn TransitionValue>()
+        .ok_or_else(|| TransitionError::WrongType(value.to_repr()))?;
+    let id = transition.transition_id()?;
+    match (transition.is_split(), split) {
+        (true, false) => Err(TransitionError::SplitNotAllowed(id, usage).into()),
+        (false, true) => Err(TransitionError::SplitRequired(id, usage).into()),
+        _ => Ok(id),
+    }
+}
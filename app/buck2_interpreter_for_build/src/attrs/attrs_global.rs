@@ -14,7 +14,7 @@ use anyhow::Context as _;
 use buck2_core::provider::label::ProvidersLabel;
 use buck2_interpreter::coerce::COERCE_TARGET_LABEL;
 use buck2_interpreter::types::provider::callable::ValueAsProviderCallableLike;
-use buck2_interpreter::types::transition::transition_id_from_value;
+use buck2_interpreter::types::transition::transition_id_from_value_of_kind;
 use buck2_node::attrs::attr::Attribute;
 use buck2_node::attrs::attr_type::any::AnyAttrType;
 use buck2_node::attrs::attr_type::AttrType;
@@ -225,6 +225,9 @@ fn attr_module(registry: &mut MethodsBuilder) {
         Attribute::attr(eval, default, doc, coercer)
     }
 
+    /// Takes a target from the user, as a string, and supplies a dependency to the rule.
+    /// The dependency is configured with the outgoing transition `cfg`, applied to the
+    /// configuration of this target, e.g. to build a tool for the host inside a target build.
     fn transition_dep<'v>(
         #[starlark(this)] _this: Value<'v>,
         #[starlark(require = named, default = Vec::new())] providers: Vec<Value<'v>>,
@@ -235,7 +238,7 @@ fn attr_module(registry: &mut MethodsBuilder) {
     ) -> anyhow::Result<AttributeAsStarlarkValue> {
         Attribute::check_not_relative_label(default, "attrs.transition_dep")?;
         let required_providers = dep_like_attr_handle_providers_arg(providers)?;
-        let transition_id = transition_id_from_value(cfg)?;
+        let transition_id = transition_id_from_value_of_kind(cfg, false, "attrs.transition_dep")?;
         let coercer = AttrType::transition_dep(required_providers, transition_id);
 
         let coerced_default = match default {
@@ -272,6 +275,9 @@ fn attr_module(registry: &mut MethodsBuilder) {
         Attribute::attr(eval, default, doc, coercer)
     }
 
+    /// Takes a target from the user, as a string, and supplies a dict of dependencies to the
+    /// rule, one for each configuration produced by the split transition `cfg`, keyed by the
+    /// names the transition returns.
     fn split_transition_dep<'v>(
         #[starlark(this)] _this: Value<'v>,
         #[starlark(require = named, default = Vec::new())] providers: Vec<Value<'v>>,
@@ -282,7 +288,8 @@ fn attr_module(registry: &mut MethodsBuilder) {
     ) -> anyhow::Result<AttributeAsStarlarkValue> {
         Attribute::check_not_relative_label(default, "attrs.split_transition_dep")?;
         let required_providers = dep_like_attr_handle_providers_arg(providers)?;
-        let transition_id = transition_id_from_value(cfg)?;
+        let transition_id =
+            transition_id_from_value_of_kind(cfg, true, "attrs.split_transition_dep")?;
         let coercer = AttrType::split_transition_dep(required_providers, transition_id);
 
         let coerced_default = match default {
@@ -183,6 +183,7 @@ impl ModuleInternals {
                             package: Arc::new(Package {
                                 buildfile_path: self.buildfile_path.dupe(),
                                 oncall,
+                                package_values: self.super_package.package_values_json(),
                             }),
                             recorder: TargetsRecorder::new(),
                         });
@@ -15,6 +15,7 @@ use buck2_node::attrs::attr::CoercedValue;
 use buck2_node::attrs::attr_type::string::StringLiteral;
 use buck2_node::attrs::coerced_attr::CoercedAttr;
 use buck2_node::attrs::internal::attr_is_configurable;
+use buck2_node::attrs::internal::DEFAULT_TARGET_PLATFORM_ATTRIBUTE_FIELD;
 use buck2_node::attrs::internal::NAME_ATTRIBUTE_FIELD;
 use buck2_node::attrs::internal::VISIBILITY_ATTRIBUTE_FIELD;
 use buck2_node::attrs::internal::WITHIN_VIEW_ATTRIBUTE_FIELD;
@@ -84,6 +85,7 @@ impl AttributeSpecExt for AttributeSpec {
 
             let is_visibility = attr_name == VISIBILITY_ATTRIBUTE_FIELD;
             let is_with_view = attr_name == WITHIN_VIEW_ATTRIBUTE_FIELD;
+            let is_default_target_platform = attr_name == DEFAULT_TARGET_PLATFORM_ATTRIBUTE_FIELD;
             if let Some(v) = user_value {
                 let mut coerced = attribute
                     .coerce(
@@ -113,6 +115,12 @@ impl AttributeSpecExt for AttributeSpec {
                             internals.super_package.within_view().dupe(),
                         ));
                     }
+                } else if is_default_target_platform {
+                    if coerced == CoercedValue::Default {
+                        if let Some(platform) = internals.super_package.default_target_platform() {
+                            coerced = CoercedValue::Custom(CoercedAttr::Dep(platform.clone()));
+                        }
+                    }
                 }
 
                 match coerced {
@@ -131,6 +139,10 @@ impl AttributeSpecExt for AttributeSpec {
                     attr_idx,
                     CoercedAttr::WithinView(internals.super_package.within_view().dupe()),
                 );
+            } else if is_default_target_platform {
+                if let Some(platform) = internals.super_package.default_target_platform() {
+                    attr_values.push_sorted(attr_idx, CoercedAttr::Dep(platform.clone()));
+                }
             }
         }
 
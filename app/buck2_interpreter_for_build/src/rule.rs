@@ -8,6 +8,7 @@
  */
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
 use std::sync::Arc;
@@ -17,8 +18,9 @@ use anyhow::Context;
 use buck2_core::bzl::ImportPath;
 use buck2_core::configuration::transition::id::TransitionId;
 use buck2_interpreter::types::rule::FROZEN_RULE_GET_IMPL;
-use buck2_interpreter::types::transition::transition_id_from_value;
+use buck2_interpreter::types::transition::transition_id_from_value_of_kind;
 use buck2_node::attrs::attr::Attribute;
+use buck2_node::attrs::display::AttrDisplayWithContextExt;
 use buck2_node::attrs::spec::AttributeSpec;
 use buck2_node::nodes::unconfigured::RuleKind;
 use buck2_node::nodes::unconfigured::TargetNode;
@@ -32,6 +34,7 @@ use itertools::Itertools;
 use starlark::any::ProvidesStaticType;
 use starlark::docs::DocFunction;
 use starlark::docs::DocItem;
+use starlark::docs::DocParam;
 use starlark::docs::DocStringKind;
 use starlark::docs::DocType;
 use starlark::environment::GlobalsBuilder;
@@ -143,8 +146,6 @@ impl<'v> StarlarkValue<'v> for RuleCallable<'v> {
             .borrow()
             .as_ref()
             .map_or_else(|| "unbound_rule".to_owned(), |rt| rt.name.clone());
-        // TODO(nmj): These return 'None' for default values right now. It's going to take some
-        //            refactoring to get that pulled out of the attributespec
         let parameters_spec = self.attributes.signature(name);
 
         let parameter_types = self
@@ -155,9 +156,32 @@ impl<'v> StarlarkValue<'v> for RuleCallable<'v> {
             .map(|(i, raw_type)| (i, DocType { raw_type }))
             .collect();
         let parameter_docs = self.attributes.docstrings();
+        let mut params = parameters_spec.documentation(parameter_types, parameter_docs);
+        // The signature only knows that attributes are optional, the defaults live in the
+        // attribute spec.
+        let defaults: HashMap<&str, String> = self
+            .attributes
+            .attr_specs()
+            .filter_map(|(name, _idx, attr)| {
+                attr.default()
+                    .map(|d| (name, d.as_display_no_ctx().to_string()))
+            })
+            .collect();
+        for param in &mut params {
+            if let DocParam::Arg {
+                name,
+                default_value: Some(default_value),
+                ..
+            } = param
+            {
+                if let Some(default) = defaults.get(name.as_str()) {
+                    *default_value = default.clone();
+                }
+            }
+        }
         let function_docs = DocFunction::from_docstring(
             DocStringKind::Starlark,
-            parameters_spec.documentation(parameter_types, parameter_docs),
+            params,
             Some(DocType {
                 raw_type: Ty::none(),
             }),
@@ -232,6 +256,10 @@ impl FrozenRuleCallable {
     pub fn attributes(&self) -> &AttributeSpec {
         &self.rule.attributes
     }
+
+    pub fn rule_kind(&self) -> RuleKind {
+        self.rule.rule_kind
+    }
 }
 
 #[starlark_value(type = "rule")]
@@ -321,7 +349,7 @@ pub fn register_rule_function(builder: &mut GlobalsBuilder) {
             })
             .collect::<anyhow::Result<Vec<(String, Attribute)>>>()?;
 
-        let cfg = cfg.try_map(transition_id_from_value)?;
+        let cfg = cfg.try_map(|cfg| transition_id_from_value_of_kind(cfg, false, "rule"))?;
 
         let rule_kind = match (is_configuration_rule, is_toolchain_rule) {
             (false, false) => RuleKind::Normal,
@@ -7,9 +7,11 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use allocative::Allocative;
+use buck2_core::provider::label::ProvidersLabel;
 use buck2_node::visibility::VisibilitySpecification;
 use buck2_node::visibility::WithinViewSpecification;
 use dupe::Dupe;
@@ -21,6 +23,7 @@ pub(crate) struct SuperPackageData {
     package_values: SmallMap<String, OwnedFrozenValue>,
     visibility: VisibilitySpecification,
     within_view: WithinViewSpecification,
+    default_target_platform: Option<ProvidersLabel>,
 }
 
 /// Contents of a `PACKAGE` file merged with contents of containing `PACKAGE` files.
@@ -33,11 +36,13 @@ impl SuperPackage {
         package_values: SmallMap<String, OwnedFrozenValue>,
         visibility: VisibilitySpecification,
         within_view: WithinViewSpecification,
+        default_target_platform: Option<ProvidersLabel>,
     ) -> SuperPackage {
         SuperPackage(Arc::new(SuperPackageData {
             package_values,
             visibility,
             within_view,
+            default_target_platform,
         }))
     }
 
@@ -52,6 +57,20 @@ impl SuperPackage {
     pub(crate) fn within_view(&self) -> &WithinViewSpecification {
         &self.0.within_view
     }
+
+    pub(crate) fn default_target_platform(&self) -> Option<&ProvidersLabel> {
+        self.0.default_target_platform.as_ref()
+    }
+
+    /// Package values encoded as JSON, to be stored in target nodes, so they are available
+    /// outside of the interpreter. Values which cannot be encoded (e.g. functions) are skipped.
+    pub(crate) fn package_values_json(&self) -> BTreeMap<String, String> {
+        self.0
+            .package_values
+            .iter()
+            .filter_map(|(k, v)| Some((k.clone(), v.value().to_json().ok()?)))
+            .collect()
+    }
 }
 
 impl PartialEq for SuperPackage {
@@ -60,13 +79,23 @@ impl PartialEq for SuperPackage {
             package_values: this_values,
             visibility: this_visibility,
             within_view: this_within_view,
+            default_target_platform: this_default_target_platform,
         } = &*self.0;
         let SuperPackageData {
             package_values: other_values,
             visibility: other_visibility,
             within_view: other_within_view,
+            default_target_platform: other_default_target_platform,
         } = &*other.0;
-        (this_visibility, this_within_view) == (other_visibility, other_within_view) && {
+        (
+            this_visibility,
+            this_within_view,
+            this_default_target_platform,
+        ) == (
+            other_visibility,
+            other_within_view,
+            other_default_target_platform,
+        ) && {
             // If either package values are not empty, we cannot compare them
             // because we cannot reliably compare arbitrary Starlark values.
             // So if either package values are not empty, we consider super package not equal.
@@ -9,6 +9,7 @@
 
 use std::cell::RefCell;
 
+use buck2_core::provider::label::ProvidersLabel;
 use buck2_node::visibility::VisibilitySpecification;
 use buck2_node::visibility::WithinViewSpecification;
 use starlark::values::OwnedFrozenValue;
@@ -21,6 +22,8 @@ pub(crate) struct PackageFileVisibilityFields {
     pub(crate) visibility: VisibilitySpecification,
     pub(crate) within_view: WithinViewSpecification,
     pub(crate) inherit: bool,
+    /// If not set, the default target platform of the parent is used.
+    pub(crate) default_target_platform: Option<ProvidersLabel>,
 }
 
 #[derive(Debug)]
@@ -43,6 +46,7 @@ impl PackageFileEvalCtx {
             visibility,
             within_view,
             inherit,
+            default_target_platform,
         } = self.visibility.into_inner().unwrap_or_default();
 
         let (visibility, within_view) = if inherit {
@@ -54,6 +58,14 @@ impl PackageFileEvalCtx {
             (visibility, within_view)
         };
 
-        SuperPackage::new(merged_package_values, visibility, within_view)
+        let default_target_platform =
+            default_target_platform.or_else(|| self.parent.default_target_platform().cloned());
+
+        SuperPackage::new(
+            merged_package_values,
+            visibility,
+            within_view,
+            default_target_platform,
+        )
     }
 }
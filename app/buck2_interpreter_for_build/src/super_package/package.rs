@@ -9,6 +9,7 @@
 
 use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
+use buck2_core::pattern::pattern_type::ProvidersPatternExtra;
 use buck2_core::pattern::ParsedPattern;
 use buck2_node::visibility::VisibilityPattern;
 use buck2_node::visibility::VisibilitySpecification;
@@ -81,6 +82,7 @@ pub(crate) fn register_package_function(globals: &mut GlobalsBuilder) {
         #[starlark(require=named, default=false)] inherit: bool,
         #[starlark(require=named, default=Vec::new())] visibility: Vec<String>,
         #[starlark(require=named, default=Vec::new())] within_view: Vec<String>,
+        #[starlark(require=named)] default_target_platform: Option<&str>,
         eval: &mut Evaluator,
     ) -> anyhow::Result<NoneType> {
         let build_context = BuildContext::from_context(eval)?;
@@ -99,6 +101,17 @@ pub(crate) fn register_package_function(globals: &mut GlobalsBuilder) {
             build_context.cell_info().cell_resolver(),
         )?;
 
+        let default_target_platform = default_target_platform
+            .map(|p| {
+                ParsedPattern::<ProvidersPatternExtra>::parse_precise(
+                    p,
+                    build_context.cell_info().name().name(),
+                    build_context.cell_info().cell_resolver(),
+                )?
+                .as_providers_label(p)
+            })
+            .transpose()?;
+
         match &mut *package_file_eval_ctx.visibility.borrow_mut() {
             Some(_) => return Err(PackageFileError::AtMostOnce.into()),
             x => {
@@ -106,6 +119,7 @@ pub(crate) fn register_package_function(globals: &mut GlobalsBuilder) {
                     visibility,
                     within_view,
                     inherit,
+                    default_target_platform,
                 })
             }
         };
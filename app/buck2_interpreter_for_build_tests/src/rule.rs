@@ -371,6 +371,7 @@ fn returns_documentation() -> anyhow::Result<()> {
             }),
         },
         dot_type: None,
+        deprecated: None,
     });
 
     let tester = rule_tester();
@@ -141,3 +141,45 @@ simple(name = "a")
         a.visibility().unwrap(),
     );
 }
+
+#[tokio::test]
+async fn test_package_default_target_platform_inherited() {
+    let fs = ProjectRootTemp::new().unwrap();
+
+    fs.write_file("rules.bzl", RULES_BZL);
+    fs.write_file(
+        "PACKAGE",
+        r#"
+package(
+    default_target_platform = "//platforms:linux",
+)
+"#,
+    );
+    fs.write_file(
+        "juxtaposition/PACKAGE",
+        r#"
+package(
+    visibility = ["//bbb/..."],
+)
+"#,
+    );
+    fs.write_file(
+        "juxtaposition/BUCK",
+        r#"
+load("//:rules.bzl", "simple")
+simple(name = "a")
+"#,
+    );
+
+    let ctx = calculation(&fs).await;
+
+    let a = ctx
+        .get_target_node(&TargetLabel::testing_parse("root//juxtaposition:a"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Some(&TargetLabel::testing_parse("root//platforms:linux")),
+        a.get_default_target_platform(),
+    );
+}
@@ -197,3 +197,44 @@ async fn test_overwrite_package_value_with_flag() {
             .to_string()
     );
 }
+
+#[tokio::test]
+async fn test_package_values_stored_in_target_nodes() {
+    let fs = ProjectRootTemp::new().unwrap();
+
+    fs.write_file("rules.bzl", RULES);
+    fs.write_file(
+        "PACKAGE",
+        "write_package_value('aaa.bbb', {'x': [1, 2]})\nwrite_package_value('aaa.fff', len)",
+    );
+    fs.write_file(
+        "foo/BUCK",
+        indoc!(
+            r#"
+                load("//:rules.bzl", "rrr")
+                rrr(
+                    name = "foo",
+                    value = "",
+                )
+            "#
+        ),
+    );
+
+    let ctx = calculation(&fs).await;
+    let result = ctx
+        .get_interpreter_results(PackageLabel::testing_parse("root//foo"))
+        .await
+        .unwrap();
+
+    let target_nodes: Vec<_> = result.targets().values().collect();
+    assert_eq!(1, target_nodes.len());
+    // Functions can't be encoded as JSON, so they are skipped.
+    assert_eq!(
+        vec![("aaa.bbb", "{\"x\":[1,2]}")],
+        target_nodes[0]
+            .package_values()
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect::<Vec<_>>()
+    );
+}
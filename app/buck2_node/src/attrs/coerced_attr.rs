@@ -137,6 +137,16 @@ impl CoercedSelector {
         Ok(())
     }
 
+    /// The `select()` keys and their values, in declaration order.
+    pub fn entries(&self) -> &[(TargetLabel, CoercedAttr)] {
+        &self.entries
+    }
+
+    /// The value of the `DEFAULT` key, if any.
+    pub fn default(&self) -> Option<&CoercedAttr> {
+        self.default.as_ref()
+    }
+
     fn all_entries(&self) -> impl Iterator<Item = (CoercedSelectorKeyRef, &CoercedAttr)> {
         self.entries
             .iter()
@@ -464,11 +474,55 @@ impl CoercedAttr {
         }
     }
 
+    /// Calls `f` for each selector in this attribute, including selectors nested in the values
+    /// of other selectors.
+    pub fn for_each_selector<'a>(&'a self, f: &mut dyn FnMut(&'a CoercedSelector)) {
+        match self {
+            CoercedAttr::Selector(selector) => {
+                f(selector);
+                for v in selector.all_values() {
+                    v.for_each_selector(f);
+                }
+            }
+            CoercedAttr::Concat(items) => {
+                for item in items.iter() {
+                    item.for_each_selector(f);
+                }
+            }
+            CoercedAttr::List(list) => {
+                for v in list.iter() {
+                    v.for_each_selector(f);
+                }
+            }
+            CoercedAttr::Tuple(tuple) => {
+                for v in tuple.iter() {
+                    v.for_each_selector(f);
+                }
+            }
+            CoercedAttr::Dict(dict) => {
+                for (k, v) in dict.iter() {
+                    k.for_each_selector(f);
+                    v.for_each_selector(f);
+                }
+            }
+            CoercedAttr::OneOf(box l, _) => l.for_each_selector(f),
+            _ => {}
+        }
+    }
+
     /// If more than one select key matches, select the most specific.
     pub fn select_the_most_specific<'a>(
         ctx: &dyn AttrConfigurationContext,
         select_entries: &'a [(TargetLabel, CoercedAttr)],
     ) -> anyhow::Result<Option<&'a CoercedAttr>> {
+        Ok(Self::select_the_most_specific_entry(ctx, select_entries)?.map(|(_k, v)| v))
+    }
+
+    /// Like `select_the_most_specific`, but also returns the key which was chosen.
+    pub fn select_the_most_specific_entry<'a>(
+        ctx: &dyn AttrConfigurationContext,
+        select_entries: &'a [(TargetLabel, CoercedAttr)],
+    ) -> anyhow::Result<Option<(&'a TargetLabel, &'a CoercedAttr)>> {
         let mut matching: Option<(&TargetLabel, &ConfigSettingData, &CoercedAttr)> = None;
         for (k, v) in select_entries {
             matching = match (ctx.matches(k), matching) {
@@ -489,7 +543,7 @@ impl CoercedAttr {
                 }
             }
         }
-        Ok(matching.map(|(_k, _conf, v)| v))
+        Ok(matching.map(|(k, _conf, v)| (k, v)))
     }
 
     fn select<'a>(
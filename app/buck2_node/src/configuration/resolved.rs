@@ -88,6 +88,16 @@ impl ResolvedConfiguration {
         }
     }
 
+    /// The configuration node for a `select()` key, whether or not it matches.
+    pub fn setting(&self, label: &TargetLabel) -> &ConfigurationNode {
+        self.0
+            .settings
+            .get(&ConfigurationSettingKeyRef(label))
+            .expect(
+                "framework should've ensured all necessary configuration setting keys are present",
+            )
+    }
+
     pub fn matches(&self, label: &TargetLabel) -> Option<&ConfigSettingData> {
         self.setting_matches(ConfigurationSettingKeyRef(label))
     }
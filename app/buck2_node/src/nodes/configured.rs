@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::hash::Hash;
@@ -45,6 +46,7 @@ use crate::attrs::attr_type::query::ResolvedQueryLiterals;
 use crate::attrs::attr_type::string::StringLiteral;
 use crate::attrs::attr_type::AttrType;
 use crate::attrs::coerced_attr::CoercedAttr;
+use crate::attrs::coerced_attr::CoercedSelector;
 use crate::attrs::coerced_attr_full::CoercedAttrFull;
 use crate::attrs::configuration_context::AttrConfigurationContextImpl;
 use crate::attrs::configured_attr::ConfiguredAttr;
@@ -138,6 +140,13 @@ impl TargetNodeOrForward {
         }
     }
 
+    fn package_values(&self) -> &BTreeMap<String, String> {
+        match self {
+            TargetNodeOrForward::TargetNode(node) => node.package_values(),
+            TargetNodeOrForward::Forward(_, forward) => forward.package_values(),
+        }
+    }
+
     fn attr_or_none<'a>(
         &'a self,
         name: &str,
@@ -370,6 +379,30 @@ impl ConfiguredTargetNode {
         traversal.inputs.into_iter()
     }
 
+    /// Names of the attributes which reference `dep`.
+    pub fn attrs_introducing_dep(&self, dep: &ConfiguredTargetLabel) -> Vec<&str> {
+        struct DepFinder<'a> {
+            dep: &'a ConfiguredTargetLabel,
+            found: bool,
+        }
+        impl ConfiguredAttrTraversal for DepFinder<'_> {
+            fn dep(&mut self, dep: &ConfiguredProvidersLabel) -> anyhow::Result<()> {
+                if dep.target() == self.dep {
+                    self.found = true;
+                }
+                Ok(())
+            }
+        }
+        self.attrs(AttrInspectOptions::All)
+            .filter_map(|a| {
+                let mut traversal = DepFinder { dep, found: false };
+                a.traverse(self.label().pkg(), &mut traversal)
+                    .expect("dep finder shouldn't return errors");
+                traversal.found.then_some(a.name)
+            })
+            .collect()
+    }
+
     // TODO(cjhopman): switch to for_each_query?
     pub fn queries(
         &self,
@@ -504,6 +537,11 @@ impl ConfiguredTargetNode {
         self.0.target_node.oncall()
     }
 
+    /// Values set in `PACKAGE` files for the package of this target, encoded as JSON.
+    pub fn package_values(&self) -> &BTreeMap<String, String> {
+        self.0.target_node.package_values()
+    }
+
     fn attr_configuration_context(&self) -> AttrConfigurationContextImpl {
         AttrConfigurationContextImpl::new(
             &self.0.resolved_configuration,
@@ -513,6 +551,33 @@ impl ConfiguredTargetNode {
         )
     }
 
+    pub fn resolved_configuration(&self) -> &ResolvedConfiguration {
+        &self.0.resolved_configuration
+    }
+
+    /// The `select()`s in the attributes of this node, along with the attribute they appear in.
+    pub fn selectors(&self) -> Vec<(&str, &CoercedSelector)> {
+        let mut selectors = Vec::new();
+        for attr in self.0.target_node.attrs(AttrInspectOptions::All) {
+            attr.value
+                .for_each_selector(&mut |selector| selectors.push((attr.name, selector)));
+        }
+        selectors
+    }
+
+    /// The key of `selector` which was chosen when configuring this node, or `None` if the
+    /// `DEFAULT` branch was used.
+    pub fn selected_key<'a>(
+        &self,
+        selector: &'a CoercedSelector,
+    ) -> anyhow::Result<Option<&'a TargetLabel>> {
+        Ok(CoercedAttr::select_the_most_specific_entry(
+            &self.attr_configuration_context(),
+            selector.entries(),
+        )?
+        .map(|(k, _v)| k))
+    }
+
     pub fn attrs<'a>(
         &'a self,
         opts: AttrInspectOptions,
@@ -155,6 +155,18 @@ impl QueryTarget for ConfiguredGraphNodeRef {
         self.0.call_stack()
     }
 
+    fn configuration_hash(&self) -> Option<String> {
+        Some(self.label().cfg().output_hash().to_string())
+    }
+
+    fn attrs_introducing_dep(&self, dep: &Self::NodeRef) -> Vec<String> {
+        self.0
+            .attrs_introducing_dep(dep.label())
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
     fn attr_to_string_alternate(&self, attr: &Self::Attr<'_>) -> String {
         format!(
             "{:#}",
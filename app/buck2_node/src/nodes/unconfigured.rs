@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::sync::Arc;
@@ -25,10 +26,12 @@ use crate::attrs::attr_type::string::StringLiteral;
 use crate::attrs::coerced_attr::CoercedAttr;
 use crate::attrs::coerced_attr_full::CoercedAttrFull;
 use crate::attrs::coerced_deps_collector::CoercedDeps;
+use crate::attrs::coerced_deps_collector::CoercedDepsCollector;
 use crate::attrs::display::AttrDisplayWithContextExt;
 use crate::attrs::inspect_options::AttrInspectOptions;
 use crate::attrs::internal::DEFAULT_TARGET_PLATFORM_ATTRIBUTE_FIELD;
 use crate::attrs::internal::TESTS_ATTRIBUTE_FIELD;
+use crate::attrs::internal::WITHIN_VIEW_ATTRIBUTE_FIELD;
 use crate::attrs::spec::AttributeSpec;
 use crate::attrs::traversal::CoercedAttrTraversal;
 use crate::attrs::values::AttrValues;
@@ -42,11 +45,14 @@ use crate::package::Package;
 use crate::rule::Rule;
 use crate::rule_type::RuleType;
 use crate::visibility::VisibilitySpecification;
+use crate::visibility::WithinViewSpecification;
 
 #[derive(Debug, thiserror::Error)]
 enum TargetNodeError {
     #[error("`visibility` attribute coerced incorrectly (`{0}`) (internal error)")]
     IncorrectVisibilityAttribute(String),
+    #[error("`within_view` attribute coerced incorrectly (`{0}`) (internal error)")]
+    IncorrectWithinViewAttribute(String),
 }
 
 /// Describes a target including its name, type, and the values that the user provided.
@@ -213,6 +219,11 @@ impl TargetNode {
         self.0.package.oncall.as_ref().map(|x| x.as_str())
     }
 
+    /// Values set in `PACKAGE` files for the package of this target, encoded as JSON.
+    pub fn package_values(&self) -> &BTreeMap<String, String> {
+        &self.0.package.package_values
+    }
+
     pub fn visibility(&self) -> anyhow::Result<&VisibilitySpecification> {
         match self.0.attributes.get(AttributeSpec::visibility_attr_id()) {
             Some(CoercedAttr::Visibility(v)) => Ok(v),
@@ -232,6 +243,20 @@ impl TargetNode {
         }
     }
 
+    pub fn within_view(&self) -> anyhow::Result<&WithinViewSpecification> {
+        match self.attr(WITHIN_VIEW_ATTRIBUTE_FIELD, AttrInspectOptions::All)? {
+            Some(CoercedAttr::WithinView(v)) => Ok(v),
+            Some(a) => Err(TargetNodeError::IncorrectWithinViewAttribute(
+                a.as_display_no_ctx().to_string(),
+            )
+            .into()),
+            None => {
+                static DEFAULT: WithinViewSpecification = WithinViewSpecification::PUBLIC;
+                Ok(&DEFAULT)
+            }
+        }
+    }
+
     pub fn is_visible_to(&self, target: &TargetLabel) -> anyhow::Result<bool> {
         if self.label().pkg() == target.pkg() {
             return Ok(true);
@@ -243,6 +268,24 @@ impl TargetNode {
         self.0.rule.attributes.attrs(&self.0.attributes, opts)
     }
 
+    /// Names of the attributes whose values reference `dep` (as any kind of dependency).
+    pub fn attrs_with_dep(&self, dep: &TargetLabel) -> Vec<&str> {
+        self.attrs(AttrInspectOptions::All)
+            .filter(|a| {
+                let mut collector = CoercedDepsCollector::new();
+                a.traverse(self.label().pkg(), &mut collector)
+                    .expect("deps collector shouldn't return errors");
+                collector.deps.contains(dep)
+                    || collector.exec_deps.contains(dep)
+                    || collector.toolchain_deps.contains(dep)
+                    || collector.configuration_deps.contains(dep)
+                    || collector.platform_deps.contains(dep)
+                    || collector.transition_deps.iter().any(|(d, _)| d == dep)
+            })
+            .map(|a| a.name)
+            .collect()
+    }
+
     pub fn platform_deps(&self) -> impl Iterator<Item = &TargetLabel> {
         self.deps_cache().platform_deps.iter()
     }
@@ -493,6 +536,7 @@ pub mod testing {
                 Arc::new(Package {
                     buildfile_path,
                     oncall: None,
+                    package_values: Default::default(),
                 }),
                 label,
                 attributes,
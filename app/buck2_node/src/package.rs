@@ -7,19 +7,20 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use allocative::Allocative;
 use buck2_core::build_file_path::BuildFilePath;
 
 /// Package-specific data for `TargetNode`.
-///
-/// (Note this has nothing to do with `PACKAGE` files which are not implemented
-/// at the moment of writing.)
 #[derive(Debug, Hash, Allocative, Eq, PartialEq)]
 pub struct Package {
     /// The build file which defined this target, e.g. `fbcode//foo/bar/TARGETS`
     pub buildfile_path: Arc<BuildFilePath>,
     /// The oncall attribute, if set
     pub oncall: Option<Arc<String>>,
+    /// Values set with `write_package_value` in this or enclosing `PACKAGE` files, encoded as
+    /// JSON. Values which cannot be encoded as JSON are omitted.
+    pub package_values: BTreeMap<String, String>,
 }
@@ -110,6 +110,17 @@ impl QueryTarget for ConfiguredTargetNode {
         self.call_stack()
     }
 
+    fn configuration_hash(&self) -> Option<String> {
+        Some(self.label().cfg().output_hash().to_string())
+    }
+
+    fn attrs_introducing_dep(&self, dep: &Self::NodeRef) -> Vec<String> {
+        ConfiguredTargetNode::attrs_introducing_dep(self, dep)
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
     fn attr_to_string_alternate(&self, attr: &Self::Attr<'_>) -> String {
         format!(
             "{:#}",
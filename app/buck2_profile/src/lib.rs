@@ -99,5 +99,7 @@ pub fn get_profile_response(
     Ok(buck2_cli_proto::ProfileResponse {
         elapsed: Some(profile_data.elapsed().try_into()?),
         total_retained_bytes: profile_data.total_retained_bytes() as u64,
+        total_allocated_bytes: profile_data.total_allocated_bytes() as u64,
+        package_profiles: Vec::new(),
     })
 }
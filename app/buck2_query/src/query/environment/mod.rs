@@ -9,6 +9,7 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::hash::Hash;
@@ -134,6 +135,31 @@ pub trait QueryTarget: LabeledNode + Dupe + Send + Sync + 'static {
     fn map_attr<R, F: FnMut(Option<&Self::Attr<'_>>) -> R>(&self, key: &str, func: F) -> R;
 
     fn call_stack(&self) -> Option<String>;
+
+    /// Hash of the configuration of this node, for nodes of the configured graph.
+    fn configuration_hash(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the names of the attributes through which this node depends on `dep`, used to
+    /// explain the edges of paths found by `somepath` and `allpaths`.
+    ///
+    /// The default implementation looks for attribute values which render as the label of `dep`
+    /// (possibly with a subtarget).
+    fn attrs_introducing_dep(&self, dep: &Self::NodeRef) -> Vec<String> {
+        let dep = dep.to_string();
+        let subtarget_prefix = format!("{}[", dep);
+        let mut names = Vec::new();
+        self.attrs_for_each::<anyhow::Error, _>(|name, attr| {
+            if Self::attr_any_matches(attr, &|v| Ok(v == dep || v.starts_with(&subtarget_prefix)))?
+            {
+                names.push(name.to_owned());
+            }
+            Ok(())
+        })
+        .ok();
+        names
+    }
 }
 
 #[async_trait]
@@ -248,6 +274,54 @@ pub trait QueryEnvironment: Send + Sync {
         Ok(delegate.path)
     }
 
+    /// Like `somepath`, but returns a path with the fewest edges. The path is ordered the same
+    /// way, starting from the target in `to`.
+    async fn shortestpath(
+        &self,
+        from: &TargetSet<Self::Target>,
+        to: &TargetSet<Self::Target>,
+    ) -> anyhow::Result<TargetSet<Self::Target>> {
+        // Restrict the search to the nodes which are on some path, so we only traverse the
+        // graph once and the breadth first search below can be done synchronously.
+        let universe = self.allpaths(from, to).await?;
+
+        let mut parents: HashMap<
+            &<Self::Target as LabeledNode>::NodeRef,
+            Option<&<Self::Target as LabeledNode>::NodeRef>,
+        > = HashMap::new();
+        let mut queue = VecDeque::new();
+        for target in from.iter() {
+            if let Some(target) = universe.get(target.node_ref()) {
+                if parents.insert(target.node_ref(), None).is_none() {
+                    queue.push_back(target);
+                }
+            }
+        }
+
+        while let Some(target) = queue.pop_front() {
+            if to.contains(target.node_ref()) {
+                let mut path = TargetSet::new();
+                let mut node = Some(target.node_ref());
+                while let Some(node_ref) = node {
+                    // Every node on the path was taken from the universe.
+                    path.insert(universe.get(node_ref).unwrap().dupe());
+                    node = parents[node_ref];
+                }
+                return Ok(path);
+            }
+            for dep in target.deps() {
+                if let Some(dep) = universe.get(dep) {
+                    if !parents.contains_key(dep.node_ref()) {
+                        parents.insert(dep.node_ref(), Some(target.node_ref()));
+                        queue.push_back(dep);
+                    }
+                }
+            }
+        }
+
+        Ok(TargetSet::new())
+    }
+
     async fn allbuildfiles(&self, _universe: &TargetSet<Self::Target>) -> anyhow::Result<FileSet> {
         Err(anyhow::anyhow!(QueryError::FunctionUnimplemented(
             "allbuildfiles() is implemented only for uquery and cquery.",
@@ -279,6 +279,10 @@ async fn test_many_paths() -> anyhow::Result<()> {
     let expected = env.set("3,11,10,1")?;
     assert_eq!(path, expected);
 
+    let path = env.shortestpath(&env.set("1")?, &env.set("3")?).await?;
+    let expected = env.set("3,2,1")?;
+    assert_eq!(path, expected);
+
     Ok(())
 }
 
@@ -318,6 +322,10 @@ async fn test_no_path() -> anyhow::Result<()> {
     let expected = TargetSet::new();
     assert_eq!(path, expected);
 
+    let path = env.shortestpath(&env.set("1")?, &env.set("20")?).await?;
+    let expected = TargetSet::new();
+    assert_eq!(path, expected);
+
     Ok(())
 }
 
@@ -335,6 +343,9 @@ async fn test_nested_paths() -> anyhow::Result<()> {
     let path = env.somepath(&env.set("1")?, &env.set("2,4")?).await?;
     assert_eq!(path, env.set("2,1")?);
 
+    let path = env.shortestpath(&env.set("1")?, &env.set("2,4")?).await?;
+    assert_eq!(path, env.set("2,1")?);
+
     Ok(())
 }
 
@@ -359,6 +370,9 @@ async fn test_paths_with_cycles_present() -> anyhow::Result<()> {
     let path = env.allpaths(&env.set("1")?, &env.set("5")?).await?;
     assert_eq!(path, env.set("1,2,3,4,5")?);
 
+    let path = env.shortestpath(&env.set("1")?, &env.set("5")?).await?;
+    assert_eq!(path, env.set("5,4,3,2,1")?);
+
     let path = env.rdeps(&env.set("1")?, &env.set("3")?, Some(2)).await?;
     assert_eq!(path, env.set("3,2,4,1")?);
 
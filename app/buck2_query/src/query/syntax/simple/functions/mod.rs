@@ -209,6 +209,8 @@ impl<Env: QueryEnvironment> DefaultQueryFunctionsModule<Env> {
     /// ```
     ///
     /// Graphviz is an open-source graph-visualization software tool. Graphviz uses the dot language to describe graphs.
+    ///
+    /// To see which attributes introduce each of the edges, use `--output-format=path`.
     async fn allpaths(
         &self,
         env: &Env,
@@ -218,6 +220,18 @@ impl<Env: QueryEnvironment> DefaultQueryFunctionsModule<Env> {
         Ok(self.implementation.allpaths(env, &from, &to).await?.into())
     }
 
+    /// The `somepath(from, to)` function evaluates to a single path from a target in `from` to a target in `to`, following the dependencies between nodes.
+    ///
+    /// Combined with `--output-format=path`, it answers "why does A depend on B": each hop of the path is printed along with the attribute that introduced the edge. For example:
+    ///
+    /// ```ignore
+    /// $ buck2 cquery "somepath('//foo:bar', '//foo/bar/lib:baz')" --output-format=path
+    /// //foo:bar (cfg)
+    ///   -> //foo/bar:lib (cfg) (via deps)
+    /// //foo/bar:lib (cfg)
+    ///   -> //foo/bar/lib:baz (cfg) (via exported_deps)
+    /// //foo/bar/lib:baz (cfg)
+    /// ```
     async fn somepath(
         &self,
         env: &Env,
@@ -227,6 +241,22 @@ impl<Env: QueryEnvironment> DefaultQueryFunctionsModule<Env> {
         Ok(self.implementation.somepath(env, &from, &to).await?.into())
     }
 
+    /// The `shortestpath(from, to)` function is like `somepath(from, to)`, but evaluates to a path with the fewest dependency edges from a target in `from` to a target in `to`.
+    ///
+    /// It is what `buck2 explain` uses to show why a target is part of a build.
+    async fn shortestpath(
+        &self,
+        env: &Env,
+        from: TargetSet<Env::Target>,
+        to: TargetSet<Env::Target>,
+    ) -> QueryFuncResult<Env> {
+        Ok(self
+            .implementation
+            .shortestpath(env, &from, &to)
+            .await?
+            .into())
+    }
+
     async fn attrfilter(
         &self,
         attr: String,
@@ -435,6 +465,15 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         Ok(env.somepath(from, to).await?)
     }
 
+    pub async fn shortestpath(
+        &self,
+        env: &Env,
+        from: &TargetSet<Env::Target>,
+        to: &TargetSet<Env::Target>,
+    ) -> Result<TargetSet<Env::Target>, QueryError> {
+        Ok(env.shortestpath(from, to).await?)
+    }
+
     pub fn attrfilter(
         &self,
         attr: &str,
@@ -35,7 +35,8 @@ impl AqueryEvaluator<'_> {
         query: &str,
         query_args: &[String],
     ) -> anyhow::Result<QueryEvaluationResult<ActionQueryNode>> {
-        eval_query(&self.functions, query, query_args, async move |literals| {
+        let query = self.dice_query_delegate.expand_query_aliases(query).await?;
+        eval_query(&self.functions, &query, query_args, async move |literals| {
             let resolved_literals =
                 PreresolvedQueryLiterals::pre_resolve(&*self.dice_query_delegate, &literals).await;
             Ok(AqueryEnvironment::new(
@@ -47,7 +47,8 @@ impl CqueryEvaluator<'_> {
         query_args: &[A],
         target_universe: Option<&[U]>,
     ) -> anyhow::Result<QueryEvaluationResult<ConfiguredTargetNode>> {
-        eval_query(&self.functions, query, query_args, async move |literals| {
+        let query = self.dice_query_delegate.expand_query_aliases(query).await?;
+        eval_query(&self.functions, &query, query_args, async move |literals| {
             let (universe, resolved_literals) = match target_universe {
                 None => {
                     if literals.is_empty() {
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::borrow::Cow;
 use std::hash::Hash;
 use std::sync::Arc;
 
@@ -234,6 +235,13 @@ impl<'c> DiceAqueryDelegate<'c> {
         )
         .await
     }
+
+    pub(crate) async fn expand_query_aliases<'q>(
+        &self,
+        query: &'q str,
+    ) -> anyhow::Result<Cow<'q, str>> {
+        self.base_delegate.expand_query_aliases(query).await
+    }
 }
 
 #[async_trait]
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -15,6 +17,7 @@ use buck2_build_api::configure_targets::load_compatible_patterns;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::data::HasIoProvider;
 use buck2_common::dice::file_ops::HasFileOps;
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
 use buck2_common::package_boundary::HasPackageBoundaryExceptions;
 use buck2_common::package_boundary::PackageBoundaryExceptions;
 use buck2_common::package_listing::dice::HasPackageListingResolver;
@@ -54,6 +57,7 @@ use buck2_node::target_calculation::ConfiguredTargetCalculation;
 use buck2_query::query::syntax::simple::eval::file_set::FileNode;
 use buck2_query::query::syntax::simple::eval::file_set::FileSet;
 use buck2_query::query::syntax::simple::eval::set::TargetSet;
+use buck2_query_parser::alias::expand_query_aliases;
 use dice::DiceComputations;
 use dupe::Dupe;
 use gazebo::prelude::*;
@@ -187,6 +191,26 @@ impl<'c> DiceQueryDelegate<'c> {
     pub(crate) fn global_target_platform(&self) -> Option<&TargetLabel> {
         self.global_target_platform.as_ref()
     }
+
+    /// Expands the query aliases defined in the `[query_aliases]` section of the buckconfig of
+    /// the working dir's cell.
+    pub(crate) async fn expand_query_aliases<'q>(
+        &self,
+        query: &'q str,
+    ) -> anyhow::Result<Cow<'q, str>> {
+        let config = self
+            .ctx
+            .get_legacy_config_for_cell(self.literal_parser.working_dir.cell())
+            .await?;
+        let aliases: BTreeMap<String, String> = match config.get_section("query_aliases") {
+            Some(section) => section
+                .iter()
+                .map(|(name, value)| (name.to_owned(), value.as_str().to_owned()))
+                .collect(),
+            None => BTreeMap::new(),
+        };
+        expand_query_aliases(query, &aliases)
+    }
 }
 
 #[async_trait]
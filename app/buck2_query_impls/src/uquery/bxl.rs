@@ -124,6 +124,16 @@ impl<'c> BxlUqueryFunctions<'c> for BxlUqueryFunctionsImpl<'c> {
             .owner(&self.uquery_env().await?, file_set)
             .await?)
     }
+    async fn allbuildfiles(&self, universe: &TargetSet<TargetNode>) -> anyhow::Result<FileSet> {
+        Ok(uquery_functions()
+            .allbuildfiles(&self.uquery_env().await?, universe)
+            .await?)
+    }
+    async fn rbuildfiles(&self, universe: &FileSet, argset: &FileSet) -> anyhow::Result<FileSet> {
+        Ok(uquery_functions()
+            .rbuildfiles(&self.uquery_env().await?, universe, argset)
+            .await?)
+    }
 }
 
 pub(crate) fn init_new_bxl_uquery_functions() {
@@ -0,0 +1,195 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Expansion of query aliases, which are named query templates (usually defined in the
+//! `[query_aliases]` buckconfig section) called like functions.
+//!
+//! With `mytests = kind(".*_test", deps(%s))`, the query `mytests(//foo:bar)` expands to
+//! `(kind(".*_test", deps(//foo:bar)))`. Aliases may be used in the definition of other aliases.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use thiserror::Error;
+
+use crate::parse_expr;
+use crate::placeholder::QUERY_PERCENT_S_PLACEHOLDER;
+use crate::Expr;
+use crate::SpannedExpr;
+
+#[derive(Debug, Error)]
+enum QueryAliasError {
+    #[error("Query alias `{0}` takes {1} argument(s), got {2}")]
+    WrongNumberOfArgs(String, usize, usize),
+    #[error("Cycle in query aliases: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// Collects the calls to aliases in `expr` which are not nested in another alias call.
+fn outermost_alias_calls<'e, 'a>(
+    expr: &'e SpannedExpr<'a>,
+    aliases: &BTreeMap<String, String>,
+    calls: &mut Vec<&'e SpannedExpr<'a>>,
+) {
+    match &expr.value {
+        Expr::Function {
+            function_name,
+            args,
+        } => {
+            if aliases.contains_key(function_name.fragment()) {
+                calls.push(expr);
+            } else {
+                for arg in args {
+                    outermost_alias_calls(arg, aliases, calls);
+                }
+            }
+        }
+        Expr::BinaryOpSequence(left, rest) => {
+            outermost_alias_calls(left, aliases, calls);
+            for (_, right) in rest {
+                outermost_alias_calls(right, aliases, calls);
+            }
+        }
+        Expr::String(..) | Expr::Integer(..) | Expr::Set(..) | Expr::FileSet(..) => {}
+    }
+}
+
+fn expand<'q>(
+    query: &'q str,
+    aliases: &BTreeMap<String, String>,
+    stack: &mut Vec<String>,
+) -> anyhow::Result<Cow<'q, str>> {
+    let expr = parse_expr(query)?;
+    let mut calls = Vec::new();
+    outermost_alias_calls(&expr, aliases, &mut calls);
+    if calls.is_empty() {
+        return Ok(Cow::Borrowed(query));
+    }
+
+    let mut expanded = String::new();
+    let mut last = 0;
+    for call in calls {
+        let (name, args) = match &call.value {
+            Expr::Function {
+                function_name,
+                args,
+            } => (function_name.fragment(), args),
+            _ => unreachable!("only function calls are collected"),
+        };
+        let template = &aliases[name];
+        let expected_args = if template.contains(QUERY_PERCENT_S_PLACEHOLDER) {
+            1
+        } else {
+            0
+        };
+        if args.len() != expected_args {
+            return Err(QueryAliasError::WrongNumberOfArgs(
+                name.to_owned(),
+                expected_args,
+                args.len(),
+            )
+            .into());
+        }
+        if stack.iter().any(|s| s == name) {
+            let mut cycle = stack.clone();
+            cycle.push(name.to_owned());
+            return Err(QueryAliasError::Cycle(cycle).into());
+        }
+
+        let body = match args.first() {
+            Some(arg) => {
+                let arg = expand(&query[arg.position.clone()], aliases, stack)?;
+                template.replace(QUERY_PERCENT_S_PLACEHOLDER, &arg)
+            }
+            None => template.clone(),
+        };
+        stack.push(name.to_owned());
+        let body = expand(&body, aliases, stack)
+            .with_context(|| format!("Error expanding query alias `{}`", name))?
+            .into_owned();
+        stack.pop();
+
+        expanded.push_str(&query[last..call.position.start]);
+        // Parenthesize the expansion so it binds like the function call it replaces.
+        expanded.push('(');
+        expanded.push_str(&body);
+        expanded.push(')');
+        last = call.position.end;
+    }
+    expanded.push_str(&query[last..]);
+    Ok(Cow::Owned(expanded))
+}
+
+/// Replaces calls to the given aliases in `query` by their definitions, with `%s` in the
+/// definition replaced by the argument of the call.
+///
+/// Queries which do not parse are returned unchanged, so that evaluating them reports the error.
+pub fn expand_query_aliases<'q>(
+    query: &'q str,
+    aliases: &BTreeMap<String, String>,
+) -> anyhow::Result<Cow<'q, str>> {
+    if aliases.is_empty() || parse_expr(query).is_err() {
+        return Ok(Cow::Borrowed(query));
+    }
+    expand(query, aliases, &mut Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_expand() -> anyhow::Result<()> {
+        let aliases = aliases(&[
+            ("mytests", "kind(\".*_test\", deps(%s))"),
+            ("lib", "//foo:lib"),
+            ("libtests", "mytests(lib())"),
+        ]);
+        assert_eq!(
+            "(kind(\".*_test\", deps(//foo:bar))) + //baz:qux",
+            expand_query_aliases("mytests(//foo:bar) + //baz:qux", &aliases)?
+        );
+        assert_eq!(
+            "((kind(\".*_test\", deps((//foo:lib)))))",
+            expand_query_aliases("libtests()", &aliases)?
+        );
+        assert_eq!(
+            "deps((kind(\".*_test\", deps(set(a b)))), 1)",
+            expand_query_aliases("deps(mytests(set(a b)), 1)", &aliases)?
+        );
+        assert!(matches!(
+            expand_query_aliases("deps(//foo:bar)", &aliases)?,
+            Cow::Borrowed(..)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        let aliases = aliases(&[
+            ("mytests", "kind(\".*_test\", deps(%s))"),
+            ("a", "b()"),
+            ("b", "a()"),
+        ]);
+        assert!(expand_query_aliases("mytests()", &aliases).is_err());
+        assert!(expand_query_aliases("mytests(//a:b, //c:d)", &aliases).is_err());
+        assert!(
+            format!("{:#}", expand_query_aliases("a()", &aliases).unwrap_err())
+                .contains("Cycle in query aliases: a -> b -> a")
+        );
+    }
+}
@@ -44,6 +44,7 @@
 //!
 //! ```
 
+pub mod alias;
 pub mod placeholder;
 pub mod span;
 pub mod spanned;
@@ -8,6 +8,7 @@
  */
 
 use std::str::FromStr;
+use std::time::Duration;
 
 use allocative::Allocative;
 use buck2_common::legacy_configs::LegacyBuckConfig;
@@ -162,6 +163,13 @@ pub struct Buck2OssReConfiguration {
     pub capabilities: Option<bool>,
     /// The instance name to use in requests.
     pub instance_name: Option<String>,
+    /// Maximum total size of the blobs in a batch request, overriding the
+    /// `max_batch_total_size_bytes` reported by the server. Larger blobs use ByteStream.
+    pub max_total_batch_size: Option<usize>,
+    /// Maximum size of a message received from the RE services.
+    pub max_decoding_message_size: Option<usize>,
+    /// Whether to transfer blobs zstd-compressed, if the server supports it.
+    pub compression: bool,
 }
 
 #[derive(Clone, Debug, Default, Allocative)]
@@ -215,6 +223,104 @@ impl Buck2OssReConfiguration {
                 .unwrap_or_default(), // Empty list is as good None.
             capabilities: legacy_config.parse(BUCK2_RE_CLIENT_CFG_SECTION, "capabilities")?,
             instance_name: legacy_config.parse(BUCK2_RE_CLIENT_CFG_SECTION, "instance_name")?,
+            max_total_batch_size: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "max_total_batch_size")?,
+            max_decoding_message_size: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "max_decoding_message_size")?,
+            compression: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "compression")?
+                .unwrap_or(false),
+        })
+    }
+}
+
+/// How to retry one kind of RE call.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetryPolicy {
+    /// How many times to retry a call failing with a transient error.
+    pub retries: u32,
+    /// Deadline for each attempt.
+    pub timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    fn from_legacy_config(legacy_config: &LegacyBuckConfig, method: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            retries: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, &format!("{}_retries", method))?
+                .unwrap_or(0),
+            timeout: legacy_config
+                .parse(
+                    BUCK2_RE_CLIENT_CFG_SECTION,
+                    &format!("{}_timeout_ms", method),
+                )?
+                .map(Duration::from_millis),
+        })
+    }
+}
+
+/// Retries of RE calls, and when to consider RE unhealthy. This applies to all builds.
+#[derive(Clone, Debug)]
+pub struct RemoteExecutionRetryConfiguration {
+    pub action_cache: RetryPolicy,
+    pub write_action_result: RetryPolicy,
+    pub execute: RetryPolicy,
+    pub upload: RetryPolicy,
+    pub download: RetryPolicy,
+    /// Queries of the expiration of digests (`FindMissingBlobs`).
+    pub find_missing: RetryPolicy,
+    /// Delay before the first retry, doubled for every later one.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Number of calls failing in a row after which RE is considered unhealthy, and actions
+    /// which can run locally do. Zero disables this.
+    pub circuit_breaker_threshold: u32,
+    /// How long RE is considered unhealthy before calls are tried again.
+    pub circuit_breaker_cooldown: Duration,
+}
+
+impl Default for RemoteExecutionRetryConfiguration {
+    fn default() -> Self {
+        Self {
+            action_cache: RetryPolicy::default(),
+            write_action_result: RetryPolicy::default(),
+            execute: RetryPolicy::default(),
+            upload: RetryPolicy::default(),
+            download: RetryPolicy::default(),
+            find_missing: RetryPolicy::default(),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            circuit_breaker_threshold: 0,
+            circuit_breaker_cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RemoteExecutionRetryConfiguration {
+    pub fn from_legacy_config(legacy_config: &LegacyBuckConfig) -> anyhow::Result<Self> {
+        let default = Self::default();
+        Ok(Self {
+            action_cache: RetryPolicy::from_legacy_config(legacy_config, "action_cache")?,
+            write_action_result: RetryPolicy::from_legacy_config(
+                legacy_config,
+                "write_action_result",
+            )?,
+            execute: RetryPolicy::from_legacy_config(legacy_config, "execute")?,
+            upload: RetryPolicy::from_legacy_config(legacy_config, "upload")?,
+            download: RetryPolicy::from_legacy_config(legacy_config, "download")?,
+            find_missing: RetryPolicy::from_legacy_config(legacy_config, "find_missing")?,
+            initial_backoff: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "retry_initial_backoff_ms")?
+                .map_or(default.initial_backoff, Duration::from_millis),
+            max_backoff: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "retry_max_backoff_ms")?
+                .map_or(default.max_backoff, Duration::from_millis),
+            circuit_breaker_threshold: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "circuit_breaker_threshold")?
+                .unwrap_or(default.circuit_breaker_threshold),
+            circuit_breaker_cooldown: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "circuit_breaker_cooldown_s")?
+                .map_or(default.circuit_breaker_cooldown, Duration::from_secs),
         })
     }
 }
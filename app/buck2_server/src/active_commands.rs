@@ -25,11 +25,58 @@ use dupe::Dupe;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use parking_lot::MutexGuard;
+use tokio::sync::broadcast;
 use tokio::sync::oneshot;
 
 static ACTIVE_COMMANDS: Lazy<Mutex<HashMap<TraceId, ActiveCommandHandle>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// File changes observed by any command, for subscriptions. Subscribers that fall behind by more
+/// than this many notifications lose the oldest ones.
+static FILE_CHANGES: Lazy<broadcast::Sender<Arc<buck2_subscription_proto::FileChanges>>> =
+    Lazy::new(|| broadcast::channel(16).0);
+
+/// Receive the file changes reported by the file watcher when commands sync it.
+pub fn subscribe_to_file_changes() -> broadcast::Receiver<Arc<buck2_subscription_proto::FileChanges>>
+{
+    FILE_CHANGES.subscribe()
+}
+
+fn file_changes(
+    trace_id: String,
+    stats: &buck2_data::FileWatcherStats,
+) -> buck2_subscription_proto::FileChanges {
+    use buck2_subscription_proto::file_change::Kind;
+    use buck2_subscription_proto::file_change::Type;
+
+    buck2_subscription_proto::FileChanges {
+        trace_id,
+        fresh_instance: stats.fresh_instance,
+        changes: stats
+            .events
+            .iter()
+            .map(|e| {
+                let r#type = match e.event() {
+                    buck2_data::FileWatcherEventType::Create => Type::Create,
+                    buck2_data::FileWatcherEventType::Modify => Type::Modify,
+                    buck2_data::FileWatcherEventType::Delete => Type::Delete,
+                };
+                let kind = match e.kind() {
+                    buck2_data::FileWatcherKind::File => Kind::File,
+                    buck2_data::FileWatcherKind::Directory => Kind::Directory,
+                    buck2_data::FileWatcherKind::Symlink => Kind::Symlink,
+                };
+                buck2_subscription_proto::FileChange {
+                    path: e.path.clone(),
+                    r#type: r#type as i32,
+                    kind: kind as i32,
+                }
+            })
+            .collect(),
+        incomplete_reason: stats.incomplete_events_reason.clone(),
+    }
+}
+
 /// Return the active commands, if you can access them.
 pub fn try_active_commands() -> Option<HashMap<TraceId, ActiveCommandHandle>> {
     // Note that this function is accessed during panic, so have to be super careful
@@ -100,7 +147,6 @@ impl Drop for ActiveCommandDropGuard {
 
 /// A handle to the stats for this command. We use this to broadcast state about this command.
 pub struct ActiveCommandState {
-    #[allow(unused)]
     pub argv: Vec<String>,
 
     spans: Mutex<SpansSnapshot>,
@@ -174,7 +220,20 @@ impl ActiveCommandStateWriter {
                     self.non_roots.insert(span_id);
                 }
             }
-            SpanEnd(..) => {
+            SpanEnd(end) => {
+                if let Some(buck2_data::span_end_event::Data::FileWatcher(
+                    buck2_data::FileWatcherEnd { stats: Some(stats) },
+                )) = &end.data
+                {
+                    if stats.fresh_instance || !stats.events.is_empty() {
+                        // An error only means nobody is subscribed.
+                        let _ignored = FILE_CHANGES.send(Arc::new(file_changes(
+                            buck_event.event().trace_id.clone(),
+                            stats,
+                        )));
+                    }
+                }
+
                 let span_id = match buck_event.span_id() {
                     Some(id) => id,
                     None => return,
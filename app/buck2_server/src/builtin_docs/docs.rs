@@ -48,6 +48,7 @@ use starlark::environment::Globals;
 
 use super::bxl_docs::get_builtin_bxl_docs;
 use crate::builtin_docs::markdown::generate_markdown_files;
+use crate::builtin_docs::rules::get_rule_docs;
 
 #[derive(Debug, thiserror::Error)]
 enum DocsError {
@@ -292,6 +293,22 @@ async fn docs(
         &request.symbol_patterns,
     )?;
 
+    if request.rules {
+        let prelude_path = prelude_path(&cell_resolver)?;
+        let rule_docs =
+            get_rule_docs(&dice_ctx, std::iter::once(prelude_path).chain(lookups)).await?;
+        return Ok(match format {
+            Format::Json => UnstableDocsResponse {
+                json_output: Some(rule_docs.to_json()?),
+                markdown_output: None,
+            },
+            Format::Markdown => UnstableDocsResponse {
+                json_output: None,
+                markdown_output: Some(rule_docs.to_markdown()),
+            },
+        });
+    }
+
     let mut docs = if request.retrieve_builtins {
         get_builtin_docs(dice_ctx.get_global_interpreter_state().await?.dupe())?
     } else {
@@ -325,5 +342,8 @@ async fn docs(
         }
     };
 
-    Ok(UnstableDocsResponse { json_output })
+    Ok(UnstableDocsResponse {
+        json_output,
+        markdown_output: None,
+    })
 }
@@ -10,3 +10,4 @@
 mod bxl_docs;
 pub mod docs;
 pub(crate) mod markdown;
+mod rules;
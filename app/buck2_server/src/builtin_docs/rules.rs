@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Documentation for the rules, toolchain rules and providers reachable from the prelude,
+//! generated from the `rule()` and `provider()` definitions themselves.
+
+use buck2_core::bzl::ImportPath;
+use buck2_interpreter::load_module::InterpreterCalculation;
+use buck2_interpreter_for_build::rule::FrozenRuleCallable;
+use buck2_node::nodes::unconfigured::RuleKind;
+use dice::DiceComputations;
+use starlark::docs::Doc;
+use starlark::docs::Identifier;
+use starlark::docs::Location;
+use starlark::docs::MarkdownFlavor;
+use starlark::docs::RenderMarkdown;
+use starlark::values::structs::StructRef;
+use starlark::values::Value;
+use starlark::values::ValueLike;
+
+#[derive(Default)]
+pub(crate) struct RuleDocs {
+    rules: Vec<Doc>,
+    toolchain_rules: Vec<Doc>,
+    providers: Vec<Doc>,
+}
+
+impl RuleDocs {
+    fn add(&mut self, name: &str, location: &str, value: Value) {
+        let docs = if let Some(rule) = value.downcast_ref::<FrozenRuleCallable>() {
+            match rule.rule_kind() {
+                RuleKind::Toolchain => &mut self.toolchain_rules,
+                RuleKind::Normal | RuleKind::Configuration => &mut self.rules,
+            }
+        } else if value.get_type() == "provider_callable" {
+            &mut self.providers
+        } else {
+            return;
+        };
+        if let Some(item) = value.documentation() {
+            docs.push(Doc {
+                id: Identifier {
+                    name: name.to_owned(),
+                    location: Some(Location {
+                        path: location.to_owned(),
+                        position: None,
+                    }),
+                },
+                item,
+                custom_attrs: Default::default(),
+            });
+        }
+    }
+
+    /// Sort by name, dropping symbols which were reachable in more than one way (e.g. both
+    /// as a global and as a member of `native`).
+    fn finish(mut self) -> Self {
+        for docs in [
+            &mut self.rules,
+            &mut self.toolchain_rules,
+            &mut self.providers,
+        ] {
+            docs.sort_by(|a, b| a.id.name.cmp(&b.id.name));
+            docs.dedup_by(|a, b| a.id.name == b.id.name);
+        }
+        self
+    }
+
+    pub(crate) fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(&serde_json::json!({
+            "rules": self.rules,
+            "toolchain_rules": self.toolchain_rules,
+            "providers": self.providers,
+        }))?)
+    }
+
+    pub(crate) fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for (title, docs) in [
+            ("Rules", &self.rules),
+            ("Toolchain rules", &self.toolchain_rules),
+            ("Providers", &self.providers),
+        ] {
+            if docs.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("# {}\n\n", title));
+            for doc in docs {
+                if let Some(markdown) = doc.render_markdown_opt(MarkdownFlavor::DocFile) {
+                    out.push_str(markdown.trim_end());
+                    out.push_str("\n\n");
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Collect the rules, toolchain rules and providers exported by each of `import_paths`,
+/// including those exported as members of a struct (like `native` in the prelude).
+pub(crate) async fn get_rule_docs(
+    ctx: &DiceComputations,
+    import_paths: impl IntoIterator<Item = ImportPath>,
+) -> anyhow::Result<RuleDocs> {
+    let mut docs = RuleDocs::default();
+    for import_path in import_paths {
+        let location = format!(
+            "{}:{}",
+            import_path.path().parent().unwrap(),
+            import_path.path().path().file_name().unwrap()
+        );
+        let module = ctx.get_loaded_module_from_import_path(&import_path).await?;
+        let env = module.env();
+        for name in env.names() {
+            let value = match env.get_option(name.as_str())? {
+                Some(value) => value,
+                None => continue,
+            };
+            docs.add(name.as_str(), &location, value.value());
+            if let Some(members) = StructRef::from_value(value.value()) {
+                for (member_name, member) in members.iter() {
+                    docs.add(member_name.as_str(), &location, member);
+                }
+            }
+        }
+    }
+    Ok(docs.finish())
+}
@@ -7,8 +7,15 @@
  * of this source tree.
  */
 
+use std::sync::Arc;
+
 use anyhow::Context;
 use async_trait::async_trait;
+use buck2_common::legacy_configs::LegacyBuckConfig;
+use buck2_core::rollout_percentage::RolloutPercentage;
+use buck2_events::dispatch::with_dispatcher_async;
+use buck2_events::dispatch::EventDispatcher;
+use buck2_execute::materialize::materializer::Materializer;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::partial_result_dispatcher::NoPartialResult;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
@@ -18,6 +25,7 @@ use chrono::TimeZone;
 use chrono::Utc;
 use dice::DiceTransaction;
 
+use crate::active_commands::active_commands;
 use crate::ctx::ServerCommandContext;
 
 pub(crate) async fn clean_stale_command(
@@ -91,3 +99,95 @@ impl ServerCommandTemplate for CleanStaleServerCommand {
         Some("clean --stale".to_owned())
     }
 }
+
+/// Cleaning of stale artifacts in the background, so that `buck-out` stays bounded on hosts
+/// which never run `buck2 clean --stale` themselves.
+pub(crate) struct PeriodicCleanStaleConfig {
+    start_offset: std::time::Duration,
+    period: std::time::Duration,
+    artifact_ttl: chrono::Duration,
+    dry_run: bool,
+}
+
+impl PeriodicCleanStaleConfig {
+    pub(crate) fn from_config(config: &LegacyBuckConfig) -> anyhow::Result<Option<Self>> {
+        let enabled = config
+            .parse::<RolloutPercentage>("buck2", "clean_stale_enabled")?
+            .unwrap_or_else(RolloutPercentage::never)
+            .roll();
+        if !enabled {
+            return Ok(None);
+        }
+
+        let hours = |key, default: u64| -> anyhow::Result<u64> {
+            let value = config.parse::<u64>("buck2", key)?.unwrap_or(default);
+            value
+                .checked_mul(3600)
+                .with_context(|| format!("Invalid `buck2.{}`: {} is too large", key, value))
+        };
+        // A period of 0 would mean cleaning continuously, which is never what's wanted.
+        let period = hours("clean_stale_period_hours", 24)?;
+        if period == 0 {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            start_offset: std::time::Duration::from_secs(hours(
+                "clean_stale_start_offset_hours",
+                12,
+            )?),
+            period: std::time::Duration::from_secs(period),
+            artifact_ttl: chrono::Duration::seconds(
+                hours("clean_stale_artifact_ttl_hours", 24 * 7)? as i64,
+            ),
+            dry_run: config
+                .parse("buck2", "clean_stale_dry_run")?
+                .unwrap_or(false),
+        }))
+    }
+}
+
+/// Clean stale artifacts every `period`, for as long as the daemon is alive.
+pub(crate) async fn periodic_clean_stale(
+    materializer: Arc<dyn Materializer>,
+    config: PeriodicCleanStaleConfig,
+) {
+    let mut ticker = tokio::time::interval_at(
+        tokio::time::Instant::now() + config.start_offset,
+        config.period,
+    );
+    loop {
+        ticker.tick().await;
+
+        // Commands may be using the artifacts we would delete, so only clean when the daemon is
+        // idle, and otherwise wait for the next period.
+        if !active_commands().is_empty() {
+            tracing::info!("Skipping scheduled clean of stale artifacts, a command is running");
+            continue;
+        }
+
+        match clean_stale_once(&*materializer, &config).await {
+            Ok(response) => {
+                tracing::info!("Scheduled clean of stale artifacts: {:?}", response.stats)
+            }
+            Err(e) => tracing::warn!("Scheduled clean of stale artifacts failed: {:#}", e),
+        }
+    }
+}
+
+async fn clean_stale_once(
+    materializer: &dyn Materializer,
+    config: &PeriodicCleanStaleConfig,
+) -> anyhow::Result<buck2_cli_proto::CleanStaleResponse> {
+    let extension = materializer
+        .as_deferred_materializer_extension()
+        .context("Deferred materializer is not in use")?;
+    let keep_since_time = Utc::now()
+        .checked_sub_signed(config.artifact_ttl)
+        .context("Duration underflow")?;
+    // There is no command to report events to.
+    with_dispatcher_async(
+        EventDispatcher::null(),
+        extension.clean_stale_artifacts(keep_since_time, config.dry_run, false),
+    )
+    .await
+}
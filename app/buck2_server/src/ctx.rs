@@ -13,11 +13,17 @@ use std::io::BufWriter;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::Instant;
 
 use allocative::Allocative;
 use anyhow::Context;
 use async_trait::async_trait;
+use buck2_build_api::actions::cas_backfill::CasBackfillReruns;
+use buck2_build_api::actions::cas_backfill::HasCasBackfillReruns;
+use buck2_build_api::actions::execute::action_timings::ActionTimingsCollector;
+use buck2_build_api::actions::execute::action_timings::HasActionTimings;
+use buck2_build_api::actions::impls::run_action_knobs::ActionTimeouts;
 use buck2_build_api::actions::impls::run_action_knobs::HasRunActionKnobs;
 use buck2_build_api::actions::impls::run_action_knobs::RunActionKnobs;
 use buck2_build_api::build::HasCreateUnhashedSymlinkLock;
@@ -56,6 +62,7 @@ use buck2_core::cells::CellResolver;
 use buck2_core::facebook_only;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
@@ -78,6 +85,9 @@ use buck2_execute::re::client::RemoteExecutionClient;
 use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute::re::manager::ReConnectionManager;
 use buck2_execute::re::manager::ReConnectionObserver;
+use buck2_execute_impl::executors::caching::BackgroundCacheUploads;
+use buck2_execute_impl::executors::hybrid::HybridRacingPolicy;
+use buck2_execute_impl::executors::local_action_cache::LocalActionCache;
 use buck2_execute_impl::executors::worker::WorkerPool;
 use buck2_execute_impl::low_pass_filter::LowPassFilter;
 use buck2_file_watcher::file_watcher::FileWatcher;
@@ -128,6 +138,7 @@ use crate::daemon::common::CommandExecutorFactory;
 use crate::dice_tracker::BuckDiceTracker;
 use crate::heartbeat_guard::HeartbeatGuard;
 use crate::host_info;
+use crate::workspace_status::WorkspaceStatus;
 
 #[derive(Debug, thiserror::Error)]
 enum DaemonCommunicationError {
@@ -174,6 +185,12 @@ pub struct BaseServerCommandContext {
     pub create_unhashed_outputs_lock: Arc<Mutex<()>>,
     /// Http client used during run actions; shared with materializer.
     pub http_client: CountingHttpClient,
+    /// On-disk cache of local action results, if enabled.
+    pub local_action_cache: Option<Arc<LocalActionCache>>,
+    /// Persistent workers, shared by all the commands.
+    pub worker_pool: Arc<WorkerPool>,
+    /// Throttle for the cache uploads running in the background, if enabled.
+    pub background_cache_uploads: Option<Arc<BackgroundCacheUploads>>,
 }
 
 /// ServerCommandContext provides access to the global daemon state and information about the calling client for
@@ -309,6 +326,7 @@ impl<'a> ServerCommandContext<'a> {
             working_dir: working_dir_project_relative.to_buf().into(),
             reuse_current_config: client_context.reuse_current_config,
             config_overrides: client_context.config_overrides.clone(),
+            buck_out_dir: buck_out_dir.clone(),
             loaded_cell_configs: AsyncOnceCell::new(),
         });
 
@@ -416,6 +434,9 @@ impl<'a> ServerCommandContext<'a> {
                 .as_ref()
                 .map_or(false, |opts| opts.keep_going),
             http_client: self.base_context.http_client.dupe(),
+            local_action_cache: self.base_context.local_action_cache.dupe(),
+            worker_pool: self.base_context.worker_pool.dupe(),
+            background_cache_uploads: self.base_context.background_cache_uploads.dupe(),
         }
     }
 
@@ -454,6 +475,8 @@ struct CellConfigLoader {
     /// Reuses build config from the previous invocation if there is one
     reuse_current_config: bool,
     config_overrides: Vec<ConfigOverride>,
+    /// Where the workspace status files are written.
+    buck_out_dir: ProjectRelativePathBuf,
     loaded_cell_configs:
         AsyncOnceCell<SharedResult<(CellResolver, LegacyBuckConfigs, HashSet<AbsNormPathBuf>)>>,
 }
@@ -488,14 +511,48 @@ impl CellConfigLoader {
                         );
                     }
                 }
-                parse_legacy_cells(self.config_overrides.iter(), &self.working_dir, &self.project_root)
-                    .shared_error()
+                self.parse_cells_and_configs().await.shared_error()
             })
             .await
             .clone()
     }
 }
 
+impl CellConfigLoader {
+    async fn parse_cells_and_configs(
+        &self,
+    ) -> anyhow::Result<(CellResolver, LegacyBuckConfigs, HashSet<AbsNormPathBuf>)> {
+        let (cell_resolver, legacy_configs, config_paths) = parse_legacy_cells(
+            self.config_overrides.iter(),
+            &self.working_dir,
+            &self.project_root,
+        )?;
+        let command = legacy_configs
+            .get(cell_resolver.root_cell())
+            .ok()
+            .and_then(|config| config.get("buck2", "workspace_status_command"))
+            .map(|command| command.to_owned());
+        let command = match command {
+            Some(command) if !command.is_empty() => command,
+            _ => return Ok((cell_resolver, legacy_configs, config_paths)),
+        };
+
+        // The stable values are exposed as config values, so parse the configs again with them.
+        let status = WorkspaceStatus::compute(&self.project_root, &command).await?;
+        let status_overrides = status.write(
+            &self.project_root,
+            &self
+                .buck_out_dir
+                .join(ForwardRelativePath::unchecked_new("workspace_status")),
+        )?;
+        parse_legacy_cells(
+            self.config_overrides.iter().chain(status_overrides.iter()),
+            &self.working_dir,
+            &self.project_root,
+        )
+    }
+}
+
 struct DiceCommandDataProvider {
     cell_configs_loader: Arc<CellConfigLoader>,
     execution_strategy: ExecutionStrategy,
@@ -515,6 +572,9 @@ struct DiceCommandDataProvider {
     starlark_debugger: Option<BuckStarlarkDebuggerHandle>,
     keep_going: bool,
     http_client: CountingHttpClient,
+    local_action_cache: Option<Arc<LocalActionCache>>,
+    worker_pool: Arc<WorkerPool>,
+    background_cache_uploads: Option<Arc<BackgroundCacheUploads>>,
 }
 
 #[async_trait]
@@ -552,20 +612,59 @@ impl DiceDataProvider for DiceCommandDataProvider {
             .unwrap_or_else(RolloutPercentage::always)
             .roll();
 
+        let sandbox_local_actions = root_config
+            .parse::<bool>("buck2", "sandbox_local_actions")?
+            .unwrap_or_default();
+
+        let live_action_output = root_config
+            .parse::<bool>("buck2", "live_action_output")?
+            .unwrap_or_default();
+
         let executor_global_knobs = ExecutorGlobalKnobs {
             enable_miniperf,
             log_action_keys,
+            sandbox_local_actions,
+            live_action_output,
         };
 
-        let host_sharing_broker =
+        let mut host_sharing_broker =
             HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, concurrency);
+        if let Some(memory_mb) = root_config.parse::<usize>("buck2", "local_memory_budget_mb")? {
+            host_sharing_broker = host_sharing_broker.with_memory_budget(memory_mb);
+        }
+        if let Some(io) = root_config.parse::<usize>("buck2", "local_io_budget")? {
+            host_sharing_broker = host_sharing_broker.with_io_budget(io);
+        }
 
         // We use the job count for the low pass filter too. The low pass filter prevents sending
         // RE-eligile tasks to local if their concurrency is higher than our threshold. While it
         // doesn't *have* to be the same as the concurrency we give the actual executor, it's a
         // reasonable pick, because if we send more tasks than our concurrency limit allows, we
         // would expect to start losing out to RE in terms of perf.
-        let low_pass_filter = LowPassFilter::new(concurrency);
+        let low_pass_filter = LowPassFilter::new(
+            root_config
+                .parse("buck2", "hybrid_low_pass_filter_threshold")?
+                .unwrap_or(concurrency),
+        );
+
+        let hybrid_racing_policy = HybridRacingPolicy {
+            local_delay: root_config
+                .parse::<u64>("buck2", "hybrid_prefer_cache_ms")?
+                .map(Duration::from_millis),
+            keep_running_local: root_config
+                .parse("buck2", "hybrid_keep_running_local")?
+                .unwrap_or(false),
+            prefer_local_categories: root_config
+                .parse_list::<String>("buck2", "hybrid_prefer_local_categories")?
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            prefer_remote_categories: root_config
+                .parse_list::<String>("buck2", "hybrid_prefer_remote_categories")?
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+        };
 
         let mut data = DiceData::new();
         data.set(self.events.dupe());
@@ -589,6 +688,32 @@ impl DiceDataProvider for DiceCommandDataProvider {
             .parse::<bool>("buck2", "enforce_re_timeouts")?
             .unwrap_or(true);
 
+        run_action_knobs.verify_dep_files =
+            root_config.parse::<RolloutPercentage>("buck2", "verify_dep_files")?;
+
+        run_action_knobs.cas_backfill_max_depth = root_config
+            .parse::<usize>("buck2", "cas_backfill_max_depth")?
+            .unwrap_or(3);
+
+        let mut action_timeouts = ActionTimeouts {
+            default: root_config
+                .parse::<u64>("buck2", "default_action_timeout_s")?
+                .map(Duration::from_secs),
+            ..Default::default()
+        };
+        if let Some(section) = root_config.get_section("buck2_action_timeouts") {
+            for (category, _) in section.iter() {
+                if let Some(timeout) =
+                    root_config.parse::<u64>("buck2_action_timeouts", category)?
+                {
+                    action_timeouts
+                        .categories
+                        .insert(category.to_owned(), Duration::from_secs(timeout));
+                }
+            }
+        }
+        run_action_knobs.action_timeouts = Arc::new(action_timeouts);
+
         let mut data = UserComputationData {
             data,
             tracker: Arc::new(BuckDiceTracker::new(self.events.dupe())),
@@ -597,8 +722,6 @@ impl DiceDataProvider for DiceCommandDataProvider {
             ..Default::default()
         };
 
-        let worker_pool = Arc::new(WorkerPool::new());
-
         let critical_path_backend = root_config
             .parse("buck2", "critical_path_backend2")?
             .unwrap_or(CriticalPathBackendName::Default);
@@ -609,6 +732,7 @@ impl DiceDataProvider for DiceCommandDataProvider {
             self.re_connection.dupe(),
             host_sharing_broker,
             low_pass_filter,
+            hybrid_racing_policy,
             self.materializer.dupe(),
             self.blocking_executor.dupe(),
             self.execution_strategy,
@@ -617,17 +741,21 @@ impl DiceDataProvider for DiceCommandDataProvider {
             self.forkserver.dupe(),
             self.skip_cache_read,
             self.skip_cache_write,
+            self.background_cache_uploads.dupe(),
             ctx.global_data()
                 .get_io_provider()
                 .project_root()
                 .to_owned(),
-            worker_pool,
+            self.worker_pool.dupe(),
+            self.local_action_cache.dupe(),
         )));
         data.set_blocking_executor(self.blocking_executor.dupe());
         data.set_http_client(self.http_client.dupe());
         data.set_materializer(self.materializer.dupe());
         data.set_build_signals(self.build_signals.build_signals.dupe());
         data.set_run_action_knobs(run_action_knobs);
+        data.set_action_timings(Arc::new(ActionTimingsCollector::default()));
+        data.set_cas_backfill_reruns(Arc::new(CasBackfillReruns::default()));
         data.set_create_unhashed_symlink_lock(self.create_unhashed_symlink_lock.dupe());
         data.set_starlark_debugger_handle(self.starlark_debugger.clone().map(|v| Box::new(v) as _));
         data.set_keep_going(self.keep_going);
@@ -31,14 +31,19 @@ use buck2_execute::execute::dice_data::CommandExecutorResponse;
 use buck2_execute::execute::dice_data::HasCommandExecutor;
 use buck2_execute::execute::prepared::NoOpCommandExecutor;
 use buck2_execute::execute::prepared::PreparedCommandExecutor;
+use buck2_execute::execute::prepared::PreparedCommandOptionalExecutor;
 use buck2_execute::execute::request::ExecutorPreference;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute_impl::executors::action_cache::ActionCacheChecker;
+use buck2_execute_impl::executors::caching::BackgroundCacheUploads;
 use buck2_execute_impl::executors::caching::CacheUploader;
 use buck2_execute_impl::executors::hybrid::HybridExecutor;
+use buck2_execute_impl::executors::hybrid::HybridRacingPolicy;
 use buck2_execute_impl::executors::local::LocalExecutor;
+use buck2_execute_impl::executors::local_action_cache::LocalActionCache;
+use buck2_execute_impl::executors::local_action_cache::LocalActionCacheChecker;
 use buck2_execute_impl::executors::re::ReExecutor;
 use buck2_execute_impl::executors::worker::WorkerPool;
 use buck2_execute_impl::low_pass_filter::LowPassFilter;
@@ -59,6 +64,18 @@ pub fn parse_concurrency(requested: u32) -> anyhow::Result<usize> {
     Ok(ret)
 }
 
+#[derive(Debug, thiserror::Error)]
+enum ExecutionStrategyError {
+    #[error(
+        "This action can only run {allowed}, which is not allowed by {flag}. Executor config: {config}"
+    )]
+    IncompatibleExecutor {
+        flag: &'static str,
+        allowed: &'static str,
+        config: String,
+    },
+}
+
 /// For each buck invocations, we'll have a single CommandExecutorFactory. This contains shared
 /// state used by all command executor strategies.
 pub struct CommandExecutorFactory {
@@ -69,6 +86,7 @@ pub struct CommandExecutorFactory {
     // one CommandExecutorFactory per DICE context).
     pub host_sharing_broker: Arc<HostSharingBroker>,
     pub low_pass_filter: Arc<LowPassFilter>,
+    pub hybrid_racing_policy: Arc<HybridRacingPolicy>,
     pub materializer: Arc<dyn Materializer>,
     pub blocking_executor: Arc<dyn BlockingExecutor>,
     pub strategy: ExecutionStrategy,
@@ -77,8 +95,10 @@ pub struct CommandExecutorFactory {
     pub forkserver: Option<ForkserverClient>,
     pub skip_cache_read: bool,
     pub skip_cache_write: bool,
+    pub background_cache_uploads: Option<Arc<BackgroundCacheUploads>>,
     project_root: ProjectRoot,
     worker_pool: Arc<WorkerPool>,
+    local_action_cache: Option<Arc<LocalActionCache>>,
 }
 
 impl CommandExecutorFactory {
@@ -86,6 +106,7 @@ impl CommandExecutorFactory {
         re_connection: Arc<ReConnectionHandle>,
         host_sharing_broker: HostSharingBroker,
         low_pass_filter: LowPassFilter,
+        hybrid_racing_policy: HybridRacingPolicy,
         materializer: Arc<dyn Materializer>,
         blocking_executor: Arc<dyn BlockingExecutor>,
         strategy: ExecutionStrategy,
@@ -94,13 +115,16 @@ impl CommandExecutorFactory {
         forkserver: Option<ForkserverClient>,
         skip_cache_read: bool,
         skip_cache_write: bool,
+        background_cache_uploads: Option<Arc<BackgroundCacheUploads>>,
         project_root: ProjectRoot,
         worker_pool: Arc<WorkerPool>,
+        local_action_cache: Option<Arc<LocalActionCache>>,
     ) -> Self {
         Self {
             re_connection,
             host_sharing_broker: Arc::new(host_sharing_broker),
             low_pass_filter: Arc::new(low_pass_filter),
+            hybrid_racing_policy: Arc::new(hybrid_racing_policy),
             materializer,
             blocking_executor,
             strategy,
@@ -109,8 +133,26 @@ impl CommandExecutorFactory {
             forkserver,
             skip_cache_read,
             skip_cache_write,
+            background_cache_uploads,
             project_root,
             worker_pool,
+            local_action_cache,
+        }
+    }
+
+    /// Actions that don't use a remote cache are looked up in the local action cache, if any.
+    fn local_action_cache_checker(
+        &self,
+        artifact_fs: &ArtifactFs,
+    ) -> Arc<dyn PreparedCommandOptionalExecutor> {
+        match &self.local_action_cache {
+            Some(cache) if !self.skip_cache_read => Arc::new(LocalActionCacheChecker {
+                artifact_fs: artifact_fs.clone(),
+                materializer: self.materializer.dupe(),
+                blocking_executor: self.blocking_executor.dupe(),
+                cache: cache.dupe(),
+            }),
+            _ => Arc::new(NoOpCommandExecutor {}),
         }
     }
 }
@@ -121,12 +163,17 @@ impl HasCommandExecutor for CommandExecutorFactory {
         artifact_fs: &ArtifactFs,
         executor_config: &CommandExecutorConfig,
     ) -> anyhow::Result<CommandExecutorResponse> {
-        let local_executor_new = |options: &LocalExecutorOptions| {
+        let local_executor_new = |options: &LocalExecutorOptions, use_local_action_cache: bool| {
             let worker_pool = if options.use_persistent_workers {
                 Some(self.worker_pool.dupe())
             } else {
                 None
             };
+            let local_action_cache = if use_local_action_cache && !self.skip_cache_write {
+                self.local_action_cache.dupe()
+            } else {
+                None
+            };
             LocalExecutor::new(
                 artifact_fs.clone(),
                 self.materializer.dupe(),
@@ -136,6 +183,7 @@ impl HasCommandExecutor for CommandExecutorFactory {
                 self.forkserver.dupe(),
                 self.executor_global_knobs.dupe(),
                 worker_pool,
+                local_action_cache,
             )
         };
 
@@ -146,16 +194,18 @@ impl HasCommandExecutor for CommandExecutorFactory {
             });
 
             if self.strategy.ban_local() {
-                return Err(anyhow::anyhow!(
-                    "The desired execution strategy (`{:?}`) is incompatible with the local executor",
-                    self.strategy,
-                ));
+                return Err(ExecutionStrategyError::IncompatibleExecutor {
+                    flag: self.strategy.flag(),
+                    allowed: "locally (remote execution is disabled in Cargo builds)",
+                    config: format!("{:?}", executor_config),
+                }
+                .into());
             }
 
             return Ok(CommandExecutorResponse {
-                executor: Arc::new(local_executor_new(&LocalExecutorOptions::default())),
+                executor: Arc::new(local_executor_new(&LocalExecutorOptions::default(), true)),
                 platform: Default::default(),
-                cache_checker: Arc::new(NoOpCommandExecutor {}),
+                cache_checker: self.local_action_cache_checker(artifact_fs),
                 cache_uploader: Arc::new(NoOpCacheUploader {}),
             });
         }
@@ -190,9 +240,9 @@ impl HasCommandExecutor for CommandExecutorFactory {
                     None
                 } else {
                     Some(CommandExecutorResponse {
-                        executor: Arc::new(local_executor_new(local)),
+                        executor: Arc::new(local_executor_new(local, true)),
                         platform: Default::default(),
-                        cache_checker: Arc::new(NoOpCommandExecutor {}),
+                        cache_checker: self.local_action_cache_checker(artifact_fs),
                         cache_uploader: Arc::new(NoOpCacheUploader {}),
                     })
                 }
@@ -207,7 +257,7 @@ impl HasCommandExecutor for CommandExecutorFactory {
             } => {
                 let executor: Option<Arc<dyn PreparedCommandExecutor>> = match &executor {
                     RemoteEnabledExecutor::Local(local) if !self.strategy.ban_local() => {
-                        Some(Arc::new(local_executor_new(local)))
+                        Some(Arc::new(local_executor_new(local, !remote_cache_enabled)))
                     }
                     RemoteEnabledExecutor::Remote(remote) if !self.strategy.ban_remote() => {
                         Some(Arc::new(remote_executor_new(
@@ -222,7 +272,7 @@ impl HasCommandExecutor for CommandExecutorFactory {
                         remote,
                         level,
                     } if !self.strategy.ban_hybrid() => Some(Arc::new(HybridExecutor {
-                        local: local_executor_new(local),
+                        local: local_executor_new(local, !remote_cache_enabled),
                         remote: remote_executor_new(
                             remote,
                             re_use_case,
@@ -232,6 +282,7 @@ impl HasCommandExecutor for CommandExecutorFactory {
                         level: *level,
                         executor_preference: self.strategy.hybrid_preference(),
                         low_pass_filter: self.low_pass_filter.dupe(),
+                        racing_policy: self.hybrid_racing_policy.dupe(),
                     })),
                     _ => None,
                 };
@@ -246,11 +297,16 @@ impl HasCommandExecutor for CommandExecutorFactory {
                     .get_copied()?
                     .unwrap_or(self.skip_cache_read);
 
-                let (cache_checker, cache_uploader) = if disable_caching || !remote_cache_enabled {
+                let (cache_checker, cache_uploader) = if disable_caching {
                     (
                         Arc::new(NoOpCommandExecutor {}) as _,
                         Arc::new(NoOpCacheUploader {}) as _,
                     )
+                } else if !remote_cache_enabled {
+                    (
+                        self.local_action_cache_checker(artifact_fs),
+                        Arc::new(NoOpCacheUploader {}) as _,
+                    )
                 } else {
                     let cache_checker = Arc::new(ActionCacheChecker {
                         artifact_fs: artifact_fs.clone(),
@@ -270,6 +326,7 @@ impl HasCommandExecutor for CommandExecutorFactory {
                                 re_use_case: *re_use_case,
                                 knobs: self.executor_global_knobs.dupe(),
                                 max_bytes: *max_bytes,
+                                background_uploads: self.background_cache_uploads.dupe(),
                             }) as _
                         } else {
                             Arc::new(NoOpCacheUploader {}) as _
@@ -296,16 +353,31 @@ impl HasCommandExecutor for CommandExecutorFactory {
             }
         };
 
-        let response = response
-            .with_context(|| format!(
-"The desired execution strategy (`{:?}`) is incompatible with the executor config that was selected: {:?}",
-self.strategy, executor_config))?;
+        let response = response.ok_or_else(|| ExecutionStrategyError::IncompatibleExecutor {
+            flag: self.strategy.flag(),
+            allowed: allowed_executors(&executor_config.executor),
+            config: format!("{:?}", executor_config),
+        })?;
 
         Ok(response)
     }
 }
 
+/// Where actions using this executor can run, for error messages.
+fn allowed_executors(executor: &Executor) -> &'static str {
+    match executor {
+        Executor::Local(..) => "locally",
+        Executor::RemoteEnabled { executor, .. } => match executor {
+            RemoteEnabledExecutor::Local(..) => "locally",
+            RemoteEnabledExecutor::Remote(..) => "remotely",
+            RemoteEnabledExecutor::Hybrid { .. } => "locally or remotely",
+        },
+    }
+}
+
 trait ExecutionStrategyExt {
+    /// Describes the command line flag selecting this strategy.
+    fn flag(&self) -> &'static str;
     fn ban_local(&self) -> bool;
     fn ban_remote(&self) -> bool;
     fn ban_hybrid(&self) -> bool;
@@ -313,6 +385,17 @@ trait ExecutionStrategyExt {
 }
 
 impl ExecutionStrategyExt for ExecutionStrategy {
+    fn flag(&self) -> &'static str {
+        match self {
+            Self::Default => "the default execution strategy",
+            Self::LocalOnly => "`--local-only`",
+            Self::RemoteOnly => "`--remote-only`",
+            Self::HybridPreferLocal => "`--prefer-local`",
+            Self::HybridPreferRemote => "`--prefer-remote`",
+            Self::NoExecution => "`--unstable-no-execution`",
+        }
+    }
+
     fn ban_local(&self) -> bool {
         match self {
             Self::RemoteOnly | Self::NoExecution => true,
@@ -859,6 +859,7 @@ impl DaemonApi for BuckdServer {
                         data.materializer.dupe(),
                         data.scribe_sink.dupe() as _,
                         data.http_client.dupe(),
+                        data.worker_pool.dupe(),
                     )
                     .create_snapshot(),
                 )
@@ -879,6 +880,14 @@ impl DaemonApi for BuckdServer {
             let mut daemon_constraints = self.0.base_daemon_constraints.clone();
             daemon_constraints.extra = extra_constraints;
 
+            let active_commands = crate::active_commands::active_commands()
+                .iter()
+                .map(|(trace_id, handle)| ActiveCommandStatus {
+                    trace_id: trace_id.to_string(),
+                    argv: handle.state().argv.clone(),
+                })
+                .collect();
+
             let uptime = self.0.start_instant.elapsed();
             let base = StatusResponse {
                 process_info: Some(self.0.process_info.clone()),
@@ -898,7 +907,24 @@ impl DaemonApi for BuckdServer {
                     .as_ref()
                     .ok()
                     .map(|state| state.http_client.supports_vpnless()),
-                ..Default::default()
+                rss_bytes: buck2_util::process_stats::process_stats().rss_bytes,
+                dice: daemon_state.data().as_ref().ok().map(|state| {
+                    let metrics = state.dice_manager.unsafe_dice().metrics();
+                    DiceStatus {
+                        key_count: metrics.key_count as u64,
+                        currently_active_key_count: metrics.currently_active_key_count as u64,
+                        active_transaction_count: metrics.active_transaction_count,
+                    }
+                }),
+                active_commands,
+                file_watcher: daemon_state.data().as_ref().ok().map(|state| {
+                    let status = state.file_watcher.status();
+                    FileWatcherStatus {
+                        provider: status.provider.to_owned(),
+                        syncs: status.syncs,
+                        last_sync_error: status.last_sync_error,
+                    }
+                }),
             };
             Ok(base)
         })
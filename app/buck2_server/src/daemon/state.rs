@@ -46,8 +46,13 @@ use buck2_execute::execute::blocking::BuckBlockingExecutor;
 use buck2_execute::materialize::materializer::MaterializationMethod;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::manager::ReConnectionManager;
+use buck2_execute_impl::executors::caching::BackgroundCacheUploads;
+use buck2_execute_impl::executors::local_action_cache::LocalActionCache;
+use buck2_execute_impl::executors::worker::WorkerPool;
+use buck2_execute_impl::materializers::dedup::DedupMode;
 use buck2_execute_impl::materializers::deferred::DeferredMaterializer;
 use buck2_execute_impl::materializers::deferred::DeferredMaterializerConfigs;
+use buck2_execute_impl::materializers::deferred::GcConfiguration;
 use buck2_execute_impl::materializers::deferred::TtlRefreshConfiguration;
 use buck2_execute_impl::materializers::immediate::ImmediateMaterializer;
 use buck2_execute_impl::materializers::sqlite::MaterializerState;
@@ -55,6 +60,7 @@ use buck2_execute_impl::materializers::sqlite::MaterializerStateIdentity;
 use buck2_execute_impl::materializers::sqlite::MaterializerStateSqliteDb;
 use buck2_file_watcher::file_watcher::FileWatcher;
 use buck2_forkserver::client::ForkserverClient;
+use buck2_re_configuration::RemoteExecutionRetryConfiguration;
 use buck2_re_configuration::RemoteExecutionStaticMetadata;
 use buck2_re_configuration::RemoteExecutionStaticMetadataImpl;
 use buck2_server_ctx::concurrency::ConcurrencyHandler;
@@ -66,6 +72,8 @@ use gazebo::variants::VariantName;
 use tokio::sync::Mutex;
 
 use crate::active_commands::ActiveCommandDropGuard;
+use crate::clean_stale::periodic_clean_stale;
+use crate::clean_stale::PeriodicCleanStaleConfig;
 use crate::ctx::BaseServerCommandContext;
 use crate::daemon::check_working_dir;
 use crate::daemon::disk_state::delete_unknown_disk_state;
@@ -98,7 +106,7 @@ pub struct DaemonStateData {
     pub(crate) dice_manager: ConcurrencyHandler,
 
     /// Synced every time we run a command.
-    file_watcher: Arc<dyn FileWatcher>,
+    pub(crate) file_watcher: Arc<dyn FileWatcher>,
 
     /// Settled every time we run a command.
     pub io: Arc<dyn IoProvider>,
@@ -159,6 +167,18 @@ pub struct DaemonStateData {
 
     /// Did we enable eden I/O v2?
     pub eden_io_v2: bool,
+
+    /// On-disk cache of local action results, if enabled.
+    #[allocative(skip)]
+    pub local_action_cache: Option<Arc<LocalActionCache>>,
+
+    /// Persistent workers, shared by all the commands.
+    #[allocative(skip)]
+    pub worker_pool: Arc<WorkerPool>,
+
+    /// Throttle for the cache uploads running in the background, shared by all the commands.
+    #[allocative(skip)]
+    pub background_cache_uploads: Option<Arc<BackgroundCacheUploads>>,
 }
 
 impl DaemonStateData {
@@ -324,6 +344,51 @@ impl DaemonState {
                 .unwrap_or_else(RolloutPercentage::never)
                 .roll();
 
+            let dedup = root_config
+                .parse("buck2", "materialization_dedup")?
+                .unwrap_or(DedupMode::Disabled);
+
+            let gc = match root_config.parse::<u64>("buck2", "buck_out_gc_max_size_gb")? {
+                Some(max_size_gb) => {
+                    let gb_to_bytes = |gb: u64, key: &str| {
+                        gb.checked_mul(1 << 30).with_context(|| {
+                            format!("Invalid `buck2.{}`: {} is too large", key, gb)
+                        })
+                    };
+                    let max_size = gb_to_bytes(max_size_gb, "buck_out_gc_max_size_gb")?;
+                    // By default, collect down to 80% of the maximum so we don't collect again
+                    // as soon as a few more artifacts get materialized.
+                    let target_size =
+                        match root_config.parse::<u64>("buck2", "buck_out_gc_target_size_gb")? {
+                            Some(gb) => gb_to_bytes(gb, "buck_out_gc_target_size_gb")?,
+                            None => max_size / 10 * 8,
+                        }
+                        .min(max_size);
+                    let frequency = root_config
+                        .parse("buck2", "buck_out_gc_frequency_seconds")?
+                        .unwrap_or(300);
+                    if frequency == 0 {
+                        return Err(anyhow::anyhow!(
+                            "Invalid `buck2.buck_out_gc_frequency_seconds`: must be greater than 0"
+                        ));
+                    }
+                    let min_age = root_config
+                        .parse("buck2", "buck_out_gc_min_age_hours")?
+                        .unwrap_or(24);
+                    let max_deletions = root_config
+                        .parse("buck2", "buck_out_gc_max_deletions")?
+                        .unwrap_or(1000);
+                    Some(GcConfiguration {
+                        frequency: std::time::Duration::from_secs(frequency),
+                        max_size,
+                        target_size,
+                        min_age: chrono::Duration::hours(min_age),
+                        max_deletions,
+                    })
+                }
+                None => None,
+            };
+
             DeferredMaterializerConfigs {
                 materialize_final_artifacts: matches!(
                     materialization_method,
@@ -335,6 +400,8 @@ impl DaemonState {
                     min_ttl: chrono::Duration::seconds(ttl_refresh_min_ttl),
                     enabled: ttl_refresh_enabled,
                 },
+                dedup,
+                gc,
             }
         };
 
@@ -383,14 +450,53 @@ impl DaemonState {
 
         let materializer_state_identity = materializer_db.as_ref().map(|d| d.identity().clone());
 
+        let local_action_cache =
+            match root_config.parse::<u64>("buck2", "local_action_cache_max_bytes")? {
+                Some(max_bytes) if max_bytes > 0 => {
+                    let dir = paths.local_action_cache_path();
+                    let compress = root_config
+                        .parse("buck2", "local_action_cache_compression")?
+                        .unwrap_or(false);
+                    let cache = (blocking_executor.dupe() as Arc<dyn BlockingExecutor>)
+                        .execute_io_inline(|| LocalActionCache::open(dir, max_bytes, compress))
+                        .await
+                        .context("Error opening local action cache")?;
+                    Some(Arc::new(cache))
+                }
+                _ => None,
+            };
+
+        let worker_idle_timeout = root_config
+            .parse::<u64>("buck2", "worker_idle_timeout_s")?
+            .unwrap_or(600);
+        let worker_pool = Arc::new(WorkerPool::new(
+            Some(Duration::from_secs(worker_idle_timeout)).filter(|t| !t.is_zero()),
+        ));
+
+        let background_cache_uploads = root_config
+            .parse::<usize>("buck2", "cache_upload_background_concurrency")?
+            .map(|max_concurrency| {
+                anyhow::Ok(Arc::new(BackgroundCacheUploads::new(
+                    max_concurrency,
+                    root_config
+                        .parse::<usize>("buck2", "cache_upload_background_max_queued")?
+                        .unwrap_or(1000),
+                    blocking_executor.dupe(),
+                )))
+            })
+            .transpose()?;
+
         let re_client_manager = Arc::new(ReConnectionManager::new(
             fb,
             false,
             10,
             static_metadata,
+            RemoteExecutionRetryConfiguration::from_legacy_config(root_config)?,
             Some(paths.re_logs_dir()),
             paths.buck_out_path(),
         ));
+        let periodic_clean_stale_config = PeriodicCleanStaleConfig::from_config(root_config)?;
+
         let materializer = Self::create_materializer(
             fb,
             io.project_root().dupe(),
@@ -405,6 +511,12 @@ impl DaemonState {
             http_client.dupe(),
         )?;
 
+        if let Some(config) = periodic_clean_stale_config {
+            if materializer.as_deferred_materializer_extension().is_some() {
+                tokio::spawn(periodic_clean_stale(materializer.dupe(), config));
+            }
+        }
+
         // Create this after the materializer because it'll want to write to buck-out, and an Eden
         // materializer would create buck-out now.
         let forkserver =
@@ -492,6 +604,9 @@ impl DaemonState {
             http_client,
             cwd_buck_out,
             eden_io_v2,
+            local_action_cache,
+            worker_pool,
+            background_cache_uploads,
         }))
     }
 
@@ -684,6 +799,9 @@ impl DaemonState {
             daemon_start_time: data.start_time,
             create_unhashed_outputs_lock: data.create_unhashed_outputs_lock.dupe(),
             http_client: data.http_client.dupe(),
+            local_action_cache: data.local_action_cache.dupe(),
+            worker_pool: data.worker_pool.dupe(),
+            background_cache_uploads: data.background_cache_uploads.dupe(),
         })
     }
 
@@ -36,6 +36,7 @@ impl HeartbeatGuard {
             ctx.materializer.dupe(),
             Some(ctx.events.sink().dupe()),
             ctx.http_client.dupe(),
+            ctx.worker_pool.dupe(),
         );
 
         // NOTE: This doesn't use the ambient dispatcher wrappers because we want to control the
@@ -28,3 +28,4 @@ pub mod profile;
 mod snapshot;
 mod subscription;
 mod trace_io;
+mod workspace_status;
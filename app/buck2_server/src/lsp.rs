@@ -29,6 +29,7 @@ use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::package::PackageLabel;
 use buck2_core::pattern::pattern_type::ProvidersPatternExtra;
 use buck2_core::pattern::ParsedPattern;
 use buck2_core::target::name::TargetName;
@@ -40,6 +41,7 @@ use buck2_interpreter::path::OwnedStarlarkModulePath;
 use buck2_interpreter::path::StarlarkPath;
 use buck2_interpreter_for_build::interpreter::dice_calculation_delegate::HasCalculationDelegate;
 use buck2_interpreter_for_build::interpreter::global_interpreter_state::HasGlobalInterpreterState;
+use buck2_node::nodes::frontend::TargetGraphCalculation;
 use buck2_server_ctx::command_end::command_end;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::ctx::ServerCommandDiceContext;
@@ -60,6 +62,7 @@ use lsp_types::Range;
 use lsp_types::Url;
 use starlark::docs::Doc;
 use starlark::docs::DocItem;
+use starlark::docs::DocParam;
 use starlark::docs::Identifier;
 use starlark::docs::Location;
 use starlark::errors::EvalMessage;
@@ -172,6 +175,9 @@ struct DocsCache {
     global_urls: HashMap<String, LspUrl>,
     /// Mapping of starlark: urls to a synthesized starlark representation.
     native_starlark_files: HashMap<LspUrl, String>,
+    /// Mapping of global function names (including rules) to the names of the parameters
+    /// which can be passed by keyword.
+    global_params: HashMap<String, Vec<String>>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -242,7 +248,19 @@ impl DocsCache {
     ) -> anyhow::Result<Self> {
         let mut global_urls = HashMap::with_capacity(builtin_symbols.len());
         let mut native_starlark_files = HashMap::new();
+        let mut global_params = HashMap::new();
         for doc in builtin_symbols {
+            if let DocItem::Function(function) = &doc.item {
+                let params = function
+                    .params
+                    .iter()
+                    .filter_map(|p| match p {
+                        DocParam::Arg { name, .. } => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                global_params.insert(doc.id.name.clone(), params);
+            }
             let url = match &doc.id.location {
                 Some(l) => location_lookup(l).await?,
                 None => {
@@ -274,6 +292,7 @@ impl DocsCache {
         Ok(Self {
             global_urls,
             native_starlark_files,
+            global_params,
         })
     }
 
@@ -286,6 +305,14 @@ impl DocsCache {
     fn url_for_symbol(&self, symbol: &str) -> Option<&LspUrl> {
         self.global_urls.get(symbol)
     }
+
+    fn symbol_names(&self) -> impl Iterator<Item = &str> {
+        self.global_urls.keys().map(|s| s.as_str())
+    }
+
+    fn params_for_symbol(&self, symbol: &str) -> Option<&[String]> {
+        self.global_params.get(symbol).map(|p| p.as_slice())
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -570,6 +597,88 @@ impl<'a> BuckLspContext<'a> {
         }
     }
 
+    /// Complete a partially typed target label (e.g. `//foo:b` or `:b`) from the targets
+    /// of its package, or a partially typed package (e.g. `//fo`) from the directories on disk.
+    async fn complete_string_literal(
+        &self,
+        current_package: CellPathRef<'_>,
+        prefix: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let cell_resolver = self
+            .with_dice_ctx(|dice_ctx| async move { dice_ctx.get_cell_resolver().await })
+            .await?;
+        let parse_package = |package: &str| -> anyhow::Result<Option<PackageLabel>> {
+            match ParsedPattern::<ProvidersPatternExtra>::parsed_opt_absolute(
+                &format!("{}:", package),
+                Some(current_package),
+                current_package.cell(),
+                &cell_resolver,
+            )? {
+                ParsedPattern::Package(package) => Ok(Some(package)),
+                _ => Ok(None),
+            }
+        };
+
+        if let Some((package_str, name_prefix)) = prefix.rsplit_once(':') {
+            let package = if package_str.is_empty() {
+                PackageLabel::from_cell_path(current_package)
+            } else {
+                match parse_package(package_str)? {
+                    Some(package) => package,
+                    None => return Ok(Vec::new()),
+                }
+            };
+            let names = self
+                .with_dice_ctx(async move |dice_ctx| {
+                    let result = dice_ctx.get_interpreter_results(package).await?;
+                    Ok(result
+                        .targets()
+                        .keys()
+                        .map(|name| name.as_str())
+                        .filter(|name| name.starts_with(name_prefix))
+                        .map(|name| name.to_owned())
+                        .collect::<Vec<_>>())
+                })
+                .await?;
+            return Ok(names
+                .into_iter()
+                .map(|name| format!("{}:{}", package_str, name))
+                .collect());
+        }
+
+        // Only absolute package paths are completed, relative ones are ambiguous with files.
+        if !prefix.contains("//") {
+            return Ok(Vec::new());
+        }
+        let (dir, name_prefix) = prefix.split_at(prefix.rfind('/').unwrap() + 1);
+        let package = match dir.strip_suffix('/') {
+            Some(parent) if !dir.ends_with("//") => parse_package(parent)?,
+            _ => parse_package(dir)?,
+        };
+        let package = match package {
+            Some(package) => package,
+            None => return Ok(Vec::new()),
+        };
+        let path = self.fs.resolve(&cell_resolver.resolve_package(package)?);
+        let mut completions = Vec::new();
+        for entry in std::fs::read_dir(&path)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = match name.to_str() {
+                Some(name) => name,
+                None => continue,
+            };
+            if !name.starts_with('.')
+                && name.starts_with(name_prefix)
+                && entry.file_type()?.is_dir()
+            {
+                completions.push(format!("{}{}", dir, name));
+            }
+        }
+        completions.sort();
+        Ok(completions)
+    }
+
     fn find_target(ast: &AstModule, target: TargetName) -> Option<Range> {
         ast.find_function_call_with_name(target.as_str())
             .map(Range::from)
@@ -737,6 +846,64 @@ impl<'a> LspContext for BuckLspContext<'a> {
                 Ok(docs_cache.url_for_symbol(symbol).cloned())
             }))
     }
+
+    fn get_global_symbol_names(&self, _current_file: &LspUrl) -> anyhow::Result<Vec<String>> {
+        let dispatcher = self.server_ctx.events().dupe();
+        self.runtime
+            .block_on(with_dispatcher_async(dispatcher, async {
+                let docs_cache = self
+                    .with_dice_ctx(|dice_ctx| async {
+                        self.docs_cache_manager.get_cache(dice_ctx).await
+                    })
+                    .await?;
+                Ok(docs_cache.symbol_names().map(|s| s.to_owned()).collect())
+            }))
+    }
+
+    fn get_function_parameter_names(
+        &self,
+        _current_file: &LspUrl,
+        function: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let dispatcher = self.server_ctx.events().dupe();
+        self.runtime
+            .block_on(with_dispatcher_async(dispatcher, async {
+                let docs_cache = self
+                    .with_dice_ctx(|dice_ctx| async {
+                        self.docs_cache_manager.get_cache(dice_ctx).await
+                    })
+                    .await?;
+                // Rules are also reachable as `native.<rule>` in macros.
+                let function = function.strip_prefix("native.").unwrap_or(function);
+                Ok(docs_cache
+                    .params_for_symbol(function)
+                    .map(|p| p.to_vec())
+                    .unwrap_or_default())
+            }))
+    }
+
+    fn get_string_literal_completions(
+        &self,
+        prefix: &str,
+        current_file: &LspUrl,
+    ) -> anyhow::Result<Vec<String>> {
+        let dispatcher = self.server_ctx.events().dupe();
+        self.runtime
+            .block_on(with_dispatcher_async(dispatcher, async {
+                let import_path = match current_file {
+                    LspUrl::File(current_file) => {
+                        self.import_path(current_file.parent().unwrap()).await?
+                    }
+                    _ => return Ok(Vec::new()),
+                };
+                // As with `resolve_string_literal`, most failures here (unknown cells, packages
+                // which fail to evaluate, ...) just mean there is nothing to offer.
+                Ok(self
+                    .complete_string_literal(import_path.path(), prefix)
+                    .await
+                    .unwrap_or_default())
+            }))
+    }
 }
 
 pub(crate) async fn run_lsp_server_command(
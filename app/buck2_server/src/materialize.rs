@@ -8,14 +8,43 @@
  */
 
 use anyhow::Context;
+use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
+use buck2_cli_proto::ClientContext;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_common::dice::file_ops::HasFileOps;
+use buck2_common::pattern::resolve::resolve_target_patterns;
+use buck2_core::fs::fs_util;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_core::pattern::pattern_type::ProvidersPatternExtra;
+use buck2_core::pattern::PackageSpec;
+use buck2_core::provider::label::ProvidersLabel;
+use buck2_core::target::label::TargetLabel;
 use buck2_events::dispatch::span_async;
+use buck2_execute::artifact::artifact_dyn::ArtifactDyn;
+use buck2_node::nodes::frontend::TargetGraphCalculation;
+use buck2_node::target_calculation::ConfiguredTargetCalculation;
 use buck2_server_ctx::command_end::command_end;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
+use buck2_server_ctx::pattern::target_platform_from_client_context;
+use dice::DiceTransaction;
+use dupe::Dupe;
+use gazebo::prelude::*;
 
-use crate::ctx::BaseServerCommandContext;
 use crate::ctx::ServerCommandContext;
 
+#[derive(Debug, thiserror::Error)]
+enum MaterializeCommandError {
+    #[error(
+        "Paths are not known to the materializer, they must be built before they can be materialized:\n{}",
+        .0.iter().map(|p| format!("  {}", p)).collect::<Vec<_>>().join("\n")
+    )]
+    NotDeclared(Vec<ProjectRelativePathBuf>),
+}
+
 pub(crate) async fn materialize_command(
     context: &ServerCommandContext<'_>,
     req: buck2_cli_proto::MaterializeRequest,
@@ -26,7 +55,7 @@ pub(crate) async fn materialize_command(
         data: Some(buck2_data::MaterializeCommandStart {}.into()),
     };
     span_async(start_event, async move {
-        let result = materialize(&context.base_context, req.paths)
+        let result = materialize(context, req)
             .await
             .map(|()| buck2_cli_proto::MaterializeResponse {})
             .context("Failed to materialize paths");
@@ -37,15 +66,100 @@ pub(crate) async fn materialize_command(
 }
 
 async fn materialize(
-    server_ctx: &BaseServerCommandContext,
-    paths: Vec<String>,
+    server_ctx: &ServerCommandContext<'_>,
+    req: buck2_cli_proto::MaterializeRequest,
 ) -> anyhow::Result<()> {
     let mut project_paths = Vec::new();
-    for path in paths {
-        project_paths.push(ProjectRelativePath::new(&path)?.to_owned())
+    for path in &req.paths {
+        project_paths.push(ProjectRelativePath::new(path)?.to_owned())
+    }
+    if !req.targets.is_empty() {
+        let client_ctx = req.context.context("Missing client context")?;
+        let targets = req.targets;
+        let dice_ctx: &dyn ServerCommandContextTrait = server_ctx;
+        project_paths.extend(
+            dice_ctx
+                .with_dice_ctx(move |server_ctx, ctx| {
+                    default_output_paths(server_ctx, ctx, client_ctx, targets)
+                })
+                .await?,
+        );
     }
+
     server_ctx
-        .materializer
-        .ensure_materialized(project_paths)
-        .await
+        .materializer()
+        .ensure_materialized(project_paths.clone())
+        .await?;
+
+    // The materializer ignores paths it doesn't know about, which is usually because they
+    // were never built, so report those rather than silently doing nothing.
+    let project_root = server_ctx.project_root();
+    let mut missing = Vec::new();
+    for path in project_paths {
+        if !fs_util::try_exists(project_root.resolve(&path))? {
+            missing.push(path);
+        }
+    }
+    if !missing.is_empty() {
+        return Err(MaterializeCommandError::NotDeclared(missing).into());
+    }
+    Ok(())
+}
+
+/// The paths of the default outputs of the targets matching `patterns`.
+async fn default_output_paths(
+    server_ctx: &dyn ServerCommandContextTrait,
+    mut ctx: DiceTransaction,
+    client_ctx: ClientContext,
+    patterns: Vec<String>,
+) -> anyhow::Result<Vec<ProjectRelativePathBuf>> {
+    let cells = ctx.get_cell_resolver().await?;
+    let target_platform =
+        target_platform_from_client_context(&client_ctx, server_ctx, &ctx).await?;
+    let parsed_patterns = parse_patterns_from_cli_args::<ProvidersPatternExtra>(
+        &mut ctx,
+        &patterns.map(|pat| buck2_data::TargetPattern { value: pat.clone() }),
+        server_ctx.working_dir(),
+    )
+    .await?;
+    let resolved_pattern =
+        resolve_target_patterns(&cells, &parsed_patterns, &ctx.file_ops()).await?;
+
+    let mut labels = Vec::new();
+    for (package, spec) in resolved_pattern.specs {
+        match spec {
+            PackageSpec::Targets(targets) => {
+                for (target_name, providers) in targets {
+                    labels
+                        .push(providers.into_providers_label(package.dupe(), target_name.as_ref()));
+                }
+            }
+            PackageSpec::All => {
+                let interpreter_results = ctx.get_interpreter_results(package.dupe()).await?;
+                for target_name in interpreter_results.targets().keys() {
+                    labels.push(ProvidersLabel::default_for(TargetLabel::new(
+                        package.dupe(),
+                        target_name,
+                    )));
+                }
+            }
+        }
+    }
+
+    let artifact_fs = ctx.get_artifact_fs().await?;
+    let mut paths = Vec::new();
+    for label in labels {
+        let label = ctx
+            .get_configured_provider_label(&label, target_platform.as_ref())
+            .await?;
+        let providers = ctx.get_providers(&label).await?.require_compatible()?;
+        providers
+            .provider_collection()
+            .default_info()
+            .for_each_default_output_artifact_only(&mut |artifact| {
+                paths.push(artifact.resolve_path(&artifact_fs)?);
+                Ok(())
+            })?;
+    }
+    Ok(paths)
 }
@@ -81,7 +81,7 @@ async fn generate_profile_analysis(
 }
 
 async fn generate_profile_loading(
-    ctx: DiceTransaction,
+    ctx: &DiceTransaction,
     package: PackageLabel,
     spec: PackageSpec<TargetPatternExtra>,
     profile_mode: &StarlarkProfilerConfiguration,
@@ -158,7 +158,7 @@ impl ServerCommandTemplate for ProfileServerCommand {
                     .as_ref()
                     .context("Missing client context")?;
 
-                let profile_data = generate_profile(
+                let (profile_data, package_profiles) = generate_profile(
                     server_ctx,
                     ctx,
                     context,
@@ -168,7 +168,9 @@ impl ServerCommandTemplate for ProfileServerCommand {
                 )
                 .await?;
 
-                get_profile_response(profile_data, &self.req, output)
+                let mut response = get_profile_response(profile_data, &self.req, output)?;
+                response.package_profiles = package_profiles;
+                Ok(response)
             }
             _ => {
                 return Err(anyhow::anyhow!(
@@ -191,7 +193,10 @@ async fn generate_profile(
     pattern: &buck2_data::TargetPattern,
     action: Action,
     profile_mode: &StarlarkProfilerConfiguration,
-) -> anyhow::Result<Arc<StarlarkProfileDataAndStats>> {
+) -> anyhow::Result<(
+    Arc<StarlarkProfileDataAndStats>,
+    Vec<buck2_cli_proto::PackageLoadingProfile>,
+)> {
     let cells = ctx.get_cell_resolver().await?;
 
     let global_target_platform =
@@ -207,15 +212,49 @@ async fn generate_profile(
     let resolved_pattern =
         resolve_target_patterns(&cells, &parsed_patterns, &ctx.file_ops()).await?;
 
-    let (package, spec) =
-        one(resolved_pattern.specs).context("Did not find exactly one pattern")?;
-
     match action {
         Action::Analysis => {
-            generate_profile_analysis(ctx, package, spec, global_target_platform, profile_mode)
-                .await
+            let (package, spec) =
+                one(resolved_pattern.specs).context("Did not find exactly one pattern")?;
+            let profile_data =
+                generate_profile_analysis(ctx, package, spec, global_target_platform, profile_mode)
+                    .await?;
+            Ok((profile_data, Vec::new()))
+        }
+        Action::Loading => {
+            // Packages are evaluated one at a time, so that the time attributed to each package
+            // is not skewed by the others competing for the CPU.
+            let mut profiles = Vec::new();
+            for (package, spec) in resolved_pattern.specs {
+                let profile =
+                    generate_profile_loading(&ctx, package.dupe(), spec, profile_mode).await?;
+                profiles.push((package, profile));
+            }
+            if profiles.is_empty() {
+                return Err(anyhow::anyhow!("No packages matched the pattern"));
+            }
+            let profile_data = match profiles.as_slice() {
+                [(_, profile)] => profile.dupe(),
+                profiles => Arc::new(StarlarkProfileDataAndStats::merge(
+                    profiles.iter().map(|(_, profile)| &**profile),
+                )?),
+            };
+
+            profiles.sort_by(|(_, a), (_, b)| b.elapsed().cmp(&a.elapsed()));
+            let package_profiles = profiles
+                .iter()
+                .map(|(package, profile)| {
+                    Ok(buck2_cli_proto::PackageLoadingProfile {
+                        package: package.to_string(),
+                        elapsed: Some(profile.elapsed().try_into()?),
+                        allocated_bytes: profile.total_allocated_bytes() as u64,
+                        peak_allocated_bytes: profile.peak_allocated_bytes() as u64,
+                        allocation_count: profile.allocation_count() as u64,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?;
+            Ok((profile_data, package_profiles))
         }
-        Action::Loading => generate_profile_loading(ctx, package, spec, profile_mode).await,
     }
 }
 
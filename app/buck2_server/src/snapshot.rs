@@ -18,6 +18,7 @@ use buck2_events::EventSink;
 use buck2_execute::execute::blocking::BlockingExecutor;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::manager::ReConnectionManager;
+use buck2_execute_impl::executors::worker::WorkerPool;
 use buck2_util::process_stats::process_stats;
 use buck2_util::system_stats::UnixSystemStats;
 use dice::Dice;
@@ -38,6 +39,7 @@ pub struct SnapshotCollector {
     net_io_collector: SystemNetworkIoCollector,
     /// This is only used to obtain statistics from the HTTP client.
     http_client: CountingHttpClient,
+    worker_pool: Arc<WorkerPool>,
 }
 
 impl SnapshotCollector {
@@ -49,6 +51,7 @@ impl SnapshotCollector {
         materializer: Arc<dyn Materializer>,
         event_sink: Option<Arc<dyn EventSink>>,
         http_client: CountingHttpClient,
+        worker_pool: Arc<WorkerPool>,
     ) -> SnapshotCollector {
         SnapshotCollector {
             re_client_manager,
@@ -59,6 +62,7 @@ impl SnapshotCollector {
             event_sink,
             net_io_collector: SystemNetworkIoCollector::new(),
             http_client,
+            worker_pool,
         }
     }
 
@@ -80,6 +84,7 @@ impl SnapshotCollector {
         self.add_io_metrics(&mut snapshot);
         self.add_dice_metrics(&mut snapshot);
         self.add_materializer_metrics(&mut snapshot);
+        self.add_worker_pool_metrics(&mut snapshot);
         self.add_sink_metrics(&mut snapshot);
         self.add_net_io_metrics(&mut snapshot);
         snapshot
@@ -108,6 +113,7 @@ impl SnapshotCollector {
                 IoCounterKey::Remove => &mut snapshot.io_in_flight_remove,
                 IoCounterKey::Rename => &mut snapshot.io_in_flight_rename,
                 IoCounterKey::Read => &mut snapshot.io_in_flight_read,
+                IoCounterKey::ReadEden => &mut snapshot.io_in_flight_read_eden,
                 IoCounterKey::Write => &mut snapshot.io_in_flight_write,
                 IoCounterKey::Canonicalize => &mut snapshot.io_in_flight_canonicalize,
                 IoCounterKey::EdenSettle => &mut snapshot.io_in_flight_eden_settle,
@@ -158,6 +164,8 @@ impl SnapshotCollector {
                 stats.get_digest_expirations.finished_successfully;
             snapshot.re_get_digest_expirations_finished_with_error =
                 stats.get_digest_expirations.finished_with_error;
+            snapshot.re_retries = stats.retries;
+            snapshot.re_circuit_breaker_open = stats.circuit_breaker_open;
 
             Ok(())
         }
@@ -184,6 +192,10 @@ impl SnapshotCollector {
         self.materializer.add_snapshot_stats(snapshot);
     }
 
+    fn add_worker_pool_metrics(&self, snapshot: &mut buck2_data::Snapshot) {
+        self.worker_pool.add_snapshot_stats(snapshot);
+    }
+
     fn add_sink_metrics(&self, snapshot: &mut buck2_data::Snapshot) {
         if let Some(metrics) = self.event_sink.as_ref().and_then(|sink| sink.stats()) {
             snapshot.sink_successes = Some(metrics.successes);
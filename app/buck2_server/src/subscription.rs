@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context as _;
@@ -17,6 +18,7 @@ use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::streaming_request_handler::StreamingRequestHandler;
 use futures::future::FutureExt;
 use gazebo::prelude::*;
+use tokio::sync::broadcast;
 use tokio::time::MissedTickBehavior;
 
 use crate::active_commands;
@@ -50,6 +52,7 @@ pub(crate) async fn run_subscription_server_command(
                 .context("Error creating a materializer subscription")?;
 
             let mut wants_active_commands = false;
+            let mut file_changes: Option<broadcast::Receiver<_>> = None;
 
             let mut ticker = tokio::time::interval(Duration::from_millis(100));
             ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
@@ -74,8 +77,20 @@ pub(crate) async fn run_subscription_server_command(
                             Request::SubscribeToActiveCommands(buck2_subscription_proto::SubscribeToActiveCommands {}) => {
                                 wants_active_commands = true;
                             }
+                            Request::SubscribeToFileChanges(buck2_subscription_proto::SubscribeToFileChanges {}) => {
+                                if file_changes.is_none() {
+                                    file_changes = Some(active_commands::subscribe_to_file_changes());
+                                }
+                            }
                         }
                     }
+                    changes = next_file_changes(&mut file_changes).fuse() => {
+                        partial_result_dispatcher.emit(buck2_cli_proto::SubscriptionResponseWrapper {
+                            response: Some(buck2_subscription_proto::SubscriptionResponse {
+                                response: Some(changes.into())
+                            })
+                        });
+                    }
                     path = materializer_subscription.next_materialization().fuse() => {
                         let path = path.context("Materializer hung up")?;
                         partial_result_dispatcher.emit(buck2_cli_proto::SubscriptionResponseWrapper {
@@ -115,6 +130,32 @@ pub(crate) async fn run_subscription_server_command(
     .await
 }
 
+/// Wait for the next file changes, if subscribed to them.
+async fn next_file_changes(
+    receiver: &mut Option<broadcast::Receiver<Arc<buck2_subscription_proto::FileChanges>>>,
+) -> buck2_subscription_proto::FileChanges {
+    let receiver = match receiver {
+        Some(receiver) => receiver,
+        None => return futures::future::pending().await,
+    };
+    match receiver.recv().await {
+        Ok(changes) => (*changes).clone(),
+        Err(broadcast::error::RecvError::Lagged(missed)) => {
+            // We don't know what changed, so tell the client to assume everything did.
+            buck2_subscription_proto::FileChanges {
+                fresh_instance: true,
+                incomplete_reason: Some(format!(
+                    "Subscriber fell behind and missed {} notifications",
+                    missed
+                )),
+                ..Default::default()
+            }
+        }
+        // The sender is static, so it is never closed.
+        Err(broadcast::error::RecvError::Closed) => futures::future::pending().await,
+    }
+}
+
 fn active_commands_snapshot() -> buck2_subscription_proto::ActiveCommandsSnapshot {
     let active_commands = active_commands::active_commands()
         .iter()
@@ -0,0 +1,179 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Workspace status (a.k.a. build stamping).
+//!
+//! The command configured in `buck2.workspace_status_command` is run at the start of each command
+//! and prints `KEY value` lines. Keys starting with `STABLE_` are stable: they are exposed as
+//! `[workspace_status]` buckconfig entries, so only targets reading them are invalidated when they
+//! change, and `workspace_status.stable_keys` lists them so rules can write them to an artifact
+//! (see `prelude//workspace_status.bzl`). Other keys are volatile: they are only written to a file
+//! in buck-out whose path is exposed as `workspace_status.volatile_status_file`, so changing them
+//! never invalidates anything, and only local actions can read them.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use buck2_cli_proto::config_override::ConfigType;
+use buck2_cli_proto::ConfigOverride;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_util::process::async_background_command;
+use itertools::Itertools;
+
+/// Prefix of the keys which are stable.
+const STABLE_PREFIX: &str = "STABLE_";
+
+const WORKSPACE_STATUS_SECTION: &str = "workspace_status";
+
+#[derive(Debug, thiserror::Error)]
+enum WorkspaceStatusError {
+    #[error("Workspace status command `{0}` failed with {1}, stderr:\n{2}")]
+    CommandFailed(String, std::process::ExitStatus, String),
+    #[error(
+        "Workspace status key `{0}` is invalid: keys may only contain ASCII letters, digits and `_`"
+    )]
+    InvalidKey(String),
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct WorkspaceStatus {
+    pub(crate) stable: Vec<(String, String)>,
+    pub(crate) volatile: Vec<(String, String)>,
+}
+
+impl WorkspaceStatus {
+    /// Parse the output of a workspace status command: one `KEY value` entry per line. Keys become
+    /// buckconfig keys, so they are restricted to characters which can't break the config syntax.
+    pub(crate) fn parse(output: &str) -> anyhow::Result<WorkspaceStatus> {
+        let mut status = WorkspaceStatus::default();
+        for line in output.lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(WorkspaceStatusError::InvalidKey(key.to_owned()).into());
+            }
+            let entry = (key.to_owned(), value.trim_start().to_owned());
+            if key.starts_with(STABLE_PREFIX) {
+                status.stable.push(entry);
+            } else {
+                status.volatile.push(entry);
+            }
+        }
+        Ok(status)
+    }
+
+    /// Run the workspace status command from the project root.
+    pub(crate) async fn compute(
+        project_root: &ProjectRoot,
+        command: &str,
+    ) -> anyhow::Result<WorkspaceStatus> {
+        let mut cmd = if cfg!(windows) {
+            let mut cmd = async_background_command("cmd");
+            cmd.arg("/C");
+            cmd
+        } else {
+            let mut cmd = async_background_command("sh");
+            cmd.arg("-c");
+            cmd
+        };
+        let output = cmd
+            .arg(command)
+            .current_dir(project_root.root())
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(WorkspaceStatusError::CommandFailed(
+                command.to_owned(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            )
+            .into());
+        }
+        Self::parse(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Write `volatile-status.txt` to `dir`, and return the config overrides exposing the stable
+    /// values, their keys and the path to the volatile ones.
+    pub(crate) fn write(
+        &self,
+        project_root: &ProjectRoot,
+        dir: &ProjectRelativePath,
+    ) -> anyhow::Result<Vec<ConfigOverride>> {
+        // Concurrent commands write this file too, so replace it atomically: readers then see the
+        // values of one command or another, but never a mix of both.
+        static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let dir = project_root.resolve(dir);
+        fs_util::create_dir_all(&dir)?;
+        let volatile_file = dir.join(ForwardRelativePath::unchecked_new("volatile-status.txt"));
+        let tmp_file = dir.join(ForwardRelativePath::new(&format!(
+            "volatile-status.txt.{}.{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))?);
+        let contents: String = self
+            .volatile
+            .iter()
+            .map(|(k, v)| format!("{} {}\n", k, v))
+            .collect();
+        fs_util::write(&tmp_file, contents)?;
+        fs_util::rename(&tmp_file, &volatile_file)?;
+
+        let value = |key: &str, value: &str| ConfigOverride {
+            config_override: format!("{}.{}={}", WORKSPACE_STATUS_SECTION, key, value),
+            config_type: ConfigType::Value as i32,
+        };
+        let stable_keys = self.stable.iter().map(|(k, _)| k.as_str()).join(" ");
+        let mut overrides = Vec::with_capacity(self.stable.len() + 2);
+        overrides.push(value("stable_keys", &stable_keys));
+        overrides.push(value("volatile_status_file", &volatile_file.to_string()));
+        overrides.extend(self.stable.iter().map(|(k, v)| value(k, v)));
+        Ok(overrides)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> anyhow::Result<()> {
+        let status = WorkspaceStatus::parse(
+            "STABLE_GIT_COMMIT abc123\nBUILD_TIMESTAMP 1234\n\nSTABLE_EMPTY\nBUILD_USER  me \n",
+        )?;
+        assert_eq!(
+            vec![
+                ("STABLE_GIT_COMMIT".to_owned(), "abc123".to_owned()),
+                ("STABLE_EMPTY".to_owned(), "".to_owned()),
+            ],
+            status.stable
+        );
+        assert_eq!(
+            vec![
+                ("BUILD_TIMESTAMP".to_owned(), "1234".to_owned()),
+                ("BUILD_USER".to_owned(), "me".to_owned()),
+            ],
+            status.volatile
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_keys() {
+        for output in ["STABLE_A.B x", "STABLE_A=B x", "-x y"] {
+            assert!(WorkspaceStatus::parse(output).is_err(), "{}", output);
+        }
+    }
+}
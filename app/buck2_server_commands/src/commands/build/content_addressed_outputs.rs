@@ -0,0 +1,232 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Content-addressed layout of the outputs of a build.
+//!
+//! Each output of the built targets is placed in `buck-out/v2/cas` under a name derived from its
+//! digest, made of hardlinks to the files in `buck-out/v2/gen`. A forest of symlinks in
+//! `buck-out/v2/out`, laid out by target without the configuration, points to them. Targets built
+//! in different configurations which produce the same output share the same entry, so their
+//! symlinks don't conflict.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use anyhow::Context;
+use buck2_artifact::artifact::artifact_type::BaseArtifactKind;
+use buck2_build_api::build::BuildProviderType;
+use buck2_build_api::build::ProviderArtifacts;
+use buck2_core::directory::DirectoryEntry;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileName;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::directory::ActionDirectoryMember;
+use buck2_execute::execute::clean_content_addressed_outputs::CONTENT_ADDRESSED_OUTPUTS_DIR;
+use buck2_execute::execute::clean_content_addressed_outputs::CONTENT_ADDRESSED_OUTPUTS_TMP_SUFFIX;
+use buck2_query::__derive_refs::indexmap::IndexMap;
+use tracing::info;
+
+use crate::commands::build::unhashed_outputs::create_unhashed_link;
+
+pub(crate) fn create_content_addressed_outputs(
+    provider_artifacts: &[ProviderArtifacts],
+    artifact_fs: &ArtifactFs,
+    fs: &ProjectRoot,
+) -> anyhow::Result<u64> {
+    let buck_out = artifact_fs.buck_out_path_resolver().root();
+    let buck_out_root = fs.resolve(buck_out);
+    let cas_dir = fs.resolve(&buck_out.join(ForwardRelativePath::unchecked_new(
+        CONTENT_ADDRESSED_OUTPUTS_DIR,
+    )));
+
+    let start = std::time::Instant::now();
+    // The symlinks to create, with the entries each of them could point to, by name.
+    let mut links: IndexMap<AbsNormPathBuf, HashMap<String, AbsNormPathBuf>> = IndexMap::new();
+    for provider_artifact in provider_artifacts {
+        if !matches!(provider_artifact.provider_type, BuildProviderType::Default) {
+            continue;
+        }
+
+        for (artifact, value) in provider_artifact.values.iter() {
+            let (build, projected_path) = match artifact.as_parts() {
+                (BaseArtifactKind::Build(build), projected_path) => (build, projected_path),
+                _ => continue,
+            };
+            let name = match cas_name(value) {
+                Some(name) => name,
+                None => continue,
+            };
+            let unhashed_path = match build.get_path().owner().make_unhashed_path() {
+                Some(path) => path,
+                None => continue,
+            };
+            let projected_path = projected_path.unwrap_or_else(ForwardRelativePath::empty);
+
+            let link = buck_out.join(&ForwardRelativePathBuf::concat([
+                ForwardRelativePath::unchecked_new("out"),
+                &unhashed_path,
+                build.get_path().path(),
+                projected_path,
+            ]));
+            let original = artifact_fs
+                .resolve_build(build.get_path())
+                .join(projected_path);
+            links
+                .entry(fs.resolve(&link))
+                .or_default()
+                .insert(name, fs.resolve(&original));
+        }
+    }
+
+    let mut num_links_made = 0;
+    for (link, entries) in links {
+        if entries.len() != 1 {
+            info!(
+                "The following outputs have a conflicting content-addressed path at {}: {:?}",
+                link,
+                entries.values().collect::<Vec<_>>()
+            );
+            continue;
+        }
+        let (name, original) = entries.into_iter().next().unwrap();
+        if fs_util::symlink_metadata_if_exists(&original)?.is_none() {
+            // Not materialized.
+            tracing::debug!("Not linking `{}`: `{}` does not exist", link, original);
+            continue;
+        }
+        let cas_path = create_cas_entry(&original, &cas_dir, &name)?;
+        create_unhashed_link(&link, &cas_path, &buck_out_root)?;
+        num_links_made += 1;
+    }
+    let duration = start.elapsed();
+    info!(
+        "Creating {} content-addressed output symlinks in {:3}s",
+        num_links_made,
+        duration.as_secs_f64()
+    );
+    Ok(num_links_made)
+}
+
+/// Name of the entry of an output in `buck-out/v2/cas`. Symlinks are not content-addressed.
+fn cas_name(value: &ArtifactValue) -> Option<String> {
+    match value.entry() {
+        DirectoryEntry::Dir(d) => {
+            let digest = d.fingerprint();
+            Some(format!("{}_{}.dir", digest.raw_digest(), digest.size()))
+        }
+        DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) => Some(format!(
+            "{}_{}{}",
+            f.digest.raw_digest(),
+            f.digest.size(),
+            // Files only differing by their executable bit can't share hardlinks.
+            if f.is_executable { ".x" } else { "" }
+        )),
+        DirectoryEntry::Leaf(_) => None,
+    }
+}
+
+/// Create the entry `name` in `cas_dir` from `original`, unless it already exists, in which case
+/// it has the same contents. Entries are deleted by the garbage collection of buck-out once what
+/// they link to is, see `CleanContentAddressedOutputs`.
+fn create_cas_entry(
+    original: &AbsNormPath,
+    cas_dir: &AbsNormPath,
+    name: &str,
+) -> anyhow::Result<AbsNormPathBuf> {
+    let cas_path = cas_dir.join(FileName::new(name)?);
+    if fs_util::symlink_metadata_if_exists(&cas_path)?.is_some() {
+        return Ok(cas_path);
+    }
+
+    // Create the entry aside and move it into place, so a partial entry is never used. Several
+    // builds (in this daemon or others) may create the same entry at once, so each uses its own.
+    static NEXT_TMP_ID: AtomicU64 = AtomicU64::new(0);
+    let tmp_path = cas_dir.join(FileName::new(&format!(
+        "{}.{}.{}{}",
+        name,
+        std::process::id(),
+        NEXT_TMP_ID.fetch_add(1, Ordering::Relaxed),
+        CONTENT_ADDRESSED_OUTPUTS_TMP_SUFFIX,
+    ))?);
+    fs_util::create_dir_all(cas_dir)?;
+    let res = link_tree(original, &tmp_path)
+        .with_context(|| format!("Error linking `{}` into `{}`", original, tmp_path))
+        .and_then(|()| fs_util::rename(&tmp_path, &cas_path));
+    if res.is_err() {
+        fs_util::remove_all(&tmp_path)?;
+        // Another build created it first: it has the same contents.
+        if fs_util::symlink_metadata_if_exists(&cas_path)?.is_some() {
+            return Ok(cas_path);
+        }
+    }
+    res?;
+    Ok(cas_path)
+}
+
+/// Recreate `original` at `dest`, hardlinking its files.
+fn link_tree(original: &AbsNormPath, dest: &AbsNormPath) -> anyhow::Result<()> {
+    let metadata = fs_util::symlink_metadata(original)?;
+    if metadata.is_dir() {
+        fs_util::create_dir_all(dest)?;
+        for entry in fs_util::read_dir(original)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let file_name = file_name
+                .to_str()
+                .with_context(|| format!("Invalid file name in `{}`", original))?;
+            link_tree(&entry.path(), &dest.join(FileName::new(file_name)?))?;
+        }
+    } else if metadata.is_symlink() {
+        let target = fs_util::read_link(original)?;
+        // Relative symlinks may point to other outputs, which are not next to this entry.
+        let target = match original.parent() {
+            Some(parent) if target.is_relative() => AsRef::<Path>::as_ref(parent).join(target),
+            _ => target,
+        };
+        fs_util::symlink(target, dest)?;
+    } else {
+        fs_util::hardlink(original, dest)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_common::file_ops::TrackedFileDigest;
+    use buck2_execute::artifact_value::ArtifactValue;
+    use buck2_execute::digest_config::DigestConfig;
+    use dupe::Dupe;
+
+    use super::*;
+
+    #[test]
+    fn test_cas_name() {
+        let digest_config = DigestConfig::testing_default();
+        let digest = TrackedFileDigest::from_content(b"hello", digest_config.cas_digest_config());
+        let file = |is_executable| {
+            ArtifactValue::file(FileMetadata {
+                digest: digest.dupe(),
+                is_executable,
+            })
+        };
+
+        let name = cas_name(&file(false)).unwrap();
+        assert_eq!(name, format!("{}_5", digest.raw_digest()));
+        assert_eq!(cas_name(&file(true)).unwrap(), format!("{}.x", name));
+    }
+}
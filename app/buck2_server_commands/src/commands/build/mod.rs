@@ -14,6 +14,7 @@ use std::sync::Arc;
 use anyhow::Context as _;
 use async_trait::async_trait;
 use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::actions::execute::action_timings::HasActionTimings;
 use buck2_build_api::build;
 use buck2_build_api::build::BuildEvent;
 use buck2_build_api::build::BuildTargetResult;
@@ -66,6 +67,7 @@ use futures::stream::Stream;
 use futures::stream::StreamExt;
 use itertools::Itertools;
 
+use crate::commands::build::content_addressed_outputs::create_content_addressed_outputs;
 use crate::commands::build::results::build_report::BuildReportCollector;
 use crate::commands::build::results::providers::ProvidersPrinter;
 use crate::commands::build::results::result_report::ResultReporter;
@@ -74,6 +76,7 @@ use crate::commands::build::results::BuildOwner;
 use crate::commands::build::results::BuildResultCollector;
 use crate::commands::build::unhashed_outputs::create_unhashed_outputs;
 
+mod content_addressed_outputs;
 mod results;
 mod unhashed_outputs;
 
@@ -146,6 +149,13 @@ async fn build(
     let should_create_unhashed_links = ctx
         .parse_legacy_config_property(cell_resolver.root_cell(), "buck2", "create_unhashed_links")
         .await?;
+    let should_create_content_addressed_links = ctx
+        .parse_legacy_config_property(
+            cell_resolver.root_cell(),
+            "buck2",
+            "content_addressed_outputs",
+        )
+        .await?;
 
     let parsed_patterns: Vec<ParsedPattern<ConfiguredProvidersPatternExtra>> =
         parse_patterns_from_cli_args(&mut ctx, &request.target_patterns, cwd).await?;
@@ -200,6 +210,7 @@ async fn build(
             )
             .await?
             .unwrap_or(false),
+            ctx.per_transaction_data().get_action_timings(),
         ))
     } else {
         None
@@ -252,6 +263,23 @@ async fn build(
         provider_artifacts.extend(&mut outputs);
     }
 
+    if should_create_content_addressed_links.unwrap_or(false) {
+        span_async(buck2_data::CreateOutputSymlinksStart {}, async {
+            let lock = ctx
+                .per_transaction_data()
+                .get_create_unhashed_symlink_lock();
+            let _guard = lock.lock().await;
+            let res = create_content_addressed_outputs(&provider_artifacts, &artifact_fs, fs);
+
+            let created = match res.as_ref() {
+                Ok(n) => *n,
+                Err(..) => 0,
+            };
+            (res, buck2_data::CreateOutputSymlinksEnd { created })
+        })
+        .await?;
+    }
+
     if should_create_unhashed_links.unwrap_or(false) {
         span_async(buck2_data::CreateOutputSymlinksStart {}, async {
             let lock = ctx
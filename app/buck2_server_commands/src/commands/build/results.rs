@@ -115,16 +115,18 @@ pub mod result_report {
                             continue;
                         }
 
-                        for (artifact, _value) in values.iter() {
-                            let entry =
-                                artifacts
-                                    .entry(artifact)
-                                    .or_insert_with(|| BuildOutputProviders {
+                        for (artifact, value) in values.iter() {
+                            let (entry, _digest) = artifacts.entry(artifact).or_insert_with(|| {
+                                (
+                                    BuildOutputProviders {
                                         default_info: false,
                                         run_info: false,
                                         other: false,
                                         test_info: false,
-                                    });
+                                    },
+                                    value.digest(),
+                                )
+                            });
 
                             match provider_type {
                                 BuildProviderType::Default => {
@@ -147,9 +149,11 @@ pub mod result_report {
 
                     artifacts
                         .into_iter()
-                        .map(|(a, providers)| BuildOutput {
+                        .map(|(a, (providers, digest))| BuildOutput {
                             path: a.resolve_path(artifact_fs).unwrap().to_string(),
                             providers: Some(providers),
+                            digest: digest.map_or_else(String::new, |d| d.raw_digest().to_string()),
+                            size: digest.map_or(0, |d| d.size()),
                         })
                         .collect()
                 } else {
@@ -173,7 +177,10 @@ pub mod result_report {
 
 pub mod build_report {
     use std::collections::HashMap;
+    use std::sync::Arc;
 
+    use buck2_build_api::actions::execute::action_timings::ActionTimings;
+    use buck2_build_api::actions::execute::action_timings::ActionTimingsCollector;
     use buck2_build_api::build::BuildProviderType;
     use buck2_core::configuration::data::ConfigurationData;
     use buck2_core::fs::artifact_path_resolver::ArtifactFs;
@@ -231,6 +238,29 @@ pub mod build_report {
         /// the hidden, implicitly built outputs of the subtarget. There are multiple outputs
         /// per subtarget
         other_outputs: HashMap<String, Vec<ProjectRelativePathBuf>>,
+        /// time spent by the commands of this target which ran during this build in each phase of
+        /// their execution
+        #[serde(skip_serializing_if = "Option::is_none")]
+        action_timings: Option<BuildReportActionTimings>,
+    }
+
+    #[derive(Debug, Serialize)]
+    struct BuildReportActionTimings {
+        queue_us: u64,
+        input_materialization_us: u64,
+        execution_us: u64,
+        output_upload_us: u64,
+    }
+
+    impl From<ActionTimings> for BuildReportActionTimings {
+        fn from(timings: ActionTimings) -> Self {
+            Self {
+                queue_us: timings.queue.as_micros() as u64,
+                input_materialization_us: timings.input_materialization.as_micros() as u64,
+                execution_us: timings.execution.as_micros() as u64,
+                output_upload_us: timings.output_upload.as_micros() as u64,
+            }
+        }
     }
 
     #[derive(Debug, Serialize)]
@@ -259,6 +289,7 @@ pub mod build_report {
         project_root: &'a ProjectRoot,
         include_unconfigured_section: bool,
         include_other_outputs: bool,
+        action_timings: Option<Arc<ActionTimingsCollector>>,
     }
 
     impl<'a> BuildReportCollector<'a> {
@@ -268,6 +299,7 @@ pub mod build_report {
             project_root: &'a ProjectRoot,
             include_unconfigured_section: bool,
             include_other_outputs: bool,
+            action_timings: Option<Arc<ActionTimingsCollector>>,
         ) -> Self {
             Self {
                 trace_id,
@@ -277,6 +309,7 @@ pub mod build_report {
                 project_root,
                 include_unconfigured_section,
                 include_other_outputs,
+                action_timings,
             }
         }
 
@@ -365,6 +398,12 @@ pub mod build_report {
                     BuildOwner::Target(t) => t.cfg().dupe(),
                 })
                 .or_insert_with(BuildReportEntry::default);
+            if let Some(action_timings) = &self.action_timings {
+                configured_report.action_timings = match label {
+                    BuildOwner::Target(t) => action_timings.get(t.target()),
+                }
+                .map(BuildReportActionTimings::from);
+            }
             if !default_outs.is_empty() {
                 if let Some(report) = unconfigured_report {
                     report.outputs.insert(
@@ -15,6 +15,7 @@ use std::net::Ipv4Addr;
 use std::net::SocketAddr;
 use std::net::TcpListener;
 use std::process::Stdio;
+use std::sync::Mutex;
 
 use anyhow::Context;
 use async_trait::async_trait;
@@ -82,7 +83,6 @@ use futures::future::try_join;
 use futures::future::try_join_all;
 use futures::future::FutureExt;
 use futures::stream::StreamExt;
-use futures::stream::TryStreamExt;
 use starlark_map::small_map::SmallMap;
 use tokio::sync::mpsc;
 use tonic::transport::Channel;
@@ -106,6 +106,20 @@ pub enum InstallError {
         installer_log: String,
     },
 
+    #[error(
+        "Installer failed to install `{install_id}` on some devices. Artifact: `{artifact}` located at `{path}`. Failures:\n{failures}\nMore details can be found at `{installer_log}`"
+    )]
+    DeviceInstallFailure {
+        install_id: String,
+        artifact: String,
+        path: AbsNormPathBuf,
+        failures: String,
+        installer_log: String,
+    },
+
+    #[error("{} artifacts failed to install:\n{}", .0.len(), .0.join("\n"))]
+    MultipleFailures(Vec<String>),
+
     #[error("Installer failed for `{install_id}` with `{err}`")]
     InternalInstallerFailure { install_id: String, err: String },
 
@@ -281,6 +295,7 @@ async fn install(
                 &install_files_vector,
                 installer_label,
                 installer_run_args,
+                &request.devices,
                 request.installer_debug,
             )
             .await
@@ -322,6 +337,7 @@ async fn handle_install_request<'a>(
     install_files_slice: &[(&String, SmallMap<&str, Artifact>)],
     installer_label: &ConfiguredProvidersLabel,
     initial_installer_run_args: &[String],
+    devices: &[String],
     installer_debug: bool,
 ) -> anyhow::Result<()> {
     let (files_tx, files_rx) = mpsc::unbounded_channel();
@@ -368,21 +384,43 @@ async fn handle_install_request<'a>(
         let artifact_fs = ctx.get_artifact_fs().await?;
 
         for (install_id, install_files) in install_files_slice {
-            send_install_info(client.clone(), install_id, install_files, &artifact_fs).await?;
+            send_install_info(
+                client.clone(),
+                install_id,
+                install_files,
+                devices,
+                &artifact_fs,
+            )
+            .await?;
         }
 
-        let send_files_result = tokio_stream::wrappers::UnboundedReceiverStream::new(files_rx)
-            .map(anyhow::Ok)
-            .try_for_each_concurrent(None, |file| {
-                send_file(
+        // Keep sending the other files when one fails, so that all the failures (e.g. on
+        // every device) are reported at once.
+        let failures = Mutex::new(Vec::new());
+        tokio_stream::wrappers::UnboundedReceiverStream::new(files_rx)
+            .for_each_concurrent(None, |file| async {
+                if let Err(e) = send_file(
                     file,
                     &artifact_fs,
                     client.clone(),
                     installer_log_filename.to_owned(),
                 )
+                .await
+                {
+                    failures.lock().unwrap().push(e);
+                }
             })
             .await;
         send_shutdown_command(client.clone()).await?;
+        let mut failures = failures.into_inner().unwrap();
+        let send_files_result = match failures.len() {
+            0 => Ok(()),
+            1 => Err(failures.pop().unwrap()),
+            _ => Err(InstallError::MultipleFailures(
+                failures.iter().map(|e| format!("{:#}", e)).collect(),
+            )
+            .into()),
+        };
         send_files_result.context("Failed to send artifacts to installer")?;
         anyhow::Ok(())
     };
@@ -394,6 +432,7 @@ async fn send_install_info(
     mut client: InstallerClient<Channel>,
     install_id: &str,
     install_files: &SmallMap<&str, Artifact>,
+    devices: &[String],
     artifact_fs: &ArtifactFs,
 ) -> anyhow::Result<()> {
     let mut files_map = HashMap::new();
@@ -409,6 +448,7 @@ async fn send_install_info(
     let install_info_request = tonic::Request::new(InstallInfoRequest {
         install_id: install_id.to_owned(),
         files: files_map,
+        devices: devices.to_vec(),
     });
 
     let response_result = client.install(install_info_request).await;
@@ -638,8 +678,8 @@ async fn send_file(
         artifact_name: name.to_owned(),
         file_path: path.to_string(),
     };
-    let end = InstallEventInfoEnd {};
     span_async(start, async {
+        let mut end = InstallEventInfoEnd::default();
         let mut outcome: anyhow::Result<()> = Ok(());
         let response_result = client.file_ready(request).await;
         let response = match response_result {
@@ -683,6 +723,30 @@ async fn send_file(
             }
             .into());
         }
+
+        let mut device_failures = Vec::new();
+        for device_result in response.device_results {
+            match device_result.error_detail {
+                None => end.installed_devices.push(device_result.device),
+                Some(error_detail) => {
+                    device_failures.push(format!(
+                        "  {}: {}",
+                        device_result.device, error_detail.message
+                    ));
+                    end.failed_devices.push(device_result.device);
+                }
+            }
+        }
+        if outcome.is_ok() && !device_failures.is_empty() {
+            outcome = Err(InstallError::DeviceInstallFailure {
+                install_id: install_id.to_owned(),
+                artifact: name.to_owned(),
+                path: path.to_owned(),
+                failures: device_failures.join("\n"),
+                installer_log: install_log.to_owned(),
+            }
+            .into());
+        }
         (outcome, end)
     })
     .await?;
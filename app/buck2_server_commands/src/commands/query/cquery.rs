@@ -95,7 +95,8 @@ async fn cquery(
         &cell_resolver,
         &request.output_attributes,
         request.unstable_output_format,
-    )?;
+    )?
+    .with_dot_options(request.dot_options.as_ref());
 
     let CqueryRequest {
         query,
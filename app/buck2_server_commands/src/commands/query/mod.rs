@@ -20,4 +20,8 @@ enum QueryCommandError {
         "query result was a set of files and one or more --output-attribute was requested, but files have not attributes"
     )]
     FileSetHasNoAttributes,
+    #[error(
+        "query result was a set of files and path output was requested, but files have no edges"
+    )]
+    FileSetHasNoEdges,
 }
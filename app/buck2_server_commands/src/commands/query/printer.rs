@@ -9,6 +9,7 @@
 
 #![allow(clippy::drop_non_drop)] // FIXME?
 
+use std::collections::HashSet;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::io::Write;
@@ -17,6 +18,7 @@ use async_trait::async_trait;
 use buck2_build_api::actions::query::ActionQueryNode;
 use buck2_build_api::actions::query::PRINT_ACTION_NODE;
 use buck2_build_api::interpreter::rule_defs::provider::collection::FrozenProviderCollectionValue;
+use buck2_cli_proto::QueryDotOptions;
 use buck2_cli_proto::QueryOutputFormat;
 use buck2_core::cells::CellResolver;
 use buck2_core::configuration::compatibility::MaybeCompatible;
@@ -32,7 +34,6 @@ use dupe::Copy_;
 use dupe::Dupe_;
 use gazebo::variants::UnpackVariants;
 use indent_write::fmt::IndentWriter;
-use regex::RegexSet;
 use serde::ser::SerializeMap;
 use serde::ser::SerializeSeq;
 use serde::Serialize;
@@ -42,6 +43,7 @@ use crate::commands::query::QueryCommandError;
 use crate::dot::targets::DotTargetGraph;
 use crate::dot::Dot;
 use crate::dot::DotCompact;
+use crate::output_attributes::OutputAttributes;
 
 #[derive(Copy_, Dupe_, Clone_, UnpackVariants)]
 pub enum ShouldPrintProviders<'a, T> {
@@ -58,8 +60,9 @@ pub trait ProviderLookUp<T: QueryTarget>: Send + Sync {
 #[derive(Debug)]
 pub struct QueryResultPrinter<'a> {
     resolver: &'a CellResolver,
-    attributes: Option<RegexSet>,
+    attributes: Option<OutputAttributes>,
     output_format: QueryOutputFormat,
+    dot_options: QueryDotOptions,
 }
 
 struct TargetSetJsonPrinter<'a, T: QueryTarget> {
@@ -71,7 +74,7 @@ impl<'a, T: QueryTarget> TargetSetJsonPrinter<'a, T> {
     async fn new(
         target_call_stacks: bool,
         print_providers: ShouldPrintProviders<'a, T>,
-        attributes: &'a Option<RegexSet>,
+        attributes: &'a Option<OutputAttributes>,
         targets: &'a TargetSet<T>,
     ) -> anyhow::Result<TargetSetJsonPrinter<'a, T>> {
         Ok(TargetSetJsonPrinter {
@@ -86,7 +89,7 @@ impl<'a, T: QueryTarget> TargetSetJsonPrinter<'a, T> {
 
 struct PrintableQueryTarget<'a, T: QueryTarget> {
     value: &'a T,
-    attributes: &'a Option<RegexSet>,
+    attributes: &'a Option<OutputAttributes>,
     providers: Option<FrozenProviderCollectionValue>,
     target_call_stacks: bool,
 }
@@ -136,30 +139,40 @@ impl<'a, T: QueryTarget> Serialize for PrintableQueryTarget<'a, T> {
     {
         let mut map = serializer.serialize_map(None)?;
 
-        QueryTargets::for_all_attrs(self.value, |attr_name, attr_value| {
-            if let Some(attr_regex) = self.attributes {
-                if attr_regex.is_match(attr_name) {
-                    struct AttrValueSerialize<'a, 'b, T: QueryTarget> {
-                        target: &'a T,
-                        attr: &'a T::Attr<'b>,
-                    }
+        struct AttrValueSerialize<'a, 'b, T: QueryTarget> {
+            target: &'a T,
+            attr: &'a T::Attr<'b>,
+        }
 
-                    impl<'a, 'b, T: QueryTarget> Serialize for AttrValueSerialize<'a, 'b, T> {
-                        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-                        where
-                            S: Serializer,
-                        {
-                            self.target.attr_serialize(self.attr, serializer)
-                        }
-                    }
+        impl<'a, 'b, T: QueryTarget> Serialize for AttrValueSerialize<'a, 'b, T> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                self.target.attr_serialize(self.attr, serializer)
+            }
+        }
 
-                    map.serialize_entry(
-                        attr_name,
-                        &AttrValueSerialize {
-                            target: self.value,
-                            attr: attr_value,
-                        },
-                    )?;
+        QueryTargets::for_all_attrs::<S::Error, _, _>(self.value, |attr_name, attr_value| {
+            if let Some(attributes) = self.attributes {
+                let value = AttrValueSerialize {
+                    target: self.value,
+                    attr: attr_value,
+                };
+                if attributes.regex.is_match(attr_name) {
+                    map.serialize_entry(attr_name, &value)?;
+                }
+                let mut paths = attributes
+                    .paths
+                    .iter()
+                    .filter(|path| path.attr() == attr_name)
+                    .peekable();
+                if paths.peek().is_some() {
+                    let value = serde_json::to_value(&value)
+                        .map_err(<S::Error as serde::ser::Error>::custom)?;
+                    for path in paths {
+                        map.serialize_entry(path.expr(), &path.project(&value))?;
+                    }
                 }
             }
             Ok(())
@@ -240,19 +253,36 @@ impl<'a> QueryResultPrinter<'a> {
             (v, _) => v,
         };
 
-        let attributes = if attributes.is_empty() {
-            None
-        } else {
-            Some(RegexSet::new(attributes)?)
-        };
+        let attributes = OutputAttributes::new(attributes)?;
 
         Ok(Self {
             resolver,
             attributes,
             output_format,
+            dot_options: QueryDotOptions::default(),
         })
     }
 
+    /// Node labels and depth limit to use for dot output.
+    pub fn with_dot_options(mut self, dot_options: Option<&QueryDotOptions>) -> Self {
+        if let Some(dot_options) = dot_options {
+            self.dot_options = dot_options.clone();
+        }
+        self
+    }
+
+    fn dot_graph<T: QueryTarget>(&self, targets: TargetSet<T>) -> DotTargetGraph<T> {
+        let targets = match self.dot_options.max_depth {
+            Some(max_depth) => DotTargetGraph::limit_depth(targets, max_depth),
+            None => targets,
+        };
+        DotTargetGraph {
+            targets,
+            attributes: self.attributes.as_ref().map(|a| a.regex.clone()),
+            node_labels: self.dot_options.node_labels().collect(),
+        }
+    }
+
     pub async fn print_multi_output<'b, T: QueryTarget, W: std::io::Write>(
         &self,
         mut output: W,
@@ -351,23 +381,12 @@ impl<'a> QueryResultPrinter<'a> {
                     writeln!(&mut output)?
                 }
                 QueryOutputFormat::Dot => {
-                    Dot::render(
-                        &DotTargetGraph {
-                            targets,
-                            attributes: self.attributes.clone(),
-                        },
-                        &mut output,
-                    )?;
+                    Dot::render(&self.dot_graph(targets), &mut output)?;
                 }
                 QueryOutputFormat::DotCompact => {
-                    DotCompact::render(
-                        &DotTargetGraph {
-                            targets,
-                            attributes: self.attributes.clone(),
-                        },
-                        &mut output,
-                    )?;
+                    DotCompact::render(&self.dot_graph(targets), &mut output)?;
                 }
+                QueryOutputFormat::Path => print_path(&mut output, &targets)?,
             },
             QueryEvaluationValue::FileSet(files) => {
                 if self.attributes.is_some() {
@@ -400,6 +419,9 @@ impl<'a> QueryResultPrinter<'a> {
                     QueryOutputFormat::DotCompact => {
                         unimplemented!("dot_compact output for files not implemented yet")
                     }
+                    QueryOutputFormat::Path => {
+                        return Err(QueryCommandError::FileSetHasNoEdges.into());
+                    }
                 }
             }
         }
@@ -408,10 +430,54 @@ impl<'a> QueryResultPrinter<'a> {
     }
 }
 
+/// Prints each target followed by its edges to the other targets of the set, along with the
+/// attributes which introduced them. Printing starts from the targets no other target in the set
+/// depends on, so that the result of `somepath(from, to)` reads from `from` to `to`.
+fn print_path<T: QueryTarget>(
+    mut output: impl Write,
+    targets: &TargetSet<T>,
+) -> anyhow::Result<()> {
+    let has_dependents: HashSet<&T::NodeRef> = targets
+        .iter()
+        .flat_map(|t| t.deps())
+        .filter(|dep| targets.contains(dep))
+        .collect();
+    let roots = targets
+        .iter()
+        .filter(|t| !has_dependents.contains(t.node_ref()));
+
+    let mut visited = HashSet::new();
+    // Nodes which are only reachable through a cycle have no root, so visit all of them after
+    // the roots.
+    for start in roots.chain(targets.iter()) {
+        let mut stack = vec![start];
+        while let Some(target) = stack.pop() {
+            if !visited.insert(target.node_ref()) {
+                continue;
+            }
+            writeln!(output, "{}", target.node_ref())?;
+            let mut children = Vec::new();
+            for dep in target.deps() {
+                if let Some(child) = targets.get(dep) {
+                    let attrs = target.attrs_introducing_dep(dep);
+                    if attrs.is_empty() {
+                        writeln!(output, "  -> {}", dep)?;
+                    } else {
+                        writeln!(output, "  -> {} (via {})", dep, attrs.join(", "))?;
+                    }
+                    children.push(child);
+                }
+            }
+            stack.extend(children.into_iter().rev());
+        }
+    }
+    Ok(())
+}
+
 async fn printable_targets<'a, T: QueryTarget>(
     targets: &'a TargetSet<T>,
     print_providers: ShouldPrintProviders<'a, T>,
-    attributes: &'a Option<RegexSet>,
+    attributes: &'a Option<OutputAttributes>,
     target_call_stacks: bool,
 ) -> anyhow::Result<Vec<PrintableQueryTarget<'a, T>>> {
     futures::future::join_all(targets.iter().map(|t| {
@@ -76,7 +76,8 @@ async fn uquery(
         &cell_resolver,
         &request.output_attributes,
         request.unstable_output_format,
-    )?;
+    )?
+    .with_dot_options(request.dot_options.as_ref());
 
     let UqueryRequest {
         query,
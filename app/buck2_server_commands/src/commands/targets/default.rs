@@ -15,6 +15,7 @@ use std::path::Path;
 
 use buck2_cli_proto::targets_request;
 use buck2_cli_proto::targets_request::TargetHashFileMode;
+use buck2_cli_proto::targets_request::TargetHashFunction;
 use buck2_cli_proto::targets_request::TargetHashGraphType;
 use buck2_cli_proto::TargetsResponse;
 use buck2_core::cells::CellResolver;
@@ -43,7 +44,7 @@ use crate::target_hash::TargetHashesFileMode;
 
 pub(crate) struct TargetHashOptions {
     file_mode: TargetHashesFileMode,
-    fast_hash: bool,
+    hash_function: TargetHashFunction,
     graph_type: TargetHashGraphType,
     recursive: bool,
 }
@@ -74,7 +75,8 @@ impl TargetHashOptions {
 
         Ok(Self {
             file_mode,
-            fast_hash: request.target_hash_use_fast_hash,
+            hash_function: TargetHashFunction::from_i32(request.target_hash_function)
+                .expect("buck cli should send valid target hash function"),
             graph_type: TargetHashGraphType::from_i32(request.target_hash_graph_type)
                 .expect("buck cli should send valid target hash graph type"),
             recursive: request.target_hash_recursive,
@@ -101,7 +103,7 @@ pub(crate) async fn targets_batch(
                 results.iter_loaded_targets_by_package().collect(),
                 target_platform,
                 hash_options.file_mode,
-                hash_options.fast_hash,
+                hash_options.hash_function,
                 hash_options.recursive,
             )
             .await?,
@@ -113,7 +115,7 @@ pub(crate) async fn targets_batch(
                 results.iter_loaded_targets_by_package().collect(),
                 target_platform,
                 hash_options.file_mode,
-                hash_options.fast_hash,
+                hash_options.hash_function,
                 hash_options.recursive,
             )
             .await?,
@@ -31,9 +31,9 @@ use buck2_node::nodes::attributes::TYPE;
 use buck2_node::nodes::unconfigured::TargetNode;
 use buck2_util::indent::indent;
 use gazebo::prelude::SliceExt;
-use regex::RegexSet;
 
 use crate::json::QuotedJson;
+use crate::output_attributes::OutputAttributes;
 use crate::target_hash::BuckTargetHash;
 
 #[derive(Debug, thiserror::Error)]
@@ -149,7 +149,7 @@ impl JsonWriter {
 }
 
 struct JsonFormat {
-    attributes: Option<RegexSet>,
+    attributes: Option<OutputAttributes>,
     attr_inspect_opts: AttrInspectOptions,
     target_call_stacks: bool,
     writer: JsonWriter,
@@ -180,13 +180,36 @@ impl TargetFormatter for JsonFormat {
             v: impl FnOnce() -> QuotedJson,
         ) {
             if let Some(filter) = &this.attributes {
-                if !filter.is_match(k) {
+                if !filter.regex.is_match(k) {
                     return;
                 }
             }
             this.writer.entry_item(buffer, first, k, v());
         }
 
+        fn print_attr_paths(
+            this: &JsonFormat,
+            buffer: &mut String,
+            first: &mut bool,
+            k: &str,
+            v: impl FnOnce() -> serde_json::Value,
+        ) {
+            if let Some(attributes) = &this.attributes {
+                let mut paths = attributes.paths.iter().filter(|p| p.attr() == k).peekable();
+                if paths.peek().is_some() {
+                    let v = v();
+                    for path in paths {
+                        this.writer.entry_item(
+                            buffer,
+                            first,
+                            path.expr(),
+                            QuotedJson::from_serde_json_value(path.project(&v)),
+                        );
+                    }
+                }
+            }
+        }
+
         print_attr(self, buffer, &mut first, TYPE, || {
             QuotedJson::quote_str(&target_info.node.rule_type().to_string())
         });
@@ -218,6 +241,9 @@ impl TargetFormatter for JsonFormat {
                     value_to_json(a.value, target_info.node.label().pkg()).unwrap(),
                 )
             });
+            print_attr_paths(self, buffer, &mut first, a.name, || {
+                value_to_json(a.value, target_info.node.label().pkg()).unwrap()
+            });
         }
 
         if self.target_call_stacks {
@@ -375,11 +401,7 @@ pub(crate) fn create_formatter(
                 .expect("buck cli should send valid target hash graph type"),
         })),
         OutputFormat::Json | OutputFormat::JsonLines => Ok(Arc::new(JsonFormat {
-            attributes: if other.output_attributes.is_empty() {
-                None
-            } else {
-                Some(RegexSet::new(&other.output_attributes)?)
-            },
+            attributes: OutputAttributes::new(&other.output_attributes)?,
             attr_inspect_opts: if other.include_default_attributes {
                 AttrInspectOptions::All
             } else {
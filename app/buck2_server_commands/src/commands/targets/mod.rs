@@ -19,6 +19,7 @@ use std::io::Write;
 use anyhow::Context as _;
 use async_trait::async_trait;
 use buck2_cli_proto::targets_request;
+use buck2_cli_proto::targets_request::TargetHashFunction;
 use buck2_cli_proto::targets_request::TargetHashGraphType;
 use buck2_cli_proto::HasClientContext;
 use buck2_cli_proto::TargetsRequest;
@@ -187,7 +188,10 @@ async fn targets(
                     .expect("buck cli should send valid target hash graph type")
                 {
                     TargetHashGraphType::None => None,
-                    _ => Some(other.target_hash_use_fast_hash),
+                    _ => Some(
+                        TargetHashFunction::from_i32(other.target_hash_function)
+                            .expect("buck cli should send valid target hash function"),
+                    ),
                 };
 
                 let res = targets_streaming(
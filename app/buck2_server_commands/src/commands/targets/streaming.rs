@@ -15,6 +15,7 @@ use std::mem;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use buck2_cli_proto::targets_request::TargetHashFunction;
 use buck2_cli_proto::TargetsResponse;
 use buck2_common::pattern::package_roots::find_package_roots_stream;
 use buck2_common::pattern::resolve::ResolvedPattern;
@@ -64,7 +65,7 @@ pub(crate) async fn targets_streaming(
     keep_going: bool,
     cached: bool,
     imports: bool,
-    fast_hash: Option<bool>, // None = no hashing
+    hash_function: Option<TargetHashFunction>, // None = no hashing
     threads: Option<usize>,
 ) -> anyhow::Result<TargetsResponse> {
     struct Res {
@@ -139,8 +140,10 @@ pub(crate) async fn targets_streaming(
                                         formatter.target(
                                             TargetInfo {
                                                 node,
-                                                target_hash: fast_hash.map(|fast| {
-                                                    TargetHashes::compute_immediate_one(node, fast)
+                                                target_hash: hash_function.map(|function| {
+                                                    TargetHashes::compute_immediate_one(
+                                                        node, function,
+                                                    )
                                                 }),
                                             },
                                             &mut res.stdout,
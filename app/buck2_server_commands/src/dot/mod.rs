@@ -153,13 +153,18 @@ impl DotCompact {
         graph.for_each_node(|node| {
             let attrs = node.attrs()?;
             let node_name = &escape_id(&node.id());
-            writeln!(
-                w,
-                "  {} [{},label={}];",
-                name_to_number(node_name),
-                attrs,
-                escape_id(&node.id())
-            )?;
+            if attrs.label.is_some() {
+                writeln!(w, "  {} [{}];", name_to_number(node_name), attrs)?;
+            } else {
+                // Nodes are identified by numbers, so label them with their id to be readable.
+                writeln!(
+                    w,
+                    "  {} [{},label={}];",
+                    name_to_number(node_name),
+                    attrs,
+                    escape_id(&node.id())
+                )?;
+            }
             graph.for_each_edge(node, |edge| {
                 writeln!(
                     w,
@@ -7,9 +7,14 @@
  * of this source tree.
  */
 
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use buck2_cli_proto::QueryDotNodeLabel;
 use buck2_query::query::environment::QueryTarget;
 use buck2_query::query::environment::QueryTargets;
 use buck2_query::query::syntax::simple::eval::set::TargetSet;
+use dupe::Dupe;
 use regex::RegexSet;
 use starlark_map::small_map::SmallMap;
 
@@ -24,6 +29,48 @@ pub struct DotTargetGraphNode<'a, T: QueryTarget>(&'a T, &'a DotTargetGraph<T>);
 pub struct DotTargetGraph<T: QueryTarget> {
     pub targets: TargetSet<T>,
     pub attributes: Option<RegexSet>,
+    /// Fields shown in the label of each node. When empty, the node id is used.
+    pub node_labels: Vec<QueryDotNodeLabel>,
+}
+
+impl<T: QueryTarget> DotTargetGraph<T> {
+    /// Only keeps the targets at most `max_depth` edges away from the roots of the set (the
+    /// targets no other target of the set depends on).
+    pub fn limit_depth(targets: TargetSet<T>, max_depth: u32) -> TargetSet<T> {
+        let has_dependents: HashSet<&T::NodeRef> = targets
+            .iter()
+            .flat_map(|t| t.deps())
+            .filter(|dep| targets.contains(dep))
+            .collect();
+        let roots = targets
+            .iter()
+            .filter(|t| !has_dependents.contains(t.node_ref()));
+
+        let mut visited = HashSet::new();
+        let mut limited = TargetSet::new();
+        // Nodes which are only reachable through a cycle have no root, so treat the first of them
+        // we see as roots.
+        for start in roots.chain(targets.iter()) {
+            if !visited.insert(start.node_ref()) {
+                continue;
+            }
+            let mut queue = VecDeque::from([(start, 0)]);
+            while let Some((target, depth)) = queue.pop_front() {
+                limited.insert(target.dupe());
+                if depth == max_depth {
+                    continue;
+                }
+                for dep in target.deps() {
+                    if let Some(child) = targets.get(dep) {
+                        if visited.insert(dep) {
+                            queue.push_back((child, depth + 1));
+                        }
+                    }
+                }
+            }
+        }
+        limited
+    }
 }
 
 impl<'a, T: QueryTarget> DotDigraph<'a> for DotTargetGraph<T> {
@@ -82,11 +129,28 @@ impl<'a, T: QueryTarget> DotNode for DotTargetGraphNode<'a, T> {
             }
             None => SmallMap::new(),
         };
+        // Fields which don't apply to this node (e.g. the configuration hash in uquery) are
+        // skipped, and nodes with no applicable fields are labelled with their id.
+        let fields: Vec<String> = self
+            .1
+            .node_labels
+            .iter()
+            .filter_map(|field| match field {
+                QueryDotNodeLabel::TargetLabel => Some(self.0.node_ref().to_string()),
+                QueryDotNodeLabel::ConfigurationHash => self.0.configuration_hash(),
+                QueryDotNodeLabel::RuleType => Some(self.0.rule_type().into_owned()),
+            })
+            .collect();
+        let label = if fields.is_empty() {
+            None
+        } else {
+            Some(fields.join("\\n"))
+        };
         Ok(DotNodeAttrs {
             style: Some("filled".to_owned()),
             color: Some("#DFECDF".to_owned()),
+            label,
             extra,
-            ..DotNodeAttrs::default()
         })
     }
 
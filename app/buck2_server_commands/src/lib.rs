@@ -16,6 +16,7 @@
 pub mod commands;
 pub mod dot;
 pub(crate) mod json;
+pub(crate) mod output_attributes;
 pub mod target_hash;
 
 pub fn init_late_bindings() {
@@ -0,0 +1,217 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Parsing of `--output-attribute` values.
+//!
+//! A value is either a regular expression matching the names of the attributes to print in full,
+//! or a path expression projecting a part of an attribute, like `deps[*]`, `labels[0]` or
+//! `platform_srcs.*`. Projections are printed under the path expression as the key.
+
+use regex::RegexSet;
+
+/// Special attributes are named `buck.xxx`, so `buck.type` is a regular expression,
+/// not a path into an attribute named `buck`.
+const SPECIAL_ATTRIBUTE_PREFIX: &str = "buck";
+
+#[derive(Debug, Clone)]
+pub(crate) struct OutputAttributes {
+    /// Attributes to print in full.
+    pub(crate) regex: RegexSet,
+    /// Parts of attributes to print.
+    pub(crate) paths: Vec<AttrPath>,
+}
+
+impl OutputAttributes {
+    /// `None` if no attributes were requested.
+    pub(crate) fn new(attributes: &[String]) -> anyhow::Result<Option<OutputAttributes>> {
+        if attributes.is_empty() {
+            return Ok(None);
+        }
+        let mut regexes = Vec::new();
+        let mut paths = Vec::new();
+        for attribute in attributes {
+            match AttrPath::parse(attribute) {
+                Some(path) => paths.push(path),
+                None => regexes.push(attribute),
+            }
+        }
+        Ok(Some(OutputAttributes {
+            regex: RegexSet::new(regexes)?,
+            paths,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    /// `.key`: value of a dict.
+    Key(String),
+    /// `[n]`: element of a list.
+    Index(usize),
+    /// `[*]` or `.*`: all elements of a list or all values of a dict.
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AttrPath {
+    expr: String,
+    attr: String,
+    selectors: Vec<Selector>,
+}
+
+impl AttrPath {
+    /// Parse a path expression, `None` if the string is not a path expression.
+    fn parse(expr: &str) -> Option<AttrPath> {
+        fn is_ident_char(c: char) -> bool {
+            c.is_ascii_alphanumeric() || c == '_'
+        }
+
+        let attr_len = expr.find(|c| !is_ident_char(c)).unwrap_or(expr.len());
+        let (attr, mut rest) = expr.split_at(attr_len);
+        if attr.is_empty() || attr == SPECIAL_ATTRIBUTE_PREFIX {
+            return None;
+        }
+
+        let mut selectors = Vec::new();
+        while !rest.is_empty() {
+            if let Some(r) = rest.strip_prefix(".*") {
+                selectors.push(Selector::All);
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('.') {
+                let key_len = r.find(|c| !is_ident_char(c)).unwrap_or(r.len());
+                if key_len == 0 {
+                    return None;
+                }
+                selectors.push(Selector::Key(r[..key_len].to_owned()));
+                rest = &r[key_len..];
+            } else if let Some(r) = rest.strip_prefix('[') {
+                let (index, r) = r.split_once(']')?;
+                selectors.push(if index == "*" {
+                    Selector::All
+                } else {
+                    Selector::Index(index.parse().ok()?)
+                });
+                rest = r;
+            } else {
+                return None;
+            }
+        }
+
+        if selectors.is_empty() {
+            return None;
+        }
+        Some(AttrPath {
+            expr: expr.to_owned(),
+            attr: attr.to_owned(),
+            selectors,
+        })
+    }
+
+    /// The expression as written by the user.
+    pub(crate) fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// The name of the attribute this path projects.
+    pub(crate) fn attr(&self) -> &str {
+        &self.attr
+    }
+
+    /// Project the JSON value of the attribute. Missing keys or out of bounds indices produce
+    /// `null`, or are skipped under `[*]`.
+    pub(crate) fn project(&self, value: &serde_json::Value) -> serde_json::Value {
+        fn project(selectors: &[Selector], value: &serde_json::Value) -> Option<serde_json::Value> {
+            let (selector, rest) = match selectors.split_first() {
+                None => return Some(value.clone()),
+                Some(x) => x,
+            };
+            match (selector, value) {
+                (Selector::Key(key), serde_json::Value::Object(map)) => {
+                    project(rest, map.get(key)?)
+                }
+                (Selector::Index(index), serde_json::Value::Array(list)) => {
+                    project(rest, list.get(*index)?)
+                }
+                (Selector::All, serde_json::Value::Array(list)) => Some(serde_json::Value::Array(
+                    list.iter().filter_map(|v| project(rest, v)).collect(),
+                )),
+                (Selector::All, serde_json::Value::Object(map)) => Some(serde_json::Value::Array(
+                    map.values().filter_map(|v| project(rest, v)).collect(),
+                )),
+                _ => None,
+            }
+        }
+
+        project(&self.selectors, value).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::output_attributes::AttrPath;
+    use crate::output_attributes::OutputAttributes;
+    use crate::output_attributes::Selector;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(None, AttrPath::parse("srcs"));
+        assert_eq!(None, AttrPath::parse("^srcs$"));
+        assert_eq!(None, AttrPath::parse("buck.type"));
+        assert_eq!(None, AttrPath::parse("srcs."));
+        assert_eq!(None, AttrPath::parse("srcs[x]"));
+        assert_eq!(
+            vec![Selector::All, Selector::Key("name".to_owned())],
+            AttrPath::parse("deps[*].name").unwrap().selectors
+        );
+        assert_eq!(
+            vec![Selector::All],
+            AttrPath::parse("platform_srcs.*").unwrap().selectors
+        );
+        assert_eq!(
+            vec![Selector::Key("a".to_owned()), Selector::Index(1)],
+            AttrPath::parse("env.a[1]").unwrap().selectors
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        let attrs = OutputAttributes::new(&["^name$".to_owned(), "labels[0]".to_owned()])
+            .unwrap()
+            .unwrap();
+        assert!(attrs.regex.is_match("name"));
+        assert!(!attrs.regex.is_match("labels"));
+        assert_eq!("labels", attrs.paths[0].attr());
+        assert!(OutputAttributes::new(&[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_project() {
+        let value = json!([{"name": "a"}, {"name": "b"}, {"other": "c"}]);
+        assert_eq!(
+            json!(["a", "b"]),
+            AttrPath::parse("deps[*].name").unwrap().project(&value)
+        );
+        assert_eq!(
+            json!({"name": "b"}),
+            AttrPath::parse("deps[1]").unwrap().project(&value)
+        );
+        assert_eq!(
+            json!(null),
+            AttrPath::parse("deps[5]").unwrap().project(&value)
+        );
+        assert_eq!(
+            json!([["x"], ["y"]]),
+            AttrPath::parse("platform_srcs.*")
+                .unwrap()
+                .project(&json!({"linux": ["x"], "macos": ["y"]}))
+        );
+    }
+}
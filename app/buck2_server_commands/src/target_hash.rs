@@ -16,6 +16,7 @@ use std::sync::Arc;
 use async_recursion::async_recursion;
 use async_trait::async_trait;
 use buck2_build_api::configure_targets::get_compatible_targets;
+use buck2_cli_proto::targets_request::TargetHashFunction;
 use buck2_common::dice::file_ops::HasFileOps;
 use buck2_common::file_ops::FileOps;
 use buck2_common::file_ops::PathMetadata;
@@ -47,6 +48,8 @@ use futures::StreamExt;
 use more_futures::spawn::spawn_cancellable;
 use more_futures::spawn::DropCancelFuture;
 use os_str_bytes::OsStrBytes;
+use sha2::digest::FixedOutputReset;
+use sha2::Digest;
 use siphasher::sip128::Hasher128;
 use siphasher::sip128::SipHasher24;
 
@@ -90,6 +93,30 @@ impl BuckTargetHasher for Blake3Adapter {
     }
 }
 
+/// sha1 and sha256 are supported for tools which expect them. `BuckTargetHash` is 128 bits wide,
+/// so the digests are truncated to their first 128 bits (the first 32 hex digits of the digest
+/// other tools would print).
+struct DigestAdapter<D>(D);
+
+impl<D: Digest + Clone + Send + 'static> Hasher for DigestAdapter<D> {
+    /// The first 64 bits of the digest of what was written so far.
+    fn finish(&self) -> u64 {
+        let hash = self.0.clone().finalize();
+        u64::from_be_bytes(hash[..8].try_into().unwrap())
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
+
+impl<D: Digest + FixedOutputReset + Clone + Send + 'static> BuckTargetHasher for DigestAdapter<D> {
+    fn finish_u128(&mut self) -> BuckTargetHash {
+        let hash = self.0.finalize_reset();
+        BuckTargetHash(u128::from_be_bytes(hash[..16].try_into().unwrap()))
+    }
+}
+
 pub enum TargetHashesFileMode {
     /// The following files have changed in some way (don't do any IO)
     PathsOnly(HashSet<CellPath>),
@@ -255,7 +282,7 @@ impl TargetHashes {
         lookup: L,
         targets: TargetSet<T>,
         file_hasher: Option<Arc<dyn FileHasher>>,
-        use_fast_hash: bool,
+        hash_function: TargetHashFunction,
     ) -> anyhow::Result<Self>
     where
         T::NodeRef: ConfiguredOrUnconfiguredTargetLabel,
@@ -263,7 +290,7 @@ impl TargetHashes {
         struct Delegate<T: QueryTarget> {
             hashes: HashMap<T::NodeRef, Shared<DropCancelFuture<SharedResult<BuckTargetHash>>>>,
             file_hasher: Option<Arc<dyn FileHasher>>,
-            use_fast_hash: bool,
+            hash_function: TargetHashFunction,
             dice: DiceTransaction,
         }
 
@@ -286,7 +313,7 @@ impl TargetHashes {
                 let file_hasher = self.file_hasher.dupe();
                 let dice = self.dice.dupe();
 
-                let use_fast_hash = self.use_fast_hash;
+                let hash_function = self.hash_function;
                 // we spawn off the hash computation since it can't be done in visit directly. Even if it could,
                 // this allows us to start the computations for dependents before finishing the computation for a node.
                 self.hashes.insert(
@@ -294,7 +321,7 @@ impl TargetHashes {
                     spawn_cancellable(
                         |_| {
                             async move {
-                                let mut hasher = TargetHashes::new_hasher(use_fast_hash);
+                                let mut hasher = TargetHashes::new_hasher(hash_function);
                                 TargetHashes::hash_node(&target, &mut *hasher);
 
                                 let mut input_futs = Vec::new();
@@ -345,7 +372,7 @@ impl TargetHashes {
         let mut delegate = Delegate::<T> {
             hashes: HashMap::new(),
             file_hasher,
-            use_fast_hash,
+            hash_function,
             dice,
         };
 
@@ -380,7 +407,7 @@ impl TargetHashes {
     async fn compute_immediate_target_hashes<T: TargetHashingTargetNode>(
         targets: TargetSet<T>,
         file_hasher: Option<Arc<dyn FileHasher>>,
-        use_fast_hash: bool,
+        hash_function: TargetHashFunction,
     ) -> anyhow::Result<Self>
     where
         T::NodeRef: ConfiguredOrUnconfiguredTargetLabel,
@@ -391,7 +418,7 @@ impl TargetHashes {
                 let file_hasher = file_hasher.dupe();
                 async move {
                     let hash_result: anyhow::Result<BuckTargetHash> = try {
-                        let mut hasher = TargetHashes::new_hasher(use_fast_hash);
+                        let mut hasher = TargetHashes::new_hasher(hash_function);
                         TargetHashes::hash_node(&target, &mut *hasher);
 
                         if let Some(file_hasher) = file_hasher {
@@ -426,8 +453,11 @@ impl TargetHashes {
         Ok(Self { target_mapping })
     }
 
-    pub fn compute_immediate_one(node: &TargetNode, use_fast_hash: bool) -> BuckTargetHash {
-        let mut hasher = TargetHashes::new_hasher(use_fast_hash);
+    pub fn compute_immediate_one(
+        node: &TargetNode,
+        hash_function: TargetHashFunction,
+    ) -> BuckTargetHash {
+        let mut hasher = TargetHashes::new_hasher(hash_function);
         TargetHashes::hash_node(node, &mut *hasher);
         hasher.finish_u128()
     }
@@ -438,7 +468,7 @@ impl TargetHashes {
         targets: Vec<(PackageLabel, anyhow::Result<Vec<TargetNode>>)>,
         global_target_platform: Option<TargetLabel>,
         file_hash_mode: TargetHashesFileMode,
-        use_fast_hash: bool,
+        hash_function: TargetHashFunction,
         target_hash_recursive: bool,
     ) -> anyhow::Result<Self>
     where
@@ -447,10 +477,10 @@ impl TargetHashes {
         let targets = T::get_target_nodes(&dice, targets, global_target_platform).await?;
         let file_hasher = Self::new_file_hasher(dice.dupe(), file_hash_mode);
         if target_hash_recursive {
-            Self::compute_recursive_target_hashes(dice, lookup, targets, file_hasher, use_fast_hash)
+            Self::compute_recursive_target_hashes(dice, lookup, targets, file_hasher, hash_function)
                 .await
         } else {
-            Self::compute_immediate_target_hashes(targets, file_hasher, use_fast_hash).await
+            Self::compute_immediate_target_hashes(targets, file_hasher, hash_function).await
         }
     }
 
@@ -471,11 +501,12 @@ impl TargetHashes {
         }
     }
 
-    fn new_hasher(use_fast_hash: bool) -> Box<dyn BuckTargetHasher> {
-        if use_fast_hash {
-            Box::new(SipHasher24::new())
-        } else {
-            Box::new(Blake3Adapter(blake3::Hasher::new()))
+    fn new_hasher(hash_function: TargetHashFunction) -> Box<dyn BuckTargetHasher> {
+        match hash_function {
+            TargetHashFunction::Fast => Box::new(SipHasher24::new()),
+            TargetHashFunction::Strong => Box::new(Blake3Adapter(blake3::Hasher::new())),
+            TargetHashFunction::Sha1 => Box::new(DigestAdapter(sha1::Sha1::new())),
+            TargetHashFunction::Sha256 => Box::new(DigestAdapter(sha2::Sha256::new())),
         }
     }
 
@@ -509,7 +540,13 @@ impl TargetHashes {
 
 #[cfg(test)]
 mod tests {
+    use std::hash::Hasher;
+
+    use sha2::Digest;
+
     use crate::target_hash::BuckTargetHash;
+    use crate::target_hash::BuckTargetHasher;
+    use crate::target_hash::DigestAdapter;
 
     #[test]
     fn test_hash_display() {
@@ -522,4 +559,23 @@ mod tests {
             BuckTargetHash(u128::MAX).to_string()
         );
     }
+
+    #[test]
+    fn test_digest_truncation() {
+        let mut sha1 = DigestAdapter(sha1::Sha1::new());
+        sha1.write(b"abc");
+        assert_eq!(0xa9993e364706816a, sha1.finish());
+        assert_eq!(
+            "a9993e364706816aba3e25717850c26c",
+            sha1.finish_u128().to_string()
+        );
+
+        let mut sha256 = DigestAdapter(sha2::Sha256::new());
+        sha256.write(b"abc");
+        assert_eq!(0xba7816bf8f01cfea, sha256.finish());
+        assert_eq!(
+            "ba7816bf8f01cfea414140de5dae2223",
+            sha256.finish_u128().to_string()
+        );
+    }
 }
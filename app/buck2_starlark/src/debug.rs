@@ -132,7 +132,7 @@ impl StreamingCommand for StarlarkDebugAttachCommand {
         false
     }
 
-    fn extra_subscribers(&self) -> Vec<Box<dyn EventSubscriber>> {
+    fn extra_subscribers(&self, _ctx: &ClientCommandContext<'_>) -> Vec<Box<dyn EventSubscriber>> {
         /// We add an additional subscriber that converts a handful of informative events
         /// to DAP "output" events. Without this, at best these would go to stderr, but vscode's
         /// executable DAP client ignores stderr, so this subscriber allows us to get that information
@@ -1446,6 +1446,7 @@ mod tests {
                     name: "First - test".to_owned(),
                     duration: Some(Duration::from_micros(1)),
                     details: "1".to_owned(),
+                    attempt: 0,
                 })
                 .await?;
 
@@ -1457,6 +1458,7 @@ mod tests {
                     name: "Second - test".to_owned(),
                     duration: Some(Duration::from_micros(2)),
                     details: "2".to_owned(),
+                    attempt: 0,
                 })
                 .await?;
 
@@ -1478,6 +1480,7 @@ mod tests {
                     name: "First - test".to_owned(),
                     duration: Some(Duration::from_micros(1)),
                     details: "1".to_owned(),
+                    attempt: 0,
                 }),
                 ExecutorMessage::TestResult(TestResult {
                     target,
@@ -1487,6 +1490,7 @@ mod tests {
                     name: "Second - test".to_owned(),
                     duration: Some(Duration::from_micros(2)),
                     details: "2".to_owned(),
+                    attempt: 0,
                 }),
                 ExecutorMessage::ExitCode(0),
             ]
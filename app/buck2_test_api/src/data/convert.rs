@@ -247,6 +247,7 @@ impl TryFrom<buck2_test_proto::TestResult> for TestResult {
             msg,
             duration,
             details,
+            attempt,
         } = s;
 
         let duration = duration
@@ -264,6 +265,7 @@ impl TryFrom<buck2_test_proto::TestResult> for TestResult {
             msg: msg.map(|m| m.msg),
             duration,
             details,
+            attempt,
         })
     }
 }
@@ -281,6 +283,7 @@ impl TryInto<buck2_test_proto::TestResult> for TestResult {
             details: self.details,
             msg: self.msg.map(|msg| OptionalMsg { msg }),
             duration: self.duration.try_map(|d| d.try_into())?,
+            attempt: self.attempt,
         })
     }
 }
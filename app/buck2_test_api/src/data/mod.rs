@@ -92,6 +92,8 @@ pub struct TestResult {
     pub duration: Option<Duration>,
     // the output of the test execution (combining stdout and stderr)
     pub details: String,
+    // the 1-based attempt this result is for, if the test runner retries tests (0 otherwise)
+    pub attempt: u32,
 }
 
 /// different possible test results
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -23,6 +24,17 @@ pub struct Config {
     #[clap(long, default_value = "600", parse(try_from_str=try_parse_timeout_from_str))]
     pub timeout: Duration,
 
+    /// Number of times to retry a failing or timed out test. Failed attempts are reported with
+    /// the RERUN status, and a test which passes on a retry is reported as passing.
+    #[clap(long, default_value = "0")]
+    pub max_retries: u32,
+
+    /// File listing quarantined tests, one per line (e.g. `cell//pkg:target`). Lines starting
+    /// with `#` are ignored. Failures of quarantined tests are reported as skipped and do not
+    /// fail the test run.
+    #[clap(long)]
+    pub quarantine_file: Option<PathBuf>,
+
     #[clap(flatten)]
     ignored_args: IgnoredArgs,
 }
@@ -7,6 +7,9 @@
  * of this source tree.
  */
 
+use std::collections::HashSet;
+use std::path::Path;
+
 use anyhow::Context;
 use buck2_test_api::data::ArgValue;
 use buck2_test_api::data::ArgValueContent;
@@ -43,6 +46,7 @@ pub struct Buck2TestRunner {
     orchestrator_client: TestOrchestratorClient,
     spec_receiver: Mutex<Option<SpecReceiver>>,
     config: Config,
+    quarantined_tests: HashSet<String>,
 }
 
 impl Buck2TestRunner {
@@ -52,10 +56,15 @@ impl Buck2TestRunner {
         args: Vec<String>,
     ) -> anyhow::Result<Self> {
         let config = Config::try_parse_from(args).context("Error parsing test runner arguments")?;
+        let quarantined_tests = match &config.quarantine_file {
+            Some(path) => read_quarantine_file(path)?,
+            None => HashSet::new(),
+        };
         Ok(Self {
             orchestrator_client,
             spec_receiver: Mutex::new(Some(spec_receiver)),
             config,
+            quarantined_tests,
         })
     }
 
@@ -76,24 +85,43 @@ impl Buck2TestRunner {
                 );
                 let target_handle = spec.target.handle.to_owned();
 
-                let execution_response = self
-                    .execute_test_from_spec(spec)
-                    .await
-                    .expect("Test execution request failed");
+                let mut attempt = 1;
+                loop {
+                    let execution_response = self
+                        .execute_test_from_spec(spec.clone())
+                        .await
+                        .expect("Test execution request failed");
 
-                let execution_result = match execution_response {
-                    ExecuteResponse::Result(r) => r,
-                    ExecuteResponse::Cancelled => return TestStatus::OMITTED,
-                };
+                    let execution_result = match execution_response {
+                        ExecuteResponse::Result(r) => r,
+                        ExecuteResponse::Cancelled => return TestStatus::OMITTED,
+                    };
 
-                let test_result = get_test_result(name, target_handle, execution_result);
-                let test_status = test_result.status.clone();
+                    let mut test_result =
+                        get_test_result(name.clone(), target_handle, execution_result, attempt);
+                    let failed =
+                        matches!(test_result.status, TestStatus::FAIL | TestStatus::TIMEOUT);
+                    let retry = failed && attempt <= self.config.max_retries;
+                    if retry {
+                        test_result.status = TestStatus::RERUN;
+                    } else if failed && self.quarantined_tests.contains(&name) {
+                        test_result.msg = Some(format!(
+                            "Quarantined test failed with status {:?}, not failing the run",
+                            test_result.status
+                        ));
+                        test_result.status = TestStatus::SKIP;
+                    }
+                    let test_status = test_result.status.clone();
 
-                self.report_test_result(test_result)
-                    .await
-                    .expect("Test result reporting failed");
+                    self.report_test_result(test_result)
+                        .await
+                        .expect("Test result reporting failed");
 
-                test_status
+                    if !retry {
+                        return test_status;
+                    }
+                    attempt += 1;
+                }
             })
             // Use an arbitrarily large buffer -- execution throttling will be handled by the Buck2
             // executor, so no need to hold back on requests here.
@@ -102,7 +130,7 @@ impl Buck2TestRunner {
             .fold(
                 RunVerdict::Pass,
                 async move |mut run_verdict, test_status| {
-                    if test_status != TestStatus::PASS {
+                    if !matches!(test_status, TestStatus::PASS | TestStatus::SKIP) {
                         run_verdict = RunVerdict::Fail;
                     }
                     run_verdict
@@ -191,6 +219,7 @@ fn get_test_result(
     name: String,
     target: ConfiguredTargetHandle,
     execution_result: ExecutionResult2,
+    attempt: u32,
 ) -> TestResult {
     let status = match execution_result.status {
         ExecutionStatus::Finished { exitcode } => match exitcode {
@@ -209,9 +238,21 @@ fn get_test_result(
             "---- STDOUT ----\n{:?}\n---- STDERR ----\n{:?}\n",
             execution_result.stdout, execution_result.stderr
         ),
+        attempt,
     }
 }
 
+fn read_quarantine_file(path: &Path) -> anyhow::Result<HashSet<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Error reading quarantine file `{}`", path.display()))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect())
+}
+
 #[derive(Debug)]
 enum RunVerdict {
     Pass,
@@ -157,16 +157,41 @@ impl<'v> TransitionValue for Transition<'v> {
             .map(Dupe::dupe)
             .ok_or_else(|| TransitionError::TransitionNotAssigned.into())
     }
+
+    fn is_split(&self) -> bool {
+        self.split
+    }
 }
 
 impl TransitionValue for FrozenTransition {
     fn transition_id(&self) -> anyhow::Result<Arc<TransitionId>> {
         Ok(self.id.dupe())
     }
+
+    fn is_split(&self) -> bool {
+        self.split
+    }
 }
 
 #[starlark_module]
 fn register_transition_function(builder: &mut GlobalsBuilder) {
+    /// Declare a configuration transition, implemented in Starlark. The transition can be used
+    /// as an incoming transition with `rule(cfg = ...)`, applied to the target itself, or as an
+    /// outgoing transition on a dependency with `attrs.transition_dep(cfg = ...)`, or, for split
+    /// transitions, `attrs.split_transition_dep(cfg = ...)`.
+    ///
+    /// `impl` is a function taking `platform` (the `PlatformInfo` of the configuration being
+    /// transitioned), `refs` (a struct of the providers of the targets named in `refs`) and, if
+    /// `attrs` is given, `attrs` (a struct of the named attributes of the target being
+    /// configured). It returns a `PlatformInfo`, or if `split` is set, a dict of names to
+    /// `PlatformInfo`, one configuration per entry.
+    ///
+    /// ```python
+    /// def _host_impl(platform, refs):
+    ///     return refs.host[PlatformInfo]
+    ///
+    /// host = transition(impl = _host_impl, refs = {"host": "//platforms:host"})
+    /// ```
     fn transition<'v>(
         #[starlark(require = named)] r#impl: Value<'v>,
         #[starlark(require = named)] refs: DictOf<'v, StringValue<'v>, StringValue<'v>>,
@@ -11,7 +11,7 @@ use std::fs::File;
 use std::path::PathBuf;
 
 use clap::Parser;
-use dice::introspection::graph::SerializedGraphNodesForKey;
+use dice::introspection::snapshot::GraphSnapshot;
 
 #[derive(Debug, clap::Parser)]
 #[clap(name = "read_dump", about = "dice dump reader")]
@@ -29,7 +29,7 @@ fn main() -> anyhow::Result<()> {
 
     let file = File::open(opt.file)?;
 
-    let out: Vec<SerializedGraphNodesForKey> = bincode::deserialize_from(&file)?;
+    let out = GraphSnapshot::read(&file)?.keys;
 
     match opt.out {
         Some(path) => {
@@ -15,6 +15,7 @@ use crate::api::data::DiceData;
 use crate::api::error::DiceResult;
 use crate::api::key::Key;
 use crate::api::opaque::OpaqueValue;
+use crate::api::transaction_data::TransactionData;
 use crate::api::user_data::UserComputationData;
 use crate::ctx::DiceComputationsImpl;
 use crate::UserCycleDetectorGuard;
@@ -50,6 +51,49 @@ impl DiceComputations {
         self.0.compute(key)
     }
 
+    /// Computes all the given keys as a batch, returning the results in the same order as the keys.
+    /// Every key is recorded as a dependency of the current computation.
+    ///
+    /// Prefer this over joining the futures of `compute` for many keys, since the deps are recorded
+    /// together.
+    pub fn compute_many<'a, K>(
+        &'a self,
+        keys: impl IntoIterator<Item = &'a K>,
+    ) -> impl Future<Output = Vec<DiceResult<<K as Key>::Value>>> + 'a
+    where
+        K: Key,
+    {
+        self.0.compute_many(keys, None)
+    }
+
+    /// Like `compute_many`, but only has at most `max_parallelism` (and at least one) of the keys
+    /// in flight at once, so that large batches don't flood the scheduler with tasks.
+    pub fn compute_many_with_parallelism<'a, K>(
+        &'a self,
+        keys: impl IntoIterator<Item = &'a K>,
+        max_parallelism: usize,
+    ) -> impl Future<Output = Vec<DiceResult<<K as Key>::Value>>> + 'a
+    where
+        K: Key,
+    {
+        self.0.compute_many(keys, Some(max_parallelism.max(1)))
+    }
+
+    /// Starts computing the given keys in the background at low priority, without waiting for them
+    /// or recording them as dependencies, to warm up the graph for keys that are likely to be
+    /// requested soon. Errors are discarded, and the computations are cancelled if they are still
+    /// running when the transaction ends. They are spawned with the transaction's
+    /// [`spawner`](crate::UserComputationData::spawner), and once a request waits for one of them,
+    /// it runs at the priority of that request.
+    ///
+    /// Only used by modern DICE.
+    pub fn spawn_prefetch<'a, K>(&self, keys: impl IntoIterator<Item = &'a K>)
+    where
+        K: Key,
+    {
+        self.0.spawn_prefetch(keys)
+    }
+
     /// Compute "opaque" value where the value is only accessible via projections.
     /// Projections allow accessing derived results from the "opaque" value,
     /// where the dependency of reading a projection is the projection value rather
@@ -78,6 +122,12 @@ impl DiceComputations {
         self.0.per_transaction_data()
     }
 
+    /// Data shared by the computations of the current version, and dropped once the version is no
+    /// longer in use. See [`TransactionData`](crate::TransactionData).
+    pub fn transaction_data(&self) -> &TransactionData {
+        self.0.transaction_data()
+    }
+
     /// Gets the current cycle guard if its set. If it's set but a different type, an error will be returned.
     pub fn cycle_guard<T: UserCycleDetectorGuard>(&self) -> DiceResult<Option<&T>> {
         self.0.cycle_guard()
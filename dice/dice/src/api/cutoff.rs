@@ -0,0 +1,43 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Controls when a recomputed key invalidates the keys depending on it.
+
+use allocative::Allocative;
+use dupe::Dupe;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// How a key decides whether its dependents need to be recomputed, returned from
+/// [`Key::cutoff`](crate::Key::cutoff).
+///
+/// Only used by modern DICE.
+#[derive(
+    Allocative,
+    Clone,
+    Copy,
+    Dupe,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize
+)]
+pub enum DiceCutoff {
+    /// Dependents are only recomputed if the recomputed value is not equal, according to
+    /// [`Key::equality`](crate::Key::equality), to the previous one.
+    #[default]
+    Equality,
+    /// Dependents are recomputed whenever the key is recomputed, without comparing values.
+    Version,
+    /// Like `Version`, but the key is also invalidated by every committed change, whether or not
+    /// any of its deps changed. An escape hatch for keys that read state DICE doesn't track.
+    AlwaysInvalidate,
+}
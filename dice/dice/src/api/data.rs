@@ -51,13 +51,19 @@
 //! ```
 //!
 
+use std::any::TypeId;
 use std::collections::BTreeSet;
+use std::sync::Arc;
 
 use allocative::Allocative;
 use anymap::any::Any;
 use anymap::Map;
 use itertools::Itertools;
 use thiserror::Error;
+use tokio::sync::Semaphore;
+
+use crate::api::key::Key;
+use crate::HashMap;
 
 #[derive(Error, Debug)]
 #[error(
@@ -70,11 +76,14 @@ pub struct DiceData(
     #[allocative(skip)] // TODO(nga): measure this.
     Map<dyn Any + Send + Sync>,
     BTreeSet<&'static str>,
+    /// Concurrency limits by key type
+    #[allocative(skip)]
+    HashMap<TypeId, Arc<Semaphore>>,
 );
 
 impl DiceData {
     pub fn new() -> Self {
-        Self(Map::new(), BTreeSet::new())
+        Self(Map::new(), BTreeSet::new(), HashMap::default())
     }
 
     /// Stores the given data, overriding the previous value if any.
@@ -89,4 +98,20 @@ impl DiceData {
             .get::<K>()
             .ok_or_else(|| MissingData(std::any::type_name::<K>(), self.1.iter().join(", ")))
     }
+
+    /// Limits how many computations of keys of type `K` run at the same time, without limiting
+    /// other key types. Useful to throttle expensive computations, e.g. ones spawning processes.
+    ///
+    /// A computation holds its slot while it waits for its dependencies, so keys with a limit
+    /// must not depend, even transitively, on keys of the same type, or they may deadlock.
+    ///
+    /// Only enforced by modern DICE.
+    pub fn set_key_concurrency_limit<K: Key>(&mut self, max_parallelism: usize) {
+        self.2
+            .insert(TypeId::of::<K>(), Arc::new(Semaphore::new(max_parallelism)));
+    }
+
+    pub(crate) fn key_concurrency_limit(&self, key_type: TypeId) -> Option<&Arc<Semaphore>> {
+        self.2.get(&key_type)
+    }
 }
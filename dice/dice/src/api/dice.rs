@@ -188,15 +188,27 @@
 use std::fmt::Debug;
 use std::io::Write;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
+use dupe::Dupe;
 use futures::future::Future;
 use serde::Serializer;
 
 use crate::api::cycles::DetectCycles;
+use crate::api::eviction::DiceEvictionPolicy;
+use crate::api::gc::DiceGcPolicy;
+use crate::api::invalidations::DiceInvalidations;
+use crate::api::on_computed::DiceComputedObserver;
+use crate::api::persistence::DicePersistence;
+use crate::api::transaction::DiceEquality;
 use crate::api::transaction::DiceTransactionUpdater;
+use crate::api::transient::DiceTransientRetryPolicy;
 use crate::api::user_data::UserComputationData;
 use crate::api::which::WhichSpawner;
+use crate::introspection::cancellations::CancelledComputation;
+use crate::introspection::changes::VersionChanges;
+use crate::introspection::memory::MemoryUsage;
 use crate::metrics::Metrics;
 use crate::DiceDataBuilderImpl;
 use crate::DiceImplementation;
@@ -233,6 +245,17 @@ impl Dice {
         self.implementation.updater_with_data(extra)
     }
 
+    /// A handle for external watchers to push changes into DICE, which are coalesced and
+    /// committed together. With a `batch_delay`, the changes are committed that long after the
+    /// first change of a batch, which must be pushed from within a tokio runtime. Otherwise,
+    /// they are only committed by `DiceInvalidations::flush`.
+    pub fn subscribe_invalidations(
+        self: &Arc<Dice>,
+        batch_delay: Option<Duration>,
+    ) -> DiceInvalidations {
+        DiceInvalidations::new(self.dupe(), batch_delay)
+    }
+
     pub fn serialize_tsv(
         &self,
         nodes: impl Write,
@@ -274,6 +297,52 @@ impl Dice {
     pub async fn is_idle(&self) -> bool {
         self.implementation.is_idle().await
     }
+
+    /// Collects the graph nodes of versions that were superseded by newer versions, regardless of
+    /// the [`DiceGcPolicy`](crate::DiceGcPolicy). Returns the number of nodes reclaimed. Does
+    /// nothing with legacy DICE.
+    pub async fn gc(&self) -> usize {
+        self.implementation.gc().await
+    }
+
+    /// Frees memory on hosts under memory pressure by dropping the values of keys that are
+    /// [cheap to recompute](crate::Key::cheap_to_recompute), largest first, until about `bytes`
+    /// are freed, trading CPU for memory. The dropped keys keep their place in the graph, so
+    /// they are still invalidated correctly, and are recomputed when next requested. Values are
+    /// only freed once the transactions that computed them are dropped. Call again
+    /// while the pressure persists to drop more. Returns the bytes freed, always 0 with legacy
+    /// DICE.
+    pub async fn notify_memory_pressure(&self, bytes: usize) -> usize {
+        self.implementation.notify_memory_pressure(bytes).await
+    }
+
+    /// The bytes retained by the values of the graph, by key type and version. Walks every value,
+    /// so this is meant for on demand diagnostics rather than periodic reporting like `metrics`.
+    /// `None` with legacy DICE.
+    pub async fn memory_usage(&self) -> Option<MemoryUsage> {
+        self.implementation.memory_usage().await
+    }
+
+    /// Computations that were cancelled but are still running, e.g. because they are in a
+    /// section that can't be cancelled. Useful for diagnosing a `wait_for_idle` that never
+    /// completes. Always empty with legacy DICE.
+    pub async fn cancelled_computations(&self) -> Vec<CancelledComputation> {
+        self.implementation.cancelled_computations().await
+    }
+
+    /// The keys changed by the versions committed after the one of `from` up to and including the
+    /// one of `to`, and the keys those changes invalidated. Only the changes of the most recent
+    /// versions are kept, so this is `None` for versions that are too old, and always with legacy
+    /// DICE.
+    pub async fn changes_between(
+        &self,
+        from: DiceEquality,
+        to: DiceEquality,
+    ) -> Option<VersionChanges> {
+        self.implementation
+            .changes_between(from.version(), to.version())
+            .await
+    }
 }
 
 pub struct DiceDataBuilder(DiceDataBuilderImpl);
@@ -283,10 +352,59 @@ impl DiceDataBuilder {
         self.0.set(val);
     }
 
+    /// Persist the values of keys that opt in across daemon restarts. See
+    /// [`DicePersistence`](crate::DicePersistence). Ignored by legacy DICE.
+    pub fn set_persistence(&mut self, persistence: DicePersistence) {
+        self.0.set_persistence(persistence);
+    }
+
+    /// Bound the memory held by computed values. See
+    /// [`DiceEvictionPolicy`](crate::DiceEvictionPolicy). Ignored by legacy DICE.
+    pub fn set_eviction_policy(&mut self, policy: DiceEvictionPolicy) {
+        self.0.set_eviction_policy(policy);
+    }
+
+    /// Record why keys are recomputed, so that `DiceTransaction::why_recomputed` can explain it.
+    /// Off by default, since the records grow with the number of keys. Ignored by legacy DICE.
+    pub fn set_record_recomputes(&mut self, record: bool) {
+        self.0.set_record_recomputes(record);
+    }
+
+    /// Retry computations that produce transient values. See
+    /// [`DiceTransientRetryPolicy`](crate::DiceTransientRetryPolicy). Ignored by legacy DICE.
+    pub fn set_transient_retry_policy(&mut self, policy: DiceTransientRetryPolicy) {
+        self.0.set_transient_retry_policy(policy);
+    }
+
+    /// Decide when graph nodes of superseded versions are collected. See
+    /// [`DiceGcPolicy`](crate::DiceGcPolicy). Ignored by legacy DICE.
+    pub fn set_gc_policy(&mut self, policy: DiceGcPolicy) {
+        self.0.set_gc_policy(policy);
+    }
+
+    /// The number of shards of the index of all keys, rounded up to a power of two between 64
+    /// (the default) and 4096. More shards reduce the contention between threads adding new keys
+    /// to very large graphs, see `Metrics::key_index`. Ignored by legacy DICE.
+    pub fn set_key_index_shards(&mut self, shards: u32) {
+        self.0.set_key_index_shards(shards);
+    }
+
+    /// Calls the observer whenever a key finishes computing, see [`DiceComputedObserver`].
+    /// Observers are called in the order they were added. Ignored by legacy DICE.
+    pub fn add_computed_observer(&mut self, observer: Arc<dyn DiceComputedObserver>) {
+        self.0.add_computed_observer(observer);
+    }
+
     pub fn build(self, detect_cycles: DetectCycles) -> Arc<Dice> {
         self.build_with_which_spawner(detect_cycles, WhichSpawner::ExplicitCancel)
     }
 
+    /// Process the state on the current tokio runtime instead of a dedicated thread.
+    #[cfg(test)]
+    pub(crate) fn set_state_on_current_runtime(&mut self) {
+        self.0.set_state_on_current_runtime()
+    }
+
     pub fn build_with_which_spawner(
         self,
         detect_cycles: DetectCycles,
@@ -8,6 +8,7 @@
  */
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use dupe::Dupe;
@@ -15,6 +16,7 @@ use indexmap::IndexSet;
 use itertools::Itertools;
 use thiserror::Error;
 
+use crate::api::user_error::DiceUserError;
 use crate::legacy::cycles::RequestedKey;
 
 #[derive(Clone, Dupe, Debug, Error, Allocative)]
@@ -44,9 +46,42 @@ impl DiceError {
         DiceError(Arc::new(DiceErrorImpl::Cancelled))
     }
 
+    /// Combine the errors of independent operations, e.g. of applying pushed invalidations.
+    pub(crate) fn multiple(mut errors: Vec<DiceError>) -> Self {
+        if errors.len() == 1 {
+            return errors.pop().unwrap();
+        }
+        DiceError(Arc::new(DiceErrorImpl::Multiple(errors)))
+    }
+
     pub fn duplicate_activation_data() -> Self {
         DiceError(Arc::new(DiceErrorImpl::DuplicateActivationData))
     }
+
+    pub fn timeout(
+        key: Arc<dyn RequestedKey>,
+        timeout: Duration,
+        stack: Vec<Arc<dyn RequestedKey>>,
+    ) -> Self {
+        DiceError(Arc::new(DiceErrorImpl::Timeout {
+            key,
+            timeout,
+            stack,
+        }))
+    }
+
+    /// Whether the error was caused by a computation exceeding its [`Key::timeout`](crate::Key::timeout).
+    pub fn is_timeout(&self) -> bool {
+        matches!(&*self.0, DiceErrorImpl::Timeout { .. })
+    }
+
+    /// The error of the key computation that failed, see [`DiceUserError`].
+    pub fn user_error(&self) -> Option<&DiceUserError> {
+        match &*self.0 {
+            DiceErrorImpl::User(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Error, Allocative)]
@@ -75,6 +110,17 @@ pub(crate) enum DiceErrorImpl {
     },
     #[error("Activation data was already provided for this key")]
     DuplicateActivationData,
+    #[error("{} errors: {}", .0.len(), .0.iter().join("; "))]
+    Multiple(Vec<DiceError>),
+    #[error("Computing key `{:?}` timed out after {:?}, requested via: `{}`", key, timeout, stack.iter().map(|k| format!("{:?}", k)).join(" -> "))]
+    Timeout {
+        key: Arc<dyn RequestedKey>,
+        timeout: Duration,
+        /// The keys being computed when the key was requested, outermost first
+        stack: Vec<Arc<dyn RequestedKey>>,
+    },
+    #[error(transparent)]
+    User(DiceUserError),
 }
 
 pub type DiceResult<T> = Result<T, DiceError>;
@@ -0,0 +1,32 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Bounding the memory held by the graph of computed values.
+
+use allocative::Allocative;
+use dupe::Dupe;
+
+/// Decides when computed values are evicted from the DICE graph. Evicted keys are simply
+/// recomputed the next time they are requested.
+///
+/// Keys are evicted in least recently used order, weighted by how long they took to compute, so
+/// expensive keys are kept longer than cheap ones that were used equally recently. Evicting a key
+/// also evicts everything that depends on it. Injected keys are never evicted.
+///
+/// Only supported by modern DICE.
+#[derive(Allocative, Clone, Copy, Dupe, Debug, Default, PartialEq, Eq)]
+pub enum DiceEvictionPolicy {
+    /// Never evict anything.
+    #[default]
+    KeepAll,
+    /// Keep at most this many computed keys.
+    MaxKeys(usize),
+    /// Keep the total size of computed values, as measured by `Allocative`, under this many bytes.
+    MaxBytes(usize),
+}
@@ -0,0 +1,31 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Controlling when the graph nodes of old versions are collected.
+
+use allocative::Allocative;
+use dupe::Dupe;
+
+/// Decides when DICE collects graph nodes that were superseded by newer versions of the same key
+/// and can no longer be observed by any live or future transaction. Keys keep their newest node,
+/// so collecting never causes a recomputation.
+///
+/// Collections can always be requested explicitly with [`Dice::gc`](crate::Dice::gc).
+///
+/// Only supported by modern DICE.
+#[derive(Allocative, Clone, Copy, Dupe, Debug, Default, PartialEq, Eq)]
+pub enum DiceGcPolicy {
+    /// Only collect when explicitly requested.
+    #[default]
+    Manual,
+    /// Collect every time a new version is committed.
+    OnCommit,
+    /// Collect when the last active transaction is dropped.
+    OnIdle,
+}
@@ -0,0 +1,112 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Pushing changes from external watchers, such as file notifiers or config services, into DICE.
+//!
+//! Instead of committing a transaction for every event, watchers send their changes to a
+//! [`DiceInvalidations`], which coalesces the changes of the same key and commits them together
+//! as a single new version, either when flushed or once the batch delay has passed.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use allocative::Allocative;
+use dupe::Dupe;
+use parking_lot::Mutex;
+
+use crate::api::dice::Dice;
+use crate::api::error::DiceError;
+use crate::api::error::DiceResult;
+use crate::api::key::Key;
+use crate::api::transaction::DiceTransactionUpdater;
+use crate::legacy::cycles::RequestedKey;
+use crate::HashMap;
+
+type PendingChange = Box<dyn FnOnce(&mut DiceTransactionUpdater) -> DiceResult<()> + Send>;
+
+/// A handle to push changes into DICE, created by
+/// [`Dice::subscribe_invalidations`](crate::Dice::subscribe_invalidations).
+#[derive(Clone, Dupe, Allocative)]
+pub struct DiceInvalidations(Arc<DiceInvalidationsInner>);
+
+#[derive(Allocative)]
+struct DiceInvalidationsInner {
+    dice: Arc<Dice>,
+    batch_delay: Option<Duration>,
+    /// The latest change of every key
+    #[allocative(skip)]
+    pending: Mutex<HashMap<Arc<dyn RequestedKey>, PendingChange>>,
+}
+
+impl DiceInvalidations {
+    pub(crate) fn new(dice: Arc<Dice>, batch_delay: Option<Duration>) -> Self {
+        Self(Arc::new(DiceInvalidationsInner {
+            dice,
+            batch_delay,
+            pending: Mutex::new(HashMap::default()),
+        }))
+    }
+
+    /// Records the key as changed, like `DiceTransactionUpdater::changed`.
+    pub fn invalidate<K: Key>(&self, key: K) {
+        self.push(key.clone(), Box::new(move |updater| updater.changed([key])))
+    }
+
+    /// Records the key as changed to the value, like `DiceTransactionUpdater::changed_to`.
+    pub fn changed_to<K: Key>(&self, key: K, value: K::Value) {
+        self.push(
+            key.clone(),
+            Box::new(move |updater| updater.changed_to([(key, value)])),
+        )
+    }
+
+    fn push<K: Key>(&self, key: K, change: PendingChange) {
+        let mut pending = self.0.pending.lock();
+        let first = pending.is_empty();
+        // a later change of the same key replaces the earlier one
+        pending.insert(Arc::new(key), change);
+        drop(pending);
+
+        if let (true, Some(delay)) = (first, self.0.batch_delay) {
+            let this = self.dupe();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                if let Err(e) = this.flush().await {
+                    tracing::warn!("Failed to apply the batched invalidations: {:#}", e);
+                }
+            });
+        }
+    }
+
+    /// The number of keys with changes that haven't been committed yet.
+    pub fn pending_count(&self) -> usize {
+        self.0.pending.lock().len()
+    }
+
+    /// Commits all the pending changes as a single new version now, if there are any. Changes that
+    /// can't be applied don't prevent committing the others, and their errors are returned together.
+    pub async fn flush(&self) -> DiceResult<()> {
+        let pending = std::mem::take(&mut *self.0.pending.lock());
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut updater = self.0.dice.updater();
+        let errors: Vec<_> = pending
+            .into_values()
+            .filter_map(|change| change(&mut updater).err())
+            .collect();
+        updater.commit().await;
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(DiceError::multiple(errors))
+        }
+    }
+}
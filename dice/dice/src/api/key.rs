@@ -10,6 +10,7 @@
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::hash::Hash;
+use std::time::Duration;
 
 use allocative::Allocative;
 use async_trait::async_trait;
@@ -17,7 +18,9 @@ use dupe::Dupe;
 use more_futures::cancellation::CancellationContext;
 
 use crate::api::computations::DiceComputations;
+use crate::api::cutoff::DiceCutoff;
 use crate::api::storage_type::StorageType;
+use crate::api::user_error::DiceUserResult;
 use crate::introspection::graph::short_type_name;
 
 /// The computation Key that maps to a value. The key will be used as an index
@@ -38,11 +41,27 @@ pub trait Key: Allocative + Debug + Display + Clone + Eq + Hash + Send + Sync +
         short_type_name(std::any::type_name::<Self>())
     }
 
+    /// Computes the value of the key. Keys implement either this or `try_compute`.
     async fn compute(
+        &self,
+        _ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        unimplemented!("`{}` implements neither `compute` nor `try_compute`", self)
+    }
+
+    /// Computes the value of a key whose computation can fail. The error isn't stored as the
+    /// key's value, but returned to everything requesting the key, see
+    /// [`DiceUserError`](crate::DiceUserError).
+    ///
+    /// Only supported by modern DICE, legacy DICE panics if this fails.
+    async fn try_compute(
         &self,
         ctx: &DiceComputations,
         cancellations: &CancellationContext,
-    ) -> Self::Value;
+    ) -> DiceUserResult<Self::Value> {
+        Ok(self.compute(ctx, cancellations).await)
+    }
 
     /// If computed value is equal to previously cached value,
     /// DICE won't invalidate graph nodes depending on this node.
@@ -65,4 +84,47 @@ pub trait Key: Allocative + Debug + Display + Clone + Eq + Hash + Send + Sync +
     fn storage_type() -> StorageType {
         StorageType::LastN(1)
     }
+
+    /// How long a request of this key waits for its computation. Once exceeded, the request fails
+    /// with a [`DiceError`](crate::DiceError) for which `is_timeout` holds, and the computation is
+    /// cancelled unless something else is still waiting for it.
+    ///
+    /// Only used by modern DICE.
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// How recomputing this key affects the keys depending on it. See [`DiceCutoff`].
+    fn cutoff() -> DiceCutoff {
+        DiceCutoff::Equality
+    }
+
+    /// Whether the value is cheap enough to recompute that DICE may drop it when notified of
+    /// memory pressure, see [`Dice::notify_memory_pressure`](crate::Dice::notify_memory_pressure).
+    ///
+    /// Only used by modern DICE.
+    fn cheap_to_recompute() -> bool {
+        false
+    }
+
+    /// Stable serialization of this key, used to persist its computed value across daemon
+    /// restarts when DICE is built with a [`DicePersistence`](crate::DicePersistence). Keys that
+    /// return `Some` must also implement `serialize_value` and `deserialize_value`.
+    ///
+    /// Persisted values are reused without recording dependencies, so only keys whose
+    /// serialization captures everything their value depends on should opt in.
+    fn serialize_key(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Stable serialization of a computed value. Values for which this returns `None` are not
+    /// persisted.
+    fn serialize_value(_value: &Self::Value) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Inverse of `serialize_value`. Returning `None` makes DICE compute the value instead.
+    fn deserialize_value(_bytes: &[u8]) -> Option<Self::Value> {
+        None
+    }
 }
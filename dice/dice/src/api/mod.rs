@@ -11,16 +11,26 @@
 
 pub mod activation_tracker;
 pub mod computations;
+pub mod cutoff;
 pub mod cycles;
 pub mod data;
 pub mod dice;
 pub mod error;
 pub mod events;
+pub mod eviction;
+pub mod gc;
 pub mod injected;
+pub mod invalidations;
 pub mod key;
+pub mod on_computed;
 pub mod opaque;
+pub mod persistence;
+pub mod priority;
 pub mod projection;
 pub mod storage_type;
 pub mod transaction;
+pub mod transaction_data;
+pub mod transient;
 pub mod user_data;
+pub mod user_error;
 pub mod which;
@@ -0,0 +1,45 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Observing every key that finishes computing, e.g. to write computation traces to an event log
+//! without instrumenting each key.
+
+use std::any::Any;
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::introspection::graph::VersionNumber;
+
+/// A request for a key that finished, passed to [`DiceComputedObserver::on_computed`].
+pub struct DiceComputedEvent<'a> {
+    /// The key, which the observer will want to downcast to the types it cares about.
+    pub key: &'a dyn Any,
+    /// The display representation of the key.
+    pub key_display: &'a dyn Display,
+    pub key_type: &'static str,
+    /// The version the key was requested at.
+    pub version: VersionNumber,
+    /// The time between the key being requested and its value being ready, including checking
+    /// its deps and computing it.
+    pub duration: Duration,
+    /// Whether an existing value was reused instead of running `Key::compute`.
+    pub hit: bool,
+    /// The number of deps of the value, or `None` if it was reused without checking its deps
+    /// because it was already known to be up to date at the version.
+    pub deps_count: Option<usize>,
+}
+
+/// Registered with
+/// [`DiceDataBuilder::add_computed_observer`](crate::DiceDataBuilder::add_computed_observer) to
+/// be called whenever a key finishes computing, including when its existing value is reused.
+/// Only called by modern DICE.
+pub trait DiceComputedObserver: Send + Sync + 'static {
+    /// Called on the task that computed the key, so this should be cheap.
+    fn on_computed(&self, event: &DiceComputedEvent<'_>);
+}
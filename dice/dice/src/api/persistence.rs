@@ -0,0 +1,302 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Persisting computed values across daemon restarts.
+//!
+//! Keys opt in by implementing [`Key::serialize_key`](crate::Key::serialize_key),
+//! [`Key::serialize_value`](crate::Key::serialize_value) and
+//! [`Key::deserialize_value`](crate::Key::deserialize_value). When DICE is built with a
+//! [`DicePersistence`], such keys first look for a persisted value before computing, and store
+//! newly computed valid values.
+//!
+//! Persisted values are reused without recording any dependencies, so only keys whose serialized
+//! form captures everything their value depends on (e.g. keys containing the digest of all their
+//! inputs) should opt in.
+//!
+//! Only supported by modern DICE.
+
+use std::fs;
+use std::hash::Hasher;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use anyhow::Context;
+use dupe::Dupe;
+use fnv::FnvHasher;
+
+/// Storage for persisted values. Implementations must be safe to call concurrently.
+pub trait DicePersistentStore: Allocative + Send + Sync + 'static {
+    /// Returns the value last stored for `key`, if any.
+    fn get(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>>;
+
+    fn put(&self, key: &[u8], value: &[u8]) -> anyhow::Result<()>;
+}
+
+/// A [`DicePersistentStore`] that keeps one file per entry in a directory.
+#[derive(Allocative, Debug)]
+pub struct DiskPersistentStore {
+    dir: PathBuf,
+}
+
+impl DiskPersistentStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &[u8]) -> PathBuf {
+        let mut hasher = FnvHasher::default();
+        hasher.write(key);
+        self.dir.join(format!("{:016x}", hasher.finish()))
+    }
+}
+
+impl DicePersistentStore for DiskPersistentStore {
+    fn get(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self.path(key);
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).with_context(|| format!("reading `{}`", path.display()));
+            }
+        };
+
+        // entries are `key length, key, value`. Different keys can hash to the same file, in
+        // which case the stored key won't match.
+        match decode_entry(&contents) {
+            Some((stored_key, value)) if stored_key == key => Ok(Some(value.to_vec())),
+            _ => Ok(None),
+        }
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("creating `{}`", self.dir.display()))?;
+
+        let path = self.path(key);
+        // write to a temporary file first so concurrent readers never observe partial entries,
+        // unique to this write since other threads or processes may write the same entry
+        static NEXT_TMP_ID: AtomicU64 = AtomicU64::new(0);
+        let tmp = path.with_extension(format!(
+            "tmp.{}.{}",
+            std::process::id(),
+            NEXT_TMP_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let res = fs::write(&tmp, encode_entry(key, value))
+            .with_context(|| format!("writing `{}`", tmp.display()))
+            .and_then(|()| {
+                fs::rename(&tmp, &path).with_context(|| format!("writing `{}`", path.display()))
+            });
+        if res.is_err() {
+            let _ignored = fs::remove_file(&tmp);
+        }
+        res
+    }
+}
+
+fn encode_entry(prefix: &[u8], rest: &[u8]) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(4 + prefix.len() + rest.len());
+    entry.extend((prefix.len() as u32).to_le_bytes());
+    entry.extend(prefix);
+    entry.extend(rest);
+    entry
+}
+
+fn decode_entry(entry: &[u8]) -> Option<(&[u8], &[u8])> {
+    let len = u32::from_le_bytes(entry.get(..4)?.try_into().ok()?) as usize;
+    let prefix = entry.get(4..4 + len)?;
+    Some((prefix, &entry[4 + len..]))
+}
+
+/// Counts of persistence activity since the [`DicePersistence`] was created.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DicePersistenceStats {
+    /// Values loaded instead of computed.
+    pub hits: u64,
+    /// Lookups that found nothing usable, including entries written by another version.
+    pub misses: u64,
+    /// Values written to the store.
+    pub writes: u64,
+    /// Failures reading, writing or decoding entries.
+    pub errors: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    writes: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// Persistence configuration for a DICE instance, set via
+/// [`DiceDataBuilder::set_persistence`](crate::DiceDataBuilder::set_persistence).
+#[derive(Allocative, Clone, Dupe)]
+pub struct DicePersistence {
+    store: Arc<dyn DicePersistentStore>,
+    version: Arc<str>,
+    #[allocative(skip)]
+    counters: Arc<Counters>,
+}
+
+impl DicePersistence {
+    /// `version` identifies the format of persisted values (e.g. the version of the binary that
+    /// defines the keys). Entries written under a different version are ignored.
+    pub fn new(store: Arc<dyn DicePersistentStore>, version: impl Into<String>) -> Self {
+        Self {
+            store,
+            version: version.into().into(),
+            counters: Default::default(),
+        }
+    }
+
+    pub fn stats(&self) -> DicePersistenceStats {
+        DicePersistenceStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            writes: self.counters.writes.load(Ordering::Relaxed),
+            errors: self.counters.errors.load(Ordering::Relaxed),
+        }
+    }
+
+    fn store_key(key_type: &str, key: &[u8]) -> Vec<u8> {
+        encode_entry(key_type.as_bytes(), key)
+    }
+
+    /// Loads the serialized value persisted for the key, if it was written by the same version.
+    pub(crate) fn load(&self, key_type: &str, key: &[u8]) -> Option<Vec<u8>> {
+        match self.store.get(&Self::store_key(key_type, key)) {
+            Ok(Some(entry)) => match decode_entry(&entry) {
+                Some((version, value)) if version == self.version.as_bytes() => {
+                    Some(value.to_vec())
+                }
+                _ => {
+                    self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                    None
+                }
+            },
+            Ok(None) => {
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Err(e) => {
+                debug!("failed to load persisted value for `{}`: {:#}", key_type, e);
+                self.counters.errors.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Records that the value returned by `load` was usable.
+    pub(crate) fn record_hit(&self) {
+        self.counters.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records that the value returned by `load` could not be deserialized.
+    pub(crate) fn record_error(&self) {
+        self.counters.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn persist(&self, key_type: &str, key: &[u8], value: &[u8]) {
+        let entry = encode_entry(self.version.as_bytes(), value);
+        match self.store.put(&Self::store_key(key_type, key), &entry) {
+            Ok(()) => {
+                self.counters.writes.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                debug!("failed to persist value for `{}`: {:#}", key_type, e);
+                self.counters.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::api::persistence::DicePersistence;
+    use crate::api::persistence::DicePersistenceStats;
+    use crate::api::persistence::DicePersistentStore;
+    use crate::api::persistence::DiskPersistentStore;
+
+    #[test]
+    fn disk_store_roundtrips() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = DiskPersistentStore::new(dir.path().join("cache"));
+
+        assert_eq!(None, store.get(b"k")?);
+        store.put(b"k", b"v1")?;
+        store.put(b"k", b"v2")?;
+        assert_eq!(Some(b"v2".to_vec()), store.get(b"k")?);
+        assert_eq!(None, store.get(b"other")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent_disk_store_writes() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = DiskPersistentStore::new(dir.path());
+
+        std::thread::scope(|s| {
+            let writers: Vec<_> = (0..8u8)
+                .map(|i| {
+                    let store = &store;
+                    s.spawn(move || -> anyhow::Result<()> {
+                        for _ in 0..50 {
+                            store.put(b"k", &[i; 1024])?;
+                        }
+                        Ok(())
+                    })
+                })
+                .collect();
+            writers
+                .into_iter()
+                .try_for_each(|writer| writer.join().unwrap())
+        })?;
+
+        let value = store.get(b"k")?.unwrap();
+        assert_eq!(1024, value.len());
+        assert!(value.iter().all(|b| *b == value[0]));
+        // no temporary files are left behind
+        assert_eq!(1, std::fs::read_dir(dir.path())?.count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn entries_are_validated_against_version() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store: Arc<dyn DicePersistentStore> = Arc::new(DiskPersistentStore::new(dir.path()));
+
+        let v1 = DicePersistence::new(store.clone(), "v1");
+        v1.persist("Key", b"k", b"value");
+        assert_eq!(Some(b"value".to_vec()), v1.load("Key", b"k"));
+        assert_eq!(None, v1.load("OtherKey", b"k"));
+
+        let v2 = DicePersistence::new(store, "v2");
+        assert_eq!(None, v2.load("Key", b"k"));
+        assert_eq!(
+            DicePersistenceStats {
+                hits: 0,
+                misses: 1,
+                writes: 0,
+                errors: 0,
+            },
+            v2.stats()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Hints for ordering the work of concurrent transactions.
+
+use allocative::Allocative;
+use dupe::Dupe;
+
+/// How urgently the computations of a transaction are needed, set via
+/// [`UserComputationData::priority`](crate::UserComputationData::priority).
+///
+/// When the DICE state is busy, requests of interactive transactions are handled before those of
+/// normal transactions, which are handled before those of background ones. This is only a hint:
+/// it orders the work queued at the same time, but never delays anything indefinitely.
+///
+/// Only used by modern DICE.
+#[derive(
+    Allocative, Clone, Copy, Dupe, Debug, Default, PartialEq, Eq, PartialOrd, Ord
+)]
+pub enum DicePriority {
+    /// Work nobody is waiting for yet, e.g. prefetching.
+    Background,
+    #[default]
+    Normal,
+    /// Work a user is actively waiting for.
+    Interactive,
+}
@@ -18,6 +18,8 @@ use crate::api::computations::DiceComputations;
 use crate::api::error::DiceResult;
 use crate::api::key::Key;
 use crate::api::user_data::UserComputationData;
+use crate::introspection::changes::VersionChanges;
+use crate::introspection::recompute::RecomputeChain;
 use crate::transaction::DiceTransactionImpl;
 use crate::transaction_update::DiceTransactionUpdaterImpl;
 use crate::versions::VersionNumber;
@@ -33,6 +35,14 @@ impl DiceTransactionUpdater {
         self.0.existing_state()
     }
 
+    /// Answers "what if" questions: the keys that committing the changes recorded so far would
+    /// change, and the keys those changes would invalidate, computed against the current
+    /// committed state without modifying it. The updater can then be dropped instead of
+    /// committed. Always `None` with legacy DICE.
+    pub fn preview(&self) -> impl Future<Output = Option<VersionChanges>> + '_ {
+        self.0.preview()
+    }
+
     /// Records a set of `Key`s as changed so that they, and any dependents will
     /// be recomputed on the next set of requests at the next version.
     pub fn changed<K, I>(&mut self, changed: I) -> DiceResult<()>
@@ -102,6 +112,14 @@ impl DiceTransaction {
         DiceEquality(self.0.get_version())
     }
 
+    /// Explains why the key was recomputed at this transaction's version, as the chain of changed
+    /// dependencies that caused it. Returns `None` if the key wasn't recomputed at this version,
+    /// it was too long ago to be remembered, recomputes aren't recorded (see
+    /// `DiceDataBuilder::set_record_recomputes`), or this is legacy DICE.
+    pub async fn why_recomputed<K: Key>(&self, key: &K) -> Option<RecomputeChain> {
+        self.0.why_recomputed(key).await
+    }
+
     /// Creates an Updater to record changes to DICE that upon committing, creates a new transaction
     /// that keeps the same set of user data. This is equivalent to `Dice::updater_with_user_data(data)`
     /// where the `data` is taken from the current Transaction.
@@ -114,6 +132,12 @@ impl DiceTransaction {
 #[repr(transparent)]
 pub struct DiceEquality(VersionNumber);
 
+impl DiceEquality {
+    pub(crate) fn version(self) -> VersionNumber {
+        self.0
+    }
+}
+
 mod private {
     use super::*;
 
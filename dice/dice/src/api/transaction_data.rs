@@ -0,0 +1,56 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Data shared by the computations of a single version of the graph.
+//!
+//! Unlike `DiceData`, which lives as long as Dice, and `UserComputationData`, which is set by
+//! whoever creates a transaction, this data can be written by computations, and is dropped once
+//! nothing uses the version anymore, so it never leaks into computations of later versions. This
+//! makes it suitable for caches of values that are only valid at a single version.
+//!
+//! Reads and writes are NOT tracked by Dice, so the cached values must be derivable from the
+//! tracked state of the version, or computations will be incorrectly reused.
+
+use std::sync::Arc;
+
+use allocative::Allocative;
+use anymap::any::Any;
+use anymap::Map;
+use parking_lot::Mutex;
+
+#[derive(Allocative)]
+pub struct TransactionData(#[allocative(skip)] Mutex<Map<dyn Any + Send + Sync>>);
+
+impl Default for TransactionData {
+    fn default() -> Self {
+        Self(Mutex::new(Map::new()))
+    }
+}
+
+impl TransactionData {
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.0.lock().get::<Arc<T>>().cloned()
+    }
+
+    /// Stores the given data, overriding the previous value if any.
+    pub fn set<T: Send + Sync + 'static>(&self, val: T) {
+        self.0.lock().insert(Arc::new(val));
+    }
+
+    /// Gets the data, first storing the result of `init` if there is none. `init` is called
+    /// without holding any lock, so concurrent computations may both call it, but all of them get
+    /// the value that was stored first.
+    pub fn get_or_insert_with<T: Send + Sync + 'static>(&self, init: impl FnOnce() -> T) -> Arc<T> {
+        if let Some(val) = self.get() {
+            return val;
+        }
+        let val = Arc::new(init());
+        self.0.lock().entry::<Arc<T>>().or_insert(val).clone()
+    }
+}
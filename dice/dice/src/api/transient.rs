@@ -0,0 +1,41 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Retrying computations that produce transient values.
+//!
+//! A value is transient when [`Key::validity`](crate::Key::validity) returns false for it, e.g.
+//! because it records a network flake. Transient values are shared by everything that requests
+//! the key at the same version, but are never cached across versions.
+
+use std::time::Duration;
+
+use allocative::Allocative;
+use dupe::Dupe;
+
+/// How many times DICE immediately recomputes a key whose computation produced a transient value,
+/// before handing the transient value to its requesters. Transient values caused by transient
+/// dependencies are not retried, since the dependencies would not be recomputed.
+///
+/// Only supported by modern DICE.
+#[derive(Allocative, Clone, Copy, Dupe, Debug, Default, PartialEq, Eq)]
+pub struct DiceTransientRetryPolicy {
+    /// Retries after the first attempt. `0` never retries.
+    pub max_retries: u32,
+    /// Time to wait before each retry.
+    pub backoff: Duration,
+}
+
+impl DiceTransientRetryPolicy {
+    pub fn new(max_retries: u32, backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            backoff,
+        }
+    }
+}
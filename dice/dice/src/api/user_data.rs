@@ -18,6 +18,7 @@ use crate::api::activation_tracker::ActivationTracker;
 use crate::api::data::DiceData;
 use crate::api::events::DiceEvent;
 use crate::api::events::DiceEventListener;
+use crate::api::priority::DicePriority;
 
 /// Includes all user related computation-specific data.
 #[derive(Allocative)]
@@ -37,6 +38,9 @@ pub struct UserComputationData {
     #[allocative(skip)]
     pub activation_tracker: Option<Arc<dyn ActivationTracker>>,
 
+    /// How urgently the computations of the transaction are needed. See [`DicePriority`].
+    pub priority: DicePriority,
+
     /// We require that UserComputationData always be constructed with `..Default::default()`
     pub _requires_default: RequireDefault,
 }
@@ -80,6 +84,7 @@ impl Default for UserComputationData {
             spawner: Arc::new(TokioSpawner),
             cycle_detector: None,
             activation_tracker: None,
+            priority: DicePriority::Normal,
             _requires_default: RequireDefault(()),
         }
     }
@@ -0,0 +1,101 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Errors returned by key computations.
+//!
+//! A key whose computation can fail implements [`Key::try_compute`](crate::Key::try_compute)
+//! instead of `compute`. The error is never stored as the key's value. It is shared by everything
+//! requesting the key at the same version, each of which gets it as a [`DiceError`] for which
+//! `user_error` is `Some`, so dependents that fail too can propagate it with `?`. A propagated
+//! error stays the same error, so the failures reported by many dependents of a failed key can be
+//! deduplicated with [`DiceUserError::dedup`].
+//!
+//! Like transient values, failed computations and everything depending on them are not reused at
+//! later versions, so they are recomputed when requested again.
+//!
+//! Only supported by modern DICE.
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use dupe::Dupe;
+
+use crate::api::error::DiceError;
+use crate::api::error::DiceErrorImpl;
+use crate::HashSet;
+
+/// A shareable error returned by a key computation.
+#[derive(Clone, Dupe, Allocative)]
+pub struct DiceUserError(#[allocative(skip)] Arc<anyhow::Error>);
+
+pub type DiceUserResult<T> = Result<T, DiceUserError>;
+
+impl DiceUserError {
+    pub fn new(error: impl Into<anyhow::Error>) -> Self {
+        Self(Arc::new(error.into()))
+    }
+
+    /// The underlying error, if it's of type `E`.
+    pub fn downcast_ref<E: Display + Debug + Send + Sync + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+
+    /// Whether both are the same error, rather than errors that are merely alike.
+    pub fn is_same(&self, other: &DiceUserError) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Removes the repeated errors, keeping the first of each in order.
+    pub fn dedup(errors: impl IntoIterator<Item = DiceUserError>) -> Vec<DiceUserError> {
+        let mut seen = HashSet::default();
+        errors
+            .into_iter()
+            .filter(|e| seen.insert(Arc::as_ptr(&e.0)))
+            .collect()
+    }
+}
+
+/// Propagates the error of a failed dependency as is, and wraps any other error.
+impl From<DiceError> for DiceUserError {
+    fn from(e: DiceError) -> Self {
+        match e.user_error() {
+            Some(e) => e.dupe(),
+            None => Self::new(e),
+        }
+    }
+}
+
+impl From<DiceUserError> for DiceError {
+    fn from(e: DiceUserError) -> Self {
+        DiceError(Arc::new(DiceErrorImpl::User(e)))
+    }
+}
+
+impl Display for DiceUserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&*self.0, f)
+    }
+}
+
+impl Debug for DiceUserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Error for DiceUserError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
@@ -11,13 +11,16 @@ use std::future::Future;
 use std::sync::Arc;
 
 use allocative::Allocative;
+use futures::stream::FuturesOrdered;
 use futures::FutureExt;
+use futures::StreamExt;
 
 use crate::api::data::DiceData;
 use crate::api::error::DiceResult;
 use crate::api::key::Key;
 use crate::api::opaque::OpaqueValue;
 use crate::api::transaction::DiceTransactionUpdater;
+use crate::api::transaction_data::TransactionData;
 use crate::api::user_data::UserComputationData;
 use crate::api::user_data::UserCycleDetectorGuard;
 use crate::impls::ctx::PerComputeCtx;
@@ -52,6 +55,58 @@ impl DiceComputationsImpl {
         }
     }
 
+    /// Computes all the keys, with at most `max_parallelism` of them in flight at once if given,
+    /// returning the results in the order of the keys.
+    pub(crate) fn compute_many<'a, K>(
+        &'a self,
+        keys: impl IntoIterator<Item = &'a K>,
+        max_parallelism: Option<usize>,
+    ) -> impl Future<Output = Vec<DiceResult<<K as Key>::Value>>> + 'a
+    where
+        K: Key,
+    {
+        match self {
+            DiceComputationsImpl::Legacy(_) => {
+                let keys: Vec<&'a K> = keys.into_iter().collect();
+                async move {
+                    let max_parallelism = max_parallelism.unwrap_or(keys.len());
+                    let mut keys = keys.into_iter();
+                    let mut in_flight = FuturesOrdered::new();
+                    let mut results = Vec::new();
+                    loop {
+                        while in_flight.len() < max_parallelism {
+                            match keys.next() {
+                                Some(key) => in_flight.push_back(self.compute(key)),
+                                None => break,
+                            }
+                        }
+                        match in_flight.next().await {
+                            Some(result) => results.push(result),
+                            None => break,
+                        }
+                    }
+                    results
+                }
+                .left_future()
+            }
+            DiceComputationsImpl::Modern(delegate) => {
+                delegate.compute_many(keys, max_parallelism).right_future()
+            }
+        }
+    }
+
+    pub(crate) fn spawn_prefetch<'a, K>(&self, keys: impl IntoIterator<Item = &'a K>)
+    where
+        K: Key,
+    {
+        match self {
+            DiceComputationsImpl::Legacy(_) => {
+                // prefetching is only a hint, and legacy dice has no priorities to honor it with
+            }
+            DiceComputationsImpl::Modern(delegate) => delegate.spawn_prefetch(keys),
+        }
+    }
+
     /// Compute "opaque" value where the value is only accessible via projections.
     /// Projections allow accessing derived results from the "opaque" value,
     /// where the dependency of reading a projection is the projection value rather
@@ -95,6 +150,13 @@ impl DiceComputationsImpl {
         }
     }
 
+    pub(crate) fn transaction_data(&self) -> &TransactionData {
+        match self {
+            DiceComputationsImpl::Legacy(delegate) => delegate.transaction_data(),
+            DiceComputationsImpl::Modern(delegate) => delegate.transaction_data(),
+        }
+    }
+
     pub(crate) fn cycle_guard<T: UserCycleDetectorGuard>(&self) -> DiceResult<Option<&T>> {
         match self {
             DiceComputationsImpl::Legacy(delegate) => delegate.cycle_guard(),
@@ -17,8 +17,12 @@ use dashmap::DashMap;
 use dupe::Dupe;
 use fnv::FnvBuildHasher;
 use lock_free_hashtable::sharded::ShardedLockFreeRawTable;
+use tokio::time::Instant;
 
+use crate::api::priority::DicePriority;
+use crate::api::transaction_data::TransactionData;
 use crate::arc::Arc;
+use crate::impls::evaluator::EvalPriority;
 use crate::impls::key::DiceKey;
 use crate::impls::task::dice::DiceTask;
 use crate::impls::task::dice::DiceTaskInternal;
@@ -30,7 +34,10 @@ struct Data {
     completed: ShardedLockFreeRawTable<Arc<DiceTaskInternal>, 64>,
     /// Completed tasks lazily moved into `completed` from this map.
     storage: DashMap<DiceKey, DiceTask, FnvBuildHasher>,
+    /// Priorities of the tasks spawned by prefetches, to raise when other requests join them.
+    prefetches: DashMap<DiceKey, EvalPriority, FnvBuildHasher>,
     is_cancelled: AtomicBool,
+    transaction_data: TransactionData,
 }
 
 #[derive(Allocative, Clone, Dupe)]
@@ -126,16 +133,47 @@ impl SharedCache {
         SharedCache {
             data: Arc::new(Data {
                 storage: DashMap::default(),
+                prefetches: DashMap::default(),
                 completed: ShardedLockFreeRawTable::new(),
                 is_cancelled: AtomicBool::new(false),
+                transaction_data: TransactionData::default(),
             }),
         }
     }
 
+    pub(crate) fn transaction_data(&self) -> &TransactionData {
+        &self.data.transaction_data
+    }
+
+    /// Record the priority of a task spawned by a prefetch.
+    pub(crate) fn register_prefetch(&self, key: DiceKey, priority: EvalPriority) {
+        self.data.prefetches.insert(key, priority);
+    }
+
+    /// Raise the task of `key` to `priority`, if it was spawned by a prefetch.
+    pub(crate) fn raise_prefetch(&self, key: DiceKey, priority: DicePriority) {
+        if priority == DicePriority::Background {
+            return;
+        }
+        if let Some(prefetch) = self.data.prefetches.get(&key) {
+            prefetch.raise_to(priority);
+        }
+    }
+
     pub(crate) fn active_tasks_count(&self) -> usize {
         self.data.storage.len() + self.data.completed.len()
     }
 
+    /// Tasks that were cancelled but are still running, with the time they were cancelled.
+    pub(crate) fn cancelled_tasks(&self) -> impl Iterator<Item = (DiceKey, Instant)> + '_ {
+        self.data.storage.iter().filter_map(|entry| {
+            entry
+                .value()
+                .cancelled_while_running()
+                .map(|at| (*entry.key(), at))
+        })
+    }
+
     /// This function gets the termination observer for all running tasks when transaction is
     /// cancelled and prevents further tasks from being added
     pub(crate) fn cancel_pending_tasks(self) -> Vec<DiceTask> {
@@ -193,10 +231,12 @@ mod tests {
 
     use crate::api::computations::DiceComputations;
     use crate::api::key::Key;
+    use crate::api::priority::DicePriority;
     use crate::arc::Arc;
     use crate::impls::cache::DiceTaskRef;
     use crate::impls::cache::SharedCache;
     use crate::impls::core::graph::history::CellHistory;
+    use crate::impls::evaluator::EvalPriority;
     use crate::impls::key::DiceKey;
     use crate::impls::key::ParentKey;
     use crate::impls::task::dice::DiceTask;
@@ -320,4 +360,19 @@ mod tests {
             DiceTaskRef::TransactionCancelled
         ));
     }
+
+    #[test]
+    fn test_raise_prefetch() {
+        let cache = SharedCache::new();
+        let prefetch = EvalPriority::prefetch();
+        cache.register_prefetch(DiceKey { index: 1 }, prefetch.dupe());
+
+        // only the prefetched key is raised, and never lowered
+        cache.raise_prefetch(DiceKey { index: 2 }, DicePriority::Interactive);
+        assert_eq!(DicePriority::Background, prefetch.get());
+        cache.raise_prefetch(DiceKey { index: 1 }, DicePriority::Interactive);
+        assert_eq!(DicePriority::Interactive, prefetch.get());
+        cache.raise_prefetch(DiceKey { index: 1 }, DicePriority::Normal);
+        assert_eq!(DicePriority::Interactive, prefetch.get());
+    }
 }
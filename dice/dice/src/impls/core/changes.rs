@@ -0,0 +1,152 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Records which keys each committed version changed
+
+use std::collections::VecDeque;
+
+use allocative::Allocative;
+
+use crate::impls::key::DiceKey;
+use crate::versions::VersionNumber;
+use crate::HashSet;
+
+/// Only the changes of the most recent versions are kept
+const VERSIONS_KEPT: usize = 64;
+
+#[derive(Allocative, Default)]
+struct RecordedChanges {
+    /// The keys reported as changed by the transaction
+    changed: Vec<DiceKey>,
+    /// The keys invalidated because something they depend on changed
+    invalidated: Vec<DiceKey>,
+}
+
+#[derive(Allocative, Default)]
+pub(crate) struct ChangeLog {
+    /// Sorted by version
+    versions: VecDeque<(VersionNumber, RecordedChanges)>,
+    /// The newest version whose changes are no longer kept
+    dropped_through: Option<VersionNumber>,
+}
+
+impl ChangeLog {
+    pub(crate) fn record(
+        &mut self,
+        v: VersionNumber,
+        changed: Vec<DiceKey>,
+        invalidated: Vec<DiceKey>,
+    ) {
+        self.versions.push_back((
+            v,
+            RecordedChanges {
+                changed,
+                invalidated,
+            },
+        ));
+        if self.versions.len() > VERSIONS_KEPT {
+            self.dropped_through = self.versions.pop_front().map(|(v, _)| v);
+        }
+    }
+
+    /// The keys changed by the versions after `from` up to and including `to`, and the keys
+    /// invalidated by those changes, or `None` if the changes are no longer known.
+    pub(crate) fn between(
+        &self,
+        from: VersionNumber,
+        to: VersionNumber,
+    ) -> Option<(HashSet<DiceKey>, HashSet<DiceKey>)> {
+        if self.dropped_through.map_or(false, |dropped| dropped > from) {
+            return None;
+        }
+
+        let mut changed = HashSet::default();
+        let mut invalidated = HashSet::default();
+        for (_, changes) in self.versions.iter().filter(|(v, _)| from < *v && *v <= to) {
+            changed.extend(changes.changed.iter().copied());
+            invalidated.extend(changes.invalidated.iter().copied());
+        }
+        invalidated.retain(|k| !changed.contains(k));
+
+        Some((changed, invalidated))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        if let Some((v, _)) = self.versions.back() {
+            self.dropped_through = Some(*v);
+        }
+        self.versions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::impls::core::changes::ChangeLog;
+    use crate::impls::core::changes::VERSIONS_KEPT;
+    use crate::impls::key::DiceKey;
+    use crate::versions::VersionNumber;
+    use crate::HashSet;
+
+    fn keys(indices: &[u32]) -> HashSet<DiceKey> {
+        indices
+            .iter()
+            .map(|index| DiceKey { index: *index })
+            .collect()
+    }
+
+    #[test]
+    fn between_combines_versions_in_range() {
+        let mut log = ChangeLog::default();
+        log.record(
+            VersionNumber::new(1),
+            vec![DiceKey { index: 1 }],
+            vec![DiceKey { index: 10 }],
+        );
+        log.record(
+            VersionNumber::new(2),
+            vec![DiceKey { index: 2 }, DiceKey { index: 10 }],
+            vec![DiceKey { index: 11 }],
+        );
+        log.record(
+            VersionNumber::new(3),
+            vec![DiceKey { index: 3 }],
+            vec![DiceKey { index: 12 }],
+        );
+
+        assert_eq!(
+            Some((keys(&[2, 10]), keys(&[11]))),
+            log.between(VersionNumber::new(1), VersionNumber::new(2))
+        );
+        assert_eq!(
+            Some((keys(&[1, 2, 3, 10]), keys(&[11, 12]))),
+            log.between(VersionNumber::new(0), VersionNumber::new(3))
+        );
+        assert_eq!(
+            Some((keys(&[]), keys(&[]))),
+            log.between(VersionNumber::new(3), VersionNumber::new(3))
+        );
+    }
+
+    #[test]
+    fn between_is_unknown_once_dropped() {
+        let mut log = ChangeLog::default();
+        for v in 1..=VERSIONS_KEPT + 1 {
+            log.record(VersionNumber::new(v), vec![DiceKey { index: 0 }], vec![]);
+        }
+
+        assert_eq!(
+            None,
+            log.between(VersionNumber::new(0), VersionNumber::new(2))
+        );
+        assert!(
+            log.between(VersionNumber::new(1), VersionNumber::new(2))
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,203 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Chooses which keys to evict from the graph according to the `DiceEvictionPolicy`.
+//!
+//! This implements the GreedyDual algorithm: every key gets a priority of the current "inflation"
+//! plus the cost of recomputing it whenever it is used, and the key with the lowest priority is
+//! evicted, raising the inflation to its priority. With equal costs, this is exactly LRU.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use allocative::Allocative;
+
+use crate::api::eviction::DiceEvictionPolicy;
+use crate::impls::key::DiceKey;
+use crate::impls::value::DiceValidValue;
+use crate::HashMap;
+use crate::HashSet;
+
+#[derive(Allocative)]
+pub(crate) struct EvictionTracker {
+    policy: DiceEvictionPolicy,
+    /// The priority of the last evicted key
+    inflation: u64,
+    entries: HashMap<DiceKey, TrackedKey>,
+    /// Evictable keys ordered by priority
+    queue: BTreeSet<(u64, DiceKey)>,
+    /// Keys that must never be evicted, because they can't be recomputed
+    pinned: HashSet<DiceKey>,
+    bytes: usize,
+}
+
+#[derive(Allocative)]
+struct TrackedKey {
+    priority: u64,
+    /// Time taken to compute the key, in microseconds
+    cost: u64,
+    bytes: usize,
+}
+
+impl EvictionTracker {
+    pub(crate) fn new(policy: DiceEvictionPolicy) -> Self {
+        Self {
+            policy,
+            inflation: 0,
+            entries: Default::default(),
+            queue: Default::default(),
+            pinned: Default::default(),
+            bytes: 0,
+        }
+    }
+
+    /// Marks the key as not evictable, e.g. because it is injected.
+    pub(crate) fn pin(&mut self, key: DiceKey) {
+        if self.policy == DiceEvictionPolicy::KeepAll {
+            return;
+        }
+        self.remove(key);
+        self.pinned.insert(key);
+    }
+
+    /// Records that the key's value was used, and if given, recomputed at the given cost.
+    pub(crate) fn touch(
+        &mut self,
+        key: DiceKey,
+        cost: Option<Duration>,
+        value: Option<&DiceValidValue>,
+    ) {
+        if self.policy == DiceEvictionPolicy::KeepAll || self.pinned.contains(&key) {
+            return;
+        }
+
+        let bytes = match (self.policy, value) {
+            (DiceEvictionPolicy::MaxBytes(_), Some(value)) => {
+                Some(allocative::size_of_unique_allocated_data(value))
+            }
+            _ => None,
+        };
+
+        let entry = self.entries.entry(key).or_insert(TrackedKey {
+            priority: 0,
+            cost: 1,
+            bytes: 0,
+        });
+        self.queue.remove(&(entry.priority, key));
+
+        if let Some(cost) = cost {
+            entry.cost = (cost.as_micros() as u64).max(1);
+        }
+        if let Some(bytes) = bytes {
+            self.bytes = self.bytes - entry.bytes + bytes;
+            entry.bytes = bytes;
+        }
+        entry.priority = self.inflation.saturating_add(entry.cost);
+        self.queue.insert((entry.priority, key));
+    }
+
+    /// Records that the key's value was dropped to free memory, while it stays in the graph.
+    pub(crate) fn value_dropped(&mut self, key: DiceKey) {
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.bytes -= entry.bytes;
+            entry.bytes = 0;
+        }
+    }
+
+    /// Stops tracking a key that was removed from the graph.
+    pub(crate) fn remove(&mut self, key: DiceKey) {
+        if let Some(entry) = self.entries.remove(&key) {
+            self.queue.remove(&(entry.priority, key));
+            self.bytes -= entry.bytes;
+        }
+    }
+
+    /// The next key to evict, if the policy's budget is exceeded.
+    pub(crate) fn next_to_evict(&mut self) -> Option<DiceKey> {
+        let over_budget = match self.policy {
+            DiceEvictionPolicy::KeepAll => false,
+            DiceEvictionPolicy::MaxKeys(max) => self.entries.len() > max,
+            DiceEvictionPolicy::MaxBytes(max) => self.bytes > max,
+        };
+        if !over_budget {
+            return None;
+        }
+
+        let (priority, key) = *self.queue.iter().next()?;
+        self.inflation = priority;
+        self.remove(key);
+        Some(key)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.queue.clear();
+        self.pinned.clear();
+        self.bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::api::eviction::DiceEvictionPolicy;
+    use crate::impls::core::graph::eviction::EvictionTracker;
+    use crate::impls::key::DiceKey;
+
+    fn k(index: u32) -> DiceKey {
+        DiceKey { index }
+    }
+
+    #[test]
+    fn keep_all_evicts_nothing() {
+        let mut tracker = EvictionTracker::new(DiceEvictionPolicy::KeepAll);
+        for i in 0..10 {
+            tracker.touch(k(i), Some(Duration::from_micros(1)), None);
+        }
+        assert_eq!(None, tracker.next_to_evict());
+    }
+
+    #[test]
+    fn evicts_least_recently_used_when_costs_are_equal() {
+        let mut tracker = EvictionTracker::new(DiceEvictionPolicy::MaxKeys(2));
+        let cost = Some(Duration::from_micros(10));
+        tracker.touch(k(1), cost, None);
+        tracker.touch(k(2), cost, None);
+        assert_eq!(None, tracker.next_to_evict());
+
+        tracker.touch(k(3), cost, None);
+        assert_eq!(Some(k(1)), tracker.next_to_evict());
+        assert_eq!(None, tracker.next_to_evict());
+
+        // using 2 makes 3 the least recently used
+        tracker.touch(k(2), None, None);
+        tracker.touch(k(4), cost, None);
+        assert_eq!(Some(k(3)), tracker.next_to_evict());
+    }
+
+    #[test]
+    fn keeps_expensive_keys_longer() {
+        let mut tracker = EvictionTracker::new(DiceEvictionPolicy::MaxKeys(1));
+        tracker.touch(k(1), Some(Duration::from_secs(1)), None);
+        tracker.touch(k(2), Some(Duration::from_micros(1)), None);
+        assert_eq!(Some(k(2)), tracker.next_to_evict());
+
+        tracker.touch(k(3), Some(Duration::from_micros(1)), None);
+        assert_eq!(Some(k(3)), tracker.next_to_evict());
+    }
+
+    #[test]
+    fn pinned_keys_are_never_evicted() {
+        let mut tracker = EvictionTracker::new(DiceEvictionPolicy::MaxKeys(0));
+        tracker.pin(k(1));
+        tracker.touch(k(1), Some(Duration::from_micros(1)), None);
+        assert_eq!(None, tracker.next_to_evict());
+    }
+}
@@ -11,6 +11,7 @@ use std::collections::BTreeMap;
 
 use gazebo::prelude::SliceExt;
 
+use crate::api::cutoff::DiceCutoff;
 use crate::arc::Arc;
 use crate::impls::core::graph::nodes::VersionedGraphNode;
 use crate::impls::core::graph::storage::VersionedGraph;
@@ -51,6 +52,7 @@ impl VersionedGraph {
     pub(crate) fn introspect(
         &self,
         key_map: HashMap<DiceKey, AnyKey>,
+        cutoffs: &HashMap<DiceKey, DiceCutoff>,
     ) -> VersionedGraphIntrospectable {
         let mut edges = HashMap::default();
         let mut nodes = HashMap::default();
@@ -80,6 +82,7 @@ impl VersionedGraph {
                     id: KeyID(k.index as usize),
                     key: dyn_k.to_string(),
                     type_name: dyn_k.short_type_name().to_owned(),
+                    cutoff: Some(cutoffs.get(k).copied().unwrap_or_default()),
                     nodes: versioned_nodes
                         .iter()
                         .map(|(v, node)| (v.to_introspectable(), visit_node(*k, node)))
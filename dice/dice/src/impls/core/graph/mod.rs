@@ -9,6 +9,7 @@
 
 //! The versioned dice graph of dependencies
 mod dependencies;
+pub(crate) mod eviction;
 pub(crate) mod history;
 #[allow(unused)]
 pub(crate) mod introspection;
@@ -67,7 +67,9 @@ impl VersionedGraphNode {
 #[derive(Allocative, Clone)] // TODO(bobyf) remove need to clone
 pub(crate) struct OccupiedGraphNode {
     key: DiceKey,
-    res: DiceValidValue,
+    /// `None` once the value was dropped to free memory. The node keeps its edges and history, so
+    /// it is still invalidated correctly, and the value is recomputed when next requested.
+    res: Option<DiceValidValue>,
     metadata: NodeMetadata,
 }
 
@@ -88,7 +90,7 @@ impl OccupiedGraphNode {
     ) -> Self {
         Self {
             key,
-            res,
+            res: Some(res),
             metadata: NodeMetadata {
                 hist,
                 deps,
@@ -130,13 +132,27 @@ impl OccupiedGraphNode {
         changed_since
     }
 
-    pub(crate) fn val(&self) -> &DiceValidValue {
-        &self.res
+    /// The value, unless it was dropped.
+    pub(crate) fn val(&self) -> Option<&DiceValidValue> {
+        self.res.as_ref()
     }
 
+    /// Drops the value to free memory, returning the bytes it retained.
+    pub(crate) fn drop_val(&mut self) -> usize {
+        self.res
+            .take()
+            .map_or(0, |res| allocative::size_of_unique_allocated_data(&res))
+    }
+
+    /// Replaces a dropped value with its recomputed one.
+    pub(crate) fn restore_val(&mut self, res: DiceValidValue) {
+        self.res = Some(res);
+    }
+
+    /// Panics if the value was dropped.
     pub(crate) fn computed_val(&self) -> DiceComputedValue {
         DiceComputedValue::new(
-            MaybeValidDiceValue::valid(self.res.dupe()),
+            MaybeValidDiceValue::valid(self.res.dupe().expect("value was dropped")),
             Arc::new(self.metadata.hist.clone()),
         )
     }
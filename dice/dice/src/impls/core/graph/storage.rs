@@ -39,8 +39,10 @@ use crate::impls::core::graph::types::VersionedGraphResultMismatch;
 use crate::impls::key::DiceKey;
 use crate::impls::value::DiceComputedValue;
 use crate::impls::value::DiceValidValue;
+use crate::impls::value::MaybeValidDiceValue;
 use crate::versions::VersionNumber;
 use crate::HashMap;
+use crate::HashSet;
 
 /// The actual incremental cache that checks versions and dependency's versions
 /// to maintain correct caching based on versions and the versions of its
@@ -65,6 +67,30 @@ impl VersionedGraph {
         }
     }
 
+    /// whether any version of the key has a value, i.e. the key was computed or injected and not
+    /// evicted since
+    pub(crate) fn has_value(&self, key: DiceKey) -> bool {
+        self.last_n.get(&key).map_or(false, |versioned| {
+            versioned
+                .values()
+                .any(|node| matches!(node, VersionedGraphNode::Occupied(o) if o.val().is_some()))
+        })
+    }
+
+    /// The value of every node, with its key and the version it was stored at. Values are only
+    /// duped, so that measuring them doesn't hold up the core state.
+    pub(crate) fn values(&self) -> Vec<(DiceKey, VersionNumber, DiceValidValue)> {
+        self.last_n
+            .iter()
+            .flat_map(|(k, versioned)| {
+                versioned.iter().filter_map(move |(v, node)| match node {
+                    VersionedGraphNode::Occupied(o) => o.val().map(|val| (*k, *v, val.dupe())),
+                    VersionedGraphNode::Vacant(_) => None,
+                })
+            })
+            .collect()
+    }
+
     /// gets the cache entry corresponding to the cache entry if up to date.
     /// returns 'None' if entry is missing or versions are out of date.
     pub(crate) fn get(&self, key: VersionedGraphKey) -> VersionedGraphResult {
@@ -72,11 +98,16 @@ impl VersionedGraph {
             key: VersionedGraphKey,
             entry: &OccupiedGraphNode,
         ) -> VersionedGraphResult {
+            let val = match entry.val() {
+                Some(val) => val,
+                // the value was dropped, so it has to be recomputed even if it's up to date
+                None => return VersionedGraphResult::Compute,
+            };
             match entry.metadata().hist.get_history(&key.v) {
                 HistoryState::Verified => VersionedGraphResult::Match(entry.computed_val()),
                 HistoryState::Unknown(verified_versions) => {
                     VersionedGraphResult::CheckDeps(VersionedGraphResultMismatch {
-                        entry: entry.val().dupe(),
+                        entry: val.dupe(),
                         verified_versions,
                         deps_to_validate: entry.metadata().deps.deps(),
                     })
@@ -108,15 +139,15 @@ impl VersionedGraph {
                 // to a different result. TODO add some per ctx result caching for old versions
                 versioned
                     .range((Bound::Included(key.v), Bound::Unbounded))
-                    .find_map(|(v, e)| match e {
-                        VersionedGraphNode::Occupied(e) => Some((v, e)),
+                    .find_map(|(_v, e)| match e {
+                        VersionedGraphNode::Occupied(e) => e.val().map(|val| (e, val)),
                         VersionedGraphNode::Vacant(_) => None,
                     })
                     .map_or_else(
                         || VersionedGraphResult::Compute,
-                        |(_, entry)| {
+                        |(entry, val)| {
                             VersionedGraphResult::CheckDeps(VersionedGraphResultMismatch {
-                                entry: entry.val().dupe(),
+                                entry: val.dupe(),
                                 verified_versions: entry.metadata().hist.get_verified_ranges(),
                                 deps_to_validate: entry.metadata().deps.deps(),
                             })
@@ -161,6 +192,70 @@ impl VersionedGraph {
         }
     }
 
+    /// Like `get_internal`, without needing to modify the graph.
+    fn get_node(&self, key: VersionedGraphKey) -> Option<&VersionedGraphNode> {
+        let versioned = self.last_n.get(&key.k)?;
+        versioned
+            .range((
+                Bound::Included(VersionNumber::new(0)),
+                Bound::Included(key.v),
+            ))
+            .next_back()
+            .or_else(|| {
+                versioned
+                    .range((Bound::Included(key.v), Bound::Unbounded))
+                    .next()
+            })
+            .map(|(_v, e)| e)
+    }
+
+    /// The keys that invalidating the given keys after version `v` would change, and the
+    /// dependents that would be invalidated as a result, without modifying the graph. Each key is
+    /// paired with the value it would be updated to, if any, so that updating a key to an equal
+    /// value changes nothing.
+    pub(crate) fn preview_invalidate<'a>(
+        &self,
+        v: VersionNumber,
+        keys: impl IntoIterator<Item = (DiceKey, Option<&'a DiceValidValue>)>,
+    ) -> (Vec<DiceKey>, HashSet<DiceKey>) {
+        let rdeps_of = |node: Option<&VersionedGraphNode>| {
+            node.and_then(|node| node.unpack_occupied())
+                .map(|node| {
+                    node.metadata()
+                        .rdeps
+                        .rdeps()
+                        .iter()
+                        .map(|(r, v)| (r.dupe(), *v))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut changed = Vec::new();
+        let mut queue = Vec::new();
+        for (key, value) in keys {
+            let node = self.get_node(VersionedGraphKey::new(v, key));
+            if let (Some(value), Some(VersionedGraphNode::Occupied(occ))) = (value, node) {
+                if occ.val().map_or(false, |val| val.equality(value)) {
+                    continue;
+                }
+            }
+            changed.push(key);
+            queue.extend(rdeps_of(node));
+        }
+
+        let mut invalidated = HashSet::default();
+        while let Some((rdep, relevant_version)) = queue.pop() {
+            if invalidated.insert(rdep) {
+                queue.extend(rdeps_of(
+                    self.get_node(VersionedGraphKey::new(relevant_version, rdep)),
+                ));
+            }
+        }
+
+        (changed, invalidated)
+    }
+
     /// updates the cached value based on the given key and versions. The value
     /// is only updated if the version of the new value is of a newer
     /// version than what is stored.
@@ -184,6 +279,24 @@ impl VersionedGraph {
         // most likely to reuse a node. We could implement this to check for reuse against both
         // the previous and the next version, but that complexity is likely not worth the benefit
         // of trying to reuse a node. Maybe this is worth revisiting at some point.
+        // deps can be missing if they were evicted while this key was being computed. The value
+        // is still correct to return, but we can't record its history without the deps, so it is
+        // not stored.
+        if deps.iter().any(|dep| {
+            !matches!(
+                self.get_internal(VersionedGraphKey::new(key.v, dep.dupe())),
+                Some(VersionedGraphNode::Occupied(_))
+            )
+        }) {
+            return (
+                DiceComputedValue::new(
+                    MaybeValidDiceValue::valid(value),
+                    Arc::new(CellHistory::verified(key.v)),
+                ),
+                false,
+            );
+        }
+
         let nearest = {
             let versioned_map = self.last_n.entry(key.k).or_default();
             Self::nearest_entry(&key, versioned_map)
@@ -312,7 +425,21 @@ impl VersionedGraph {
     ) -> (DiceComputedValue, bool) {
         let versioned_map = self.last_n.get_mut(&key.k).unwrap();
         let (ret, map_fixup) = match versioned_map.get_mut(&key_of_e).unwrap() {
-            VersionedGraphNode::Occupied(entry) if value.equality(entry.val()) => {
+            VersionedGraphNode::Occupied(entry)
+                if entry.val().map_or_else(
+                    // a dropped value recomputed at a version it was verified at is equal to it
+                    || {
+                        matches!(
+                            entry.metadata().hist.get_history(&key.v),
+                            HistoryState::Verified
+                        )
+                    },
+                    |val| value.equality(val),
+                ) =>
+            {
+                if entry.val().is_none() {
+                    entry.restore_val(value);
+                }
                 let since =
                     entry.mark_unchanged(key.v, latest_dep_verified, first_dep_dirtied, deps);
 
@@ -354,12 +481,45 @@ impl VersionedGraph {
         (ret, any_invalidated)
     }
 
+    /// Removes the nodes that are superseded by a newer node of the same key at or before
+    /// `oldest_in_use`, since lookups at versions from `oldest_in_use` onwards can't reach them.
+    /// Returns the number of nodes removed.
+    pub(crate) fn collect_superseded(&mut self, oldest_in_use: VersionNumber) -> usize {
+        let mut reclaimed = 0;
+        for versioned_map in self.last_n.values_mut() {
+            let superseded: Vec<_> = versioned_map
+                .range((Bound::Unbounded, Bound::Included(oldest_in_use)))
+                .map(|(v, _)| *v)
+                .collect();
+            // the newest of them is still the one observed at `oldest_in_use`
+            if let Some((_, superseded)) = superseded.split_last() {
+                for v in superseded {
+                    versioned_map.remove(v);
+                }
+                reclaimed += superseded.len();
+            }
+        }
+        reclaimed
+    }
+
     /// Invalidates an entry and its transitive rdeps. Returning true if this caused any type of
     /// change
+    #[cfg(test)]
     pub(crate) fn invalidate(
         &mut self,
         key: VersionedGraphKey,
         invalidate: InvalidateKind,
+    ) -> bool {
+        self.invalidate_recording(key, invalidate, &mut Vec::new())
+    }
+
+    /// Invalidates the key, returning whether anything changed. The dependents that were newly
+    /// invalidated as a result are added to `dirtied`.
+    pub(crate) fn invalidate_recording(
+        &mut self,
+        key: VersionedGraphKey,
+        invalidate: InvalidateKind,
+        dirtied: &mut Vec<DiceKey>,
     ) -> bool {
         let rdeps = {
             match invalidate {
@@ -422,7 +582,7 @@ impl VersionedGraph {
 
                         match entry {
                             Some(VersionedGraphNode::Occupied(occ)) => {
-                                if !occ.val().equality(&value) {
+                                if !occ.val().map_or(false, |val| val.equality(&value)) {
                                     occ.metadata()
                                         .rdeps
                                         .rdeps()
@@ -481,18 +641,53 @@ impl VersionedGraph {
             }
         };
 
-        self.invalidate_rdeps(key.v, rdeps);
+        self.invalidate_rdeps(key.v, rdeps, dirtied);
         true
     }
 
+    /// Drops the values of every version of the key to free memory, keeping the nodes. Returns
+    /// the bytes freed.
+    pub(crate) fn drop_values(&mut self, key: DiceKey) -> usize {
+        self.last_n.get_mut(&key).map_or(0, |versioned_map| {
+            versioned_map
+                .values_mut()
+                .filter_map(|node| match node {
+                    VersionedGraphNode::Occupied(o) => Some(o.drop_val()),
+                    VersionedGraphNode::Vacant(_) => None,
+                })
+                .sum()
+        })
+    }
+
+    /// Removes every version of the key, and of everything that transitively depends on it, from
+    /// the graph. Returns the removed keys.
+    pub(crate) fn evict(&mut self, key: DiceKey) -> Vec<DiceKey> {
+        let mut evicted = Vec::new();
+        let mut queue = vec![key];
+        while let Some(key) = queue.pop() {
+            if let Some(versioned_map) = self.last_n.remove(&key) {
+                for node in versioned_map.values() {
+                    if let Some(node) = node.unpack_occupied() {
+                        queue.extend(node.metadata().rdeps.rdeps().keys().copied());
+                    }
+                }
+                evicted.push(key);
+            }
+        }
+        evicted
+    }
+
     fn invalidate_rdeps(
         &mut self,
         version: VersionNumber,
         mut queue: Vec<(DiceKey, VersionNumber)>,
+        dirtied: &mut Vec<DiceKey>,
     ) {
         while let Some((rdep, relevant_version)) = queue.pop() {
             if let Some(node) = self.get_internal(VersionedGraphKey::new(relevant_version, rdep)) {
                 if node.mark_invalidated(version) {
+                    dirtied.push(rdep);
+
                     // since dirty always occurs in increasing order, it must be the case that if
                     // the history was already dirtied, it was by a version number less than the
                     // current version number.
@@ -949,6 +1144,36 @@ mod tests {
         cache.get(key7.dupe()).assert_compute()
     }
 
+    #[test]
+    fn collect_superseded_keeps_nodes_in_use() {
+        let mut cache = VersionedGraph::new();
+        let key = |v| VersionedGraphKey::new(VersionNumber::new(v), DiceKey { index: 0 });
+
+        for (v, value) in [(0, 100), (1, 200), (5, 300)] {
+            let res = DiceValidValue::testing_new(DiceKeyValue::<K>::new(value));
+            cache.invalidate(key(v), InvalidateKind::Invalidate);
+            cache.update(
+                key(v),
+                res,
+                Arc::new(vec![]),
+                StorageType::LastN(usize::MAX),
+            );
+        }
+        assert_eq!(3, cache.last_n[&DiceKey { index: 0 }].len());
+
+        // the node at 1 is still observed at 4
+        assert_eq!(1, cache.collect_superseded(VersionNumber::new(4)));
+        let res2 = DiceValidValue::testing_new(DiceKeyValue::<K>::new(200));
+        assert!(cache.get(key(4)).assert_match().value().equality(&res2));
+
+        assert_eq!(0, cache.collect_superseded(VersionNumber::new(4)));
+
+        assert_eq!(1, cache.collect_superseded(VersionNumber::new(6)));
+        let res3 = DiceValidValue::testing_new(DiceKeyValue::<K>::new(300));
+        assert!(cache.get(key(6)).assert_match().value().equality(&res3));
+        assert_eq!(1, cache.last_n[&DiceKey { index: 0 }].len());
+    }
+
     #[tokio::test]
     async fn last_2_stores_last_2() {
         let mut cache = VersionedGraph::new();
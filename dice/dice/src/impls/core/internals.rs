@@ -7,15 +7,25 @@
  * of this source tree.
  */
 
+use std::time::Duration;
+
 use gazebo::prelude::SliceExt;
+use tokio::time::Instant;
 
+use crate::api::cutoff::DiceCutoff;
+use crate::api::eviction::DiceEvictionPolicy;
+use crate::api::gc::DiceGcPolicy;
 use crate::api::storage_type::StorageType;
 use crate::arc::Arc;
 use crate::impls::cache::SharedCache;
+use crate::impls::core::changes::ChangeLog;
+use crate::impls::core::graph::eviction::EvictionTracker;
 use crate::impls::core::graph::storage::InvalidateKind;
 use crate::impls::core::graph::storage::VersionedGraph;
 use crate::impls::core::graph::types::VersionedGraphKey;
 use crate::impls::core::graph::types::VersionedGraphResult;
+use crate::impls::core::recompute::RecomputeCause;
+use crate::impls::core::recompute::RecomputeLog;
 use crate::impls::core::versions::VersionEpoch;
 use crate::impls::core::versions::VersionTracker;
 use crate::impls::key::DiceKey;
@@ -27,25 +37,51 @@ use crate::impls::value::DiceValidValue;
 use crate::introspection::graph::AnyKey;
 use crate::introspection::graph::GraphIntrospectable;
 use crate::introspection::graph::ModernIntrospectable;
+use crate::introspection::recompute::RecomputeReason;
 use crate::metrics::Metrics;
 use crate::result::CancellableResult;
 use crate::result::Cancelled;
 use crate::versions::VersionNumber;
 use crate::HashMap;
+use crate::HashSet;
 
 /// Core state of DICE, holding the actual graph and version information
 pub(super) struct CoreState {
     version_tracker: VersionTracker,
     graph: VersionedGraph,
+    eviction: EvictionTracker,
+    recomputes: RecomputeLog,
+    changes: ChangeLog,
     pending_termination_tasks: Vec<DiceTask>,
+    /// The keys with a cutoff other than `DiceCutoff::Equality`
+    cutoffs: HashMap<DiceKey, DiceCutoff>,
+    gc_policy: DiceGcPolicy,
+    gc_count: u64,
+    gc_reclaimed_node_count: u64,
 }
 
 impl CoreState {
+    #[cfg(test)]
     pub(super) fn new() -> Self {
+        Self::with_policies(DiceEvictionPolicy::KeepAll, DiceGcPolicy::Manual, false)
+    }
+
+    pub(super) fn with_policies(
+        eviction_policy: DiceEvictionPolicy,
+        gc_policy: DiceGcPolicy,
+        record_recomputes: bool,
+    ) -> Self {
         Self {
             version_tracker: VersionTracker::new(),
             graph: VersionedGraph::new(),
+            eviction: EvictionTracker::new(eviction_policy),
+            recomputes: RecomputeLog::new(record_recomputes),
+            changes: ChangeLog::default(),
             pending_termination_tasks: Vec::new(),
+            cutoffs: HashMap::default(),
+            gc_policy,
+            gc_count: 0,
+            gc_reclaimed_node_count: 0,
         }
     }
 
@@ -56,9 +92,14 @@ impl CoreState {
         let version_update = self.version_tracker.write();
         let v = version_update.version();
 
-        let mut changes_recorded = false;
+        let mut changed = Vec::new();
+        let mut invalidated = Vec::new();
         for (key, change) in updates {
-            changes_recorded |= self.graph.invalidate(
+            if let ChangeType::UpdateValue(..) = change {
+                // injected values can't be recomputed
+                self.eviction.pin(key);
+            }
+            if self.graph.invalidate_recording(
                 VersionedGraphKey::new(v, key),
                 match change {
                     ChangeType::Invalidate => InvalidateKind::ForceDirty,
@@ -66,15 +107,61 @@ impl CoreState {
                     #[cfg(test)]
                     ChangeType::TestingSoftDirty => InvalidateKind::Invalidate,
                 },
-            );
+                &mut invalidated,
+            ) {
+                changed.push(key);
+            }
         }
-        if changes_recorded {
-            version_update.commit()
+        if !changed.is_empty() {
+            for (key, cutoff) in &self.cutoffs {
+                if *cutoff == DiceCutoff::AlwaysInvalidate
+                    && self.graph.invalidate_recording(
+                        VersionedGraphKey::new(v, *key),
+                        InvalidateKind::ForceDirty,
+                        &mut invalidated,
+                    )
+                {
+                    invalidated.push(*key);
+                }
+            }
+            self.changes.record(v, changed, invalidated);
+            let v = version_update.commit();
+            if self.gc_policy == DiceGcPolicy::OnCommit {
+                self.gc();
+            }
+            v
         } else {
             version_update.undo()
         }
     }
 
+    /// The keys that committing the updates would change and invalidate, without committing them
+    /// or modifying the graph.
+    pub(super) fn preview_update(
+        &self,
+        updates: &[(DiceKey, Option<DiceValidValue>)],
+    ) -> (HashSet<DiceKey>, HashSet<DiceKey>) {
+        let v = self.version_tracker.current();
+        let (changed, mut invalidated) = self
+            .graph
+            .preview_invalidate(v, updates.iter().map(|(k, value)| (*k, value.as_ref())));
+        if !changed.is_empty() {
+            let (always_invalidated, dependents) = self.graph.preview_invalidate(
+                v,
+                self.cutoffs
+                    .iter()
+                    .filter(|(_, cutoff)| **cutoff == DiceCutoff::AlwaysInvalidate)
+                    .map(|(k, _)| (*k, None)),
+            );
+            invalidated.extend(always_invalidated);
+            invalidated.extend(dependents);
+        }
+
+        let changed: HashSet<_> = changed.into_iter().collect();
+        invalidated.retain(|k| !changed.contains(k));
+        (changed, invalidated)
+    }
+
     pub(super) fn ctx_at_version(&mut self, v: VersionNumber) -> (VersionEpoch, SharedCache) {
         self.version_tracker.at(v)
     }
@@ -89,11 +176,64 @@ impl CoreState {
                 .retain(|task| task.is_pending());
             self.pending_termination_tasks
                 .extend(evicted_cache.cancel_pending_tasks());
+
+            if self.gc_policy == DiceGcPolicy::OnIdle && self.version_tracker.is_idle() {
+                self.gc();
+            }
         }
     }
 
+    /// Collects the graph nodes superseded before the oldest version that is still in use,
+    /// returning the number of nodes reclaimed.
+    pub(super) fn gc(&mut self) -> usize {
+        let reclaimed = self
+            .graph
+            .collect_superseded(self.version_tracker.oldest_in_use());
+
+        debug!(
+            msg = "collected superseded graph nodes",
+            reclaimed = reclaimed
+        );
+
+        self.gc_count += 1;
+        self.gc_reclaimed_node_count += reclaimed as u64;
+        reclaimed
+    }
+
     pub(super) fn lookup_key(&mut self, key: VersionedGraphKey) -> VersionedGraphResult {
-        self.graph.get(key)
+        let res = self.graph.get(key);
+        match &res {
+            VersionedGraphResult::Match(_) => self.eviction.touch(key.k, None, None),
+            VersionedGraphResult::Compute => self.recomputes.record(
+                key,
+                if self.graph.has_value(key.k) {
+                    RecomputeCause::Invalidated
+                } else {
+                    RecomputeCause::NotComputed
+                },
+            ),
+            VersionedGraphResult::CheckDeps(_) => {}
+        }
+        res
+    }
+
+    pub(super) fn record_recompute(&mut self, key: VersionedGraphKey, cause: RecomputeCause) {
+        self.recomputes.record(key, cause)
+    }
+
+    pub(super) fn why_recomputed(
+        &self,
+        key: VersionedGraphKey,
+    ) -> Option<Vec<(DiceKey, RecomputeReason)>> {
+        self.recomputes.chain(key)
+    }
+
+    pub(super) fn changes_between(
+        &self,
+        from: VersionNumber,
+        to: VersionNumber,
+    ) -> Option<(HashSet<DiceKey>, HashSet<DiceKey>)> {
+        self.changes.between(from, to)
     }
 
     pub(super) fn update_computed(
@@ -101,13 +241,22 @@ impl CoreState {
         key: VersionedGraphKey,
         epoch: VersionEpoch,
         storage: StorageType,
+        cutoff: DiceCutoff,
         value: DiceValidValue,
         deps: Arc<Vec<DiceKey>>,
+        compute_duration: Option<Duration>,
     ) -> CancellableResult<DiceComputedValue> {
         if self.version_tracker.is_relevant(key.v, epoch) {
             debug!(msg = "update graph entry", k = ?key.k, v = %key.v, v_epoch = %epoch);
 
-            Ok(self.graph.update(key, value, deps, storage).0)
+            self.eviction.touch(key.k, compute_duration, Some(&value));
+            if cutoff != DiceCutoff::Equality {
+                self.cutoffs.insert(key.k, cutoff);
+            }
+            let res = self.graph.update(key, value, deps, storage).0;
+            self.evict_over_budget();
+
+            Ok(res)
         } else {
             debug!(msg = "update is rejected due to outdated epoch", k = ?key.k, v = %key.v, v_epoch = %epoch);
 
@@ -115,6 +264,26 @@ impl CoreState {
         }
     }
 
+    /// Drops the values of the keys to free memory, keeping their nodes. Returns the bytes freed.
+    pub(super) fn drop_values(&mut self, keys: &[DiceKey]) -> usize {
+        keys.iter()
+            .map(|key| {
+                self.eviction.value_dropped(*key);
+                self.graph.drop_values(*key)
+            })
+            .sum()
+    }
+
+    fn evict_over_budget(&mut self) {
+        while let Some(key) = self.eviction.next_to_evict() {
+            for evicted in self.graph.evict(key) {
+                self.eviction.remove(evicted);
+                self.recomputes.remove(evicted);
+                self.cutoffs.remove(&evicted);
+            }
+        }
+    }
+
     pub(super) fn get_tasks_pending_cancellation(&mut self) -> Vec<TerminationObserver> {
         self.pending_termination_tasks
             .retain(|task| task.is_pending());
@@ -123,9 +292,28 @@ impl CoreState {
             .map(|task| task.await_termination())
     }
 
+    pub(super) fn cancelled_tasks(&self) -> Vec<(DiceKey, Instant)> {
+        self.pending_termination_tasks
+            .iter()
+            .filter_map(|task| {
+                task.cancelled_while_running()
+                    .map(|at| (task.internal.key, at))
+            })
+            .chain(
+                self.version_tracker
+                    .currently_active()
+                    .flat_map(|(_, cache)| cache.cancelled_tasks()),
+            )
+            .collect()
+    }
+
     pub(super) fn unstable_drop_everything(&mut self) {
         self.version_tracker.write().commit();
         self.graph.last_n.clear();
+        self.eviction.clear();
+        self.recomputes.clear();
+        self.changes.clear();
+        self.cutoffs.clear();
     }
 
     pub(super) fn metrics(&self) -> Metrics {
@@ -142,11 +330,24 @@ impl CoreState {
             key_count: self.graph.last_n.len(),
             currently_active_key_count: currently_running_key_count,
             active_transaction_count: active_transaction_count as u32, // probably won't support more than u32 transactions
+            // computations are counted outside of the core state
+            compute_count: 0,
+            transient_count: 0,
+            transient_retry_count: 0,
+            key_types: Default::default(),
+            gc_count: self.gc_count,
+            gc_reclaimed_node_count: self.gc_reclaimed_node_count,
+            key_index: Default::default(),
+            queue_delay_by_priority: Default::default(),
         }
     }
 
+    pub(super) fn values(&self) -> Vec<(DiceKey, VersionNumber, DiceValidValue)> {
+        self.graph.values()
+    }
+
     pub(super) fn introspection(&self, key_map: HashMap<DiceKey, AnyKey>) -> GraphIntrospectable {
-        let graph = self.graph.introspect(key_map.clone());
+        let graph = self.graph.introspect(key_map.clone(), &self.cutoffs);
         let version_data = self.version_tracker.introspect();
 
         GraphIntrospectable::Modern {
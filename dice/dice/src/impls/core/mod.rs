@@ -7,8 +7,10 @@
  * of this source tree.
  */
 
+mod changes;
 pub(crate) mod graph;
 mod internals;
 mod processor;
+pub(crate) mod recompute;
 pub(crate) mod state;
 pub(crate) mod versions;
@@ -9,22 +9,42 @@
 
 use gazebo::variants::VariantName;
 
+use crate::api::eviction::DiceEvictionPolicy;
+use crate::api::gc::DiceGcPolicy;
 use crate::impls::core::internals::CoreState;
 use crate::impls::core::state::CoreStateHandle;
 use crate::impls::core::state::StateRequest;
+use crate::impls::core::state::StateRequestQueue;
 use crate::impls::ctx::SharedLiveTransactionCtx;
 
 pub(super) struct StateProcessor {
     state: CoreState,
     rx: tokio::sync::mpsc::UnboundedReceiver<StateRequest>,
+    queue: StateRequestQueue,
 }
 
 impl StateProcessor {
-    pub(super) fn spawn() -> CoreStateHandle {
+    /// Spawns the processor on a dedicated thread, or as a task of the current tokio runtime if
+    /// `on_current_runtime`, so that it is scheduled deterministically with the computations on a
+    /// single threaded runtime.
+    pub(super) fn spawn(
+        eviction_policy: DiceEvictionPolicy,
+        gc_policy: DiceGcPolicy,
+        record_recomputes: bool,
+        on_current_runtime: bool,
+    ) -> CoreStateHandle {
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-        let state = CoreState::new();
+        let processor = StateProcessor {
+            state: CoreState::with_policies(eviction_policy, gc_policy, record_recomputes),
+            rx,
+            queue: StateRequestQueue::default(),
+        };
 
-        std::thread::spawn(move || StateProcessor { state, rx }.event_loop());
+        if on_current_runtime {
+            tokio::spawn(processor.event_loop_on_runtime());
+        } else {
+            std::thread::spawn(move || processor.event_loop());
+        }
         CoreStateHandle::new(tx)
     }
 
@@ -32,17 +52,42 @@ impl StateProcessor {
         loop {
             // Skip tokio scheduling.
             while let Ok(message) = self.rx.try_recv() {
-                self.iteration(message);
+                self.queue.push(message);
+            }
+            if self.queue.is_empty() {
+                if let Some(message) = self.rx.blocking_recv() {
+                    self.queue.push(message);
+                } else {
+                    break;
+                }
             }
-            if let Some(message) = self.rx.blocking_recv() {
-                self.iteration(message);
-            } else {
-                break;
+
+            self.handle_queued();
+        }
+        debug!("Processor terminated");
+    }
+
+    async fn event_loop_on_runtime(mut self) {
+        while let Some(message) = self.rx.recv().await {
+            self.queue.push(message);
+            while let Ok(message) = self.rx.try_recv() {
+                self.queue.push(message);
             }
+
+            self.handle_queued();
         }
         debug!("Processor terminated");
     }
 
+    /// Handles everything that arrived together in order of priority
+    fn handle_queued(&mut self) {
+        let mut queue = std::mem::take(&mut self.queue);
+        for message in queue.drain() {
+            self.iteration(message);
+        }
+        self.queue = queue;
+    }
+
     #[instrument(skip_all, fields(kind = %message.variant_name()))]
     fn iteration(&mut self, message: StateRequest) {
         match message {
@@ -50,6 +95,12 @@ impl StateProcessor {
                 // ignore error if the requester dropped it.
                 let _ = resp.send(self.state.update_state(changes));
             }
+            StateRequest::PreviewUpdate { changes, resp } => {
+                let _ignored = resp.send(self.state.preview_update(&changes));
+            }
+            StateRequest::DropValues { keys, resp } => {
+                let _ignored = resp.send(self.state.drop_values(&keys));
+            }
             StateRequest::CtxAtVersion {
                 version,
                 guard,
@@ -65,26 +116,54 @@ impl StateProcessor {
                 // ignore error if the requester dropped it.
                 let _ = resp.send(self.state.current_version());
             }
-            StateRequest::LookupKey { key, resp } => drop(resp.send(self.state.lookup_key(key))),
+            StateRequest::LookupKey { key, resp, .. } => {
+                drop(resp.send(self.state.lookup_key(key)))
+            }
+            StateRequest::RecordRecompute { key, cause } => self.state.record_recompute(key, cause),
+            StateRequest::WhyRecomputed { key, resp } => {
+                let _ignored = resp.send(self.state.why_recomputed(key));
+            }
+            StateRequest::ChangesBetween { from, to, resp } => {
+                let _ignored = resp.send(self.state.changes_between(from, to));
+            }
             StateRequest::UpdateComputed {
                 key,
                 epoch,
                 storage,
+                cutoff,
                 value,
                 deps,
+                compute_duration,
                 resp,
                 ..
             } => {
                 // ignore error if the requester dropped it.
-                drop(resp.send(self.state.update_computed(key, epoch, storage, value, deps)));
+                drop(resp.send(self.state.update_computed(
+                    key,
+                    epoch,
+                    storage,
+                    cutoff,
+                    value,
+                    deps,
+                    compute_duration,
+                )));
             }
             StateRequest::GetTasksPendingCancellation { resp } => {
                 let _ignored = resp.send(self.state.get_tasks_pending_cancellation());
             }
+            StateRequest::CancelledTasks { resp } => {
+                let _ignored = resp.send(self.state.cancelled_tasks());
+            }
+            StateRequest::Gc { resp } => {
+                let _ignored = resp.send(self.state.gc());
+            }
             StateRequest::UnstableDropEverything => self.state.unstable_drop_everything(),
             StateRequest::Metrics { resp } => {
                 let _ignored = resp.send(self.state.metrics());
             }
+            StateRequest::Values { resp } => {
+                let _ignored = resp.send(self.state.values());
+            }
             StateRequest::Introspection { resp, key_map } => {
                 let _ignored = resp.send(self.state.introspection(key_map));
             }
@@ -0,0 +1,188 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Records why keys were recomputed
+
+use allocative::Allocative;
+use dupe::Dupe;
+
+use crate::impls::core::graph::types::VersionedGraphKey;
+use crate::impls::key::DiceKey;
+use crate::introspection::recompute::RecomputeReason;
+use crate::versions::VersionNumber;
+use crate::HashMap;
+use crate::HashSet;
+
+/// Only the most recent recomputations of each key are kept
+const RECORDS_PER_KEY: usize = 4;
+
+#[derive(Allocative, Clone, Copy, Dupe, Debug, PartialEq, Eq)]
+pub(crate) enum RecomputeCause {
+    NotComputed,
+    Invalidated,
+    DepChanged(DiceKey),
+}
+
+/// Disabled unless requested with `DiceDataBuilder::set_record_recomputes`, since it holds records
+/// for every key that was ever recomputed.
+#[derive(Allocative)]
+pub(crate) struct RecomputeLog {
+    enabled: bool,
+    /// Sorted by version
+    records: HashMap<DiceKey, Vec<(VersionNumber, RecomputeCause)>>,
+}
+
+impl RecomputeLog {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            records: HashMap::default(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, key: VersionedGraphKey, cause: RecomputeCause) {
+        if !self.enabled {
+            return;
+        }
+        let records = self.records.entry(key.k).or_default();
+        let pos = records.partition_point(|(v, _)| *v <= key.v);
+        if pos > 0 && records[pos - 1].0 == key.v {
+            records[pos - 1].1 = cause;
+        } else {
+            records.insert(pos, (key.v, cause));
+        }
+        if records.len() > RECORDS_PER_KEY {
+            records.remove(0);
+        }
+    }
+
+    /// The chain of recomputations leading to the key being recomputed at exactly its version.
+    pub(crate) fn chain(&self, key: VersionedGraphKey) -> Option<Vec<(DiceKey, RecomputeReason)>> {
+        let mut cause = self
+            .records
+            .get(&key.k)?
+            .iter()
+            .find(|(v, _)| *v == key.v)?
+            .1;
+
+        let mut chain = Vec::new();
+        let mut seen = HashSet::default();
+        let mut current = key.k;
+        loop {
+            seen.insert(current);
+            match cause {
+                RecomputeCause::NotComputed => {
+                    chain.push((current, RecomputeReason::NotComputed));
+                    break;
+                }
+                RecomputeCause::Invalidated => {
+                    chain.push((current, RecomputeReason::Invalidated));
+                    break;
+                }
+                RecomputeCause::DepChanged(dep) => {
+                    chain.push((current, RecomputeReason::DepChanged));
+                    if !seen.insert(dep) {
+                        break;
+                    }
+
+                    // the dep changed at or before the version, so find its latest recomputation
+                    // up to the version
+                    match self.records.get(&dep).and_then(|records| {
+                        records.iter().rev().find(|(v, _)| *v <= key.v).map(|r| r.1)
+                    }) {
+                        Some(dep_cause) => {
+                            current = dep;
+                            cause = dep_cause;
+                        }
+                        None => {
+                            chain.push((dep, RecomputeReason::Changed));
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        Some(chain)
+    }
+
+    /// Forgets the key, once it has been evicted from the graph.
+    pub(crate) fn remove(&mut self, key: DiceKey) {
+        self.records.remove(&key);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::impls::core::graph::types::VersionedGraphKey;
+    use crate::impls::core::recompute::RecomputeCause;
+    use crate::impls::core::recompute::RecomputeLog;
+    use crate::impls::key::DiceKey;
+    use crate::introspection::recompute::RecomputeReason;
+    use crate::versions::VersionNumber;
+
+    fn key(index: u32, v: usize) -> VersionedGraphKey {
+        VersionedGraphKey::new(VersionNumber::new(v), DiceKey { index })
+    }
+
+    #[test]
+    fn chain_follows_changed_deps() {
+        let mut log = RecomputeLog::new(true);
+        log.record(key(1, 1), RecomputeCause::Invalidated);
+        log.record(key(2, 2), RecomputeCause::DepChanged(DiceKey { index: 1 }));
+        log.record(key(3, 3), RecomputeCause::DepChanged(DiceKey { index: 2 }));
+        log.record(key(4, 3), RecomputeCause::DepChanged(DiceKey { index: 5 }));
+
+        assert_eq!(
+            Some(vec![
+                (DiceKey { index: 3 }, RecomputeReason::DepChanged),
+                (DiceKey { index: 2 }, RecomputeReason::DepChanged),
+                (DiceKey { index: 1 }, RecomputeReason::Invalidated),
+            ]),
+            log.chain(key(3, 3))
+        );
+        assert_eq!(
+            Some(vec![
+                (DiceKey { index: 4 }, RecomputeReason::DepChanged),
+                (DiceKey { index: 5 }, RecomputeReason::Changed),
+            ]),
+            log.chain(key(4, 3))
+        );
+        // not recomputed at that version
+        assert_eq!(None, log.chain(key(3, 2)));
+    }
+
+    #[test]
+    fn only_recent_records_are_kept() {
+        let mut log = RecomputeLog::new(true);
+        for v in 0..10 {
+            log.record(key(1, v), RecomputeCause::NotComputed);
+        }
+        assert_eq!(None, log.chain(key(1, 5)));
+        assert_eq!(
+            Some(vec![(DiceKey { index: 1 }, RecomputeReason::NotComputed)]),
+            log.chain(key(1, 6))
+        );
+    }
+
+    #[test]
+    fn evicted_and_disabled_records_are_not_kept() {
+        let mut log = RecomputeLog::new(true);
+        log.record(key(1, 1), RecomputeCause::NotComputed);
+        log.remove(DiceKey { index: 1 });
+        assert_eq!(None, log.chain(key(1, 1)));
+
+        let mut log = RecomputeLog::new(false);
+        log.record(key(1, 1), RecomputeCause::NotComputed);
+        assert_eq!(None, log.chain(key(1, 1)));
+    }
+}
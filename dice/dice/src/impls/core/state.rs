@@ -7,17 +7,25 @@
  * of this source tree.
  */
 
+use std::time::Duration;
+
 use allocative::Allocative;
 use derivative::Derivative;
 use dupe::Dupe;
 use gazebo::variants::VariantName;
 use tokio::sync::oneshot::Sender;
+use tokio::time::Instant;
 
+use crate::api::cutoff::DiceCutoff;
+use crate::api::eviction::DiceEvictionPolicy;
+use crate::api::gc::DiceGcPolicy;
+use crate::api::priority::DicePriority;
 use crate::api::storage_type::StorageType;
 use crate::arc::Arc;
 use crate::impls::core::graph::types::VersionedGraphKey;
 use crate::impls::core::graph::types::VersionedGraphResult;
 use crate::impls::core::processor::StateProcessor;
+use crate::impls::core::recompute::RecomputeCause;
 use crate::impls::core::versions::VersionEpoch;
 use crate::impls::ctx::SharedLiveTransactionCtx;
 use crate::impls::key::DiceKey;
@@ -28,10 +36,12 @@ use crate::impls::value::DiceComputedValue;
 use crate::impls::value::DiceValidValue;
 use crate::introspection::graph::AnyKey;
 use crate::introspection::graph::GraphIntrospectable;
+use crate::introspection::recompute::RecomputeReason;
 use crate::metrics::Metrics;
 use crate::result::CancellableResult;
 use crate::versions::VersionNumber;
 use crate::HashMap;
+use crate::HashSet;
 
 /// Core state is accessed via message passing to a single threaded processor
 #[derive(Derivative, VariantName)]
@@ -43,6 +53,18 @@ pub(crate) enum StateRequest {
         changes: Vec<(DiceKey, ChangeType)>,
         resp: Sender<VersionNumber>,
     },
+    /// Get the keys that the changes would change and invalidate if they were committed, where
+    /// each key is paired with the value it would be updated to, if any
+    PreviewUpdate {
+        changes: Vec<(DiceKey, Option<DiceValidValue>)>,
+        resp: Sender<(HashSet<DiceKey>, HashSet<DiceKey>)>,
+    },
+    /// Drops the values of the keys to free memory, keeping their nodes, and responds with the
+    /// bytes freed
+    DropValues {
+        keys: Vec<DiceKey>,
+        resp: Sender<usize>,
+    },
     /// Gets the current version number
     CurrentVersion { resp: Sender<VersionNumber> },
     /// Obtains the shared state ctx at the given version
@@ -56,18 +78,40 @@ pub(crate) enum StateRequest {
     /// Lookup the state of a key
     LookupKey {
         key: VersionedGraphKey,
+        /// The priority of the transaction the lookup is for
+        priority: DicePriority,
         resp: Sender<VersionedGraphResult>,
     },
+    /// Record why a key is about to be recomputed
+    RecordRecompute {
+        key: VersionedGraphKey,
+        cause: RecomputeCause,
+    },
+    /// Get the chain of changes that caused the key to be recomputed at its version
+    WhyRecomputed {
+        key: VersionedGraphKey,
+        resp: Sender<Option<Vec<(DiceKey, RecomputeReason)>>>,
+    },
+    /// Get the keys changed and invalidated by the versions after `from` up to `to`
+    ChangesBetween {
+        from: VersionNumber,
+        to: VersionNumber,
+        resp: Sender<Option<(HashSet<DiceKey>, HashSet<DiceKey>)>>,
+    },
     /// Report that a value has been computed
     UpdateComputed {
         key: VersionedGraphKey,
         epoch: VersionEpoch,
         /// The storage selection for the key,
         storage: StorageType,
+        /// How recomputing the key affects its dependents
+        cutoff: DiceCutoff,
         /// The newly computed value
         value: DiceValidValue,
         /// The deps accessed during the computation of newly computed value
         deps: Arc<Vec<DiceKey>>,
+        /// How long the value took to compute, if it was computed rather than reused
+        compute_duration: Option<Duration>,
         /// Response of the new value to use. This could be a different instance that is `Eq` to the
         /// given computed value if the state already stores an instance of value that is equal.
         resp: Sender<CancellableResult<DiceComputedValue>>,
@@ -77,10 +121,20 @@ pub(crate) enum StateRequest {
         #[derivative(Debug = "ignore")]
         resp: Sender<Vec<TerminationObserver>>,
     },
+    /// Get the tasks that were cancelled but are still running, with when they were cancelled
+    CancelledTasks {
+        resp: Sender<Vec<(DiceKey, Instant)>>,
+    },
+    /// Collect the graph nodes of superseded versions, responding with the number reclaimed
+    Gc { resp: Sender<usize> },
     /// For unstable take
     UnstableDropEverything,
     /// Collect metrics
     Metrics { resp: Sender<Metrics> },
+    /// Collect the values of the graph, by key and version, to be measured off the core state
+    Values {
+        resp: Sender<Vec<(DiceKey, VersionNumber, DiceValidValue)>>,
+    },
     /// Collects the introspectable dice state
     Introspection {
         resp: Sender<GraphIntrospectable>,
@@ -97,6 +151,52 @@ pub(crate) struct CoreStateHandle {
     // should this handle hold onto the thread and terminate it when all of Dice is dropped?
 }
 
+/// The requests waiting to be handled by the processor, ordered by the priority of lookups
+#[derive(Default)]
+pub(crate) struct StateRequestQueue {
+    interactive: Vec<StateRequest>,
+    ordered: Vec<StateRequest>,
+    background: Vec<StateRequest>,
+    /// The keys of the `UpdateComputed` requests in `ordered`
+    updated: HashSet<DiceKey>,
+}
+
+impl StateRequestQueue {
+    /// Lookups only read the state at an already committed version, so they can mostly be handled
+    /// before other requests that were sent earlier. The exception is an `UpdateComputed` of the
+    /// same key: a lookup handled before it would miss the value being stored, and compute the key
+    /// again. Everything else is handled in the order it was sent.
+    pub(crate) fn push(&mut self, request: StateRequest) {
+        match &request {
+            StateRequest::LookupKey { key, priority, .. } => match priority {
+                DicePriority::Interactive if !self.updated.contains(&key.k) => {
+                    self.interactive.push(request)
+                }
+                DicePriority::Background => self.background.push(request),
+                _ => self.ordered.push(request),
+            },
+            StateRequest::UpdateComputed { key, .. } => {
+                self.updated.insert(key.k);
+                self.ordered.push(request)
+            }
+            _ => self.ordered.push(request),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.interactive.is_empty() && self.ordered.is_empty() && self.background.is_empty()
+    }
+
+    /// Takes every queued request, interactive lookups first and background lookups last
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = StateRequest> + '_ {
+        self.updated.clear();
+        self.interactive
+            .drain(..)
+            .chain(self.ordered.drain(..))
+            .chain(self.background.drain(..))
+    }
+}
+
 impl CoreStateHandle {
     pub(crate) fn new(tx: tokio::sync::mpsc::UnboundedSender<StateRequest>) -> Self {
         Self { tx }
@@ -105,11 +205,142 @@ impl CoreStateHandle {
     pub(crate) fn request(&self, message: StateRequest) {
         self.tx.send(message).expect("dice runner died");
     }
+
+    /// Collects the graph nodes of superseded versions now, regardless of the `DiceGcPolicy`.
+    /// Returns the number of nodes reclaimed.
+    pub(crate) async fn gc(&self) -> usize {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.request(StateRequest::Gc { resp: tx });
+        rx.await.unwrap()
+    }
 }
 
 impl Dupe for CoreStateHandle {}
 
 /// Start processing state
-pub(crate) fn init_state() -> CoreStateHandle {
-    StateProcessor::spawn()
+pub(crate) fn init_state(
+    eviction_policy: DiceEvictionPolicy,
+    gc_policy: DiceGcPolicy,
+    record_recomputes: bool,
+    on_current_runtime: bool,
+) -> CoreStateHandle {
+    StateProcessor::spawn(
+        eviction_policy,
+        gc_policy,
+        record_recomputes,
+        on_current_runtime,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use allocative::Allocative;
+    use async_trait::async_trait;
+    use derive_more::Display;
+    use dupe::Dupe;
+    use more_futures::cancellation::CancellationContext;
+
+    use crate::api::computations::DiceComputations;
+    use crate::api::cutoff::DiceCutoff;
+    use crate::api::key::Key;
+    use crate::api::priority::DicePriority;
+    use crate::api::storage_type::StorageType;
+    use crate::arc::Arc;
+    use crate::impls::core::graph::types::VersionedGraphKey;
+    use crate::impls::core::state::StateRequest;
+    use crate::impls::core::state::StateRequestQueue;
+    use crate::impls::core::versions::VersionEpoch;
+    use crate::impls::key::DiceKey;
+    use crate::impls::value::DiceKeyValue;
+    use crate::impls::value::DiceValidValue;
+    use crate::versions::VersionNumber;
+
+    #[derive(Allocative, Clone, Dupe, Debug, Display, PartialEq, Eq, Hash)]
+    struct K;
+
+    #[async_trait]
+    impl Key for K {
+        type Value = usize;
+
+        async fn compute(
+            &self,
+            _ctx: &DiceComputations,
+            _cancellations: &CancellationContext,
+        ) -> Self::Value {
+            unimplemented!("test")
+        }
+
+        fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+            x == y
+        }
+    }
+
+    fn lookup(index: u32, priority: DicePriority) -> StateRequest {
+        StateRequest::LookupKey {
+            key: VersionedGraphKey::new(VersionNumber::new(0), DiceKey { index }),
+            priority,
+            resp: tokio::sync::oneshot::channel().0,
+        }
+    }
+
+    fn update(index: u32) -> StateRequest {
+        StateRequest::UpdateComputed {
+            key: VersionedGraphKey::new(VersionNumber::new(0), DiceKey { index }),
+            epoch: VersionEpoch::testing_new(0),
+            storage: StorageType::LastN(1),
+            cutoff: DiceCutoff::Equality,
+            value: DiceValidValue::testing_new(DiceKeyValue::<K>::new(index as usize)),
+            deps: Arc::new(Vec::new()),
+            compute_duration: None,
+            resp: tokio::sync::oneshot::channel().0,
+        }
+    }
+
+    fn describe(request: &StateRequest) -> String {
+        match request {
+            StateRequest::LookupKey { key, .. } => format!("lookup {}", key.k.index),
+            StateRequest::DropCtxAtVersion { version } => format!("drop {}", version),
+            StateRequest::UpdateComputed { key, .. } => format!("update {}", key.k.index),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn lookups_are_ordered_by_priority() {
+        let mut queue = StateRequestQueue::default();
+        queue.push(lookup(0, DicePriority::Background));
+        queue.push(lookup(1, DicePriority::Normal));
+        queue.push(StateRequest::DropCtxAtVersion {
+            version: VersionNumber::new(1),
+        });
+        queue.push(lookup(2, DicePriority::Interactive));
+        queue.push(lookup(3, DicePriority::Normal));
+        assert!(!queue.is_empty());
+
+        assert_eq!(
+            vec!["lookup 2", "lookup 1", "drop v1", "lookup 3", "lookup 0"],
+            queue.drain().map(|r| describe(&r)).collect::<Vec<_>>()
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn lookups_stay_behind_updates_of_their_key() {
+        let mut queue = StateRequestQueue::default();
+        queue.push(update(0));
+        queue.push(lookup(0, DicePriority::Interactive));
+        queue.push(lookup(1, DicePriority::Interactive));
+        assert_eq!(
+            vec!["lookup 1", "update 0", "lookup 0"],
+            queue.drain().map(|r| describe(&r)).collect::<Vec<_>>()
+        );
+
+        // the update was handled, so the next lookups can go first again
+        queue.push(lookup(2, DicePriority::Normal));
+        queue.push(lookup(0, DicePriority::Interactive));
+        assert_eq!(
+            vec!["lookup 0", "lookup 2"],
+            queue.drain().map(|r| describe(&r)).collect::<Vec<_>>()
+        );
+    }
 }
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::cmp;
+
 use allocative::Allocative;
 use derivative::Derivative;
 use derive_more::Display;
@@ -79,6 +81,19 @@ impl VersionTracker {
             .map(|data| (data.ref_count, &data.per_transaction_data))
     }
 
+    /// true when no transaction is holding any version
+    pub(crate) fn is_idle(&self) -> bool {
+        self.active_versions.is_empty()
+    }
+
+    /// The oldest version that a live or future transaction could observe
+    pub(crate) fn oldest_in_use(&self) -> VersionNumber {
+        self.active_versions
+            .keys()
+            .copied()
+            .fold(self.current, cmp::min)
+    }
+
     /// hands out the current "latest" committed version's associated transaction context
     pub(crate) fn current(&self) -> VersionNumber {
         self.current
@@ -11,11 +11,14 @@ use std::any::Any;
 use std::future::Future;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use derivative::Derivative;
 use dupe::Dupe;
+use futures::stream::FuturesOrdered;
 use futures::FutureExt;
+use futures::StreamExt;
 use parking_lot::Mutex;
 use parking_lot::MutexGuard;
 
@@ -25,15 +28,18 @@ use crate::api::data::DiceData;
 use crate::api::error::DiceResult;
 use crate::api::key::Key;
 use crate::api::projection::ProjectionKey;
+use crate::api::transaction_data::TransactionData;
 use crate::api::user_data::UserComputationData;
 use crate::ctx::DiceComputationsImpl;
 use crate::impls::cache::DiceTaskRef;
 use crate::impls::cache::SharedCache;
 use crate::impls::core::state::CoreStateHandle;
 use crate::impls::core::versions::VersionEpoch;
+use crate::impls::cycles::CycleDetector;
 use crate::impls::dep_trackers::RecordingDepsTracker;
 use crate::impls::dice::DiceModern;
 use crate::impls::evaluator::AsyncEvaluator;
+use crate::impls::evaluator::EvalPriority;
 use crate::impls::evaluator::SyncEvaluator;
 use crate::impls::events::DiceEventDispatcher;
 use crate::impls::incremental::IncrementalEngine;
@@ -52,6 +58,7 @@ use crate::impls::user_cycle::UserCycleDetectorData;
 use crate::impls::value::DiceComputedValue;
 use crate::impls::value::DiceValidity;
 use crate::impls::value::MaybeValidDiceValue;
+use crate::introspection::recompute::RecomputeChain;
 use crate::result::CancellableResult;
 use crate::result::Cancelled;
 use crate::versions::VersionNumber;
@@ -82,7 +89,10 @@ impl Clone for BaseComputeCtx {
                         ctx.async_evaluator.per_live_version_ctx.dupe(),
                         ctx.async_evaluator.user_data.dupe(),
                         ctx.async_evaluator.dice.dupe(),
-                        KeyComputingUserCycleDetectorData::Untracked,
+                        ctx.async_evaluator.priority.dupe(),
+                        KeyComputingUserCycleDetectorData::root(
+                            *ctx.async_evaluator.dice.detect_cycles(),
+                        ),
                     )))
                 }
             },
@@ -100,13 +110,16 @@ impl BaseComputeCtx {
         dice: Arc<DiceModern>,
         live_version_guard: ActiveTransactionGuard,
     ) -> Self {
+        let cycles = KeyComputingUserCycleDetectorData::root(*dice.detect_cycles());
+        let priority = EvalPriority::Fixed(user_data.priority);
         Self {
             data: DiceComputations(DiceComputationsImpl::Modern(PerComputeCtx::new(
                 ParentKey::None,
                 per_live_version_ctx,
                 user_data,
                 dice,
-                KeyComputingUserCycleDetectorData::Untracked,
+                priority,
+                cycles,
             ))),
             live_version_guard,
         }
@@ -120,6 +133,13 @@ impl BaseComputeCtx {
         self.data.0.into_updater()
     }
 
+    pub(crate) async fn why_recomputed<K: Key>(&self, key: &K) -> Option<RecomputeChain> {
+        self.async_evaluator
+            .dice
+            .why_recomputed(key, self.get_version())
+            .await
+    }
+
     pub(crate) fn as_computations(&self) -> &DiceComputations {
         &self.data
     }
@@ -161,6 +181,7 @@ impl PerComputeCtx {
         per_live_version_ctx: SharedLiveTransactionCtx,
         user_data: Arc<UserComputationData>,
         dice: Arc<DiceModern>,
+        priority: EvalPriority,
         cycles: KeyComputingUserCycleDetectorData,
     ) -> Self {
         Self {
@@ -168,6 +189,7 @@ impl PerComputeCtx {
                 per_live_version_ctx,
                 user_data,
                 dice,
+                priority,
             },
             dep_trackers: Mutex::new(RecordingDepsTracker::new()),
             parent_key,
@@ -190,6 +212,45 @@ impl PerComputeCtx {
             .map(|r| r.map(|opaque| opaque.into_value()))
     }
 
+    /// Computes all the keys, with at most `max_parallelism` of them in flight at once if given,
+    /// returning the results in the order of the keys. The deps are recorded together once
+    /// every key has finished.
+    pub(crate) fn compute_many<'a, K>(
+        &'a self,
+        keys: impl IntoIterator<Item = &'a K>,
+        max_parallelism: Option<usize>,
+    ) -> impl Future<Output = Vec<DiceResult<<K as Key>::Value>>> + 'a
+    where
+        K: Key,
+    {
+        let keys: Vec<&'a K> = keys.into_iter().collect();
+
+        async move {
+            let max_parallelism = max_parallelism.unwrap_or(keys.len());
+            let mut keys = keys.into_iter();
+            let mut in_flight = FuturesOrdered::new();
+            let mut results = Vec::new();
+            loop {
+                while in_flight.len() < max_parallelism {
+                    match keys.next() {
+                        Some(key) => in_flight.push_back(self.compute_opaque(key)),
+                        None => break,
+                    }
+                }
+                match in_flight.next().await {
+                    Some(result) => results.push(result),
+                    None => break,
+                }
+            }
+
+            let mut dep_trackers = self.dep_trackers();
+            results
+                .into_iter()
+                .map(|r| r.map(|opaque| opaque.into_value_recorded_in(&mut dep_trackers)))
+                .collect()
+        }
+    }
+
     /// Compute "opaque" value where the value is only accessible via projections.
     /// Projections allow accessing derived results from the "opaque" value,
     /// where the dependency of reading a projection is the projection value rather
@@ -207,22 +268,95 @@ impl PerComputeCtx {
             .key_index
             .index(CowDiceKeyHashed::key_ref(key));
 
-        self.async_evaluator
+        let cycles = match self
+            .cycles
+            .subrequest(dice_key, &self.async_evaluator.dice.key_index)
+        {
+            Ok(cycles) => cycles,
+            Err(e) => return futures::future::ready(Err(e)).left_future(),
+        };
+
+        let timeout = key.timeout();
+        let compute = self
+            .async_evaluator
             .per_live_version_ctx
-            .compute_opaque(
-                dice_key,
-                self.parent_key,
-                &self.async_evaluator,
-                self.cycles
-                    .subrequest(dice_key, &self.async_evaluator.dice.key_index),
-            )
+            .compute_opaque(dice_key, self.parent_key, &self.async_evaluator, cycles)
             .map(move |cancellable_result| {
-                let cancellable = cancellable_result.map(move |dice_value| {
-                    OpaqueValueModern::new(self, dice_key, dice_value.value().dupe())
-                });
+                let dice_value = cancellable_result.map_err(|_| DiceError::cancelled())?;
+                match dice_value.value().user_error() {
+                    // the failure is a dependency too, since the computation may handle it
+                    Some(e) => {
+                        self.dep_trackers()
+                            .record(dice_key, dice_value.value().validity());
+                        Err(e.dupe().into())
+                    }
+                    None => Ok(OpaqueValueModern::new(
+                        self,
+                        dice_key,
+                        dice_value.value().dupe(),
+                    )),
+                }
+            });
+
+        async move {
+            match timeout {
+                // dropping the request on timeout cancels the computation if nothing else is
+                // waiting for it
+                Some(timeout) => match tokio::time::timeout(timeout, compute).await {
+                    Ok(res) => res,
+                    Err(_elapsed) => Err(self.timeout_error(dice_key, timeout)),
+                },
+                None => compute.await,
+            }
+        }
+        .right_future()
+    }
+
+    /// Starts computing the keys in the background at `Background` priority, without waiting for
+    /// them or recording them as dependencies. The computations are spawned with the transaction's
+    /// spawner and cancelled with the transaction, a computation is raised to the priority of any
+    /// request joining it, and keys that would form a cycle are skipped.
+    pub(crate) fn spawn_prefetch<'a, K>(&self, keys: impl IntoIterator<Item = &'a K>)
+    where
+        K: Key,
+    {
+        for key in keys {
+            let eval = AsyncEvaluator {
+                priority: EvalPriority::prefetch(),
+                ..self.async_evaluator.dupe()
+            };
+            let dice_key = eval.dice.key_index.index(CowDiceKeyHashed::key_ref(key));
+            let cycles = match self.cycles.subrequest(dice_key, &eval.dice.key_index) {
+                Ok(cycles) => cycles,
+                Err(_) => continue,
+            };
+            // the computation is cancelled once nothing waits for it, so hold on to it until
+            // it finishes
+            let compute =
+                eval.per_live_version_ctx
+                    .compute_opaque(dice_key, ParentKey::None, &eval, cycles);
+            let _ignored = eval.user_data.spawner.spawn(
+                &eval.user_data,
+                async move {
+                    let _ignored = compute.await;
+                    Box::new(()) as Box<dyn Any + Send + 'static>
+                }
+                .boxed(),
+            );
+        }
+    }
 
-                cancellable.map_err(|_| DiceError::cancelled())
-            })
+    fn timeout_error(&self, key: DiceKey, timeout: Duration) -> DiceError {
+        let key_index = &self.async_evaluator.dice.key_index;
+        DiceError::timeout(
+            CycleDetector::requested_key(key, key_index),
+            timeout,
+            self.cycles
+                .stack()
+                .into_iter()
+                .map(|k| CycleDetector::requested_key(k, key_index))
+                .collect(),
+        )
     }
 
     /// Compute "projection" based on deriving value
@@ -288,6 +422,10 @@ impl PerComputeCtx {
         &self.async_evaluator.user_data
     }
 
+    pub(crate) fn transaction_data(&self) -> &TransactionData {
+        self.async_evaluator.per_live_version_ctx.transaction_data()
+    }
+
     pub(crate) fn get_version(&self) -> VersionNumber {
         self.async_evaluator.per_live_version_ctx.get_version()
     }
@@ -346,6 +484,10 @@ pub(crate) struct SharedLiveTransactionCtx {
 
 #[allow(clippy::manual_async_fn, unused)]
 impl SharedLiveTransactionCtx {
+    pub(crate) fn transaction_data(&self) -> &TransactionData {
+        self.cache.transaction_data()
+    }
+
     pub(crate) fn new(v: VersionNumber, version_epoch: VersionEpoch, cache: SharedCache) -> Self {
         Self {
             version: v,
@@ -374,6 +516,8 @@ impl SharedLiveTransactionCtx {
                     MaybeCancelled::Ok(promise) => {
                         debug!(msg = "shared state is waiting on existing task", k = ?key, v = ?self.version, v_epoch = ?self.version_epoch);
 
+                        self.cache.raise_prefetch(key, eval.priority.get());
+
                         promise
                     },
                     MaybeCancelled::Cancelled => {
@@ -384,6 +528,9 @@ impl SharedLiveTransactionCtx {
                             eval.user_data.tracker.dupe(),
                             eval.dice.dupe(),
                         );
+                        if let EvalPriority::Prefetch(_) = eval.priority {
+                            self.cache.register_prefetch(key, eval.priority.dupe());
+                        }
 
                         take_mut::take(occupied.get_mut(), |previous| {
                             IncrementalEngine::spawn_for_key(
@@ -413,6 +560,9 @@ impl SharedLiveTransactionCtx {
                 let eval = eval.dupe();
                 let events =
                     DiceEventDispatcher::new(eval.user_data.tracker.dupe(), eval.dice.dupe());
+                if let EvalPriority::Prefetch(_) = eval.priority {
+                    self.cache.register_prefetch(key, eval.priority.dupe());
+                }
 
                 let task = IncrementalEngine::spawn_for_key(
                     key,
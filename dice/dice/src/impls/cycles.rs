@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Cycle detection in modern DICE
+
+use std::sync::Arc;
+
+use indexmap::IndexSet;
+
+use crate::api::cycles::DetectCycles;
+use crate::api::error::DiceError;
+use crate::api::error::DiceResult;
+use crate::impls::key::DiceKey;
+use crate::impls::key::DiceKeyErased;
+use crate::impls::key_index::DiceKeyIndex;
+use crate::legacy::cycles::RequestedKey;
+
+/// Tracks the chain of keys that are being computed to reach the current request.
+#[derive(Clone, Debug)]
+pub(crate) struct CycleDetector {
+    stack: IndexSet<DiceKey>,
+}
+
+impl CycleDetector {
+    pub(crate) fn new(detect_cycles: DetectCycles) -> Option<Self> {
+        match detect_cycles {
+            DetectCycles::Enabled => Some(Self {
+                stack: IndexSet::new(),
+            }),
+            DetectCycles::Disabled => None,
+        }
+    }
+
+    /// Records that `key` is requested by the current key, returning the detector for the
+    /// computation of `key`. Errors if `key` is already being computed higher up the chain, as
+    /// waiting for it would never finish.
+    pub(crate) fn visit(&self, key: DiceKey, key_index: &DiceKeyIndex) -> DiceResult<Self> {
+        let mut stack = self.stack.clone();
+
+        // computing a projection requires its base, so requesting a projection of a key that is
+        // being computed is equally a cycle.
+        let base = match key_index.get(key) {
+            DiceKeyErased::Key(_) => None,
+            DiceKeyErased::Projection(proj) => Some(proj.base()),
+        };
+        let trigger = match base {
+            Some(base) if stack.contains(&base) => Some(base),
+            _ if stack.contains(&key) => Some(key),
+            _ => None,
+        };
+
+        if let Some(trigger) = trigger {
+            return Err(DiceError::cycle(
+                Self::requested_key(trigger, key_index),
+                stack
+                    .iter()
+                    .map(|k| Self::requested_key(*k, key_index))
+                    .collect(),
+            ));
+        }
+
+        stack.insert(key);
+        Ok(Self { stack })
+    }
+
+    pub(crate) fn requested_key(key: DiceKey, key_index: &DiceKeyIndex) -> Arc<dyn RequestedKey> {
+        Arc::new(key_index.get(key).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use allocative::Allocative;
+    use async_trait::async_trait;
+    use derive_more::Display;
+    use dupe::Dupe;
+    use more_futures::cancellation::CancellationContext;
+
+    use crate::api::computations::DiceComputations;
+    use crate::api::cycles::DetectCycles;
+    use crate::api::error::DiceErrorImpl;
+    use crate::api::key::Key;
+    use crate::impls::cycles::CycleDetector;
+    use crate::impls::key_index::DiceKeyIndex;
+
+    #[derive(Allocative, Clone, Dupe, Debug, Display, PartialEq, Eq, Hash)]
+    struct K(usize);
+
+    #[async_trait]
+    impl Key for K {
+        type Value = ();
+
+        async fn compute(
+            &self,
+            _ctx: &DiceComputations,
+            _cancellations: &CancellationContext,
+        ) -> Self::Value {
+            unimplemented!("test")
+        }
+
+        fn equality(_: &Self::Value, _: &Self::Value) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn disabled_detects_nothing() {
+        assert!(CycleDetector::new(DetectCycles::Disabled).is_none());
+    }
+
+    #[test]
+    fn cycle_detection_when_no_cycles() -> anyhow::Result<()> {
+        let index = DiceKeyIndex::default();
+        let k = |i| index.index_key(K(i));
+
+        let detector = CycleDetector::new(DetectCycles::Enabled).unwrap();
+        let detector1 = detector.visit(k(1), &index)?;
+        let detector12 = detector1.visit(k(2), &index)?;
+        let _detector123 = detector12.visit(k(3), &index)?;
+
+        let detector13 = detector1.visit(k(3), &index)?;
+        let _detector132 = detector13.visit(k(2), &index)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn cycle_detection_when_cycles() -> anyhow::Result<()> {
+        let index = DiceKeyIndex::default();
+        let k = |i| index.index_key(K(i));
+
+        let detector = CycleDetector::new(DetectCycles::Enabled).unwrap();
+        let detector = detector.visit(k(1), &index)?;
+        let detector = detector.visit(k(2), &index)?;
+        let detector = detector.visit(k(3), &index)?;
+
+        let err = detector.visit(k(2), &index).unwrap_err();
+        match &*err.0 {
+            DiceErrorImpl::Cycle {
+                trigger,
+                cyclic_keys,
+            } => {
+                assert_eq!("2", trigger.to_string());
+                assert_eq!(
+                    vec!["K(1)", "K(2)", "K(3)"],
+                    cyclic_keys
+                        .iter()
+                        .map(|k| format!("{:?}", k))
+                        .collect::<Vec<_>>()
+                );
+            }
+            _ => panic!("wrong error type"),
+        }
+
+        Ok(())
+    }
+}
@@ -7,29 +7,56 @@
  * of this source tree.
  */
 
+use std::cmp::Reverse;
 use std::fmt::Debug;
 use std::future::Future;
 use std::sync::Arc;
 
 use allocative::Allocative;
 use dupe::Dupe;
+use itertools::Itertools;
+use tokio::time::Instant;
 
 use crate::api::cycles::DetectCycles;
 use crate::api::data::DiceData;
+use crate::api::eviction::DiceEvictionPolicy;
+use crate::api::gc::DiceGcPolicy;
+use crate::api::key::Key;
+use crate::api::on_computed::DiceComputedObserver;
+use crate::api::persistence::DicePersistence;
+use crate::api::transient::DiceTransientRetryPolicy;
 use crate::api::user_data::UserComputationData;
+use crate::impls::core::graph::types::VersionedGraphKey;
 use crate::impls::core::state::init_state;
 use crate::impls::core::state::CoreStateHandle;
 use crate::impls::core::state::StateRequest;
+use crate::impls::key::DiceKey;
 use crate::impls::key_index::DiceKeyIndex;
+use crate::impls::key_metrics::KeyTypeMetricsRecorder;
 use crate::impls::transaction::TransactionUpdater;
+use crate::impls::transient::TransientRetries;
+use crate::introspection::cancellations::CancelledComputation;
+use crate::introspection::changes::VersionChanges;
 use crate::introspection::graph::GraphIntrospectable;
+use crate::introspection::memory::MemoryUsage;
+use crate::introspection::recompute::RecomputeChain;
+use crate::introspection::recompute::RecomputeStep;
 use crate::metrics::Metrics;
+use crate::versions::VersionNumber;
+use crate::HashMap;
+use crate::HashSet;
 
 #[derive(Allocative)]
 pub(crate) struct DiceModern {
     pub(crate) key_index: DiceKeyIndex,
     pub(crate) state_handle: CoreStateHandle,
     pub(crate) global_data: DiceData,
+    detect_cycles: DetectCycles,
+    pub(crate) persistence: Option<DicePersistence>,
+    pub(crate) transients: TransientRetries,
+    pub(crate) key_metrics: KeyTypeMetricsRecorder,
+    #[allocative(skip)]
+    pub(crate) computed_observers: Vec<Arc<dyn DiceComputedObserver>>,
 }
 
 impl Debug for DiceModern {
@@ -38,32 +65,99 @@ impl Debug for DiceModern {
     }
 }
 
-pub(crate) struct DiceModernDataBuilder(DiceData);
+pub(crate) struct DiceModernDataBuilder {
+    global_data: DiceData,
+    persistence: Option<DicePersistence>,
+    eviction_policy: DiceEvictionPolicy,
+    gc_policy: DiceGcPolicy,
+    record_recomputes: bool,
+    transient_retry_policy: DiceTransientRetryPolicy,
+    key_index_shards: u32,
+    state_on_current_runtime: bool,
+    computed_observers: Vec<Arc<dyn DiceComputedObserver>>,
+}
 
 impl DiceModernDataBuilder {
     pub(crate) fn new() -> Self {
-        Self(DiceData::new())
+        Self {
+            global_data: DiceData::new(),
+            persistence: None,
+            eviction_policy: DiceEvictionPolicy::KeepAll,
+            gc_policy: DiceGcPolicy::Manual,
+            record_recomputes: false,
+            transient_retry_policy: DiceTransientRetryPolicy::default(),
+            key_index_shards: DiceKeyIndex::DEFAULT_SHARDS,
+            state_on_current_runtime: false,
+            computed_observers: Vec::new(),
+        }
     }
 
     pub fn set<K: Send + Sync + 'static>(&mut self, val: K) {
-        self.0.set(val);
+        self.global_data.set(val);
     }
 
-    pub fn build(self, _detect_cycles: DetectCycles) -> Arc<DiceModern> {
-        DiceModern::new(self.0)
+    pub fn set_persistence(&mut self, persistence: DicePersistence) {
+        self.persistence = Some(persistence);
     }
-}
 
-impl DiceModern {
-    pub(crate) fn new(global_data: DiceData) -> Arc<Self> {
-        let state_handle = init_state();
+    pub fn set_eviction_policy(&mut self, policy: DiceEvictionPolicy) {
+        self.eviction_policy = policy;
+    }
+
+    pub fn set_gc_policy(&mut self, policy: DiceGcPolicy) {
+        self.gc_policy = policy;
+    }
+
+    pub fn set_record_recomputes(&mut self, record: bool) {
+        self.record_recomputes = record;
+    }
+
+    pub fn set_transient_retry_policy(&mut self, policy: DiceTransientRetryPolicy) {
+        self.transient_retry_policy = policy;
+    }
+
+    pub fn set_key_index_shards(&mut self, shards: u32) {
+        self.key_index_shards = shards;
+    }
+
+    pub fn add_computed_observer(&mut self, observer: Arc<dyn DiceComputedObserver>) {
+        self.computed_observers.push(observer);
+    }
+
+    /// Process the state on the current tokio runtime instead of a dedicated thread.
+    #[cfg(test)]
+    pub(crate) fn set_state_on_current_runtime(&mut self) {
+        self.state_on_current_runtime = true;
+    }
+
+    pub fn build(self, detect_cycles: DetectCycles) -> Arc<DiceModern> {
+        let state_handle = init_state(
+            self.eviction_policy,
+            self.gc_policy,
+            self.record_recomputes,
+            self.state_on_current_runtime,
+        );
 
         Arc::new(DiceModern {
-            key_index: Default::default(),
+            key_index: DiceKeyIndex::new(self.key_index_shards),
             state_handle,
-            global_data,
+            global_data: self.global_data,
+            detect_cycles,
+            persistence: self.persistence,
+            transients: TransientRetries::new(self.transient_retry_policy),
+            key_metrics: KeyTypeMetricsRecorder::default(),
+            computed_observers: self.computed_observers,
         })
     }
+}
+
+impl DiceModern {
+    #[cfg(test)]
+    pub(crate) fn new(global_data: DiceData, detect_cycles: DetectCycles) -> Arc<Self> {
+        let mut builder = DiceModernDataBuilder::new();
+        builder.global_data = global_data;
+        builder.build(detect_cycles)
+    }
 
     #[cfg(test)]
     pub(crate) fn builder() -> DiceModernDataBuilder {
@@ -86,7 +180,60 @@ impl DiceModern {
 
         // Modern dice can just run on a blocking runtime and block waiting for the channel.
         // This is safe since the processing dice thread is dedicated, and never awaits any other tasks.
-        tokio::task::block_in_place(|| rx.blocking_recv().unwrap())
+        let mut metrics = tokio::task::block_in_place(|| rx.blocking_recv().unwrap());
+        self.transients.add_to_metrics(&mut metrics);
+        self.key_metrics.add_to_metrics(&mut metrics);
+        self.key_index.add_to_metrics(&mut metrics);
+        metrics
+    }
+
+    /// Measures the bytes retained by the values of the graph. This walks every value, so unlike
+    /// `metrics` it is expensive on large graphs, but the walk happens on the caller rather than
+    /// on the core state.
+    pub async fn memory_usage(&self) -> MemoryUsage {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.state_handle.request(StateRequest::Values { resp: tx });
+
+        let mut usage = MemoryUsage::default();
+        for (k, v, value) in rx.await.unwrap() {
+            usage.add(
+                self.key_index.get(k).key_type_name(),
+                v.to_introspectable(),
+                value.retained_bytes(),
+            );
+        }
+        usage
+    }
+
+    /// Drops the values of keys that are cheap to recompute, largest first, until about `bytes`
+    /// are freed. Returns the bytes freed.
+    pub async fn notify_memory_pressure(&self, bytes: usize) -> usize {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.state_handle.request(StateRequest::Values { resp: tx });
+
+        let mut retained_by_key: HashMap<DiceKey, usize> = HashMap::default();
+        for (k, _v, value) in rx.await.unwrap() {
+            if self.key_index.get(k).cheap_to_recompute() {
+                *retained_by_key.entry(k).or_default() += value.retained_bytes();
+            }
+        }
+
+        let mut selected = 0;
+        let keys = retained_by_key
+            .into_iter()
+            .sorted_by_key(|(_, retained)| Reverse(*retained))
+            .take_while(|(_, retained)| {
+                let needed = selected < bytes;
+                selected += retained;
+                needed
+            })
+            .map(|(k, _)| k)
+            .collect();
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.state_handle
+            .request(StateRequest::DropValues { keys, resp: tx });
+        rx.await.unwrap()
     }
 
     pub fn to_introspectable(&self) -> GraphIntrospectable {
@@ -97,14 +244,38 @@ impl DiceModern {
             key_map: self.key_index.introspect(),
         });
 
-        rx.blocking_recv().unwrap()
+        tokio::task::block_in_place(|| rx.blocking_recv().unwrap())
+    }
+
+    /// Explains why the key was recomputed at the given version, as the chain of changed
+    /// dependencies leading to the recomputation. Returns `None` if the key wasn't recomputed at
+    /// that version, it was too long ago to be remembered, or recomputes aren't recorded.
+    pub async fn why_recomputed<K: Key>(
+        &self,
+        key: &K,
+        version: VersionNumber,
+    ) -> Option<RecomputeChain> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.state_handle.request(StateRequest::WhyRecomputed {
+            key: VersionedGraphKey::new(version, self.key_index.index_key(key.clone())),
+            resp: tx,
+        });
+
+        let chain = rx.await.unwrap()?;
+        Some(RecomputeChain(
+            chain
+                .into_iter()
+                .map(|(key, reason)| RecomputeStep {
+                    key: self.key_index.get(key).to_string(),
+                    reason,
+                })
+                .collect(),
+        ))
     }
 
-    /// Note: modern dice does not support cycle detection yet
     pub fn detect_cycles(&self) -> &DetectCycles {
-        // TODO(bobyf) actually have cycles for dice modern
-        const CYCLES: DetectCycles = DetectCycles::Disabled;
-        &CYCLES
+        &self.detect_cycles
     }
 
     /// Wait until all active versions have exited.
@@ -120,6 +291,63 @@ impl DiceModern {
         }
     }
 
+    /// Collects the graph nodes of superseded versions, returning the number of nodes reclaimed.
+    pub async fn gc(&self) -> usize {
+        self.state_handle.gc().await
+    }
+
+    /// Computations that were cancelled but haven't finished running yet, e.g. because they are
+    /// in a section that can't be cancelled.
+    pub async fn cancelled_computations(&self) -> Vec<CancelledComputation> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.state_handle
+            .request(StateRequest::CancelledTasks { resp: tx });
+
+        let now = Instant::now();
+        rx.await
+            .unwrap()
+            .into_iter()
+            .map(|(key, cancelled_at)| CancelledComputation {
+                key: format!("{:?}", self.key_index.get(key)),
+                elapsed: now.saturating_duration_since(cancelled_at),
+            })
+            .collect()
+    }
+
+    /// The keys changed by the versions committed after `from` up to and including `to`, and the
+    /// keys those changes invalidated. `None` if the changes of those versions are no longer kept.
+    pub async fn changes_between(
+        &self,
+        from: VersionNumber,
+        to: VersionNumber,
+    ) -> Option<VersionChanges> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.state_handle
+            .request(StateRequest::ChangesBetween { from, to, resp: tx });
+
+        let (changed, invalidated) = rx.await.unwrap()?;
+        Some(self.describe_changes(changed, invalidated))
+    }
+
+    pub(crate) fn describe_changes(
+        &self,
+        changed: HashSet<DiceKey>,
+        invalidated: HashSet<DiceKey>,
+    ) -> VersionChanges {
+        let describe = |keys: HashSet<DiceKey>| {
+            keys.into_iter()
+                .map(|k| format!("{:?}", self.key_index.get(k)))
+                .sorted()
+                .collect()
+        };
+        VersionChanges {
+            changed: describe(changed),
+            invalidated: describe(invalidated),
+        }
+    }
+
     /// true when there are no tasks pending cancellation
     pub async fn is_idle(&self) -> bool {
         let (tx, rx) = tokio::sync::oneshot::channel();
@@ -7,12 +7,16 @@
  * of this source tree.
  */
 
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use allocative::Allocative;
 use dupe::Dupe;
 
 use crate::api::computations::DiceComputations;
+use crate::api::cutoff::DiceCutoff;
+use crate::api::priority::DicePriority;
 use crate::api::projection::DiceProjectionComputations;
 use crate::api::storage_type::StorageType;
 use crate::api::user_data::UserComputationData;
@@ -24,6 +28,9 @@ use crate::impls::dice::DiceModern;
 use crate::impls::key::DiceKey;
 use crate::impls::key::DiceKeyErased;
 use crate::impls::key::ParentKey;
+use crate::impls::value::DiceUserErrorValue;
+use crate::impls::value::DiceValidity;
+use crate::impls::value::DiceValueDyn;
 use crate::impls::value::MaybeValidDiceValue;
 use crate::impls::worker::state::DiceWorkerStateComputing;
 use crate::impls::worker::state::DiceWorkerStateFinishedEvaluating;
@@ -36,6 +43,41 @@ pub(crate) struct AsyncEvaluator {
     pub(super) per_live_version_ctx: SharedLiveTransactionCtx,
     pub(super) user_data: Arc<UserComputationData>,
     pub(super) dice: Arc<DiceModern>,
+    /// The priority of the lookups made on behalf of the computations, which is the transaction's
+    /// priority unless the computations were prefetched.
+    pub(super) priority: EvalPriority,
+}
+
+/// The priority of evaluations. Prefetches start at `Background`, and are raised to the priority
+/// of the requests joining any of their computations, since those now wait for them.
+#[derive(Clone, Dupe, Allocative)]
+pub(crate) enum EvalPriority {
+    Fixed(DicePriority),
+    Prefetch(#[allocative(skip)] Arc<AtomicU8>),
+}
+
+impl EvalPriority {
+    pub(crate) fn prefetch() -> Self {
+        Self::Prefetch(Arc::new(AtomicU8::new(DicePriority::Background as u8)))
+    }
+
+    pub(crate) fn get(&self) -> DicePriority {
+        match self {
+            Self::Fixed(priority) => *priority,
+            Self::Prefetch(priority) => match priority.load(Ordering::Relaxed) {
+                0 => DicePriority::Background,
+                1 => DicePriority::Normal,
+                _ => DicePriority::Interactive,
+            },
+        }
+    }
+
+    /// Raise a prefetch to `priority`, if it is lower.
+    pub(crate) fn raise_to(&self, priority: DicePriority) {
+        if let Self::Prefetch(current) = self {
+            current.fetch_max(priority as u8, Ordering::Relaxed);
+        }
+    }
 }
 
 impl AsyncEvaluator {
@@ -47,6 +89,13 @@ impl AsyncEvaluator {
         }
     }
 
+    pub(crate) fn cutoff(&self, key: DiceKey) -> DiceCutoff {
+        match self.dice.key_index.get(key) {
+            DiceKeyErased::Key(k) => k.cutoff(),
+            DiceKeyErased::Projection(_) => DiceCutoff::Equality,
+        }
+    }
+
     pub(crate) async fn evaluate<'a>(
         &'a self,
         key: DiceKey,
@@ -58,24 +107,103 @@ impl AsyncEvaluator {
 
         match key_erased {
             DiceKeyErased::Key(key_dyn) => {
-                let new_ctx = DiceComputations(DiceComputationsImpl::Modern(PerComputeCtx::new(
-                    ParentKey::Some(key), // within this key's compute, this key is the parent
-                    self.per_live_version_ctx.dupe(),
-                    self.user_data.dupe(),
-                    self.dice.dupe(),
-                    cycles,
-                )));
-
-                let value = key_dyn
-                    .compute(&new_ctx, &state.cancellation_ctx().into_compatible())
-                    .await;
-                let ((deps, dep_validity), evaluation_data, cycles) = match new_ctx.0 {
-                    DiceComputationsImpl::Legacy(_) => {
-                        unreachable!("modern dice created above")
+                let persisted = self.dice.persistence.as_ref().and_then(|persistence| {
+                    let serialized_key = key_dyn.serialize_key()?;
+                    Some((persistence, serialized_key))
+                });
+
+                if let Some((persistence, serialized_key)) = &persisted {
+                    if let Some(value) = persistence
+                        .load(key_dyn.key_type_name(), serialized_key)
+                        .and_then(|bytes| {
+                            let value = key_dyn.deserialize_value(&bytes);
+                            if value.is_none() {
+                                persistence.record_error();
+                            }
+                            value
+                        })
+                    {
+                        persistence.record_hit();
+
+                        // persisted keys promise their value only depends on the key itself, so
+                        // there are no deps to record.
+                        return state.finished(
+                            cycles,
+                            KeyEvaluationResult {
+                                value: MaybeValidDiceValue::new(value, DiceValidity::Valid),
+                                deps: HashSet::default(),
+                                storage: key_dyn.storage_type(),
+                                evaluation_data: EvaluationData::none(),
+                            },
+                        );
                     }
-                    DiceComputationsImpl::Modern(new_ctx) => new_ctx.finalize(),
+                }
+
+                // held until the computation, including any retries, finishes
+                let key_type = key_dyn.as_any().type_id();
+                let _permit = match self.dice.global_data.key_concurrency_limit(key_type) {
+                    Some(limit) => Some(
+                        limit
+                            .acquire()
+                            .await
+                            .expect("concurrency limit semaphores are never closed"),
+                    ),
+                    None => None,
                 };
 
+                let mut cycles = cycles;
+                let mut attempt = 0;
+                let (value, (deps, dep_validity), evaluation_data, cycles) = loop {
+                    let new_ctx =
+                        DiceComputations(DiceComputationsImpl::Modern(PerComputeCtx::new(
+                            ParentKey::Some(key), // within this key's compute, this key is the parent
+                            self.per_live_version_ctx.dupe(),
+                            self.user_data.dupe(),
+                            self.dice.dupe(),
+                            self.priority.dupe(),
+                            cycles,
+                        )));
+
+                    let value = key_dyn
+                        .compute(&new_ctx, &state.cancellation_ctx().into_compatible())
+                        .await;
+                    let ((deps, dep_validity), evaluation_data, new_cycles) = match new_ctx.0 {
+                        DiceComputationsImpl::Legacy(_) => {
+                            unreachable!("modern dice created above")
+                        }
+                        DiceComputationsImpl::Modern(new_ctx) => new_ctx.finalize(),
+                    };
+
+                    // transient deps are shared by the whole version, so recomputing this key
+                    // would see the same values again. Failed computations aren't transient
+                    // values, and would fail again.
+                    let retry = self
+                        .dice
+                        .transients
+                        .should_retry(
+                            attempt,
+                            dep_validity == DiceValidity::Transient
+                                || (!value.validity() && value.user_error().is_none()),
+                            dep_validity == DiceValidity::Valid,
+                        )
+                        .await;
+                    if !retry {
+                        break (value, (deps, dep_validity), evaluation_data, new_cycles);
+                    }
+
+                    debug!(msg = "retrying transient value", attempt = attempt);
+                    attempt += 1;
+                    cycles = new_cycles;
+                };
+
+                if let Some((persistence, serialized_key)) = &persisted {
+                    if dep_validity == DiceValidity::Valid && value.validity() {
+                        if let Some(bytes) = key_dyn.serialize_value(&*value) {
+                            persistence.persist(key_dyn.key_type_name(), serialized_key, &bytes);
+                        }
+                    }
+                }
+
                 state.finished(
                     cycles,
                     KeyEvaluationResult {
@@ -93,7 +221,9 @@ impl AsyncEvaluator {
                         proj.base(),
                         ParentKey::Some(key), // the parent requesting the projection base is the projection itself
                         self,
-                        cycles.subrequest(proj.base(), &self.dice.key_index),
+                        cycles
+                            .subrequest(proj.base(), &self.dice.key_index)
+                            .expect("projection base is checked for cycles when the projection is requested"),
                     )
                     .await?;
 
@@ -102,7 +232,11 @@ impl AsyncEvaluator {
                     user_data: &self.user_data,
                 };
 
-                let value = proj.proj().compute(base.value(), &ctx);
+                // a failed base fails its projections the same way
+                let value: Arc<dyn DiceValueDyn> = match base.value().user_error() {
+                    Some(e) => Arc::new(DiceUserErrorValue(e.dupe())),
+                    None => proj.proj().compute(base.value(), &ctx),
+                };
 
                 state.finished(
                     cycles,
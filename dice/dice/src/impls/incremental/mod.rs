@@ -22,13 +22,18 @@ use dupe::Dupe;
 use futures::stream::FuturesUnordered;
 use futures::FutureExt;
 use futures::StreamExt;
+use scopeguard::ScopeGuard;
 use tokio::sync::oneshot;
+use tokio::time::Instant;
 
 use crate::api::activation_tracker::ActivationData;
+use crate::api::cutoff::DiceCutoff;
+use crate::api::on_computed::DiceComputedEvent;
 use crate::arc::Arc;
 use crate::impls::core::graph::history::CellHistory;
 use crate::impls::core::graph::types::VersionedGraphKey;
 use crate::impls::core::graph::types::VersionedGraphResult;
+use crate::impls::core::recompute::RecomputeCause;
 use crate::impls::core::state::CoreStateHandle;
 use crate::impls::core::state::StateRequest;
 use crate::impls::core::versions::VersionEpoch;
@@ -68,6 +73,9 @@ mod tests;
 pub(crate) struct IncrementalEngine {
     state: CoreStateHandle,
     pub(crate) version_epoch: VersionEpoch,
+    /// When the key was requested, to report how long it took
+    #[allocative(skip)]
+    requested_at: Instant,
 }
 
 impl Debug for IncrementalEngine {
@@ -81,6 +89,7 @@ impl IncrementalEngine {
         Self {
             state,
             version_epoch,
+            requested_at: Instant::now(),
         }
     }
 
@@ -135,8 +144,10 @@ impl IncrementalEngine {
                             key: VersionedGraphKey::new(v, k),
                             epoch: version_epoch,
                             storage: eval_result.storage,
+                            cutoff: DiceCutoff::Equality,
                             value,
                             deps: Arc::new(eval_result.deps.into_iter().collect()),
+                            compute_duration: None,
                             resp: tx,
                         });
                         // TODO(bobyf) consider if we want to block and wait for the cache
@@ -154,6 +165,19 @@ impl IncrementalEngine {
         })
     }
 
+    /// Emits a span per key requested, recording whether it was a cache hit, including when the
+    /// previous value was reused because its deps didn't change.
+    #[instrument(
+        level = "debug",
+        name = "dice_key",
+        skip_all,
+        fields(
+            key_type = eval.dice.key_index.get(k).key_type_name(),
+            key = %eval.dice.key_index.get(k),
+            version = %eval.per_live_version_ctx.get_version(),
+            hit = tracing::field::Empty,
+        ),
+    )]
     pub(crate) async fn eval_entry_versioned(
         &self,
         k: DiceKey,
@@ -163,16 +187,31 @@ impl IncrementalEngine {
     ) -> CancellableResult<DiceWorkerStateFinishedAndCached> {
         let v = eval.per_live_version_ctx.get_version();
         let (tx, rx) = oneshot::channel();
+        let queued_at = Instant::now();
         self.state.request(StateRequest::LookupKey {
             key: VersionedGraphKey::new(v, k),
+            priority: eval.priority.get(),
             resp: tx,
         });
 
         let state_result = rx.await.unwrap();
+        eval.dice.key_metrics.record_queue_delay(
+            eval.dice.key_index.get(k).key_type_name(),
+            eval.priority.get(),
+            queued_at.elapsed(),
+        );
 
         match state_result {
-            VersionedGraphResult::Match(entry) => Ok(task_state.lookup_matches(entry)),
+            VersionedGraphResult::Match(entry) => {
+                tracing::Span::current().record("hit", true);
+                eval.dice
+                    .key_metrics
+                    .record_cache_hit(eval.dice.key_index.get(k).key_type_name());
+                self.report_computed(k, eval, true, None);
+                Ok(task_state.lookup_matches(entry))
+            }
             VersionedGraphResult::Compute => {
+                tracing::Span::current().record("hit", false);
                 self.compute(k, eval, &events_dispatcher, task_state.lookup_dirtied(eval))
                     .await
             }
@@ -197,11 +236,22 @@ impl IncrementalEngine {
                 };
 
                 match deps_changed {
-                    DidDepsChange::Changed | DidDepsChange::NoDeps => {
+                    changed @ (DidDepsChange::Changed(_) | DidDepsChange::NoDeps) => {
+                        tracing::Span::current().record("hit", false);
+                        self.state.request(StateRequest::RecordRecompute {
+                            key: VersionedGraphKey::new(v, k),
+                            cause: match changed {
+                                DidDepsChange::Changed(dep) => RecomputeCause::DepChanged(dep),
+                                // keys without deps are only checked when they are invalidated
+                                _ => RecomputeCause::Invalidated,
+                            },
+                        });
+
                         self.compute(k, eval, &events_dispatcher, task_state.deps_not_match())
                             .await
                     }
                     DidDepsChange::NoChange(deps) => {
+                        tracing::Span::current().record("hit", true);
                         report_key_activation(
                             &eval.dice.key_index,
                             eval.user_data.activation_tracker.as_deref(),
@@ -212,18 +262,29 @@ impl IncrementalEngine {
 
                         let task_state = task_state.deps_match()?;
 
+                        eval.dice
+                            .key_metrics
+                            .record_cache_hit(eval.dice.key_index.get(k).key_type_name());
+
+                        let deps_count = deps.len();
+
                         // report reuse
                         let (tx, rx) = tokio::sync::oneshot::channel();
                         self.state.request(StateRequest::UpdateComputed {
                             key: VersionedGraphKey::new(v, k),
                             epoch: self.version_epoch,
                             storage: eval.storage_type(k),
+                            cutoff: eval.cutoff(k),
                             value: mismatch.entry,
                             deps,
+                            compute_duration: None,
                             resp: tx,
                         });
 
-                        rx.await.unwrap().map(|r| task_state.cached(r))
+                        rx.await.unwrap().map(|r| {
+                            self.report_computed(k, eval, true, Some(deps_count));
+                            task_state.cached(r)
+                        })
                     }
                 }
             }
@@ -244,11 +305,24 @@ impl IncrementalEngine {
 
         let v = eval.per_live_version_ctx.get_version();
 
-        // TODO(bobyf) these also make good locations where we want to perform instrumentation
         debug!(msg = "running evaluator");
 
+        let key_type = eval.dice.key_index.get(k).key_type_name();
+        // counts the computation as cancelled unless it finishes, including when this future is
+        // dropped
+        let cancelled =
+            scopeguard::guard((), |()| eval.dice.key_metrics.record_cancellation(key_type));
+
+        let start = Instant::now();
         let eval_result_state = eval.evaluate(k, task_state).await?;
+        let compute_duration = start.elapsed();
+
+        ScopeGuard::into_inner(cancelled);
+        eval.dice
+            .key_metrics
+            .record_compute(key_type, compute_duration);
         let eval_result = eval_result_state.result;
+        let deps_count = eval_result.deps.len();
 
         let res = {
             report_key_activation(
@@ -266,8 +340,10 @@ impl IncrementalEngine {
                         key: VersionedGraphKey::new(v, k),
                         epoch: self.version_epoch,
                         storage: eval_result.storage,
+                        cutoff: eval.cutoff(k),
                         value,
                         deps: Arc::new(eval_result.deps.into_iter().collect()),
+                        compute_duration: Some(compute_duration),
                         resp: tx,
                     });
 
@@ -280,7 +356,36 @@ impl IncrementalEngine {
             }
         };
 
-        res.map(|res| eval_result_state.state.cached(res))
+        res.map(|res| {
+            self.report_computed(k, eval, false, Some(deps_count));
+            eval_result_state.state.cached(res)
+        })
+    }
+
+    fn report_computed(
+        &self,
+        k: DiceKey,
+        eval: &AsyncEvaluator,
+        hit: bool,
+        deps_count: Option<usize>,
+    ) {
+        if eval.dice.computed_observers.is_empty() {
+            return;
+        }
+
+        let key = eval.dice.key_index.get(k);
+        let event = DiceComputedEvent {
+            key: key.as_any(),
+            key_display: key,
+            key_type: key.key_type_name(),
+            version: eval.per_live_version_ctx.get_version().to_introspectable(),
+            duration: self.requested_at.elapsed(),
+            hit,
+            deps_count,
+        };
+        for observer in &eval.dice.computed_observers {
+            observer.on_computed(&event);
+        }
     }
 
     /// determines if the given 'Dependency' has changed between versions 'last_version' and
@@ -304,29 +409,33 @@ impl IncrementalEngine {
             return Ok(DidDepsChange::NoDeps);
         }
 
-        let mut fs: FuturesUnordered<_> = deps
-            .iter()
-            .map(|dep| {
+        let mut fs = FuturesUnordered::new();
+        for dep in deps.iter() {
+            let cycles = match check_deps_state.cycles_for_dep(*dep, &eval) {
+                Ok(cycles) => cycles,
+                Err(_cycle) => {
+                    // requesting the dep would never finish. Recompute this key instead, which
+                    // reports the cycle to the key's computation.
+                    return Ok(DidDepsChange::Changed(*dep));
+                }
+            };
+            let dep = dep.dupe();
+            fs.push(
                 eval.per_live_version_ctx
-                    .compute_opaque(
-                        dep.dupe(),
-                        parent_key,
-                        &eval,
-                        check_deps_state.cycles_for_dep(*dep, &eval),
-                    )
-                    .map(|r| r.map(|v| v.history().get_verified_ranges()))
-            })
-            .collect();
+                    .compute_opaque(dep, parent_key, &eval, cycles)
+                    .map(move |r| r.map(|v| (dep, v.history().get_verified_ranges()))),
+            );
+        }
 
         let mut verified_versions = Cow::Borrowed(verified_versions);
 
         while let Some(dep_result) = fs.next().await {
             match dep_result {
-                Ok(dep_version_ranges) => {
+                Ok((dep, dep_version_ranges)) => {
                     verified_versions =
                         Cow::Owned(verified_versions.intersect(&dep_version_ranges));
                     if verified_versions.is_empty() {
-                        return Ok(DidDepsChange::Changed);
+                        return Ok(DidDepsChange::Changed(dep));
                     }
                 }
                 Err(Cancelled) => {
@@ -340,7 +449,8 @@ impl IncrementalEngine {
 }
 
 enum DidDepsChange {
-    Changed,
+    /// The given dep changed
+    Changed(DiceKey),
     /// These deps did not change
     NoChange(Arc<Vec<DiceKey>>),
     NoDeps,
@@ -372,7 +482,7 @@ pub(crate) mod testing {
     impl DidDepsChangeExt for DidDepsChange {
         fn is_changed(&self) -> bool {
             match self {
-                DidDepsChange::Changed => true,
+                DidDepsChange::Changed(_) => true,
                 DidDepsChange::NoChange(..) => false,
                 DidDepsChange::NoDeps => false,
             }
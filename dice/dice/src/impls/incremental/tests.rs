@@ -33,8 +33,11 @@ use more_futures::cancellation::CancellationContext;
 use sorted_vector_map::sorted_vector_set;
 
 use crate::api::computations::DiceComputations;
+use crate::api::cutoff::DiceCutoff;
+use crate::api::cycles::DetectCycles;
 use crate::api::data::DiceData;
 use crate::api::key::Key;
+use crate::api::priority::DicePriority;
 use crate::api::storage_type::StorageType;
 use crate::api::user_data::NoOpTracker;
 use crate::api::user_data::UserComputationData;
@@ -46,6 +49,7 @@ use crate::impls::core::state::StateRequest;
 use crate::impls::core::versions::VersionEpoch;
 use crate::impls::dice::DiceModern;
 use crate::impls::evaluator::AsyncEvaluator;
+use crate::impls::evaluator::EvalPriority;
 use crate::impls::events::DiceEventDispatcher;
 use crate::impls::incremental::testing::DidDepsChangeExt;
 use crate::impls::incremental::IncrementalEngine;
@@ -138,7 +142,7 @@ impl Key for Finish {
 
 #[tokio::test]
 async fn test_detecting_changed_dependencies() -> anyhow::Result<()> {
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
     let engine = IncrementalEngine::new(dice.state_handle.dupe(), VersionEpoch::testing_new(0));
 
     let user_data = std::sync::Arc::new(UserComputationData::new());
@@ -155,6 +159,7 @@ async fn test_detecting_changed_dependencies() -> anyhow::Result<()> {
         per_live_version_ctx: ctx.dupe(),
         user_data: user_data.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
 
     assert!(
@@ -185,6 +190,7 @@ async fn test_detecting_changed_dependencies() -> anyhow::Result<()> {
         per_live_version_ctx: ctx.dupe(),
         user_data: user_data.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
 
     assert!(
@@ -218,6 +224,7 @@ async fn test_detecting_changed_dependencies() -> anyhow::Result<()> {
         per_live_version_ctx: ctx.dupe(),
         user_data: user_data.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
 
     assert!(
@@ -241,7 +248,7 @@ async fn test_detecting_changed_dependencies() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn test_values_gets_reevaluated_when_deps_change() -> anyhow::Result<()> {
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
     let user_data = std::sync::Arc::new(UserComputationData::new());
     let events = DiceEventDispatcher::new(user_data.tracker.dupe(), dice.dupe());
@@ -263,8 +270,10 @@ async fn test_values_gets_reevaluated_when_deps_change() -> anyhow::Result<()> {
         key: VersionedGraphKey::new(VersionNumber::new(0), DiceKey { index: 100 }),
         epoch: ctx.testing_get_epoch(),
         storage: StorageType::LastN(1),
+        cutoff: DiceCutoff::Equality,
         value: DiceValidValue::testing_new(DiceKeyValue::<K>::new(1)),
         deps: Arc::new(vec![]),
+        compute_duration: None,
         resp: tx,
     });
     let (tx, _rx) = tokio::sync::oneshot::channel();
@@ -272,8 +281,10 @@ async fn test_values_gets_reevaluated_when_deps_change() -> anyhow::Result<()> {
         key: VersionedGraphKey::new(VersionNumber::new(0), key.dupe()),
         epoch: ctx.testing_get_epoch(),
         storage: StorageType::LastN(1),
+        cutoff: DiceCutoff::Equality,
         value: DiceValidValue::testing_new(DiceKeyValue::<IsRan>::new(())),
         deps: Arc::new(vec![DiceKey { index: 100 }]),
+        compute_duration: None,
         resp: tx,
     });
 
@@ -298,6 +309,7 @@ async fn test_values_gets_reevaluated_when_deps_change() -> anyhow::Result<()> {
         per_live_version_ctx: ctx.dupe(),
         user_data: user_data.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
 
     let task = IncrementalEngine::spawn_for_key(
@@ -339,6 +351,7 @@ async fn test_values_gets_reevaluated_when_deps_change() -> anyhow::Result<()> {
         per_live_version_ctx: ctx.dupe(),
         user_data: user_data.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
 
     let task = IncrementalEngine::spawn_for_key(
@@ -391,6 +404,7 @@ async fn test_values_gets_reevaluated_when_deps_change() -> anyhow::Result<()> {
         per_live_version_ctx: ctx.dupe(),
         user_data: user_data.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
 
     let task = IncrementalEngine::spawn_for_key(
@@ -417,7 +431,7 @@ async fn test_values_gets_reevaluated_when_deps_change() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn when_equal_return_same_instance() -> anyhow::Result<()> {
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
     let user_data = std::sync::Arc::new(UserComputationData::new());
     let events = DiceEventDispatcher::new(user_data.tracker.dupe(), dice.dupe());
@@ -481,6 +495,7 @@ async fn when_equal_return_same_instance() -> anyhow::Result<()> {
         per_live_version_ctx: ctx.dupe(),
         user_data: user_data.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
 
     let task = IncrementalEngine::spawn_for_key(
@@ -509,6 +524,7 @@ async fn when_equal_return_same_instance() -> anyhow::Result<()> {
         per_live_version_ctx: ctx.dupe(),
         user_data: user_data.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
 
     let task = IncrementalEngine::spawn_for_key(
@@ -552,7 +568,7 @@ async fn when_equal_return_same_instance() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn spawn_with_no_previously_cancelled_task() {
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
     let (shared_ctx, _guard) = dice.testing_shared_ctx(VersionNumber::new(0)).await;
 
@@ -564,6 +580,7 @@ async fn spawn_with_no_previously_cancelled_task() {
         per_live_version_ctx: shared_ctx.dupe(),
         user_data: extra.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
     let cycles = UserCycleDetectorData::testing_new();
     let events_dispatcher = DiceEventDispatcher::new(std::sync::Arc::new(NoOpTracker), dice.dupe());
@@ -591,7 +608,7 @@ async fn spawn_with_no_previously_cancelled_task() {
 
 #[tokio::test]
 async fn spawn_with_previously_cancelled_task_that_cancelled() {
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
     let (shared_ctx, _guard) = dice.testing_shared_ctx(VersionNumber::new(0)).await;
 
@@ -600,6 +617,7 @@ async fn spawn_with_previously_cancelled_task_that_cancelled() {
         per_live_version_ctx: shared_ctx.dupe(),
         user_data: extra.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
     let cycles = UserCycleDetectorData::testing_new();
     let events_dispatcher = DiceEventDispatcher::new(std::sync::Arc::new(NoOpTracker), dice.dupe());
@@ -665,7 +683,7 @@ async fn spawn_with_previously_cancelled_task_that_cancelled() {
 
 #[tokio::test]
 async fn spawn_with_previously_cancelled_task_that_finished() {
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
     let (shared_ctx, _guard) = dice.testing_shared_ctx(VersionNumber::new(0)).await;
 
@@ -674,6 +692,7 @@ async fn spawn_with_previously_cancelled_task_that_finished() {
         per_live_version_ctx: shared_ctx.dupe(),
         user_data: extra.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
     let cycles = UserCycleDetectorData::testing_new();
     let events_dispatcher = DiceEventDispatcher::new(std::sync::Arc::new(NoOpTracker), dice.dupe());
@@ -744,7 +763,7 @@ async fn spawn_with_previously_cancelled_task_that_finished() {
 
 #[tokio::test]
 async fn mismatch_epoch_results_in_cancelled_result() {
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
     let (shared_ctx, guard) = dice.testing_shared_ctx(VersionNumber::new(0)).await;
 
@@ -753,6 +772,7 @@ async fn mismatch_epoch_results_in_cancelled_result() {
         per_live_version_ctx: shared_ctx.dupe(),
         user_data: extra.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
     let cycles = UserCycleDetectorData::testing_new();
     let events_dispatcher = DiceEventDispatcher::new(std::sync::Arc::new(NoOpTracker), dice.dupe());
@@ -781,7 +801,7 @@ async fn mismatch_epoch_results_in_cancelled_result() {
 
 #[tokio::test]
 async fn spawn_with_previously_cancelled_task_nested_cancelled() -> anyhow::Result<()> {
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
     let (shared_ctx, _guard) = dice.testing_shared_ctx(VersionNumber::new(0)).await;
 
@@ -792,6 +812,7 @@ async fn spawn_with_previously_cancelled_task_nested_cancelled() -> anyhow::Resu
         per_live_version_ctx: shared_ctx.dupe(),
         user_data: extra.dupe(),
         dice: dice.dupe(),
+        priority: EvalPriority::Fixed(DicePriority::Normal),
     };
     let cycles = UserCycleDetectorData::testing_new();
     let events_dispatcher = DiceEventDispatcher::new(std::sync::Arc::new(NoOpTracker), dice.dupe());
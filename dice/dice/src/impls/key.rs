@@ -8,6 +8,7 @@
  */
 
 use std::any::Any;
+use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::hash::Hash;
@@ -23,6 +24,7 @@ use fnv::FnvHasher;
 use more_futures::cancellation::CancellationContext;
 
 use crate::api::computations::DiceComputations;
+use crate::api::cutoff::DiceCutoff;
 use crate::api::key::Key;
 use crate::api::projection::DiceProjectionComputations;
 use crate::api::projection::ProjectionKey;
@@ -31,6 +33,7 @@ use crate::impls::hash;
 use crate::impls::hash::key_hash;
 use crate::impls::value::DiceKeyValue;
 use crate::impls::value::DiceProjectValue;
+use crate::impls::value::DiceUserErrorValue;
 use crate::impls::value::DiceValueDyn;
 use crate::impls::value::MaybeValidDiceValue;
 
@@ -78,6 +81,13 @@ impl DiceKeyErased {
         }
     }
 
+    pub(crate) fn cheap_to_recompute(&self) -> bool {
+        match self {
+            DiceKeyErased::Key(k) => k.cheap_to_recompute(),
+            DiceKeyErased::Projection(_) => false,
+        }
+    }
+
     pub(crate) fn hash(&self) -> u64 {
         match self {
             DiceKeyErased::Key(k) => k.hash(),
@@ -136,6 +146,22 @@ impl PartialEq for DiceKeyErased {
 
 impl Eq for DiceKeyErased {}
 
+impl Hash for DiceKeyErased {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(DiceKeyErased::hash(self))
+    }
+}
+
+/// Formats as the user key, so that errors (e.g. cycles) show the keys the user knows about.
+impl Debug for DiceKeyErased {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiceKeyErased::Key(k) => Debug::fmt(k, f),
+            DiceKeyErased::Projection(proj) => Debug::fmt(&proj.proj, f),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Dupe, Display)]
 pub(crate) enum DiceKeyErasedRef<'a> {
     Key(&'a dyn DiceKeyDyn),
@@ -253,7 +279,7 @@ impl<'a> Display for CowDiceKeyHashed<'a> {
 }
 
 #[async_trait]
-pub(crate) trait DiceKeyDyn: Allocative + Display + Send + Sync + 'static {
+pub(crate) trait DiceKeyDyn: Allocative + Display + Debug + Send + Sync + 'static {
     async fn compute(
         &self,
         ctx: &DiceComputations,
@@ -271,6 +297,17 @@ pub(crate) trait DiceKeyDyn: Allocative + Display + Send + Sync + 'static {
     fn key_type_name(&self) -> &'static str;
 
     fn storage_type(&self) -> StorageType;
+
+    fn cutoff(&self) -> DiceCutoff;
+
+    fn cheap_to_recompute(&self) -> bool;
+
+    fn serialize_key(&self) -> Option<Vec<u8>>;
+
+    /// Panics if called with a value of another key type.
+    fn serialize_value(&self, value: &dyn DiceValueDyn) -> Option<Vec<u8>>;
+
+    fn deserialize_value(&self, bytes: &[u8]) -> Option<Arc<dyn DiceValueDyn>>;
 }
 
 #[async_trait]
@@ -283,8 +320,10 @@ where
         ctx: &DiceComputations,
         cancellations: &CancellationContext,
     ) -> Arc<dyn DiceValueDyn> {
-        let value = self.compute(ctx, cancellations).await;
-        Arc::new(DiceKeyValue::<K>::new(value))
+        match self.try_compute(ctx, cancellations).await {
+            Ok(value) => Arc::new(DiceKeyValue::<K>::new(value)),
+            Err(e) => Arc::new(DiceUserErrorValue(e)),
+        }
     }
 
     fn cmp_any(&self) -> PartialEqAny {
@@ -310,9 +349,35 @@ where
     fn storage_type(&self) -> StorageType {
         K::storage_type()
     }
+
+    fn cutoff(&self) -> DiceCutoff {
+        K::cutoff()
+    }
+
+    fn cheap_to_recompute(&self) -> bool {
+        K::cheap_to_recompute()
+    }
+
+    fn serialize_key(&self) -> Option<Vec<u8>> {
+        K::serialize_key(self)
+    }
+
+    fn serialize_value(&self, value: &dyn DiceValueDyn) -> Option<Vec<u8>> {
+        K::serialize_value(
+            value
+                .downcast_ref()
+                .expect("Type mismatch when persisting key"),
+        )
+    }
+
+    fn deserialize_value(&self, bytes: &[u8]) -> Option<Arc<dyn DiceValueDyn>> {
+        K::deserialize_value(bytes).map(|v| Arc::new(DiceKeyValue::<K>::new(v)) as _)
+    }
 }
 
-pub(crate) trait DiceProjectionDyn: Allocative + Display + Send + Sync + 'static {
+pub(crate) trait DiceProjectionDyn:
+    Allocative + Display + Debug + Send + Sync + 'static
+{
     fn compute(
         &self,
         derive_from: &MaybeValidDiceValue,
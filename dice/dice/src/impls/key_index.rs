@@ -7,8 +7,9 @@
  * of this source tree.
  */
 
-use std::array;
 use std::num::NonZeroU32;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 use allocative::Allocative;
 use lock_free_hashtable::raw::LockFreeRawTable;
@@ -20,12 +21,15 @@ use crate::impls::key::CowDiceKeyHashed;
 use crate::impls::key::DiceKey;
 use crate::impls::key::DiceKeyErased;
 use crate::impls::key::DiceKeyErasedRef;
+use crate::metrics::KeyIndexMetrics;
+use crate::metrics::Metrics;
 use crate::Key;
 
+/// Sized for the smallest number of shards, which store the most keys each.
 const KEY_BY_INDEX_BUCKETS: usize =
-    lock_free_vec::buckets_for_max_capacity(DiceKeyIndex::MAX_INDEX_IN_SHARD as usize + 1);
+    lock_free_vec::buckets_for_max_capacity((u32::MAX / DiceKeyIndex::MIN_SHARDS) as usize + 1);
 
-/// We bound each shard to only store up to u32 size entry. Together with `SHARDS`s shards, this
+/// We bound each shard to only store up to u32 size entry. Together with all the shards, this
 /// is capable to ~4 billion keys. After which point, it is probably too large for DICE to
 /// store in memory anyways.
 #[derive(Allocative, Default)]
@@ -34,6 +38,9 @@ struct Shard {
     key_by_index: LockFreeVec<DiceKeyErased, KEY_BY_INDEX_BUCKETS>,
     /// Mutex is used for updates. Lookups do not need to acquire the lock.
     mutex: Mutex<()>,
+    /// The number of updates that had to wait for the mutex
+    #[allocative(skip)]
+    contentions: AtomicU64,
 }
 
 impl Shard {
@@ -49,14 +56,25 @@ impl Shard {
             .map(|k| k.get() - 1)
     }
 
+    fn lock(&self) -> MutexGuard<()> {
+        match self.mutex.try_lock() {
+            Some(guard) => guard,
+            None => {
+                self.contentions.fetch_add(1, Ordering::Relaxed);
+                self.mutex.lock()
+            }
+        }
+    }
+
     fn insert_unique_unchecked(
         &self,
         _lock: &MutexGuard<()>,
         key: DiceKeyErased,
         hash: u64,
+        max_index: u32,
     ) -> u32 {
         assert!(
-            self.key_by_index.len() < DiceKeyIndex::MAX_INDEX_IN_SHARD as usize,
+            self.key_by_index.len() < max_index as usize,
             "too many dice keys"
         );
         let index = self.key_by_index.len() as u32;
@@ -78,32 +96,51 @@ impl Shard {
 
 #[derive(Allocative)]
 pub(crate) struct DiceKeyIndex {
-    shards: [Shard; DiceKeyIndex::SHARDS as usize],
+    shards: Box<[Shard]>,
 }
 
 impl Default for DiceKeyIndex {
     fn default() -> DiceKeyIndex {
-        DiceKeyIndex {
-            shards: array::from_fn(|_| Shard::default()),
-        }
+        DiceKeyIndex::new(DiceKeyIndex::DEFAULT_SHARDS)
     }
 }
 
 impl DiceKeyIndex {
-    pub(crate) const SHARDS: u32 = 64;
-    pub(crate) const MAX_INDEX_IN_SHARD: u32 = u32::MAX / DiceKeyIndex::SHARDS;
+    pub(crate) const DEFAULT_SHARDS: u32 = 64;
+    pub(crate) const MIN_SHARDS: u32 = 64;
+    pub(crate) const MAX_SHARDS: u32 = 4096;
+
+    /// The number of shards is rounded up to a power of two within `MIN_SHARDS..=MAX_SHARDS`.
+    pub(crate) fn new(shards: u32) -> DiceKeyIndex {
+        let shards = shards
+            .clamp(DiceKeyIndex::MIN_SHARDS, DiceKeyIndex::MAX_SHARDS)
+            .next_power_of_two();
+        DiceKeyIndex {
+            shards: (0..shards).map(|_| Shard::default()).collect(),
+        }
+    }
+
+    #[inline]
+    fn shard_count(&self) -> u32 {
+        self.shards.len() as u32
+    }
 
     #[inline]
-    fn shard_index_for_hash(hash: u64) -> u32 {
+    fn max_index_in_shard(&self) -> u32 {
+        u32::MAX / self.shard_count()
+    }
+
+    #[inline]
+    fn shard_index_for_hash(&self, hash: u64) -> u32 {
         // `LockFreeRawTable` uses low bits to select bucket.
         // So we should not use low bits as is to select shard.
-        (hash >> 32) as u32 % DiceKeyIndex::SHARDS
+        (hash >> 32) as u32 % self.shard_count()
     }
 
     pub(crate) fn index(&self, key: CowDiceKeyHashed) -> DiceKey {
         let hash = key.hash();
         let key = key.into_cow();
-        let shard_index = DiceKeyIndex::shard_index_for_hash(hash);
+        let shard_index = self.shard_index_for_hash(hash);
         let shard = &self.shards[shard_index as usize];
 
         // First try lookup without locking.
@@ -112,15 +149,20 @@ impl DiceKeyIndex {
                 shard_index,
                 index_in_shard,
             }
-            .pack();
+            .pack(self.shard_count());
         }
 
         // If not found, lock and try insert.
-        let guard = shard.mutex.lock();
+        let guard = shard.lock();
         let index_in_shard = if let Some(index_in_shard) = shard.get(key.borrow(), hash) {
             index_in_shard
         } else {
-            let index_in_shard = shard.insert_unique_unchecked(&guard, key.into_owned(), hash);
+            let index_in_shard = shard.insert_unique_unchecked(
+                &guard,
+                key.into_owned(),
+                hash,
+                self.max_index_in_shard(),
+            );
 
             trace!(
                 "{} ({}) maps to {:?}",
@@ -134,7 +176,7 @@ impl DiceKeyIndex {
                     shard_index,
                     index_in_shard,
                 }
-                .pack()
+                .pack(self.shard_count())
             );
 
             index_in_shard
@@ -143,7 +185,7 @@ impl DiceKeyIndex {
             shard_index,
             index_in_shard,
         }
-        .pack()
+        .pack(self.shard_count())
     }
 
     pub(crate) fn index_key<K: Key>(&self, key: K) -> DiceKey {
@@ -151,12 +193,26 @@ impl DiceKeyIndex {
     }
 
     pub(crate) fn get(&self, key: DiceKey) -> &DiceKeyErased {
-        let unpack = DiceKeyUnpacked::unpack(key);
+        let unpack = DiceKeyUnpacked::unpack(key, self.shard_count());
         self.shards[unpack.shard_index as usize]
             .key_by_index
             .get(unpack.index_in_shard as usize)
             .unwrap()
     }
+
+    pub(crate) fn add_to_metrics(&self, metrics: &mut Metrics) {
+        let key_counts = self.shards.iter().map(|shard| shard.key_by_index.len());
+        metrics.key_index = KeyIndexMetrics {
+            shard_count: self.shards.len(),
+            min_shard_key_count: key_counts.clone().min().unwrap_or_default(),
+            max_shard_key_count: key_counts.max().unwrap_or_default(),
+            lock_contention_count: self
+                .shards
+                .iter()
+                .map(|shard| shard.contentions.load(Ordering::Relaxed))
+                .sum(),
+        };
+    }
 }
 
 mod introspect {
@@ -177,7 +233,7 @@ mod introspect {
                             shard_index: shard_index as u32,
                             index_in_shard: index_in_shard as u32,
                         }
-                        .pack(),
+                        .pack(self.shard_count()),
                         key.introspect(),
                     );
                 }
@@ -194,17 +250,17 @@ struct DiceKeyUnpacked {
 }
 
 impl DiceKeyUnpacked {
-    fn pack(&self) -> DiceKey {
+    fn pack(&self, shards: u32) -> DiceKey {
         DiceKey {
-            index: self.shard_index + DiceKeyIndex::SHARDS * self.index_in_shard,
+            index: self.shard_index + shards * self.index_in_shard,
         }
     }
 
-    fn unpack(key: DiceKey) -> DiceKeyUnpacked {
+    fn unpack(key: DiceKey, shards: u32) -> DiceKeyUnpacked {
         let index = key.index;
         DiceKeyUnpacked {
-            shard_index: index % DiceKeyIndex::SHARDS,
-            index_in_shard: index / DiceKeyIndex::SHARDS,
+            shard_index: index % shards,
+            index_in_shard: index / shards,
         }
     }
 }
@@ -226,17 +282,27 @@ mod tests {
 
     #[test]
     fn test_max_index_in_shard() {
-        for i in 0..DiceKeyIndex::SHARDS {
-            let unpacked = DiceKeyUnpacked {
-                shard_index: i,
-                index_in_shard: DiceKeyIndex::MAX_INDEX_IN_SHARD,
-            };
-            let repack = DiceKeyUnpacked::unpack(unpacked.pack());
-            assert_eq!(repack.index_in_shard, unpacked.index_in_shard);
-            assert_eq!(repack.shard_index, unpacked.shard_index);
+        for shards in [DiceKeyIndex::MIN_SHARDS, DiceKeyIndex::MAX_SHARDS] {
+            let key_index = DiceKeyIndex::new(shards);
+            for i in 0..shards {
+                let unpacked = DiceKeyUnpacked {
+                    shard_index: i,
+                    index_in_shard: key_index.max_index_in_shard(),
+                };
+                let repack = DiceKeyUnpacked::unpack(unpacked.pack(shards), shards);
+                assert_eq!(repack.index_in_shard, unpacked.index_in_shard);
+                assert_eq!(repack.shard_index, unpacked.shard_index);
+            }
         }
     }
 
+    #[test]
+    fn shard_count_is_a_bounded_power_of_two() {
+        assert_eq!(64, DiceKeyIndex::new(1).shard_count());
+        assert_eq!(256, DiceKeyIndex::new(200).shard_count());
+        assert_eq!(4096, DiceKeyIndex::new(u32::MAX).shard_count());
+    }
+
     #[test]
     fn test() {
         #[derive(Hash, Clone, Copy, Dupe, Eq, PartialEq, Allocative, Display, Debug)]
@@ -262,7 +328,7 @@ mod tests {
         let key_index = DiceKeyIndex::default();
         let mut max_index = 0;
 
-        let mut seen_shards = [0; DiceKeyIndex::SHARDS as usize];
+        let mut seen_shards = [0; DiceKeyIndex::DEFAULT_SHARDS as usize];
 
         let mut i = 0;
         loop {
@@ -279,7 +345,8 @@ mod tests {
                     .0
             );
 
-            seen_shards[DiceKeyUnpacked::unpack(coin_key).shard_index as usize] += 1;
+            seen_shards[DiceKeyUnpacked::unpack(coin_key, key_index.shard_count()).shard_index
+                as usize] += 1;
             max_index = cmp::max(max_index, coin_key.index);
             if seen_shards.iter().all(|&x| x != 0) {
                 println!(
@@ -308,7 +375,8 @@ mod tests {
                     .0
             );
 
-            seen_shards[DiceKeyUnpacked::unpack(coin_key).shard_index as usize] += 1;
+            seen_shards[DiceKeyUnpacked::unpack(coin_key, key_index.shard_count()).shard_index
+                as usize] += 1;
             max_index = cmp::max(max_index, coin_key.index);
             i += 1;
         }
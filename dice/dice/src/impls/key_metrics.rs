@@ -0,0 +1,222 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Collects the per key type statistics reported in `Metrics`
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use allocative::Allocative;
+use dashmap::DashMap;
+
+use crate::api::priority::DicePriority;
+use crate::metrics::KeyTypeMetrics;
+use crate::metrics::Metrics;
+use crate::metrics::QueueDelayMetrics;
+
+/// Recorded on every request, so the counters are updated without locking: the map is only
+/// written to the first time a key type is seen.
+#[derive(Allocative, Default)]
+pub(crate) struct KeyTypeMetricsRecorder {
+    #[allocative(skip)]
+    by_type: DashMap<&'static str, KeyTypeCounters>,
+    /// Indexed by `DicePriority as usize`
+    #[allocative(skip)]
+    queue_delay_by_priority: [RecentDelays; 3],
+}
+
+#[derive(Default)]
+struct KeyTypeCounters {
+    computes: AtomicU64,
+    cache_hits: AtomicU64,
+    cancellations: AtomicU64,
+    total_compute_nanos: AtomicU64,
+    max_compute_nanos: AtomicU64,
+    compute_time_histogram: [AtomicU64; 6],
+    queue_delay: RecentDelays,
+}
+
+impl KeyTypeCounters {
+    fn record_compute(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.computes.fetch_add(1, Ordering::Relaxed);
+        self.total_compute_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.max_compute_nanos.fetch_max(nanos, Ordering::Relaxed);
+        self.compute_time_histogram[KeyTypeMetrics::histogram_bucket(duration)]
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn to_metrics(&self) -> KeyTypeMetrics {
+        KeyTypeMetrics {
+            computes: self.computes.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cancellations: self.cancellations.load(Ordering::Relaxed),
+            total_compute_time: Duration::from_nanos(
+                self.total_compute_nanos.load(Ordering::Relaxed),
+            ),
+            max_compute_time: Duration::from_nanos(self.max_compute_nanos.load(Ordering::Relaxed)),
+            compute_time_histogram: std::array::from_fn(|i| {
+                self.compute_time_histogram[i].load(Ordering::Relaxed)
+            }),
+            queue_delay: self.queue_delay.to_metrics(),
+        }
+    }
+}
+
+/// The most recent delays, which percentiles are computed from. Written round-robin, so that
+/// recording never waits for other threads.
+struct RecentDelays {
+    count: AtomicU64,
+    /// Nanoseconds
+    samples: [AtomicU64; QueueDelayMetrics::SAMPLES],
+}
+
+impl Default for RecentDelays {
+    fn default() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            samples: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl RecentDelays {
+    fn record(&self, delay: Duration) {
+        let nanos = u64::try_from(delay.as_nanos()).unwrap_or(u64::MAX);
+        let i = self.count.fetch_add(1, Ordering::Relaxed) as usize % QueueDelayMetrics::SAMPLES;
+        self.samples[i].store(nanos, Ordering::Relaxed);
+    }
+
+    fn to_metrics(&self) -> QueueDelayMetrics {
+        let count = self.count.load(Ordering::Relaxed);
+        let sampled = (count as usize).min(QueueDelayMetrics::SAMPLES);
+        QueueDelayMetrics::from_samples(
+            count,
+            self.samples[..sampled]
+                .iter()
+                .map(|nanos| Duration::from_nanos(nanos.load(Ordering::Relaxed))),
+        )
+    }
+}
+
+impl KeyTypeMetricsRecorder {
+    fn with<R>(&self, key_type: &'static str, f: impl FnOnce(&KeyTypeCounters) -> R) -> R {
+        if let Some(counters) = self.by_type.get(key_type) {
+            return f(&counters);
+        }
+        f(&self.by_type.entry(key_type).or_default())
+    }
+
+    pub(crate) fn record_compute(&self, key_type: &'static str, duration: Duration) {
+        self.with(key_type, |c| c.record_compute(duration))
+    }
+
+    pub(crate) fn record_cache_hit(&self, key_type: &'static str) {
+        self.with(key_type, |c| c.cache_hits.fetch_add(1, Ordering::Relaxed));
+    }
+
+    pub(crate) fn record_cancellation(&self, key_type: &'static str) {
+        self.with(key_type, |c| {
+            c.cancellations.fetch_add(1, Ordering::Relaxed)
+        });
+    }
+
+    /// Records how long a request waited between being queued and its computation starting.
+    pub(crate) fn record_queue_delay(
+        &self,
+        key_type: &'static str,
+        priority: DicePriority,
+        delay: Duration,
+    ) {
+        self.with(key_type, |c| c.queue_delay.record(delay));
+        self.queue_delay_by_priority[priority as usize].record(delay);
+    }
+
+    pub(crate) fn add_to_metrics(&self, metrics: &mut Metrics) {
+        metrics.key_types = self
+            .by_type
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().to_metrics()))
+            .collect();
+        metrics.queue_delay_by_priority = [
+            DicePriority::Background,
+            DicePriority::Normal,
+            DicePriority::Interactive,
+        ]
+        .into_iter()
+        .zip(&self.queue_delay_by_priority)
+        .filter(|(_, delays)| delays.count.load(Ordering::Relaxed) > 0)
+        .map(|(priority, delays)| (priority, delays.to_metrics()))
+        .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::api::priority::DicePriority;
+    use crate::impls::key_metrics::KeyTypeMetricsRecorder;
+    use crate::metrics::QueueDelayMetrics;
+
+    #[test]
+    fn compute_times_are_bucketed() {
+        let recorder = KeyTypeMetricsRecorder::default();
+        recorder.record_compute("Foo", Duration::from_micros(10));
+        recorder.record_compute("Foo", Duration::from_millis(50));
+        recorder.record_compute("Foo", Duration::from_secs(30));
+        recorder.record_cache_hit("Foo");
+        recorder.record_cancellation("Bar");
+
+        let metrics = recorder.by_type.get("Foo").unwrap().to_metrics();
+        assert_eq!(3, metrics.computes);
+        assert_eq!(1, metrics.cache_hits);
+        assert_eq!(Duration::from_secs(30), metrics.max_compute_time);
+        assert_eq!([1, 0, 1, 0, 0, 1], metrics.compute_time_histogram);
+        assert_eq!(
+            Some(Duration::from_micros(10_016_670)),
+            metrics.mean_compute_time()
+        );
+
+        let metrics = recorder.by_type.get("Bar").unwrap().to_metrics();
+        assert_eq!(0, metrics.computes);
+        assert_eq!(1, metrics.cancellations);
+    }
+
+    #[test]
+    fn queue_delays_keep_recent_samples() {
+        let recorder = KeyTypeMetricsRecorder::default();
+        for _ in 0..QueueDelayMetrics::SAMPLES {
+            recorder.record_queue_delay("Foo", DicePriority::Background, Duration::from_secs(1));
+        }
+        for _ in 0..QueueDelayMetrics::SAMPLES {
+            recorder.record_queue_delay("Foo", DicePriority::Background, Duration::from_millis(1));
+        }
+
+        let delays = recorder
+            .by_type
+            .get("Foo")
+            .unwrap()
+            .to_metrics()
+            .queue_delay;
+        assert_eq!(2 * QueueDelayMetrics::SAMPLES as u64, delays.count);
+        assert_eq!(Duration::from_millis(1), delays.max);
+
+        let delays =
+            recorder.queue_delay_by_priority[DicePriority::Background as usize].to_metrics();
+        assert_eq!(Duration::from_millis(1), delays.max);
+        assert_eq!(
+            0,
+            recorder.queue_delay_by_priority[DicePriority::Interactive as usize]
+                .to_metrics()
+                .count
+        );
+    }
+}
@@ -10,6 +10,7 @@
 pub(crate) mod cache;
 pub(crate) mod core;
 pub(crate) mod ctx;
+pub(crate) mod cycles;
 mod dep_trackers;
 pub(crate) mod dice;
 pub(crate) mod evaluator;
@@ -18,11 +19,13 @@ mod hash;
 pub(crate) mod incremental;
 pub(crate) mod key;
 mod key_index;
+mod key_metrics;
 pub(crate) mod opaque;
 pub(crate) mod task;
 #[cfg(test)]
 mod tests;
 pub(crate) mod transaction;
+mod transient;
 pub(crate) mod user_cycle;
 pub(crate) mod value;
 pub(crate) mod worker;
@@ -16,6 +16,7 @@ use crate::api::error::DiceResult;
 use crate::api::key::Key;
 use crate::api::projection::ProjectionKey;
 use crate::impls::ctx::PerComputeCtx;
+use crate::impls::dep_trackers::RecordingDepsTracker;
 use crate::impls::key::DiceKey;
 use crate::impls::value::MaybeValidDiceValue;
 
@@ -60,9 +61,17 @@ where
 
     /// Get a value and record parent computation dependency on `K`.
     pub(crate) fn into_value(self) -> K::Value {
-        self.parent_computation
-            .dep_trackers()
-            .record(self.derive_from_key, self.derive_from.validity());
+        let parent_computation = self.parent_computation;
+        self.into_value_recorded_in(&mut parent_computation.dep_trackers())
+    }
+
+    /// Get a value and record parent computation dependency on `K` in the already locked deps of
+    /// the parent computation.
+    pub(crate) fn into_value_recorded_in(
+        self,
+        dep_trackers: &mut RecordingDepsTracker,
+    ) -> K::Value {
+        dep_trackers.record(self.derive_from_key, self.derive_from.validity());
 
         self.derive_from
             .downcast_maybe_transient::<K::Value>()
@@ -80,6 +89,7 @@ mod tests {
     use derive_more::Display;
     use more_futures::cancellation::CancellationContext;
 
+    use crate::api::cycles::DetectCycles;
     use crate::api::data::DiceData;
     use crate::api::key::Key;
     use crate::impls::dep_trackers::testing::RecordingDepsTrackersExt;
@@ -114,7 +124,7 @@ mod tests {
 
     #[tokio::test]
     async fn opaque_records_deps_when_used() {
-        let dice = DiceModern::new(DiceData::new());
+        let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
         let ctx = dice.updater().commit().await;
 
@@ -27,6 +27,7 @@ use parking_lot::Mutex;
 use parking_lot::MutexGuard;
 use slab::Slab;
 use tokio::sync::oneshot;
+use tokio::time::Instant;
 
 use crate::arc::Arc;
 use crate::impls::key::DiceKey;
@@ -231,6 +232,15 @@ impl DiceTask {
         self.cancellations.cancel(&lock);
     }
 
+    /// When the task was cancelled, if it was cancelled but is still running.
+    pub(crate) fn cancelled_while_running(&self) -> Option<Instant> {
+        if !self.is_pending() {
+            return None;
+        }
+        let lock = self.internal.critical.lock();
+        self.cancellations.cancelled_at(&lock)
+    }
+
     pub(crate) fn await_termination(&self) -> TerminationObserver {
         if let Some(_result) = self.internal.read_value() {
             TerminationObserver(TerminationObserverInternal::Done)
@@ -392,7 +402,8 @@ pub(super) struct Cancellations {
 
 enum CancellationsInternal {
     NotCancelled(CancellationHandle),
-    Cancelled,
+    /// Cancelled at the given time
+    Cancelled(Instant),
 }
 
 impl Cancellations {
@@ -418,7 +429,7 @@ impl Cancellations {
                 |internal| match internal {
                     CancellationsInternal::NotCancelled(handle) => {
                         handle.cancel();
-                        CancellationsInternal::Cancelled
+                        CancellationsInternal::Cancelled(Instant::now())
                     }
                     cancelled => cancelled,
                 },
@@ -426,14 +437,21 @@ impl Cancellations {
         };
     }
 
-    pub(super) fn is_cancelled(&self, _lock: &MutexGuard<DiceTaskInternalCritical>) -> bool {
-        self.internal.as_ref().map_or(false, |internal| {
+    pub(super) fn is_cancelled(&self, lock: &MutexGuard<DiceTaskInternalCritical>) -> bool {
+        self.cancelled_at(lock).is_some()
+    }
+
+    pub(super) fn cancelled_at(
+        &self,
+        _lock: &MutexGuard<DiceTaskInternalCritical>,
+    ) -> Option<Instant> {
+        self.internal.as_ref().and_then(|internal| {
             match unsafe {
                 // SAFETY: locked by the MutexGuard of Slab
                 &*internal.get()
             } {
-                CancellationsInternal::NotCancelled(_) => false,
-                CancellationsInternal::Cancelled => true,
+                CancellationsInternal::NotCancelled(_) => None,
+                CancellationsInternal::Cancelled(at) => Some(*at),
             }
         })
     }
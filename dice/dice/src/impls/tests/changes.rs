@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::impls::dice::DiceModern;
+use crate::introspection::changes::VersionChanges;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Input(u32);
+
+impl InjectedKey for Input {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Sum;
+
+#[async_trait]
+impl Key for Sum {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        ctx.compute(&Input(0)).await.unwrap() + ctx.compute(&Input(1)).await.unwrap()
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+fn changes(changed: &[&str], invalidated: &[&str]) -> Option<VersionChanges> {
+    Some(VersionChanges {
+        changed: changed.iter().map(|k| (*k).to_owned()).collect(),
+        invalidated: invalidated.iter().map(|k| (*k).to_owned()).collect(),
+    })
+}
+
+#[tokio::test]
+async fn changes_between_reports_changed_and_invalidated_keys() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 1), (Input(1), 1)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&Sum).await?;
+    let first = ctx.get_version();
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 2)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&Sum).await?;
+    let second = ctx.get_version();
+    drop(ctx);
+
+    // changing to an equal value is not a change
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 2), (Input(1), 3)])?;
+    let ctx = updater.commit().await;
+    let third = ctx.get_version();
+
+    assert_eq!(
+        changes(&["Input(0)"], &["Sum"]),
+        dice.changes_between(first, second).await
+    );
+    assert_eq!(
+        changes(&["Input(1)"], &["Sum"]),
+        dice.changes_between(second, third).await
+    );
+    assert_eq!(
+        changes(&["Input(0)", "Input(1)"], &["Sum"]),
+        dice.changes_between(first, third).await
+    );
+    assert_eq!(changes(&[], &[]), dice.changes_between(third, third).await);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn preview_reports_changes_without_committing_them() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 1), (Input(1), 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(2, ctx.compute(&Sum).await?);
+    let before = ctx.get_version();
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 1)])?;
+    assert_eq!(changes(&[], &[]).unwrap(), updater.preview().await);
+    drop(updater);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 5)])?;
+    updater.changed([Input(2)])?;
+    assert_eq!(
+        changes(&["Input(0)", "Input(2)"], &["Sum"]).unwrap(),
+        updater.preview().await
+    );
+    drop(updater);
+
+    // nothing was committed, so the graph is as it was
+    let ctx = dice.updater().existing_state().await;
+    assert_eq!(before, ctx.get_version());
+    assert_eq!(2, ctx.compute(&Sum).await?);
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cutoff::DiceCutoff;
+use crate::api::cycles::DetectCycles;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::impls::dice::DiceModern;
+use crate::introspection::snapshot::GraphSnapshot;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Input;
+
+impl InjectedKey for Input {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Unrelated;
+
+impl InjectedKey for Unrelated {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// Always computes the same value, but depends on `Input`
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Constant;
+
+#[async_trait]
+impl Key for Constant {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        ctx.compute(&Input).await.unwrap();
+        0
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+
+    fn cutoff() -> DiceCutoff {
+        DiceCutoff::Version
+    }
+}
+
+/// Reads state that DICE doesn't track
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "Untracked")]
+struct Untracked(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>);
+
+#[async_trait]
+impl Key for Untracked {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        _ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.0.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+
+    fn cutoff() -> DiceCutoff {
+        DiceCutoff::AlwaysInvalidate
+    }
+}
+
+/// Counts how often it is computed
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "Counting")]
+struct Counting(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>);
+
+#[async_trait]
+impl Key for Counting {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        ctx.compute(&Constant).await.unwrap()
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[tokio::test]
+async fn version_cutoff_recomputes_dependents_of_equal_values() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let computed = Arc::new(AtomicU32::new(0));
+    let counting = Counting(computed.dupe());
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, 1)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&counting).await?;
+    assert_eq!(1, computed.load(Ordering::SeqCst));
+    drop(ctx);
+
+    // `Constant` recomputes to an equal value, but still changes
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, 2)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&counting).await?;
+    assert_eq!(2, computed.load(Ordering::SeqCst));
+    drop(ctx);
+
+    // reusing `Constant` without recomputing it is not a change
+    let mut updater = dice.updater();
+    updater.changed_to([(Unrelated, 1)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&counting).await?;
+    assert_eq!(2, computed.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn always_invalidate_recomputes_on_every_commit() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let untracked = Untracked(Arc::new(AtomicU32::new(0)));
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Unrelated, 0)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(0, ctx.compute(&untracked).await?);
+    assert_eq!(0, ctx.compute(&untracked).await?);
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Unrelated, 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(1, ctx.compute(&untracked).await?);
+    drop(ctx);
+
+    // nothing changed, so the version doesn't advance
+    let ctx = dice.updater().commit().await;
+    assert_eq!(1, ctx.compute(&untracked).await?);
+
+    let snapshot = GraphSnapshot::capture(&dice.to_introspectable());
+    assert_eq!(
+        Some(DiceCutoff::AlwaysInvalidate),
+        snapshot.get("Untracked").unwrap().cutoff
+    );
+    assert_eq!(
+        Some(DiceCutoff::Equality),
+        snapshot.get("Unrelated").unwrap().cutoff
+    );
+
+    Ok(())
+}
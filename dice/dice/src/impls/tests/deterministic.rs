@@ -0,0 +1,155 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+use parking_lot::Mutex;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::dice::Dice;
+use crate::api::dice::DiceDataBuilder;
+use crate::api::key::Key;
+
+/// Runs the DICE state and all computations on a single thread with virtual time, so that
+/// tests of incrementality see the same ordering of tasks on every run, and timers such as
+/// `Key::timeout` or retry backoffs elapse as soon as nothing else can make progress.
+///
+/// `Dice::metrics` and introspection block waiting for the state, so they can't be used with
+/// a DICE built by this runtime. Only supported by modern DICE.
+pub(crate) struct DeterministicRuntime {
+    runtime: tokio::runtime::Runtime,
+}
+
+impl DeterministicRuntime {
+    pub(crate) fn new() -> Self {
+        Self {
+            runtime: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .start_paused(true)
+                .build()
+                .expect("failed to build the deterministic runtime"),
+        }
+    }
+
+    /// Builds a DICE whose state is processed by this runtime.
+    pub(crate) fn build(
+        &self,
+        mut builder: DiceDataBuilder,
+        detect_cycles: DetectCycles,
+    ) -> Arc<Dice> {
+        let _guard = self.runtime.enter();
+        builder.set_state_on_current_runtime();
+        builder.build(detect_cycles)
+    }
+
+    /// Runs the future, along with all the computations it starts, to completion.
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Finishes after the given number of minutes
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Sleep({})", _0)]
+struct Sleep(
+    u64,
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")]
+    #[allocative(skip)]
+    Log,
+);
+
+#[async_trait]
+impl Key for Sleep {
+    type Value = u64;
+
+    async fn compute(
+        &self,
+        _ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        tokio::time::sleep(Duration::from_secs(self.0 * 60)).await;
+        self.1.lock().push(self.to_string());
+        self.0
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Sum")]
+struct Sum(
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")]
+    #[allocative(skip)]
+    Log,
+);
+
+#[async_trait]
+impl Key for Sum {
+    type Value = u64;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        let keys = [3, 1, 2].map(|n| Sleep(n, self.0.dupe()));
+        let mut sum = 0;
+        for v in ctx.compute_many(keys.iter()).await {
+            sum += v.unwrap();
+        }
+        self.0.lock().push(self.to_string());
+        sum
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+fn run() -> (Vec<String>, Duration) {
+    let runtime = DeterministicRuntime::new();
+    let dice = runtime.build(Dice::modern(), DetectCycles::Enabled);
+    let log = Log::default();
+
+    let elapsed = runtime.block_on(async {
+        let start = tokio::time::Instant::now();
+        let ctx = dice.updater().commit().await;
+        assert_eq!(6, ctx.compute(&Sum(log.dupe())).await.unwrap());
+        start.elapsed()
+    });
+
+    let log = log.lock().clone();
+    (log, elapsed)
+}
+
+#[test]
+fn deterministic_runtime_is_repeatable_with_virtual_time() {
+    let (log, elapsed) = run();
+    assert_eq!(vec!["Sleep(1)", "Sleep(2)", "Sleep(3)", "Sum"], log);
+    // the sleeps run concurrently in virtual time
+    assert!(elapsed >= Duration::from_secs(3 * 60));
+    assert!(elapsed < Duration::from_secs(4 * 60));
+
+    assert_eq!((log, elapsed), run());
+}
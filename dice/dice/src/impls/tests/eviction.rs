@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::eviction::DiceEvictionPolicy;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::impls::dice::DiceModern;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+#[display(fmt = "{:?}", self)]
+struct Input(u8);
+
+impl InjectedKey for Input {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// How many times each key was computed.
+#[derive(Debug, Default, Allocative)]
+struct Computed {
+    top: AtomicUsize,
+    mid: AtomicUsize,
+}
+
+impl Computed {
+    fn get(&self) -> (usize, usize) {
+        (
+            self.top.load(Ordering::SeqCst),
+            self.mid.load(Ordering::SeqCst),
+        )
+    }
+}
+
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "{:?}", self)]
+struct Mid(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<Computed>);
+
+#[async_trait]
+impl Key for Mid {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.0.mid.fetch_add(1, Ordering::SeqCst);
+        ctx.compute(&Input(0)).await.unwrap() + 1
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "{:?}", self)]
+struct Top(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<Computed>);
+
+#[async_trait]
+impl Key for Top {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.0.top.fetch_add(1, Ordering::SeqCst);
+        // so that `Top` is always more expensive to recompute than `Mid`
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        ctx.compute(&Mid(self.0.dupe())).await.unwrap() + 1
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+fn dice_with_policy(policy: DiceEvictionPolicy) -> Arc<DiceModern> {
+    let mut builder = DiceModern::builder();
+    builder.set_eviction_policy(policy);
+    builder.build(DetectCycles::Enabled)
+}
+
+#[tokio::test]
+async fn keys_within_budget_are_kept() -> anyhow::Result<()> {
+    let dice = dice_with_policy(DiceEvictionPolicy::MaxKeys(10));
+    let computed = Arc::new(Computed::default());
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(3, ctx.compute(&Top(computed.dupe())).await?);
+    assert_eq!((1, 1), computed.get());
+    drop(ctx);
+
+    // an unrelated change moves to a new version, where the cached values are reused
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(1), 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(3, ctx.compute(&Top(computed.dupe())).await?);
+    assert_eq!((1, 1), computed.get());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn evicted_keys_are_recomputed() -> anyhow::Result<()> {
+    let dice = dice_with_policy(DiceEvictionPolicy::MaxKeys(1));
+    let computed = Arc::new(Computed::default());
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(3, ctx.compute(&Top(computed.dupe())).await?);
+    assert_eq!((1, 1), computed.get());
+    drop(ctx);
+
+    // only one of `Top` and `Mid` fits: the cheaper `Mid` is evicted, and `Top` along with it
+    // since it depends on it, so both are recomputed
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(1), 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(3, ctx.compute(&Top(computed.dupe())).await?);
+    assert_eq!((2, 2), computed.get());
+    drop(ctx);
+
+    // injected values are never evicted, and changes to them are still seen
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 5)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(7, ctx.compute(&Top(computed.dupe())).await?);
+    assert_eq!((3, 3), computed.get());
+    assert_eq!(5, ctx.compute(&Input(0)).await?);
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+
+use allocative::Allocative;
+use derive_more::Display;
+use dupe::Dupe;
+
+use crate::api::cycles::DetectCycles;
+use crate::api::gc::DiceGcPolicy;
+use crate::api::injected::InjectedKey;
+use crate::impls::dice::DiceModern;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Input;
+
+impl InjectedKey for Input {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+async fn inject(dice: &Arc<DiceModern>, values: impl IntoIterator<Item = u32>) {
+    for value in values {
+        let mut updater = dice.updater();
+        updater.changed_to([(Input, value)]).unwrap();
+        drop(updater.commit().await);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manual_gc_reclaims_superseded_versions() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+
+    inject(&dice, [1, 2, 3]).await;
+    assert_eq!(0, dice.metrics().gc_count);
+
+    assert_eq!(2, dice.gc().await);
+    assert_eq!(0, dice.gc().await);
+
+    let metrics = dice.metrics();
+    assert_eq!(2, metrics.gc_count);
+    assert_eq!(2, metrics.gc_reclaimed_node_count);
+
+    let ctx = dice.updater().commit().await;
+    assert_eq!(3, ctx.compute(&Input).await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn gc_keeps_versions_in_use() -> anyhow::Result<()> {
+    let mut builder = DiceModern::builder();
+    builder.set_gc_policy(DiceGcPolicy::OnCommit);
+    let dice = builder.build(DetectCycles::Enabled);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, 1)])?;
+    let old_ctx = updater.commit().await;
+
+    inject(&dice, [2, 3]).await;
+    assert_eq!(3, dice.metrics().gc_count);
+    assert_eq!(0, dice.metrics().gc_reclaimed_node_count);
+    assert_eq!(1, old_ctx.compute(&Input).await?);
+
+    drop(old_ctx);
+    assert_eq!(2, dice.gc().await);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn gc_on_idle() -> anyhow::Result<()> {
+    let mut builder = DiceModern::builder();
+    builder.set_gc_policy(DiceGcPolicy::OnIdle);
+    let dice = builder.build(DetectCycles::Enabled);
+
+    inject(&dice, [1, 2, 3]).await;
+
+    let metrics = dice.metrics();
+    assert_eq!(3, metrics.gc_count);
+    assert_eq!(2, metrics.gc_reclaimed_node_count);
+
+    Ok(())
+}
@@ -8,10 +8,12 @@
  */
 
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Barrier;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use allocative::Allocative;
 use assert_matches::assert_matches;
@@ -24,9 +26,11 @@ use tokio::sync::oneshot;
 
 use crate::api::computations::DiceComputations;
 use crate::api::cycles::DetectCycles;
+use crate::api::error::DiceError;
 use crate::api::error::DiceErrorImpl;
 use crate::api::injected::InjectedKey;
 use crate::api::key::Key;
+use crate::api::priority::DicePriority;
 use crate::api::user_data::UserComputationData;
 use crate::impls::dice::DiceModern;
 use crate::versions::VersionNumber;
@@ -305,7 +309,7 @@ fn invalid_update() {
         }
     }
 
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
     let mut updater = dice.updater();
 
     assert!(updater.changed_to([(Invalid, ())]).is_err());
@@ -699,3 +703,381 @@ async fn test_dice_usable_after_cancellations() {
 
     assert!(is_ran.load(Ordering::SeqCst));
 }
+
+#[tokio::test]
+async fn cancelled_computations_are_reported_until_terminated() {
+    #[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+    #[derivative(Hash, PartialEq, Eq)]
+    #[display(fmt = "Uncancellable")]
+    #[allocative(skip)]
+    struct Uncancellable {
+        #[derivative(Hash = "ignore", PartialEq = "ignore")]
+        started: Arc<tokio::sync::Semaphore>,
+        #[derivative(Hash = "ignore", PartialEq = "ignore")]
+        finish: Arc<tokio::sync::Semaphore>,
+    }
+
+    #[async_trait]
+    impl Key for Uncancellable {
+        type Value = ();
+
+        async fn compute(
+            &self,
+            _ctx: &DiceComputations,
+            cancellations: &CancellationContext,
+        ) -> Self::Value {
+            cancellations
+                .critical_section(|| async move {
+                    self.started.add_permits(1);
+                    let _s = self.finish.acquire().await.unwrap();
+                })
+                .await
+        }
+
+        fn equality(_x: &Self::Value, _y: &Self::Value) -> bool {
+            true
+        }
+    }
+
+    let dice = DiceModern::builder().build(DetectCycles::Disabled);
+    let key = Uncancellable {
+        started: Arc::new(tokio::sync::Semaphore::new(0)),
+        finish: Arc::new(tokio::sync::Semaphore::new(0)),
+    };
+
+    let ctx = dice.updater().commit().await;
+    let req = ctx.compute(&key);
+
+    // ensure that the key starts computing
+    let _s = key.started.acquire().await.unwrap();
+    assert!(dice.cancelled_computations().await.is_empty());
+
+    drop(req);
+    drop(ctx);
+
+    let cancelled = dice.cancelled_computations().await;
+    assert_eq!(1, cancelled.len());
+    assert_eq!(format!("{:?}", key), cancelled[0].key);
+
+    key.finish.add_permits(1);
+    dice.wait_for_idle().await;
+    assert!(dice.cancelled_computations().await.is_empty());
+}
+
+#[derive(Clone, Dupe, Debug, Display, Eq, Hash, PartialEq, Allocative)]
+#[display(fmt = "{:?}", self)]
+struct CycleKey(u8);
+
+#[async_trait]
+impl Key for CycleKey {
+    type Value = Result<(), DiceError>;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        // 0 -> 1 -> 2 -> 1
+        let next = match self.0 {
+            0 => 1,
+            1 => 2,
+            _ => 1,
+        };
+        ctx.compute(&CycleKey(next)).await??;
+        Ok(())
+    }
+
+    fn equality(_: &Self::Value, _: &Self::Value) -> bool {
+        false
+    }
+}
+
+#[tokio::test]
+async fn cycles_are_detected() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let ctx = dice.updater().commit().await;
+
+    let err = ctx.compute(&CycleKey(0)).await?.unwrap_err();
+    assert_matches!(
+        &*err.0,
+        DiceErrorImpl::Cycle { trigger, cyclic_keys } => {
+            assert_eq!("CycleKey(1)", trigger.to_string());
+            assert_eq!(
+                vec!["CycleKey(0)", "CycleKey(1)", "CycleKey(2)"],
+                cyclic_keys.iter().map(|k| format!("{:?}", k)).collect::<Vec<_>>()
+            );
+        }
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn metrics_are_tracked_per_key_type() -> anyhow::Result<()> {
+    #[derive(Clone, Dupe, Debug, Display, Eq, Hash, PartialEq, Allocative)]
+    #[display(fmt = "{:?}", self)]
+    struct Doubled(i32);
+
+    #[async_trait]
+    impl Key for Doubled {
+        type Value = i32;
+
+        async fn compute(
+            &self,
+            ctx: &DiceComputations,
+            _cancellations: &CancellationContext,
+        ) -> Self::Value {
+            ctx.compute(&Foo(self.0)).await.unwrap() * 2
+        }
+
+        fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+            x == y
+        }
+    }
+
+    let dice = DiceModern::builder().build(DetectCycles::Disabled);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Foo(1), 1), (Foo(2), 2)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(2, ctx.compute(&Doubled(1)).await?);
+    assert_eq!(4, ctx.compute(&Doubled(2)).await?);
+    drop(ctx);
+
+    // at a new version, `Doubled(1)` is reused after checking its deps, while `Doubled(2)` is
+    // recomputed
+    let mut updater = dice.updater();
+    updater.changed_to([(Foo(2), 3)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(2, ctx.compute(&Doubled(1)).await?);
+    assert_eq!(6, ctx.compute(&Doubled(2)).await?);
+
+    let mut all_metrics = dice.metrics();
+    let metrics = all_metrics.key_types.remove("Doubled").unwrap();
+    assert_eq!(3, metrics.computes);
+    assert_eq!(1, metrics.cache_hits);
+    assert_eq!(0, metrics.cancellations);
+    assert_eq!(3, metrics.compute_time_histogram.iter().sum::<u64>());
+    assert!(metrics.mean_compute_time().unwrap() <= metrics.max_compute_time);
+
+    // every request waited to be looked up, all at normal priority
+    assert_eq!(4, metrics.queue_delay.count);
+    assert!(metrics.queue_delay.p50 <= metrics.queue_delay.max);
+    assert_eq!(
+        vec![DicePriority::Normal],
+        all_metrics
+            .queue_delay_by_priority
+            .keys()
+            .copied()
+            .collect::<Vec<_>>()
+    );
+    assert!(all_metrics.queue_delay_by_priority[&DicePriority::Normal].count >= 4);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memory_usage_reports_retained_bytes() -> anyhow::Result<()> {
+    #[derive(Clone, Dupe, Debug, Display, Eq, Hash, PartialEq, Allocative)]
+    #[display(fmt = "{:?}", self)]
+    struct Buffer;
+
+    #[async_trait]
+    impl Key for Buffer {
+        type Value = Arc<Vec<u8>>;
+
+        async fn compute(
+            &self,
+            ctx: &DiceComputations,
+            _cancellations: &CancellationContext,
+        ) -> Self::Value {
+            Arc::new(vec![0; ctx.compute(&Foo(0)).await.unwrap() as usize])
+        }
+
+        fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+            x == y
+        }
+    }
+
+    let dice = DiceModern::builder().build(DetectCycles::Disabled);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Foo(0), 1000)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&Buffer).await?;
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Foo(0), 3000)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&Buffer).await?;
+
+    let usage = dice.memory_usage().await;
+    let buffers = &usage.key_types["Buffer"];
+    // the recomputed value replaced the previous one
+    assert_eq!(1, buffers.values);
+    assert!(buffers.bytes >= 3000);
+    assert!(buffers.bytes < 4000);
+
+    let (latest, latest_bytes) = usage.versions.iter().last().unwrap();
+    assert_eq!(ctx.get_version().to_introspectable(), *latest);
+    assert!(*latest_bytes >= buffers.bytes);
+    assert_eq!(
+        usage.key_types.values().map(|m| m.bytes).sum::<u64>(),
+        usage.versions.values().sum::<u64>()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn key_index_shards_are_configurable() -> anyhow::Result<()> {
+    let mut builder = DiceModern::builder();
+    builder.set_key_index_shards(100);
+    let dice = builder.build(DetectCycles::Disabled);
+
+    let mut updater = dice.updater();
+    updater.changed_to((0..1000).map(|i| (Foo(i), i)))?;
+    drop(updater.commit().await);
+
+    let metrics = dice.metrics().key_index;
+    assert_eq!(128, metrics.shard_count);
+    assert!(metrics.min_shard_key_count <= metrics.max_shard_key_count);
+    assert!(metrics.max_shard_key_count * metrics.shard_count >= 1000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn key_concurrency_limits_are_enforced() -> anyhow::Result<()> {
+    #[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+    #[derivative(Hash, PartialEq, Eq)]
+    #[display(fmt = "{:?}", self)]
+    struct Throttled(
+        usize,
+        #[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<(AtomicUsize, AtomicUsize)>,
+    );
+
+    #[async_trait]
+    impl Key for Throttled {
+        type Value = ();
+
+        async fn compute(
+            &self,
+            _ctx: &DiceComputations,
+            _cancellations: &CancellationContext,
+        ) -> Self::Value {
+            let (running, max_running) = &*self.1;
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        fn equality(_: &Self::Value, _: &Self::Value) -> bool {
+            true
+        }
+    }
+
+    let mut data = DiceData::new();
+    data.set_key_concurrency_limit::<Throttled>(2);
+    let dice = DiceModern::new(data, DetectCycles::Disabled);
+
+    let counters = Arc::new((AtomicUsize::new(0), AtomicUsize::new(0)));
+    let ctx = dice.updater().commit().await;
+    futures::future::try_join_all((0..10).map(|i| ctx.compute(&Throttled(i, counters.dupe()))))
+        .await?;
+
+    assert_eq!(2, counters.1.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn compute_many_returns_results_in_order_with_bounded_parallelism() -> anyhow::Result<()> {
+    #[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+    #[derivative(Hash, PartialEq, Eq)]
+    #[display(fmt = "{:?}", self)]
+    struct Child(
+        i32,
+        #[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<(AtomicUsize, AtomicUsize)>,
+    );
+
+    #[async_trait]
+    impl Key for Child {
+        type Value = i32;
+
+        async fn compute(
+            &self,
+            ctx: &DiceComputations,
+            _cancellations: &CancellationContext,
+        ) -> Self::Value {
+            let (running, max_running) = &*self.1;
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            // finish in the reverse order of the keys
+            tokio::time::sleep(Duration::from_millis(10 - self.0 as u64)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            self.0 * 10 + ctx.compute(&Foo(self.0)).await.unwrap()
+        }
+
+        fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+            x == y
+        }
+    }
+
+    #[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+    #[derivative(Hash, PartialEq, Eq)]
+    #[display(fmt = "Parent")]
+    struct Parent(
+        #[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<(AtomicUsize, AtomicUsize)>,
+    );
+
+    #[async_trait]
+    impl Key for Parent {
+        type Value = Arc<Vec<i32>>;
+
+        async fn compute(
+            &self,
+            ctx: &DiceComputations,
+            _cancellations: &CancellationContext,
+        ) -> Self::Value {
+            let children: Vec<_> = (0..5).map(|i| Child(i, self.0.dupe())).collect();
+            Arc::new(
+                ctx.compute_many_with_parallelism(&children, 2)
+                    .await
+                    .into_iter()
+                    .map(|r| r.unwrap())
+                    .collect(),
+            )
+        }
+
+        fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+            x == y
+        }
+    }
+
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let counters = Arc::new((AtomicUsize::new(0), AtomicUsize::new(0)));
+
+    let mut updater = dice.updater();
+    updater.changed_to((0..5).map(|i| (Foo(i), 0)))?;
+    let ctx = updater.commit().await;
+    assert_eq!(
+        vec![0, 10, 20, 30, 40],
+        *ctx.compute(&Parent(counters.dupe())).await?
+    );
+    assert_eq!(2, counters.1.load(Ordering::SeqCst));
+    drop(ctx);
+
+    // every child is recorded as a dep of the parent
+    let mut updater = dice.updater();
+    updater.changed_to([(Foo(4), 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(
+        vec![0, 10, 20, 30, 41],
+        *ctx.compute(&Parent(counters.dupe())).await?
+    );
+
+    Ok(())
+}
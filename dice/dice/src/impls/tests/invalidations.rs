@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::time::Duration;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::dice::Dice;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::impls::tests::deterministic::DeterministicRuntime;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+#[display(fmt = "Input({})", _0)]
+struct Input(u32);
+
+impl InjectedKey for Input {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[tokio::test]
+async fn invalidations_are_coalesced_and_flushed_together() -> anyhow::Result<()> {
+    let dice = Dice::modern().build(DetectCycles::Enabled);
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 0), (Input(1), 0)])?;
+    let ctx = updater.commit().await;
+    let before = ctx.equality_token();
+    drop(ctx);
+
+    let invalidations = dice.subscribe_invalidations(None);
+    invalidations.changed_to(Input(0), 1);
+    invalidations.changed_to(Input(0), 2);
+    invalidations.changed_to(Input(1), 3);
+    assert_eq!(2, invalidations.pending_count());
+
+    invalidations.flush().await?;
+    assert_eq!(0, invalidations.pending_count());
+
+    let ctx = dice.updater().commit().await;
+    assert_eq!(2, ctx.compute(&Input(0)).await?);
+    assert_eq!(3, ctx.compute(&Input(1)).await?);
+
+    // all the changes were committed as a single version
+    let changes = dice
+        .changes_between(before, ctx.equality_token())
+        .await
+        .unwrap();
+    assert_eq!(vec!["Input(0)", "Input(1)"], changes.changed);
+
+    Ok(())
+}
+
+#[test]
+fn invalidations_are_flushed_after_the_batch_delay() {
+    let runtime = DeterministicRuntime::new();
+    let dice = runtime.build(Dice::modern(), DetectCycles::Enabled);
+
+    runtime.block_on(async {
+        let invalidations = dice.subscribe_invalidations(Some(Duration::from_secs(1)));
+        invalidations.changed_to(Input(0), 1);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        invalidations.changed_to(Input(0), 2);
+        assert_eq!(1, invalidations.pending_count());
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(0, invalidations.pending_count());
+
+        let ctx = dice.updater().commit().await;
+        assert_eq!(2, ctx.compute(&Input(0)).await.unwrap());
+    });
+}
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+#[display(fmt = "Positive({})", _0)]
+struct Positive(u32);
+
+#[async_trait]
+impl Key for Positive {
+    type Value = i32;
+
+    async fn compute(
+        &self,
+        _ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        1
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+
+    fn validity(x: &Self::Value) -> bool {
+        *x > 0
+    }
+}
+
+#[tokio::test]
+async fn invalid_changes_dont_prevent_committing_the_others() -> anyhow::Result<()> {
+    // only legacy dice checks the validity of injected values
+    let dice = Dice::builder().build(DetectCycles::Enabled);
+
+    let invalidations = dice.subscribe_invalidations(None);
+    invalidations.changed_to(Positive(0), -1);
+    invalidations.changed_to(Positive(1), 2);
+    invalidations.changed_to(Positive(2), -2);
+    invalidations.changed_to(Positive(3), 3);
+
+    let err = invalidations.flush().await.unwrap_err();
+    assert!(err.to_string().starts_with("2 errors: "), "{}", err);
+    assert_eq!(0, invalidations.pending_count());
+
+    let ctx = dice.updater().commit().await;
+    assert_eq!(1, ctx.compute(&Positive(0)).await?);
+    assert_eq!(2, ctx.compute(&Positive(1)).await?);
+    assert_eq!(3, ctx.compute(&Positive(3)).await?);
+
+    Ok(())
+}
@@ -20,6 +20,7 @@ use more_futures::cancellation::CancellationContext;
 use tokio::sync::Mutex;
 
 use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
 use crate::api::data::DiceData;
 use crate::api::key::Key;
 use crate::impls::dice::DiceModern;
@@ -60,7 +61,7 @@ async fn concurrent_identical_requests_are_deduped() -> anyhow::Result<()> {
         }
     }
 
-    let dice = DiceModern::new(DiceData::new());
+    let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
     let guard = Arc::new(Mutex::new(0));
     let _g = guard.lock().await;
@@ -134,7 +135,7 @@ fn different_requests_are_spawned_in_parallel() -> anyhow::Result<()> {
     let barrier = Arc::new(std::sync::Barrier::new(n_thread));
 
     rt.block_on(async move {
-        let dice = DiceModern::new(DiceData::new());
+        let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
 
         let ctx = dice.updater().commit().await;
         let k = ComputeParallel(barrier.dupe());
@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::impls::dice::DiceModern;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+#[display(fmt = "{:?}", self)]
+struct Input(u8);
+
+impl InjectedKey for Input {
+    type Value = usize;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// A large value that is cheap to recompute
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "{:?}", self)]
+struct Buffer(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicUsize>);
+
+#[async_trait]
+impl Key for Buffer {
+    type Value = Arc<Vec<u8>>;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Arc::new(vec![0; ctx.compute(&Input(0)).await.unwrap()])
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+
+    fn cheap_to_recompute() -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "{:?}", self)]
+struct Len(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicUsize>);
+
+#[async_trait]
+impl Key for Len {
+    type Value = usize;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        ctx.compute(&Buffer(self.0.dupe())).await.unwrap().len()
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[tokio::test]
+async fn memory_pressure_drops_cheap_values_but_keeps_invalidating() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let computed = Arc::new(AtomicUsize::new(0));
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 1 << 20)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(1 << 20, ctx.compute(&Len(computed.dupe())).await?);
+    assert_eq!(2, computed.load(Ordering::SeqCst));
+    drop(ctx);
+
+    // only the buffer is dropped, and dropping again frees nothing more
+    assert!(dice.notify_memory_pressure(1).await >= 1 << 20);
+    assert_eq!(0, dice.notify_memory_pressure(1).await);
+
+    // the value of `Len` is still cached, while the buffer is recomputed when requested
+    let ctx = dice.updater().existing_state().await;
+    assert_eq!(1 << 20, ctx.compute(&Len(computed.dupe())).await?);
+    assert_eq!(2, computed.load(Ordering::SeqCst));
+    assert_eq!(1 << 20, ctx.compute(&Buffer(computed.dupe())).await?.len());
+    assert_eq!(3, computed.load(Ordering::SeqCst));
+    drop(ctx);
+
+    // a dropped value still invalidates the keys depending on it
+    assert!(dice.notify_memory_pressure(1).await >= 1 << 20);
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 10)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(10, ctx.compute(&Len(computed.dupe())).await?);
+    assert_eq!(5, computed.load(Ordering::SeqCst));
+
+    Ok(())
+}
@@ -8,10 +8,25 @@
  */
 
 mod activation_tracker;
+mod changes;
+mod cutoff;
 mod demo;
+pub(crate) mod deterministic;
 mod events;
+mod eviction;
+mod gc;
 mod general;
+mod invalidations;
 mod keys;
+mod memory_pressure;
+mod on_computed;
+mod persistence;
+mod prefetch;
+mod projection;
+mod recompute;
 mod spawner;
+mod timeout;
+mod transaction_data;
 mod transients;
 mod user_data;
+mod user_error;
@@ -0,0 +1,117 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::dice::Dice;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::api::on_computed::DiceComputedEvent;
+use crate::api::on_computed::DiceComputedObserver;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Input(u32);
+
+impl InjectedKey for Input {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Sum;
+
+#[async_trait]
+impl Key for Sum {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        ctx.compute(&Input(0)).await.unwrap() + ctx.compute(&Input(1)).await.unwrap()
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// Records the key, version, hit and deps count of each event
+#[derive(Default)]
+struct Recorder(Mutex<Vec<(String, usize, bool, Option<usize>)>>);
+
+impl DiceComputedObserver for Recorder {
+    fn on_computed(&self, event: &DiceComputedEvent<'_>) {
+        if event.key.is::<Sum>() {
+            assert_eq!("Sum", event.key_type);
+            self.0.lock().unwrap().push((
+                event.key_display.to_string(),
+                event.version.0,
+                event.hit,
+                event.deps_count,
+            ));
+        }
+    }
+}
+
+#[tokio::test]
+async fn observers_are_called_when_keys_finish() -> anyhow::Result<()> {
+    let recorder = Arc::new(Recorder::default());
+    let mut builder = Dice::modern();
+    builder.add_computed_observer(recorder.dupe());
+    let dice = builder.build(DetectCycles::Enabled);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 1), (Input(1), 2)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(3, ctx.compute(&Sum).await?);
+    drop(ctx);
+
+    // a new version without changes is the same version
+    let ctx = dice.updater().commit().await;
+    assert_eq!(3, ctx.compute(&Sum).await?);
+    drop(ctx);
+
+    // an unrelated change doesn't dirty the key
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(2), 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(3, ctx.compute(&Sum).await?);
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input(0), 5)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(7, ctx.compute(&Sum).await?);
+
+    assert_eq!(
+        vec![
+            ("Sum".to_owned(), 1, false, Some(2)),
+            ("Sum".to_owned(), 1, true, None),
+            ("Sum".to_owned(), 2, true, None),
+            ("Sum".to_owned(), 3, false, Some(2)),
+        ],
+        *recorder.0.lock().unwrap()
+    );
+
+    Ok(())
+}
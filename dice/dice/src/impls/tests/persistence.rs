@@ -0,0 +1,118 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::key::Key;
+use crate::api::persistence::DicePersistence;
+use crate::api::persistence::DicePersistenceStats;
+use crate::api::persistence::DicePersistentStore;
+use crate::api::persistence::DiskPersistentStore;
+use crate::impls::dice::DiceModern;
+
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "{:?}", self)]
+struct Persisted(
+    u32,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicUsize>,
+);
+
+#[async_trait]
+impl Key for Persisted {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        _ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.1.fetch_add(1, Ordering::SeqCst);
+        self.0 * 2
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+
+    fn serialize_key(&self) -> Option<Vec<u8>> {
+        Some(self.0.to_le_bytes().to_vec())
+    }
+
+    fn serialize_value(value: &Self::Value) -> Option<Vec<u8>> {
+        Some(value.to_le_bytes().to_vec())
+    }
+
+    fn deserialize_value(bytes: &[u8]) -> Option<Self::Value> {
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+fn dice_with_store(
+    store: &Arc<dyn DicePersistentStore>,
+    version: &str,
+) -> (Arc<DiceModern>, DicePersistence) {
+    let persistence = DicePersistence::new(store.dupe(), version);
+    let mut builder = DiceModern::builder();
+    builder.set_persistence(persistence.dupe());
+    (builder.build(DetectCycles::Enabled), persistence)
+}
+
+#[tokio::test]
+async fn persisted_values_survive_restarts() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let store: Arc<dyn DicePersistentStore> = Arc::new(DiskPersistentStore::new(dir.path()));
+    let computed = Arc::new(AtomicUsize::new(0));
+
+    {
+        let (dice, persistence) = dice_with_store(&store, "v1");
+        let ctx = dice.updater().commit().await;
+        assert_eq!(2, ctx.compute(&Persisted(1, computed.dupe())).await?);
+        assert_eq!(1, computed.load(Ordering::SeqCst));
+        assert_eq!(
+            DicePersistenceStats {
+                hits: 0,
+                misses: 1,
+                writes: 1,
+                errors: 0,
+            },
+            persistence.stats()
+        );
+    }
+
+    // a new dice, as after a daemon restart, reuses the persisted value
+    {
+        let (dice, persistence) = dice_with_store(&store, "v1");
+        let ctx = dice.updater().commit().await;
+        assert_eq!(2, ctx.compute(&Persisted(1, computed.dupe())).await?);
+        assert_eq!(1, computed.load(Ordering::SeqCst));
+        assert_eq!(1, persistence.stats().hits);
+    }
+
+    // values persisted by another version are ignored
+    {
+        let (dice, _persistence) = dice_with_store(&store, "v2");
+        let ctx = dice.updater().commit().await;
+        assert_eq!(2, ctx.compute(&Persisted(1, computed.dupe())).await?);
+        assert_eq!(2, computed.load(Ordering::SeqCst));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::any::Any;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use futures::future::BoxFuture;
+use more_futures::cancellation::CancellationContext;
+use more_futures::spawner::Spawner;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::key::Key;
+use crate::api::user_data::UserComputationData;
+use crate::impls::dice::DiceModern;
+
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Prefetched")]
+struct Prefetched(
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>,
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")]
+    #[allocative(skip)]
+    Arc<Notify>,
+);
+
+#[async_trait]
+impl Key for Prefetched {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        _ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        let count = self.0.fetch_add(1, Ordering::SeqCst);
+        self.1.notify_one();
+        count
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Prefetching")]
+struct Prefetching(
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>,
+    Prefetched,
+);
+
+#[async_trait]
+impl Key for Prefetching {
+    type Value = ();
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        ctx.spawn_prefetch([&self.1]);
+    }
+
+    fn equality(_x: &Self::Value, _y: &Self::Value) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn prefetched_keys_are_computed_without_being_deps() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let notify = Arc::new(Notify::new());
+    let prefetched = Prefetched(Arc::new(AtomicU32::new(0)), notify.dupe());
+    let prefetching = Prefetching(Arc::new(AtomicU32::new(0)), prefetched.dupe());
+
+    let ctx = dice.updater().commit().await;
+    ctx.compute(&prefetching).await?;
+    notify.notified().await;
+
+    // the prefetched value is reused
+    assert_eq!(0, ctx.compute(&prefetched).await?);
+    assert_eq!(1, prefetched.0.load(Ordering::SeqCst));
+    drop(ctx);
+
+    // invalidating the prefetched key doesn't invalidate the key that prefetched it
+    let mut updater = dice.updater();
+    updater.changed([prefetched.dupe()])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&prefetching).await?;
+    assert_eq!(1, prefetching.0.load(Ordering::SeqCst));
+    assert_eq!(1, ctx.compute(&prefetched).await?);
+
+    Ok(())
+}
+
+struct CountingSpawner(AtomicUsize);
+
+impl<S> Spawner<S> for CountingSpawner {
+    fn spawn(
+        &self,
+        _ctx: &S,
+        fut: BoxFuture<'static, Box<dyn Any + Send + 'static>>,
+    ) -> JoinHandle<Box<dyn Any + Send + 'static>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+
+        tokio::spawn(fut)
+    }
+}
+
+#[tokio::test]
+async fn prefetches_use_the_transaction_spawner() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let spawner = Arc::new(CountingSpawner(AtomicUsize::new(0)));
+    let notify = Arc::new(Notify::new());
+    let prefetched = Prefetched(Arc::new(AtomicU32::new(0)), notify.dupe());
+
+    let mut data = UserComputationData::new();
+    data.spawner = spawner.dupe();
+    let ctx = dice.updater_with_data(data).commit().await;
+    ctx.spawn_prefetch([&prefetched]);
+    notify.notified().await;
+
+    // one task holding on to the prefetch, and one computing the key
+    assert_eq!(2, spawner.0.load(Ordering::SeqCst));
+    assert_eq!(0, ctx.compute(&prefetched).await?);
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::api::projection::DiceProjectionComputations;
+use crate::api::projection::ProjectionKey;
+use crate::impls::dice::DiceModern;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Input;
+
+impl InjectedKey for Input {
+    type Value = Arc<(u32, u32)>;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// A large value that consumers only need parts of
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Base;
+
+#[async_trait]
+impl Key for Base {
+    type Value = Arc<(u32, u32)>;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        ctx.compute(&Input).await.unwrap()
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct First;
+
+impl ProjectionKey for First {
+    type DeriveFromKey = Base;
+    type Value = u32;
+
+    fn compute(&self, derive_from: &Arc<(u32, u32)>, _ctx: &DiceProjectionComputations) -> u32 {
+        derive_from.0
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// Only depends on the first element of `Base`
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "UsesFirst")]
+struct UsesFirst(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>);
+
+#[async_trait]
+impl Key for UsesFirst {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        ctx.compute_opaque(&Base)
+            .await
+            .unwrap()
+            .projection(&First)
+            .unwrap()
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[tokio::test]
+async fn projections_only_invalidate_on_projected_changes() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let computes = Arc::new(AtomicU32::new(0));
+    let key = UsesFirst(computes.dupe());
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, Arc::new((1, 1)))])?;
+    let ctx = updater.commit().await;
+    assert_eq!(1, ctx.compute(&key).await?);
+    assert_eq!(1, computes.load(Ordering::SeqCst));
+    drop(ctx);
+
+    // only the part of `Base` that isn't projected changes
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, Arc::new((1, 2)))])?;
+    let ctx = updater.commit().await;
+    assert_eq!(1, ctx.compute(&key).await?);
+    assert_eq!(1, computes.load(Ordering::SeqCst));
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, Arc::new((3, 2)))])?;
+    let ctx = updater.commit().await;
+    assert_eq!(3, ctx.compute(&key).await?);
+    assert_eq!(2, computes.load(Ordering::SeqCst));
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::impls::dice::DiceModern;
+use crate::introspection::recompute::RecomputeChain;
+use crate::introspection::recompute::RecomputeReason;
+use crate::introspection::recompute::RecomputeStep;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Input;
+
+impl InjectedKey for Input {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Unrelated;
+
+impl InjectedKey for Unrelated {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// Produces a different value every time it is computed
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "Mid")]
+struct Mid(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>);
+
+#[async_trait]
+impl Key for Mid {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        ctx.compute(&Input).await.unwrap() + self.0.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+#[derivative(Hash, PartialEq, Eq)]
+#[display(fmt = "Top")]
+struct Top(#[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>);
+
+#[async_trait]
+impl Key for Top {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        ctx.compute(&Mid(self.0.dupe())).await.unwrap()
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+fn chain(steps: &[(&str, RecomputeReason)]) -> Option<RecomputeChain> {
+    Some(RecomputeChain(
+        steps
+            .iter()
+            .map(|(key, reason)| RecomputeStep {
+                key: (*key).to_owned(),
+                reason: *reason,
+            })
+            .collect(),
+    ))
+}
+
+#[tokio::test]
+async fn why_recomputed_reports_changed_deps() -> anyhow::Result<()> {
+    let mut builder = DiceModern::builder();
+    builder.set_record_recomputes(true);
+    let dice = builder.build(DetectCycles::Enabled);
+    let counter = Arc::new(AtomicU32::new(0));
+    let top = Top(counter.dupe());
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, 1)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&top).await?;
+    assert_eq!(
+        chain(&[("Top", RecomputeReason::NotComputed)]),
+        ctx.why_recomputed(&top).await
+    );
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, 2)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&top).await?;
+    assert_eq!(
+        chain(&[
+            ("Top", RecomputeReason::DepChanged),
+            ("Mid", RecomputeReason::DepChanged),
+            ("Input", RecomputeReason::Changed),
+        ]),
+        ctx.why_recomputed(&top).await
+    );
+    assert_eq!(
+        "Top (dep changed) <- Mid (dep changed) <- Input (changed)",
+        ctx.why_recomputed(&top).await.unwrap().to_string()
+    );
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed([Mid(counter.dupe())])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&top).await?;
+    assert_eq!(
+        chain(&[
+            ("Top", RecomputeReason::DepChanged),
+            ("Mid", RecomputeReason::Invalidated),
+        ]),
+        ctx.why_recomputed(&top).await
+    );
+    drop(ctx);
+
+    // nothing that `Top` depends on changed, so it isn't recomputed
+    let mut updater = dice.updater();
+    updater.changed_to([(Unrelated, 1)])?;
+    let ctx = updater.commit().await;
+    ctx.compute(&top).await?;
+    assert_eq!(None, ctx.why_recomputed(&top).await);
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::key::Key;
+use crate::impls::dice::DiceModern;
+
+struct SetOnDrop(Arc<AtomicBool>);
+
+impl Drop for SetOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Never finishes computing
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Stuck")]
+struct Stuck(
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")] Arc<AtomicBool>,
+);
+
+#[async_trait]
+impl Key for Stuck {
+    type Value = ();
+
+    async fn compute(
+        &self,
+        _ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        let _dropped = SetOnDrop(self.0.dupe());
+        futures::future::pending().await
+    }
+
+    fn equality(_x: &Self::Value, _y: &Self::Value) -> bool {
+        true
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        Some(Duration::from_millis(10))
+    }
+}
+
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Waiting")]
+struct Waiting(
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")] Arc<AtomicBool>,
+);
+
+#[async_trait]
+impl Key for Waiting {
+    type Value = Arc<String>;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        let err = ctx.compute(&Stuck(self.0.dupe())).await.unwrap_err();
+        assert!(err.is_timeout());
+        Arc::new(err.to_string())
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[tokio::test]
+async fn timeout_cancels_computation_and_reports_stack() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let dropped = Arc::new(AtomicBool::new(false));
+
+    let ctx = dice.updater().commit().await;
+    let message = ctx.compute(&Waiting(dropped.dupe())).await?;
+    assert_eq!(
+        "Computing key `Stuck` timed out after 10ms, requested via: `Waiting`",
+        &*message
+    );
+
+    // the computation is cancelled asynchronously
+    for _ in 0..100 {
+        if dropped.load(Ordering::SeqCst) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(dropped.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn timeout_reports_stack_without_cycle_detection() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Disabled);
+    let dropped = Arc::new(AtomicBool::new(false));
+
+    let ctx = dice.updater().commit().await;
+    let message = ctx.compute(&Waiting(dropped.dupe())).await?;
+    assert_eq!(
+        "Computing key `Stuck` timed out after 10ms, requested via: `Waiting`",
+        &*message
+    );
+
+    Ok(())
+}
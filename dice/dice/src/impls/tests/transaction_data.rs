@@ -0,0 +1,88 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::injected::InjectedKey;
+use crate::api::key::Key;
+use crate::impls::dice::DiceModern;
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct Input;
+
+impl InjectedKey for Input {
+    type Value = u32;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// Returns how many keys were computed at its version before it
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Hash, Allocative)]
+struct ComputedBefore(u32);
+
+#[async_trait]
+impl Key for ComputedBefore {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        ctx.transaction_data()
+            .get_or_insert_with(|| AtomicU32::new(0))
+            .fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[tokio::test]
+async fn transaction_data_is_shared_within_a_version_only() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, 1)])?;
+    let ctx = updater.commit().await;
+    assert_eq!(0, ctx.compute(&ComputedBefore(0)).await?);
+    assert_eq!(1, ctx.compute(&ComputedBefore(1)).await?);
+    // transactions at the same version share the data
+    let same_version = dice.updater().commit().await;
+    assert_eq!(
+        2,
+        same_version
+            .transaction_data()
+            .get::<AtomicU32>()
+            .unwrap()
+            .load(Ordering::SeqCst)
+    );
+    drop(same_version);
+    drop(ctx);
+
+    let mut updater = dice.updater();
+    updater.changed_to([(Input, 2)])?;
+    let ctx = updater.commit().await;
+    assert!(ctx.transaction_data().get::<AtomicU32>().is_none());
+    assert_eq!(0, ctx.compute(&ComputedBefore(2)).await?);
+
+    Ok(())
+}
@@ -8,8 +8,10 @@
  */
 
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use async_trait::async_trait;
@@ -21,6 +23,7 @@ use more_futures::cancellation::CancellationContext;
 use crate::api::computations::DiceComputations;
 use crate::api::cycles::DetectCycles;
 use crate::api::key::Key;
+use crate::api::transient::DiceTransientRetryPolicy;
 use crate::impls::dice::DiceModern;
 
 #[tokio::test]
@@ -168,3 +171,68 @@ async fn demo_with_transient() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transient_results_are_retried() -> anyhow::Result<()> {
+    /// Transient until it has been computed more than the given number of times
+    #[derive(Clone, Dupe, Debug, Display, Derivative, Allocative)]
+    #[derivative(Hash, PartialEq, Eq)]
+    #[display(fmt = "{:?}", self)]
+    struct Flaky(
+        u32,
+        #[derivative(PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>,
+    );
+
+    #[async_trait]
+    impl Key for Flaky {
+        type Value = Result<u32, ()>;
+
+        async fn compute(
+            &self,
+            _ctx: &DiceComputations,
+            _cancellations: &CancellationContext,
+        ) -> Self::Value {
+            let attempt = self.1.fetch_add(1, Ordering::SeqCst);
+            if attempt < self.0 {
+                Err(())
+            } else {
+                Ok(attempt)
+            }
+        }
+
+        fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+            x == y
+        }
+
+        fn validity(x: &Self::Value) -> bool {
+            x.is_ok()
+        }
+    }
+
+    let mut builder = DiceModern::builder();
+    builder.set_transient_retry_policy(DiceTransientRetryPolicy::new(2, Duration::from_millis(1)));
+    let dice = builder.build(DetectCycles::Enabled);
+
+    let ctx = dice.updater().commit().await;
+    let attempts = Arc::new(AtomicU32::new(0));
+    assert_eq!(Ok(2), ctx.compute(&Flaky(2, attempts.dupe())).await?);
+    assert_eq!(3, attempts.load(Ordering::SeqCst));
+
+    // retries are bounded by the policy
+    let attempts = Arc::new(AtomicU32::new(0));
+    assert_eq!(Err(()), ctx.compute(&Flaky(5, attempts.dupe())).await?);
+    assert_eq!(3, attempts.load(Ordering::SeqCst));
+    drop(ctx);
+
+    // the transient value is retried again at the next version
+    let ctx = dice.updater().commit().await;
+    assert_eq!(Ok(5), ctx.compute(&Flaky(5, attempts.dupe())).await?);
+    assert_eq!(6, attempts.load(Ordering::SeqCst));
+
+    let metrics = dice.metrics();
+    assert_eq!(9, metrics.compute_count);
+    assert_eq!(7, metrics.transient_count);
+    assert_eq!(6, metrics.transient_retry_count);
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derivative::Derivative;
+use derive_more::Display;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+use thiserror::Error;
+
+use crate::api::computations::DiceComputations;
+use crate::api::cycles::DetectCycles;
+use crate::api::key::Key;
+use crate::api::user_error::DiceUserError;
+use crate::api::user_error::DiceUserResult;
+use crate::impls::dice::DiceModern;
+
+#[derive(Debug, Error)]
+#[error("failed to compute")]
+struct Failure;
+
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Failing")]
+struct Failing {
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")]
+    fail: Arc<AtomicBool>,
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")]
+    computes: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Key for Failing {
+    type Value = u32;
+
+    async fn try_compute(
+        &self,
+        _ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> DiceUserResult<Self::Value> {
+        self.computes.fetch_add(1, Ordering::SeqCst);
+        if self.fail.load(Ordering::SeqCst) {
+            Err(DiceUserError::new(Failure))
+        } else {
+            Ok(1)
+        }
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Dependent({})", _0)]
+struct Dependent(
+    u32,
+    Failing,
+    #[derivative(Debug = "ignore", PartialEq = "ignore", Hash = "ignore")] Arc<AtomicU32>,
+);
+
+#[async_trait]
+impl Key for Dependent {
+    type Value = u32;
+
+    async fn try_compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> DiceUserResult<Self::Value> {
+        self.2.fetch_add(1, Ordering::SeqCst);
+        Ok(ctx.compute(&self.1).await? + self.0)
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+/// Handles the error of `Failing` instead of propagating it.
+#[derive(Clone, Dupe, Display, Derivative, Allocative)]
+#[derivative(Debug, Hash, PartialEq, Eq)]
+#[display(fmt = "Fallback")]
+struct Fallback(Failing);
+
+#[async_trait]
+impl Key for Fallback {
+    type Value = u32;
+
+    async fn compute(
+        &self,
+        ctx: &DiceComputations,
+        _cancellations: &CancellationContext,
+    ) -> Self::Value {
+        ctx.compute(&self.0).await.unwrap_or(0)
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+fn failing() -> Failing {
+    Failing {
+        fail: Arc::new(AtomicBool::new(true)),
+        computes: Arc::new(AtomicU32::new(0)),
+    }
+}
+
+#[tokio::test]
+async fn user_errors_propagate_to_dependents() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let failing = failing();
+    let computes = Arc::new(AtomicU32::new(0));
+
+    let ctx = dice.updater().commit().await;
+    let errors = vec![
+        ctx.compute(&Dependent(1, failing.dupe(), computes.dupe()))
+            .await
+            .unwrap_err(),
+        ctx.compute(&Dependent(2, failing.dupe(), computes.dupe()))
+            .await
+            .unwrap_err(),
+        ctx.compute(&failing).await.unwrap_err(),
+    ];
+    let errors = errors
+        .iter()
+        .map(|e| e.user_error().expect("user error").dupe())
+        .collect::<Vec<_>>();
+    assert!(errors[0].downcast_ref::<Failure>().is_some());
+    assert_eq!("failed to compute", errors[0].to_string());
+
+    // the dependents report the failure of the key they depend on, which was computed once
+    assert_eq!(1, DiceUserError::dedup(errors).len());
+    assert_eq!(1, failing.computes.load(Ordering::SeqCst));
+    assert_eq!(2, computes.load(Ordering::SeqCst));
+
+    // the failure is shared by everything requesting the key at the same version
+    ctx.compute(&Dependent(1, failing.dupe(), computes.dupe()))
+        .await
+        .unwrap_err();
+    assert_eq!(1, failing.computes.load(Ordering::SeqCst));
+    assert_eq!(2, computes.load(Ordering::SeqCst));
+
+    assert_eq!(0, ctx.compute(&Fallback(failing.dupe())).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn user_errors_are_recomputed() -> anyhow::Result<()> {
+    let dice = DiceModern::builder().build(DetectCycles::Enabled);
+    let failing = failing();
+    let computes = Arc::new(AtomicU32::new(0));
+
+    {
+        let ctx = dice.updater().commit().await;
+        ctx.compute(&Dependent(1, failing.dupe(), computes.dupe()))
+            .await
+            .unwrap_err();
+        assert_eq!(0, ctx.compute(&Fallback(failing.dupe())).await?);
+    }
+    assert_eq!(1, failing.computes.load(Ordering::SeqCst));
+    assert_eq!(1, computes.load(Ordering::SeqCst));
+
+    // nothing was invalidated, but the failure and everything depending on it is computed again
+    failing.fail.store(false, Ordering::SeqCst);
+    {
+        let ctx = dice.updater().commit().await;
+        assert_eq!(
+            2,
+            ctx.compute(&Dependent(1, failing.dupe(), computes.dupe()))
+                .await?
+        );
+        assert_eq!(1, ctx.compute(&Fallback(failing.dupe())).await?);
+    }
+    assert_eq!(2, failing.computes.load(Ordering::SeqCst));
+    assert_eq!(2, computes.load(Ordering::SeqCst));
+
+    // once computed successfully, the values are reused as usual
+    {
+        let ctx = dice.updater().commit().await;
+        assert_eq!(
+            2,
+            ctx.compute(&Dependent(1, failing.dupe(), computes.dupe()))
+                .await?
+        );
+    }
+    assert_eq!(2, failing.computes.load(Ordering::SeqCst));
+    assert_eq!(2, computes.load(Ordering::SeqCst));
+
+    Ok(())
+}
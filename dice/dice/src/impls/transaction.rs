@@ -28,6 +28,7 @@ use crate::impls::value::DiceKeyValue;
 use crate::impls::value::DiceValidValue;
 use crate::impls::value::DiceValidity;
 use crate::impls::value::MaybeValidDiceValue;
+use crate::introspection::changes::VersionChanges;
 use crate::versions::VersionNumber;
 use crate::DiceModern;
 use crate::HashMap;
@@ -109,6 +110,30 @@ impl TransactionUpdater {
         BaseComputeCtx::new(transaction, Arc::new(extra), dice, guard)
     }
 
+    /// The keys that committing the changes would change and invalidate, without committing them.
+    pub(crate) async fn preview(&self) -> VersionChanges {
+        let changes = self
+            .scheduled_changes
+            .changes
+            .iter()
+            .map(|(k, change)| {
+                let value = match change {
+                    ChangeType::UpdateValue(value, _) => Some(value.dupe()),
+                    _ => None,
+                };
+                (*k, value)
+            })
+            .collect();
+
+        let (tx, rx) = oneshot::channel();
+        self.dice
+            .state_handle
+            .request(StateRequest::PreviewUpdate { changes, resp: tx });
+
+        let (changed, invalidated) = rx.await.unwrap();
+        self.dice.describe_changes(changed, invalidated)
+    }
+
     pub(crate) async fn existing_state(&self) -> BaseComputeCtx {
         let (tx, rx) = oneshot::channel();
         self.dice
@@ -226,6 +251,7 @@ mod tests {
     use more_futures::cancellation::CancellationContext;
 
     use crate::api::computations::DiceComputations;
+    use crate::api::cycles::DetectCycles;
     use crate::api::data::DiceData;
     use crate::api::key::Key;
     use crate::impls::dice::DiceModern;
@@ -255,7 +281,7 @@ mod tests {
 
     #[test]
     fn changes_are_recorded() -> anyhow::Result<()> {
-        let dice = DiceModern::new(DiceData::new());
+        let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
         let mut updater = dice.updater();
 
         updater.changed(vec![K(1), K(2)])?;
@@ -300,7 +326,7 @@ mod tests {
 
     #[tokio::test]
     async fn transaction_versions() -> anyhow::Result<()> {
-        let dice = DiceModern::new(DiceData::new());
+        let dice = DiceModern::new(DiceData::new(), DetectCycles::Disabled);
         let mut updater = dice.updater();
 
         updater.changed(vec![K(1), K(2)])?;
@@ -0,0 +1,72 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Applies the `DiceTransientRetryPolicy` and counts transient results
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use allocative::Allocative;
+
+use crate::api::transient::DiceTransientRetryPolicy;
+use crate::metrics::Metrics;
+
+#[derive(Allocative)]
+pub(crate) struct TransientRetries {
+    policy: DiceTransientRetryPolicy,
+    #[allocative(skip)]
+    computes: AtomicU64,
+    #[allocative(skip)]
+    transients: AtomicU64,
+    #[allocative(skip)]
+    retries: AtomicU64,
+}
+
+impl TransientRetries {
+    pub(crate) fn new(policy: DiceTransientRetryPolicy) -> Self {
+        Self {
+            policy,
+            computes: AtomicU64::new(0),
+            transients: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+        }
+    }
+
+    /// Records the result of a run of `Key::compute`, and decides whether it should be retried.
+    /// `attempt` is the number of retries already made, and `retryable` is whether the value
+    /// itself, rather than one of its deps, is transient.
+    pub(crate) async fn should_retry(
+        &self,
+        attempt: u32,
+        transient: bool,
+        retryable: bool,
+    ) -> bool {
+        self.computes.fetch_add(1, Ordering::Relaxed);
+        if !transient {
+            return false;
+        }
+        self.transients.fetch_add(1, Ordering::Relaxed);
+
+        if !retryable || attempt >= self.policy.max_retries {
+            return false;
+        }
+        self.retries.fetch_add(1, Ordering::Relaxed);
+
+        if !self.policy.backoff.is_zero() {
+            tokio::time::sleep(self.policy.backoff).await;
+        }
+        true
+    }
+
+    pub(crate) fn add_to_metrics(&self, metrics: &mut Metrics) {
+        metrics.compute_count = self.computes.load(Ordering::Relaxed);
+        metrics.transient_count = self.transients.load(Ordering::Relaxed);
+        metrics.transient_retry_count = self.retries.load(Ordering::Relaxed);
+    }
+}
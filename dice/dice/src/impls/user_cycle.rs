@@ -11,16 +11,46 @@ use std::sync::Arc;
 
 use dupe::Dupe;
 
+use crate::api::cycles::DetectCycles;
 use crate::api::error::DiceError;
 use crate::api::error::DiceErrorImpl;
 use crate::api::error::DiceResult;
 use crate::api::user_data::UserCycleDetector;
 use crate::api::user_data::UserCycleDetectorGuard;
+use crate::impls::cycles::CycleDetector;
 use crate::impls::key::DiceKey;
 use crate::impls::key::DiceKeyErased;
 use crate::impls::key_index::DiceKeyIndex;
 
-pub(crate) struct UserCycleDetectorData(());
+/// The keys being computed to reach a computation. Unlike the stack of the `CycleDetector`, this
+/// is tracked whether or not cycles are detected, to report it in errors.
+#[derive(Clone, Dupe, Default)]
+struct ActiveKeys(Option<Arc<(DiceKey, ActiveKeys)>>);
+
+impl ActiveKeys {
+    fn push(&self, k: DiceKey) -> Self {
+        Self(Some(Arc::new((k, self.dupe()))))
+    }
+
+    /// The keys, outermost first.
+    fn to_vec(&self) -> Vec<DiceKey> {
+        let mut keys = Vec::new();
+        let mut next = &self.0;
+        while let Some(node) = next {
+            keys.push(node.0);
+            next = &node.1.0;
+        }
+        keys.reverse();
+        keys
+    }
+}
+
+/// Cycle detection data passed to the computation of a requested key.
+pub(crate) struct UserCycleDetectorData {
+    cycle_detector: Option<CycleDetector>,
+    /// The keys being computed by the requester.
+    active_keys: ActiveKeys,
+}
 
 impl UserCycleDetectorData {
     pub(crate) fn start_computing_key(
@@ -29,29 +59,51 @@ impl UserCycleDetectorData {
         key_index: &DiceKeyIndex,
         detector: Option<&Arc<dyn UserCycleDetector>>,
     ) -> KeyComputingUserCycleDetectorData {
-        if let Some(detector) = detector {
-            let k_erased = key_index.get(k);
-            if let Some(guard) = detector.start_computing_key(k_erased.as_any()) {
-                debug!("cycles start key {:?}", k);
-                return KeyComputingUserCycleDetectorData::Detecting {
-                    k_erased: k_erased.dupe(),
-                    k,
-                    guard,
-                    detector: detector.dupe(),
-                };
+        let user = match detector {
+            Some(detector) => {
+                let k_erased = key_index.get(k);
+                match detector.start_computing_key(k_erased.as_any()) {
+                    Some(guard) => {
+                        debug!("cycles start key {:?}", k);
+                        KeyComputingUserCycleDetector::Detecting {
+                            k_erased: k_erased.dupe(),
+                            k,
+                            guard,
+                            detector: detector.dupe(),
+                        }
+                    }
+                    None => KeyComputingUserCycleDetector::Untracked,
+                }
             }
+            None => KeyComputingUserCycleDetector::Untracked,
+        };
+
+        KeyComputingUserCycleDetectorData {
+            cycle_detector: self.cycle_detector,
+            active_keys: self.active_keys.push(k),
+            user,
         }
-        KeyComputingUserCycleDetectorData::Untracked
     }
 
     #[cfg(test)]
     pub(crate) fn testing_new() -> Self {
-        Self(())
+        Self {
+            cycle_detector: None,
+            active_keys: ActiveKeys::default(),
+        }
     }
 }
 
+/// Cycle detection data of a key that is being computed
+pub(crate) struct KeyComputingUserCycleDetectorData {
+    /// Present when dice was built with `DetectCycles::Enabled`
+    cycle_detector: Option<CycleDetector>,
+    active_keys: ActiveKeys,
+    user: KeyComputingUserCycleDetector,
+}
+
 /// User supplied cycle detector
-pub(crate) enum KeyComputingUserCycleDetectorData {
+enum KeyComputingUserCycleDetector {
     Detecting {
         k_erased: DiceKeyErased,
         k: DiceKey,
@@ -62,20 +114,53 @@ pub(crate) enum KeyComputingUserCycleDetectorData {
 }
 
 impl KeyComputingUserCycleDetectorData {
-    pub(crate) fn subrequest(&self, k: DiceKey, key_index: &DiceKeyIndex) -> UserCycleDetectorData {
-        match self {
-            KeyComputingUserCycleDetectorData::Detecting { guard, .. } => {
+    /// Data for the top level computations that are not computing any key.
+    pub(crate) fn root(detect_cycles: DetectCycles) -> Self {
+        Self {
+            cycle_detector: CycleDetector::new(detect_cycles),
+            active_keys: ActiveKeys::default(),
+            user: KeyComputingUserCycleDetector::Untracked,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn testing_untracked() -> Self {
+        Self::root(DetectCycles::Disabled)
+    }
+
+    /// Records a request of `k` from the current computation. Errors if requesting `k` would form
+    /// a cycle.
+    pub(crate) fn subrequest(
+        &self,
+        k: DiceKey,
+        key_index: &DiceKeyIndex,
+    ) -> DiceResult<UserCycleDetectorData> {
+        let cycle_detector = match &self.cycle_detector {
+            Some(detector) => Some(detector.visit(k, key_index)?),
+            None => None,
+        };
+
+        match &self.user {
+            KeyComputingUserCycleDetector::Detecting { guard, .. } => {
                 guard.add_edge(key_index.get(k).as_any());
             }
-            KeyComputingUserCycleDetectorData::Untracked => {}
+            KeyComputingUserCycleDetector::Untracked => {}
         }
 
-        UserCycleDetectorData(())
+        Ok(UserCycleDetectorData {
+            cycle_detector,
+            active_keys: self.active_keys.dupe(),
+        })
+    }
+
+    /// The keys being computed to reach the current computation, outermost first.
+    pub(crate) fn stack(&self) -> Vec<DiceKey> {
+        self.active_keys.to_vec()
     }
 
     pub(crate) fn cycle_guard<T: UserCycleDetectorGuard>(&self) -> DiceResult<Option<&T>> {
-        match self {
-            KeyComputingUserCycleDetectorData::Detecting { guard, .. } => {
+        match &self.user {
+            KeyComputingUserCycleDetector::Detecting { guard, .. } => {
                 match guard.as_any().downcast_ref() {
                     Some(guard) => Ok(Some(guard)),
                     None => Err(DiceError(Arc::new(
@@ -86,15 +171,15 @@ impl KeyComputingUserCycleDetectorData {
                     ))),
                 }
             }
-            KeyComputingUserCycleDetectorData::Untracked => Ok(None),
+            KeyComputingUserCycleDetector::Untracked => Ok(None),
         }
     }
 }
 
-impl Drop for KeyComputingUserCycleDetectorData {
+impl Drop for KeyComputingUserCycleDetector {
     fn drop(&mut self) {
         match self {
-            KeyComputingUserCycleDetectorData::Detecting {
+            KeyComputingUserCycleDetector::Detecting {
                 k_erased,
                 k,
                 detector,
@@ -103,7 +188,7 @@ impl Drop for KeyComputingUserCycleDetectorData {
                 debug!("cycles finish key {:?}", k);
                 detector.finished_computing_key(k_erased.as_any())
             }
-            KeyComputingUserCycleDetectorData::Untracked => {}
+            KeyComputingUserCycleDetector::Untracked => {}
         }
     }
 }
@@ -12,8 +12,11 @@ use std::fmt::Debug;
 use std::fmt::Formatter;
 
 use allocative::Allocative;
+use allocative::FlameGraphBuilder;
 use dupe::Dupe;
 
+use crate::api::cutoff::DiceCutoff;
+use crate::api::user_error::DiceUserError;
 use crate::arc::Arc;
 use crate::impls::core::graph::history::CellHistory;
 use crate::Key;
@@ -40,6 +43,14 @@ impl DiceValidValue {
     pub(crate) fn equality(&self, other: &DiceValidValue) -> bool {
         self.0.equality(&*other.0)
     }
+
+    /// The bytes retained by the value as measured by `allocative`, including the data it
+    /// shares with other values.
+    pub(crate) fn retained_bytes(&self) -> usize {
+        let mut builder = FlameGraphBuilder::default();
+        builder.visit_root(&self.0);
+        builder.finish().flamegraph().total_size()
+    }
 }
 
 /// Type erased value that may be transient, or whose dependencies are transient
@@ -86,6 +97,11 @@ impl MaybeValidDiceValue {
         self.value.downcast_ref()
     }
 
+    /// The error of the computation, if it failed.
+    pub(crate) fn user_error(&self) -> Option<&DiceUserError> {
+        self.value.user_error()
+    }
+
     /// Dynamic version of `Key::equality`.
     #[cfg(test)]
     pub(crate) fn equality(&self, other: &DiceValidValue) -> bool {
@@ -143,6 +159,7 @@ pub(crate) trait DiceValueDyn: Allocative + Any + Send + Sync + 'static {
     /// Panics if called with incompatible values.
     fn equality(&self, other: &dyn DiceValueDyn) -> bool;
     fn validity(&self) -> bool;
+    fn user_error(&self) -> Option<&DiceUserError>;
 }
 
 impl dyn DiceValueDyn {
@@ -174,12 +191,23 @@ where
     }
 
     fn equality(&self, other: &dyn DiceValueDyn) -> bool {
-        K::equality(&self.value, other.downcast_ref().unwrap())
+        match K::cutoff() {
+            DiceCutoff::Equality => K::equality(&self.value, other.downcast_ref().unwrap()),
+            // a reused value is the same instance, and is still unchanged
+            DiceCutoff::Version | DiceCutoff::AlwaysInvalidate => std::ptr::eq(
+                self as *const Self as *const (),
+                other as *const dyn DiceValueDyn as *const (),
+            ),
+        }
     }
 
     fn validity(&self) -> bool {
         K::validity(&self.value)
     }
+
+    fn user_error(&self) -> Option<&DiceUserError> {
+        None
+    }
 }
 
 #[derive(Allocative)]
@@ -211,6 +239,33 @@ where
     fn validity(&self) -> bool {
         K::validity(&self.value)
     }
+
+    fn user_error(&self) -> Option<&DiceUserError> {
+        None
+    }
+}
+
+/// The error of a key computation that failed. It is never valid, so it is shared by the
+/// requests at the same version but never stored.
+#[derive(Allocative)]
+pub(crate) struct DiceUserErrorValue(pub(crate) DiceUserError);
+
+impl DiceValueDyn for DiceUserErrorValue {
+    fn value_as_any(&self) -> &dyn Any {
+        &self.0
+    }
+
+    fn equality(&self, _other: &dyn DiceValueDyn) -> bool {
+        false
+    }
+
+    fn validity(&self) -> bool {
+        false
+    }
+
+    fn user_error(&self) -> Option<&DiceUserError> {
+        Some(&self.0)
+    }
 }
 
 #[cfg(test)]
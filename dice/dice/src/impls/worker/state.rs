@@ -12,6 +12,7 @@
 use more_futures::cancellable_future::DisableCancellationGuard;
 use more_futures::cancellation::ExplicitCancellationContext;
 
+use crate::api::error::DiceResult;
 use crate::impls::evaluator::AsyncEvaluator;
 use crate::impls::evaluator::KeyEvaluationResult;
 use crate::impls::key::DiceKey;
@@ -143,7 +144,7 @@ impl<'a> DiceWorkerStateCheckingDeps<'a> {
         &self,
         dep: DiceKey,
         eval: &AsyncEvaluator,
-    ) -> UserCycleDetectorData {
+    ) -> DiceResult<UserCycleDetectorData> {
         self.cycles.subrequest(dep, &eval.dice.key_index)
     }
 
@@ -180,7 +181,7 @@ impl<'a> DiceWorkerStateCheckingDeps<'a> {
     #[cfg(test)]
     pub(crate) fn testing() -> Self {
         DiceWorkerStateCheckingDeps {
-            cycles: KeyComputingUserCycleDetectorData::Untracked,
+            cycles: KeyComputingUserCycleDetectorData::testing_untracked(),
             internals: DiceTaskHandle::testing_new(),
         }
     }
@@ -0,0 +1,22 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Reporting computations that were cancelled but are still running, for diagnosing stuck
+//! cancellations.
+
+use std::time::Duration;
+
+/// A computation that was cancelled but hasn't terminated yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CancelledComputation {
+    /// The debug representation of the key being computed.
+    pub key: String,
+    /// How long ago the computation was cancelled.
+    pub elapsed: Duration,
+}
@@ -0,0 +1,21 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Reporting what changed between two committed versions, e.g. for explaining what a build has
+//! to redo since the last one.
+
+/// The changes committed between two versions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VersionChanges {
+    /// The debug representations of the keys reported as changed, sorted.
+    pub changed: Vec<String>,
+    /// The debug representations of the keys that were invalidated because something they
+    /// depend on changed, sorted.
+    pub invalidated: Vec<String>,
+}
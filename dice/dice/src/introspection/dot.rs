@@ -0,0 +1,113 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Exporting the dependency graph in the Graphviz DOT format, to visualize what a computation
+//! depends on when debugging over-invalidation.
+
+use std::collections::VecDeque;
+use std::io::Write;
+
+use anyhow::Context as _;
+
+use crate::introspection::graph::AnyKey;
+use crate::introspection::graph::GraphIntrospectable;
+use crate::HashMap;
+use crate::HashSet;
+
+/// Selects the part of the graph written by [`GraphIntrospectable::write_dot`].
+#[derive(Clone, Debug, Default)]
+pub struct DotOptions {
+    /// Only write the keys reachable from the key with this display representation.
+    pub root: Option<String>,
+    /// Only write the keys at most this many dependency edges away from `root`.
+    pub max_depth: Option<usize>,
+    /// Only write keys of these types, as given by `AnyKey::short_type_name`. Keys of other types
+    /// are still followed to reach further keys, but they and their edges are left out.
+    pub key_types: Option<Vec<String>>,
+}
+
+impl GraphIntrospectable {
+    /// Writes the dependencies of the newest version of each key as a DOT digraph.
+    pub fn write_dot(&self, mut out: impl Write, options: &DotOptions) -> anyhow::Result<()> {
+        let mut deps: HashMap<AnyKey, Vec<AnyKey>> = HashMap::default();
+        for engine in self.introspectables() {
+            for k in engine.keys() {
+                deps.entry(k).or_default();
+            }
+            for (k, vs) in engine.edges() {
+                deps.entry(k).or_default().extend(vs);
+            }
+        }
+
+        let reachable: HashSet<&AnyKey> = match &options.root {
+            Some(root) => {
+                let root = deps
+                    .keys()
+                    .find(|k| k.to_string() == *root)
+                    .with_context(|| format!("Key `{}` is not in the graph", root))?;
+
+                let mut reachable = HashSet::default();
+                reachable.insert(root);
+                let mut queue = VecDeque::from([(root, 0)]);
+                while let Some((k, depth)) = queue.pop_front() {
+                    if options.max_depth.map_or(false, |max| depth >= max) {
+                        continue;
+                    }
+                    for dep in deps.get(k).into_iter().flatten() {
+                        if reachable.insert(dep) {
+                            queue.push_back((dep, depth + 1));
+                        }
+                    }
+                }
+                reachable
+            }
+            None => deps.keys().collect(),
+        };
+
+        let mut nodes: Vec<(String, &AnyKey)> = reachable
+            .into_iter()
+            .filter(|k| {
+                options
+                    .key_types
+                    .as_ref()
+                    .map_or(true, |types| types.iter().any(|t| t == k.short_type_name()))
+            })
+            .map(|k| (k.to_string(), k))
+            .collect();
+        nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let ids: HashMap<&AnyKey, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(id, (_, k))| (*k, id))
+            .collect();
+
+        writeln!(out, "digraph dice {{")?;
+        for (id, (label, _)) in nodes.iter().enumerate() {
+            writeln!(out, "  n{} [label=\"{}\"];", id, escape(label))?;
+        }
+        for (id, (_, k)) in nodes.iter().enumerate() {
+            let mut dep_ids: Vec<usize> = deps[*k]
+                .iter()
+                .filter_map(|d| ids.get(d).copied())
+                .collect();
+            dep_ids.sort_unstable();
+            dep_ids.dedup();
+            for dep_id in dep_ids {
+                writeln!(out, "  n{} -> n{};", id, dep_id)?;
+            }
+        }
+        writeln!(out, "}}")?;
+
+        Ok(())
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -29,6 +29,7 @@ use serde::Deserializer;
 use serde::Serialize;
 use serde::Serializer;
 
+use crate::api::cutoff::DiceCutoff;
 use crate::impls::core::graph::introspection::VersionedGraphIntrospectable;
 use crate::impls::core::versions::introspection::VersionIntrospectable;
 use crate::impls::key::DiceKey;
@@ -131,6 +132,7 @@ pub struct NodeID(pub usize);
     Clone,
     Dupe,
     Copy,
+    Debug,
     Ord,
     PartialOrd,
     derive_more::Display
@@ -155,7 +157,7 @@ impl<'de> Visitor<'de> for VersionNumberVisitor {
         formatter.write_str("string of format `vX` where X is a usize, like `v2`")
     }
 
-    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
     where
         E: Error,
     {
@@ -239,6 +241,9 @@ pub struct SerializedGraphNodesForKey {
     pub id: KeyID,
     pub key: String,
     pub type_name: String,
+    /// `None` if the engine doesn't track cutoffs, or the dump predates them
+    #[serde(default)]
+    pub cutoff: Option<DiceCutoff>,
     pub nodes: BTreeMap<VersionNumber, Option<SerializedGraphNode>>,
 }
 
@@ -0,0 +1,42 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Reporting the memory retained by the values of the graph, for attributing daemon memory to
+//! specific computations. Measuring walks every value, so this is only computed on demand.
+
+use std::collections::BTreeMap;
+
+use crate::introspection::graph::VersionNumber;
+
+/// The bytes retained by the values held by the graph, as measured by `allocative`. Data shared
+/// between values is counted once for each of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// By key type name.
+    pub key_types: BTreeMap<&'static str, KeyTypeMemoryUsage>,
+    /// By the version the values were stored at, where a version only retains the values that
+    /// changed at it.
+    pub versions: BTreeMap<VersionNumber, u64>,
+}
+
+/// The values of a single key type held by the graph, across all versions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyTypeMemoryUsage {
+    pub values: u64,
+    pub bytes: u64,
+}
+
+impl MemoryUsage {
+    pub(crate) fn add(&mut self, key_type: &'static str, version: VersionNumber, bytes: usize) {
+        let key_type = self.key_types.entry(key_type).or_default();
+        key_type.values += 1;
+        key_type.bytes += bytes as u64;
+        *self.versions.entry(version).or_default() += bytes as u64;
+    }
+}
@@ -16,8 +16,14 @@ use crate::introspection::graph::LegacyIntrospectable;
 use crate::Dice;
 use crate::DiceImplementation;
 
+pub mod cancellations;
+pub mod changes;
+pub mod dot;
 pub mod graph;
 pub(crate) mod introspect;
+pub mod memory;
+pub mod recompute;
+pub mod snapshot;
 
 pub use crate::introspection::introspect::serialize_dense_graph;
 pub use crate::introspection::introspect::serialize_graph;
@@ -27,9 +33,7 @@ impl Dice {
     pub fn to_introspectable(&self) -> GraphIntrospectable {
         match &self.implementation {
             DiceImplementation::Legacy(dice) => dice.to_introspectable(),
-            DiceImplementation::Modern(_) => {
-                unimplemented!("todo")
-            }
+            DiceImplementation::Modern(dice) => dice.to_introspectable(),
         }
     }
 }
@@ -44,18 +48,27 @@ impl DiceLegacy {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use allocative::Allocative;
     use anyhow::Context as _;
     use async_trait::async_trait;
     use derive_more::Display;
     use dupe::Dupe;
     use more_futures::cancellation::CancellationContext;
+    use serde::Serialize;
 
     use crate::api::computations::DiceComputations;
     use crate::api::cycles::DetectCycles;
     use crate::api::key::Key;
+    use crate::impls::dice::DiceModern;
+    use crate::introspection::dot::DotOptions;
+    use crate::introspection::graph::KeyID;
+    use crate::introspection::graph::SerializedGraphNode;
     use crate::introspection::graph::SerializedGraphNodesForKey;
+    use crate::introspection::graph::VersionNumber;
     use crate::introspection::serialize_graph;
+    use crate::introspection::snapshot::GraphSnapshot;
     use crate::DiceLegacy;
     use crate::HashMap;
     use crate::WhichSpawner;
@@ -173,4 +186,137 @@ mod tests {
         let _out: Vec<SerializedGraphNodesForKey> = bincode::deserialize(&node)?;
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_snapshot_round_trip() -> anyhow::Result<()> {
+        let dice = DiceModern::builder().build(DetectCycles::Disabled);
+        let ctx = dice.updater().commit().await;
+        ctx.compute(&KeyA(1)).await?;
+        drop(ctx);
+
+        let mut updater = dice.updater();
+        updater.changed([KeyB])?;
+        drop(updater.commit().await);
+
+        let mut file = Vec::new();
+        GraphSnapshot::capture(&dice.to_introspectable()).write(&mut file)?;
+        let snapshot = GraphSnapshot::read(file.as_slice())?;
+
+        let a1 = snapshot.get("KeyA(1)").context("Missing key")?;
+        assert_eq!(
+            vec!["KeyA(0)"],
+            snapshot
+                .deps(a1)
+                .into_iter()
+                .map(|k| &k.key)
+                .collect::<Vec<_>>()
+        );
+
+        let mut dirtied = snapshot
+            .dirtied_at(VersionNumber(1))
+            .map(|k| k.key.as_str())
+            .collect::<Vec<_>>();
+        dirtied.sort_unstable();
+        assert_eq!(vec!["KeyA(0)", "KeyA(1)", "KeyB"], dirtied);
+
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        snapshot.write_tsv(&mut nodes, &mut edges)?;
+        assert_eq!(3, String::from_utf8(nodes)?.lines().count());
+        assert_eq!(2, String::from_utf8(edges)?.lines().count());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_snapshot_reads_dense_graphs() -> anyhow::Result<()> {
+        /// The layout of dense graph serializations before keys recorded their cutoff
+        #[derive(Serialize)]
+        struct WithoutCutoff {
+            id: KeyID,
+            key: String,
+            type_name: String,
+            nodes: BTreeMap<VersionNumber, Option<SerializedGraphNode>>,
+        }
+
+        let dice = DiceModern::builder().build(DetectCycles::Disabled);
+        let ctx = dice.updater().commit().await;
+        ctx.compute(&KeyA(1)).await?;
+        drop(ctx);
+
+        let dense = bincode::serialize(&dice.to_introspectable())?;
+        let snapshot = GraphSnapshot::read(dense.as_slice())?;
+        assert_eq!(3, snapshot.keys().len());
+
+        let old = bincode::serialize(
+            &snapshot
+                .keys
+                .iter()
+                .map(|k| WithoutCutoff {
+                    id: k.id,
+                    key: k.key.clone(),
+                    type_name: k.type_name.clone(),
+                    nodes: k.nodes.clone(),
+                })
+                .collect::<Vec<_>>(),
+        )?;
+        let old_snapshot = GraphSnapshot::read(old.as_slice())?;
+        let a1 = old_snapshot.get("KeyA(1)").context("Missing key")?;
+        assert!(a1.cutoff.is_none());
+        assert_eq!(1, old_snapshot.deps(a1).len());
+
+        let mut newer = Vec::new();
+        snapshot.write(&mut newer)?;
+        newer[8] += 1;
+        assert!(GraphSnapshot::read(newer.as_slice()).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_write_dot() -> anyhow::Result<()> {
+        let dice = DiceModern::builder().build(DetectCycles::Disabled);
+        let ctx = dice.updater().commit().await;
+        ctx.compute(&KeyA(2)).await?;
+        drop(ctx);
+        let graph = dice.to_introspectable();
+
+        let dot = |options: DotOptions| -> anyhow::Result<String> {
+            let mut out = Vec::new();
+            graph.write_dot(&mut out, &options)?;
+            Ok(String::from_utf8(out)?)
+        };
+
+        assert_eq!(
+            "digraph dice {\n  n0 [label=\"KeyA(1)\"];\n  n1 [label=\"KeyA(2)\"];\n  n1 -> n0;\n}\n",
+            dot(DotOptions {
+                root: Some("KeyA(2)".to_owned()),
+                max_depth: Some(1),
+                ..Default::default()
+            })?
+        );
+        assert_eq!(
+            "digraph dice {\n  n0 [label=\"KeyA(0)\"];\n  n1 [label=\"KeyA(1)\"];\n  n2 [label=\"KeyB\"];\n  n0 -> n2;\n  n1 -> n0;\n}\n",
+            dot(DotOptions {
+                root: Some("KeyA(1)".to_owned()),
+                ..Default::default()
+            })?
+        );
+        assert_eq!(
+            "digraph dice {\n  n0 [label=\"KeyB\"];\n}\n",
+            dot(DotOptions {
+                key_types: Some(vec!["KeyB".to_owned()]),
+                ..Default::default()
+            })?
+        );
+        assert!(
+            dot(DotOptions {
+                root: Some("KeyA(3)".to_owned()),
+                ..Default::default()
+            })
+            .is_err()
+        );
+
+        Ok(())
+    }
 }
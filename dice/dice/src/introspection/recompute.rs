@@ -0,0 +1,58 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Explaining why a key was recomputed, for debugging unexpected cache misses.
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+/// Why a key in a [`RecomputeChain`] was recomputed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecomputeReason {
+    /// There was no previous value, because the key was never computed or was evicted.
+    NotComputed,
+    /// The key itself was invalidated.
+    Invalidated,
+    /// The next key in the chain changed.
+    DepChanged,
+    /// The key changed without being recomputed, e.g. because it was injected. This ends a chain.
+    Changed,
+}
+
+/// One key of a [`RecomputeChain`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecomputeStep {
+    pub key: String,
+    pub reason: RecomputeReason,
+}
+
+/// The chain of changed dependencies that caused a key to be recomputed. The first step is the
+/// requested key, every `DepChanged` step is followed by the dependency that changed, and the
+/// last step is the origin of the change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecomputeChain(pub Vec<RecomputeStep>);
+
+impl Display for RecomputeChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, " <- ")?;
+            }
+            let reason = match step.reason {
+                RecomputeReason::NotComputed => "not computed",
+                RecomputeReason::Invalidated => "invalidated",
+                RecomputeReason::DepChanged => "dep changed",
+                RecomputeReason::Changed => "changed",
+            };
+            write!(f, "{} ({})", step.key, reason)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Snapshots of the shape of the DICE graph that can be saved to a file and analysed offline,
+//! e.g. to debug incrementality issues reported by users.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::io::Write;
+
+use anyhow::Context as _;
+use bincode::Options;
+use once_cell::sync::OnceCell;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::introspection::graph::GraphIntrospectable;
+use crate::introspection::graph::HistoryState;
+use crate::introspection::graph::KeyID;
+use crate::introspection::graph::SerializedGraphNode;
+use crate::introspection::graph::SerializedGraphNodesForKey;
+use crate::introspection::graph::VersionNumber;
+use crate::HashMap;
+
+/// Starts every snapshot file, followed by the `u32` version of its layout.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"DICESNAP";
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+enum GraphSnapshotError {
+    #[error("Unsupported graph snapshot version {0}, expected {SNAPSHOT_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// The nodes, edges, versions and dirtiness of every key in the graph, without their values.
+///
+/// Snapshots start with a version header. Files without one are read as dense graph
+/// serializations, including those written before keys recorded their cutoff.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    keys: Vec<SerializedGraphNodesForKey>,
+    /// Index of each key in `keys`, by id, built the first time it is needed.
+    #[serde(skip)]
+    by_id: OnceCell<HashMap<KeyID, usize>>,
+}
+
+/// `SerializedGraphNodesForKey` as dense graph serializations wrote it before `cutoff` was added.
+#[derive(Deserialize)]
+struct SerializedGraphNodesForKeyWithoutCutoff {
+    id: KeyID,
+    key: String,
+    type_name: String,
+    nodes: BTreeMap<VersionNumber, Option<SerializedGraphNode>>,
+}
+
+/// Bincode as written by `bincode::serialize`, but rejecting input that isn't fully consumed, so
+/// that a layout that doesn't match is noticed.
+fn deserialize_exact<T: DeserializeOwned>(bytes: &[u8]) -> bincode::Result<T> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+}
+
+impl GraphSnapshot {
+    fn new(keys: Vec<SerializedGraphNodesForKey>) -> Self {
+        Self {
+            keys,
+            by_id: OnceCell::new(),
+        }
+    }
+
+    pub fn capture(graph: &GraphIntrospectable) -> Self {
+        let mut reg = HashMap::default();
+        Self::new(
+            graph
+                .introspectables()
+                .flat_map(|engine| engine.nodes(&mut reg).collect::<Vec<_>>())
+                .collect(),
+        )
+    }
+
+    pub fn keys(&self) -> &[SerializedGraphNodesForKey] {
+        &self.keys
+    }
+
+    pub fn write(&self, mut out: impl Write) -> anyhow::Result<()> {
+        out.write_all(SNAPSHOT_MAGIC)
+            .context("Failed to write graph snapshot")?;
+        bincode::serialize_into(&mut out, &SNAPSHOT_VERSION)
+            .context("Failed to write graph snapshot")?;
+        bincode::serialize_into(out, self).context("Failed to write graph snapshot")
+    }
+
+    pub fn read(mut input: impl Read) -> anyhow::Result<Self> {
+        let mut bytes = Vec::new();
+        input
+            .read_to_end(&mut bytes)
+            .context("Failed to read graph snapshot")?;
+
+        match bytes.strip_prefix(SNAPSHOT_MAGIC) {
+            Some(versioned) => {
+                let (version, body) = versioned.split_at(versioned.len().min(4));
+                let version: u32 =
+                    deserialize_exact(version).context("Failed to read graph snapshot")?;
+                if version != SNAPSHOT_VERSION {
+                    return Err(GraphSnapshotError::UnsupportedVersion(version).into());
+                }
+                deserialize_exact(body).context("Failed to read graph snapshot")
+            }
+            None => Self::read_dense_graph(&bytes),
+        }
+    }
+
+    fn read_dense_graph(bytes: &[u8]) -> anyhow::Result<Self> {
+        if let Ok(keys) = deserialize_exact(bytes) {
+            return Ok(Self::new(keys));
+        }
+        let keys: Vec<SerializedGraphNodesForKeyWithoutCutoff> =
+            deserialize_exact(bytes).context("Failed to read graph snapshot")?;
+        Ok(Self::new(
+            keys.into_iter()
+                .map(|k| SerializedGraphNodesForKey {
+                    id: k.id,
+                    key: k.key,
+                    type_name: k.type_name,
+                    cutoff: None,
+                    nodes: k.nodes,
+                })
+                .collect(),
+        ))
+    }
+
+    /// Finds a key by its display representation.
+    pub fn get(&self, key: &str) -> Option<&SerializedGraphNodesForKey> {
+        self.keys.iter().find(|k| k.key == key)
+    }
+
+    /// The deps of the newest node of the key.
+    pub fn deps(&self, key: &SerializedGraphNodesForKey) -> Vec<&SerializedGraphNodesForKey> {
+        let by_id = self.by_id();
+        key.nodes
+            .values()
+            .rev()
+            .find_map(|node| node.as_ref().and_then(|node| node.deps.as_ref()))
+            .map_or_else(Vec::new, |deps| {
+                deps.iter()
+                    .filter_map(|id| by_id.get(id).map(|i| &self.keys[*i]))
+                    .collect()
+            })
+    }
+
+    /// The keys that were dirtied at exactly the given version, i.e. that were invalidated or had
+    /// a dep that changed.
+    pub fn dirtied_at(
+        &self,
+        version: VersionNumber,
+    ) -> impl Iterator<Item = &SerializedGraphNodesForKey> + '_ {
+        self.keys.iter().filter(move |k| {
+            k.nodes.values().flatten().any(|node| {
+                matches!(
+                    node.history.history.get(&version),
+                    Some(HistoryState::Dirty | HistoryState::ForceDirty)
+                )
+            })
+        })
+    }
+
+    /// Writes the keys and the edges between them in the same tab separated format as
+    /// `serialize_graph`.
+    pub fn write_tsv(&self, mut nodes: impl Write, mut edges: impl Write) -> anyhow::Result<()> {
+        let mut keys = self.keys.iter().collect::<Vec<_>>();
+        keys.sort_by_key(|k| k.id.0);
+
+        for key in keys {
+            writeln!(nodes, "{}\t{}\t{}", key.id.0, key.type_name, key.key)
+                .context("Failed to write node")?;
+            for dep in self.deps(key) {
+                writeln!(edges, "{}\t{}", key.id.0, dep.id.0).context("Failed to write edge")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn by_id(&self) -> &HashMap<KeyID, usize> {
+        self.by_id.get_or_init(|| {
+            self.keys
+                .iter()
+                .enumerate()
+                .map(|(i, k)| (k.id, i))
+                .collect()
+        })
+    }
+}
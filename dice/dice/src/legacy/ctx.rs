@@ -23,6 +23,7 @@ use crate::api::error::DiceErrorImpl;
 use crate::api::error::DiceResult;
 use crate::api::key::Key;
 use crate::api::projection::ProjectionKey;
+use crate::api::transaction_data::TransactionData;
 use crate::api::user_data::UserComputationData;
 use crate::api::user_data::UserCycleDetectorGuard;
 use crate::legacy::cycles::CycleDetector;
@@ -339,6 +340,10 @@ impl DiceComputationsImplLegacy {
         &self.extra.user_data
     }
 
+    pub(crate) fn transaction_data(&self) -> &TransactionData {
+        self.transaction_ctx.transaction_data()
+    }
+
     pub(crate) fn cycle_guard<T: UserCycleDetectorGuard>(&self) -> DiceResult<Option<&T>> {
         match &self.extra.user_cycle_detector_guard {
             None => Ok(None),
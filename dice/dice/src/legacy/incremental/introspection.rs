@@ -164,6 +164,7 @@ where
                 id: map_id(k.clone()),
                 key: k.to_string(),
                 type_name: k.short_type_name().to_owned(),
+                cutoff: None,
                 nodes: e
                     .value()
                     .iter()
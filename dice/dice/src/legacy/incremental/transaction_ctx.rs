@@ -19,6 +19,7 @@ use parking_lot::MutexGuard;
 use crate::api::error::DiceError;
 use crate::api::error::DiceResult;
 use crate::api::key::Key;
+use crate::api::transaction_data::TransactionData;
 use crate::legacy::incremental::versions::MinorVersion;
 use crate::legacy::incremental::versions::VersionForWrites;
 use crate::legacy::incremental::versions::VersionGuard;
@@ -66,6 +67,7 @@ pub(crate) struct TransactionCtx {
     version_guard: VersionGuard,
     version_for_writes: VersionForWrites,
     changes: Mutex<Changes>,
+    transaction_data: TransactionData,
     _active_transaction_count_guard: ActiveTransactionCountGuard,
 }
 
@@ -80,6 +82,7 @@ impl TransactionCtx {
             version_guard,
             version_for_writes,
             changes: Mutex::new(changes),
+            transaction_data: TransactionData::default(),
             _active_transaction_count_guard: active_transaction_count_guard,
         }
     }
@@ -96,6 +99,10 @@ impl TransactionCtx {
         self.version_for_writes.get()
     }
 
+    pub(crate) fn transaction_data(&self) -> &TransactionData {
+        &self.transaction_data
+    }
+
     pub(crate) fn changes(&self) -> MutexGuard<'_, Changes> {
         self.changes.lock()
     }
@@ -110,6 +117,7 @@ impl TransactionCtx {
             ),
             version_for_writes: VersionForWrites::testing_new(v),
             changes: Mutex::new(Changes::new()),
+            transaction_data: TransactionData::default(),
             _active_transaction_count_guard: ActiveTransactionCountGuard::testing_new(),
         }
     }
@@ -225,6 +225,14 @@ impl DiceLegacy {
             active_transaction_count: self
                 .active_transaction_count
                 .load(std::sync::atomic::Ordering::SeqCst),
+            compute_count: 0,
+            transient_count: 0,
+            transient_retry_count: 0,
+            gc_count: 0,
+            gc_reclaimed_node_count: 0,
+            key_index: Default::default(),
+            queue_delay_by_priority: Default::default(),
+            key_types: Default::default(),
         }
     }
 
@@ -284,11 +292,17 @@ impl<K: Key> Evaluator for StoragePropertiesForKey<K> {
         );
 
         let value = k
-            .compute(
+            .try_compute(
                 &DiceComputations(DiceComputationsImpl::Legacy(ctx.dupe())),
                 cancellations,
             )
-            .await;
+            .await
+            .unwrap_or_else(|e| {
+                panic!(
+                    "computing `{}` failed, which is only supported by modern DICE: {:#}",
+                    k, e
+                )
+            });
 
         let (both_deps, extra) = ctx.finalize();
 
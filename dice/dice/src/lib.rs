@@ -241,6 +241,7 @@ use serde::Serializer;
 pub use crate::api::activation_tracker::ActivationData;
 pub use crate::api::activation_tracker::ActivationTracker;
 pub use crate::api::computations::DiceComputations;
+pub use crate::api::cutoff::DiceCutoff;
 pub use crate::api::cycles::DetectCycles;
 pub use crate::api::data::DiceData;
 pub use crate::api::dice::Dice;
@@ -249,27 +250,45 @@ pub use crate::api::error::DiceError;
 pub use crate::api::error::DiceResult;
 pub use crate::api::events::DiceEvent;
 pub use crate::api::events::DiceEventListener;
+pub use crate::api::eviction::DiceEvictionPolicy;
+pub use crate::api::gc::DiceGcPolicy;
 pub use crate::api::injected::InjectedKey;
+pub use crate::api::invalidations::DiceInvalidations;
 pub use crate::api::key::Key;
+pub use crate::api::on_computed::DiceComputedEvent;
+pub use crate::api::on_computed::DiceComputedObserver;
 pub use crate::api::opaque::OpaqueValue;
+pub use crate::api::persistence::DicePersistence;
+pub use crate::api::persistence::DicePersistenceStats;
+pub use crate::api::persistence::DicePersistentStore;
+pub use crate::api::persistence::DiskPersistentStore;
+pub use crate::api::priority::DicePriority;
 pub use crate::api::projection::DiceProjectionComputations;
 pub use crate::api::projection::ProjectionKey;
 pub use crate::api::transaction::DiceEquality;
 pub use crate::api::transaction::DiceTransaction;
 pub use crate::api::transaction::DiceTransactionUpdater;
+pub use crate::api::transaction_data::TransactionData;
+pub use crate::api::transient::DiceTransientRetryPolicy;
 pub use crate::api::user_data::UserComputationData;
 pub use crate::api::user_data::UserCycleDetector;
 pub use crate::api::user_data::UserCycleDetectorGuard;
+pub use crate::api::user_error::DiceUserError;
+pub use crate::api::user_error::DiceUserResult;
 pub use crate::api::which::WhichDice;
 pub use crate::api::which::WhichSpawner;
 use crate::impls::dice::DiceModern;
 use crate::impls::dice::DiceModernDataBuilder;
+use crate::introspection::cancellations::CancelledComputation;
+use crate::introspection::changes::VersionChanges;
 use crate::introspection::graph::GraphIntrospectable;
+use crate::introspection::memory::MemoryUsage;
 use crate::introspection::serialize_dense_graph;
 use crate::introspection::serialize_graph;
 use crate::legacy::DiceLegacy;
 use crate::legacy::DiceLegacyDataBuilder;
 use crate::transaction_update::DiceTransactionUpdaterImpl;
+use crate::versions::VersionNumber;
 
 #[derive(Allocative, Debug)]
 pub(crate) enum DiceImplementation {
@@ -353,6 +372,45 @@ impl DiceImplementation {
             DiceImplementation::Modern(dice) => dice.is_idle().await,
         }
     }
+
+    pub async fn gc(&self) -> usize {
+        match self {
+            DiceImplementation::Legacy(_) => 0,
+            DiceImplementation::Modern(dice) => dice.gc().await,
+        }
+    }
+
+    pub async fn notify_memory_pressure(&self, bytes: usize) -> usize {
+        match self {
+            DiceImplementation::Legacy(_) => 0,
+            DiceImplementation::Modern(dice) => dice.notify_memory_pressure(bytes).await,
+        }
+    }
+
+    pub async fn memory_usage(&self) -> Option<MemoryUsage> {
+        match self {
+            DiceImplementation::Legacy(_) => None,
+            DiceImplementation::Modern(dice) => Some(dice.memory_usage().await),
+        }
+    }
+
+    pub async fn cancelled_computations(&self) -> Vec<CancelledComputation> {
+        match self {
+            DiceImplementation::Legacy(_) => Vec::new(),
+            DiceImplementation::Modern(dice) => dice.cancelled_computations().await,
+        }
+    }
+
+    pub async fn changes_between(
+        &self,
+        from: VersionNumber,
+        to: VersionNumber,
+    ) -> Option<VersionChanges> {
+        match self {
+            DiceImplementation::Legacy(_) => None,
+            DiceImplementation::Modern(dice) => dice.changes_between(from, to).await,
+        }
+    }
 }
 
 pub(crate) enum DiceDataBuilderImpl {
@@ -376,6 +434,63 @@ impl DiceDataBuilderImpl {
         }
     }
 
+    pub fn set_persistence(&mut self, persistence: DicePersistence) {
+        match self {
+            DiceDataBuilderImpl::Legacy(_) => {}
+            DiceDataBuilderImpl::Modern(d) => d.set_persistence(persistence),
+        }
+    }
+
+    pub fn set_eviction_policy(&mut self, policy: DiceEvictionPolicy) {
+        match self {
+            DiceDataBuilderImpl::Legacy(_) => {}
+            DiceDataBuilderImpl::Modern(d) => d.set_eviction_policy(policy),
+        }
+    }
+
+    pub fn set_record_recomputes(&mut self, record: bool) {
+        match self {
+            DiceDataBuilderImpl::Legacy(_) => {}
+            DiceDataBuilderImpl::Modern(d) => d.set_record_recomputes(record),
+        }
+    }
+
+    pub fn set_transient_retry_policy(&mut self, policy: DiceTransientRetryPolicy) {
+        match self {
+            DiceDataBuilderImpl::Legacy(_) => {}
+            DiceDataBuilderImpl::Modern(d) => d.set_transient_retry_policy(policy),
+        }
+    }
+
+    pub fn set_gc_policy(&mut self, policy: DiceGcPolicy) {
+        match self {
+            DiceDataBuilderImpl::Legacy(_) => {}
+            DiceDataBuilderImpl::Modern(d) => d.set_gc_policy(policy),
+        }
+    }
+
+    pub fn set_key_index_shards(&mut self, shards: u32) {
+        match self {
+            DiceDataBuilderImpl::Legacy(_) => {}
+            DiceDataBuilderImpl::Modern(d) => d.set_key_index_shards(shards),
+        }
+    }
+
+    pub fn add_computed_observer(&mut self, observer: Arc<dyn DiceComputedObserver>) {
+        match self {
+            DiceDataBuilderImpl::Legacy(_) => {}
+            DiceDataBuilderImpl::Modern(d) => d.add_computed_observer(observer),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_state_on_current_runtime(&mut self) {
+        match self {
+            DiceDataBuilderImpl::Legacy(_) => {}
+            DiceDataBuilderImpl::Modern(d) => d.set_state_on_current_runtime(),
+        }
+    }
+
     pub fn build(self, detect_cycles: DetectCycles, which_spawner: WhichSpawner) -> Arc<Dice> {
         Dice::new(match self {
             DiceDataBuilderImpl::Legacy(d) => {
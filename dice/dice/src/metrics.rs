@@ -7,6 +7,11 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::api::priority::DicePriority;
+
 /// Dice metrics.
 #[derive(Debug)]
 pub struct Metrics {
@@ -14,4 +19,197 @@ pub struct Metrics {
     /// The number of keys currently active in the per transaction cache
     pub currently_active_key_count: usize,
     pub active_transaction_count: u32,
+    /// The number of times `Key::compute` has run, including retries. Only tracked by modern DICE.
+    pub compute_count: u64,
+    /// The number of computations that produced transient values, either themselves or through
+    /// transient deps. Only tracked by modern DICE.
+    pub transient_count: u64,
+    /// The number of computations retried because of transient values, see
+    /// [`DiceTransientRetryPolicy`](crate::DiceTransientRetryPolicy). Only tracked by modern DICE.
+    pub transient_retry_count: u64,
+    /// Statistics by key type name. Only tracked by modern DICE.
+    pub key_types: BTreeMap<&'static str, KeyTypeMetrics>,
+    /// The number of times superseded versions were collected from the graph, see
+    /// [`DiceGcPolicy`](crate::DiceGcPolicy). Only tracked by modern DICE.
+    pub gc_count: u64,
+    /// The total number of graph nodes reclaimed by those collections.
+    pub gc_reclaimed_node_count: u64,
+    /// Statistics of the index assigning ids to keys. Only tracked by modern DICE.
+    pub key_index: KeyIndexMetrics,
+    /// How long computations waited to start, by the priority of their transaction. Only tracked
+    /// by modern DICE.
+    pub queue_delay_by_priority: BTreeMap<DicePriority, QueueDelayMetrics>,
+}
+
+impl Metrics {
+    /// The priorities whose work is waiting too long to start, see
+    /// [`QueueDelayMetrics::is_starved`].
+    pub fn starved_priorities(&self) -> Vec<DicePriority> {
+        self.queue_delay_by_priority
+            .iter()
+            .filter(|(_, delay)| delay.is_starved())
+            .map(|(priority, _)| *priority)
+            .collect()
+    }
+}
+
+/// Statistics of the sharded index of all keys ever requested, see
+/// [`DiceDataBuilder::set_key_index_shards`](crate::DiceDataBuilder::set_key_index_shards).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyIndexMetrics {
+    pub shard_count: usize,
+    /// The number of keys in the least occupied shard.
+    pub min_shard_key_count: usize,
+    /// The number of keys in the most occupied shard.
+    pub max_shard_key_count: usize,
+    /// The number of times adding a key had to wait for another key being added to the same
+    /// shard.
+    pub lock_contention_count: u64,
+}
+
+/// Statistics of computations of a single key type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyTypeMetrics {
+    /// Computations that finished.
+    pub computes: u64,
+    /// Requests answered by an existing value, including values reused after checking their deps.
+    pub cache_hits: u64,
+    /// Computations cancelled before they finished.
+    pub cancellations: u64,
+    /// Total time spent in finished computations.
+    pub total_compute_time: Duration,
+    pub max_compute_time: Duration,
+    /// `compute_time_histogram[i]` is the number of finished computations that took less than
+    /// `KeyTypeMetrics::HISTOGRAM_BOUNDS[i]`, but not less than the previous bound. The last
+    /// element counts computations that took longer than every bound.
+    pub compute_time_histogram: [u64; 6],
+    /// How long requests of the key type waited to start.
+    pub queue_delay: QueueDelayMetrics,
+}
+
+/// Percentiles of the time between the lookup of a key being queued for the DICE state and it
+/// being answered, i.e. how long the key waited to start computing, over the
+/// `QueueDelayMetrics::SAMPLES` most recent requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueDelayMetrics {
+    /// The number of requests measured, including those no longer sampled.
+    pub count: u64,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl QueueDelayMetrics {
+    pub const SAMPLES: usize = 1024;
+
+    /// Requests are starved when one in ten waits at least this long to start.
+    pub const STARVATION_THRESHOLD: Duration = Duration::from_millis(500);
+
+    pub fn is_starved(&self) -> bool {
+        self.p90 >= Self::STARVATION_THRESHOLD
+    }
+
+    pub(crate) fn from_samples(count: u64, samples: impl IntoIterator<Item = Duration>) -> Self {
+        let mut samples: Vec<_> = samples.into_iter().collect();
+        if samples.is_empty() {
+            return Self {
+                count,
+                ..Self::default()
+            };
+        }
+        samples.sort_unstable();
+        let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+        Self {
+            count,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: *samples.last().unwrap(),
+        }
+    }
+}
+
+impl KeyTypeMetrics {
+    pub const HISTOGRAM_BOUNDS: [Duration; 5] = [
+        Duration::from_millis(1),
+        Duration::from_millis(10),
+        Duration::from_millis(100),
+        Duration::from_secs(1),
+        Duration::from_secs(10),
+    ];
+
+    pub fn mean_compute_time(&self) -> Option<Duration> {
+        if self.computes == 0 {
+            None
+        } else {
+            let nanos = self.total_compute_time.as_nanos() / u128::from(self.computes);
+            Some(Duration::new(
+                (nanos / 1_000_000_000) as u64,
+                (nanos % 1_000_000_000) as u32,
+            ))
+        }
+    }
+
+    /// The index in `compute_time_histogram` of a computation that took `duration`.
+    pub(crate) fn histogram_bucket(duration: Duration) -> usize {
+        Self::HISTOGRAM_BOUNDS
+            .iter()
+            .position(|bound| duration < *bound)
+            .unwrap_or(Self::HISTOGRAM_BOUNDS.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::metrics::KeyTypeMetrics;
+    use crate::metrics::QueueDelayMetrics;
+
+    #[test]
+    fn mean_compute_time_is_exact() {
+        assert_eq!(None, KeyTypeMetrics::default().mean_compute_time());
+
+        let metrics = KeyTypeMetrics {
+            computes: 3,
+            total_compute_time: Duration::from_micros(30_050_010),
+            ..KeyTypeMetrics::default()
+        };
+        assert_eq!(
+            Some(Duration::from_micros(10_016_670)),
+            metrics.mean_compute_time()
+        );
+
+        // more computes than fit in a u32
+        let metrics = KeyTypeMetrics {
+            computes: 1 << 33,
+            total_compute_time: Duration::from_secs(1 << 33),
+            ..KeyTypeMetrics::default()
+        };
+        assert_eq!(Some(Duration::from_secs(1)), metrics.mean_compute_time());
+    }
+
+    #[test]
+    fn queue_delay_percentiles() {
+        assert_eq!(
+            QueueDelayMetrics {
+                count: 3,
+                ..QueueDelayMetrics::default()
+            },
+            QueueDelayMetrics::from_samples(3, [])
+        );
+
+        let delays = QueueDelayMetrics::from_samples(200, (1..=101).map(Duration::from_millis));
+        assert_eq!(200, delays.count);
+        assert_eq!(Duration::from_millis(51), delays.p50);
+        assert_eq!(Duration::from_millis(91), delays.p90);
+        assert_eq!(Duration::from_millis(100), delays.p99);
+        assert_eq!(Duration::from_millis(101), delays.max);
+        assert!(!delays.is_starved());
+
+        let delays =
+            QueueDelayMetrics::from_samples(10, (0..10).map(|i| Duration::from_secs(i % 2)));
+        assert!(delays.is_starved());
+    }
 }
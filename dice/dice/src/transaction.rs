@@ -10,8 +10,10 @@
 use allocative::Allocative;
 use dupe::Dupe;
 
+use crate::api::key::Key;
 use crate::ctx::DiceComputationsImpl;
 use crate::impls::ctx::BaseComputeCtx;
+use crate::introspection::recompute::RecomputeChain;
 use crate::versions::VersionNumber;
 use crate::DiceComputations;
 use crate::DiceTransactionUpdater;
@@ -57,6 +59,13 @@ impl DiceTransactionImpl {
         }
     }
 
+    pub(crate) async fn why_recomputed<K: Key>(&self, key: &K) -> Option<RecomputeChain> {
+        match self {
+            DiceTransactionImpl::Legacy(_) => None,
+            DiceTransactionImpl::Modern(ctx) => ctx.why_recomputed(key).await,
+        }
+    }
+
     pub(crate) fn as_computations(&self) -> &DiceComputations {
         match self {
             DiceTransactionImpl::Legacy(ctx) => ctx,
@@ -21,6 +21,7 @@ use crate::api::key::Key;
 use crate::api::user_data::UserComputationData;
 use crate::ctx::DiceComputationsImpl;
 use crate::impls::transaction::TransactionUpdater;
+use crate::introspection::changes::VersionChanges;
 use crate::legacy::ctx::DiceComputationsImplLegacy;
 use crate::transaction::DiceTransactionImpl;
 use crate::DiceTransaction;
@@ -50,6 +51,15 @@ impl DiceTransactionUpdaterImpl {
         }
     }
 
+    pub(crate) fn preview(&self) -> impl Future<Output = Option<VersionChanges>> + '_ {
+        match self {
+            DiceTransactionUpdaterImpl::Legacy(_) => futures::future::ready(None).left_future(),
+            DiceTransactionUpdaterImpl::Modern(delegate) => {
+                delegate.preview().map(Some).right_future()
+            }
+        }
+    }
+
     /// Records a set of `Key`s as changed so that they, and any dependents will
     /// be recomputed on the next set of requests at the next version.
     pub(crate) fn changed<K, I>(&mut self, changed: I) -> DiceResult<()>
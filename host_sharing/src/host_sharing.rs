@@ -75,6 +75,23 @@ impl WeightPercentage {
     }
 }
 
+/// Host resources other than cpu required by a command. Each is compared against the
+/// corresponding budget of the host, if one is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Allocative)]
+pub struct ResourceWeights {
+    /// Peak memory used by the command, in megabytes.
+    pub memory_mb: usize,
+    /// Disk or network bandwidth used by the command, in arbitrary units relative to the io
+    /// budget of the host.
+    pub io: usize,
+}
+
+impl fmt::Display for ResourceWeights {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(w, "memory_mb={}, io={}", self.memory_mb, self.io)
+    }
+}
+
 /// Some commands require that we only run one instance of this binary (using an identifier)
 /// to check for other instances of the same binary.
 /// Some commands required the full host to run, others just dont care.
@@ -101,6 +118,32 @@ impl Default for HostSharingRequirements {
 pub struct HostSharingGuard {
     _run_guard: SharedSemaphoreReleaser,
     _name_guard: Option<SharedSemaphoreReleaser>,
+    _resource_guards: Vec<SharedSemaphoreReleaser>,
+}
+
+/// Budget of a host resource, shared by all the commands running at once.
+struct ResourceBudget {
+    permits: SharedSemaphore,
+    total: usize,
+}
+
+impl ResourceBudget {
+    fn new(host_sharing_strategy: &HostSharingStrategy, total: usize) -> Self {
+        let is_fair = match host_sharing_strategy {
+            HostSharingStrategy::Fifo => true,
+            HostSharingStrategy::SmallerTasksFirst => false,
+        };
+        Self {
+            permits: SharedSemaphore::new(is_fair, total),
+            total,
+        }
+    }
+
+    // As with machine permits, a command requiring more than the budget is capped to the budget,
+    // and then runs alone.
+    fn requested(&self, required: usize) -> usize {
+        self.total.min(required)
+    }
 }
 
 /// Used to ensure that host resources are properly reserved before executing a command spec.
@@ -108,6 +151,9 @@ pub struct HostSharingBroker {
     permits: SharedSemaphore,
     num_machine_permits: usize,
     named_semaphores: NamedSemaphores,
+    memory: Option<ResourceBudget>,
+    io: Option<ResourceBudget>,
+    host_sharing_strategy: HostSharingStrategy,
 }
 
 impl HostSharingBroker {
@@ -137,9 +183,24 @@ impl HostSharingBroker {
             permits,
             num_machine_permits,
             named_semaphores: NamedSemaphores::new(),
+            memory: None,
+            io: None,
+            host_sharing_strategy,
         }
     }
 
+    /// Limit the total memory, in megabytes, declared by the commands running at once.
+    pub fn with_memory_budget(mut self, memory_mb: usize) -> Self {
+        self.memory = Some(ResourceBudget::new(&self.host_sharing_strategy, memory_mb));
+        self
+    }
+
+    /// Limit the total io weight of the commands running at once.
+    pub fn with_io_budget(mut self, io: usize) -> Self {
+        self.io = Some(ResourceBudget::new(&self.host_sharing_strategy, io));
+        self
+    }
+
     pub fn num_machine_permits(&self) -> usize {
         self.num_machine_permits
     }
@@ -148,6 +209,29 @@ impl HostSharingBroker {
         &self,
         host_sharing_requirements: &HostSharingRequirements,
     ) -> HostSharingGuard {
+        self.acquire_with_resources(host_sharing_requirements, &ResourceWeights::default())
+            .await
+    }
+
+    pub async fn acquire_with_resources(
+        &self,
+        host_sharing_requirements: &HostSharingRequirements,
+        resource_weights: &ResourceWeights,
+    ) -> HostSharingGuard {
+        // Resources are always acquired in the same order so commands can't deadlock waiting on
+        // each other. Machine permits come last so they are not held while waiting for memory.
+        let mut _resource_guards = Vec::new();
+        for (budget, required) in [
+            (&self.memory, resource_weights.memory_mb),
+            (&self.io, resource_weights.io),
+        ] {
+            if let Some(budget) = budget {
+                if required > 0 {
+                    _resource_guards.push(budget.permits.acquire(budget.requested(required)).await);
+                }
+            }
+        }
+
         match host_sharing_requirements {
             HostSharingRequirements::Shared(weight_class) => {
                 let permits = self.requested_permits(weight_class);
@@ -155,6 +239,7 @@ impl HostSharingBroker {
                 HostSharingGuard {
                     _run_guard,
                     _name_guard: None,
+                    _resource_guards,
                 }
             }
             HostSharingRequirements::ExclusiveAccess => {
@@ -162,6 +247,7 @@ impl HostSharingBroker {
                 HostSharingGuard {
                     _run_guard,
                     _name_guard: None,
+                    _resource_guards,
                 }
             }
             HostSharingRequirements::OnePerToken(identifier, weight_class) => {
@@ -176,6 +262,7 @@ impl HostSharingBroker {
                 HostSharingGuard {
                     _run_guard,
                     _name_guard,
+                    _resource_guards,
                 }
             }
         }
@@ -202,6 +289,36 @@ mod tests {
         assert_eq!(2, permits);
     }
 
+    #[test]
+    fn test_memory_budget() {
+        let broker = HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, 4)
+            .with_memory_budget(1000);
+        let requirements = HostSharingRequirements::default();
+        let weights = ResourceWeights {
+            memory_mb: 600,
+            io: 0,
+        };
+
+        let first =
+            futures::executor::block_on(broker.acquire_with_resources(&requirements, &weights));
+        // Machine permits are available, but not memory.
+        let second = broker.acquire_with_resources(&requirements, &weights);
+        futures::pin_mut!(second);
+        assert!(
+            futures::executor::block_on(futures::future::poll_immediate(&mut second)).is_none()
+        );
+
+        drop(first);
+        assert!(
+            futures::executor::block_on(futures::future::poll_immediate(&mut second)).is_some()
+        );
+
+        // Commands exceeding the budget are capped to it.
+        let broker = HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, 4)
+            .with_memory_budget(100);
+        futures::executor::block_on(broker.acquire_with_resources(&requirements, &weights));
+    }
+
     #[test]
     fn test_percentage() {
         let broker = HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, 10);
@@ -0,0 +1,17 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under both the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree and the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree.
+
+def stable_status_lines() -> [str.type]:
+    """
+    The stable workspace status entries (see `buck2.workspace_status_command`),
+    as `KEY value` lines. This reads buckconfig, so use it as the default of a
+    rule attribute, and write that attribute with `ctx.actions.write` to get an
+    artifact which remote actions can read as well. Only targets with such an
+    attribute are invalidated when the entries change.
+    """
+    keys = (read_root_config("workspace_status", "stable_keys") or "").split()
+    return ["{} {}".format(key, read_root_config("workspace_status", key, "")) for key in keys]
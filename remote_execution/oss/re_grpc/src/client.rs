@@ -29,7 +29,6 @@ use prost::Message;
 use re_grpc_proto::build::bazel::remote::execution::v2::action_cache_client::ActionCacheClient;
 use re_grpc_proto::build::bazel::remote::execution::v2::batch_update_blobs_request::Request;
 use re_grpc_proto::build::bazel::remote::execution::v2::capabilities_client::CapabilitiesClient;
-use re_grpc_proto::build::bazel::remote::execution::v2::compressor;
 use re_grpc_proto::build::bazel::remote::execution::v2::content_addressable_storage_client::ContentAddressableStorageClient;
 use re_grpc_proto::build::bazel::remote::execution::v2::execution_client::ExecutionClient;
 use re_grpc_proto::build::bazel::remote::execution::v2::execution_stage;
@@ -70,6 +69,8 @@ use tonic::transport::Channel;
 use tonic::transport::Identity;
 use tonic::transport::Uri;
 
+use crate::compression::decode_batch_read;
+use crate::compression::Compression;
 use crate::error::*;
 use crate::metadata::*;
 use crate::request::*;
@@ -82,6 +83,21 @@ const CONCURRENT_UPLOAD_LIMIT: usize = 64;
 
 const DEFAULT_MAX_MSG_SIZE: usize = 4 * 1000 * 1000;
 
+/// Largest message accepted from the server by default. tonic only accepts 4MiB by default,
+/// which is less than what servers advertising a larger `max_batch_total_size_bytes` send.
+const DEFAULT_MAX_DECODING_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Space left in batch responses for the fields other than the blobs (digests, statuses,
+/// protobuf framing).
+const BATCH_RESPONSE_OVERHEAD: usize = 1024 * 1024;
+
+/// Size of the chunks of ByteStream writes. This is independent of the batch size limit, which
+/// servers may set higher than the maximum size of a gRPC message they accept.
+const BYTESTREAM_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Major version of the remote execution API implemented by this client.
+const REAPI_MAJOR_VERSION: i32 = 2;
+
 fn tdigest_to(tdigest: TDigest) -> Digest {
     Digest {
         hash: tdigest.hash,
@@ -205,11 +221,24 @@ pub struct RECapabilities {
     max_msg_size: usize,
     /// Does the remote server support execution.
     exec_enabled: bool,
+    /// Compressors used for CAS transfers.
+    compression: Compression,
 }
 
 struct InstanceName(Option<String>);
 
 impl InstanceName {
+    /// Instance names are used as a prefix of bytestream resource names, so leading and trailing
+    /// slashes are dropped, and an empty instance name is the same as no instance name.
+    fn new(instance_name: Option<&str>) -> Self {
+        Self(
+            instance_name
+                .map(|name| name.trim_matches('/'))
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_owned()),
+        )
+    }
+
     fn as_str(&self) -> &str {
         match &self.0 {
             Some(instance_name) => instance_name,
@@ -266,40 +295,61 @@ impl REClientBuilder {
 
         let interceptor = InjectHeadersInterceptor::new(&opts.http_headers)?;
 
+        let max_decoding_message_size = opts
+            .max_decoding_message_size
+            .unwrap_or(DEFAULT_MAX_DECODING_MESSAGE_SIZE);
+
         let mut grpc_clients = GRPCClients {
             cas_client: ContentAddressableStorageClient::with_interceptor(
                 cas.context("Error creating CAS client")?,
                 interceptor.dupe(),
-            ),
+            )
+            .max_decoding_message_size(max_decoding_message_size),
             execution_client: ExecutionClient::with_interceptor(
                 execution.context("Error creating Execution client")?,
                 interceptor.dupe(),
-            ),
+            )
+            .max_decoding_message_size(max_decoding_message_size),
             action_cache_client: ActionCacheClient::with_interceptor(
                 action_cache.context("Error creating ActionCache client")?,
                 interceptor.dupe(),
-            ),
+            )
+            .max_decoding_message_size(max_decoding_message_size),
             bytestream_client: ByteStreamClient::with_interceptor(
                 bytestream.context("Error creating Bytestream client")?,
                 interceptor.dupe(),
-            ),
+            )
+            .max_decoding_message_size(max_decoding_message_size),
             capabilities_client: CapabilitiesClient::with_interceptor(
                 capabilities.context("Error creating Capabilities client")?,
                 interceptor.dupe(),
-            ),
+            )
+            .max_decoding_message_size(max_decoding_message_size),
         };
 
-        let instance_name = InstanceName(opts.instance_name.clone());
+        let instance_name = InstanceName::new(opts.instance_name.as_deref());
 
-        let capabilities = if opts.capabilities.unwrap_or(true) {
-            Self::fetch_rbe_capabilities(&mut grpc_clients, &instance_name).await?
+        let mut capabilities = if opts.capabilities.unwrap_or(true) {
+            Self::fetch_rbe_capabilities(&mut grpc_clients, &instance_name, opts.compression)
+                .await?
         } else {
+            // Compression can't be negotiated without capabilities.
             RECapabilities {
                 exec_enabled: true,
                 max_msg_size: DEFAULT_MAX_MSG_SIZE,
+                compression: Compression::default(),
             }
         };
 
+        if let Some(max_total_batch_size) = opts.max_total_batch_size {
+            capabilities.max_msg_size = max_total_batch_size;
+        }
+        // Batch responses must fit in the messages we accept.
+        capabilities.max_msg_size = std::cmp::min(
+            capabilities.max_msg_size,
+            max_decoding_message_size.saturating_sub(BATCH_RESPONSE_OVERHEAD),
+        );
+
         if !capabilities.exec_enabled {
             return Err(anyhow::anyhow!("Server has remote execution disabled."));
         }
@@ -310,6 +360,7 @@ impl REClientBuilder {
     async fn fetch_rbe_capabilities(
         clients: &mut GRPCClients,
         instance_name: &InstanceName,
+        compression_enabled: bool,
     ) -> anyhow::Result<RECapabilities> {
         // TODO use more of the capabilities of the remote build executor
 
@@ -321,10 +372,17 @@ impl REClientBuilder {
             .await
             .context("Failed to query capabilities of remote")?
             .into_inner();
+
+        check_api_version(
+            resp.low_api_version.as_ref().map(|v| v.major),
+            resp.high_api_version.as_ref().map(|v| v.major),
+        )?;
+
         // Default is a reasonable size for the gRPC transport
         // with enough room for headers.
         let mut max_msg_size = DEFAULT_MAX_MSG_SIZE;
         let mut exec_enabled = true;
+        let mut compression = Compression::default();
 
         if let Some(cache_cap) = resp.cache_capabilities {
             let size = cache_cap.max_batch_total_size_bytes as usize;
@@ -332,6 +390,11 @@ impl REClientBuilder {
             if size != 0 {
                 max_msg_size = size;
             }
+            compression = Compression::negotiate(
+                compression_enabled,
+                &cache_cap.supported_compressors,
+                &cache_cap.supported_batch_update_compressors,
+            );
         }
 
         if let Some(exec_cap) = resp.execution_capabilities {
@@ -341,10 +404,29 @@ impl REClientBuilder {
         Ok(RECapabilities {
             max_msg_size,
             exec_enabled,
+            compression,
         })
     }
 }
 
+/// Check the range of API versions supported by the server includes the version implemented by
+/// this client. Servers which do not report versions are assumed to be compatible.
+fn check_api_version(low_major: Option<i32>, high_major: Option<i32>) -> anyhow::Result<()> {
+    let low_major = low_major.filter(|v| *v != 0);
+    let high_major = high_major.filter(|v| *v != 0);
+    if low_major.map_or(false, |v| v > REAPI_MAJOR_VERSION)
+        || high_major.map_or(false, |v| v < REAPI_MAJOR_VERSION)
+    {
+        return Err(anyhow::anyhow!(
+            "Server supports remote execution API versions {}.x to {}.x, but buck2 requires {}.x",
+            low_major.map_or_else(|| "?".to_owned(), |v| v.to_string()),
+            high_major.map_or_else(|| "?".to_owned(), |v| v.to_string()),
+            REAPI_MAJOR_VERSION,
+        ));
+    }
+    Ok(())
+}
+
 #[derive(Clone, Dupe)]
 struct InjectHeadersInterceptor {
     headers: Arc<Vec<(MetadataKey<metadata::Ascii>, MetadataValue<metadata::Ascii>)>>,
@@ -453,7 +535,7 @@ impl BatchUploadReqAggregator {
         };
         self.curr_request_size += size_in_bytes;
 
-        if self.curr_request_size >= self.max_msg_size {
+        if self.curr_request_size >= self.max_msg_size && !self.curr_req.is_empty() {
             self.requests.push(std::mem::take(&mut self.curr_req));
             self.curr_request_size = size_in_bytes;
         }
@@ -640,6 +722,7 @@ impl REClient {
             &self.instance_name,
             request,
             self.capabilities.max_msg_size,
+            self.capabilities.compression,
             |re_request| async {
                 let metadata = metadata.clone();
                 let mut cas_client = self.grpc_clients.cas_client.clone();
@@ -680,6 +763,7 @@ impl REClient {
             &self.instance_name,
             request,
             self.capabilities.max_msg_size,
+            self.capabilities.compression,
             |re_request| async {
                 let metadata = metadata.clone();
                 let mut client = self.grpc_clients.cas_client.clone();
@@ -878,6 +962,7 @@ async fn download_impl<Byt, BytRet, Cas>(
     instance_name: &InstanceName,
     request: DownloadRequest,
     max_msg_size: usize,
+    compression: Compression,
     cas_f: impl Fn(BatchReadBlobsRequest) -> Cas,
     bystream_fut: impl Fn(ReadRequest) -> Byt + Sync + Send + Copy,
 ) -> anyhow::Result<DownloadResponse>
@@ -887,15 +972,8 @@ where
     Cas: Future<Output = anyhow::Result<BatchReadBlobsResponse>>,
 {
     let bystream_fut = |digest: TDigest| async move {
-        let hash = digest.hash;
-        let size_in_bytes = digest.size_in_bytes;
-
-        let resource_name = format!(
-            "{}blobs/{}/{}",
-            instance_name.as_resource_prefix(),
-            hash,
-            size_in_bytes
-        );
+        let resource_name =
+            compression.read_resource_name(&instance_name.as_resource_prefix(), &digest);
 
         bystream_fut(ReadRequest {
             resource_name: resource_name.clone(),
@@ -925,13 +1003,14 @@ where
             continue;
         }
         curr_size += digest.size_bytes;
-        if curr_size >= max_msg_size as i64 {
+        if curr_size >= max_msg_size as i64 && !curr_digests.is_empty() {
             let read_blob_req = BatchReadBlobsRequest {
                 instance_name: instance_name.as_str().to_owned(),
                 digests: std::mem::take(&mut curr_digests),
-                acceptable_compressors: vec![compressor::Value::Identity as i32],
+                acceptable_compressors: compression.acceptable_compressors(),
             };
             requests.push(read_blob_req);
+            curr_size = digest.size_bytes;
         }
         curr_digests.push(digest.clone());
     }
@@ -940,7 +1019,7 @@ where
         let read_blob_req = BatchReadBlobsRequest {
             instance_name: instance_name.as_str().to_owned(),
             digests: std::mem::take(&mut curr_digests),
-            acceptable_compressors: vec![compressor::Value::Identity as i32],
+            acceptable_compressors: compression.acceptable_compressors(),
         };
         requests.push(read_blob_req);
     }
@@ -953,7 +1032,8 @@ where
         for r in resp.responses.into_iter() {
             let digest = tdigest_from(r.digest.context("Response digest not found.")?);
             check_status(r.status.unwrap_or_default())?;
-            batched_blobs_response.insert(digest, r.data);
+            let data = decode_batch_read(r.compressor, r.data, &digest)?;
+            batched_blobs_response.insert(digest, data);
         }
     }
 
@@ -972,13 +1052,15 @@ where
     for digest in inlined_digests {
         let data = if digest.size_in_bytes as usize >= max_msg_size {
             let mut accum = vec![];
+            let mut decoder = compression.decoder(&digest)?;
             let mut responses = bystream_fut(digest.clone()).await?;
             while let Some(resp) = responses.next().await {
                 let data = resp
                     .with_context(|| format!("Failed to fetch inline digest: {digest}"))?
                     .data;
-                accum.extend_from_slice(&data);
+                accum.extend_from_slice(&decoder.decode(data)?);
             }
+            decoder.finish(&digest)?;
             accum
         } else {
             get(&digest)?
@@ -1017,15 +1099,18 @@ where
                     .await
                     .with_context(|| format!("Error writing: {}", req.named_digest.digest))?;
             } else {
+                let mut decoder = compression.decoder(&req.named_digest.digest)?;
                 let mut responses = bystream_fut(req.named_digest.digest.clone()).await?;
                 while let Some(resp) = responses.next().await {
                     let data = resp
                         .with_context(|| format!("Failed to fetch file: {:?}", file))?
                         .data;
+                    let data = decoder.decode(data)?;
                     file.write_all(&data).await.with_context(|| {
                         format!("Error writing chunk of: {}", req.named_digest.digest)
                     })?;
                 }
+                decoder.finish(&req.named_digest.digest)?;
             }
             file.flush().await.context("Error flushing")?;
             anyhow::Ok(())
@@ -1050,6 +1135,7 @@ async fn upload_impl<Byt, Cas>(
     instance_name: &InstanceName,
     request: UploadRequest,
     max_msg_size: usize,
+    compression: Compression,
     cas_f: impl Fn(BatchUpdateBlobsRequest) -> Cas + Sync + Send + Copy,
     bystream_fut: impl Fn(Vec<WriteRequest>) -> Byt + Sync + Send + Copy,
 ) -> anyhow::Result<UploadResponse>
@@ -1074,22 +1160,23 @@ where
             continue;
         }
 
-        let data = blob.blob;
         let client_uuid = uuid::Uuid::new_v4().to_string();
-        let resource_name = format!(
-            "{}uploads/{}/blobs/{}/{}",
-            instance_name.as_resource_prefix(),
-            client_uuid,
-            hash,
-            size
+        let resource_name = compression.write_resource_name(
+            &instance_name.as_resource_prefix(),
+            &client_uuid,
+            &blob.digest,
         );
+        let data = blob.blob;
         let fut = async move {
+            let data = compression.encode_blob(data)?;
+
             // Number of complete (non-partial) messages
+            let chunk_size = std::cmp::min(max_msg_size, BYTESTREAM_CHUNK_SIZE);
             let mut upload_segments = vec![];
-            for (i, chunk) in data.chunks(max_msg_size).enumerate() {
+            for (i, chunk) in data.chunks(chunk_size).enumerate() {
                 upload_segments.push(WriteRequest {
                     resource_name: resource_name.to_owned(),
-                    write_offset: (i * max_msg_size) as i64,
+                    write_offset: (i * chunk_size) as i64,
                     finish_write: false,
                     data: chunk.to_owned(),
                 });
@@ -1097,7 +1184,7 @@ where
             upload_segments.last_mut().unwrap().finish_write = true;
 
             let resp = bystream_fut(upload_segments).await?;
-            if resp.committed_size != size {
+            if !is_committed(compression, resp.committed_size, size, data.len() as i64) {
                 return Err(anyhow::anyhow!(
                     "Failed to upload inline blob: invalid committed_size from WriteResponse"
                 ));
@@ -1118,21 +1205,33 @@ where
             continue;
         }
         let client_uuid = uuid::Uuid::new_v4().to_string();
-        let resource_name = format!(
-            "{}uploads/{}/blobs/{}/{}",
-            instance_name.as_resource_prefix(),
-            client_uuid,
-            hash.clone(),
-            size
+        let resource_name = compression.write_resource_name(
+            &instance_name.as_resource_prefix(),
+            &client_uuid,
+            &file.digest,
         );
         let fut = async move {
             let mut file = tokio::fs::File::open(&name)
                 .await
                 .with_context(|| format!("Opening `{name}` for reading failed"))?;
-            let mut data = vec![0; max_msg_size];
+            let mut data = vec![0; std::cmp::min(max_msg_size, BYTESTREAM_CHUNK_SIZE)];
+            let mut encoder = compression.encoder()?;
 
             let mut write_offset = 0;
             let mut upload_segments = Vec::new();
+            let mut push_segment = |segment: Vec<u8>| {
+                // Compressors may buffer the data.
+                if !segment.is_empty() {
+                    let length = segment.len() as i64;
+                    upload_segments.push(WriteRequest {
+                        resource_name: resource_name.to_owned(),
+                        write_offset,
+                        finish_write: false,
+                        data: segment,
+                    });
+                    write_offset += length;
+                }
+            };
             loop {
                 let length = file
                     .read(&mut data)
@@ -1141,21 +1240,16 @@ where
                 if length == 0 {
                     break;
                 }
-                upload_segments.push(WriteRequest {
-                    resource_name: resource_name.to_owned(),
-                    write_offset,
-                    finish_write: false,
-                    data: data[..length].to_owned(),
-                });
-                write_offset += length as i64;
+                push_segment(encoder.encode(&data[..length])?);
             }
+            push_segment(encoder.finish()?);
             upload_segments
                 .last_mut()
                 .with_context(|| format!("Read no segments from `{name} "))?
                 .finish_write = true;
 
             let resp = bystream_fut(upload_segments).await?;
-            if resp.committed_size != size {
+            if !is_committed(compression, resp.committed_size, size, write_offset) {
                 return Err(anyhow::anyhow!(
                     "Failed to upload `{name}`: invalid committed_size from WriteResponse"
                 ));
@@ -1177,10 +1271,12 @@ where
             for blob in batch {
                 match blob {
                     BatchUploadRequest::Blob(blob) => {
+                        let (data, compressor) =
+                            compression.encode_batch_update(blob.blob.clone())?;
                         re_request.requests.push(Request {
                             digest: Some(tdigest_to(blob.digest.clone())),
-                            data: blob.blob.clone(),
-                            compressor: compressor::Value::Identity as i32,
+                            data,
+                            compressor,
                         });
                     }
                     BatchUploadRequest::File(file) => {
@@ -1191,10 +1287,11 @@ where
                         let mut data = vec![];
                         fin.read_to_end(&mut data).await?;
 
+                        let (data, compressor) = compression.encode_batch_update(data)?;
                         re_request.requests.push(Request {
                             digest: Some(tdigest_to(file.digest.clone())),
                             data,
-                            compressor: compressor::Value::Identity as i32,
+                            compressor,
                         });
                     }
                 }
@@ -1241,6 +1338,16 @@ where
     Ok(UploadResponse {})
 }
 
+/// Whether a ByteStream write of `written` bytes committed a blob of `size` bytes. Compressed
+/// writes of blobs which already exist commit `-1`.
+fn is_committed(compression: Compression, committed_size: i64, size: i64, written: i64) -> bool {
+    if compression.zstd {
+        committed_size == -1 || committed_size == written || committed_size == size
+    } else {
+        committed_size == size
+    }
+}
+
 fn with_internal_metadata<T>(t: T, metadata: RemoteExecutionMetadata) -> tonic::Request<T> {
     // This is pretty ugly, but the protobuf spec that defines this is internal, so considering
     // field numbers need to be stable anyway (= low risk), and this is not used in prod (= low
@@ -1376,6 +1483,7 @@ mod tests {
             &InstanceName(None),
             req,
             10000,
+            Compression::default(),
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -1482,6 +1590,7 @@ mod tests {
             &InstanceName(None),
             req,
             10, // kept small to simulate a large file download
+            Compression::default(),
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -1563,6 +1672,7 @@ mod tests {
             &InstanceName(None),
             req,
             100000,
+            Compression::default(),
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -1636,6 +1746,7 @@ mod tests {
             &InstanceName(None),
             req,
             10, // intentionally small value to keep data in the test blobs small
+            Compression::default(),
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -1691,6 +1802,7 @@ mod tests {
             &InstanceName(None),
             req,
             100000,
+            Compression::default(),
             |req| {
                 let res = res.clone();
                 async move {
@@ -1729,6 +1841,7 @@ mod tests {
             &InstanceName(Some("instance".to_owned())),
             req,
             0,
+            Compression::default(),
             |_req| async { panic!("not called") },
             |req| async move {
                 assert_eq!(req.resource_name, "instance/blobs/aa/0");
@@ -1798,6 +1911,7 @@ mod tests {
             &InstanceName(None),
             req,
             10000,
+            Compression::default(),
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -1880,6 +1994,7 @@ mod tests {
             &InstanceName(None),
             req,
             10, // kept small to simulate a large file upload
+            Compression::default(),
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -1953,6 +2068,7 @@ mod tests {
             &InstanceName(None),
             req,
             10, // kept small to simulate a large inlined upload
+            Compression::default(),
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -2073,6 +2189,7 @@ mod tests {
             &InstanceName(None),
             req,
             3,
+            Compression::default(),
             |_req| async move {
                 panic!("Not called");
             },
@@ -2113,6 +2230,7 @@ mod tests {
             &InstanceName(None),
             req,
             0,
+            Compression::default(),
             |_req| async move {
                 panic!("Not called");
             },
@@ -2158,6 +2276,7 @@ mod tests {
             &InstanceName(Some("instance".to_owned())),
             req,
             1,
+            Compression::default(),
             |_req| async move {
                 panic!("Not called");
             },
@@ -2172,6 +2291,132 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_download_compressed() -> anyhow::Result<()> {
+        let work = tempfile::tempdir()?;
+
+        let path1 = work.path().join("path1");
+        let path1 = path1.to_str().context("tempdir is not utf8")?;
+
+        let blob_data = b"abcabcabcabcabcabcabcabc".to_vec();
+        let digest1 = TDigest {
+            hash: "xl".to_owned(),
+            size_in_bytes: blob_data.len() as i64,
+            ..Default::default()
+        };
+
+        let req = DownloadRequest {
+            file_digests: Some(vec![NamedDigestWithPermissions {
+                named_digest: NamedDigest {
+                    name: path1.to_owned(),
+                    digest: digest1.clone(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        let compressed = zstd::bulk::compress(&blob_data, 0)?;
+
+        download_impl(
+            &InstanceName(None),
+            req,
+            10, // kept small to simulate a large file download
+            Compression {
+                zstd: true,
+                zstd_batch_update: false,
+            },
+            |_req| async { Ok(BatchReadBlobsResponse::default()) },
+            |req| {
+                let compressed = compressed.clone();
+                async move {
+                    assert_eq!(req.resource_name, "compressed-blobs/zstd/xl/24");
+                    let (data1, data2) = compressed.split_at(compressed.len() / 2);
+                    anyhow::Ok(Box::pin(futures::stream::iter(vec![
+                        Ok(ReadResponse {
+                            data: data1.to_vec(),
+                        }),
+                        Ok(ReadResponse {
+                            data: data2.to_vec(),
+                        }),
+                    ])))
+                }
+            },
+        )
+        .await?;
+
+        assert_eq!(tokio::fs::read(&path1).await?, blob_data);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_upload_compressed() -> anyhow::Result<()> {
+        let blob_data = b"abcabcabcabcabcabcabcabc".to_vec();
+        let digest1 = TDigest {
+            hash: "xl".to_owned(),
+            size_in_bytes: blob_data.len() as i64,
+            ..Default::default()
+        };
+
+        let work = tempfile::tempdir()?;
+
+        let path1 = work.path().join("path1");
+        let path1 = path1.to_str().context("tempdir is not utf8")?;
+        tokio::fs::write(path1, &blob_data).await?;
+
+        let req = UploadRequest {
+            files_with_digest: Some(vec![NamedDigest {
+                name: path1.to_owned(),
+                digest: digest1.clone(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        upload_impl(
+            &InstanceName(None),
+            req,
+            10, // kept small to simulate a large file upload
+            Compression {
+                zstd: true,
+                zstd_batch_update: false,
+            },
+            |_req| async move {
+                panic!("Not called");
+            },
+            |write_reqs| {
+                let blob_data = blob_data.clone();
+                async move {
+                    assert!(
+                        write_reqs[0]
+                            .resource_name
+                            .ends_with("/compressed-blobs/zstd/xl/24")
+                    );
+                    assert!(write_reqs.last().unwrap().finish_write);
+
+                    let mut write_offset = 0;
+                    let mut compressed = Vec::new();
+                    for req in &write_reqs {
+                        assert_eq!(req.write_offset, write_offset);
+                        write_offset += req.data.len() as i64;
+                        compressed.extend_from_slice(&req.data);
+                    }
+                    assert_eq!(
+                        zstd::bulk::decompress(&compressed, blob_data.len())?,
+                        blob_data
+                    );
+                    // The blob already exists.
+                    anyhow::Ok(WriteResponse { committed_size: -1 })
+                }
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
     #[test]
     fn test_substitute_env_vars() {
         let getter = |s: &str| match s {
@@ -2197,4 +2442,24 @@ mod tests {
         assert_eq!(substitute_env_vars_impl("FOO", getter).unwrap(), "FOO");
         assert!(substitute_env_vars_impl("$FOO$BAZ", getter).is_err());
     }
+
+    #[test]
+    fn test_instance_name() {
+        assert_eq!(InstanceName::new(None).as_str(), "");
+        assert_eq!(InstanceName::new(Some("/")).as_str(), "");
+        assert_eq!(
+            InstanceName::new(Some("/main/")).as_resource_prefix(),
+            "main/"
+        );
+    }
+
+    #[test]
+    fn test_check_api_version() {
+        assert!(check_api_version(None, None).is_ok());
+        assert!(check_api_version(Some(0), Some(0)).is_ok());
+        assert!(check_api_version(Some(2), Some(2)).is_ok());
+        assert!(check_api_version(Some(1), Some(3)).is_ok());
+        assert!(check_api_version(Some(3), None).is_err());
+        assert!(check_api_version(None, Some(1)).is_err());
+    }
 }
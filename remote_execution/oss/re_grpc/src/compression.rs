@@ -0,0 +1,302 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Compressed transfers of CAS blobs.
+//!
+//! Servers advertise the compressors they support in their capabilities. When zstd is supported
+//! (and compression is enabled in the configuration), blobs are written and read through the
+//! `compressed-blobs/zstd` ByteStream resources, and batch requests carry compressed data.
+
+use std::io::Write;
+
+use anyhow::Context;
+use re_grpc_proto::build::bazel::remote::execution::v2::compressor;
+
+use crate::digest::TDigest;
+
+/// Let zstd pick its default level, which favours speed.
+const ZSTD_LEVEL: i32 = 0;
+
+/// The compressors negotiated with the server.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Compression {
+    /// Blobs transferred with ByteStream are zstd-compressed, and batch reads accept zstd.
+    pub(crate) zstd: bool,
+    /// Blobs in batch uploads are zstd-compressed.
+    pub(crate) zstd_batch_update: bool,
+}
+
+impl Compression {
+    pub(crate) fn negotiate(
+        enabled: bool,
+        supported_compressors: &[i32],
+        supported_batch_update_compressors: &[i32],
+    ) -> Self {
+        let zstd = compressor::Value::Zstd as i32;
+        Self {
+            zstd: enabled && supported_compressors.contains(&zstd),
+            zstd_batch_update: enabled && supported_batch_update_compressors.contains(&zstd),
+        }
+    }
+
+    /// Name of the ByteStream resource to read a blob from.
+    pub(crate) fn read_resource_name(&self, resource_prefix: &str, digest: &TDigest) -> String {
+        format!(
+            "{}{}/{}/{}",
+            resource_prefix,
+            self.blobs_resource(),
+            digest.hash,
+            digest.size_in_bytes
+        )
+    }
+
+    /// Name of the ByteStream resource to write a blob to.
+    pub(crate) fn write_resource_name(
+        &self,
+        resource_prefix: &str,
+        client_uuid: &str,
+        digest: &TDigest,
+    ) -> String {
+        format!(
+            "{}uploads/{}/{}/{}/{}",
+            resource_prefix,
+            client_uuid,
+            self.blobs_resource(),
+            digest.hash,
+            digest.size_in_bytes
+        )
+    }
+
+    fn blobs_resource(&self) -> &'static str {
+        if self.zstd {
+            "compressed-blobs/zstd"
+        } else {
+            "blobs"
+        }
+    }
+
+    /// Compressors accepted for the blobs returned by batch reads, in order of preference.
+    pub(crate) fn acceptable_compressors(&self) -> Vec<i32> {
+        if self.zstd {
+            vec![
+                compressor::Value::Zstd as i32,
+                compressor::Value::Identity as i32,
+            ]
+        } else {
+            vec![compressor::Value::Identity as i32]
+        }
+    }
+
+    /// Data of a blob written with ByteStream in one go.
+    pub(crate) fn encode_blob(&self, data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        if self.zstd {
+            zstd::bulk::compress(&data, ZSTD_LEVEL).context("Error compressing blob")
+        } else {
+            Ok(data)
+        }
+    }
+
+    /// Data and compressor of a blob in a batch upload.
+    pub(crate) fn encode_batch_update(&self, data: Vec<u8>) -> anyhow::Result<(Vec<u8>, i32)> {
+        if self.zstd_batch_update {
+            Ok((
+                zstd::bulk::compress(&data, ZSTD_LEVEL).context("Error compressing blob")?,
+                compressor::Value::Zstd as i32,
+            ))
+        } else {
+            Ok((data, compressor::Value::Identity as i32))
+        }
+    }
+
+    pub(crate) fn encoder(&self) -> anyhow::Result<ChunkEncoder> {
+        if self.zstd {
+            Ok(ChunkEncoder::Zstd(zstd::stream::write::Encoder::new(
+                Vec::new(),
+                ZSTD_LEVEL,
+            )?))
+        } else {
+            Ok(ChunkEncoder::Identity)
+        }
+    }
+
+    pub(crate) fn decoder(&self, digest: &TDigest) -> anyhow::Result<ChunkDecoder> {
+        Ok(ChunkDecoder {
+            zstd: if self.zstd {
+                Some(zstd::stream::write::Decoder::new(Vec::new())?)
+            } else {
+                None
+            },
+            expected_size: digest.size_in_bytes,
+            decoded_size: 0,
+        })
+    }
+}
+
+/// Decode a blob returned by a batch read with the given compressor.
+pub(crate) fn decode_batch_read(
+    compressor: i32,
+    data: Vec<u8>,
+    digest: &TDigest,
+) -> anyhow::Result<Vec<u8>> {
+    if compressor == compressor::Value::Identity as i32 {
+        Ok(data)
+    } else if compressor == compressor::Value::Zstd as i32 {
+        let decoded = zstd::bulk::decompress(&data, digest.size_in_bytes as usize)
+            .with_context(|| format!("Error decompressing `{}`", digest))?;
+        check_decoded_size(digest.size_in_bytes, decoded.len() as i64, digest)?;
+        Ok(decoded)
+    } else {
+        Err(anyhow::anyhow!(
+            "Unexpected compressor `{}` for `{}`",
+            compressor,
+            digest
+        ))
+    }
+}
+
+fn check_decoded_size(expected: i64, actual: i64, digest: &TDigest) -> anyhow::Result<()> {
+    if expected != actual {
+        return Err(anyhow::anyhow!(
+            "Decompressed `{}` to {} bytes, expected {}",
+            digest,
+            actual,
+            expected
+        ));
+    }
+    Ok(())
+}
+
+/// Compresses a blob written in chunks.
+pub(crate) enum ChunkEncoder {
+    Identity,
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+}
+
+impl ChunkEncoder {
+    /// The data to send for a chunk of the blob. This may be empty if the compressor buffers it.
+    pub(crate) fn encode(&mut self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::Identity => Ok(data.to_vec()),
+            Self::Zstd(encoder) => {
+                encoder.write_all(data).context("Error compressing blob")?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+        }
+    }
+
+    /// The data left to send after the last chunk.
+    pub(crate) fn finish(self) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::Identity => Ok(Vec::new()),
+            Self::Zstd(encoder) => encoder.finish().context("Error compressing blob"),
+        }
+    }
+}
+
+/// Decompresses a blob read in chunks, and checks its size.
+pub(crate) struct ChunkDecoder {
+    zstd: Option<zstd::stream::write::Decoder<'static, Vec<u8>>>,
+    expected_size: i64,
+    decoded_size: i64,
+}
+
+impl ChunkDecoder {
+    pub(crate) fn decode(&mut self, data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        let decoded = match &mut self.zstd {
+            None => data,
+            Some(decoder) => {
+                decoder
+                    .write_all(&data)
+                    .context("Error decompressing blob")?;
+                decoder.flush().context("Error decompressing blob")?;
+                std::mem::take(decoder.get_mut())
+            }
+        };
+        self.decoded_size += decoded.len() as i64;
+        Ok(decoded)
+    }
+
+    /// Check the blob was complete once all chunks were decoded. Uncompressed blobs are not
+    /// checked.
+    pub(crate) fn finish(self, digest: &TDigest) -> anyhow::Result<()> {
+        if self.zstd.is_some() {
+            check_decoded_size(self.expected_size, self.decoded_size, digest)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(size_in_bytes: i64) -> TDigest {
+        TDigest {
+            hash: "aa".to_owned(),
+            size_in_bytes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let zstd = compressor::Value::Zstd as i32;
+        let compression = Compression::negotiate(true, &[zstd], &[]);
+        assert!(compression.zstd);
+        assert!(!compression.zstd_batch_update);
+        assert!(!Compression::negotiate(false, &[zstd], &[zstd]).zstd);
+        assert_eq!(
+            "instance/compressed-blobs/zstd/aa/3",
+            compression.read_resource_name("instance/", &digest(3))
+        );
+        assert_eq!(
+            "blobs/aa/3",
+            Compression::default().read_resource_name("", &digest(3))
+        );
+    }
+
+    #[test]
+    fn test_chunks_roundtrip() -> anyhow::Result<()> {
+        let data = b"hello hello hello hello hello hello".to_vec();
+        let compression = Compression {
+            zstd: true,
+            zstd_batch_update: false,
+        };
+
+        let mut encoder = compression.encoder()?;
+        let mut encoded = Vec::new();
+        for chunk in data.chunks(4) {
+            encoded.push(encoder.encode(chunk)?);
+        }
+        encoded.push(encoder.finish()?);
+
+        let digest = digest(data.len() as i64);
+        let mut decoder = compression.decoder(&digest)?;
+        let mut decoded = Vec::new();
+        for chunk in encoded.concat().chunks(3) {
+            decoded.extend(decoder.decode(chunk.to_vec())?);
+        }
+        decoder.finish(&digest)?;
+        assert_eq!(data, decoded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncated_blob() -> anyhow::Result<()> {
+        let compression = Compression {
+            zstd: true,
+            zstd_batch_update: false,
+        };
+        let digest = digest(10);
+        let mut decoder = compression.decoder(&digest)?;
+        decoder.decode(zstd::bulk::compress(b"hello", ZSTD_LEVEL)?)?;
+        assert!(decoder.finish(&digest).is_err());
+        Ok(())
+    }
+}
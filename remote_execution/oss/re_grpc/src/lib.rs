@@ -13,6 +13,7 @@
 #![cfg_attr(feature = "gazebo_lint", plugin(gazebo_lint))]
 
 mod client;
+mod compression;
 mod digest;
 mod error;
 mod grpc;
@@ -23,14 +23,14 @@ pub struct DownloadRequest {
     pub _dot_dot: (),
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct NamedDigestWithPermissions {
     pub named_digest: NamedDigest,
     pub is_executable: bool,
     pub _dot_dot: (),
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct NamedDigest {
     pub name: String,
     pub digest: TDigest,
@@ -46,7 +46,7 @@ pub struct UploadRequest {
     pub _dot_dot: (),
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Path {
     pub path: String,
     pub follow_symlinks: bool,
@@ -54,7 +54,7 @@ pub struct Path {
     pub _dot_dot: (),
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct InlinedBlobWithDigest {
     pub blob: Vec<u8>,
     pub digest: TDigest,
@@ -30,6 +30,7 @@ use starlark::docs::get_registered_starlark_docs;
 use starlark::docs::render_docs_as_code;
 use starlark::docs::Doc;
 use starlark::docs::DocItem;
+use starlark::docs::DocModule;
 use starlark::environment::FrozenModule;
 use starlark::environment::Globals;
 use starlark::environment::Module;
@@ -66,6 +67,7 @@ pub(crate) struct Context {
     pub(crate) module: Option<Module>,
     pub(crate) builtin_docs: HashMap<LspUrl, String>,
     pub(crate) builtin_symbols: HashMap<String, LspUrl>,
+    pub(crate) globals_docs: DocModule,
 }
 
 /// The outcome of evaluating (checking, parsing or running) given starlark code.
@@ -134,6 +136,7 @@ impl Context {
             module,
             builtin_docs,
             builtin_symbols,
+            globals_docs: globals.documentation(),
         })
     }
 
@@ -271,7 +274,7 @@ impl Context {
         };
 
         module
-            .lint(globals.as_ref())
+            .lint_with_docs(globals.as_ref(), &self.globals_docs)
             .into_iter()
             .map(EvalMessage::from)
     }
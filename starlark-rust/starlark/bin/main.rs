@@ -25,9 +25,11 @@
 use std::ffi::OsStr;
 use std::fmt;
 use std::fmt::Display;
+use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use anyhow::Context as _;
 use clap::Parser;
 use clap::ValueEnum;
 use dupe::Dupe;
@@ -36,6 +38,7 @@ use itertools::Either;
 use itertools::Itertools;
 use starlark::docs::get_registered_starlark_docs;
 use starlark::docs::render_docs_as_code;
+use starlark::docs::render_docs_as_html;
 use starlark::docs::Doc;
 use starlark::docs::DocItem;
 use starlark::docs::MarkdownFlavor;
@@ -109,6 +112,14 @@ struct Args {
     )]
     docs: Option<ArgsDoc>,
 
+    #[arg(
+        long = "docs-output",
+        value_name = "DIR",
+        help = "Directory to write `--docs=html` output to.",
+        requires = "docs"
+    )]
+    docs_output: Option<PathBuf>,
+
     #[arg(
         long = "extension",
         help = "File extension when searching directories."
@@ -143,6 +154,7 @@ enum ArgsDoc {
     Lsp,
     Markdown,
     Code,
+    Html,
 }
 
 // Treat directories as things to recursively walk for .<extension> files,
@@ -288,6 +300,15 @@ fn main() -> anyhow::Result<()> {
                     )
                 }
                 ArgsDoc::Code => println!("{}", render_docs_as_code(&builtin)),
+                ArgsDoc::Html => {
+                    let dir = args
+                        .docs_output
+                        .context("`--docs=html` requires `--docs-output`")?;
+                    fs::create_dir_all(&dir)?;
+                    for (name, contents) in render_docs_as_html(&builtin) {
+                        fs::write(dir.join(name), contents)?;
+                    }
+                }
             };
         } else if is_interactive {
             interactive(&ctx)?;
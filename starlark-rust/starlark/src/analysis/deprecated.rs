@@ -0,0 +1,178 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::analysis::bind;
+use crate::analysis::bind::Bind;
+use crate::analysis::bind::Scope;
+use crate::analysis::types::LintT;
+use crate::analysis::types::LintWarning;
+use crate::codemap::CodeMap;
+use crate::codemap::Span;
+use crate::docs::DocDeprecation;
+use crate::docs::DocMember;
+use crate::docs::DocModule;
+use crate::syntax::AstModule;
+
+#[derive(Error, Debug)]
+pub(crate) enum DeprecatedWarning {
+    #[error("{0}")]
+    UsingDeprecated(String),
+}
+
+impl LintWarning for DeprecatedWarning {
+    fn is_serious(&self) -> bool {
+        false
+    }
+
+    fn short_name(&self) -> &'static str {
+        match self {
+            DeprecatedWarning::UsingDeprecated(..) => "using-deprecated",
+        }
+    }
+}
+
+fn deprecation(member: &DocMember) -> Option<&DocDeprecation> {
+    match member {
+        DocMember::Function(x) => x.deprecated.as_ref(),
+        DocMember::Property(x) => x.deprecated.as_ref(),
+    }
+}
+
+/// Report every reference to a global which is documented as deprecated,
+/// unless a local definition shadows it.
+fn using_deprecated<'a>(
+    codemap: &CodeMap,
+    scope: &'a Scope,
+    deprecated: &HashMap<&str, &DocDeprecation>,
+    shadowed: &mut Vec<&'a str>,
+    res: &mut Vec<LintT<DeprecatedWarning>>,
+) {
+    let shadowed_len = shadowed.len();
+    shadowed.extend(scope.bound.keys().map(|x| x.as_str()));
+
+    let mut check = |name: &str, span: Span| {
+        if shadowed.contains(&name) {
+            return;
+        }
+        if let Some(d) = deprecated.get(name) {
+            res.push(LintT::new(
+                codemap,
+                span,
+                DeprecatedWarning::UsingDeprecated(d.describe(name)),
+            ));
+        }
+    };
+    for x in &scope.inner {
+        match x {
+            Bind::Get(x) => check(&x.node.0, x.span),
+            Bind::GetDotted(x) => check(&x.variable.node.0, x.variable.span),
+            Bind::Set(..) | Bind::Flow | Bind::Scope(..) => {}
+        }
+    }
+    for x in &scope.inner {
+        if let Bind::Scope(inner) = x {
+            using_deprecated(codemap, inner, deprecated, shadowed, res);
+        }
+    }
+
+    shadowed.truncate(shadowed_len);
+}
+
+pub(crate) fn lint(module: &AstModule, globals: &DocModule) -> Vec<LintT<DeprecatedWarning>> {
+    let deprecated: HashMap<&str, &DocDeprecation> = globals
+        .members
+        .iter()
+        .filter_map(|(name, member)| Some((name.as_str(), deprecation(member)?)))
+        .collect();
+    let mut res = Vec::new();
+    if !deprecated.is_empty() {
+        let scope = bind::scope(module);
+        using_deprecated(
+            &module.codemap,
+            &scope,
+            &deprecated,
+            &mut Vec::new(),
+            &mut res,
+        );
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docs::DocFunction;
+    use crate::docs::DocProperty;
+    use crate::slice_vec_ext::SliceExt;
+    use crate::syntax::Dialect;
+
+    fn module(x: &str) -> AstModule {
+        AstModule::parse("X", x.to_owned(), &Dialect::Extended).unwrap()
+    }
+
+    #[test]
+    fn test_lint_using_deprecated() {
+        let mut globals = DocModule::default();
+        globals.members.insert(
+            "old_function".to_owned(),
+            DocMember::Function(DocFunction {
+                deprecated: Some(DocDeprecation {
+                    replacement: Some("new_function".to_owned()),
+                    ..DocDeprecation::default()
+                }),
+                ..DocFunction::default()
+            }),
+        );
+        globals.members.insert(
+            "old_property".to_owned(),
+            DocMember::Property(DocProperty {
+                deprecated: Some(DocDeprecation::default()),
+                ..DocProperty::default()
+            }),
+        );
+        globals.members.insert(
+            "new_function".to_owned(),
+            DocMember::Function(DocFunction::default()),
+        );
+
+        let m = module(
+            r#"
+old_function()
+new_function(old_property.field)
+def foo(old_function):
+    return old_function
+def bar():
+    return [old_function for _ in old_property]
+"#,
+        );
+        let mut res = lint(&m, &globals).map(|x| x.to_string());
+        res.sort();
+        assert_eq!(
+            res,
+            &[
+                "X:2:1-13: `old_function` is deprecated. Use `new_function` instead.",
+                "X:3:14-26: `old_property` is deprecated.",
+                "X:7:13-25: `old_function` is deprecated. Use `new_function` instead.",
+                "X:7:35-47: `old_property` is deprecated.",
+            ]
+        );
+    }
+}
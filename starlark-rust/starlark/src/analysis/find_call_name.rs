@@ -66,6 +66,32 @@ impl AstModule {
         self.statement.visit_expr(|x| visit_expr(&mut ret, name, x));
         ret.map(|span| self.codemap.resolve_span(span))
     }
+
+    /// Find the locations of all the calls of the global function `function` (`function(...)`,
+    /// but not `x.function(...)`), anywhere in the module, in source order.
+    ///
+    /// NOTE: If the AST is exposed in the future, this function may be removed and implemented
+    ///       by specific programs instead.
+    pub fn find_function_calls(&self, function: &str) -> Vec<ResolvedSpan> {
+        fn visit_expr(ret: &mut Vec<Span>, function: &str, node: &AstExpr) {
+            if let Expr::Call(callee, _) = &node.node {
+                if let Expr::Identifier(ident) = &callee.node {
+                    if ident.node.0 == function {
+                        ret.push(node.span);
+                    }
+                }
+            }
+            node.visit_expr(|x| visit_expr(ret, function, x));
+        }
+
+        let mut ret = Vec::new();
+        self.statement
+            .visit_expr(|x| visit_expr(&mut ret, function, x));
+        ret.sort_by_key(|span| span.begin());
+        ret.into_iter()
+            .map(|span| self.codemap.resolve_span(span))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -100,4 +126,27 @@ def x(name = "foo_name"):
         assert_eq!(None, module.find_function_call_with_name("bar_name"));
         Ok(())
     }
+
+    #[test]
+    fn finds_all_function_calls() -> anyhow::Result<()> {
+        let contents = r#"
+foo(bar(1))
+x.bar(2)
+def f():
+    return [bar(y) for y in []]
+"#;
+
+        let module = AstModule::parse("foo.star", contents.to_owned(), &Dialect::Extended).unwrap();
+
+        assert_eq!(
+            vec!["2:5-11".to_owned(), "5:13-19".to_owned()],
+            module
+                .find_function_calls("bar")
+                .iter()
+                .map(|span| span.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert!(module.find_function_calls("baz").is_empty());
+        Ok(())
+    }
 }
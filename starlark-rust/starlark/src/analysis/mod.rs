@@ -22,10 +22,12 @@ pub use types::EvalSeverity;
 pub use types::Lint;
 
 use crate::analysis::types::LintT;
+use crate::docs::DocModule;
 use crate::syntax::AstModule;
 
 mod bind;
 pub(crate) mod definition;
+mod deprecated;
 mod dubious;
 pub(crate) mod exported;
 mod find_call_name;
@@ -50,4 +52,21 @@ impl AstModule {
         res.extend(performance::lint(self).into_iter().map(LintT::erase));
         res
     }
+
+    /// Like [`lint`](AstModule::lint), but also warns about uses of globals which
+    /// `globals_docs` (usually from [`Globals::documentation`](crate::environment::Globals::documentation))
+    /// marks as deprecated.
+    pub fn lint_with_docs(
+        &self,
+        globals: Option<&HashSet<String>>,
+        globals_docs: &DocModule,
+    ) -> Vec<Lint> {
+        let mut res = self.lint(globals);
+        res.extend(
+            deprecated::lint(self, globals_docs)
+                .into_iter()
+                .map(LintT::erase),
+        );
+        res
+    }
 }
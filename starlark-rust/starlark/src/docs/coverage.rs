@@ -0,0 +1,278 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Report which documented symbols are missing docstrings, parameter docs or return types.
+//!
+//! Feed it the [`Doc`]s of registered types ([`get_registered_starlark_docs`](crate::docs::get_registered_starlark_docs)),
+//! of [`Globals`](crate::environment::Globals::documentation) and of
+//! [`FrozenModule`](crate::environment::FrozenModule::documentation)s, and enforce
+//! whatever threshold is appropriate on the result.
+
+use std::fmt;
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::docs::Doc;
+use crate::docs::DocFunction;
+use crate::docs::DocItem;
+use crate::docs::DocMember;
+use crate::docs::DocParam;
+use crate::docs::DocProperty;
+
+/// Documentation coverage of a single function or property.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolCoverage {
+    /// Name of the symbol, e.g. `string.capitalize`.
+    pub name: String,
+    /// The symbol has no docstring at all.
+    pub missing_docstring: bool,
+    /// Names of parameters which have no docs.
+    pub undocumented_params: Vec<String>,
+    /// A function without a declared return type.
+    pub missing_return_type: bool,
+}
+
+impl SymbolCoverage {
+    /// Nothing is missing.
+    pub fn is_complete(&self) -> bool {
+        !self.missing_docstring && self.undocumented_params.is_empty() && !self.missing_return_type
+    }
+
+    fn function(name: String, function: &DocFunction) -> Self {
+        let undocumented_params = function
+            .params
+            .iter()
+            .filter_map(|p| match p {
+                DocParam::Arg { name, docs, .. }
+                | DocParam::Args { name, docs, .. }
+                | DocParam::Kwargs { name, docs, .. } => match docs {
+                    None => Some(name.clone()),
+                    Some(_) => None,
+                },
+                DocParam::NoArgs | DocParam::OnlyPosBefore => None,
+            })
+            .collect();
+        SymbolCoverage {
+            name,
+            missing_docstring: function.docs.is_none(),
+            undocumented_params,
+            missing_return_type: function.ret.typ.is_none(),
+        }
+    }
+
+    fn property(name: String, property: &DocProperty) -> Self {
+        SymbolCoverage {
+            name,
+            missing_docstring: property.docs.is_none(),
+            undocumented_params: Vec::new(),
+            missing_return_type: false,
+        }
+    }
+
+    fn member(name: String, member: &DocMember) -> Self {
+        match member {
+            DocMember::Function(f) => Self::function(name, f),
+            DocMember::Property(p) => Self::property(name, p),
+        }
+    }
+}
+
+/// Documentation coverage of a top level [`Doc`] (usually a module or a type).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModuleCoverage {
+    /// Name of the [`Doc`].
+    pub name: String,
+    /// The module or type itself has no docstring.
+    pub missing_docstring: bool,
+    /// Every function and property defined by the [`Doc`].
+    pub symbols: Vec<SymbolCoverage>,
+}
+
+impl ModuleCoverage {
+    /// Compute the coverage of a single [`Doc`].
+    pub fn new(doc: &Doc) -> Self {
+        let name = doc.id.name.clone();
+        match &doc.item {
+            DocItem::Module(m) => ModuleCoverage {
+                missing_docstring: m.docs.is_none(),
+                symbols: m
+                    .members
+                    .iter()
+                    .map(|(k, v)| SymbolCoverage::member(k.clone(), v))
+                    .collect(),
+                name,
+            },
+            DocItem::Object(o) => ModuleCoverage {
+                missing_docstring: o.docs.is_none(),
+                symbols: o
+                    .members
+                    .iter()
+                    .map(|(k, v)| SymbolCoverage::member(format!("{}.{}", name, k), v))
+                    .collect(),
+                name,
+            },
+            DocItem::Function(f) => ModuleCoverage {
+                missing_docstring: false,
+                symbols: vec![SymbolCoverage::function(name.clone(), f)],
+                name,
+            },
+            DocItem::Property(p) => ModuleCoverage {
+                missing_docstring: false,
+                symbols: vec![SymbolCoverage::property(name.clone(), p)],
+                name,
+            },
+        }
+    }
+
+    /// Percentage (0 to 100) of symbols which are completely documented.
+    /// A module without symbols is considered completely documented.
+    pub fn percentage(&self) -> f64 {
+        if self.symbols.is_empty() {
+            return 100.0;
+        }
+        let complete = self.symbols.iter().filter(|s| s.is_complete()).count();
+        complete as f64 * 100.0 / self.symbols.len() as f64
+    }
+}
+
+impl Display for ModuleCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: {:.1}%", self.name, self.percentage())?;
+        if self.missing_docstring {
+            writeln!(f, "  {}: missing docstring", self.name)?;
+        }
+        for symbol in &self.symbols {
+            let mut problems = Vec::new();
+            if symbol.missing_docstring {
+                problems.push("missing docstring".to_owned());
+            }
+            if !symbol.undocumented_params.is_empty() {
+                problems.push(format!(
+                    "undocumented parameters: {}",
+                    symbol.undocumented_params.join(", ")
+                ));
+            }
+            if symbol.missing_return_type {
+                problems.push("missing return type".to_owned());
+            }
+            if !problems.is_empty() {
+                writeln!(f, "  {}: {}", symbol.name, problems.join("; "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compute documentation coverage for each of `docs`.
+pub fn coverage_report(docs: &[Doc]) -> Vec<ModuleCoverage> {
+    docs.iter().map(ModuleCoverage::new).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert;
+    use crate::docs::DocStringKind;
+
+    #[test]
+    fn reports_starlark_module() {
+        let module = assert::pass_module(
+            r#"
+"""Module docs"""
+
+def documented(x: "int") -> "int":
+    """
+    Doubles.
+
+    Args:
+        x: The value
+    """
+    return x * 2
+
+def undocumented(x, *args):
+    return x
+
+CONSTANT = 1
+"#,
+        );
+        let coverage = ModuleCoverage::new(&Doc::named_item(
+            "my.star".to_owned(),
+            DocItem::Module(module.documentation()),
+        ));
+
+        assert!(!coverage.missing_docstring);
+        let mut symbols = coverage.symbols.clone();
+        symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(
+            vec![
+                SymbolCoverage {
+                    name: "CONSTANT".to_owned(),
+                    missing_docstring: true,
+                    undocumented_params: vec![],
+                    missing_return_type: false,
+                },
+                SymbolCoverage {
+                    name: "documented".to_owned(),
+                    missing_docstring: false,
+                    undocumented_params: vec![],
+                    missing_return_type: false,
+                },
+                SymbolCoverage {
+                    name: "undocumented".to_owned(),
+                    missing_docstring: true,
+                    undocumented_params: vec!["x".to_owned(), "*args".to_owned()],
+                    missing_return_type: true,
+                },
+            ],
+            symbols
+        );
+        assert_eq!(100.0 / 3.0, coverage.percentage());
+    }
+
+    #[test]
+    fn renders_report() {
+        let function = DocFunction::from_docstring(
+            DocStringKind::Rust,
+            vec![DocParam::Arg {
+                name: "x".to_owned(),
+                docs: None,
+                typ: None,
+                default_value: None,
+            }],
+            None,
+            Some("Summary"),
+            None,
+        );
+        let report = coverage_report(&[
+            Doc::named_item("f".to_owned(), DocItem::Function(function)),
+            Doc::named_item(
+                "p".to_owned(),
+                DocItem::Property(DocProperty::from_docstring(
+                    DocStringKind::Rust,
+                    None,
+                    Some("Summary"),
+                )),
+            ),
+        ]);
+        assert_eq!(
+            "f: 0.0%\n  f: undocumented parameters: x; missing return type\n",
+            report[0].to_string()
+        );
+        assert_eq!("p: 100.0%\n", report[1].to_string());
+    }
+}
@@ -0,0 +1,402 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Run the code examples embedded in documentation.
+//!
+//! Examples are fenced code blocks in docstrings whose language is empty, `python`,
+//! `starlark` or `rust` (the latter because `#[starlark_module]` docs use rustdoc-style
+//! blocks, whose commented lines are stripped when the docs are parsed).
+//! Each example is evaluated in a fresh module, one top level statement at a time:
+//!
+//! * a statement which evaluates to `False` fails the example, so blocks of
+//!   `x == y` lines act as assertions;
+//! * a statement with a trailing `# error: <message>` (or `# fail: <message>`) comment
+//!   must fail with an error containing `<message>`; a bare `# error` accepts any error.
+
+use std::fmt;
+use std::fmt::Display;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::docs::Doc;
+use crate::docs::DocItem;
+use crate::docs::DocMember;
+use crate::docs::DocParam;
+use crate::docs::DocString;
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::errors::Diagnostic;
+use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+
+/// A code example extracted from a docstring.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocExample {
+    /// The symbol whose documentation contains the example, e.g. `string.capitalize`.
+    pub symbol: String,
+    /// Line of the first line of code within the docstring, one based.
+    ///
+    /// The docstring is counted as its summary, a blank line, and then its details.
+    pub line: usize,
+    /// The code inside the fenced block.
+    pub code: String,
+}
+
+/// An example which did not evaluate successfully.
+#[derive(Debug)]
+pub struct DocTestFailure {
+    /// The symbol whose documentation contains the example.
+    pub symbol: String,
+    /// Line within the docstring of the statement that failed, one based.
+    pub line: usize,
+    /// What went wrong.
+    pub error: anyhow::Error,
+}
+
+impl Display for DocTestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "doctest for `{}` failed at docstring line {}: {:#}",
+            self.symbol, self.line, self.error
+        )
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum DocTestError {
+    #[error("Statement evaluated to `False`:\n{0}")]
+    NotTrue(String),
+    #[error("Expected statement to fail with `{0}`, but it succeeded:\n{1}")]
+    ExpectedFailure(String, String),
+    #[error("Expected statement to fail with `{0}`, but it failed with `{1}`:\n{2}")]
+    WrongFailure(String, String, String),
+}
+
+/// Languages of fenced code blocks that are considered examples.
+const EXAMPLE_LANGUAGES: &[&str] = &["", "python", "starlark", "rust"];
+
+fn docstring_examples(symbol: &str, docs: &Option<DocString>, out: &mut Vec<DocExample>) {
+    let docs = match docs {
+        Some(docs) => docs,
+        None => return,
+    };
+    let text = match &docs.details {
+        Some(details) => format!("{}\n\n{}", docs.summary, details),
+        None => docs.summary.clone(),
+    };
+
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    let mut skipping = false;
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            if let Some((start, code)) = current.take() {
+                out.push(DocExample {
+                    symbol: symbol.to_owned(),
+                    line: start,
+                    code: textwrap::dedent(&code.join("\n")),
+                });
+            } else if skipping {
+                skipping = false;
+            } else if EXAMPLE_LANGUAGES.contains(&info.trim()) {
+                current = Some((i + 2, Vec::new()));
+            } else {
+                skipping = true;
+            }
+        } else if let Some((_, code)) = &mut current {
+            code.push(line);
+        }
+    }
+}
+
+fn function_examples(symbol: &str, params: &[DocParam], out: &mut Vec<DocExample>) {
+    for p in params {
+        match p {
+            DocParam::Arg { docs, .. }
+            | DocParam::Args { docs, .. }
+            | DocParam::Kwargs { docs, .. } => docstring_examples(symbol, docs, out),
+            DocParam::NoArgs | DocParam::OnlyPosBefore => {}
+        }
+    }
+}
+
+fn member_examples(symbol: &str, member: &DocMember, out: &mut Vec<DocExample>) {
+    match member {
+        DocMember::Property(p) => docstring_examples(symbol, &p.docs, out),
+        DocMember::Function(f) => {
+            docstring_examples(symbol, &f.docs, out);
+            function_examples(symbol, &f.params, out);
+            docstring_examples(symbol, &f.ret.docs, out);
+        }
+    }
+}
+
+/// Extract all the code examples from a documentation item and its members.
+///
+/// Members of modules and objects are reported as `name.member`.
+pub fn extract_examples(doc: &Doc) -> Vec<DocExample> {
+    let name = &doc.id.name;
+    let mut res = Vec::new();
+    match &doc.item {
+        DocItem::Module(m) => {
+            docstring_examples(name, &m.docs, &mut res);
+            for (k, v) in &m.members {
+                member_examples(&format!("{}.{}", name, k), v, &mut res);
+            }
+        }
+        DocItem::Object(o) => {
+            docstring_examples(name, &o.docs, &mut res);
+            for (k, v) in &o.members {
+                member_examples(&format!("{}.{}", name, k), v, &mut res);
+            }
+        }
+        DocItem::Function(f) => member_examples(name, &DocMember::Function(f.clone()), &mut res),
+        DocItem::Property(p) => docstring_examples(name, &p.docs, &mut res),
+    }
+    res
+}
+
+/// Split an example into top level statements, returning the line offset of each.
+///
+/// A new statement starts at an unindented line, as long as everything accumulated
+/// so far parses on its own (so multi-line expressions stay together).
+fn split_statements(code: &str) -> Vec<(usize, String)> {
+    fn parses(code: &str) -> bool {
+        AstModule::parse("doctest.star", code.to_owned(), &Dialect::Extended).is_ok()
+    }
+
+    let mut res = Vec::new();
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    for (i, line) in code.lines().enumerate() {
+        let starts_statement = !line.is_empty()
+            && !line.starts_with(char::is_whitespace)
+            && !line.starts_with(['#', ')', ']', '}'])
+            && !line.starts_with("else")
+            && !line.starts_with("elif");
+        match &mut current {
+            Some((start, lines)) if starts_statement && parses(&lines.join("\n")) => {
+                res.push((*start, lines.join("\n")));
+                current = Some((i, vec![line]));
+            }
+            Some((_, lines)) => lines.push(line),
+            None if line.trim().is_empty() => {}
+            None => current = Some((i, vec![line])),
+        }
+    }
+    if let Some((start, lines)) = current {
+        res.push((start, lines.join("\n")));
+    }
+    res
+}
+
+/// Evaluate a single example, returning the line offset of the failing statement on error.
+fn run_example(code: &str, globals: &Globals) -> Result<(), (usize, anyhow::Error)> {
+    static ERROR_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"#\s*(?:error|fail)\b:?\s*(.*?)\s*$").unwrap());
+
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    for (offset, stmt) in split_statements(code) {
+        let expected_error = stmt
+            .lines()
+            .find_map(|l| ERROR_RE.captures(l))
+            .map(|c| c.get(1).unwrap().as_str().to_owned());
+        let res = AstModule::parse("doctest.star", stmt.clone(), &Dialect::Extended)
+            .and_then(|ast| eval.eval_module(ast, globals));
+        match (res, expected_error) {
+            (Ok(v), None) => {
+                if v.unpack_bool() == Some(false) {
+                    return Err((offset, DocTestError::NotTrue(stmt).into()));
+                }
+            }
+            (Ok(_), Some(expected)) => {
+                return Err((offset, DocTestError::ExpectedFailure(expected, stmt).into()));
+            }
+            (Err(e), None) => return Err((offset, e)),
+            (Err(e), Some(expected)) => {
+                // The rendered diagnostic contains the source, which contains the expected
+                // message in the comment, so only look at the inner message.
+                let message = match e.downcast_ref::<Diagnostic>() {
+                    Some(d) => format!("{:#}", d.message),
+                    None => format!("{:#}", e),
+                };
+                if !message.contains(&expected) {
+                    return Err((
+                        offset,
+                        DocTestError::WrongFailure(expected, message, stmt).into(),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Evaluate every code example found in `docs` against `globals`.
+///
+/// Returns the examples that failed, each with the originating symbol and the line of
+/// the failing statement within that symbol's docstring.
+pub fn run_doctests(docs: &[Doc], globals: &Globals) -> Vec<DocTestFailure> {
+    let mut failures = Vec::new();
+    for doc in docs {
+        for example in extract_examples(doc) {
+            if let Err((offset, error)) = run_example(&example.code, globals) {
+                failures.push(DocTestFailure {
+                    symbol: example.symbol,
+                    line: example.line + offset,
+                    error,
+                });
+            }
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docs::DocFunction;
+    use crate::docs::DocModule;
+    use crate::docs::DocStringKind;
+    use crate::environment::GlobalsBuilder;
+
+    fn function_doc(name: &str, docstring: &str) -> Doc {
+        Doc::named_item(
+            name.to_owned(),
+            DocItem::Function(DocFunction::from_docstring(
+                DocStringKind::Starlark,
+                Vec::new(),
+                None,
+                Some(docstring),
+                None,
+            )),
+        )
+    }
+
+    #[test]
+    fn extracts_examples_with_lines() {
+        let doc = function_doc(
+            "f",
+            r#"Summary
+
+            Some details.
+
+            ```python
+            1 == 1
+            ```
+
+            ```text
+            not starlark
+            ```
+
+            ```
+            x = 1
+            x == 1
+            ```
+            "#,
+        );
+        assert_eq!(
+            vec![
+                DocExample {
+                    symbol: "f".to_owned(),
+                    line: 6,
+                    code: "1 == 1".to_owned(),
+                },
+                DocExample {
+                    symbol: "f".to_owned(),
+                    line: 14,
+                    code: "x = 1\nx == 1".to_owned(),
+                },
+            ],
+            extract_examples(&doc)
+        );
+    }
+
+    #[test]
+    fn splits_statements() {
+        assert_eq!(
+            vec![
+                (0, "x = [\n    1,\n]".to_owned()),
+                (3, "if x:\n    y = 1\nelse:\n    y = 2".to_owned()),
+                (7, "y == 1".to_owned()),
+            ],
+            split_statements("x = [\n    1,\n]\nif x:\n    y = 1\nelse:\n    y = 2\ny == 1")
+        );
+    }
+
+    #[test]
+    fn reports_failures() {
+        let docs = vec![Doc::named_item(
+            "m".to_owned(),
+            DocItem::Module(DocModule {
+                docs: None,
+                members: [
+                    (
+                        "good".to_owned(),
+                        DocMember::Function(DocFunction::from_docstring(
+                            DocStringKind::Starlark,
+                            Vec::new(),
+                            None,
+                            Some("Good\n\n```\n1 == 1\nfail('x') # error: x\n```"),
+                            None,
+                        )),
+                    ),
+                    (
+                        "bad".to_owned(),
+                        DocMember::Function(DocFunction::from_docstring(
+                            DocStringKind::Starlark,
+                            Vec::new(),
+                            None,
+                            Some("Bad\n\n```\n1 == 1\n\n1 == 2\n```"),
+                            None,
+                        )),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+            }),
+        )];
+        let failures = run_doctests(&docs, &Globals::standard());
+        assert_eq!(1, failures.len());
+        assert_eq!("m.bad", failures[0].symbol);
+        assert_eq!(6, failures[0].line);
+        assert!(failures[0].to_string().contains("1 == 2"));
+    }
+
+    #[test]
+    fn standard_library_examples_pass() {
+        let globals = GlobalsBuilder::extended().build();
+        let mut docs = crate::docs::get_registered_starlark_docs();
+        docs.push(Doc::named_item(
+            "globals".to_owned(),
+            DocItem::Module(globals.documentation()),
+        ));
+        let failures = run_doctests(&docs, &globals);
+        assert!(
+            failures.is_empty(),
+            "{}",
+            failures
+                .iter()
+                .map(|f| f.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+}
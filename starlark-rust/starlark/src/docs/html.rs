@@ -0,0 +1,599 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Render a collection of [`Doc`]s as a static HTML site.
+//!
+//! Every top level [`Doc`] gets its own page. Modules (and free standing functions and
+//! properties) are listed under "Modules" in the navigation, objects under "Types".
+//! The site also contains an `index.html` with a client side search box, backed by
+//! `search-index.js`, which lists every documented symbol.
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use serde::Serialize;
+use starlark_map::small_map::SmallMap;
+
+use crate::docs::markdown::TypeRenderer;
+use crate::docs::Doc;
+use crate::docs::DocDeprecation;
+use crate::docs::DocFunction;
+use crate::docs::DocItem;
+use crate::docs::DocMember;
+use crate::docs::DocParam;
+use crate::docs::DocProperty;
+use crate::docs::DocString;
+use crate::docs::MarkdownFlavor;
+use crate::docs::RenderMarkdown;
+
+const STYLE_CSS: &str = r#"body { font-family: sans-serif; margin: 0; display: flex; }
+nav { width: 16em; min-height: 100vh; padding: 1em; background: #f4f4f4; box-sizing: border-box; }
+nav h3 { margin-bottom: 0.3em; }
+nav ul { list-style: none; padding-left: 0.5em; margin-top: 0; }
+main { flex: 1; padding: 1em 2em; max-width: 60em; }
+pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }
+code { font-family: monospace; }
+.deprecated { border-left: 4px solid #d9822b; padding-left: 0.5em; }
+.kind { color: #777; font-size: 0.8em; }
+#search { width: 100%; font-size: 1.1em; padding: 0.3em; box-sizing: border-box; }
+"#;
+
+const SEARCH_JS: &str = r#"function runSearch(query) {
+  const results = document.getElementById("search-results");
+  results.innerHTML = "";
+  query = query.trim().toLowerCase();
+  if (query === "") {
+    return;
+  }
+  for (const entry of SEARCH_INDEX) {
+    if (entry.name.toLowerCase().includes(query)) {
+      const item = document.createElement("li");
+      const link = document.createElement("a");
+      link.href = entry.url;
+      link.textContent = entry.name;
+      const kind = document.createElement("span");
+      kind.className = "kind";
+      kind.textContent = " " + entry.kind + (entry.summary ? " - " + entry.summary : "");
+      item.appendChild(link);
+      item.appendChild(kind);
+      results.appendChild(item);
+    }
+  }
+}
+"#;
+
+/// One entry in `search-index.js`.
+#[derive(Serialize)]
+struct SearchEntry {
+    name: String,
+    kind: &'static str,
+    url: String,
+    summary: Option<String>,
+}
+
+fn escape_html(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&#39;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Escape text, rendering `code` spans as `<code>`.
+fn render_inline(s: &str) -> String {
+    s.split('`')
+        .enumerate()
+        .map(|(i, x)| {
+            if i % 2 == 1 {
+                format!("<code>{}</code>", escape_html(x))
+            } else {
+                escape_html(x)
+            }
+        })
+        .join("")
+}
+
+/// Convert the subset of markdown used in docstrings (paragraphs, headers, bullet lists,
+/// fenced code blocks and inline code) to HTML.
+fn markdown_to_html(s: &str) -> String {
+    let mut res = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut list: Vec<String> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    fn flush(res: &mut Vec<String>, paragraph: &mut Vec<&str>, list: &mut Vec<String>) {
+        if !paragraph.is_empty() {
+            res.push(format!("<p>{}</p>", render_inline(&paragraph.join("\n"))));
+            paragraph.clear();
+        }
+        if !list.is_empty() {
+            res.push(format!(
+                "<ul>\n{}\n</ul>",
+                list.iter().map(|x| format!("<li>{}</li>", x)).join("\n")
+            ));
+            list.clear();
+        }
+    }
+
+    for line in s.lines() {
+        let trimmed = line.trim_start();
+        if let Some(lines) = &mut code {
+            if trimmed.starts_with("```") {
+                res.push(format!(
+                    "<pre><code>{}</code></pre>",
+                    escape_html(&lines.join("\n"))
+                ));
+                code = None;
+            } else {
+                lines.push(line);
+            }
+        } else if trimmed.starts_with("```") {
+            flush(&mut res, &mut paragraph, &mut list);
+            code = Some(Vec::new());
+        } else if trimmed.is_empty() {
+            flush(&mut res, &mut paragraph, &mut list);
+        } else if let Some(header) = trimmed.strip_prefix('#') {
+            flush(&mut res, &mut paragraph, &mut list);
+            let level = 3 + header.chars().take_while(|c| *c == '#').count().min(3);
+            let header = header.trim_start_matches('#').trim();
+            res.push(format!("<h{level}>{}</h{level}>", render_inline(header)));
+        } else if let Some(item) = trimmed
+            .strip_prefix("* ")
+            .or_else(|| trimmed.strip_prefix("- "))
+        {
+            if !paragraph.is_empty() {
+                flush(&mut res, &mut paragraph, &mut list);
+            }
+            list.push(render_inline(item));
+        } else if !list.is_empty() && line.starts_with(' ') {
+            // Continuation of the previous list item.
+            let last = list.last_mut().unwrap();
+            last.push(' ');
+            last.push_str(&render_inline(trimmed));
+        } else {
+            if !list.is_empty() {
+                flush(&mut res, &mut paragraph, &mut list);
+            }
+            paragraph.push(line);
+        }
+    }
+    if let Some(lines) = code {
+        res.push(format!(
+            "<pre><code>{}</code></pre>",
+            escape_html(&lines.join("\n"))
+        ));
+    }
+    flush(&mut res, &mut paragraph, &mut list);
+    res.join("\n")
+}
+
+fn render_doc_string(docs: &Option<DocString>) -> Option<String> {
+    docs.as_ref().map(|d| match &d.details {
+        Some(details) => markdown_to_html(&format!("{}\n\n{}", d.summary, details)),
+        None => markdown_to_html(&d.summary),
+    })
+}
+
+fn render_deprecation(name: &str, deprecated: &Option<DocDeprecation>) -> Option<String> {
+    deprecated.as_ref().map(|d| {
+        format!(
+            "<p class=\"deprecated\"><strong>Deprecated</strong>: {}</p>",
+            render_inline(&d.describe(name))
+        )
+    })
+}
+
+fn render_parameters(params: &[DocParam]) -> Option<String> {
+    let items: Vec<String> = params
+        .iter()
+        .filter_map(|p| match p {
+            DocParam::Arg { name, docs, .. }
+            | DocParam::Args { name, docs, .. }
+            | DocParam::Kwargs { name, docs, .. } => Some((name, docs.as_ref()?)),
+            DocParam::NoArgs | DocParam::OnlyPosBefore => None,
+        })
+        .map(|(name, docs)| {
+            format!(
+                "<li><code>{}</code>: {}</li>",
+                escape_html(name),
+                render_doc_string(&Some(docs.clone())).unwrap_or_default()
+            )
+        })
+        .collect();
+    if items.is_empty() {
+        None
+    } else {
+        Some(format!("<ul>\n{}\n</ul>", items.join("\n")))
+    }
+}
+
+fn render_function(name: &str, function: &DocFunction) -> String {
+    let prototype = TypeRenderer::Function {
+        function_name: name,
+        f: function,
+    }
+    .render_markdown(MarkdownFlavor::DocFile);
+    let mut res = vec![format!(
+        "<pre><code>{}</code></pre>",
+        escape_html(&prototype)
+    )];
+    res.extend(render_deprecation(name, &function.deprecated));
+    res.extend(render_doc_string(&function.docs));
+    if let Some(params) = render_parameters(&function.params) {
+        res.push("<h4>Parameters</h4>".to_owned());
+        res.push(params);
+    }
+    if let Some(ret) = render_doc_string(&function.ret.docs) {
+        res.push("<h4>Returns</h4>".to_owned());
+        res.push(ret);
+    }
+    res.join("\n")
+}
+
+fn render_property(name: &str, property: &DocProperty) -> String {
+    let prototype = format!(
+        "{}: {}",
+        name,
+        TypeRenderer::Type(&property.typ).render_markdown(MarkdownFlavor::DocFile)
+    );
+    let mut res = vec![format!(
+        "<pre><code>{}</code></pre>",
+        escape_html(&prototype)
+    )];
+    res.extend(render_deprecation(name, &property.deprecated));
+    res.extend(render_doc_string(&property.docs));
+    res.join("\n")
+}
+
+fn render_member(name: &str, member: &DocMember) -> String {
+    match member {
+        DocMember::Property(p) => render_property(name, p),
+        DocMember::Function(f) => render_function(name, f),
+    }
+}
+
+fn member_kind(member: &DocMember) -> &'static str {
+    match member {
+        DocMember::Property(_) => "property",
+        DocMember::Function(_) => "function",
+    }
+}
+
+fn member_summary(member: &DocMember) -> Option<String> {
+    match member {
+        DocMember::Property(p) => p.docs.as_ref(),
+        DocMember::Function(f) => f.docs.as_ref(),
+    }
+    .map(|d| d.summary.clone())
+}
+
+/// The file name of the page for a top level [`Doc`], before disambiguating it from the pages of
+/// the other docs.
+///
+/// Pages of modules which would clash with `index.html` or the pages of types are prefixed
+/// with `module.`, and so are the modules whose name already starts with it.
+fn page_name(doc: &Doc) -> String {
+    let name: String = doc
+        .id
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match &doc.item {
+        DocItem::Object(_) => format!("type.{}", name),
+        _ if name == "index" || name.starts_with("type.") || name.starts_with("module.") => {
+            format!("module.{}", name)
+        }
+        _ => name,
+    }
+}
+
+/// The file names of the pages for `docs`, in the same order. Names which only differ in the
+/// characters that can't appear in a page name get the same page name, in which case the later
+/// docs get a numeric suffix.
+fn page_names(docs: &[Doc]) -> Vec<String> {
+    let mut taken = HashSet::new();
+    taken.insert("index.html".to_owned());
+    docs.iter()
+        .map(|doc| {
+            let name = page_name(doc);
+            let mut page = format!("{}.html", name);
+            let mut suffix = 1;
+            while !taken.insert(page.clone()) {
+                suffix += 1;
+                page = format!("{}-{}.html", name, suffix);
+            }
+            page
+        })
+        .collect()
+}
+
+fn render_navigation(docs: &[Doc], pages: &[String]) -> String {
+    let (types, modules): (Vec<_>, Vec<_>) = docs
+        .iter()
+        .zip(pages)
+        .sorted_by(|(a, _), (b, _)| a.id.name.cmp(&b.id.name))
+        .partition(|(d, _)| matches!(d.item, DocItem::Object(_)));
+    let list = |docs: Vec<(&Doc, &String)>| {
+        docs.iter()
+            .map(|(d, page)| {
+                format!(
+                    "<li><a href=\"{}\">{}</a></li>",
+                    escape_html(page),
+                    escape_html(&d.id.name)
+                )
+            })
+            .join("\n")
+    };
+    format!(
+        "<nav>\n<a href=\"index.html\">Index</a>\n<h3>Modules</h3>\n<ul>\n{}\n</ul>\n<h3>Types</h3>\n<ul>\n{}\n</ul>\n</nav>",
+        list(modules),
+        list(types)
+    )
+}
+
+fn render_page(title: &str, navigation: &str, body: &str, scripts: &[&str]) -> String {
+    let scripts = scripts
+        .iter()
+        .map(|s| format!("<script src=\"{}\"></script>\n", s))
+        .join("");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"style.css\">\n{}</head>\n<body>\n{}\n<main>\n{}\n</main>\n</body>\n</html>\n",
+        escape_html(title),
+        scripts,
+        navigation,
+        body
+    )
+}
+
+/// Render the body of `page`, the page for `doc`, adding everything it defines to `index`.
+fn render_doc_page(doc: &Doc, page: &str, index: &mut Vec<SearchEntry>) -> String {
+    let name = &doc.id.name;
+    let (kind, docs, members) = match &doc.item {
+        DocItem::Module(m) => ("module", &m.docs, Some(&m.members)),
+        DocItem::Object(o) => ("type", &o.docs, Some(&o.members)),
+        DocItem::Function(f) => ("function", &f.docs, None),
+        DocItem::Property(p) => ("property", &p.docs, None),
+    };
+    index.push(SearchEntry {
+        name: name.clone(),
+        kind,
+        url: page.to_owned(),
+        summary: docs.as_ref().map(|d| d.summary.clone()),
+    });
+
+    let mut res = vec![format!(
+        "<h1>{} <span class=\"kind\">{}</span></h1>",
+        escape_html(name),
+        kind
+    )];
+    match &doc.item {
+        DocItem::Function(f) => res.push(render_function(name, f)),
+        DocItem::Property(p) => res.push(render_property(name, p)),
+        DocItem::Module(_) | DocItem::Object(_) => {
+            res.extend(render_doc_string(docs));
+        }
+    }
+    if let Some(members) = members {
+        let prefix = match &doc.item {
+            DocItem::Object(_) => format!("{}.", name),
+            _ => String::new(),
+        };
+        let members = members.iter().sorted_by(|(a, _), (b, _)| a.cmp(b));
+        for (member_name, member) in members {
+            let full_name = format!("{}{}", prefix, member_name);
+            index.push(SearchEntry {
+                name: full_name.clone(),
+                kind: member_kind(member),
+                url: format!("{}#{}", page, member_name),
+                summary: member_summary(member),
+            });
+            res.push(format!(
+                "<h2 id=\"{}\">{}</h2>",
+                escape_html(member_name),
+                escape_html(&full_name)
+            ));
+            res.push(render_member(&full_name, member));
+        }
+    }
+    res.join("\n")
+}
+
+/// Render `docs` as a static HTML site.
+///
+/// Returns a map from file name (relative to the root of the site) to file contents.
+/// Callers are expected to write every file into a single directory.
+pub fn render_docs_as_html(docs: &[Doc]) -> SmallMap<String, String> {
+    let pages = page_names(docs);
+    let navigation = render_navigation(docs, &pages);
+    let mut index = Vec::new();
+    let mut res = SmallMap::new();
+    for (doc, page) in docs.iter().zip(pages) {
+        let body = render_doc_page(doc, &page, &mut index);
+        res.insert(page, render_page(&doc.id.name, &navigation, &body, &[]));
+    }
+
+    let index_body = "<h1>Documentation</h1>\n\
+        <input id=\"search\" type=\"search\" placeholder=\"Search\" oninput=\"runSearch(this.value)\">\n\
+        <ul id=\"search-results\"></ul>";
+    res.insert(
+        "index.html".to_owned(),
+        render_page(
+            "Documentation",
+            &navigation,
+            index_body,
+            &["search-index.js", "search.js"],
+        ),
+    );
+    res.insert(
+        "search-index.js".to_owned(),
+        format!(
+            "const SEARCH_INDEX = {};\n",
+            serde_json::to_string(&index).unwrap()
+        ),
+    );
+    res.insert("search.js".to_owned(), SEARCH_JS.to_owned());
+    res.insert("style.css".to_owned(), STYLE_CSS.to_owned());
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docs::DocModule;
+    use crate::docs::DocObject;
+    use crate::docs::DocStringKind;
+    use crate::environment::GlobalsBuilder;
+
+    #[test]
+    fn converts_markdown() {
+        assert_eq!(
+            "<p>Some <code>code</code> &amp; text\nover two lines</p>\n\
+            <h3>Header</h3>\n\
+            <ul>\n<li>one item</li>\n<li>two <code>a&lt;b</code> continued</li>\n</ul>\n\
+            <pre><code>x = 1\n  y = 2</code></pre>",
+            markdown_to_html(
+                "Some `code` & text\nover two lines\n\n# Header\n* one item\n* two `a<b`\n  continued\n```python\nx = 1\n  y = 2\n```"
+            )
+        );
+    }
+
+    #[test]
+    fn renders_site() {
+        let object = DocObject {
+            docs: DocString::from_docstring(DocStringKind::Rust, "An object <with> markup"),
+            members: SmallMap::from_iter([(
+                "field".to_owned(),
+                DocMember::Property(DocProperty::default()),
+            )]),
+        };
+        let docs = vec![
+            Doc::named_item(
+                "globals".to_owned(),
+                DocItem::Module(GlobalsBuilder::standard().build().documentation()),
+            ),
+            Doc::named_item("my obj".to_owned(), DocItem::Object(object)),
+            Doc::named_item("empty".to_owned(), DocItem::Module(DocModule::default())),
+        ];
+        let site = render_docs_as_html(&docs);
+
+        assert_eq!(
+            vec![
+                "globals.html",
+                "type.my_obj.html",
+                "empty.html",
+                "index.html",
+                "search-index.js",
+                "search.js",
+                "style.css",
+            ],
+            site.keys().collect::<Vec<_>>()
+        );
+
+        let object_page = site.get("type.my_obj.html").unwrap();
+        assert!(object_page.contains("An object &lt;with&gt; markup"));
+        assert!(object_page.contains("<h2 id=\"field\">my obj.field</h2>"));
+        assert!(object_page.contains("<a href=\"globals.html\">globals</a>"));
+
+        let globals_page = site.get("globals.html").unwrap();
+        assert!(globals_page.contains("<h2 id=\"len\">len</h2>"));
+
+        let index = site.get("search-index.js").unwrap();
+        assert!(index.contains(r#"{"name":"len","kind":"function","url":"globals.html#len""#));
+        assert!(index.contains(r#""name":"my obj.field","kind":"property""#));
+    }
+
+    #[test]
+    fn page_names_dont_clash() {
+        let module =
+            |name: &str| Doc::named_item(name.to_owned(), DocItem::Module(DocModule::default()));
+        let docs = vec![
+            module("index"),
+            module("module.index"),
+            module("type.foo"),
+            Doc::named_item("foo".to_owned(), DocItem::Object(DocObject::default())),
+        ];
+        let site = render_docs_as_html(&docs);
+
+        assert_eq!(
+            vec![
+                "module.index.html",
+                "module.module.index.html",
+                "module.type.foo.html",
+                "type.foo.html",
+                "index.html",
+                "search-index.js",
+                "search.js",
+                "style.css",
+            ],
+            site.keys().collect::<Vec<_>>()
+        );
+        assert!(
+            site.get("index.html")
+                .unwrap()
+                .contains("<h1>Documentation</h1>")
+        );
+    }
+
+    #[test]
+    fn colliding_page_names_are_disambiguated() {
+        let object =
+            |name: &str| Doc::named_item(name.to_owned(), DocItem::Object(DocObject::default()));
+        let module =
+            |name: &str| Doc::named_item(name.to_owned(), DocItem::Module(DocModule::default()));
+        let docs = vec![
+            object("my obj"),
+            object("my_obj"),
+            object("my$obj"),
+            module("a b"),
+            module("a_b"),
+        ];
+        let site = render_docs_as_html(&docs);
+
+        assert_eq!(
+            vec![
+                "type.my_obj.html",
+                "type.my_obj-2.html",
+                "type.my_obj-3.html",
+                "a_b.html",
+                "a_b-2.html",
+                "index.html",
+                "search-index.js",
+                "search.js",
+                "style.css",
+            ],
+            site.keys().collect::<Vec<_>>()
+        );
+        let page = site.get("type.my_obj-2.html").unwrap();
+        assert!(page.contains("<h1>my_obj <span class=\"kind\">type</span></h1>"));
+        assert!(page.contains("<a href=\"type.my_obj-3.html\">my$obj</a>"));
+        assert!(page.contains("<a href=\"a_b.html\">a b</a>"));
+    }
+}
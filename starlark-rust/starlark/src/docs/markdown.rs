@@ -20,6 +20,7 @@ use itertools::Itertools;
 use starlark_map::small_map::SmallMap;
 
 use crate::docs::Doc;
+use crate::docs::DocDeprecation;
 use crate::docs::DocFunction;
 use crate::docs::DocItem;
 use crate::docs::DocMember;
@@ -94,16 +95,27 @@ fn escape_name(name: &str) -> String {
     name.replace('_', "\\_")
 }
 
+fn render_deprecation(name: &str, deprecated: &Option<DocDeprecation>) -> Option<String> {
+    deprecated
+        .as_ref()
+        .map(|d| format!("> **Deprecated**: {}", d.describe(name)))
+}
+
 fn render_property(name: &str, property: &DocProperty) -> String {
     let prototype = render_code_block(&format!(
         "{name}: {}",
         TypeRenderer::Type(&property.typ).render_markdown(MarkdownFlavor::DocFile)
     ));
     let header = format!("## {}\n\n{prototype}", escape_name(name));
+    let deprecation = render_deprecation(name, &property.deprecated);
     let summary = render_doc_string(DSOpts::Summary, &property.docs);
     let details = render_doc_string(DSOpts::Details, &property.docs);
 
     let mut body = header;
+    if let Some(deprecation) = deprecation {
+        body.push_str("\n\n");
+        body.push_str(&deprecation);
+    }
     if let Some(summary) = summary {
         body.push_str("\n\n");
         body.push_str(&summary);
@@ -158,6 +170,7 @@ fn render_function(name: &str, function: &DocFunction) -> String {
         .render_markdown(MarkdownFlavor::DocFile)),
     );
     let header = format!("## {}\n\n{prototype}", escape_name(name));
+    let deprecation = render_deprecation(name, &function.deprecated);
     let summary = render_doc_string(DSOpts::Summary, &function.docs);
     let details = render_doc_string(DSOpts::Details, &function.docs);
 
@@ -165,6 +178,10 @@ fn render_function(name: &str, function: &DocFunction) -> String {
     let return_docs = render_doc_string(DSOpts::Combined, &function.ret.docs);
 
     let mut body = header;
+    if let Some(deprecation) = &deprecation {
+        body.push_str("\n\n");
+        body.push_str(deprecation);
+    }
     if let Some(summary) = &summary {
         body.push_str("\n\n");
         body.push_str(summary);
@@ -273,7 +290,7 @@ const MAX_LENGTH_BEFORE_MULTILINE: usize = 80;
 
 /// Render a "type". This is either a [`Type`] object, or details about a function to
 /// produce a function prototype.
-enum TypeRenderer<'a> {
+pub(crate) enum TypeRenderer<'a> {
     /// A general "type".
     Type(&'a Option<DocType>),
     /// A function, with some extra formatting options.
@@ -20,12 +20,23 @@
 // TODO(nga): document it
 #![allow(missing_docs)]
 
+mod coverage;
+mod doctest;
+mod html;
 mod markdown;
 
 use std::collections::HashMap;
 
 use allocative::Allocative;
+pub use coverage::coverage_report;
+pub use coverage::ModuleCoverage;
+pub use coverage::SymbolCoverage;
+pub use doctest::extract_examples;
+pub use doctest::run_doctests;
+pub use doctest::DocExample;
+pub use doctest::DocTestFailure;
 use dupe::Dupe;
+pub use html::render_docs_as_html;
 use itertools::Itertools;
 pub use markdown::MarkdownFlavor;
 pub use markdown::RenderMarkdown;
@@ -386,6 +397,8 @@ pub struct DocFunction {
     pub ret: DocReturn,
     /// Does this function provide a `.type` value.
     pub dot_type: Option<String>,
+    /// If present, this function should no longer be used.
+    pub deprecated: Option<DocDeprecation>,
 }
 
 impl DocFunction {
@@ -422,6 +435,11 @@ impl DocFunction {
             let indented = indent_trimmed(&ret_docs, "    ");
             docs.push_str(&format!("\n\nRet:\n{}", indented));
         }
+
+        if let Some(deprecated) = &self.deprecated {
+            let indented = indent_trimmed(&deprecated.starlark_docstring(), "    ");
+            docs.push_str(&format!("\n\nDeprecated:\n{}", indented));
+        }
         if docs.is_empty() {
             None
         } else {
@@ -470,8 +488,8 @@ impl DocFunction {
     /// * `return_type`: The return type. This is pulled from typing info / directly from users,
     ///                  so it cannot be inferred generically.
     /// * `raw_docstring`: The raw docstring to be parsed and potentially modified,
-    ///                    removing the sections detailing arguments and return values.
-    ///                    The format is determined by `kind`.
+    ///                    removing the sections detailing arguments, return values
+    ///                    and deprecation. The format is determined by `kind`.
     pub fn from_docstring(
         kind: DocStringKind,
         mut params: Vec<DocParam>,
@@ -481,8 +499,10 @@ impl DocFunction {
     ) -> Self {
         match raw_docstring.and_then(|raw| DocString::from_docstring(kind, raw)) {
             Some(ds) => {
-                let (function_docstring, sections) =
-                    ds.parse_and_remove_sections(kind, &["arguments", "args", "returns", "return"]);
+                let (function_docstring, sections) = ds.parse_and_remove_sections(
+                    kind,
+                    &["arguments", "args", "returns", "return", "deprecated"],
+                );
 
                 match sections.get("arguments").or_else(|| sections.get("args")) {
                     Some(args) => {
@@ -491,10 +511,12 @@ impl DocFunction {
                             match x {
                                 DocParam::Arg { name, docs, .. }
                                 | DocParam::Args { name, docs, .. }
-                                | DocParam::Kwargs { name, docs, .. } => match entries.get(name) {
-                                    Some(raw) => *docs = DocString::from_docstring(kind, raw),
-                                    _ => (),
-                                },
+                                | DocParam::Kwargs { name, docs, .. } => {
+                                    match entries.get(name.trim_start_matches('*')) {
+                                        Some(raw) => *docs = DocString::from_docstring(kind, raw),
+                                        _ => (),
+                                    }
+                                }
                                 _ => (),
                             }
                         }
@@ -507,6 +529,10 @@ impl DocFunction {
                     .or_else(|| sections.get("returns"))
                     .and_then(|raw| DocString::from_docstring(kind, raw));
 
+                let deprecated = sections
+                    .get("deprecated")
+                    .map(|raw| DocDeprecation::parse(raw));
+
                 DocFunction {
                     docs: Some(function_docstring),
                     params,
@@ -515,6 +541,7 @@ impl DocFunction {
                         typ: return_type,
                     },
                     dot_type,
+                    deprecated,
                 }
             }
             None => DocFunction {
@@ -525,6 +552,7 @@ impl DocFunction {
                     typ: return_type,
                 },
                 dot_type,
+                deprecated: None,
             },
         }
     }
@@ -535,11 +563,16 @@ impl DocFunction {
     /// the `DocString::parse_and_remove_sections()` function call. This is done as a
     /// separate function to reduce the number of times that sections are parsed out of
     /// docstring (e.g. if a user wants both the `Args:` and `Returns:` sections)
+    ///
+    /// Starlark entries may be written as `name: docs` or, Google style, as `name (type): docs`.
+    /// Leading `*`s are stripped from the returned names, so `args:` and `*args:` both
+    /// document a `*args` parameter.
     fn parse_params(kind: DocStringKind, args_section: &str) -> HashMap<String, String> {
-        static STARLARK_ARG_RE: Lazy<Regex> =
-            Lazy::new(|| Regex::new(r"^(\*{0,2}\w+):\s*(.*)").unwrap());
+        static STARLARK_ARG_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^\*{0,2}(?P<name>\w+)(?:\s*\([^)]*\))?:\s*(?P<docs>.*)").unwrap()
+        });
         static RUST_ARG_RE: Lazy<Regex> =
-            Lazy::new(|| Regex::new(r"^(?:\* )?`(\w+)`:?\s*(.*)").unwrap());
+            Lazy::new(|| Regex::new(r"^(?:\* )?`(?P<name>\w+)`:?\s*(?P<docs>.*)").unwrap());
 
         static INDENTED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:\s|$)").unwrap());
 
@@ -558,9 +591,9 @@ impl DocFunction {
                     ret.insert(a, DocString::join_and_dedent_lines(&current_text));
                 }
 
-                current_arg = Some(matches.get(1).unwrap().as_str().to_owned());
+                current_arg = Some(matches.name("name").unwrap().as_str().to_owned());
 
-                let doc_match = matches.get(2).unwrap();
+                let doc_match = matches.name("docs").unwrap();
                 current_text = vec![format!(
                     "{}{}",
                     " ".repeat(doc_match.start()),
@@ -666,16 +699,143 @@ impl DocReturn {
     }
 }
 
+/// Details about why, since when, and in favour of what a function or property is deprecated.
+///
+/// Parsed from a `Deprecated:` (Starlark) or `# Deprecated` (Rust) docstring section, e.g.:
+///
+/// ```text
+/// Deprecated:
+///     Sorting is now stable by default.
+///     Since: 0.9
+///     Replacement: sorted
+/// ```
+///
+/// or set with `#[starlark(deprecated(...))]` in `#[starlark_module]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Default, Allocative)]
+pub struct DocDeprecation {
+    /// Free-form explanation of the deprecation.
+    pub message: Option<String>,
+    /// What should be used instead, e.g. the name of another function.
+    pub replacement: Option<String>,
+    /// The version in which the deprecation happened.
+    pub since: Option<String>,
+}
+
+impl DocDeprecation {
+    /// Parse the (dedented) contents of a deprecation section.
+    fn parse(section: &str) -> Self {
+        static KEY_RE: Lazy<Regex> = Lazy::new(|| {
+            RegexBuilder::new(r"^(?:\* )?`?(since|replacement)`?:\s*(.*?)\s*$")
+                .case_insensitive(true)
+                .build()
+                .unwrap()
+        });
+
+        let mut res = DocDeprecation::default();
+        let mut message = Vec::new();
+        for line in section.lines() {
+            match KEY_RE.captures(line) {
+                Some(captures) => {
+                    let value = captures.get(2).unwrap().as_str().to_owned();
+                    match captures
+                        .get(1)
+                        .unwrap()
+                        .as_str()
+                        .to_ascii_lowercase()
+                        .as_str()
+                    {
+                        "since" => res.since = Some(value),
+                        _ => res.replacement = Some(value),
+                    }
+                }
+                None => message.push(line.trim()),
+            }
+        }
+        let message = message.join(" ").trim().to_owned();
+        if !message.is_empty() {
+            res.message = Some(message);
+        }
+        res
+    }
+
+    /// A one line human readable description of the deprecation of `name`, e.g.
+    /// "`sort` is deprecated since 0.9: Sorting is now stable by default. Use `sorted` instead."
+    pub fn describe(&self, name: &str) -> String {
+        let mut res = format!("`{}` is deprecated", name);
+        if let Some(since) = &self.since {
+            res.push_str(&format!(" since {}", since));
+        }
+        if let Some(message) = &self.message {
+            res.push_str(&format!(": {}", message.trim_end_matches('.')));
+        }
+        res.push('.');
+        if let Some(replacement) = &self.replacement {
+            res.push_str(&format!(" Use `{}` instead.", replacement));
+        }
+        res
+    }
+
+    /// Render in the same format that [`DocDeprecation::parse`] reads.
+    fn starlark_docstring(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(message) = &self.message {
+            lines.push(message.clone());
+        }
+        if let Some(since) = &self.since {
+            lines.push(format!("Since: {}", since));
+        }
+        if let Some(replacement) = &self.replacement {
+            lines.push(format!("Replacement: {}", replacement));
+        }
+        lines.join("\n")
+    }
+}
+
 /// A single property of an object. These are explicitly not functions (see [`DocMember`]).
 #[derive(Debug, Clone, PartialEq, Serialize, Default, Allocative)]
 pub struct DocProperty {
     pub docs: Option<DocString>,
     #[serde(rename = "type")]
     pub typ: Option<DocType>,
+    /// If present, this property should no longer be used.
+    pub deprecated: Option<DocDeprecation>,
 }
 
 impl DocProperty {
+    /// Parses property documentation out of a docstring, removing any deprecation section.
+    pub fn from_docstring(
+        kind: DocStringKind,
+        typ: Option<DocType>,
+        raw_docstring: Option<&str>,
+    ) -> Self {
+        match raw_docstring.and_then(|raw| DocString::from_docstring(kind, raw)) {
+            Some(ds) => {
+                let (docs, sections) = ds.parse_and_remove_sections(kind, &["deprecated"]);
+                DocProperty {
+                    docs: Some(docs),
+                    typ,
+                    deprecated: sections
+                        .get("deprecated")
+                        .map(|raw| DocDeprecation::parse(raw)),
+                }
+            }
+            None => DocProperty {
+                docs: None,
+                typ,
+                deprecated: None,
+            },
+        }
+    }
+
     fn render_as_code(&self, name: &str) -> String {
+        let code = self.render_as_code_without_deprecation(name);
+        match &self.deprecated {
+            Some(deprecated) => format!("# {}\n{}", deprecated.describe(name), code),
+            None => code,
+        }
+    }
+
+    fn render_as_code_without_deprecation(&self, name: &str) -> String {
         match (
             self.typ.as_ref(),
             self.docs.as_ref().map(DocString::render_as_quoted_code),
@@ -718,6 +878,7 @@ impl DocMember {
                 typ: Some(DocType {
                     raw_type: value.get_type_starlark_repr(),
                 }),
+                deprecated: None,
             }),
         }
     }
@@ -1210,6 +1371,7 @@ mod tests {
                 typ: return_type.clone(),
             },
             dot_type: None,
+            deprecated: None,
         };
 
         let function_docs = DocFunction::from_docstring(
@@ -1228,6 +1390,61 @@ mod tests {
         assert_eq!(expected, function_docs);
     }
 
+    #[test]
+    fn parses_google_style_starlark_params() {
+        let docstring = r#"Summary
+
+        Args:
+            name (str): The name, which
+                spans two lines
+            args: Docs for args
+            **kwargs (dict): Docs for kwargs
+        "#;
+
+        let kind = DocStringKind::Starlark;
+        let function_docs = DocFunction::from_docstring(
+            kind,
+            vec![
+                arg("name"),
+                DocParam::Args {
+                    name: "*args".to_owned(),
+                    docs: None,
+                    typ: None,
+                },
+                DocParam::Kwargs {
+                    name: "**kwargs".to_owned(),
+                    docs: None,
+                    typ: None,
+                },
+            ],
+            None,
+            Some(docstring),
+            None,
+        );
+
+        assert_eq!(
+            vec![
+                DocParam::Arg {
+                    name: "name".to_owned(),
+                    docs: DocString::from_docstring(kind, "The name, which\nspans two lines"),
+                    typ: None,
+                    default_value: None,
+                },
+                DocParam::Args {
+                    name: "*args".to_owned(),
+                    docs: DocString::from_docstring(kind, "Docs for args"),
+                    typ: None,
+                },
+                DocParam::Kwargs {
+                    name: "**kwargs".to_owned(),
+                    docs: DocString::from_docstring(kind, "Docs for kwargs"),
+                    typ: None,
+                },
+            ],
+            function_docs.params
+        );
+    }
+
     #[test]
     fn parses_rust_function_docstring() {
         let docstring = r#"This is an example docstring
@@ -1276,6 +1493,7 @@ mod tests {
                 typ: return_type.clone(),
             },
             dot_type: None,
+            deprecated: None,
         };
 
         let function_docs = DocFunction::from_docstring(
@@ -1288,4 +1506,59 @@ mod tests {
 
         assert_eq!(expected, function_docs);
     }
+
+    #[test]
+    fn parses_deprecation_sections() {
+        let starlark = DocFunction::from_docstring(
+            DocStringKind::Starlark,
+            vec![],
+            None,
+            Some(
+                r#"Sorts things.
+
+            Deprecated:
+                Sorting is now stable by default.
+                Since: 0.9
+                Replacement: sorted
+            "#,
+            ),
+            None,
+        );
+        let expected = DocDeprecation {
+            message: Some("Sorting is now stable by default.".to_owned()),
+            replacement: Some("sorted".to_owned()),
+            since: Some("0.9".to_owned()),
+        };
+        assert_eq!(Some(expected.clone()), starlark.deprecated);
+        assert_eq!(
+            DocString::from_docstring(DocStringKind::Starlark, "Sorts things."),
+            starlark.docs
+        );
+        assert_eq!(
+            "`sort` is deprecated since 0.9: Sorting is now stable by default. Use `sorted` instead.",
+            expected.describe("sort")
+        );
+
+        let rust = DocProperty::from_docstring(
+            DocStringKind::Rust,
+            None,
+            Some("The old name.\n\n# Deprecated\n* `replacement`: new_name"),
+        );
+        assert_eq!(
+            Some(DocDeprecation {
+                message: None,
+                replacement: Some("new_name".to_owned()),
+                since: None,
+            }),
+            rust.deprecated
+        );
+        assert_eq!(
+            DocString::from_docstring(DocStringKind::Rust, "The old name."),
+            rust.docs
+        );
+
+        let not_deprecated =
+            DocProperty::from_docstring(DocStringKind::Rust, None, Some("Just a property."));
+        assert_eq!(None, not_deprecated.deprecated);
+    }
 }
@@ -0,0 +1,189 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Working out what is being completed at a cursor position.
+//!
+//! Files being edited often don't parse, so this works on the raw text before the cursor
+//! rather than on the AST.
+
+/// What the user is in the middle of typing.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) enum CompletionSite {
+    /// Inside a string literal. `prefix` is the contents of the literal before the cursor.
+    StringLiteral { prefix: String },
+    /// An identifier, possibly empty. `function` is the name of the function being called if
+    /// the cursor is directly within the arguments of a call, in which case the identifier
+    /// may be a keyword argument.
+    Identifier {
+        prefix: String,
+        function: Option<String>,
+    },
+}
+
+/// Work out what is being completed at the given zero-based `line` and `character`.
+pub(crate) fn completion_site(text: &str, line: u32, character: u32) -> CompletionSite {
+    let before = text_before(text, line, character);
+
+    // For each open bracket, the name of the function being called, if it is a call.
+    let mut brackets: Vec<Option<String>> = Vec::new();
+    // The identifier (possibly dotted) immediately before the current position.
+    let mut last_ident = String::new();
+    let mut ident_ended = false;
+    let mut string: Option<(char, String)> = None;
+    let mut chars = before.chars();
+
+    while let Some(c) = chars.next() {
+        if let Some((quote, contents)) = &mut string {
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        contents.push(escaped);
+                    }
+                }
+                '\n' => string = None,
+                c if c == *quote => string = None,
+                c => contents.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                string = Some((c, String::new()));
+                last_ident.clear();
+            }
+            '#' => {
+                // Skip the comment, leaving the newline to be processed.
+                let rest = chars.as_str();
+                let skip = rest.find('\n').unwrap_or(rest.len());
+                chars = rest[skip..].chars();
+            }
+            '(' => brackets.push(Some(std::mem::take(&mut last_ident)).filter(|f| !f.is_empty())),
+            '[' | '{' => {
+                brackets.push(None);
+                last_ident.clear();
+            }
+            ')' | ']' | '}' => {
+                brackets.pop();
+                last_ident.clear();
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                if ident_ended {
+                    last_ident.clear();
+                    ident_ended = false;
+                }
+                last_ident.push(c);
+            }
+            '.' if !last_ident.is_empty() && !ident_ended => last_ident.push(c),
+            c if c.is_whitespace() => ident_ended = !last_ident.is_empty(),
+            _ => last_ident.clear(),
+        }
+    }
+
+    match string {
+        Some((_, prefix)) => CompletionSite::StringLiteral { prefix },
+        None => CompletionSite::Identifier {
+            prefix: if ident_ended {
+                String::new()
+            } else {
+                last_ident.rsplit('.').next().unwrap_or_default().to_owned()
+            },
+            function: brackets.pop().flatten(),
+        },
+    }
+}
+
+fn text_before(text: &str, line: u32, character: u32) -> &str {
+    let mut offset = 0;
+    for (i, l) in text.split_inclusive('\n').enumerate() {
+        if i as u32 == line {
+            // LSP positions are in UTF-16 code units.
+            let mut units = 0;
+            for (byte, c) in l.char_indices() {
+                if units >= character as usize || c == '\n' {
+                    return &text[..offset + byte];
+                }
+                units += c.len_utf16();
+            }
+            return &text[..offset + l.len()];
+        }
+        offset += l.len();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(text: &str) -> CompletionSite {
+        let line = text.matches('\n').count() as u32;
+        let character = text.rsplit('\n').next().unwrap().len() as u32;
+        completion_site(text, line, character)
+    }
+
+    fn ident(prefix: &str, function: Option<&str>) -> CompletionSite {
+        CompletionSite::Identifier {
+            prefix: prefix.to_owned(),
+            function: function.map(|f| f.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_identifier() {
+        assert_eq!(ident("", None), site(""));
+        assert_eq!(ident("rust_l", None), site("x = 1\nrust_l"));
+        assert_eq!(ident("bar", None), site("foo.bar"));
+        assert_eq!(ident("", None), site("x = foo()\n"));
+    }
+
+    #[test]
+    fn test_call_arguments() {
+        assert_eq!(
+            ident("sr", Some("rust_library")),
+            site("rust_library(\n    name = \"foo\",\n    sr")
+        );
+        assert_eq!(
+            ident("", Some("rust_library")),
+            site("rust_library(\n    deps = [\":a\", \":b\"],\n    ")
+        );
+        assert_eq!(ident("x", None), site("foo(deps = [x"));
+        assert_eq!(ident("", Some("native.genrule")), site("native.genrule("));
+        assert_eq!(ident("", Some("f")), site("f(g(1), # comment (\n"));
+    }
+
+    #[test]
+    fn test_string_literal() {
+        assert_eq!(
+            CompletionSite::StringLiteral {
+                prefix: "//foo:b".to_owned()
+            },
+            site("rust_library(\n    deps = [\":a\", \"//foo:b")
+        );
+        assert_eq!(
+            CompletionSite::StringLiteral {
+                prefix: "it's".to_owned()
+            },
+            site("x = 'it\\'s")
+        );
+    }
+
+    #[test]
+    fn test_position_in_middle_of_text() {
+        assert_eq!(ident("fo", None), completion_site("foo\nbar\n", 0, 2));
+        assert_eq!(ident("bar", None), completion_site("foo\nbar\n", 1, 10));
+    }
+}
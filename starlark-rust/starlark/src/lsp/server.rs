@@ -18,6 +18,7 @@
 //! Based on the reference lsp-server example at <https://github.com/rust-analyzer/lsp-server/blob/master/examples/goto_def.rs>.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::path::Path;
 use std::path::PathBuf;
@@ -40,7 +41,13 @@ use lsp_types::notification::DidCloseTextDocument;
 use lsp_types::notification::DidOpenTextDocument;
 use lsp_types::notification::LogMessage;
 use lsp_types::notification::PublishDiagnostics;
+use lsp_types::request::Completion;
 use lsp_types::request::GotoDefinition;
+use lsp_types::CompletionItem;
+use lsp_types::CompletionItemKind;
+use lsp_types::CompletionOptions;
+use lsp_types::CompletionParams;
+use lsp_types::CompletionResponse;
 use lsp_types::DefinitionOptions;
 use lsp_types::Diagnostic;
 use lsp_types::DidChangeTextDocumentParams;
@@ -70,7 +77,10 @@ use crate::analysis::definition::Definition;
 use crate::analysis::definition::DottedDefinition;
 use crate::analysis::definition::IdentifierDefinition;
 use crate::analysis::definition::LspModule;
+use crate::analysis::exported::SymbolKind;
 use crate::codemap::ResolvedSpan;
+use crate::lsp::completion::completion_site;
+use crate::lsp::completion::CompletionSite;
 use crate::lsp::server::LoadContentsError::WrongScheme;
 use crate::syntax::AstModule;
 
@@ -298,6 +308,34 @@ pub trait LspContext {
         current_file: &LspUrl,
         symbol: &str,
     ) -> anyhow::Result<Option<LspUrl>>;
+
+    /// Get the names of the global symbols available in `current_file`, to offer as
+    /// completions.
+    fn get_global_symbol_names(&self, _current_file: &LspUrl) -> anyhow::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Get the names of the parameters of the global function `function`, to offer as
+    /// keyword argument completions within a call to it.
+    fn get_function_parameter_names(
+        &self,
+        _current_file: &LspUrl,
+        _function: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Get completions for a string literal, of which `prefix` has been typed so far.
+    ///
+    /// This can be used for things like file paths or build targets. Each completion is the
+    /// full contents of the string literal.
+    fn get_string_literal_completions(
+        &self,
+        _prefix: &str,
+        _current_file: &LspUrl,
+    ) -> anyhow::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
 }
 
 /// Errors when [`LspContext::resolve_load()`] cannot resolve a given path.
@@ -322,6 +360,9 @@ struct Backend<T: LspContext> {
     /// The `AstModule` from the last time that a file was opened / changed and parsed successfully.
     /// Entries are evicted when the file is closed.
     last_valid_parse: RwLock<HashMap<LspUrl, Arc<LspModule>>>,
+    /// The contents of open files, which are used for completion even if they don't parse.
+    /// Entries are evicted when the file is closed.
+    open_file_contents: RwLock<HashMap<LspUrl, String>>,
 }
 
 /// The logic implementations of stuff
@@ -337,6 +378,10 @@ impl<T: LspContext> Backend<T> {
         ServerCapabilities {
             text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
             definition_provider,
+            completion_provider: Some(CompletionOptions {
+                trigger_characters: Some(vec!["\"".to_owned(), ":".to_owned(), "/".to_owned()]),
+                ..CompletionOptions::default()
+            }),
             ..ServerCapabilities::default()
         }
     }
@@ -358,7 +403,11 @@ impl<T: LspContext> Backend<T> {
     }
 
     fn validate(&self, uri: Url, version: Option<i64>, text: String) -> anyhow::Result<()> {
-        let uri = uri.try_into()?;
+        let uri: LspUrl = uri.try_into()?;
+        self.open_file_contents
+            .write()
+            .unwrap()
+            .insert(uri.clone(), text.clone());
         let eval_result = self.context.parse_file_with_contents(&uri, text);
         if let Some(ast) = eval_result.ast {
             let module = Arc::new(LspModule::new(ast));
@@ -389,8 +438,11 @@ impl<T: LspContext> Backend<T> {
 
     fn did_close(&self, params: DidCloseTextDocumentParams) -> anyhow::Result<()> {
         {
+            let uri: LspUrl = params.text_document.uri.clone().try_into()?;
             let mut last_valid_parse = self.last_valid_parse.write().unwrap();
-            last_valid_parse.remove(&params.text_document.uri.clone().try_into()?);
+            last_valid_parse.remove(&uri);
+            let mut open_file_contents = self.open_file_contents.write().unwrap();
+            open_file_contents.remove(&uri);
         }
         self.publish_diagnostics(params.text_document.uri, Vec::new(), None);
         Ok(())
@@ -406,6 +458,77 @@ impl<T: LspContext> Backend<T> {
         self.send_response(new_response(id, self.find_definition(params)));
     }
 
+    /// Offer completions at the current cursor: keyword arguments when within a call,
+    /// symbols defined or loaded in the file and global symbols otherwise, and whatever the
+    /// context provides for string literals (e.g. build targets).
+    fn completion(&self, id: RequestId, params: CompletionParams) {
+        self.send_response(new_response(id, self.find_completions(params)));
+    }
+
+    fn find_completions(&self, params: CompletionParams) -> anyhow::Result<CompletionResponse> {
+        let uri: LspUrl = params.text_document_position.text_document.uri.try_into()?;
+        let position = params.text_document_position.position;
+        let contents = self.open_file_contents.read().unwrap().get(&uri).cloned();
+        let contents = match contents {
+            Some(contents) => contents,
+            None => match self.context.get_load_contents(&uri)? {
+                Some(contents) => contents,
+                None => return Ok(CompletionResponse::Array(Vec::new())),
+            },
+        };
+
+        let mut items = Vec::new();
+        match completion_site(&contents, position.line, position.character) {
+            CompletionSite::StringLiteral { prefix } => {
+                for completion in self.context.get_string_literal_completions(&prefix, &uri)? {
+                    items.push(CompletionItem {
+                        label: completion,
+                        kind: Some(CompletionItemKind::VALUE),
+                        ..CompletionItem::default()
+                    });
+                }
+            }
+            CompletionSite::Identifier { prefix, function } => {
+                let mut seen = HashSet::new();
+                let mut add =
+                    |name: String, kind: CompletionItemKind, insert_text: Option<String>| {
+                        if name.starts_with(&prefix) && seen.insert(name.clone()) {
+                            items.push(CompletionItem {
+                                label: name,
+                                kind: Some(kind),
+                                insert_text,
+                                ..CompletionItem::default()
+                            });
+                        }
+                    };
+                if let Some(function) = &function {
+                    for param in self.context.get_function_parameter_names(&uri, function)? {
+                        let insert_text = format!("{} = ", param);
+                        add(param, CompletionItemKind::PROPERTY, Some(insert_text));
+                    }
+                }
+                if let Some(module) = self.get_ast(&uri) {
+                    for symbol in module.ast.exported_symbols() {
+                        let kind = match symbol.kind {
+                            SymbolKind::Function => CompletionItemKind::FUNCTION,
+                            SymbolKind::Any => CompletionItemKind::VARIABLE,
+                        };
+                        add(symbol.name.to_owned(), kind, None);
+                    }
+                    for load in module.ast.loads() {
+                        for name in load.symbols.keys() {
+                            add((*name).to_owned(), CompletionItemKind::VARIABLE, None);
+                        }
+                    }
+                }
+                for name in self.context.get_global_symbol_names(&uri)? {
+                    add(name, CompletionItemKind::FUNCTION, None);
+                }
+            }
+        }
+        Ok(CompletionResponse::Array(items))
+    }
+
     /// Get the file contents of a starlark: URI.
     fn get_starlark_file_contents(&self, id: RequestId, params: StarlarkFileContentsParams) {
         let response: anyhow::Result<_> = match params.uri {
@@ -629,6 +752,8 @@ impl<T: LspContext> Backend<T> {
                     //            be handled client side.
                     if let Some(params) = as_request::<GotoDefinition>(&req) {
                         self.goto_definition(req.id, params);
+                    } else if let Some(params) = as_request::<Completion>(&req) {
+                        self.completion(req.id, params);
                     } else if let Some(params) = as_request::<StarlarkFileContentsRequest>(&req) {
                         self.get_starlark_file_contents(req.id, params);
                     } else if self.connection.handle_shutdown(&req)? {
@@ -694,6 +819,7 @@ pub fn server_with_connection<T: LspContext>(
         connection,
         context,
         last_valid_parse: RwLock::default(),
+        open_file_contents: RwLock::default(),
     }
     .main_loop(initialization_params)?;
 
@@ -779,7 +905,10 @@ mod test {
     use anyhow::Context;
     use lsp_server::Request;
     use lsp_server::RequestId;
+    use lsp_types::request::Completion;
     use lsp_types::request::GotoDefinition;
+    use lsp_types::CompletionParams;
+    use lsp_types::CompletionResponse;
     use lsp_types::GotoDefinitionParams;
     use lsp_types::GotoDefinitionResponse;
     use lsp_types::LocationLink;
@@ -1887,4 +2016,48 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn completes_symbols_and_keyword_arguments() -> anyhow::Result<()> {
+        if is_wasm() {
+            return Ok(());
+        }
+
+        let uri = temp_file_uri("file.star");
+        let mut server = TestServer::new()?;
+        let contents = "def nothing():\n    pass\nnothing()\n";
+        server.open_file(uri.clone(), contents.to_owned())?;
+
+        let labels = |server: &mut TestServer, line, character| -> anyhow::Result<Vec<String>> {
+            let req = server.new_request::<Completion>(CompletionParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position { line, character },
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            });
+            let request_id = server.send_request(req)?;
+            match server.get_response::<CompletionResponse>(request_id)? {
+                CompletionResponse::Array(items) => {
+                    Ok(items.into_iter().map(|i| i.label).collect())
+                }
+                response => Err(anyhow::anyhow!("Got invalid message type: {:?}", response)),
+            }
+        };
+
+        let mut completions = labels(&mut server, 2, 1)?;
+        completions.sort();
+        assert_eq!(
+            vec!["native_function1", "native_function2", "nothing"],
+            completions
+        );
+
+        server.change_file(uri.clone(), "native_function1(".to_owned())?;
+        let completions = labels(&mut server, 0, 17)?;
+        assert!(completions.contains(&"nothing".to_owned()));
+        assert!(completions.contains(&"native_function2".to_owned()));
+        Ok(())
+    }
 }
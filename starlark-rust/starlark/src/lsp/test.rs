@@ -227,6 +227,10 @@ impl LspContext for TestServerContext {
     ) -> anyhow::Result<Option<LspUrl>> {
         Ok(self.builtin_symbols.get(symbol).cloned())
     }
+
+    fn get_global_symbol_names(&self, _current_file: &LspUrl) -> anyhow::Result<Vec<String>> {
+        Ok(self.builtin_symbols.keys().cloned().collect())
+    }
 }
 
 /// A server for use in testing that provides helpers for sending requests, correlating
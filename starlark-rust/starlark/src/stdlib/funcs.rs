@@ -895,7 +895,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// `range` returns a tuple of integers defined by the specified interval
     /// and stride.
     ///
-    /// ```python
+    /// ```text
     /// range(stop)                             # equivalent to range(0, stop)
     /// range(start, stop)                      # equivalent to range(start, stop, 1)
     /// range(start, stop, step)
@@ -955,7 +955,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// repr([1, "x"])          == "[1, \"x\"]"
     /// repr("test \"'")        == "\"test \\\"'\""
     /// repr("x\"y😿 \\'")      == "\"x\\\"y\\U0001f63f \\\\'\""
-    /// "#);
+    /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn repr<'v>(
@@ -769,10 +769,10 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// "banana".replace("", "x") == "xbxaxnxaxnxax"
     /// "banana".replace("", "x", 2) == "xbxanana"
     /// "".replace("", "x") == "x"
-    /// "# );
+    /// # "#);
     /// # starlark::assert::fail(r#"
     /// "banana".replace("a", "o", -2)  # error: argument was negative
-    /// "#, "argument was negative");
+    /// # "#, "argument was negative");
     /// ```
     #[starlark(speculative_exec_safe)]
     fn replace<'v>(
@@ -25,6 +25,7 @@ use starlark_derive::NoSerialize;
 use crate as starlark;
 use crate::any::ProvidesStaticType;
 use crate::assert;
+use crate::collections::SmallMap;
 use crate::docs::Doc;
 use crate::docs::DocItem;
 use crate::docs::MarkdownFlavor;
@@ -34,6 +35,7 @@ use crate::environment::Methods;
 use crate::environment::MethodsBuilder;
 use crate::environment::MethodsStatic;
 use crate::tests::docs::golden::docs_golden_test;
+use crate::values::none::NoneOr;
 use crate::values::none::NoneType;
 use crate::values::Heap;
 use crate::values::StarlarkValue;
@@ -82,6 +84,17 @@ def f4(a: "string") -> "string":
     """ This is a docstring with no 'Args:' section """
     return a
 
+def f5():
+    """
+    This function should no longer be used
+
+    Deprecated:
+        It does nothing.
+        Since: 1.0
+        Replacement: f4
+    """
+    return None
+
 # Not public, so shouldn't show up
 def _do_not_export():
     pass
@@ -150,8 +163,26 @@ fn module(builder: &mut GlobalsBuilder) {
         #[starlark(default=Vec::new())] explicit_default: Vec<String>,
         hidden_default: Option<Vec<String>>,
         #[starlark(default = "my_default")] string_default: &str,
+        #[starlark(default = -1)] negative_default: i32,
+        #[starlark(default = 2u32)] suffixed_default: u32,
+        #[starlark(default = 0.5)] float_default: f64,
+        #[starlark(default = NoneOr::None)] none_default: NoneOr<i32>,
+        #[starlark(default = 5_000_000_000)] large_default: i64,
+        #[starlark(default = "owned".to_owned())] owned_default: String,
+        #[starlark(default = NoneOr::Other(3))] other_default: NoneOr<i32>,
     ) -> anyhow::Result<NoneType> {
-        let _unused = (explicit_default, hidden_default, string_default);
+        let _unused = (
+            explicit_default,
+            hidden_default,
+            string_default,
+            negative_default,
+            suffixed_default,
+            float_default,
+            none_default,
+            large_default,
+            owned_default,
+            other_default,
+        );
         Ok(NoneType)
     }
 
@@ -167,6 +198,20 @@ fn module(builder: &mut GlobalsBuilder) {
     fn notypes<'v>(a: Value<'v>) -> anyhow::Result<Value<'v>> {
         Ok(a)
     }
+
+    fn with_star_args(
+        #[starlark(args)] args: Vec<i32>,
+        #[starlark(kwargs)] kwargs: SmallMap<String, i32>,
+    ) -> anyhow::Result<NoneType> {
+        let _unused = (args, kwargs);
+        Ok(NoneType)
+    }
+
+    /// An old version of func2.
+    #[starlark(deprecated(message = "Superseded.", replacement = "func2", since = "0.9"))]
+    fn old_func2() -> anyhow::Result<String> {
+        Ok("func2".to_owned())
+    }
 }
 
 #[derive(ProvidesStaticType, Debug, Display, Allocative, Serialize)]
@@ -195,6 +240,15 @@ fn object(builder: &mut MethodsBuilder) {
         Ok("attr2".to_owned())
     }
 
+    /// Docs for attr3
+    ///
+    /// # Deprecated
+    /// Replacement: attr1
+    #[starlark(attribute)]
+    fn attr3<'v>(this: Value<'v>) -> anyhow::Result<String> {
+        Ok("attr1".to_owned())
+    }
+
     /// Docs for func1
     ///
     /// # Arguments
@@ -236,6 +290,18 @@ fn golden_docs_module() {
     );
     assert!(!res.contains("starlark::assert::all_true"));
     assert!(res.contains(r#"string_default: str.type = "my_default"#));
+    assert!(res.contains("negative_default: int.type = -1"));
+    assert!(res.contains("suffixed_default: int.type = 2,"));
+    assert!(res.contains(r#"float_default: "float" = 0.5,"#));
+    assert!(res.contains("none_default: [None, int.type] = None"));
+    assert!(res.contains("large_default: int.type = 5000000000"));
+    assert!(res.contains(r#"owned_default: str.type = "owned""#));
+    assert!(res.contains("other_default: [None, int.type] = 3"));
+    assert!(
+        res.contains(
+            "def with_star_args(*args: tuple.type, **kwargs: {str.type: int.type}) -> None"
+        )
+    );
 }
 
 #[test]
@@ -31,7 +31,6 @@ use crate::coerce::Coerce;
 use crate::docs::DocFunction;
 use crate::docs::DocItem;
 use crate::docs::DocProperty;
-use crate::docs::DocString;
 use crate::docs::DocStringKind;
 use crate::docs::DocType;
 use crate::eval::Arguments;
@@ -366,14 +365,14 @@ impl<'v> StarlarkValue<'v> for NativeAttribute {
     }
 
     fn documentation(&self) -> Option<DocItem> {
-        let ds = self
-            .docstring
-            .as_ref()
-            .and_then(|ds| DocString::from_docstring(DocStringKind::Rust, ds));
         let typ = Some(DocType {
             raw_type: self.typ.clone(),
         });
-        Some(DocItem::Property(DocProperty { docs: ds, typ }))
+        Some(DocItem::Property(DocProperty::from_docstring(
+            DocStringKind::Rust,
+            typ,
+            self.docstring.as_deref(),
+        )))
     }
 }
 
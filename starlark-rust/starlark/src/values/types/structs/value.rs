@@ -181,6 +181,7 @@ where
                         DocMember::Property(DocProperty {
                             docs: None,
                             typ: None,
+                            deprecated: None,
                         }),
                     ),
                 }
@@ -28,6 +28,7 @@ use syn::GenericParam;
 use syn::Generics;
 use syn::ItemFn;
 use syn::Lifetime;
+use syn::LitStr;
 use syn::Pat;
 use syn::PatType;
 use syn::PathArguments;
@@ -55,11 +56,72 @@ struct FnAttrs {
     as_type: Option<syn::Path>,
     starlark_ty_custom_function: Option<Expr>,
     speculative_exec_safe: bool,
+    deprecated: Option<FnDeprecation>,
     docstring: Option<String>,
     /// Rest attributes
     attrs: Vec<Attribute>,
 }
 
+/// Contents of `#[starlark(deprecated(...))]`.
+#[derive(Default)]
+struct FnDeprecation {
+    message: Option<String>,
+    replacement: Option<String>,
+    since: Option<String>,
+}
+
+impl FnDeprecation {
+    /// Parse the optional `(message = "...", replacement = "...", since = "...")` part.
+    fn parse(parser: ParseStream) -> syn::Result<FnDeprecation> {
+        let mut res = FnDeprecation::default();
+        if !parser.peek(syn::token::Paren) {
+            return Ok(res);
+        }
+        let content;
+        syn::parenthesized!(content in parser);
+        while !content.is_empty() {
+            let ident = content.parse::<Ident>()?;
+            content.parse::<Token![=]>()?;
+            let value = Some(content.parse::<LitStr>()?.value());
+            if ident == "message" {
+                res.message = value;
+            } else if ident == "replacement" {
+                res.replacement = value;
+            } else if ident == "since" {
+                res.since = value;
+            } else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Expecting `message`, `replacement` or `since` in `deprecated(...)`",
+                ));
+            }
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        Ok(res)
+    }
+
+    /// Append the deprecation as a `# Deprecated` docstring section,
+    /// which is parsed back into `DocDeprecation` when documentation is requested.
+    fn append_to_docstring(self, docstring: &mut Option<String>) {
+        let docstring = docstring.get_or_insert_with(|| "Deprecated.".to_owned());
+        docstring.push_str("\n\n# Deprecated");
+        if let Some(message) = self.message {
+            docstring.push('\n');
+            docstring.push_str(&message);
+        }
+        if let Some(since) = self.since {
+            docstring.push_str("\nSince: ");
+            docstring.push_str(&since);
+        }
+        if let Some(replacement) = self.replacement {
+            docstring.push_str("\nReplacement: ");
+            docstring.push_str(&replacement);
+        }
+    }
+}
+
 #[derive(Default)]
 struct FnParamAttrs {
     default: Option<Expr>,
@@ -168,6 +230,9 @@ fn parse_starlark_fn_attr(tokens: &Attribute, attrs: &mut FnAttrs) -> syn::Resul
             } else if ident == "speculative_exec_safe" {
                 attrs.speculative_exec_safe = true;
                 continue;
+            } else if ident == "deprecated" {
+                attrs.deprecated = Some(FnDeprecation::parse(parser)?);
+                continue;
             } else if ident == "ty_custom_function" {
                 parser.parse::<Token![=]>()?;
                 attrs.starlark_ty_custom_function = Some(parser.parse::<Expr>()?);
@@ -179,7 +244,8 @@ fn parse_starlark_fn_attr(tokens: &Attribute, attrs: &mut FnAttrs) -> syn::Resul
                     `#[starlark(as_type = ImplStarlarkValue)]`, \
                     `#[starlark(ty_custom_function = MyTy)]`, \
                     `#[starlark(attribute)]`, \
-                    `#[starlark(speculative_exec_safe)]` attribute",
+                    `#[starlark(speculative_exec_safe)]`, \
+                    `#[starlark(deprecated(message = \"...\"))]` attribute",
             ));
         }
 
@@ -212,6 +278,9 @@ fn parse_fn_attrs(span: Span, xs: Vec<Attribute>) -> syn::Result<FnAttrs> {
     if res.is_attribute && res.as_type.is_some() {
         return Err(syn::Error::new(span, "Can't be an attribute with a .type"));
     }
+    if let Some(deprecated) = res.deprecated.take() {
+        deprecated.append_to_docstring(&mut res.docstring);
+    }
     Ok(res)
 }
 
@@ -263,6 +332,7 @@ pub(crate) fn parse_fun(func: ItemFn, module_kind: ModuleKind) -> syn::Result<St
         is_attribute,
         as_type,
         speculative_exec_safe,
+        deprecated: _,
         docstring,
         starlark_ty_custom_function,
         attrs,
@@ -23,9 +23,15 @@ use quote::quote_spanned;
 use syn::spanned::Spanned;
 use syn::Attribute;
 use syn::Expr;
+use syn::ExprCall;
+use syn::ExprCast;
 use syn::ExprLit;
+use syn::ExprMethodCall;
+use syn::ExprParen;
+use syn::ExprUnary;
 use syn::Lit;
 use syn::Type;
+use syn::UnOp;
 
 use crate::module::render::render_starlark_return_type;
 use crate::module::render::render_starlark_type;
@@ -576,9 +582,15 @@ fn render_documentation(x: &StarFun) -> syn::Result<(Ident, TokenStream)> {
             a.pass_style != StarArgPassStyle::This && a.pass_style != StarArgPassStyle::Arguments
         })
         .enumerate()
-        .filter(|(_, a)| a.pass_style != StarArgPassStyle::Args) // these aren't coerced according to their type (Vec vs tuple)
         .map(|(i, arg)| {
-            let typ_str = render_starlark_type(span, arg.without_option());
+            // Like the types of `*args` and `**kwargs` in `def`, these are the types of the whole
+            // collections. Whatever the Rust type of `*args`, the function receives a tuple, and
+            // tuple types can't express their elements without fixing their length.
+            let typ_str = if arg.pass_style == StarArgPassStyle::Args {
+                quote_spanned!(span=> starlark::typing::Ty::name("tuple"))
+            } else {
+                render_starlark_type(span, arg.without_option())
+            };
             quote_spanned!(span=> (#i, starlark::docs::DocType { raw_type: #typ_str }) )
         })
         .collect();
@@ -731,26 +743,90 @@ fn render_signature_arg(
 /// That _might_ have a valid `FrozenValue` representation, if so, it would be great to use for documentation.
 /// Try and synthesise it if we can.
 fn render_default_as_frozen_value(default: &Expr) -> Option<TokenStream> {
+    match default {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Int(_) | Lit::Float(_) => render_number_as_frozen_value(lit, false),
+            Lit::Bool(x) => {
+                let x = x.value;
+                Some(quote! { starlark::values::FrozenValue::new_bool(#x) })
+            }
+            // Make sure we don't splice in `x` again, or we double quote the string
+            Lit::Str(_) => Some(quote! { globals_builder.alloc(#default) }),
+            _ => None,
+        },
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            Expr::Lit(ExprLit { lit, .. }) => render_number_as_frozen_value(lit, true),
+            _ => None,
+        },
+        // `(x)`, `x as u64`.
+        Expr::Paren(ExprParen { expr, .. }) | Expr::Cast(ExprCast { expr, .. }) => {
+            render_default_as_frozen_value(expr)
+        }
+        // `"x".to_owned()` and friends.
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if args.is_empty()
+            && matches!(
+                &**receiver,
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(_),
+                    ..
+                })
+            )
+            && (method == "to_owned" || method == "to_string" || method == "into") =>
+        {
+            render_default_as_frozen_value(receiver)
+        }
+        // `String::from("x")`, `NoneOr::Other(x)`.
+        Expr::Call(ExprCall { func, args, .. })
+            if args.len() == 1 && {
+                let func = quote!(#func).to_string();
+                ["String :: from", "NoneOr :: Other"]
+                    .iter()
+                    .any(|suffix| func == *suffix || func.ends_with(&format!(":: {}", suffix)))
+            } =>
+        {
+            render_default_as_frozen_value(&args[0])
+        }
+        _ => render_path_default_as_frozen_value(default),
+    }
+}
+
+/// Integers which fit in an `i64` and floats, optionally negated.
+fn render_number_as_frozen_value(lit: &Lit, negate: bool) -> Option<TokenStream> {
+    let sign = if negate { -1 } else { 1 };
+    match lit {
+        Lit::Int(x) => {
+            let x = sign * x.base10_parse::<i64>().ok()?;
+            Some(quote! { globals_builder.alloc(#x) })
+        }
+        Lit::Float(x) => {
+            let x = sign as f64 * x.base10_parse::<f64>().ok()?;
+            Some(quote! { globals_builder.alloc(#x) })
+        }
+        _ => None,
+    }
+}
+
+fn render_path_default_as_frozen_value(default: &Expr) -> Option<TokenStream> {
+    // Match paths by their suffix, so both `NoneOr::None` and `starlark::values::none::NoneOr::None` work.
     let x = quote!(#default).to_string();
-    if let Ok(x) = x.trim_end_matches("i32").parse::<i32>() {
-        Some(quote! { globals_builder.alloc(#x) })
-    } else if let Ok(x) = x.parse::<bool>() {
-        Some(quote! { starlark::values::FrozenValue::new_bool(#x) })
-    } else if x == "NoneOr :: None" {
+    let is = |suffix: &str| x == suffix || x.ends_with(&format!(":: {}", suffix));
+    if is("NoneOr :: None") || is("NoneType") || x == "None" {
         Some(quote! { starlark::values::FrozenValue::new_none() })
-    } else if matches!(
-        default,
-        Expr::Lit(ExprLit {
-            lit: Lit::Str(_),
-            ..
-        })
-    ) {
-        // Make sure we don't splice in `x` again, or we double quote the string
-        Some(quote! { globals_builder.alloc(#default) })
-    } else if x == "Vec :: new()" {
+    } else if is("Vec :: new()") || x == "vec ! []" || is("AllocList :: EMPTY") {
         Some(quote! { globals_builder.alloc(starlark::values::list::AllocList::EMPTY) })
-    } else if x == "SmallMap :: new()" {
+    } else if is("SmallMap :: new()") || is("AllocDict :: EMPTY") {
         Some(quote! { globals_builder.alloc(starlark::values::dict::AllocDict::EMPTY) })
+    } else if is("String :: new()") {
+        Some(quote! { globals_builder.alloc("") })
     } else {
         None
     }